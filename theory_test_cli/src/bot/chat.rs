@@ -0,0 +1,231 @@
+//! The commands of the bot, and the running scores of the chats.
+use std::collections::HashMap;
+
+use theory_test_parser::{
+    exam::ExamSpec,
+    question_parser::{ExamQuestions, LicenseClass},
+};
+
+use crate::poll::{Poll, quiz_message};
+
+/// The number of questions of `/quiz` without one.
+const DEFAULT_QUIZ_LEN: usize = 5;
+/// The most questions of a `/quiz`, to not flood the chat.
+const MAX_QUIZ_LEN: usize = 10;
+
+const HELP: &str = "/quiz 5 - חמש שאלות אקראיות לדרגה B\n\
+/sign - שאלה עם תמונה\n\
+/score - אחוז התשובות הנכונות בצ'אט";
+
+/// A message to send to a chat.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Outgoing {
+    Text(String),
+    /// An image, by its url.
+    Photo(String),
+    Poll(Poll),
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Score {
+    pub answered: usize,
+    pub correct: usize,
+}
+
+/// A poll the bot sent, to score its answers.
+struct SentPoll {
+    chat_id: i64,
+    correct_option_id: usize,
+}
+
+/// The state of the bot over all of its chats, kept in memory.
+pub struct Bot {
+    bank: ExamQuestions,
+    polls: HashMap<String, SentPoll>,
+    scores: HashMap<i64, Score>,
+}
+
+impl Bot {
+    pub fn new(bank: ExamQuestions) -> Self {
+        Self {
+            bank,
+            polls: HashMap::new(),
+            scores: HashMap::new(),
+        }
+    }
+
+    /// The replies to the message `text` in chat `chat_id`, drawing questions with `seed`.
+    /// Messages which aren't commands of the bot get none.
+    pub fn handle_message(&self, chat_id: i64, text: &str, seed: u64) -> Vec<Outgoing> {
+        let mut words = text.split_whitespace();
+        // commands in groups may be addressed, e.g. "/quiz@theory_test_bot"
+        let command = words
+            .next()
+            .and_then(|word| word.split('@').next())
+            .unwrap_or_default();
+        match command {
+            "/quiz" => {
+                let len = words
+                    .next()
+                    .and_then(|len| len.parse::<usize>().ok())
+                    .unwrap_or(DEFAULT_QUIZ_LEN)
+                    .clamp(1, MAX_QUIZ_LEN);
+                self.quiz(len, seed)
+            }
+            "/sign" => self.sign(seed),
+            "/score" => vec![Outgoing::Text(self.score_text(chat_id))],
+            "/start" | "/help" => vec![Outgoing::Text(HELP.to_string())],
+            _ => Vec::new(),
+        }
+    }
+
+    fn quiz(&self, len: usize, seed: u64) -> Vec<Outgoing> {
+        let spec = ExamSpec {
+            num_questions: len,
+            class: LicenseClass::B,
+            seed,
+            ..Default::default()
+        };
+        spec.generate(&self.bank)
+            .questions
+            .iter()
+            .filter_map(quiz_message)
+            .flat_map(|message| {
+                message
+                    .text
+                    .map(Outgoing::Text)
+                    .into_iter()
+                    .chain([Outgoing::Poll(message.poll)])
+            })
+            .collect()
+    }
+
+    /// A question with an image, e.g. of a road sign.
+    fn sign(&self, seed: u64) -> Vec<Outgoing> {
+        let pool = self
+            .bank
+            .questions
+            .iter()
+            .filter(|q| q.image_url.is_some() && q.license_classes.contains(&LicenseClass::B))
+            .collect::<Vec<_>>();
+        let Some(question) = pool.get(seed as usize % pool.len().max(1)) else {
+            return vec![Outgoing::Text("אין בבנק שאלות עם תמונה".to_string())];
+        };
+        let Some(message) = quiz_message(question) else {
+            return Vec::new();
+        };
+        let mut outgoing = vec![Outgoing::Photo(
+            question.image_url.clone().expect("the pool has images"),
+        )];
+        outgoing.extend(message.text.map(Outgoing::Text));
+        outgoing.push(Outgoing::Poll(message.poll));
+        outgoing
+    }
+
+    fn score_text(&self, chat_id: i64) -> String {
+        match self.scores.get(&chat_id) {
+            Some(score) if score.answered > 0 => format!(
+                "נענו נכון {} מתוך {} תשובות ({}%)",
+                score.correct,
+                score.answered,
+                score.correct * 100 / score.answered
+            ),
+            _ => "עדיין לא נענו שאלות בצ'אט".to_string(),
+        }
+    }
+
+    /// Keep the poll `poll_id` sent to `chat_id`, to score the answers to it.
+    pub fn poll_sent(&mut self, chat_id: i64, poll_id: String, poll: &Poll) {
+        self.polls.insert(
+            poll_id,
+            SentPoll {
+                chat_id,
+                correct_option_id: poll.correct_option_id,
+            },
+        );
+    }
+
+    /// Score an answer to the poll `poll_id`. Retracted votes have no options and aren't scored.
+    pub fn poll_answered(&mut self, poll_id: &str, option_ids: &[usize]) {
+        let (Some(poll), Some(option_id)) = (self.polls.get(poll_id), option_ids.first()) else {
+            return;
+        };
+        let score = self.scores.entry(poll.chat_id).or_default();
+        score.answered += 1;
+        score.correct += usize::from(*option_id == poll.correct_option_id);
+    }
+
+    #[cfg(test)]
+    fn score(&self, chat_id: i64) -> Score {
+        self.scores.get(&chat_id).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bot() -> Bot {
+        Bot::new(ExamQuestions::parse_from_xlsx(crate::BUNDLED_BANK).unwrap())
+    }
+
+    fn polls(outgoing: &[Outgoing]) -> Vec<&Poll> {
+        outgoing
+            .iter()
+            .filter_map(|message| match message {
+                Outgoing::Poll(poll) => Some(poll),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn commands() {
+        let bot = bot();
+        assert_eq!(polls(&bot.handle_message(1, "/quiz 3", 7)).len(), 3);
+        assert_eq!(polls(&bot.handle_message(1, "/quiz@some_bot", 7)).len(), 5);
+        assert_eq!(polls(&bot.handle_message(1, "/quiz 100", 7)).len(), 10);
+        assert_eq!(
+            bot.handle_message(1, "/quiz 3", 7),
+            bot.handle_message(1, "/quiz 3", 7)
+        );
+        let sign = bot.handle_message(1, "/sign", 7);
+        assert!(matches!(&sign[0], Outgoing::Photo(url) if url.starts_with("http")));
+        assert_eq!(polls(&sign).len(), 1);
+        assert_eq!(bot.handle_message(1, "hello", 7), []);
+    }
+
+    #[test]
+    fn scores() {
+        let mut bot = bot();
+        let poll = |correct_option_id| Poll {
+            question: "?".to_string(),
+            options: vec!["a".to_string(), "b".to_string()],
+            correct_option_id,
+        };
+        bot.poll_sent(1, "p1".to_string(), &poll(0));
+        bot.poll_sent(2, "p2".to_string(), &poll(1));
+        bot.poll_answered("p1", &[0]);
+        bot.poll_answered("p1", &[1]);
+        bot.poll_answered("p1", &[]);
+        bot.poll_answered("unknown", &[0]);
+        bot.poll_answered("p2", &[1]);
+        assert_eq!(
+            bot.score(1),
+            Score {
+                answered: 2,
+                correct: 1
+            }
+        );
+        assert_eq!(
+            bot.handle_message(1, "/score", 0),
+            [Outgoing::Text(
+                "נענו נכון 1 מתוך 2 תשובות (50%)".to_string()
+            )]
+        );
+        assert_eq!(
+            bot.handle_message(3, "/score", 0),
+            [Outgoing::Text("עדיין לא נענו שאלות בצ'אט".to_string())]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! A Telegram bot quizzing a chat on the question bank, e.g. a study group.
+use std::{
+    path::PathBuf,
+    process::ExitCode,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Parser;
+use theory_test_parser::question_parser::ExamQuestions;
+
+use crate::{chat::Bot, telegram::Telegram};
+
+mod chat;
+mod poll;
+mod telegram;
+
+/// The bank the app is built with, used when no other is given.
+const BUNDLED_BANK: &[u8] = include_bytes!("../../../theory_test_parser/test.xlsx");
+/// How long to wait after failing to get updates, e.g. while offline.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Parser)]
+#[command(version, about = "A Telegram bot quizzing chats on the theory test")]
+struct Args {
+    /// The token of the bot, from @BotFather.
+    #[arg(long, env = "TELEGRAM_BOT_TOKEN", hide_env_values = true)]
+    token: String,
+    /// The xlsx or TOML file of the bank, the bundled bank by default.
+    #[arg(long)]
+    bank: Option<PathBuf>,
+    #[arg(long, default_value = "https://api.telegram.org")]
+    api_url: String,
+}
+
+/// A seed from the clock, for each quiz.
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let bank = match &args.bank {
+        Some(path) => ExamQuestions::parse_from_file(path),
+        None => ExamQuestions::parse_from_xlsx(BUNDLED_BANK),
+    };
+    let mut bot = match bank {
+        Ok(bank) => Bot::new(bank),
+        Err(error) => {
+            eprintln!("error: can't read the bank: {error}");
+            return ExitCode::from(3);
+        }
+    };
+    let telegram = Telegram::new(&args.api_url, &args.token);
+    let mut offset = 0;
+    loop {
+        let updates = match telegram.get_updates(offset) {
+            Ok(updates) => updates,
+            Err(error) => {
+                eprintln!("error: can't get updates: {error}");
+                thread::sleep(RETRY_DELAY);
+                continue;
+            }
+        };
+        for update in updates {
+            offset = offset.max(update.update_id + 1);
+            if let Some(answer) = update.poll_answer {
+                bot.poll_answered(&answer.poll_id, &answer.option_ids);
+            }
+            let Some(message) = update.message else {
+                continue;
+            };
+            let Some(text) = message.text else {
+                continue;
+            };
+            let chat_id = message.chat.id;
+            for outgoing in bot.handle_message(chat_id, &text, random_seed()) {
+                match telegram.send(chat_id, &outgoing) {
+                    Ok(Some(poll_id)) => {
+                        if let chat::Outgoing::Poll(poll) = &outgoing {
+                            bot.poll_sent(chat_id, poll_id, poll);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(error) => eprintln!("error: can't send to {chat_id}: {error}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Questions as Telegram quiz polls, within the limits of the Bot API on their lengths.
+use serde::Serialize;
+use theory_test_parser::question_parser::Question;
+
+/// The longest question of a poll, in UTF-16 code units as Telegram counts them.
+pub const MAX_QUESTION_LEN: usize = 300;
+/// The longest option of a poll.
+pub const MAX_OPTION_LEN: usize = 100;
+/// The most options a poll may have.
+pub const MAX_OPTIONS: usize = 10;
+
+/// The letters answers are marked with when they're sent as text.
+const ANSWER_LETTERS: [char; MAX_OPTIONS] = ['א', 'ב', 'ג', 'ד', 'ה', 'ו', 'ז', 'ח', 'ט', 'י'];
+
+/// A quiz poll, whose correct option Telegram shows once answered.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct Poll {
+    pub question: String,
+    pub options: Vec<String>,
+    pub correct_option_id: usize,
+}
+
+/// The messages asking a question: a poll, after the question as text when it doesn't fit in
+/// one.
+#[derive(Clone, PartialEq, Debug)]
+pub struct QuizMessage {
+    pub text: Option<String>,
+    pub poll: Poll,
+}
+
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// `text` cut to `max` UTF-16 code units with an ellipsis, at the end of a word when there's one
+/// in the second half.
+fn truncate(text: &str, max: usize) -> String {
+    if utf16_len(text) <= max {
+        return text.to_string();
+    }
+    let mut len = 0;
+    let mut end = 0;
+    for (idx, c) in text.char_indices() {
+        // room is left for the ellipsis
+        if len + c.len_utf16() > max - 1 {
+            break;
+        }
+        len += c.len_utf16();
+        end = idx + c.len_utf8();
+    }
+    let cut = &text[..end];
+    let cut = match cut.rfind(char::is_whitespace) {
+        Some(space) if space > cut.len() / 2 => &cut[..space],
+        _ => cut,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+/// The text of `question` without the number it starts with, e.g. "0862. ".
+fn question_body(question: &Question) -> &str {
+    question.question.get(6..).unwrap_or(&question.question)
+}
+
+/// `question` as a quiz poll. When its answers are too long for options, the question is sent
+/// as text with lettered answers and the poll's options are the letters. `None` when it has
+/// more answers than a poll may have options, or fewer than two.
+pub fn quiz_message(question: &Question) -> Option<QuizMessage> {
+    let answers = &question.answers.possible_answers;
+    if !(2..=MAX_OPTIONS).contains(&answers.len()) {
+        return None;
+    }
+    let body = question_body(question);
+    let fits = utf16_len(body) <= MAX_QUESTION_LEN
+        && answers
+            .iter()
+            .all(|answer| (1..=MAX_OPTION_LEN).contains(&utf16_len(answer)));
+    if fits {
+        return Some(QuizMessage {
+            text: None,
+            poll: Poll {
+                question: body.to_string(),
+                options: answers.clone(),
+                correct_option_id: question.answers.correct_answer,
+            },
+        });
+    }
+    let mut text = format!("{:04}. {body}\n", question.num);
+    for (letter, answer) in ANSWER_LETTERS.iter().zip(answers) {
+        text += &format!("\n{letter}. {answer}");
+    }
+    Some(QuizMessage {
+        text: Some(text),
+        poll: Poll {
+            question: truncate(body, MAX_QUESTION_LEN),
+            options: ANSWER_LETTERS[..answers.len()]
+                .iter()
+                .map(char::to_string)
+                .collect(),
+            correct_option_id: question.answers.correct_answer,
+        },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use theory_test_parser::question_parser::ExamQuestions;
+
+    fn bank() -> ExamQuestions {
+        ExamQuestions::parse_from_xlsx(crate::BUNDLED_BANK).unwrap()
+    }
+
+    fn within_limits(message: &QuizMessage) -> bool {
+        let poll = &message.poll;
+        (1..=MAX_QUESTION_LEN).contains(&utf16_len(&poll.question))
+            && (2..=MAX_OPTIONS).contains(&poll.options.len())
+            && poll
+                .options
+                .iter()
+                .all(|option| (1..=MAX_OPTION_LEN).contains(&utf16_len(option)))
+            && poll.correct_option_id < poll.options.len()
+    }
+
+    #[test]
+    fn bank_questions() {
+        let bank = bank();
+        let mut lettered = 0;
+        for question in &bank.questions {
+            let message = quiz_message(question).unwrap();
+            assert!(within_limits(&message), "{}", question.num);
+            assert_eq!(
+                message.poll.correct_option_id,
+                question.answers.correct_answer
+            );
+            lettered += usize::from(message.text.is_some());
+        }
+        // the questions with answers longer than an option
+        assert!(lettered > 0);
+
+        let question = bank.questions.iter().find(|q| q.num == 37).unwrap();
+        let message = quiz_message(question).unwrap();
+        let text = message.text.unwrap();
+        assert!(text.starts_with("0037. "));
+        assert!(text.contains(&format!("\nא. {}", question.answers.possible_answers[0])));
+        assert_eq!(message.poll.options, ["א", "ב", "ג", "ד"]);
+        assert_eq!(message.poll.question, question_body(question));
+    }
+
+    #[test]
+    fn truncating() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("one two three", 10), "one two…");
+        assert_eq!(truncate(&"א".repeat(20), 10), format!("{}…", "א".repeat(9)));
+        // characters outside the BMP count twice, and aren't split
+        assert_eq!(
+            truncate(&"🚗".repeat(10), 10),
+            format!("{}…", "🚗".repeat(4))
+        );
+
+        let mut question = bank().questions.swap_remove(0);
+        question.question = format!("0001. {}", "מילה ".repeat(100));
+        let message = quiz_message(&question).unwrap();
+        assert!(within_limits(&message));
+        assert!(message.poll.question.ends_with("מילה…"));
+        // the whole question is still sent
+        assert!(
+            message
+                .text
+                .unwrap()
+                .contains(question_body(&question).trim_end())
+        );
+        question.answers.possible_answers.truncate(1);
+        assert_eq!(quiz_message(&question), None);
+    }
+}
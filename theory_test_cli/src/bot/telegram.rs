@@ -0,0 +1,127 @@
+//! The few methods of the Telegram Bot API the bot uses, over blocking requests.
+use std::time::Duration;
+
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use ureq::Agent;
+
+use crate::chat::Outgoing;
+
+/// How long a request for updates waits for one.
+const POLL_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    result: Option<T>,
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Update {
+    pub update_id: i64,
+    pub message: Option<Message>,
+    pub poll_answer: Option<PollAnswer>,
+}
+
+#[derive(Deserialize)]
+pub struct Message {
+    pub chat: Chat,
+    pub text: Option<String>,
+    pub poll: Option<SentPoll>,
+}
+
+#[derive(Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct SentPoll {
+    pub id: String,
+}
+
+#[derive(Deserialize)]
+pub struct PollAnswer {
+    pub poll_id: String,
+    pub option_ids: Vec<usize>,
+}
+
+pub struct Telegram {
+    agent: Agent,
+    /// The url of the methods, up to their names.
+    base_url: String,
+}
+
+impl Telegram {
+    pub fn new(api_url: &str, token: &str) -> Self {
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(Duration::from_secs(POLL_TIMEOUT_SECS + 30)))
+            .build()
+            .into();
+        Self {
+            agent,
+            base_url: format!("{}/bot{token}", api_url.trim_end_matches('/')),
+        }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, String> {
+        let mut response = self
+            .agent
+            .post(format!("{}/{method}", self.base_url))
+            .header("Content-Type", "application/json")
+            .send(params.to_string())
+            .map_err(|error| error.to_string())?;
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|error| error.to_string())?;
+        let response: ApiResponse<T> =
+            serde_json::from_str(&body).map_err(|error| error.to_string())?;
+        match (response.ok, response.result) {
+            (true, Some(result)) => Ok(result),
+            _ => Err(format!(
+                "{method} failed: {}",
+                response.description.unwrap_or_default()
+            )),
+        }
+    }
+
+    /// The updates after `offset`, waiting for one when there are none yet.
+    pub fn get_updates(&self, offset: i64) -> Result<Vec<Update>, String> {
+        self.call(
+            "getUpdates",
+            json!({
+                "offset": offset,
+                "timeout": POLL_TIMEOUT_SECS,
+                "allowed_updates": ["message", "poll_answer"],
+            }),
+        )
+    }
+
+    /// Send `outgoing` to `chat_id`, returning the id of the poll it is, if it is one.
+    pub fn send(&self, chat_id: i64, outgoing: &Outgoing) -> Result<Option<String>, String> {
+        let message: Message = match outgoing {
+            Outgoing::Text(text) => {
+                self.call("sendMessage", json!({ "chat_id": chat_id, "text": text }))?
+            }
+            Outgoing::Photo(url) => {
+                self.call("sendPhoto", json!({ "chat_id": chat_id, "photo": url }))?
+            }
+            Outgoing::Poll(poll) => self.call(
+                "sendPoll",
+                json!({
+                    "chat_id": chat_id,
+                    "question": poll.question,
+                    "options": poll.options.iter().map(|text| json!({ "text": text })).collect::<Vec<_>>(),
+                    "type": "quiz",
+                    "correct_option_id": poll.correct_option_id,
+                    // answers are only reported for polls which aren't anonymous
+                    "is_anonymous": false,
+                }),
+            )?,
+        };
+        Ok(message.poll.map(|poll| poll.id))
+    }
+}
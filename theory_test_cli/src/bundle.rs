@@ -0,0 +1,57 @@
+//! Parsing a bank ahead of time, for embedding it in the app instead of its spreadsheet.
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use clap::Args;
+use theory_test_parser::bundle::{Bundle, decompress};
+
+use crate::CliError;
+
+/// The exit code when `--check` finds the bundle out of date, for CI. Clap exits with the same
+/// code on usage errors, which a pipeline should tell apart by the error on standard error.
+const OUT_OF_DATE: u8 = 2;
+
+#[derive(Args)]
+pub struct BundleArgs {
+    /// The xlsx file of the bank.
+    input: PathBuf,
+    /// Where to write the bundle, e.g. `theory_test_ui/banks/b.json.br`.
+    #[arg(long)]
+    out: PathBuf,
+    /// Check that the bundle at `--out` is the one of the bank instead of writing it.
+    #[arg(long)]
+    check: bool,
+}
+
+pub fn run(args: BundleArgs) -> Result<ExitCode, CliError> {
+    let xlsx = fs::read(&args.input)?;
+    let bundle = Bundle::parse_xlsx(&xlsx);
+    if let Err((error, _)) = &bundle.parsed {
+        // bundled anyway, so the app shows why the bank can't be loaded
+        eprintln!("warning: the bank can't be parsed: {error}");
+    }
+    let json = serde_json::to_vec(&bundle)?;
+    if args.check {
+        // compared decompressed, as another version of brotli may compress it differently
+        let current = decompress(&fs::read(&args.out)?);
+        return Ok(if current.is_ok_and(|current| current == json) {
+            println!("{} is up to date", args.out.display());
+            ExitCode::SUCCESS
+        } else {
+            eprintln!(
+                "{} is out of date, run the command without --check to update it",
+                args.out.display()
+            );
+            ExitCode::from(OUT_OF_DATE)
+        });
+    }
+    let compressed = bundle.compress();
+    fs::write(&args.out, &compressed)?;
+    println!("xlsx: {} bytes", xlsx.len());
+    println!("json: {} bytes", json.len());
+    println!(
+        "compressed: {} bytes, {:.1}× smaller than the json",
+        compressed.len(),
+        json.len() as f64 / compressed.len() as f64
+    );
+    Ok(ExitCode::SUCCESS)
+}
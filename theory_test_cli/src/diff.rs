@@ -0,0 +1,80 @@
+//! Comparing two versions of a bank, e.g. before replacing the bundled one with an update.
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::Args;
+use theory_test_parser::diff::{BankDiff, CompareMode, diff};
+
+use crate::{CliError, load_bank};
+
+/// The exit code when the banks differ, for scripts. Clap exits with the same code on usage
+/// errors, which a pipeline should tell apart by the error on standard error.
+const DIFFERENT: u8 = 2;
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// The xlsx or TOML file of the old bank.
+    old: PathBuf,
+    /// The xlsx or TOML file of the new bank.
+    new: PathBuf,
+    /// Include the old and new texts of modified questions.
+    #[arg(long)]
+    show_text: bool,
+    /// Print the differences as JSON.
+    #[arg(long)]
+    json: bool,
+    /// Don't count changes to whitespace alone as modifications.
+    #[arg(long)]
+    ignore_whitespace: bool,
+}
+
+fn format_nums(nums: impl Iterator<Item = usize>) -> String {
+    let nums = nums.map(|num| format!("{num:04}")).collect::<Vec<_>>();
+    if nums.is_empty() {
+        "none".to_string()
+    } else {
+        nums.join(", ")
+    }
+}
+
+/// The differences as text, one line per kind of change.
+pub fn format_diff(diff: &BankDiff, show_text: bool) -> String {
+    let mut out = format!(
+        "Added: {}\nRemoved: {}\nModified: {}\n",
+        format_nums(diff.added.iter().copied()),
+        format_nums(diff.removed.iter().copied()),
+        format_nums(diff.modified.iter().map(|m| m.num)),
+    );
+    if show_text {
+        for modified in &diff.modified {
+            out += &format!(
+                "\n- {}\n+ {}\n",
+                modified.old.question, modified.new.question
+            );
+        }
+    }
+    out
+}
+
+pub fn run(args: DiffArgs) -> Result<ExitCode, CliError> {
+    let old = load_bank(Some(&args.old))?;
+    let new = load_bank(Some(&args.new))?;
+    let mode = if args.ignore_whitespace {
+        CompareMode::IgnoreWhitespace
+    } else {
+        CompareMode::Exact
+    };
+    let diff = diff(&old, &new, mode);
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string(&diff).expect("a diff is always serializable")
+        );
+    } else {
+        print!("{}", format_diff(&diff, args.show_text));
+    }
+    Ok(if diff.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(DIFFERENT)
+    })
+}
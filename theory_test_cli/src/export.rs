@@ -0,0 +1,157 @@
+//! Exporting a bank, or part of it, with the parser's exporters.
+use std::{fs, ops::RangeInclusive, path::PathBuf};
+
+use clap::{Args, ValueEnum};
+use theory_test_parser::{
+    export::{ExportFormat, export},
+    question_parser::{LicenseClass, Question, QuestionCategory},
+};
+
+use crate::{CliError, load_bank, parse_category, parse_class};
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Csv,
+    Anki,
+    Gift,
+    Markdown,
+    /// An Anki package, with the images given with `--images` embedded.
+    #[cfg(feature = "apkg")]
+    Apkg,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// The xlsx or TOML file of the bank.
+    input: PathBuf,
+    #[arg(long, value_enum)]
+    format: Format,
+    /// Where to write the export, standard output by default.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// Only export the questions of this license class.
+    #[arg(long, value_parser = parse_class)]
+    class: Option<LicenseClass>,
+    /// Only export the questions of this category, may be repeated.
+    #[arg(long, value_parser = parse_category)]
+    category: Vec<QuestionCategory>,
+    /// Only export the questions numbered in this range, e.g. "100-200" or "862".
+    #[arg(long, value_parser = parse_nums)]
+    nums: Option<RangeInclusive<usize>>,
+    /// Indent the JSON.
+    #[arg(long)]
+    pretty: bool,
+    /// The name of the Anki deck.
+    #[cfg(feature = "apkg")]
+    #[arg(long, default_value = "Theory test")]
+    deck_name: String,
+    /// Put each category in an Anki subdeck of its own.
+    #[cfg(feature = "apkg")]
+    #[arg(long)]
+    subdecks: bool,
+    /// A directory of images downloaded with `fetch-images`, to embed in the Anki package.
+    #[cfg(feature = "apkg")]
+    #[arg(long)]
+    images: Option<PathBuf>,
+}
+
+/// Parse a range of question numbers, e.g. "100-200", or a single number.
+fn parse_nums(nums: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |num: &str| {
+        num.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("\"{num}\" is not a question number"))
+    };
+    let range = match nums.split_once('-') {
+        Some((start, end)) => parse(start)?..=parse(end)?,
+        None => parse(nums)?..=parse(nums)?,
+    };
+    if range.is_empty() {
+        return Err(format!("the range \"{nums}\" is empty"));
+    }
+    Ok(range)
+}
+
+impl ExportArgs {
+    fn matches(&self, question: &Question) -> bool {
+        self.class
+            .is_none_or(|class| question.license_classes.contains(&class))
+            && (self.category.is_empty() || self.category.contains(&question.category))
+            && self
+                .nums
+                .as_ref()
+                .is_none_or(|nums| nums.contains(&question.num))
+    }
+}
+
+#[cfg(feature = "apkg")]
+fn export_apkg(args: &ExportArgs, questions: Vec<Question>) -> Result<(), CliError> {
+    use theory_test_parser::{apkg::ApkgOptions, question_parser::ExamQuestions};
+
+    let Some(out) = &args.out else {
+        return Err(CliError::Filter(
+            "an Anki package is written to a file, give one with --out".to_string(),
+        ));
+    };
+    let images = match &args.images {
+        Some(dir) => crate::fetch_images::local_images(dir)?,
+        None => Default::default(),
+    };
+    let opts = ApkgOptions {
+        deck_name: args.deck_name.clone(),
+        subdeck_per_category: args.subdecks,
+        class: None,
+        images,
+    };
+    ExamQuestions { questions }.export_apkg(out, &opts)?;
+    Ok(())
+}
+
+pub fn run(args: ExportArgs) -> Result<(), CliError> {
+    let bank = load_bank(Some(&args.input))?;
+    let questions = bank
+        .questions
+        .into_iter()
+        .filter(|q| args.matches(q))
+        .collect::<Vec<_>>();
+    if questions.is_empty() {
+        return Err(CliError::Filter(
+            "no questions match the filters".to_string(),
+        ));
+    }
+    #[cfg(feature = "apkg")]
+    if let Format::Apkg = args.format {
+        return export_apkg(&args, questions);
+    }
+    let format = match args.format {
+        Format::Json => ExportFormat::Json {
+            pretty: args.pretty,
+        },
+        Format::Csv => ExportFormat::Csv,
+        Format::Anki => ExportFormat::Anki,
+        Format::Gift => ExportFormat::Gift,
+        Format::Markdown => ExportFormat::Markdown,
+        #[cfg(feature = "apkg")]
+        Format::Apkg => unreachable!("packages are written by export_apkg"),
+    };
+    let exported = export(&questions, format);
+    match &args.out {
+        Some(path) => fs::write(path, exported)?,
+        None => print!("{exported}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nums() {
+        assert_eq!(parse_nums("100-200"), Ok(100..=200));
+        assert_eq!(parse_nums("862"), Ok(862..=862));
+        assert!(parse_nums("200-100").is_err());
+        assert!(parse_nums("a-3").is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! Downloading the images of a bank, e.g. to bundle them with the app or to print exams offline.
+//!
+//! The images are written next to a `manifest.json` mapping the questions to them, which
+//! re-runs use to skip the images the server still has the same version of.
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+    time::Duration,
+};
+
+use clap::Args;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::{LicenseClass, QuestionCategory};
+use ureq::http::header;
+
+use crate::{
+    CliError, file_name,
+    http::{Fetcher, header_value, read_body},
+    load_bank,
+};
+
+/// The exit code when some images couldn't be fetched or verified.
+const INCOMPLETE: u8 = 2;
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Args)]
+pub struct FetchImagesArgs {
+    /// The xlsx or TOML file of the bank.
+    input: PathBuf,
+    /// The directory to write the images and their manifest to.
+    #[arg(long, default_value = "assets/question_images")]
+    out: PathBuf,
+    /// How many images to download at once.
+    #[arg(long, default_value_t = 8, value_parser = clap::value_parser!(u32).range(1..=64))]
+    concurrency: u32,
+    /// How many times to try each request before giving up on the image.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    attempts: u32,
+    /// How long to wait before the first retry, in milliseconds. Doubled on each retry.
+    #[arg(long, default_value_t = 500)]
+    retry_delay_ms: u64,
+    /// Check the images against the manifest instead of downloading them.
+    #[arg(long)]
+    verify: bool,
+    /// Only the images of the N road signs asked about in the most class B questions, the ones
+    /// bundled with the app.
+    #[arg(long, value_name = "N")]
+    signs: Option<usize>,
+}
+
+/// An image in the manifest.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct ManifestImage {
+    /// The path of the image, relative to the manifest.
+    path: String,
+    url: String,
+    size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+}
+
+/// An image which couldn't be downloaded.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct Failure {
+    num: usize,
+    url: String,
+    error: String,
+}
+
+#[derive(Default, Debug, Serialize, Deserialize)]
+struct Manifest {
+    /// The image of each question, by its number.
+    images: BTreeMap<usize, ManifestImage>,
+    failures: Vec<Failure>,
+}
+
+impl Manifest {
+    fn load(dir: &Path) -> Result<Option<Self>, CliError> {
+        let path = dir.join(MANIFEST);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    fn save(&self, dir: &Path) -> Result<(), CliError> {
+        let json = serde_json::to_string_pretty(self).expect("a manifest is always serializable");
+        fs::write(dir.join(MANIFEST), json + "\n")?;
+        Ok(())
+    }
+}
+
+/// The images listed in the manifest in `dir` by the number of their question, e.g. to embed
+/// them in an export.
+#[cfg(feature = "apkg")]
+pub fn local_images(dir: &Path) -> Result<std::collections::HashMap<usize, PathBuf>, CliError> {
+    let manifest = Manifest::load(dir)?
+        .ok_or_else(|| CliError::Filter(format!("no {MANIFEST} in {}", dir.display())))?;
+    Ok(manifest
+        .images
+        .into_iter()
+        .map(|(num, image)| (num, dir.join(image.path)))
+        .filter(|(_, path)| path.is_file())
+        .collect())
+}
+
+/// An image to fetch, which may be the image of several questions.
+struct Job {
+    url: String,
+    name: String,
+    nums: Vec<usize>,
+    /// How many class B road sign questions show it.
+    sign_questions: usize,
+    /// What the last run downloaded.
+    known: Option<ManifestImage>,
+}
+
+/// A fetched image, and whether it was downloaded or already present.
+struct Fetched {
+    size: u64,
+    etag: Option<String>,
+    downloaded: bool,
+}
+
+/// Whether the server has the same version of the image at `path`, by its etag when the last run
+/// got one and by its size otherwise.
+fn is_current(fetcher: &Fetcher, job: &Job, path: &Path) -> Option<Fetched> {
+    let size = fs::metadata(path).ok()?.len();
+    let response = fetcher.head(&job.url).ok()?;
+    let etag = header_value(&response, header::ETAG);
+    let length = header_value(&response, header::CONTENT_LENGTH)
+        .and_then(|length| length.parse::<u64>().ok());
+    let known_etag = job.known.as_ref().and_then(|known| known.etag.as_ref());
+    let current = match (known_etag, &etag) {
+        (Some(known), Some(etag)) => known == etag,
+        _ => length == Some(size),
+    };
+    current.then_some(Fetched {
+        size,
+        etag,
+        downloaded: false,
+    })
+}
+
+fn fetch(fetcher: &Fetcher, job: &Job, out: &Path) -> Result<Fetched, String> {
+    let path = out.join(&job.name);
+    if let Some(fetched) = is_current(fetcher, job, &path) {
+        return Ok(fetched);
+    }
+    let mut response = fetcher.get(&job.url)?;
+    let etag = header_value(&response, header::ETAG);
+    let data = read_body(&mut response)?;
+    // written aside first, so an interrupted run doesn't leave half an image
+    let partial = out.join(format!("{}.part", job.name));
+    fs::write(&partial, &data)
+        .and_then(|()| fs::rename(&partial, &path))
+        .map_err(|error| error.to_string())?;
+    Ok(Fetched {
+        size: data.len() as u64,
+        etag,
+        downloaded: true,
+    })
+}
+
+/// The images of the bank, one job per file, with what `manifest` knows of them. With `--signs`,
+/// only the images of the most common road signs.
+fn jobs(args: &FetchImagesArgs, manifest: &Manifest) -> Result<Vec<Job>, CliError> {
+    let bank = load_bank(Some(&args.input))?;
+    let mut jobs = BTreeMap::<String, Job>::new();
+    for question in &bank.questions {
+        let Some(url) = &question.image_url else {
+            continue;
+        };
+        let Some(name) = file_name(url) else {
+            continue;
+        };
+        let known = manifest
+            .images
+            .get(&question.num)
+            .filter(|known| known.url == *url)
+            .cloned();
+        let job = jobs.entry(name.to_string()).or_insert_with(|| Job {
+            url: url.clone(),
+            name: name.to_string(),
+            nums: Vec::new(),
+            sign_questions: 0,
+            known: None,
+        });
+        job.nums.push(question.num);
+        if question.category == QuestionCategory::RoadSigns
+            && question.license_classes.contains(&LicenseClass::B)
+        {
+            job.sign_questions += 1;
+        }
+        job.known = job.known.take().or(known);
+    }
+    let mut jobs = jobs.into_values().collect::<Vec<_>>();
+    if let Some(signs) = args.signs {
+        jobs.retain(|job| job.sign_questions > 0);
+        // stable, so signs asked about as often stay in the order of their files
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.sign_questions));
+        jobs.truncate(signs);
+        jobs.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    Ok(jobs)
+}
+
+fn fetch_all(args: &FetchImagesArgs) -> Result<ExitCode, CliError> {
+    fs::create_dir_all(&args.out)?;
+    let previous = Manifest::load(&args.out)?.unwrap_or_default();
+    let jobs = jobs(args, &previous)?;
+    let fetcher = Fetcher::new(args.attempts, Duration::from_millis(args.retry_delay_ms));
+    let progress = ProgressBar::new(jobs.len() as u64).with_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}").expect("the template is valid"),
+    );
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..args.concurrency.min(jobs.len().max(1) as u32) {
+            scope.spawn(|| {
+                while let Some(job) = jobs.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    progress.set_message(job.name.clone());
+                    let result = fetch(&fetcher, job, &args.out);
+                    results.lock().unwrap().push((job, result));
+                    progress.inc(1);
+                }
+            });
+        }
+    });
+    progress.finish_and_clear();
+
+    let mut manifest = Manifest::default();
+    let (mut downloaded, mut present) = (0, 0);
+    for (job, result) in results.into_inner().unwrap() {
+        let image = match result {
+            Ok(fetched) => {
+                if fetched.downloaded {
+                    downloaded += 1;
+                } else {
+                    present += 1;
+                }
+                Some(ManifestImage {
+                    path: job.name.clone(),
+                    url: job.url.clone(),
+                    size: fetched.size,
+                    etag: fetched.etag,
+                })
+            }
+            Err(error) => {
+                for num in &job.nums {
+                    eprintln!("failed: {num:04} {}: {error}", job.url);
+                    manifest.failures.push(Failure {
+                        num: *num,
+                        url: job.url.clone(),
+                        error: error.clone(),
+                    });
+                }
+                // the image of the last run is kept until it can be replaced
+                job.known
+                    .clone()
+                    .filter(|_| args.out.join(&job.name).is_file())
+            }
+        };
+        if let Some(image) = image {
+            for num in &job.nums {
+                manifest.images.insert(*num, image.clone());
+            }
+        }
+    }
+    manifest.failures.sort_by_key(|failure| failure.num);
+    manifest.save(&args.out)?;
+    println!(
+        "Downloaded {downloaded}, already present {present}, failed {}",
+        manifest.failures.len()
+    );
+    Ok(if manifest.failures.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(INCOMPLETE)
+    })
+}
+
+/// Check the images against the manifest, and that it has the image of every question of the
+/// bank, printing a line per problem.
+fn verify(args: &FetchImagesArgs) -> Result<ExitCode, CliError> {
+    let manifest = Manifest::load(&args.out)?
+        .ok_or_else(|| CliError::Filter(format!("no {MANIFEST} in {}", args.out.display())))?;
+    let mut problems = Vec::new();
+    for job in jobs(args, &manifest)? {
+        for num in &job.nums {
+            if !manifest.images.contains_key(num) {
+                problems.push(format!("not fetched: {num:04} {}", job.url));
+            }
+        }
+    }
+    for (num, image) in &manifest.images {
+        match fs::metadata(args.out.join(&image.path)) {
+            Ok(metadata) if metadata.len() == image.size => {}
+            Ok(metadata) => problems.push(format!(
+                "changed: {num:04} {} is {} bytes, expected {}",
+                image.path,
+                metadata.len(),
+                image.size
+            )),
+            Err(_) => problems.push(format!("missing: {num:04} {}", image.path)),
+        }
+    }
+    for problem in &problems {
+        println!("{problem}");
+    }
+    println!(
+        "Verified {} images, {} problems",
+        manifest.images.len(),
+        problems.len()
+    );
+    Ok(if problems.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(INCOMPLETE)
+    })
+}
+
+pub fn run(args: FetchImagesArgs) -> Result<ExitCode, CliError> {
+    if args.verify {
+        verify(&args)
+    } else {
+        fetch_all(&args)
+    }
+}
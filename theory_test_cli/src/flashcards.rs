@@ -0,0 +1,72 @@
+//! Flashcards to print on both sides of the paper and cut out, the question on the front and its
+//! answer on the back.
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+use theory_test_parser::{
+    pdf::{FlashcardOptions, export_flashcards_pdf},
+    question_parser::{LicenseClass, QuestionCategory},
+};
+
+use crate::{CliError, load_bank, parse_category, parse_class, read_images};
+
+#[derive(Args)]
+pub struct FlashcardsArgs {
+    /// The xlsx or TOML file of the bank, the bundled bank by default.
+    #[arg(long)]
+    bank: Option<PathBuf>,
+    #[arg(long, default_value = "B", value_parser = parse_class)]
+    class: LicenseClass,
+    /// Only make cards of questions of this category, may be repeated.
+    #[arg(long, value_parser = parse_category)]
+    category: Vec<QuestionCategory>,
+    /// The number of cards across a page.
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=6))]
+    columns: u8,
+    /// The number of cards down a page.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..=8))]
+    rows: u8,
+    /// Leave the category of the question off the front of the cards.
+    #[arg(long)]
+    no_category: bool,
+    /// A directory of the bank's JPEG images, named as at the end of their urls. Questions whose
+    /// image isn't found show its url instead.
+    #[arg(long)]
+    images: Option<PathBuf>,
+    /// Where to write the PDF document, standard output by default.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+pub fn run(args: FlashcardsArgs) -> Result<(), CliError> {
+    let bank = load_bank(args.bank.as_ref())?;
+    let questions = bank
+        .questions
+        .into_iter()
+        .filter(|q| q.license_classes.contains(&args.class))
+        .filter(|q| args.category.is_empty() || args.category.contains(&q.category))
+        .collect::<Vec<_>>();
+    if questions.is_empty() {
+        return Err(CliError::Filter(
+            "no questions match the class and categories".to_string(),
+        ));
+    }
+    let opts = FlashcardOptions {
+        categories: args.category,
+        columns: args.columns.into(),
+        rows: args.rows.into(),
+        show_category: !args.no_category,
+        images: read_images(&questions, args.images.as_deref())?,
+    };
+    let mut document = Vec::new();
+    export_flashcards_pdf(&questions, &mut document, &opts)?;
+    match &args.out {
+        Some(path) => fs::write(path, document)?,
+        None => io::stdout().write_all(&document)?,
+    }
+    Ok(())
+}
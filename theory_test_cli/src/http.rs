@@ -0,0 +1,95 @@
+//! Requests to the servers of the bank, retried while their failures may pass.
+use std::{thread, time::Duration};
+
+use ureq::{
+    Agent, Body,
+    http::{Response, StatusCode, header::HeaderName},
+};
+
+/// The largest body read, well above the size of the bank's spreadsheet.
+const MAX_BODY_SIZE: u64 = 64 * 1024 * 1024;
+
+pub struct Fetcher {
+    agent: Agent,
+    attempts: u32,
+    retry_delay: Duration,
+}
+
+/// The value of the `name` header of `response`, if it's text.
+pub fn header_value(response: &Response<Body>, name: HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+impl Fetcher {
+    /// A fetcher trying each request `attempts` times, waiting `retry_delay` before the first
+    /// retry.
+    pub fn new(attempts: u32, retry_delay: Duration) -> Self {
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(Duration::from_secs(60)))
+            .build()
+            .into();
+        Self {
+            agent,
+            attempts,
+            retry_delay,
+        }
+    }
+
+    /// Send a request until it succeeds, waiting twice as long after each failure. Only
+    /// failures which may pass are retried: network errors, server errors and rate limiting.
+    fn send(
+        &self,
+        send: impl Fn() -> Result<Response<Body>, ureq::Error>,
+    ) -> Result<Response<Body>, String> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 1;
+        loop {
+            let error = match send() {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let error = format!("HTTP {status}");
+                    if !status.is_server_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                        return Err(error);
+                    }
+                    error
+                }
+                Err(error) => error.to_string(),
+            };
+            if attempt >= self.attempts {
+                return Err(error);
+            }
+            thread::sleep(delay);
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+
+    pub fn head(&self, url: &str) -> Result<Response<Body>, String> {
+        self.send(|| self.agent.head(url).call())
+    }
+
+    pub fn get(&self, url: &str) -> Result<Response<Body>, String> {
+        self.send(|| self.agent.get(url).call())
+    }
+
+    /// The body of `url`.
+    pub fn get_bytes(&self, url: &str) -> Result<Vec<u8>, String> {
+        read_body(&mut self.get(url)?)
+    }
+}
+
+/// The body of `response`, up to [`MAX_BODY_SIZE`].
+pub fn read_body(response: &mut Response<Body>) -> Result<Vec<u8>, String> {
+    response
+        .body_mut()
+        .with_config()
+        .limit(MAX_BODY_SIZE)
+        .read_to_vec()
+        .map_err(|error| error.to_string())
+}
@@ -0,0 +1,87 @@
+//! Importing the answers given in another app into a backup, to import the backup into the app.
+use std::{fs, path::PathBuf};
+
+use clap::Args;
+use theory_test_core::csv_import::{Column, CsvMapping, csv_headers};
+
+use crate::{CliError, load_bank, read_backup};
+
+#[derive(Args)]
+pub struct ImportCsvArgs {
+    /// The CSV file, with a row for each answered question.
+    input: PathBuf,
+    /// The backup exported from the app to import into.
+    #[arg(long)]
+    backup: PathBuf,
+    /// Where to write the backup with the imported answers. Without it only the report is
+    /// printed.
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// The xlsx or TOML file of the bank, the bundled bank by default.
+    #[arg(long)]
+    bank: Option<PathBuf>,
+    /// The column of the question numbers, by its 1-based position or its name in the header.
+    /// Guessed from the header by default, or else the first column.
+    #[arg(long)]
+    number_column: Option<Column>,
+    /// The column of whether the answers were correct, the second column if it can't be guessed.
+    #[arg(long)]
+    correct_column: Option<Column>,
+    /// The column of when the questions were answered, the third column if it can't be guessed.
+    #[arg(long)]
+    time_column: Option<Column>,
+    /// The first row is an answer rather than the names of the columns.
+    #[arg(long)]
+    no_header: bool,
+    /// The offset from UTC of the times which don't have a time zone, in hours.
+    #[arg(long, default_value_t = 0, allow_hyphen_values = true)]
+    utc_offset: i64,
+}
+
+/// The columns given on the command line, the rest guessed from the header of `csv`.
+fn mapping(args: &ImportCsvArgs, csv: &[u8]) -> CsvMapping {
+    let guessed = (!args.no_header)
+        .then(|| CsvMapping::guess(&csv_headers(csv)))
+        .flatten()
+        .unwrap_or(CsvMapping {
+            number: Column::Index(0),
+            correct: Column::Index(1),
+            timestamp: Column::Index(2),
+            has_header: !args.no_header,
+        });
+    CsvMapping {
+        number: args.number_column.clone().unwrap_or(guessed.number),
+        correct: args.correct_column.clone().unwrap_or(guessed.correct),
+        timestamp: args.time_column.clone().unwrap_or(guessed.timestamp),
+        has_header: !args.no_header,
+    }
+}
+
+pub fn run(args: ImportCsvArgs) -> Result<(), CliError> {
+    let bank = load_bank(args.bank.as_ref())?;
+    let mut backup = read_backup(&args.backup, args.bank.as_ref())?;
+    let csv = fs::read(&args.input)?;
+    let mapping = mapping(&args, &csv);
+    let report =
+        backup
+            .history_mut()
+            .import_csv(csv.as_slice(), &mapping, &bank, args.utc_offset * 3600)?;
+    println!(
+        "columns: number {}, correct {}, time {}",
+        mapping.number, mapping.correct, mapping.timestamp
+    );
+    println!(
+        "imported: {} answers in {} days",
+        report.imported, report.attempts
+    );
+    println!("skipped: {} rows which couldn't be read", report.skipped);
+    println!(
+        "unmatched: {} questions which aren't in the bank",
+        report.unmatched
+    );
+    println!("already imported: {} answers", report.duplicates);
+    if let Some(out) = &args.out {
+        fs::write(out, serde_json::to_string(&backup)?)?;
+    }
+    Ok(())
+}
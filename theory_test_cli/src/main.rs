@@ -0,0 +1,260 @@
+//! Command line tools over the question bank.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use theory_test_core::backup::Backup;
+use theory_test_parser::{
+    bundle::content_hash,
+    observer::LogObserver,
+    question_parser::{
+        self, ExamQuestions, LicenseClass, ParseOptions, Question, QuestionCategory,
+    },
+};
+use thiserror::Error;
+
+mod bundle;
+mod diff;
+mod export;
+mod fetch_images;
+#[cfg(feature = "pdf")]
+mod flashcards;
+mod http;
+mod import_csv;
+mod plan;
+mod quiz;
+mod sample;
+#[cfg(feature = "schema")]
+mod schema;
+mod site;
+mod stats;
+mod update;
+
+/// The bank the app is built with, used when no other is given.
+const BUNDLED_BANK: &[u8] = include_bytes!("../../theory_test_parser/test.xlsx");
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Command line tools for the theory test question bank"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Trace the parsing and the exams to stderr, more with each repetition (-v, -vv, -vvv).
+    #[cfg(feature = "tracing")]
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Take a practice exam in the terminal.
+    Quiz(quiz::QuizArgs),
+    /// Export the questions of a bank to another format.
+    Export(export::ExportArgs),
+    /// Generate a printable exam.
+    Sample(sample::SampleArgs),
+    /// Generate flashcards to print on both sides of the paper.
+    #[cfg(feature = "pdf")]
+    Flashcards(flashcards::FlashcardsArgs),
+    /// Render the bank as a static site.
+    Site(site::SiteArgs),
+    /// Count the questions of a bank.
+    Stats(stats::StatsArgs),
+    /// Compare two versions of a bank. Exits with 2 when they differ.
+    Diff(diff::DiffArgs),
+    /// Download the images of a bank. Exits with 2 when some couldn't be fetched or verified.
+    FetchImages(fetch_images::FetchImagesArgs),
+    /// Download the official bank, keeping it when it changed.
+    Update(update::UpdateArgs),
+    /// Write the JSON Schema documents of the JSON exports.
+    #[cfg(feature = "schema")]
+    Schema(schema::SchemaArgs),
+    /// Parse a bank ahead of time and compress it, for embedding it in the app.
+    Bundle(bundle::BundleArgs),
+    /// Plan the study until the exam, as a calendar file.
+    Plan(plan::PlanArgs),
+    /// Import the answers of another app from a CSV file into a backup of the app.
+    ImportCsv(import_csv::ImportCsvArgs),
+}
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("can't read the bank: {0}")]
+    Bank(#[from] question_parser::Error),
+    #[error("{0}")]
+    Filter(String),
+    #[error("can't download {0}")]
+    Download(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("can't read the backup: {0}")]
+    Backup(#[from] theory_test_core::backup::ImportError),
+    #[error("can't import the CSV file: {0}")]
+    CsvImport(#[from] theory_test_core::csv_import::CsvImportError),
+    #[cfg(feature = "apkg")]
+    #[error(transparent)]
+    Apkg(#[from] theory_test_parser::apkg::ApkgError),
+    #[cfg(feature = "pdf")]
+    #[error(transparent)]
+    Pdf(#[from] theory_test_parser::pdf::PdfError),
+}
+
+impl CliError {
+    /// The exit code of the error. 2 is taken by usage errors.
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Bank(_) => 3,
+            CliError::Filter(_) => 4,
+            CliError::Download(_) => 5,
+            CliError::Io(_) | CliError::Json(_) | CliError::Backup(_) | CliError::CsvImport(_) => 1,
+            #[cfg(feature = "apkg")]
+            CliError::Apkg(_) => 1,
+            #[cfg(feature = "pdf")]
+            CliError::Pdf(_) => 1,
+        }
+    }
+}
+
+/// Parse a license class from its code, e.g. "C1".
+fn parse_class(code: &str) -> Result<LicenseClass, String> {
+    LicenseClass::from_code(code).ok_or_else(|| {
+        let codes = LicenseClass::ALL.map(|class| class.code()).join(", ");
+        format!("unknown license class \"{code}\", expected one of: {codes}")
+    })
+}
+
+/// Parse a category from its code, e.g. "road_signs".
+fn parse_category(code: &str) -> Result<QuestionCategory, String> {
+    QuestionCategory::from_code(code).ok_or_else(|| {
+        let codes = QuestionCategory::ALL
+            .map(|category| category.code())
+            .join(", ");
+        format!("unknown category \"{code}\", expected one of: {codes}")
+    })
+}
+
+/// Load the bank at `path`, or the bundled one.
+/// Load the bank of `path`, or the bundled one, writing its warnings to stderr.
+fn load_bank(path: Option<&PathBuf>) -> Result<ExamQuestions, CliError> {
+    Ok(match path {
+        Some(path) => {
+            let observer = LogObserver::new(std::io::stderr());
+            let options = ParseOptions {
+                observer: &observer,
+                ..Default::default()
+            };
+            ExamQuestions::parse_from_file_with_options(path, options)?.0
+        }
+        None => ExamQuestions::parse_from_xlsx(BUNDLED_BANK)?,
+    })
+}
+
+/// Read a backup exported from the app, checking it was exported with the bank of `bank`, or
+/// the bundled one.
+fn read_backup(path: &Path, bank: Option<&PathBuf>) -> Result<Backup, CliError> {
+    let bank_hash = match bank {
+        Some(bank) => content_hash(&fs::read(bank)?),
+        None => content_hash(BUNDLED_BANK),
+    };
+    Ok(Backup::parse(&fs::read_to_string(path)?, bank_hash)?)
+}
+
+/// A seed from the clock, for exams which weren't given one.
+fn random_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|since| since.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+/// The name of the file at the end of `url`, e.g. the name of an image in a directory of the
+/// bank's images.
+fn file_name(url: &str) -> Option<&str> {
+    url.rsplit('/').next().filter(|name| !name.is_empty())
+}
+
+/// The JPEG images of `questions` found in `dir`, by question number, for PDF documents.
+#[cfg(feature = "pdf")]
+fn read_images(
+    questions: &[Question],
+    dir: Option<&Path>,
+) -> Result<std::collections::HashMap<usize, Vec<u8>>, CliError> {
+    let mut found = std::collections::HashMap::new();
+    let Some(dir) = dir else {
+        return Ok(found);
+    };
+    for question in questions {
+        let Some(name) = question.image_url.as_deref().and_then(file_name) else {
+            continue;
+        };
+        let path = dir.join(name);
+        if path.is_file() {
+            found.insert(question.num, fs::read(path)?);
+        }
+    }
+    Ok(found)
+}
+
+/// The text of `question` without the number it starts with, e.g. "0862. ".
+fn question_body(question: &Question) -> &str {
+    question.question.get(6..).unwrap_or(&question.question)
+}
+
+/// Write the spans of the parser to stderr, from INFO with one `--verbose` up to TRACE with three.
+/// Nothing is traced without it, the warnings of the bank are written anyway.
+#[cfg(feature = "tracing")]
+fn init_tracing(verbose: u8) {
+    use tracing_subscriber::fmt::{format::FmtSpan, time::Uptime};
+
+    let level = match verbose {
+        0 => return,
+        1 => tracing_subscriber::filter::LevelFilter::INFO,
+        2 => tracing_subscriber::filter::LevelFilter::DEBUG,
+        _ => tracing_subscriber::filter::LevelFilter::TRACE,
+    };
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::IsTerminal::is_terminal(&std::io::stderr()))
+        .with_timer(Uptime::default())
+        // the time each span took, e.g. of parsing the bank
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    #[cfg(feature = "tracing")]
+    init_tracing(cli.verbose);
+    let result = match cli.command {
+        Command::Quiz(args) => quiz::run(args).map(|()| ExitCode::SUCCESS),
+        Command::Export(args) => export::run(args).map(|()| ExitCode::SUCCESS),
+        Command::Sample(args) => sample::run(args).map(|()| ExitCode::SUCCESS),
+        #[cfg(feature = "pdf")]
+        Command::Flashcards(args) => flashcards::run(args).map(|()| ExitCode::SUCCESS),
+        Command::Site(args) => site::run(args).map(|()| ExitCode::SUCCESS),
+        Command::Stats(args) => stats::run(args).map(|()| ExitCode::SUCCESS),
+        Command::Diff(args) => diff::run(args),
+        Command::FetchImages(args) => fetch_images::run(args),
+        Command::Update(args) => update::run(args).map(|()| ExitCode::SUCCESS),
+        #[cfg(feature = "schema")]
+        Command::Schema(args) => schema::run(args).map(|()| ExitCode::SUCCESS),
+        Command::Bundle(args) => bundle::run(args),
+        Command::Plan(args) => plan::run(args).map(|()| ExitCode::SUCCESS),
+        Command::ImportCsv(args) => import_csv::run(args).map(|()| ExitCode::SUCCESS),
+    };
+    match result {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::from(error.exit_code())
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! A study plan until the exam, as a calendar file to import into a calendar app.
+use std::{fs, path::PathBuf};
+
+use clap::Args;
+use theory_test_core::{history::History, scheduler::Scheduler, time};
+use theory_test_parser::question_parser::{ExamQuestions, LicenseClass};
+
+use crate::{CliError, load_bank, parse_class, read_backup};
+
+#[derive(Args)]
+pub struct PlanArgs {
+    /// The xlsx or TOML file of the bank, the bundled bank by default.
+    #[arg(long)]
+    bank: Option<PathBuf>,
+    /// A backup exported from the app, whose history decides which questions are reviewed and
+    /// when. Without one every question is new.
+    #[arg(long)]
+    backup: Option<PathBuf>,
+    #[arg(long, default_value = "B", value_parser = parse_class)]
+    class: LicenseClass,
+    /// The day of the exam, e.g. 2026-11-20. The plan ends the day before.
+    #[arg(long, value_parser = parse_day)]
+    exam_date: i64,
+    /// The most questions to study on a single day.
+    #[arg(long, default_value_t = 40)]
+    capacity: usize,
+    /// The first day of the plan, today in UTC by default.
+    #[arg(long, value_parser = parse_day)]
+    today: Option<i64>,
+    /// Where to write the iCalendar file, stdout by default.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Parse a `yyyy-mm-dd` date into a number of days since the unix epoch.
+fn parse_day(date: &str) -> Result<i64, String> {
+    time::parse_date(date).ok_or_else(|| format!("invalid date \"{date}\", expected yyyy-mm-dd"))
+}
+
+pub fn run(args: PlanArgs) -> Result<(), CliError> {
+    let bank = load_bank(args.bank.as_ref())?;
+    let history = match &args.backup {
+        Some(path) => read_backup(path, args.bank.as_ref())?.history().clone(),
+        None => History::default(),
+    };
+    let now = match args.today {
+        Some(day) => std::time::Duration::from_secs(day as u64 * 86_400),
+        None => std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default(),
+    };
+    let pool = ExamQuestions {
+        questions: bank
+            .questions
+            .into_iter()
+            .filter(|q| q.license_classes.contains(&args.class))
+            .collect(),
+    };
+    let plan = Scheduler::new(&history, now, 0).study_plan(&pool, args.exam_date, args.capacity);
+
+    let ics = plan.to_ics();
+    match &args.out {
+        Some(out) => fs::write(out, ics)?,
+        None => print!("{ics}"),
+    }
+    let study_days = plan.days.iter().filter(|day| day.num_questions() > 0);
+    eprintln!(
+        "{} questions over {} days until {}",
+        plan.num_questions(),
+        study_days.count(),
+        time::format_date(plan.exam_day)
+    );
+    if plan.shortfall > 0 {
+        eprintln!(
+            "warning: {} questions don't fit before the exam, raise --capacity",
+            plan.shortfall
+        );
+    }
+    Ok(())
+}
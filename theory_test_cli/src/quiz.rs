@@ -0,0 +1,194 @@
+//! A practice exam in the terminal.
+use std::{
+    io::{self, BufRead, Write},
+    path::PathBuf,
+};
+
+use clap::Args;
+use theory_test_core::exam_state::ExamResult;
+use theory_test_parser::{
+    exam::{ExamSpec, GeneratedExam, OFFICIAL_NUM_QUESTIONS},
+    question_parser::{LicenseClass, QuestionCategory},
+};
+
+use crate::{CliError, load_bank, parse_category, parse_class, question_body, random_seed};
+
+#[derive(Args)]
+pub struct QuizArgs {
+    /// The xlsx or TOML file of the bank, the bundled bank by default.
+    #[arg(long)]
+    bank: Option<PathBuf>,
+    /// The number of questions.
+    #[arg(long, default_value_t = OFFICIAL_NUM_QUESTIONS)]
+    num: usize,
+    #[arg(long, default_value = "B", value_parser = parse_class)]
+    class: LicenseClass,
+    /// Only draw questions of this category, may be repeated.
+    #[arg(long, value_parser = parse_category)]
+    category: Vec<QuestionCategory>,
+    /// The seed of the exam, a random one by default. The same seed gives the same exam.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// The answers, 1-based and comma separated, instead of reading them from the terminal.
+    /// An empty answer leaves the question unanswered, e.g. "1,,3".
+    #[arg(long, value_delimiter = ',')]
+    answers: Option<Vec<String>>,
+}
+
+/// Parse an answer to a question of `num_answers`, 1-based. `Ok(None)` leaves it unanswered.
+fn parse_answer(input: &str, num_answers: usize) -> Result<Option<usize>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    match input.parse::<usize>() {
+        Ok(answer) if (1..=num_answers).contains(&answer) => Ok(Some(answer - 1)),
+        _ => Err(format!("expected a number from 1 to {num_answers}")),
+    }
+}
+
+/// Print the questions of `exam` one by one, with the answer of each read by `read_answer`.
+fn ask(
+    exam: &GeneratedExam,
+    out: &mut impl Write,
+    mut read_answer: impl FnMut(usize, &mut dyn Write) -> io::Result<Option<usize>>,
+) -> io::Result<Vec<Option<usize>>> {
+    let mut selections = Vec::with_capacity(exam.questions.len());
+    for (idx, question) in exam.questions.iter().enumerate() {
+        writeln!(
+            out,
+            "\nQuestion {}/{} (#{:04}, {})",
+            idx + 1,
+            exam.questions.len(),
+            question.num,
+            question.category.as_str_he()
+        )?;
+        writeln!(out, "{}", question_body(question))?;
+        if let Some(image_url) = &question.image_url {
+            writeln!(out, "Image: {image_url}")?;
+        }
+        for (answer_idx, answer) in question.answers.possible_answers.iter().enumerate() {
+            writeln!(out, "  {}. {}", answer_idx + 1, answer)?;
+        }
+        selections.push(read_answer(question.answers.possible_answers.len(), out)?);
+    }
+    Ok(selections)
+}
+
+/// Print the grade of `exam` answered with `selections`.
+fn print_summary(
+    exam: &GeneratedExam,
+    selections: &[Option<usize>],
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let result = ExamResult::of_selections(&exam.questions, selections);
+    let grade = exam.grade(selections);
+    writeln!(
+        out,
+        "\nScore: {}/{} ({} mistakes, {} allowed) - {}",
+        result.correct(),
+        result.num_questions(),
+        result.mistakes(),
+        result.allowed_mistakes(),
+        if result.passed() { "passed" } else { "failed" }
+    )?;
+    writeln!(out, "By category:")?;
+    for score in &grade.per_category {
+        writeln!(
+            out,
+            "  {}: {}/{}",
+            score.category.as_str_he(),
+            score.correct,
+            score.total
+        )?;
+    }
+    let missed = result.missed();
+    if !missed.is_empty() {
+        writeln!(out, "Mistakes:")?;
+        for idx in missed {
+            let question = &exam.questions[idx];
+            let given = match selections.get(idx).copied().flatten() {
+                Some(answer) => (answer + 1).to_string(),
+                None => "none".to_string(),
+            };
+            writeln!(
+                out,
+                "  {}. #{:04}: answered {}, correct {}",
+                idx + 1,
+                question.num,
+                given,
+                question.answers.correct_answer + 1
+            )?;
+        }
+    }
+    writeln!(out, "Seed: {}", exam.seed)
+}
+
+pub fn run(args: QuizArgs) -> Result<(), CliError> {
+    let bank = load_bank(args.bank.as_ref())?;
+    let spec = ExamSpec {
+        num_questions: args.num,
+        class: args.class,
+        categories: args.category,
+        official_distribution: false,
+        seed: args.seed.unwrap_or_else(random_seed),
+    };
+    let exam = spec.generate(&bank);
+    if exam.questions.is_empty() {
+        return Err(CliError::Filter(
+            "no questions match the class and categories".to_string(),
+        ));
+    }
+    let mut out = io::stdout().lock();
+    let selections = match args.answers {
+        Some(answers) => {
+            let mut answers = answers.into_iter();
+            ask(&exam, &mut out, |num_answers, out| {
+                let answer = answers.next().unwrap_or_default();
+                let selection = parse_answer(&answer, num_answers)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+                writeln!(
+                    out,
+                    "Answer: {}",
+                    selection
+                        .map(|s| s + 1)
+                        .map_or("none".to_string(), |s| s.to_string())
+                )?;
+                Ok(selection)
+            })
+        }
+        None => {
+            let mut lines = io::stdin().lock().lines();
+            ask(&exam, &mut out, |num_answers, out| {
+                loop {
+                    write!(out, "Answer [1-{num_answers}, empty to skip]: ")?;
+                    out.flush()?;
+                    // the end of the input leaves the rest unanswered
+                    let Some(line) = lines.next().transpose()? else {
+                        writeln!(out)?;
+                        return Ok(None);
+                    };
+                    match parse_answer(&line, num_answers) {
+                        Ok(selection) => return Ok(selection),
+                        Err(error) => writeln!(out, "{error}")?,
+                    }
+                }
+            })
+        }
+    }?;
+    Ok(print_summary(&exam, &selections, &mut out)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn answers() {
+        assert_eq!(parse_answer("2", 4), Ok(Some(1)));
+        assert_eq!(parse_answer(" ", 4), Ok(None));
+        assert!(parse_answer("5", 4).is_err());
+        assert!(parse_answer("0", 4).is_err());
+        assert!(parse_answer("x", 4).is_err());
+    }
+}
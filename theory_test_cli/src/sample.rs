@@ -0,0 +1,224 @@
+//! Generating an exam to print, e.g. a weekly quiz on paper out of the same bank as the app.
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::{Args, ValueEnum};
+use theory_test_parser::{
+    exam::{ExamSpec, GeneratedExam, OFFICIAL_NUM_QUESTIONS},
+    question_parser::{LicenseClass, QuestionCategory},
+};
+
+use crate::{CliError, load_bank, parse_category, parse_class, question_body, random_seed};
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Markdown,
+    Text,
+    /// An A4 document, with the answer key on its last page.
+    #[cfg(feature = "pdf")]
+    Pdf,
+}
+
+#[derive(Args)]
+pub struct SampleArgs {
+    /// The xlsx or TOML file of the bank, the bundled bank by default.
+    #[arg(long)]
+    bank: Option<PathBuf>,
+    /// The number of questions.
+    #[arg(long, default_value_t = OFFICIAL_NUM_QUESTIONS)]
+    num: usize,
+    #[arg(long, default_value = "B", value_parser = parse_class)]
+    class: LicenseClass,
+    /// Only draw questions of this category, may be repeated.
+    #[arg(long, value_parser = parse_category)]
+    category: Vec<QuestionCategory>,
+    /// The seed of the exam, a random one by default. The same seed gives the same exam.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Draw from each category in proportion to its share of the bank, as the official exam.
+    #[arg(long)]
+    official_distribution: bool,
+    #[arg(long, value_enum, default_value = "markdown")]
+    format: Format,
+    /// Add the answer key after the questions. PDF documents always end with it.
+    #[arg(long)]
+    answer_key: bool,
+    /// A directory of the bank's JPEG images, named as at the end of their urls, to print in
+    /// PDF documents. Questions whose image isn't found show its url instead.
+    #[cfg(feature = "pdf")]
+    #[arg(long)]
+    images: Option<PathBuf>,
+    /// Where to write the exam, standard output by default.
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// The exam, and its answer key if asked for, as a document to print.
+fn format_exam(
+    exam: &GeneratedExam,
+    class: LicenseClass,
+    format: Format,
+    answer_key: bool,
+) -> String {
+    let markdown = format == Format::Markdown;
+    let mut out = String::new();
+    let title = format!(
+        "Theory test exam - class {}, seed {}",
+        class.code(),
+        exam.seed
+    );
+    if markdown {
+        writeln!(out, "# {title}").unwrap();
+    } else {
+        writeln!(out, "{title}").unwrap();
+    }
+    for (idx, question) in exam.questions.iter().enumerate() {
+        writeln!(out).unwrap();
+        if markdown {
+            writeln!(out, "## {}. {}", idx + 1, question_body(question)).unwrap();
+        } else {
+            writeln!(out, "{}. {}", idx + 1, question_body(question)).unwrap();
+        }
+        if let Some(image_url) = &question.image_url {
+            if markdown {
+                writeln!(out, "\n![]({image_url})\n").unwrap();
+            } else {
+                writeln!(out, "   Image: {image_url}").unwrap();
+            }
+        }
+        for (answer_idx, answer) in question.answers.possible_answers.iter().enumerate() {
+            if markdown {
+                writeln!(out, "{}. {}", answer_idx + 1, answer).unwrap();
+            } else {
+                writeln!(out, "   {}) {}", answer_idx + 1, answer).unwrap();
+            }
+        }
+    }
+    if answer_key {
+        writeln!(out).unwrap();
+        if markdown {
+            writeln!(out, "## Answer key\n").unwrap();
+        } else {
+            writeln!(out, "Answer key").unwrap();
+        }
+        for (idx, question) in exam.questions.iter().enumerate() {
+            let line = format!(
+                "{}: {} (#{:04})",
+                idx + 1,
+                question.answers.correct_answer + 1,
+                question.num
+            );
+            if markdown {
+                writeln!(out, "- {line}").unwrap();
+            } else {
+                writeln!(out, "{line}").unwrap();
+            }
+        }
+    }
+    out
+}
+
+pub fn run(args: SampleArgs) -> Result<(), CliError> {
+    let bank = load_bank(args.bank.as_ref())?;
+    let spec = ExamSpec {
+        num_questions: args.num,
+        class: args.class,
+        categories: args.category,
+        official_distribution: args.official_distribution,
+        seed: args.seed.unwrap_or_else(random_seed),
+    };
+    let exam = spec.generate(&bank);
+    if exam.questions.is_empty() {
+        return Err(CliError::Filter(
+            "no questions match the class and categories".to_string(),
+        ));
+    }
+    let document = match args.format {
+        #[cfg(feature = "pdf")]
+        Format::Pdf => pdf::exam_pdf(&exam, args.class, args.images.as_deref())?,
+        format => format_exam(&exam, args.class, format, args.answer_key).into_bytes(),
+    };
+    match &args.out {
+        Some(path) => fs::write(path, document)?,
+        None => io::stdout().write_all(&document)?,
+    }
+    Ok(())
+}
+
+#[cfg(feature = "pdf")]
+mod pdf {
+    use std::{
+        path::Path,
+        time::{SystemTime, UNIX_EPOCH},
+    };
+
+    use theory_test_parser::{exam::GeneratedExam, pdf::PdfOptions, question_parser::LicenseClass};
+
+    use crate::{CliError, read_images};
+
+    /// Today's date in UTC, e.g. "2025-03-01".
+    fn today() -> String {
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs() / (24 * 60 * 60))
+            .unwrap_or_default();
+        civil_date(days as i64)
+    }
+
+    /// The date `days` days after 1970-01-01, by the proleptic Gregorian calendar.
+    fn civil_date(days: i64) -> String {
+        // shifted to start at 0000-03-01, so leap days end the year, see
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    /// The exam as a PDF document, with the images of its questions found in `images`.
+    pub fn exam_pdf(
+        exam: &GeneratedExam,
+        class: LicenseClass,
+        images: Option<&Path>,
+    ) -> Result<Vec<u8>, CliError> {
+        let opts = PdfOptions {
+            class,
+            date: today(),
+            images: read_images(&exam.questions, images)?,
+            ..Default::default()
+        };
+        let mut document = Vec::new();
+        exam.to_pdf(&mut document, &opts)?;
+        Ok(document)
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn dates() {
+            assert_eq!(civil_date(0), "1970-01-01");
+            assert_eq!(civil_date(59), "1970-03-01");
+            // 2024 is a leap year
+            assert_eq!(civil_date(19_782), "2024-02-29");
+            assert_eq!(civil_date(20_148), "2025-03-01");
+            assert_eq!(civil_date(-1), "1969-12-31");
+        }
+    }
+}
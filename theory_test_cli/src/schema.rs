@@ -0,0 +1,26 @@
+//! Writing the JSON Schema documents of the exports, for tools which consume them.
+use std::{fs, path::PathBuf};
+
+use clap::Args;
+use theory_test_parser::{exam::ExamResult, question_parser::ExamQuestions};
+
+use crate::CliError;
+
+#[derive(Args)]
+pub struct SchemaArgs {
+    /// The directory to write `questions.schema.json` and `result.schema.json` into.
+    #[arg(long)]
+    out: PathBuf,
+}
+
+pub fn run(args: SchemaArgs) -> Result<(), CliError> {
+    fs::create_dir_all(&args.out)?;
+    for (name, schema) in [
+        ("questions", ExamQuestions::json_schema()),
+        ("result", ExamResult::json_schema()),
+    ] {
+        let path = args.out.join(format!("{name}.schema.json"));
+        fs::write(path, serde_json::to_string_pretty(&schema)? + "\n")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,363 @@
+//! The routes of the server. Every route only reads the bank, which is loaded once at startup.
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::{HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::{
+    BankStats, ExamQuestions, LicenseClass, Question, QuestionCategory,
+};
+use thiserror::Error;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::exams;
+
+/// The number of items in a page when the request doesn't say.
+const DEFAULT_PER_PAGE: usize = 20;
+/// The most items a page may have.
+const MAX_PER_PAGE: usize = 100;
+
+pub type Bank = Arc<ExamQuestions>;
+
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    NotFound(String),
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+        };
+        let body = ErrorBody {
+            error: self.to_string(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// A page of the items matching a request.
+#[derive(Serialize)]
+struct Page<T> {
+    items: Vec<T>,
+    /// 1-based. A page past the last one is empty.
+    page: usize,
+    per_page: usize,
+    /// The number of matching items, on every page.
+    total: usize,
+    total_pages: usize,
+}
+
+impl<T> Page<T> {
+    /// The `page`th page of `items`, of `per_page` items.
+    fn of(
+        items: impl IntoIterator<Item = T>,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Self, ApiError> {
+        let page = page.unwrap_or(1);
+        let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE);
+        if page == 0 {
+            return Err(ApiError::BadRequest("pages start at 1".to_string()));
+        }
+        if !(1..=MAX_PER_PAGE).contains(&per_page) {
+            return Err(ApiError::BadRequest(format!(
+                "per_page must be from 1 to {MAX_PER_PAGE}"
+            )));
+        }
+        let items = items.into_iter().collect::<Vec<_>>();
+        let total = items.len();
+        Ok(Self {
+            items: items
+                .into_iter()
+                // saturating, as the offset of a page far past the last one would overflow
+                .skip(page.saturating_sub(1).saturating_mul(per_page))
+                .take(per_page)
+                .collect(),
+            page,
+            per_page,
+            total,
+            total_pages: total.div_ceil(per_page),
+        })
+    }
+}
+
+fn parse_class(code: Option<&str>) -> Result<Option<LicenseClass>, ApiError> {
+    code.map(|code| {
+        LicenseClass::from_code(code)
+            .ok_or_else(|| ApiError::BadRequest(format!("unknown license class \"{code}\"")))
+    })
+    .transpose()
+}
+
+fn parse_category(code: Option<&str>) -> Result<Option<QuestionCategory>, ApiError> {
+    code.map(|code| {
+        QuestionCategory::from_code(code)
+            .ok_or_else(|| ApiError::BadRequest(format!("unknown category \"{code}\"")))
+    })
+    .transpose()
+}
+
+#[derive(Deserialize)]
+struct QuestionsQuery {
+    class: Option<String>,
+    category: Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+async fn questions(
+    State(bank): State<Bank>,
+    Query(query): Query<QuestionsQuery>,
+) -> Result<Json<Page<Question>>, ApiError> {
+    let class = parse_class(query.class.as_deref())?;
+    let category = parse_category(query.category.as_deref())?;
+    let matching = bank
+        .questions
+        .iter()
+        .filter(|q| class.is_none_or(|class| q.license_classes.contains(&class)))
+        .filter(|q| category.is_none_or(|category| q.category == category))
+        .cloned();
+    Ok(Json(Page::of(matching, query.page, query.per_page)?))
+}
+
+async fn question(
+    State(bank): State<Bank>,
+    Path(num): Path<usize>,
+) -> Result<Json<Question>, ApiError> {
+    bank.questions
+        .iter()
+        .find(|q| q.num == num)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("no question is numbered {num}")))
+}
+
+#[derive(Deserialize)]
+struct StatsQuery {
+    class: Option<String>,
+}
+
+async fn stats(
+    State(bank): State<Bank>,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<BankStats>, ApiError> {
+    let class = parse_class(query.class.as_deref())?;
+    Ok(Json(bank.pool_stats(class)))
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    class: Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+async fn search(
+    State(bank): State<Bank>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Page<Question>>, ApiError> {
+    if query.q.trim().is_empty() {
+        return Err(ApiError::BadRequest("the query is empty".to_string()));
+    }
+    let class = parse_class(query.class.as_deref())?;
+    let matching = bank
+        .search(&query.q)
+        .into_iter()
+        .filter(|q| class.is_none_or(|class| q.license_classes.contains(&class)))
+        .cloned();
+    Ok(Json(Page::of(matching, query.page, query.per_page)?))
+}
+
+/// The routes over `bank`.
+pub fn router(bank: ExamQuestions) -> Router {
+    Router::new()
+        .route("/questions", get(questions))
+        .route("/questions/{num}", get(question))
+        .route("/stats", get(stats))
+        .route("/search", get(search))
+        .route("/exams", post(exams::create))
+        .route("/exams/{id}/grade", post(exams::grade))
+        .with_state(Arc::new(bank))
+}
+
+/// The CORS layer allowing `origins`, or any origin for "*". `None` when there are no origins,
+/// which leaves the API to pages of its own origin.
+pub fn cors_layer(origins: &[String]) -> Result<Option<CorsLayer>, String> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin).map_err(|_| format!("invalid origin \"{origin}\""))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(Some(
+        CorsLayer::new()
+            .allow_methods([Method::GET, Method::POST])
+            .allow_origin(allow_origin),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn bank() -> ExamQuestions {
+        ExamQuestions {
+            questions: (1..=45)
+                .map(|num| {
+                    let category = if num % 3 == 0 {
+                        QuestionCategory::RoadSigns
+                    } else {
+                        QuestionCategory::Safety
+                    };
+                    let license_classes = if num <= 40 {
+                        vec![LicenseClass::B]
+                    } else {
+                        vec![LicenseClass::C]
+                    };
+                    Question {
+                        category,
+                        license_classes,
+                        ..Question::sample(num, 0)
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    async fn get_json(router: Router, uri: &str) -> (StatusCode, Value) {
+        let response = router
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn nums(page: &Value) -> Vec<u64> {
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|q| q["num"].as_u64().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn filtering() {
+        let (status, page) =
+            get_json(router(bank()), "/questions?class=B&category=road_signs").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["total"], 13);
+        assert_eq!(nums(&page)[..3], [3, 6, 9]);
+        assert_eq!(nums(&page).len(), 13);
+        let (_, page) = get_json(router(bank()), "/questions?class=C").await;
+        assert_eq!(nums(&page), [41, 42, 43, 44, 45]);
+        let (status, error) = get_json(router(bank()), "/questions?class=X").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["error"], "unknown license class \"X\"");
+        let (_, stats) = get_json(router(bank()), "/stats?class=C").await;
+        assert_eq!(stats["total"], 45);
+        assert_eq!(stats["class_total"], 5);
+    }
+
+    #[tokio::test]
+    async fn pagination() {
+        let (_, first) = get_json(router(bank()), "/questions").await;
+        assert_eq!(first["total"], 45);
+        assert_eq!(first["total_pages"], 3);
+        assert_eq!(nums(&first), (1..=20).collect::<Vec<_>>());
+        let (_, last) = get_json(router(bank()), "/questions?page=3").await;
+        assert_eq!(nums(&last), (41..=45).collect::<Vec<_>>());
+        let (status, past) = get_json(router(bank()), "/questions?page=4").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(nums(&past).is_empty());
+        let uri = format!("/questions?page={}", usize::MAX);
+        let (status, far) = get_json(router(bank()), &uri).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(nums(&far).is_empty());
+        let (_, exact) = get_json(router(bank()), "/questions?per_page=45").await;
+        assert_eq!(exact["total_pages"], 1);
+        for uri in [
+            "/questions?page=0",
+            "/questions?per_page=0",
+            "/questions?per_page=101",
+        ] {
+            let (status, _) = get_json(router(bank()), uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn single_question() {
+        let (status, question) = get_json(router(bank()), "/questions/7").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(question["question"], "0007. question");
+        let (status, error) = get_json(router(bank()), "/questions/999").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(error["error"], "no question is numbered 999");
+    }
+
+    #[tokio::test]
+    async fn searching() {
+        let (_, page) = get_json(router(bank()), "/search?q=0042").await;
+        assert_eq!(nums(&page), [42]);
+        let (_, page) = get_json(router(bank()), "/search?q=ANSWER&class=C&per_page=2").await;
+        assert_eq!(page["total"], 5);
+        assert_eq!(nums(&page), [41, 42]);
+        let (status, _) = get_json(router(bank()), "/search?q=%20").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn cors() {
+        assert!(cors_layer(&[]).unwrap().is_none());
+        assert!(cors_layer(&["bad\norigin".to_string()]).is_err());
+        let cors = cors_layer(&["https://example.com".to_string()])
+            .unwrap()
+            .unwrap();
+        let response = router(bank())
+            .layer(cors)
+            .oneshot(
+                Request::get("/stats")
+                    .header("origin", "https://example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()["access-control-allow-origin"],
+            "https://example.com"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Generating and grading exams, for clients which don't ship the bank. Nothing is stored: an
+//! exam's id is its spec, which regenerates the same exam out of the same bank.
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use serde::{Deserialize, Serialize};
+use theory_test_core::exam_state::{self, QuestionOutcome};
+use theory_test_parser::{
+    exam::{ExamResult, ExamSpec, GeneratedExam},
+    question_parser::{LicenseClass, Question, QuestionCategory},
+};
+
+use crate::api::{ApiError, Bank};
+
+/// A question as given to the examinee, without its correct answer.
+#[derive(Serialize)]
+pub struct ExamQuestion {
+    num: usize,
+    question: String,
+    possible_answers: Vec<String>,
+    category: QuestionCategory,
+    license_classes: Vec<LicenseClass>,
+    image_url: Option<String>,
+}
+
+impl From<Question> for ExamQuestion {
+    fn from(question: Question) -> Self {
+        Self {
+            num: question.num,
+            question: question.question,
+            possible_answers: question.answers.possible_answers,
+            category: question.category,
+            license_classes: question.license_classes,
+            image_url: question.image_url,
+        }
+    }
+}
+
+/// A generated exam, to be graded at `/exams/{id}/grade`.
+#[derive(Serialize)]
+pub struct Exam {
+    id: String,
+    seed: u64,
+    questions: Vec<ExamQuestion>,
+}
+
+/// Regenerate the exam of `spec`, failing when no question matches it.
+fn generate(bank: &Bank, spec: &ExamSpec) -> Result<GeneratedExam, ApiError> {
+    let exam = spec.generate(bank);
+    if exam.questions.is_empty() {
+        return Err(ApiError::BadRequest(
+            "no questions match the class and categories".to_string(),
+        ));
+    }
+    Ok(exam)
+}
+
+pub async fn create(
+    State(bank): State<Bank>,
+    Json(spec): Json<ExamSpec>,
+) -> Result<Json<Exam>, ApiError> {
+    let exam = generate(&bank, &spec)?;
+    Ok(Json(Exam {
+        id: spec.id(),
+        seed: exam.seed,
+        questions: exam.questions.into_iter().map(ExamQuestion::from).collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct Selections {
+    /// The 0-based selected answer of each question in exam order, `null` for unanswered ones.
+    /// Missing ones at the end are unanswered.
+    selections: Vec<Option<usize>>,
+}
+
+pub async fn grade(
+    State(bank): State<Bank>,
+    Path(id): Path<String>,
+    Json(Selections { selections }): Json<Selections>,
+) -> Result<Json<ExamResult>, ApiError> {
+    let spec = ExamSpec::from_id(&id)
+        .ok_or_else(|| ApiError::NotFound(format!("\"{id}\" is not an exam id")))?;
+    let exam = generate(&bank, &spec)?;
+    if selections.len() > exam.questions.len() {
+        return Err(ApiError::BadRequest(format!(
+            "the exam has {} questions, got {} selections",
+            exam.questions.len(),
+            selections.len()
+        )));
+    }
+    let result = exam_state::ExamResult::of_selections(&exam.questions, &selections);
+    let grade = exam.grade(&selections);
+    Ok(Json(ExamResult {
+        id,
+        num_correct: result.correct(),
+        mistakes: result.mistakes(),
+        allowed_mistakes: result.allowed_mistakes(),
+        passed: result.passed(),
+        correct_answers: exam
+            .questions
+            .iter()
+            .map(|q| q.answers.correct_answer)
+            .collect(),
+        correct: result
+            .outcomes
+            .iter()
+            .map(|outcome| *outcome == QuestionOutcome::Correct)
+            .collect(),
+        per_category: grade.per_category,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use serde_json::{Value, json};
+    use theory_test_parser::question_parser::{Answers, ExamQuestions};
+    use tower::ServiceExt;
+
+    use crate::api::router;
+
+    use super::*;
+
+    /// A bank whose correct answers read "right", so a test can answer like a client would.
+    fn bank() -> ExamQuestions {
+        ExamQuestions {
+            questions: (1..=10)
+                .map(|num| Question {
+                    question: format!("{num:04}. Question {num}"),
+                    answers: Answers {
+                        possible_answers: ["right", "wrong 1", "wrong 2", "wrong 3"]
+                            .map(str::to_string)
+                            .to_vec(),
+                        correct_answer: 0,
+                    },
+                    category: if num % 2 == 0 {
+                        QuestionCategory::RoadSigns
+                    } else {
+                        QuestionCategory::Safety
+                    },
+                    license_classes: vec![LicenseClass::B],
+                    ..Question::sample(num, 0)
+                })
+                .collect(),
+        }
+    }
+
+    async fn post_json(router: Router, uri: &str, body: Value) -> (StatusCode, Value) {
+        let request = Request::post(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn answer_index(question: &Value, answer: &str) -> usize {
+        question["possible_answers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .position(|a| a == answer)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn generating() {
+        let spec = json!({ "num_questions": 6, "seed": 5 });
+        let (status, exam) = post_json(router(bank()), "/exams", spec.clone()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(exam["id"], "B.6.all.u.5");
+        assert_eq!(exam["seed"], 5);
+        let questions = exam["questions"].as_array().unwrap();
+        assert_eq!(questions.len(), 6);
+        for question in questions {
+            let mut keys = question.as_object().unwrap().keys().collect::<Vec<_>>();
+            keys.sort();
+            assert_eq!(
+                keys,
+                [
+                    "category",
+                    "image_url",
+                    "license_classes",
+                    "num",
+                    "possible_answers",
+                    "question"
+                ]
+            );
+        }
+        let (_, again) = post_json(router(bank()), "/exams", spec).await;
+        assert_eq!(again, exam);
+        let (status, _) = post_json(router(bank()), "/exams", json!({ "class": "C" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn grading() {
+        let (_, exam) = post_json(
+            router(bank()),
+            "/exams",
+            json!({ "num_questions": 6, "seed": 5 }),
+        )
+        .await;
+        let questions = exam["questions"].as_array().unwrap();
+        let mut selections = questions
+            .iter()
+            .map(|q| json!(answer_index(q, "right")))
+            .collect::<Vec<_>>();
+        selections[0] = json!(answer_index(&questions[0], "wrong 2"));
+        selections[1] = Value::Null;
+        let uri = format!("/exams/{}/grade", exam["id"].as_str().unwrap());
+        let (status, result) =
+            post_json(router(bank()), &uri, json!({ "selections": selections })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(result["num_correct"], 4);
+        assert_eq!(result["mistakes"], 2);
+        assert_eq!(result["allowed_mistakes"], 0);
+        assert_eq!(result["passed"], false);
+        assert_eq!(
+            result["correct"],
+            json!([false, false, true, true, true, true])
+        );
+        assert_eq!(
+            result["correct_answers"][0],
+            json!(answer_index(&questions[0], "right"))
+        );
+        let per_category = result["per_category"].as_array().unwrap();
+        let total = per_category
+            .iter()
+            .map(|score| score["total"].as_u64().unwrap())
+            .sum::<u64>();
+        assert_eq!(total, 6);
+
+        let (status, _) = post_json(
+            router(bank()),
+            "/exams/not-an-id/grade",
+            json!({ "selections": [] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = post_json(
+            router(bank()),
+            &uri,
+            json!({ "selections": [0, 0, 0, 0, 0, 0, 0] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,69 @@
+//! A JSON API over the question bank, for clients hosted apart from the app.
+use std::{net::SocketAddr, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use theory_test_parser::question_parser::ExamQuestions;
+
+mod api;
+mod exams;
+
+/// The bank the app is built with, used when no other is given.
+const BUNDLED_BANK: &[u8] = include_bytes!("../../../theory_test_parser/test.xlsx");
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "A read-only JSON API over the theory test question bank"
+)]
+struct Args {
+    /// The xlsx or TOML file of the bank, the bundled bank by default.
+    #[arg(long)]
+    bank: Option<PathBuf>,
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: SocketAddr,
+    /// An origin allowed to call the API from a browser, may be repeated. "*" allows any.
+    #[arg(long)]
+    cors_origin: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    let bank = match &args.bank {
+        Some(path) => ExamQuestions::parse_from_file(path),
+        None => ExamQuestions::parse_from_xlsx(BUNDLED_BANK),
+    };
+    let bank = match bank {
+        Ok(bank) => bank,
+        Err(error) => {
+            eprintln!("error: can't read the bank: {error}");
+            return ExitCode::from(3);
+        }
+    };
+    let cors = match api::cors_layer(&args.cors_origin) {
+        Ok(cors) => cors,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut router = api::router(bank);
+    if let Some(cors) = cors {
+        router = router.layer(cors);
+    }
+    let listener = match tokio::net::TcpListener::bind(args.addr).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("error: can't listen on {}: {error}", args.addr);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("listening on http://{}", args.addr);
+    match axum::serve(listener, router).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! A static site of the bank: a page per question, index pages per category and license class,
+//! and a search box over a JSON index. Every page is self-contained and right to left.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Args;
+use serde_json::json;
+use theory_test_parser::{
+    export::{html_escape, question_html},
+    question_parser::{LicenseClass, Question, QuestionCategory},
+};
+
+use crate::{CliError, file_name, load_bank, question_body};
+
+#[derive(Args)]
+pub struct SiteArgs {
+    /// The xlsx or TOML file of the bank.
+    input: PathBuf,
+    /// The directory to write the site to, created if missing.
+    #[arg(long)]
+    out: PathBuf,
+    /// A directory of the bank's images, named as at the end of their urls, e.g.
+    /// "TQ_PIC_3475.jpg". The images found in it are copied into the site.
+    #[arg(long)]
+    images: Option<PathBuf>,
+}
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:48rem;margin:auto;padding:1rem;line-height:1.5}\
+img{max-width:100%}li.correct{font-weight:bold;color:#1b5e20}nav{display:flex;gap:1rem;margin:1rem 0}\
+.category{color:#555}input{width:100%;font-size:1rem;padding:.5rem}";
+
+/// Looks up the search index and lists the matching questions as the query is typed.
+const SEARCH_SCRIPT: &str = r#"fetch("search-index.json").then(r=>r.json()).then(index=>{
+const input=document.getElementById("search"),results=document.getElementById("results");
+input.addEventListener("input",()=>{const q=input.value.trim().toLowerCase();results.replaceChildren();
+if(!q)return;for(const e of index.filter(e=>e.text.toLowerCase().includes(q)).slice(0,50)){
+const li=document.createElement("li"),a=document.createElement("a");a.href=e.url;a.textContent=e.text;
+li.append(a);results.append(li);}});});"#;
+
+/// A whole page, `root` being the relative path to the root of the site, e.g. "../".
+fn page(title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"he\" dir=\"rtl\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <nav><a href=\"{root}index.html\">ראשי</a></nav>\n{body}</body>\n</html>\n",
+        html_escape(title)
+    )
+}
+
+fn question_file(num: usize) -> String {
+    format!("{num:04}.html")
+}
+
+/// A list of links to `questions`, from a page in a directory next to "questions".
+fn question_list<'a>(questions: impl Iterator<Item = &'a Question>) -> String {
+    let mut list = String::from("<ul>\n");
+    for question in questions {
+        list += &format!(
+            "<li><a href=\"../questions/{}\">{}</a></li>\n",
+            question_file(question.num),
+            html_escape(&question.question)
+        );
+    }
+    list + "</ul>\n"
+}
+
+/// Copy the images of `questions` found in `images` into the site, pointing the questions at the
+/// copies. Returns how many were copied.
+fn copy_images(questions: &mut [Question], images: &Path, out: &Path) -> Result<usize, CliError> {
+    fs::create_dir_all(out.join("images"))?;
+    let mut copied = 0;
+    for question in questions {
+        let Some(name) = question.image_url.as_deref().and_then(file_name) else {
+            continue;
+        };
+        let source = images.join(name);
+        if source.is_file() {
+            fs::copy(&source, out.join("images").join(name))?;
+            question.image_url = Some(format!("../images/{name}"));
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+pub fn run(args: SiteArgs) -> Result<(), CliError> {
+    let mut questions = load_bank(Some(&args.input))?.questions;
+    questions.sort_by_key(|q| q.num);
+    if let Some(images) = &args.images {
+        copy_images(&mut questions, images, &args.out)?;
+    }
+    for dir in ["questions", "categories", "classes"] {
+        fs::create_dir_all(args.out.join(dir))?;
+    }
+
+    for (idx, question) in questions.iter().enumerate() {
+        let mut nav = String::from("<nav>\n");
+        if let Some(prev) = idx.checked_sub(1).map(|prev| &questions[prev]) {
+            nav += &format!(
+                "<a rel=\"prev\" href=\"{}\">הקודמת ({:04})</a>\n",
+                question_file(prev.num),
+                prev.num
+            );
+        }
+        if let Some(next) = questions.get(idx + 1) {
+            nav += &format!(
+                "<a rel=\"next\" href=\"{}\">הבאה ({:04})</a>\n",
+                question_file(next.num),
+                next.num
+            );
+        }
+        nav += &format!(
+            "<a href=\"../categories/{}.html\">{}</a>\n</nav>\n",
+            question.category.code(),
+            html_escape(question.category.as_str_he())
+        );
+        let body = question_html(question) + &nav;
+        fs::write(
+            args.out.join("questions").join(question_file(question.num)),
+            page(&format!("שאלה {:04}", question.num), "../", &body),
+        )?;
+    }
+
+    let mut categories = String::from("<h2>קטגוריות</h2>\n<ul>\n");
+    for category in QuestionCategory::ALL {
+        let matching = questions.iter().filter(|q| q.category == category);
+        categories += &format!(
+            "<li><a href=\"categories/{}.html\">{}</a> ({})</li>\n",
+            category.code(),
+            html_escape(category.as_str_he()),
+            matching.clone().count()
+        );
+        let body =
+            format!("<h1>{}</h1>\n", html_escape(category.as_str_he())) + &question_list(matching);
+        fs::write(
+            args.out
+                .join("categories")
+                .join(format!("{}.html", category.code())),
+            page(category.as_str_he(), "../", &body),
+        )?;
+    }
+    categories += "</ul>\n";
+
+    let mut classes = String::from("<h2>דרגות רישיון</h2>\n<ul>\n");
+    for class in LicenseClass::ALL {
+        let matching = questions
+            .iter()
+            .filter(|q| q.license_classes.contains(&class));
+        classes += &format!(
+            "<li><a href=\"classes/{}.html\">{}</a> ({})</li>\n",
+            class.code(),
+            class.code(),
+            matching.clone().count()
+        );
+        let title = format!("דרגה {}", class.code());
+        let body = format!("<h1>{}</h1>\n", html_escape(&title)) + &question_list(matching);
+        fs::write(
+            args.out
+                .join("classes")
+                .join(format!("{}.html", class.code())),
+            page(&title, "../", &body),
+        )?;
+    }
+    classes += "</ul>\n";
+
+    let index = questions
+        .iter()
+        .map(|q| {
+            json!({
+                "num": q.num,
+                "text": question_body(q),
+                "url": format!("questions/{}", question_file(q.num)),
+            })
+        })
+        .collect::<Vec<_>>();
+    fs::write(
+        args.out.join("search-index.json"),
+        serde_json::to_string(&index).expect("the index is plain data"),
+    )?;
+    let body = format!(
+        "<h1>מאגר שאלות התאוריה</h1>\n<input id=\"search\" type=\"search\" placeholder=\"חיפוש\">\n\
+         <ul id=\"results\"></ul>\n{categories}{classes}<script>{SEARCH_SCRIPT}</script>\n"
+    );
+    fs::write(
+        args.out.join("index.html"),
+        page("מאגר שאלות התאוריה", "", &body),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Counting the questions of a bank, e.g. to see how large a custom exam can be.
+use std::path::PathBuf;
+
+use clap::Args;
+use theory_test_parser::question_parser::{BankStats, LicenseClass};
+
+use crate::{CliError, load_bank, parse_class};
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// The xlsx or TOML file of the bank.
+    input: PathBuf,
+    /// Only count the questions of this license class.
+    #[arg(long, value_parser = parse_class)]
+    class: Option<LicenseClass>,
+    /// Print the stats as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+/// The stats as a table of labels and counts, the counts aligned to the right.
+fn format_stats(stats: &BankStats, class: Option<LicenseClass>) -> String {
+    let percent = |count: usize| match stats.class_total {
+        0 => 0.0,
+        total => count as f64 * 100.0 / total as f64,
+    };
+    let mut rows: Vec<(String, String)> = vec![("Questions".to_string(), stats.total.to_string())];
+    if let Some(class) = class {
+        rows.push((
+            format!("Class {}", class.code()),
+            stats.class_total.to_string(),
+        ));
+    }
+    rows.push(("With an image".to_string(), stats.with_image.to_string()));
+    rows.push((
+        "Image coverage".to_string(),
+        format!("{:.1}%", percent(stats.with_image)),
+    ));
+    rows.push(("By category:".to_string(), String::new()));
+    rows.extend(
+        stats
+            .per_category
+            .iter()
+            .map(|(category, count)| (format!("  {}", category.code()), count.to_string())),
+    );
+    rows.push(("By class:".to_string(), String::new()));
+    rows.extend(
+        stats
+            .per_class
+            .iter()
+            .map(|(class, count)| (format!("  {}", class.code()), count.to_string())),
+    );
+    rows.push(("By number of answers:".to_string(), String::new()));
+    rows.extend(
+        stats
+            .answer_counts
+            .iter()
+            .map(|(answers, count)| (format!("  {answers}"), count.to_string())),
+    );
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let count_width = rows.iter().map(|(_, count)| count.len()).max().unwrap_or(0);
+    rows.into_iter()
+        .map(|(label, count)| {
+            if count.is_empty() {
+                format!("{label}\n")
+            } else {
+                format!("{label:<label_width$}  {count:>count_width$}\n")
+            }
+        })
+        .collect()
+}
+
+pub fn run(args: StatsArgs) -> Result<(), CliError> {
+    let bank = load_bank(Some(&args.input))?;
+    let stats = bank.pool_stats(args.class);
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&stats).expect("stats are always serializable")
+        );
+    } else {
+        print!("{}", format_stats(&stats, args.class));
+    }
+    Ok(())
+}
@@ -0,0 +1,372 @@
+//! The routes of the sync server. Every route needs one of the tokens of the server, each token
+//! being an account of its own.
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::{
+    merge::MergeError,
+    store::{Device, Store, SyncError},
+};
+
+/// The header naming the device syncing, e.g. "phone".
+const DEVICE_HEADER: &str = "x-device";
+
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("a valid token is required")]
+    Unauthorized,
+    #[error("no backup was synced yet")]
+    NotFound,
+    #[error("{0}")]
+    Merge(#[from] MergeError),
+    #[error("the body isn't JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("storage error: {0}")]
+    Store(#[from] rusqlite::Error),
+}
+
+impl From<SyncError> for ApiError {
+    fn from(error: SyncError) -> Self {
+        match error {
+            SyncError::Merge(error) => ApiError::Merge(error),
+            SyncError::Store(error) => ApiError::Store(error),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Merge(MergeError::OtherBank) => StatusCode::CONFLICT,
+            ApiError::Merge(MergeError::NotABackup) | ApiError::Json(_) => StatusCode::BAD_REQUEST,
+            ApiError::Store(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = ErrorBody {
+            error: self.to_string(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+pub struct Server {
+    store: Store,
+    /// The accounts, by the digests of their tokens, so the store doesn't keep the tokens.
+    accounts: Vec<String>,
+}
+
+fn digest(token: &str) -> String {
+    Sha256::digest(token)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+impl Server {
+    pub fn new(store: Store, tokens: &[String]) -> Self {
+        Self {
+            store,
+            accounts: tokens.iter().map(|token| digest(token)).collect(),
+        }
+    }
+
+    /// The account of the bearer token of a request.
+    fn account(&self, headers: &HeaderMap) -> Result<String, ApiError> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(ApiError::Unauthorized)?;
+        let account = digest(token.trim());
+        if self.accounts.contains(&account) {
+            Ok(account)
+        } else {
+            Err(ApiError::Unauthorized)
+        }
+    }
+}
+
+/// The result of a sync: the merged backup, and the records written over different ones.
+#[derive(Serialize)]
+struct Synced {
+    backup: Value,
+    conflicts: Vec<String>,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default()
+}
+
+async fn get_progress(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let account = server.account(&headers)?;
+    server
+        .store
+        .backup(&account)?
+        .map(Json)
+        .ok_or(ApiError::NotFound)
+}
+
+async fn put_progress(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<Synced>, ApiError> {
+    let account = server.account(&headers)?;
+    let device = headers
+        .get(DEVICE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|device| !device.is_empty())
+        .unwrap_or("unknown");
+    let incoming = serde_json::from_str(&body)?;
+    let merged = server
+        .store
+        .merge_and_save(&account, device, incoming, now())?;
+    Ok(Json(Synced {
+        backup: merged.backup,
+        conflicts: merged.conflicts,
+    }))
+}
+
+async fn devices(
+    State(server): State<Arc<Server>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Device>>, ApiError> {
+    let account = server.account(&headers)?;
+    Ok(Json(server.store.devices(&account)?))
+}
+
+pub fn router(server: Server) -> Router {
+    Router::new()
+        .route("/progress", get(get_progress).put(put_progress))
+        .route("/devices", get(devices))
+        .with_state(Arc::new(server))
+}
+
+/// The CORS layer allowing `origins` to sync from the app, or any origin for "*". `None` when
+/// there are no origins.
+pub fn cors_layer(origins: &[String]) -> Result<Option<CorsLayer>, String> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|origin| {
+                HeaderValue::from_str(origin).map_err(|_| format!("invalid origin \"{origin}\""))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(Some(
+        CorsLayer::new()
+            .allow_methods([Method::GET, Method::PUT])
+            .allow_headers([
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                HeaderName::from_static(DEVICE_HEADER),
+            ])
+            .allow_origin(allow_origin),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    const TOKEN: &str = "secret";
+
+    fn router_with_store() -> Router {
+        router(Server::new(
+            Store::in_memory().unwrap(),
+            &[TOKEN.to_string()],
+        ))
+    }
+
+    async fn send(
+        router: &Router,
+        method: Method,
+        uri: &str,
+        token: &str,
+        device: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(DEVICE_HEADER, device)
+            .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn backup(started_at: u64, note: &str) -> Value {
+        json!({
+            "version": 1,
+            "bank_hash": 7,
+            "history": { "attempts": [{
+                "started_at": { "secs": started_at, "nanos": 0 },
+                "graded_at": { "secs": started_at + 1, "nanos": 0 },
+            }] },
+            "preferences": {},
+            "achievements": {},
+            "personal_bests": {},
+            "notes": { "3": note },
+        })
+    }
+
+    #[tokio::test]
+    async fn syncing() {
+        let router = router_with_store();
+        let (status, _) = send(&router, Method::GET, "/progress", TOKEN, "phone", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, synced) = send(
+            &router,
+            Method::PUT,
+            "/progress",
+            TOKEN,
+            "phone",
+            Some(backup(10, "phone")),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(synced["conflicts"], json!([]));
+        let (_, synced) = send(
+            &router,
+            Method::PUT,
+            "/progress",
+            TOKEN,
+            "laptop",
+            Some(backup(20, "laptop")),
+        )
+        .await;
+        assert_eq!(synced["conflicts"], json!(["notes/3"]));
+        let (_, stored) = send(&router, Method::GET, "/progress", TOKEN, "phone", None).await;
+        assert_eq!(stored, synced["backup"]);
+        assert_eq!(stored["history"]["attempts"].as_array().unwrap().len(), 2);
+        assert_eq!(stored["notes"]["3"], "laptop");
+
+        let (_, devices) = send(&router, Method::GET, "/devices", TOKEN, "phone", None).await;
+        let mut names = devices
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|device| device["device"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["laptop", "phone"]);
+
+        let mut other_bank = backup(30, "");
+        other_bank["bank_hash"] = 8.into();
+        let (status, _) = send(
+            &router,
+            Method::PUT,
+            "/progress",
+            TOKEN,
+            "phone",
+            Some(other_bank),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        let (status, _) = send(
+            &router,
+            Method::PUT,
+            "/progress",
+            TOKEN,
+            "phone",
+            Some(json!([])),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_syncs() {
+        let router = router_with_store();
+        let syncs = (0..16)
+            .map(|idx| {
+                let router = router.clone();
+                tokio::spawn(async move {
+                    let device = format!("device-{idx}");
+                    let backup = backup(100 + idx * 10, &device);
+                    send(
+                        &router,
+                        Method::PUT,
+                        "/progress",
+                        TOKEN,
+                        &device,
+                        Some(backup),
+                    )
+                    .await
+                })
+            })
+            .collect::<Vec<_>>();
+        for sync in syncs {
+            let (status, _) = sync.await.unwrap();
+            assert_eq!(status, StatusCode::OK);
+        }
+        // no sync was written over another
+        let (_, stored) = send(&router, Method::GET, "/progress", TOKEN, "phone", None).await;
+        assert_eq!(stored["history"]["attempts"].as_array().unwrap().len(), 16);
+        let (_, devices) = send(&router, Method::GET, "/devices", TOKEN, "phone", None).await;
+        assert_eq!(devices.as_array().unwrap().len(), 16);
+    }
+
+    #[tokio::test]
+    async fn bad_token() {
+        let router = router_with_store();
+        for method in [Method::GET, Method::PUT] {
+            let (status, error) = send(
+                &router,
+                method,
+                "/progress",
+                "wrong",
+                "phone",
+                Some(backup(10, "")),
+            )
+            .await;
+            assert_eq!(status, StatusCode::UNAUTHORIZED);
+            assert_eq!(error["error"], "a valid token is required");
+        }
+        let (status, _) = send(&router, Method::GET, "/devices", "", "phone", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
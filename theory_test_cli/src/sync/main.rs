@@ -0,0 +1,76 @@
+//! A server syncing the progress of the app between devices, for those hosting their own.
+//!
+//! The devices exchange the backups of the app, which the server merges so no device loses the
+//! exams taken on another.
+use std::{net::SocketAddr, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+
+use crate::{api::Server, store::Store};
+
+mod api;
+mod merge;
+mod store;
+
+#[derive(Parser)]
+#[command(
+    version,
+    about = "Syncs the progress of the theory test app between devices"
+)]
+struct Args {
+    /// The SQLite database of the backups, created if missing.
+    #[arg(long, default_value = "sync.sqlite")]
+    db: PathBuf,
+    /// A token allowed to sync, may be repeated. Each token syncs an account of its own.
+    #[arg(
+        long,
+        required = true,
+        env = "SYNC_TOKENS",
+        value_delimiter = ',',
+        hide_env_values = true
+    )]
+    token: Vec<String>,
+    #[arg(long, default_value = "127.0.0.1:8081")]
+    addr: SocketAddr,
+    /// An origin allowed to sync from a browser, may be repeated. "*" allows any.
+    #[arg(long)]
+    cors_origin: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    let store = match Store::open(&args.db) {
+        Ok(store) => store,
+        Err(error) => {
+            eprintln!("error: can't open {}: {error}", args.db.display());
+            return ExitCode::from(3);
+        }
+    };
+    let cors = match api::cors_layer(&args.cors_origin) {
+        Ok(cors) => cors,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut router = api::router(Server::new(store, &args.token));
+    if let Some(cors) = cors {
+        router = router.layer(cors);
+    }
+    let listener = match tokio::net::TcpListener::bind(args.addr).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("error: can't listen on {}: {error}", args.addr);
+            return ExitCode::FAILURE;
+        }
+    };
+    eprintln!("listening on http://{}", args.addr);
+    match axum::serve(listener, router).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
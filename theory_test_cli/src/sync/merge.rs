@@ -0,0 +1,208 @@
+//! Merging the backups of two devices into one.
+//!
+//! The server doesn't share the app's types, it merges the JSON of the backups: the attempts of
+//! the histories are merged by when they started, the keyed records (achievements, personal
+//! bests, notes and tags) are merged key by key with the latest write winning, and everything else,
+//! e.g. the preferences, is taken from the latest write.
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// The records of a backup kept by key, e.g. the notes by question number.
+const KEYED_RECORDS: [&str; 4] = ["achievements", "personal_bests", "notes", "tags"];
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MergeError {
+    #[error("not a backup")]
+    NotABackup,
+    #[error("the backup is of another question bank")]
+    OtherBank,
+}
+
+/// The merge of a backup into the stored one, and the records both had with different values.
+#[derive(Debug, PartialEq)]
+pub struct Merged {
+    pub backup: Value,
+    /// The records written by the incoming backup over different stored ones, e.g. "notes/12".
+    pub conflicts: Vec<String>,
+}
+
+/// A duration as the app serializes it, e.g. `{"secs": 1, "nanos": 0}`, as a sortable pair.
+fn duration(value: Option<&Value>) -> (u64, u64) {
+    let part = |name| {
+        value
+            .and_then(|value| value.get(name))
+            .and_then(Value::as_u64)
+            .unwrap_or_default()
+    };
+    (part("secs"), part("nanos"))
+}
+
+fn attempts(backup: &Value) -> Vec<Value> {
+    backup
+        .pointer("/history/attempts")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// The attempts of both histories, once each, ordered by when they were graded. An attempt in
+/// both with different values, e.g. abandoned on one device and graded on the other, is kept as
+/// it was graded last.
+fn merge_attempts(stored: &Value, incoming: &Value, conflicts: &mut Vec<String>) -> Vec<Value> {
+    let mut merged = BTreeMap::<(u64, u64), Value>::new();
+    for attempt in attempts(stored) {
+        merged.insert(duration(attempt.get("started_at")), attempt);
+    }
+    for attempt in attempts(incoming) {
+        let started_at = duration(attempt.get("started_at"));
+        match merged.get_mut(&started_at) {
+            Some(existing) if *existing != attempt => {
+                conflicts.push(format!("history/{}", started_at.0));
+                if duration(attempt.get("graded_at")) >= duration(existing.get("graded_at")) {
+                    *existing = attempt;
+                }
+            }
+            Some(_) => {}
+            None => {
+                merged.insert(started_at, attempt);
+            }
+        }
+    }
+    let mut merged = merged.into_values().collect::<Vec<_>>();
+    merged.sort_by_key(|attempt| duration(attempt.get("graded_at")));
+    merged
+}
+
+/// The records of `name` of both backups, those of `incoming` winning.
+fn merge_keyed(
+    name: &str,
+    stored: &Value,
+    incoming: &Value,
+    conflicts: &mut Vec<String>,
+) -> Map<String, Value> {
+    let records = |backup: &Value| {
+        backup
+            .get(name)
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let mut merged = records(stored);
+    for (key, value) in records(incoming) {
+        if merged.get(&key).is_some_and(|existing| *existing != value) {
+            conflicts.push(format!("{name}/{key}"));
+        }
+        merged.insert(key, value);
+    }
+    merged
+}
+
+/// Merge `incoming` into the `stored` backup of the same bank.
+pub fn merge(stored: Option<&Value>, incoming: Value) -> Result<Merged, MergeError> {
+    let is_backup = |backup: &Value| {
+        backup.get("version").is_some_and(Value::is_u64)
+            && backup.get("bank_hash").is_some_and(Value::is_u64)
+    };
+    if !is_backup(&incoming) {
+        return Err(MergeError::NotABackup);
+    }
+    let Some(stored) = stored.filter(|stored| is_backup(stored)) else {
+        return Ok(Merged {
+            backup: incoming,
+            conflicts: Vec::new(),
+        });
+    };
+    if stored["bank_hash"] != incoming["bank_hash"] {
+        return Err(MergeError::OtherBank);
+    }
+    let mut conflicts = Vec::new();
+    let attempts = merge_attempts(stored, &incoming, &mut conflicts);
+    let mut backup = incoming;
+    let version = stored["version"].as_u64().max(backup["version"].as_u64());
+    backup["version"] = version.into();
+    backup["history"] = serde_json::json!({ "attempts": attempts });
+    for name in KEYED_RECORDS {
+        let merged = merge_keyed(name, stored, &backup, &mut conflicts);
+        backup[name] = Value::Object(merged);
+    }
+    Ok(Merged { backup, conflicts })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn attempt(started_at: u64, graded_at: u64, abandoned: bool) -> Value {
+        json!({
+            "started_at": { "secs": started_at, "nanos": 0 },
+            "graded_at": { "secs": graded_at, "nanos": 0 },
+            "abandoned": abandoned,
+            "questions": [],
+        })
+    }
+
+    fn backup(attempts: Vec<Value>, notes: Value) -> Value {
+        json!({
+            "version": 1,
+            "bank_hash": 7,
+            "history": { "attempts": attempts },
+            "preferences": { "auto_advance": false },
+            "achievements": {},
+            "personal_bests": {},
+            "notes": notes,
+        })
+    }
+
+    #[test]
+    fn divergent_histories() {
+        let phone = backup(
+            vec![attempt(1, 2, false), attempt(5, 5, true)],
+            json!({ "12": "phone", "13": "only on the phone" }),
+        );
+        let mut laptop = backup(
+            vec![attempt(3, 4, false), attempt(5, 9, false)],
+            json!({ "12": "laptop" }),
+        );
+        laptop["preferences"]["auto_advance"] = true.into();
+        let merged = merge(Some(&phone), laptop).unwrap();
+        assert_eq!(
+            merged.backup["history"]["attempts"],
+            json!([
+                attempt(1, 2, false),
+                attempt(3, 4, false),
+                attempt(5, 9, false)
+            ])
+        );
+        assert_eq!(
+            merged.backup["notes"],
+            json!({ "12": "laptop", "13": "only on the phone" })
+        );
+        assert_eq!(merged.backup["preferences"]["auto_advance"], true);
+        assert_eq!(merged.conflicts, ["history/5", "notes/12"]);
+
+        // merging the result again changes nothing
+        let again = merge(Some(&merged.backup), merged.backup.clone()).unwrap();
+        assert_eq!(again.backup, merged.backup);
+        assert_eq!(again.conflicts, Vec::<String>::new());
+    }
+
+    #[test]
+    fn rejected() {
+        let stored = backup(Vec::new(), json!({}));
+        assert_eq!(
+            merge(Some(&stored), json!({ "history": [] })),
+            Err(MergeError::NotABackup)
+        );
+        let mut other = stored.clone();
+        other["bank_hash"] = 8.into();
+        assert_eq!(
+            merge(Some(&stored), other.clone()),
+            Err(MergeError::OtherBank)
+        );
+        assert_eq!(merge(None, other.clone()).unwrap().backup, other);
+    }
+}
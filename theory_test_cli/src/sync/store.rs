@@ -0,0 +1,124 @@
+//! The backups of the accounts, and when each device of them last synced, in SQLite.
+use std::{path::Path, sync::Mutex};
+
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::merge::{MergeError, Merged, merge};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS backups (
+    account TEXT PRIMARY KEY,
+    backup TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS devices (
+    account TEXT NOT NULL,
+    device TEXT NOT NULL,
+    last_sync INTEGER NOT NULL,
+    PRIMARY KEY (account, device)
+);
+";
+
+/// When a device of an account last synced, in seconds since the epoch.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Device {
+    pub device: String,
+    pub last_sync: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum SyncError {
+    #[error(transparent)]
+    Merge(#[from] MergeError),
+    #[error(transparent)]
+    Store(#[from] rusqlite::Error),
+}
+
+pub struct Store {
+    connection: Mutex<Connection>,
+}
+
+impl Store {
+    fn new(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> rusqlite::Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    pub fn backup(&self, account: &str) -> rusqlite::Result<Option<Value>> {
+        let connection = self.connection.lock().unwrap();
+        let backup = connection
+            .query_row(
+                "SELECT backup FROM backups WHERE account = ?1",
+                [account],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+        // only merged backups are stored, so they always parse
+        Ok(backup.and_then(|backup| serde_json::from_str(&backup).ok()))
+    }
+
+    /// Merge the `incoming` backup of `account`, synced by `device` at `now`, into the stored
+    /// one and store the merge. It is read and written in a single transaction, so backups synced
+    /// at the same time are each merged into the other rather than written over it.
+    pub fn merge_and_save(
+        &self,
+        account: &str,
+        device: &str,
+        incoming: Value,
+        now: u64,
+    ) -> Result<Merged, SyncError> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let stored = transaction
+            .query_row(
+                "SELECT backup FROM backups WHERE account = ?1",
+                [account],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .and_then(|backup| serde_json::from_str::<Value>(&backup).ok());
+        let merged = merge(stored.as_ref(), incoming)?;
+        transaction.execute(
+            "INSERT INTO backups (account, backup) VALUES (?1, ?2)
+             ON CONFLICT (account) DO UPDATE SET backup = excluded.backup",
+            params![account, merged.backup.to_string()],
+        )?;
+        transaction.execute(
+            "INSERT INTO devices (account, device, last_sync) VALUES (?1, ?2, ?3)
+             ON CONFLICT (account, device) DO UPDATE SET last_sync = excluded.last_sync",
+            params![account, device, now],
+        )?;
+        transaction.commit()?;
+        Ok(merged)
+    }
+
+    /// The devices of `account`, the latest to sync first.
+    pub fn devices(&self, account: &str) -> rusqlite::Result<Vec<Device>> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(
+            "SELECT device, last_sync FROM devices WHERE account = ?1
+             ORDER BY last_sync DESC, device",
+        )?;
+        statement
+            .query_map([account], |row| {
+                Ok(Device {
+                    device: row.get(0)?,
+                    last_sync: row.get(1)?,
+                })
+            })?
+            .collect()
+    }
+}
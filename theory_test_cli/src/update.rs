@@ -0,0 +1,167 @@
+//! Tracking the official bank: downloading it, and keeping each version which changed with a
+//! report of what did.
+//!
+//! The store holds the spreadsheet of each version, named by its content hash, and
+//! `latest.json` pointing at the latest one. It is only written once the new version was
+//! downloaded and parsed, so a failure leaves the previous version in place.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use theory_test_parser::{
+    diff::{CompareMode, diff},
+    question_parser::ExamQuestions,
+};
+
+use crate::{CliError, diff::format_diff, http::Fetcher};
+
+const LATEST: &str = "latest.json";
+/// How many characters of the content hash name the files of a version.
+const SHORT_HASH_LEN: usize = 12;
+
+#[derive(Args)]
+pub struct UpdateArgs {
+    /// The url of the official spreadsheet.
+    #[arg(long)]
+    url: String,
+    /// The directory keeping the versions of the bank.
+    #[arg(long, default_value = "data")]
+    store: PathBuf,
+    /// How many times to try the download before giving up.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+    attempts: u32,
+    /// How long to wait before the first retry, in milliseconds. Doubled on each retry.
+    #[arg(long, default_value_t = 1000)]
+    retry_delay_ms: u64,
+}
+
+/// The latest version of the bank in the store.
+#[derive(Debug, Serialize, Deserialize)]
+struct Latest {
+    /// The content hash of the bank, see [`content_hash`].
+    hash: String,
+    /// The spreadsheet, relative to the store.
+    file: String,
+    num_questions: usize,
+    /// When it was downloaded, in seconds since the unix epoch.
+    fetched_at: u64,
+    /// The report of the changes from the previous version, relative to the store. None for the
+    /// first version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    diff: Option<String>,
+}
+
+/// The numbers of the questions which changed between two versions.
+#[derive(Serialize)]
+struct DiffReport<'a> {
+    previous: &'a str,
+    current: &'a str,
+    added: Vec<usize>,
+    removed: Vec<usize>,
+    modified: Vec<usize>,
+}
+
+/// A hash of the questions of `bank`, which unlike a hash of the spreadsheet doesn't change when
+/// only its formatting or metadata do.
+fn content_hash(bank: &ExamQuestions) -> String {
+    let json = serde_json::to_vec(&bank.questions).expect("questions are always serializable");
+    Sha256::digest(json)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn load_latest(store: &Path) -> Result<Option<Latest>, CliError> {
+    let path = store.join(LATEST);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+}
+
+/// Write `contents` to `path` through a temporary file, so readers never see half of it.
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), CliError> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+pub fn run(args: UpdateArgs) -> Result<(), CliError> {
+    let previous = load_latest(&args.store)?;
+    let fetcher = Fetcher::new(args.attempts, Duration::from_millis(args.retry_delay_ms));
+    let xlsx = fetcher
+        .get_bytes(&args.url)
+        .map_err(|error| CliError::Download(format!("{}: {error}", args.url)))?;
+    let bank = ExamQuestions::parse_from_xlsx(&xlsx)?;
+    if bank.questions.is_empty() {
+        return Err(CliError::Filter(
+            "the downloaded spreadsheet has no questions".to_string(),
+        ));
+    }
+    let hash = content_hash(&bank);
+    let short_hash = &hash[..SHORT_HASH_LEN];
+    if previous
+        .as_ref()
+        .is_some_and(|previous| previous.hash == hash)
+    {
+        println!(
+            "Unchanged: {short_hash}, {} questions",
+            bank.questions.len()
+        );
+        return Ok(());
+    }
+
+    // everything is read and checked before the store is written
+    let changes = match &previous {
+        Some(previous) => {
+            let old = ExamQuestions::parse_from_xlsx_file(args.store.join(&previous.file))?;
+            let diff = diff(&old, &bank, CompareMode::Exact);
+            let report = DiffReport {
+                previous: &previous.hash,
+                current: &hash,
+                added: diff.added.clone(),
+                removed: diff.removed.clone(),
+                modified: diff.modified.iter().map(|modified| modified.num).collect(),
+            };
+            let report =
+                serde_json::to_string_pretty(&report).expect("a report is always serializable");
+            Some((
+                format!("diff-{short_hash}.json"),
+                report,
+                format_diff(&diff, false),
+            ))
+        }
+        None => None,
+    };
+    fs::create_dir_all(&args.store)?;
+    let file = format!("bank-{short_hash}.xlsx");
+    write_atomically(&args.store.join(&file), &xlsx)?;
+    if let Some((name, report, _)) = &changes {
+        write_atomically(&args.store.join(name), (report.clone() + "\n").as_bytes())?;
+    }
+    let latest = Latest {
+        hash: hash.clone(),
+        file,
+        num_questions: bank.questions.len(),
+        fetched_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default(),
+        diff: changes.as_ref().map(|(name, _, _)| name.clone()),
+    };
+    let json = serde_json::to_string_pretty(&latest).expect("a pointer is always serializable");
+    write_atomically(&args.store.join(LATEST), (json + "\n").as_bytes())?;
+
+    println!("Updated: {short_hash}, {} questions", bank.questions.len());
+    if let Some((_, _, summary)) = changes {
+        print!("{summary}");
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::{fs, path::PathBuf};
+
+use assert_cmd::Command;
+use theory_test_parser::bundle::{Bundle, decompress};
+
+mod common;
+
+#[test]
+fn bundles_the_bank() {
+    let input = common::workbook("bundle.xlsx", &common::rows());
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bundle.json.br");
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("bundle")
+        .arg(&input)
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains(&format!("xlsx: {} bytes", fs::read(&input).unwrap().len())));
+
+    let bundle = Bundle::from_json(&decompress(&fs::read(&out).unwrap()).unwrap()).unwrap();
+    let (questions, warnings) = bundle.parsed.unwrap();
+    assert_eq!(
+        questions.iter().map(|q| q.num).collect::<Vec<_>>(),
+        common::rows().iter().map(|row| row.num).collect::<Vec<_>>()
+    );
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn checks_the_bundle() {
+    let input = common::workbook("check.xlsx", &common::rows());
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("check.json.br");
+    let bundle = |check: bool| {
+        let mut command = Command::cargo_bin("theory-test-cli").unwrap();
+        command.arg("bundle").arg(&input).arg("--out").arg(&out);
+        if check {
+            command.arg("--check");
+        }
+        command.assert()
+    };
+    bundle(false).success();
+    bundle(true).success();
+
+    let mut rows = common::rows();
+    rows.truncate(1);
+    common::workbook("check.xlsx", &rows);
+    bundle(true).code(2);
+    // the stale bundle is left as it was
+    bundle(true).code(2);
+    bundle(false).success();
+    bundle(true).success();
+}
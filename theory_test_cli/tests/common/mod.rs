@@ -0,0 +1,121 @@
+//! Small workbooks in the format of the official bank, written for each test.
+use std::path::PathBuf;
+
+use rust_xlsxwriter::Workbook;
+
+/// A row of the bank.
+pub struct Row {
+    pub num: usize,
+    pub question: &'static str,
+    pub answers: [&'static str; 4],
+    pub correct: usize,
+    pub category: &'static str,
+    /// The codes of the classes as they appear in the bank, e.g. "«В»".
+    pub classes: &'static [&'static str],
+    pub image: Option<&'static str>,
+}
+
+impl Row {
+    /// The answers cell, in the html of the official bank.
+    fn answers_html(&self) -> String {
+        let answers = self
+            .answers
+            .iter()
+            .enumerate()
+            .map(|(idx, answer)| {
+                if idx == self.correct {
+                    format!(
+                        "<li><span id=\"correctAnswer{:04}\">{answer}</span></li>",
+                        self.num
+                    )
+                } else {
+                    format!("<li><span>{answer}</span></li>")
+                }
+            })
+            .collect::<String>();
+        let image = self
+            .image
+            .map(|src| format!("<img src=\"{src}\" />"))
+            .unwrap_or_default();
+        let classes = self
+            .classes
+            .iter()
+            .map(|class| format!(" {class} |"))
+            .collect::<String>();
+        format!("<div><ul>{answers}</ul>{image}<div><span>|{classes}</span></div></div>")
+    }
+}
+
+/// The rows of the default fixture, one of each category.
+pub fn rows() -> Vec<Row> {
+    vec![
+        Row {
+            num: 101,
+            question: "מהי המהירות המרבית בדרך עירונית?",
+            answers: ["50 קמ\"ש", "70 קמ\"ש", "90 קמ\"ש", "110 קמ\"ש"],
+            correct: 0,
+            category: "חוקי התנועה",
+            classes: &["«C1»", "«В»"],
+            image: None,
+        },
+        Row {
+            num: 102,
+            question: "מה משמעות התמרור?",
+            answers: ["עצור", "תן זכות קדימה", "אין כניסה", "חניה"],
+            correct: 2,
+            category: "תמרורים",
+            classes: &["«В»"],
+            image: Some("https://example.com/102.jpg"),
+        },
+        Row {
+            num: 150,
+            question: "מתי יש להדליק אורות?",
+            answers: ["בלילה", "ביום", "אף פעם", "בחניה"],
+            correct: 0,
+            category: "בטיחות",
+            classes: &["«A»", "«В»"],
+            image: None,
+        },
+        Row {
+            num: 201,
+            question: "מה תפקיד המצמד?",
+            answers: ["בלימה", "ניתוק המנוע מהגלגלים", "היגוי", "תאורה"],
+            correct: 1,
+            category: "הכרת הרכב",
+            classes: &["«C»"],
+            image: None,
+        },
+        Row {
+            num: 202,
+            question: "מה מותר בצומת, לפי הסדר?",
+            answers: ["לעקוף", "לחנות", "לעצור, ואז להמשיך", "לפנות בפרסה"],
+            correct: 2,
+            category: "חוקי התנועה",
+            classes: &["«В»", "«D»"],
+            image: None,
+        },
+    ]
+}
+
+/// Write `rows` to a workbook named `name` in the temporary directory of the tests.
+pub fn workbook(name: &str, rows: &[Row]) -> PathBuf {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    for (col, header) in ["title2", "description4", "category"]
+        .into_iter()
+        .enumerate()
+    {
+        sheet.write_string(0, col as u16, header).unwrap();
+    }
+    for (idx, row) in rows.iter().enumerate() {
+        let line = idx as u32 + 1;
+        sheet
+            .write_string(line, 0, format!("{:04}. {}", row.num, row.question))
+            .unwrap();
+        sheet.write_string(line, 1, row.answers_html()).unwrap();
+        sheet.write_string(line, 2, row.category).unwrap();
+    }
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    workbook.save(&path).unwrap();
+    path
+}
@@ -0,0 +1,84 @@
+use assert_cmd::Command;
+
+mod common;
+
+/// The default fixture with 0202 reworded and 0203 added.
+fn updated_rows() -> Vec<common::Row> {
+    let mut rows = common::rows();
+    rows[4].question = "מה מותר בצומת?";
+    rows.push(common::Row {
+        num: 203,
+        question: "מהו מרחק הבלימה?",
+        answers: ["קצר", "ארוך", "תלוי במהירות", "אפס"],
+        correct: 2,
+        category: "בטיחות",
+        classes: &["«В»"],
+        image: None,
+    });
+    rows
+}
+
+fn diff(old: &[common::Row], new: &[common::Row], name: &str, args: &[&str]) -> Command {
+    let old = common::workbook(&format!("{name}-old.xlsx"), old);
+    let new = common::workbook(&format!("{name}-new.xlsx"), new);
+    let mut cmd = Command::cargo_bin("theory-test-cli").unwrap();
+    cmd.arg("diff").arg(old).arg(new).args(args);
+    cmd
+}
+
+#[test]
+fn differences() {
+    diff(&common::rows(), &updated_rows(), "differences", &[])
+        .assert()
+        .code(2)
+        .stdout("Added: 0203\nRemoved: none\nModified: 0202\n");
+}
+
+#[test]
+fn texts() {
+    diff(&common::rows(), &updated_rows(), "texts", &["--show-text"])
+        .assert()
+        .code(2)
+        .stdout(
+            "Added: 0203\nRemoved: none\nModified: 0202\n\n\
+             - 0202. מה מותר בצומת, לפי הסדר?\n\
+             + 0202. מה מותר בצומת?\n",
+        );
+}
+
+#[test]
+fn json() {
+    let output = diff(&updated_rows(), &common::rows(), "json", &["--json"])
+        .assert()
+        .code(2)
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(r#"{"added":[],"removed":[203],"modified":[{"num":202,"#));
+}
+
+#[test]
+fn identical() {
+    diff(&common::rows(), &common::rows(), "identical", &[])
+        .assert()
+        .success()
+        .stdout("Added: none\nRemoved: none\nModified: none\n");
+}
+
+#[test]
+fn whitespace() {
+    let mut rows = common::rows();
+    rows[0].question = "מהי  המהירות המרבית בדרך עירונית? ";
+    diff(&common::rows(), &rows, "whitespace", &[])
+        .assert()
+        .code(2);
+    diff(
+        &common::rows(),
+        &rows,
+        "whitespace",
+        &["--ignore-whitespace"],
+    )
+    .assert()
+    .success();
+}
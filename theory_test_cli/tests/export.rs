@@ -0,0 +1,135 @@
+use assert_cmd::Command;
+
+mod common;
+
+fn export(args: &[&str]) -> Command {
+    let input = common::workbook("export.xlsx", &common::rows());
+    let mut cmd = Command::cargo_bin("theory-test-cli").unwrap();
+    cmd.arg("export").arg(input).args(args);
+    cmd
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+}
+
+#[test]
+fn json_snapshot() {
+    assert_eq!(
+        stdout(&mut export(&[
+            "--format", "json", "--pretty", "--class", "B"
+        ])),
+        include_str!("snapshots/export.json")
+    );
+}
+
+#[test]
+fn csv_snapshot() {
+    assert_eq!(
+        stdout(&mut export(&["--format", "csv"])),
+        include_str!("snapshots/export.csv")
+    );
+}
+
+#[test]
+fn filters() {
+    let csv = stdout(&mut export(&[
+        "--format",
+        "csv",
+        "--nums",
+        "100-199",
+        "--category",
+        "traffic_laws",
+    ]));
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.lines().nth(1).unwrap().starts_with("101,"));
+}
+
+#[test]
+fn writes_to_a_file() {
+    let out = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("export.md");
+    export(&["--format", "markdown", "--out"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("");
+    assert!(
+        std::fs::read_to_string(out)
+            .unwrap()
+            .starts_with("## 0101.")
+    );
+}
+
+#[test]
+fn errors() {
+    export(&["--format", "gift", "--nums", "900-999"])
+        .assert()
+        .failure()
+        .code(4);
+    let mut broken = common::rows();
+    broken[2].category = "לא קיימת";
+    let input = common::workbook("broken.xlsx", &broken);
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args(["export", "--format", "json"])
+        .arg(input)
+        .assert()
+        .failure()
+        .code(3)
+        .get_output()
+        .stderr
+        .clone();
+    // the header is row 1, the third question row 4
+    assert!(
+        String::from_utf8(output)
+            .unwrap()
+            .contains("Row 4: unknown category")
+    );
+}
+
+#[test]
+fn toml_banks() {
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let input = dir.join("bank.toml");
+    std::fs::write(
+        &input,
+        r#"
+[[question]]
+num = 5001
+text = "question"
+answers = ["a", "b", "c"]
+correct = 2
+category = "safety"
+classes = ["B"]
+"#,
+    )
+    .unwrap();
+    let csv = stdout(
+        Command::cargo_bin("theory-test-cli")
+            .unwrap()
+            .args(["export", "--format", "csv"])
+            .arg(&input),
+    );
+    assert_eq!(csv.lines().nth(1), Some("5001,question,a,b,c,,2,safety,B,"));
+
+    std::fs::write(&input, "[[question]]\nnum = 1\n").unwrap();
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args(["export", "--format", "csv"])
+        .arg(&input)
+        .assert()
+        .failure()
+        .code(3);
+}
+
+#[cfg(feature = "apkg")]
+#[test]
+fn anki_package() {
+    let out = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("export.apkg");
+    export(&["--format", "apkg", "--subdecks", "--out"])
+        .arg(&out)
+        .assert()
+        .success();
+    assert!(std::fs::read(out).unwrap().starts_with(b"PK"));
+    export(&["--format", "apkg"]).assert().code(4);
+}
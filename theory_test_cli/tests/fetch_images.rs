@@ -0,0 +1,156 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use assert_cmd::Command;
+use mock_server::{MockResponse, MockServer, serve};
+use serde_json::Value;
+
+mod common;
+mod mock_server;
+
+const IMAGE: &[u8] = b"\xFF\xD8 an image of question 102";
+
+/// A fixture written to `name` whose question 102 has its image on `server`.
+fn bank_with_image(name: &str, server: &MockServer) -> PathBuf {
+    let mut rows = common::rows();
+    let row = rows.iter_mut().find(|row| row.num == 102).unwrap();
+    row.image = Some(String::leak(format!("{}/images/102.jpg", server.url)));
+    common::workbook(name, &rows)
+}
+
+/// An empty output directory named `name`.
+fn out_dir(name: &str) -> PathBuf {
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&out);
+    out
+}
+
+fn fetch_images(bank: &Path, out: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("fetch-images")
+        .arg(bank)
+        .arg("--out")
+        .arg(out)
+        .args(["--retry-delay-ms", "1"])
+        .args(args)
+        .assert()
+}
+
+fn manifest(out: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(out.join("manifest.json")).unwrap()).unwrap()
+}
+
+#[test]
+fn retries_after_failures() {
+    // the first two downloads fail
+    let failures = AtomicUsize::new(0);
+    let server = serve(move |method, _| {
+        if method == "GET" && failures.fetch_add(1, Ordering::Relaxed) < 2 {
+            MockResponse::status(503)
+        } else {
+            MockResponse::ok(IMAGE)
+        }
+    });
+    let bank = bank_with_image("fetch_retries.xlsx", &server);
+    let out = out_dir("fetch_retries");
+    fetch_images(&bank, &out, &["--attempts", "3"])
+        .success()
+        .stdout("Downloaded 1, already present 0, failed 0\n");
+    assert_eq!(fs::read(out.join("102.jpg")).unwrap(), IMAGE);
+    assert_eq!(server.count("GET"), 3);
+    let manifest = manifest(&out);
+    assert_eq!(manifest["images"]["102"]["path"], "102.jpg");
+    assert_eq!(manifest["images"]["102"]["size"], IMAGE.len());
+    assert_eq!(manifest["failures"], Value::Array(Vec::new()));
+}
+
+#[test]
+fn gives_up() {
+    let server = serve(|_, _| MockResponse::status(503));
+    let bank = bank_with_image("fetch_gives_up.xlsx", &server);
+    let out = out_dir("fetch_gives_up");
+    fetch_images(&bank, &out, &["--attempts", "2"])
+        .code(2)
+        .stdout("Downloaded 0, already present 0, failed 1\n");
+    assert_eq!(server.count("GET"), 2);
+    let manifest = manifest(&out);
+    assert_eq!(manifest["images"], serde_json::json!({}));
+    assert_eq!(manifest["failures"][0]["num"], 102);
+    assert_eq!(
+        manifest["failures"][0]["error"],
+        "HTTP 503 Service Unavailable"
+    );
+    // client errors aren't retried
+    let server = serve(|_, _| MockResponse::status(404));
+    let bank = bank_with_image("fetch_not_found.xlsx", &server);
+    fetch_images(&bank, &out_dir("fetch_not_found"), &[]).code(2);
+    assert_eq!(server.count("GET"), 1);
+}
+
+#[test]
+fn rerunning_skips_present_images() {
+    let server = serve(|_, _| MockResponse::ok(IMAGE).header("ETag", "\"v1\""));
+    let bank = bank_with_image("fetch_rerun.xlsx", &server);
+    let out = out_dir("fetch_rerun");
+    fetch_images(&bank, &out, &[]).success();
+    assert_eq!(manifest(&out)["images"]["102"]["etag"], "\"v1\"");
+    fetch_images(&bank, &out, &[])
+        .success()
+        .stdout("Downloaded 0, already present 1, failed 0\n");
+    assert_eq!(server.count("GET"), 1);
+    assert_eq!(server.count("HEAD"), 1);
+    assert_eq!(
+        server.requests()[0],
+        ("GET".to_string(), "/images/102.jpg".to_string())
+    );
+
+    fetch_images(&bank, &out, &["--verify"])
+        .success()
+        .stdout("Verified 1 images, 0 problems\n");
+    fs::write(out.join("102.jpg"), b"truncated").unwrap();
+    fetch_images(&bank, &out, &["--verify"])
+        .code(2)
+        .stdout(format!(
+            "changed: 0102 102.jpg is 9 bytes, expected {}\nVerified 1 images, 1 problems\n",
+            IMAGE.len()
+        ));
+    // verifying doesn't download
+    assert_eq!(server.count("GET"), 1);
+}
+
+#[test]
+fn only_the_common_signs() {
+    let server = serve(|_, _| MockResponse::ok(IMAGE));
+    let mut rows = common::rows();
+    for row in &mut rows {
+        if row.num == 101 || row.num == 102 {
+            row.image = Some(String::leak(format!(
+                "{}/images/{}.jpg",
+                server.url, row.num
+            )));
+        }
+    }
+    let bank = common::workbook("fetch_signs.xlsx", &rows);
+    let out = out_dir("fetch_signs");
+    // 101 is a traffic law question with an image, not a sign
+    fetch_images(&bank, &out, &["--signs", "5"])
+        .success()
+        .stdout("Downloaded 1, already present 0, failed 0\n");
+    let manifest = manifest(&out);
+    assert_eq!(
+        manifest["images"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        ["102"]
+    );
+    fetch_images(&bank, &out, &["--signs", "5", "--verify"]).success();
+    fetch_images(&bank, &out_dir("fetch_no_signs"), &["--signs", "0"])
+        .success()
+        .stdout("Downloaded 0, already present 0, failed 0\n");
+}
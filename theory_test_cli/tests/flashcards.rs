@@ -0,0 +1,42 @@
+#![cfg(feature = "pdf")]
+use assert_cmd::Command;
+
+mod common;
+
+#[test]
+fn pdf() {
+    let bank = common::workbook("flashcards.xlsx", &common::rows());
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args(["flashcards", "--columns", "3", "--rows", "3", "--bank"])
+        .arg(bank)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(output.starts_with(b"%PDF-"));
+}
+
+#[test]
+fn no_questions() {
+    let bank = common::workbook("flashcards-empty.xlsx", &common::rows());
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args([
+            "flashcards",
+            "--class",
+            "A",
+            "--category",
+            "road_signs",
+            "--bank",
+        ])
+        .arg(bank)
+        .assert()
+        .code(4);
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args(["flashcards", "--columns", "0"])
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,66 @@
+use std::{fs, path::PathBuf};
+
+use assert_cmd::Command;
+use theory_test_core::{
+    backup::Backup, exam_state::ExamKind, history::History, notes::Notes,
+    personal_best::PersonalBests, preferences::Preferences,
+};
+use theory_test_parser::bundle::content_hash;
+
+mod common;
+
+#[test]
+fn imports_into_a_backup() {
+    let bank = common::workbook("import_csv.xlsx", &common::rows());
+    let bank_hash = content_hash(&fs::read(&bank).unwrap());
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let backup = dir.join("import_csv-backup.json");
+    let empty = Backup::new(
+        bank_hash,
+        "b",
+        History::default(),
+        Preferences::default(),
+        Default::default(),
+        PersonalBests::default(),
+        Notes::default(),
+    );
+    fs::write(&backup, serde_json::to_string(&empty).unwrap()).unwrap();
+    let csv = dir.join("import_csv.csv");
+    fs::write(
+        &csv,
+        "id,date,ok\n101,2026-10-01 09:00,yes\n102,01/10/2026 09:05,no\n999,2026-10-01,yes\n101,,yes\n",
+    )
+    .unwrap();
+    let out = dir.join("import_csv-out.json");
+
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("import-csv")
+        .arg(&csv)
+        .arg("--backup")
+        .arg(&backup)
+        .arg("--bank")
+        .arg(&bank)
+        .args(["--correct-column", "ok", "--time-column", "2"])
+        .args(["--utc-offset", "-1"])
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("columns: number 1, correct ok, time 2"));
+    assert!(output.contains("imported: 2 answers in 1 days"));
+    assert!(output.contains("skipped: 1 rows"));
+    assert!(output.contains("unmatched: 1 questions"));
+
+    let imported = Backup::parse(&fs::read_to_string(&out).unwrap(), bank_hash).unwrap();
+    let attempts = &imported.history().attempts;
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].kind, ExamKind::Imported);
+    assert_eq!(attempts[0].num_correct(), 1);
+    // 09:00 at UTC-1
+    assert_eq!(attempts[0].started_at.as_secs() % 86_400, 10 * 3600);
+}
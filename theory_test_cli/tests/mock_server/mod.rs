@@ -0,0 +1,101 @@
+//! A minimal HTTP server for the tests of the tools which download, answering each request with
+//! a handler and recording it.
+// each test crate uses a part of it
+#![allow(dead_code)]
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &'static str, value: &str) -> Self {
+        self.headers.push((name, value.to_string()));
+        self
+    }
+}
+
+pub struct MockServer {
+    /// The url of the server, without a trailing slash.
+    pub url: String,
+    requests: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl MockServer {
+    /// The method and path of each request so far, in order.
+    pub fn requests(&self) -> Vec<(String, String)> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// How many requests so far had `method`.
+    pub fn count(&self, method: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|(request_method, _)| request_method == method)
+            .count()
+    }
+}
+
+/// Serve on a free local port until the test ends, answering with `handler` given the method
+/// and path of each request.
+pub fn serve(handler: impl Fn(&str, &str) -> MockResponse + Send + 'static) -> MockServer {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            // the rest of the head, requests of the tools have no body
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or_default().to_string();
+            let path = parts.next().unwrap_or_default().to_string();
+            let response = handler(&method, &path);
+            recorded.lock().unwrap().push((method.clone(), path));
+            let mut head = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+                response.status,
+                response.body.len()
+            );
+            for (name, value) in &response.headers {
+                head += &format!("{name}: {value}\r\n");
+            }
+            head += "\r\n";
+            let _ = stream.write_all(head.as_bytes());
+            if method != "HEAD" {
+                let _ = stream.write_all(&response.body);
+            }
+        }
+    });
+    MockServer { url, requests }
+}
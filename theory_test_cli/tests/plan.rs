@@ -0,0 +1,40 @@
+use assert_cmd::Command;
+
+mod common;
+
+#[test]
+fn plans_until_the_exam() {
+    let bank = common::workbook("plan.xlsx", &common::rows());
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("plan")
+        .arg("--bank")
+        .arg(&bank)
+        .args(["--today", "2026-10-17", "--exam-date", "2026-10-19"])
+        .args(["--capacity", "1"])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let ics = String::from_utf8(output.stdout).unwrap();
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains(
+        "DTSTART;VALUE=DATE:20261017\r\nDTEND;VALUE=DATE:20261018\r\nSUMMARY:תיאוריה: שאלה אחת — חוקי התנועה\r\n"
+    ));
+    assert!(unfolded.contains("DTSTART;VALUE=DATE:20261018\r\n"));
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+
+    // the 4 questions of class B don't fit in 2 days
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("2 questions over 2 days until 2026-10-19"));
+    assert!(stderr.contains("warning: 2 questions don't fit before the exam"));
+}
+
+#[test]
+fn rejects_invalid_dates() {
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args(["plan", "--exam-date", "2026-02-30"])
+        .assert()
+        .code(2);
+}
@@ -0,0 +1,69 @@
+use assert_cmd::Command;
+
+fn quiz(args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("theory-test-cli").unwrap();
+    cmd.arg("quiz").args(args);
+    cmd
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+}
+
+#[test]
+fn seeded_quiz_is_reproducible() {
+    let args = ["--num", "3", "--seed", "42", "--answers", "1,2,"];
+    let first = stdout(&mut quiz(&args));
+    assert_eq!(first, stdout(&mut quiz(&args)));
+    assert!(first.contains("Question 3/3"));
+    assert!(first.contains("Answer: none"));
+    assert!(first.contains("Score: "));
+    assert!(first.contains("Seed: 42"));
+}
+
+#[test]
+fn answers_from_stdin() {
+    let piped = stdout(quiz(&["--num", "2", "--seed", "7"]).write_stdin("9\n1\n2\n"));
+    // the invalid answer is asked again
+    assert!(piped.contains("expected a number from 1 to 4"));
+    let given = stdout(&mut quiz(&[
+        "--num",
+        "2",
+        "--seed",
+        "7",
+        "--answers",
+        "1,2",
+    ]));
+    let score = |output: &str| {
+        output
+            .lines()
+            .find(|l| l.starts_with("Score"))
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(score(&piped), score(&given));
+}
+
+#[test]
+fn category_filter() {
+    let output = stdout(&mut quiz(&[
+        "--num",
+        "5",
+        "--seed",
+        "1",
+        "--category",
+        "road_signs",
+        "--answers",
+        "",
+    ]));
+    assert!(output.contains("By category:\n  תמרורים: 0/5\n"));
+}
+
+#[test]
+fn invalid_input_fails() {
+    quiz(&["--category", "signs"]).assert().failure();
+    quiz(&["--num", "1", "--seed", "1", "--answers", "5"])
+        .assert()
+        .failure();
+    quiz(&["--bank", "missing.xlsx"]).assert().failure();
+}
@@ -0,0 +1,95 @@
+use assert_cmd::Command;
+
+mod common;
+
+/// The output of `sample` with `args`, over the fixture written to `name`.
+fn sample(name: &str, args: &[&str]) -> String {
+    let bank = common::workbook(name, &common::rows());
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("sample")
+        .arg("--bank")
+        .arg(bank)
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn reproducible() {
+    let args = ["--num", "3", "--seed", "99", "--answer-key"];
+    assert_eq!(
+        sample("reproducible.xlsx", &args),
+        sample("reproducible.xlsx", &args)
+    );
+    assert_ne!(
+        sample("reproducible.xlsx", &args),
+        sample(
+            "reproducible.xlsx",
+            &["--num", "3", "--seed", "100", "--answer-key"]
+        )
+    );
+    let text = ["--num", "3", "--seed", "99", "--format", "text"];
+    assert_eq!(
+        sample("reproducible.xlsx", &text),
+        sample("reproducible.xlsx", &text)
+    );
+}
+
+#[test]
+fn answer_key_matches_the_questions() {
+    let output = sample(
+        "answer-key.xlsx",
+        &["--num", "3", "--seed", "5", "--answer-key"],
+    );
+    let (questions, key) = output.split_once("## Answer key\n").unwrap();
+    let questions = questions.split("\n## ").skip(1).collect::<Vec<_>>();
+    let key = key
+        .lines()
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(questions.len(), 3);
+    assert_eq!(key.len(), 3);
+    for (idx, (question, key)) in questions.iter().zip(key).enumerate() {
+        let (position, rest) = key.strip_prefix("- ").unwrap().split_once(": ").unwrap();
+        assert_eq!(position, (idx + 1).to_string());
+        let (answer, num) = rest.split_once(" (#").unwrap();
+        let row = common::rows()
+            .into_iter()
+            .find(|row| format!("{:04})", row.num) == num)
+            .unwrap();
+        assert!(question.contains(row.question));
+        let correct = format!("\n{answer}. {}\n", row.answers[row.correct]);
+        assert!(question.contains(&correct), "{question} lacks {correct}");
+    }
+}
+
+#[test]
+fn official_distribution() {
+    let output = sample(
+        "official.xlsx",
+        &["--num", "2", "--seed", "1", "--official-distribution"],
+    );
+    assert_eq!(output.matches("\n## ").count(), 2);
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn pdf() {
+    let bank = common::workbook("pdf.xlsx", &common::rows());
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args(["sample", "--num", "3", "--seed", "99", "--format", "pdf"])
+        .arg("--bank")
+        .arg(bank)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    assert!(output.starts_with(b"%PDF-"));
+}
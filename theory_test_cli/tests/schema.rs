@@ -0,0 +1,50 @@
+#![cfg(feature = "schema")]
+use std::{fs, path::PathBuf};
+
+use assert_cmd::Command;
+use serde_json::Value;
+
+mod common;
+
+fn read_json(path: PathBuf) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[test]
+fn exports_validate_against_the_written_schemas() {
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("schema");
+    let _ = fs::remove_dir_all(&out);
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("schema")
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success();
+    let questions = read_json(out.join("questions.schema.json"));
+    assert_eq!(
+        questions["$id"],
+        "https://usering-around.github.io/theory_test_exam/schema/v1/questions.schema.json"
+    );
+    let result = read_json(out.join("result.schema.json"));
+    assert!(jsonschema::meta::is_valid(&result));
+
+    let input = common::workbook("schema.xlsx", &common::rows());
+    let export = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("export")
+        .arg(input)
+        .args(["--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let export = serde_json::from_slice::<Value>(&export).unwrap();
+    let validator = jsonschema::validator_for(&questions).unwrap();
+    assert!(validator.is_valid(&export));
+    // a question of another shape doesn't
+    let mut other = export.clone();
+    other[0]["answers"] = Value::Null;
+    assert!(!validator.is_valid(&other));
+}
@@ -0,0 +1,86 @@
+use std::{fs, path::PathBuf};
+
+use assert_cmd::Command;
+
+mod common;
+
+/// Generate the site of the fixture into a fresh directory named `name`.
+fn site(name: &str, args: &[&str]) -> PathBuf {
+    let input = common::workbook(&format!("{name}.xlsx"), &common::rows());
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&out);
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("site")
+        .arg(input)
+        .arg("--out")
+        .arg(&out)
+        .args(args)
+        .assert()
+        .success();
+    out
+}
+
+fn read(path: PathBuf) -> String {
+    fs::read_to_string(&path).unwrap_or_else(|_| panic!("{} is missing", path.display()))
+}
+
+#[test]
+fn files_and_links() {
+    let out = site("site", &[]);
+    for num in ["0101", "0102", "0150", "0201", "0202"] {
+        let page = read(out.join("questions").join(format!("{num}.html")));
+        assert!(page.contains("<html lang=\"he\" dir=\"rtl\">"));
+        assert!(page.contains("<style>"));
+        assert!(page.contains("href=\"../index.html\""));
+    }
+    // prev and next by number, the first and last only link one way
+    let first = read(out.join("questions/0101.html"));
+    assert!(!first.contains("rel=\"prev\""));
+    assert!(first.contains("<a rel=\"next\" href=\"0102.html\">"));
+    assert!(first.contains("href=\"../categories/traffic_laws.html\""));
+    let middle = read(out.join("questions/0150.html"));
+    assert!(middle.contains("<a rel=\"prev\" href=\"0102.html\">"));
+    assert!(middle.contains("<a rel=\"next\" href=\"0201.html\">"));
+    let last = read(out.join("questions/0202.html"));
+    assert!(!last.contains("rel=\"next\""));
+    assert!(last.contains("<li class=\"correct\">לעצור, ואז להמשיך</li>"));
+
+    let index = read(out.join("index.html"));
+    for link in [
+        "categories/traffic_laws.html",
+        "categories/road_signs.html",
+        "categories/safety.html",
+        "categories/car_knowledge.html",
+        "classes/B.html",
+        "classes/C1.html",
+    ] {
+        assert!(index.contains(&format!("href=\"{link}\"")), "{link}");
+        assert!(out.join(link).is_file(), "{link}");
+    }
+    let traffic_laws = read(out.join("categories/traffic_laws.html"));
+    assert!(traffic_laws.contains("href=\"../questions/0101.html\""));
+    assert!(traffic_laws.contains("href=\"../questions/0202.html\""));
+    assert!(!traffic_laws.contains("0102.html"));
+    let class_c = read(out.join("classes/C.html"));
+    assert!(class_c.contains("href=\"../questions/0201.html\""));
+    assert!(!class_c.contains("0101.html"));
+
+    let search_index: serde_json::Value =
+        serde_json::from_str(&read(out.join("search-index.json"))).unwrap();
+    let entries = search_index.as_array().unwrap();
+    assert_eq!(entries.len(), 5);
+    assert_eq!(entries[0]["url"], "questions/0101.html");
+    assert_eq!(entries[0]["text"], "מהי המהירות המרבית בדרך עירונית?");
+    assert!(out.join(entries[4]["url"].as_str().unwrap()).is_file());
+}
+
+#[test]
+fn local_images() {
+    let images = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("site-images");
+    fs::create_dir_all(&images).unwrap();
+    fs::write(images.join("102.jpg"), b"jpeg").unwrap();
+    let out = site("site-with-images", &["--images", images.to_str().unwrap()]);
+    assert_eq!(fs::read(out.join("images/102.jpg")).unwrap(), b"jpeg");
+    assert!(read(out.join("questions/0102.html")).contains("<img src=\"../images/102.jpg\""));
+}
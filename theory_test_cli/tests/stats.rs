@@ -0,0 +1,42 @@
+use assert_cmd::Command;
+
+/// The bank the app is built with.
+const BUNDLED_BANK: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../theory_test_parser/test.xlsx"
+);
+
+fn stats(args: &[&str]) -> String {
+    let output = Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .arg("stats")
+        .arg(BUNDLED_BANK)
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn whole_bank() {
+    assert_eq!(stats(&[]), include_str!("snapshots/stats.txt"));
+}
+
+#[test]
+fn class_pool() {
+    assert_eq!(
+        stats(&["--class", "B"]),
+        include_str!("snapshots/stats-b.txt")
+    );
+}
+
+#[test]
+fn json() {
+    assert_eq!(
+        stats(&["--class", "B", "--json"]),
+        include_str!("snapshots/stats-b.json")
+    );
+}
@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use assert_cmd::Command;
+use mock_server::{MockResponse, serve};
+use serde_json::{Value, json};
+
+mod common;
+mod mock_server;
+
+fn update(url: &str, store: &Path) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("theory-test-cli")
+        .unwrap()
+        .args([
+            "update",
+            "--url",
+            url,
+            "--retry-delay-ms",
+            "1",
+            "--attempts",
+            "2",
+        ])
+        .arg("--store")
+        .arg(store)
+        .assert()
+}
+
+fn read_json(path: PathBuf) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+/// The next version of the default fixture: 0101 reworded, 0201 removed and 0203 added.
+fn next_version() -> Vec<common::Row> {
+    let mut rows = common::rows();
+    rows[0].question = "מהי המהירות המרבית המותרת בדרך עירונית?";
+    rows.retain(|row| row.num != 201);
+    rows.push(common::Row {
+        num: 203,
+        question: "מה אורכו של רכב פרטי?",
+        answers: ["2 מטר", "4.5 מטר", "8 מטר", "12 מטר"],
+        correct: 1,
+        category: "הכרת הרכב",
+        classes: &["«В»"],
+        image: None,
+    });
+    rows
+}
+
+#[test]
+fn tracks_changes() {
+    let first = fs::read(common::workbook("update_first.xlsx", &common::rows())).unwrap();
+    let second = fs::read(common::workbook("update_second.xlsx", &next_version())).unwrap();
+    let served = Arc::new(Mutex::new(first));
+    let server = serve({
+        let served = served.clone();
+        move |_, _| MockResponse::ok(served.lock().unwrap().clone())
+    });
+    let url = format!("{}/bank.xlsx", server.url);
+    let store = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("update_store");
+    let _ = fs::remove_dir_all(&store);
+
+    update(&url, &store).success();
+    let latest = read_json(store.join("latest.json"));
+    let first_hash = latest["hash"].as_str().unwrap().to_string();
+    assert_eq!(latest["num_questions"], 5);
+    assert_eq!(latest["diff"], Value::Null);
+    assert!(store.join(latest["file"].as_str().unwrap()).is_file());
+    update(&url, &store)
+        .success()
+        .stdout(format!("Unchanged: {}, 5 questions\n", &first_hash[..12]));
+
+    *served.lock().unwrap() = second;
+    let output = update(&url, &store).success().get_output().stdout.clone();
+    let latest = read_json(store.join("latest.json"));
+    let second_hash = latest["hash"].as_str().unwrap();
+    assert_ne!(second_hash, first_hash);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        format!(
+            "Updated: {}, 5 questions\nAdded: 0203\nRemoved: 0201\nModified: 0101\n",
+            &second_hash[..12]
+        )
+    );
+    let report = read_json(store.join(latest["diff"].as_str().unwrap()));
+    assert_eq!(
+        report,
+        json!({
+            "previous": first_hash,
+            "current": second_hash,
+            "added": [203],
+            "removed": [201],
+            "modified": [101],
+        })
+    );
+    // both versions are kept
+    assert_eq!(
+        fs::read_dir(&store)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().extension().unwrap() == "xlsx")
+            .count(),
+        2
+    );
+}
+
+#[test]
+fn failures_keep_the_previous_version() {
+    let bank = fs::read(common::workbook("update_kept.xlsx", &common::rows())).unwrap();
+    let status = Arc::new(Mutex::new(200));
+    let server = serve({
+        let status = status.clone();
+        move |_, path| match *status.lock().unwrap() {
+            200 if path == "/bank.xlsx" => MockResponse::ok(bank.clone()),
+            200 => MockResponse::ok("not a spreadsheet"),
+            status => MockResponse::status(status),
+        }
+    });
+    let store = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("update_kept_store");
+    let _ = fs::remove_dir_all(&store);
+    update(&format!("{}/bank.xlsx", server.url), &store).success();
+    let latest = fs::read_to_string(store.join("latest.json")).unwrap();
+
+    *status.lock().unwrap() = 500;
+    update(&format!("{}/bank.xlsx", server.url), &store)
+        .code(5)
+        .stderr(format!(
+            "error: can't download {}/bank.xlsx: HTTP 500 Internal Server Error\n",
+            server.url
+        ));
+    assert_eq!(server.count("GET"), 3);
+    *status.lock().unwrap() = 200;
+    update(&format!("{}/garbage.xlsx", server.url), &store).code(3);
+    assert_eq!(
+        fs::read_to_string(store.join("latest.json")).unwrap(),
+        latest
+    );
+    assert_eq!(fs::read_dir(&store).unwrap().count(), 2);
+}
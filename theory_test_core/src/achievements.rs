@@ -0,0 +1,318 @@
+//! Badges which are unlocked by milestones in the attempt history.
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+use crate::{
+    exam_state::{ExamKind, QuestionOutcome, REAL_EXAM_NUM_QUESTIONS},
+    history::History,
+    time,
+};
+
+const ROAD_SIGNS_TARGET: usize = 100;
+const STREAK_TARGET: usize = 7;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstPassedPractice,
+    PerfectExam,
+    HundredRoadSigns,
+    WeekStreak,
+    WholeBank,
+}
+
+/// Everything the unlock predicates are evaluated against.
+pub struct AchievementContext<'a> {
+    pub history: &'a History,
+    /// The questions which can appear in exams.
+    pub pool: &'a [Question],
+    /// Days are counted in the local time zone, which is this far from UTC.
+    pub utc_offset_secs: i64,
+}
+
+/// How far an achievement is from being unlocked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Progress {
+    pub current: usize,
+    pub target: usize,
+}
+
+impl Progress {
+    fn new(current: usize, target: usize) -> Self {
+        Self {
+            current: current.min(target),
+            target,
+        }
+    }
+
+    fn done(done: bool) -> Self {
+        Self::new(done.into(), 1)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current >= self.target
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current as f32 / self.target.max(1) as f32
+    }
+}
+
+/// The longest run of consecutive local days with a graded exam.
+fn longest_streak(history: &History, utc_offset_secs: i64) -> usize {
+    let mut days = history
+        .attempts
+        .iter()
+        .filter(|a| !a.abandoned)
+        .map(|a| time::local_day(a.graded_at, utc_offset_secs))
+        .collect::<Vec<_>>();
+    days.sort_unstable();
+    days.dedup();
+    let mut longest = 0;
+    let mut current = 0;
+    for (i, day) in days.iter().enumerate() {
+        current = if i > 0 && days[i - 1] + 1 == *day {
+            current + 1
+        } else {
+            1
+        };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::FirstPassedPractice,
+        Achievement::PerfectExam,
+        Achievement::HundredRoadSigns,
+        Achievement::WeekStreak,
+        Achievement::WholeBank,
+    ];
+
+    pub fn title_he(&self) -> &'static str {
+        match self {
+            Achievement::FirstPassedPractice => "צעד ראשון",
+            Achievement::PerfectExam => "מושלם",
+            Achievement::HundredRoadSigns => "מומחה תמרורים",
+            Achievement::WeekStreak => "שבוע ברצף",
+            Achievement::WholeBank => "כל המאגר",
+        }
+    }
+
+    pub fn description_he(&self) -> &'static str {
+        match self {
+            Achievement::FirstPassedPractice => "עבור מבחן תרגול",
+            Achievement::PerfectExam => "ענה נכון על כל 30 השאלות של מבחן",
+            Achievement::HundredRoadSigns => "ענה על 100 שאלות תמרורים",
+            Achievement::WeekStreak => "בדוק מבחן 7 ימים ברצף",
+            Achievement::WholeBank => "ראה כל שאלה במאגר לפחות פעם אחת",
+        }
+    }
+
+    pub fn progress(&self, context: &AchievementContext) -> Progress {
+        let history = context.history;
+        match self {
+            Achievement::FirstPassedPractice => Progress::done(
+                history
+                    .attempts
+                    .iter()
+                    .any(|a| a.kind == ExamKind::Practice && a.passed()),
+            ),
+            // drills and imported attempts of 30 questions aren't exams
+            Achievement::PerfectExam => Progress::done(history.attempts.iter().any(|a| {
+                matches!(a.kind, ExamKind::Real | ExamKind::Practice)
+                    && a.questions.len() == REAL_EXAM_NUM_QUESTIONS
+                    && a.num_correct() == REAL_EXAM_NUM_QUESTIONS
+            })),
+            Achievement::HundredRoadSigns => {
+                let road_signs = context
+                    .pool
+                    .iter()
+                    .filter(|q| q.category == QuestionCategory::RoadSigns)
+                    .map(|q| q.num)
+                    .collect::<HashSet<_>>();
+                let answered = history
+                    .question_attempts()
+                    .filter(|q| q.outcome != QuestionOutcome::Unanswered)
+                    .filter(|q| road_signs.contains(&q.num))
+                    .count();
+                Progress::new(answered, ROAD_SIGNS_TARGET)
+            }
+            Achievement::WeekStreak => Progress::new(
+                longest_streak(history, context.utc_offset_secs),
+                STREAK_TARGET,
+            ),
+            Achievement::WholeBank => {
+                let seen = history.seen();
+                let covered = context
+                    .pool
+                    .iter()
+                    .filter(|q| seen.contains(&q.num))
+                    .count();
+                // an empty pool can't be covered
+                Progress::new(covered, context.pool.len().max(1))
+            }
+        }
+    }
+}
+
+/// The achievements which were unlocked, with when they were, as durations since the unix epoch.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Unlocked(pub HashMap<Achievement, Duration>);
+
+impl Unlocked {
+    /// Unlock every achievement whose predicate holds, returning the newly unlocked ones.
+    pub fn update(&mut self, context: &AchievementContext, now: Duration) -> Vec<Achievement> {
+        let mut unlocked = Vec::new();
+        for achievement in Achievement::ALL {
+            if !self.0.contains_key(&achievement) && achievement.progress(context).is_complete() {
+                self.0.insert(achievement, now);
+                unlocked.push(achievement);
+            }
+        }
+        unlocked
+    }
+
+    /// Add the achievements unlocked in `other`, keeping the earliest time each was unlocked.
+    /// Returns the number of achievements which weren't unlocked before.
+    pub fn merge(&mut self, other: Unlocked) -> usize {
+        let mut added = 0;
+        for (achievement, at) in other.0 {
+            match self.0.get_mut(&achievement) {
+                Some(existing) => *existing = (*existing).min(at),
+                None => {
+                    self.0.insert(achievement, at);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::Attempt;
+
+    const DAY: u64 = 86_400;
+
+    fn attempt(graded_at: u64, kind: ExamKind, nums: &[usize], num_correct: usize) -> Attempt {
+        let questions: Vec<_> = nums
+            .iter()
+            .enumerate()
+            .map(|(i, &num)| {
+                let outcome = if i < num_correct {
+                    QuestionOutcome::Correct
+                } else {
+                    QuestionOutcome::Wrong
+                };
+                (num, outcome)
+            })
+            .collect();
+        Attempt {
+            kind,
+            ..Attempt::sample(graded_at, &questions)
+        }
+    }
+
+    fn progress(achievement: Achievement, history: &History, pool: &[Question]) -> Progress {
+        achievement.progress(&AchievementContext {
+            history,
+            pool,
+            utc_offset_secs: 0,
+        })
+    }
+
+    #[test]
+    fn exams() {
+        let nums = (1..=30).collect::<Vec<_>>();
+        let mut history = History::default();
+        history.record(attempt(1, ExamKind::Practice, &nums, 25));
+        history.record(attempt(2, ExamKind::Real, &nums, 26));
+        assert!(!progress(Achievement::FirstPassedPractice, &history, &[]).is_complete());
+        history.record(attempt(3, ExamKind::Practice, &nums, 26));
+        assert!(progress(Achievement::FirstPassedPractice, &history, &[]).is_complete());
+        history.record(attempt(4, ExamKind::Imported, &nums, 30));
+        history.record(attempt(5, ExamKind::Drill, &nums, 30));
+        assert!(!progress(Achievement::PerfectExam, &history, &[]).is_complete());
+        history.record(attempt(6, ExamKind::Real, &nums, 30));
+        assert!(progress(Achievement::PerfectExam, &history, &[]).is_complete());
+    }
+
+    #[test]
+    fn road_signs_and_bank() {
+        let pool = vec![
+            Question {
+                category: QuestionCategory::RoadSigns,
+                ..Question::sample(1, 0)
+            },
+            Question::sample(2, 0),
+            Question {
+                category: QuestionCategory::RoadSigns,
+                ..Question::sample(3, 0)
+            },
+        ];
+        let mut history = History::default();
+        for i in 0..40 {
+            history.record(attempt(i, ExamKind::Practice, &[1, 2], 2));
+        }
+        assert_eq!(
+            progress(Achievement::HundredRoadSigns, &history, &pool),
+            Progress::new(40, 100)
+        );
+        assert_eq!(
+            progress(Achievement::WholeBank, &history, &pool),
+            Progress::new(2, 3)
+        );
+        for i in 40..100 {
+            history.record(attempt(i, ExamKind::Practice, &[3], 1));
+        }
+        assert!(progress(Achievement::HundredRoadSigns, &history, &pool).is_complete());
+        assert!(progress(Achievement::WholeBank, &history, &pool).is_complete());
+    }
+
+    #[test]
+    fn streaks() {
+        let mut history = History::default();
+        for day in [0, 1, 2, 4, 5, 6, 7, 8, 9] {
+            history.record(attempt(day * DAY + 100, ExamKind::Practice, &[1], 1));
+        }
+        // two exams on the same day count once
+        history.record(attempt(9 * DAY + 200, ExamKind::Practice, &[1], 1));
+        assert_eq!(longest_streak(&history, 0), 6);
+        history.record(attempt(3 * DAY + 100, ExamKind::Practice, &[1], 1));
+        assert_eq!(longest_streak(&history, 0), 10);
+        // local days, the exam at 00:01:40 UTC was on the previous day at UTC-1
+        assert_eq!(longest_streak(&history, -3600), 10);
+    }
+
+    #[test]
+    fn unlocking() {
+        let nums = (1..=30).collect::<Vec<_>>();
+        let mut history = History::default();
+        history.record(attempt(1, ExamKind::Practice, &nums, 30));
+        let context = AchievementContext {
+            history: &history,
+            pool: &[],
+            utc_offset_secs: 0,
+        };
+        let mut unlocked = Unlocked::default();
+        assert_eq!(
+            unlocked.update(&context, Duration::from_secs(5)),
+            vec![Achievement::FirstPassedPractice, Achievement::PerfectExam]
+        );
+        // already unlocked achievements are not unlocked again
+        assert!(unlocked.update(&context, Duration::from_secs(6)).is_empty());
+        assert_eq!(
+            unlocked.0.get(&Achievement::PerfectExam),
+            Some(&Duration::from_secs(5))
+        );
+    }
+}
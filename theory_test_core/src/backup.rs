@@ -0,0 +1,237 @@
+//! All of the progress in a single versioned bundle, for moving it to another device.
+//!
+//! Importing merges into the existing data rather than replacing it, so importing the same file
+//! twice, or a file exported before the latest exams, loses nothing.
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    achievements::Unlocked, history::History, notes::Notes, personal_best::PersonalBests,
+    preferences::Preferences, tags::Tags,
+};
+
+/// The version of the backup format. Bumped on changes which older versions can't read.
+pub const BACKUP_VERSION: u32 = 1;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ImportError {
+    #[error("הקובץ אינו קובץ גיבוי תקין")]
+    Corrupt,
+    #[error("הקובץ נוצר בגרסה חדשה יותר של האפליקציה (גרסה {0}), יש לעדכן את האפליקציה")]
+    FutureVersion(u32),
+    #[error("הקובץ נוצר עם מאגר שאלות אחר")]
+    OtherBank,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Backup {
+    version: u32,
+    /// The content hash of the bank the question numbers refer to.
+    bank_hash: u64,
+    /// The id of the bundled bank the backup was exported from, for telling backups apart.
+    #[serde(default)]
+    bank_id: String,
+    history: History,
+    preferences: Preferences,
+    achievements: Unlocked,
+    personal_bests: PersonalBests,
+    #[serde(default)]
+    notes: Notes,
+    #[serde(default)]
+    tags: Tags,
+}
+
+/// What an import added to the existing data.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ImportReport {
+    pub attempts: usize,
+    pub achievements: usize,
+    pub personal_bests: usize,
+    pub notes: usize,
+    pub tags: usize,
+}
+
+impl ImportReport {
+    pub fn description_he(&self) -> String {
+        format!(
+            "יובאו {} מבחנים, {} הישגים, {} שיאים אישיים, {} הערות ו־{} תגיות",
+            self.attempts, self.achievements, self.personal_bests, self.notes, self.tags
+        )
+    }
+}
+
+impl Backup {
+    /// A backup of the given data, of the bank with `bank_hash` and `bank_id`.
+    pub fn new(
+        bank_hash: u64,
+        bank_id: &str,
+        history: History,
+        preferences: Preferences,
+        achievements: Unlocked,
+        personal_bests: PersonalBests,
+        notes: Notes,
+    ) -> Self {
+        Self {
+            version: BACKUP_VERSION,
+            bank_hash,
+            bank_id: bank_id.to_string(),
+            history,
+            preferences,
+            achievements,
+            personal_bests,
+            notes,
+            tags: Tags::default(),
+        }
+    }
+
+    /// The backup with the user's tags.
+    pub fn with_tags(self, tags: Tags) -> Self {
+        Self { tags, ..self }
+    }
+
+    /// Parse an exported file, checking it can be imported into the bank with `bank_hash`.
+    pub fn parse(json: &str, bank_hash: u64) -> Result<Self, ImportError> {
+        // the version is checked first, a newer format may not deserialize at all
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|_| ImportError::Corrupt)?;
+        let version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or(ImportError::Corrupt)?;
+        if version > u64::from(BACKUP_VERSION) {
+            return Err(ImportError::FutureVersion(
+                version.try_into().unwrap_or(u32::MAX),
+            ));
+        }
+        let backup: Backup = serde_json::from_value(value).map_err(|_| ImportError::Corrupt)?;
+        if backup.bank_hash != bank_hash {
+            return Err(ImportError::OtherBank);
+        }
+        Ok(backup)
+    }
+
+    /// The history of the backup, e.g. for planning the study on another device.
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// Merge the backup into the existing data. The preferences of the backup replace the
+    /// existing ones, everything else is added to.
+    pub fn merge_into(
+        self,
+        history: &mut History,
+        preferences: &mut Preferences,
+        achievements: &mut Unlocked,
+        personal_bests: &mut PersonalBests,
+        notes: &mut Notes,
+        tags: &mut Tags,
+    ) -> ImportReport {
+        *preferences = self.preferences;
+        ImportReport {
+            attempts: history.merge(self.history),
+            achievements: achievements.merge(self.achievements),
+            personal_bests: personal_bests.merge(self.personal_bests),
+            notes: notes.merge(self.notes),
+            tags: tags.merge(self.tags),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{exam_state::QuestionOutcome, history::Attempt};
+
+    fn attempt(started_at: u64) -> Attempt {
+        Attempt {
+            started_at: Duration::from_secs(started_at),
+            ..Attempt::sample(started_at + 10, &[(1, QuestionOutcome::Correct)])
+        }
+    }
+
+    fn backup(attempts: &[u64]) -> Backup {
+        Backup {
+            version: BACKUP_VERSION,
+            bank_hash: 7,
+            bank_id: "b".to_string(),
+            history: History {
+                attempts: attempts.iter().map(|&at| attempt(at)).collect(),
+            },
+            preferences: Preferences::default(),
+            achievements: Unlocked::default(),
+            personal_bests: PersonalBests::default(),
+            notes: Notes::default(),
+            tags: Tags::default(),
+        }
+    }
+
+    #[test]
+    fn rejected_files() {
+        assert_eq!(
+            Backup::parse("not json", 7).err(),
+            Some(ImportError::Corrupt)
+        );
+        assert_eq!(Backup::parse("{}", 7).err(), Some(ImportError::Corrupt));
+        assert_eq!(
+            Backup::parse(r#"{"version":1,"history":3}"#, 7).err(),
+            Some(ImportError::Corrupt)
+        );
+        assert_eq!(
+            Backup::parse(r#"{"version":2,"something":"new"}"#, 7).err(),
+            Some(ImportError::FutureVersion(2))
+        );
+        let json = serde_json::to_string(&backup(&[1])).unwrap();
+        assert_eq!(Backup::parse(&json, 8).err(), Some(ImportError::OtherBank));
+        assert!(Backup::parse(&json, 7).is_ok());
+    }
+
+    #[test]
+    fn import_merges() {
+        let mut history = backup(&[1, 3]).history;
+        let mut preferences = Preferences::default();
+        let mut achievements = Unlocked::default();
+        let mut bests = PersonalBests::default();
+        let mut notes = Notes::default();
+        let mut tags = Tags::default();
+        let mut exported_tags = Tags::default();
+        exported_tags.add(1, "מבלבל");
+        let json = serde_json::to_string(&backup(&[2, 3]).with_tags(exported_tags)).unwrap();
+        let report = Backup::parse(&json, 7).unwrap().merge_into(
+            &mut history,
+            &mut preferences,
+            &mut achievements,
+            &mut bests,
+            &mut notes,
+            &mut tags,
+        );
+        assert_eq!(report.attempts, 1);
+        assert_eq!(report.tags, 1);
+        assert_eq!(tags.get(1), ["מבלבל"]);
+        assert_eq!(
+            history
+                .attempts
+                .iter()
+                .map(|a| a.started_at.as_secs())
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        // importing again adds nothing
+        let report = Backup::parse(&json, 7).unwrap().merge_into(
+            &mut history,
+            &mut preferences,
+            &mut achievements,
+            &mut bests,
+            &mut notes,
+            &mut tags,
+        );
+        assert_eq!(report.attempts, 0);
+        assert_eq!(report.tags, 0);
+        assert_eq!(history.attempts.len(), 3);
+    }
+}
@@ -0,0 +1,157 @@
+//! The versions of the question bank the app had, to show what changed when a release or a
+//! download brings a new one.
+use serde::{Deserialize, Serialize};
+use theory_test_parser::{
+    diff::{BankDiff, CompareMode, diff},
+    question_parser::{ExamQuestions, Question},
+};
+
+/// A version of the bank as it was kept.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BankSnapshot {
+    pub content_hash: u64,
+    pub num_questions: usize,
+    /// The questions, to compare the next version with. Missing when they couldn't be kept.
+    #[serde(default)]
+    pub questions: Option<Vec<Question>>,
+}
+
+impl BankSnapshot {
+    pub fn new(content_hash: u64, bank: &ExamQuestions) -> Self {
+        Self {
+            content_hash,
+            num_questions: bank.questions.len(),
+            questions: Some(bank.questions.clone()),
+        }
+    }
+}
+
+/// The last version of the bank the app had and the one before it.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BankVersions {
+    pub last: Option<BankSnapshot>,
+    /// The version the last one replaced.
+    pub previous: Option<BankSnapshot>,
+    /// Whether what changed between them was seen.
+    pub seen: bool,
+}
+
+/// What changed between two versions of the bank.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BankUpdate {
+    pub previous_hash: u64,
+    pub current_hash: u64,
+    pub previous_num_questions: usize,
+    pub current_num_questions: usize,
+    /// The questions which changed, `None` when the questions of the previous version weren't
+    /// kept.
+    pub diff: Option<BankDiff>,
+}
+
+impl BankVersions {
+    /// Record that the app has the bank with `content_hash`, returning whether it is another bank
+    /// than last time. The first bank the app had changed nothing.
+    pub fn observe(&mut self, content_hash: u64, bank: &ExamQuestions) -> bool {
+        match &self.last {
+            Some(last) if last.content_hash == content_hash => false,
+            None => {
+                self.last = Some(BankSnapshot::new(content_hash, bank));
+                false
+            }
+            Some(_) => {
+                self.previous = self.last.replace(BankSnapshot::new(content_hash, bank));
+                self.seen = false;
+                true
+            }
+        }
+    }
+
+    /// What changed from the previous version to `current`, the bank with `content_hash`.
+    /// `None` if there is no previous version.
+    pub fn update(&self, content_hash: u64, current: &ExamQuestions) -> Option<BankUpdate> {
+        let previous = self.previous.as_ref()?;
+        Some(BankUpdate {
+            previous_hash: previous.content_hash,
+            current_hash: content_hash,
+            previous_num_questions: previous.num_questions,
+            current_num_questions: current.questions.len(),
+            diff: previous.questions.as_ref().map(|questions| {
+                let previous = ExamQuestions {
+                    questions: questions.clone(),
+                };
+                // a bank re-exported by another spreadsheet program didn't change
+                diff(&previous, current, CompareMode::IgnoreWhitespace)
+            }),
+        })
+    }
+
+    /// Whether there is a change which wasn't seen yet.
+    pub fn unseen(&self) -> bool {
+        self.previous.is_some() && !self.seen
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use theory_test_parser::question_parser::Answers;
+
+    fn bank(nums: &[usize], text: &str) -> ExamQuestions {
+        ExamQuestions {
+            questions: nums
+                .iter()
+                .map(|&num| Question {
+                    question: format!("{num:04}. {text}"),
+                    answers: Answers {
+                        possible_answers: vec!["a".to_string(), "b".to_string()],
+                        correct_answer: 0,
+                    },
+                    ..Question::sample(num, 0)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn observing_new_versions() {
+        let mut versions = BankVersions::default();
+        let first = bank(&[1, 2, 3], "question");
+        assert!(!versions.observe(1, &first));
+        assert!(!versions.observe(1, &first));
+        assert_eq!(versions.update(1, &first), None);
+        assert!(!versions.unseen());
+
+        let mut second = bank(&[2, 3, 4, 5], "question");
+        second.questions[1].answers.correct_answer = 1;
+        assert!(versions.observe(2, &second));
+        assert!(versions.unseen());
+        let update = versions.update(2, &second).unwrap();
+        assert_eq!((update.previous_hash, update.current_hash), (1, 2));
+        assert_eq!(
+            (update.previous_num_questions, update.current_num_questions),
+            (3, 4)
+        );
+        let diff = update.diff.unwrap();
+        assert_eq!(diff.added, [4, 5]);
+        assert_eq!(diff.removed, [1]);
+        assert_eq!(diff.modified.iter().map(|m| m.num).collect::<Vec<_>>(), [3]);
+
+        versions.seen = true;
+        assert!(!versions.observe(2, &second));
+        assert!(!versions.unseen());
+        // the previous version is the one the last replaced
+        assert!(versions.observe(3, &first));
+        assert_eq!(versions.previous.as_ref().unwrap().content_hash, 2);
+        assert!(versions.unseen());
+    }
+
+    #[test]
+    fn without_the_previous_questions() {
+        let json = r#"{"last":{"content_hash":2,"num_questions":4},"previous":{"content_hash":1,"num_questions":3},"seen":false}"#;
+        let versions: BankVersions = serde_json::from_str(json).unwrap();
+        let update = versions.update(2, &bank(&[1, 2, 3, 4], "q")).unwrap();
+        assert_eq!(update.previous_hash, 1);
+        assert_eq!(update.diff, None);
+    }
+}
@@ -0,0 +1,199 @@
+//! Which categories a practice exam draws its questions from: only some categories, or every
+//! category except some.
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+/// What a category chip of the filter is set to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CategoryChoice {
+    Neutral,
+    Include,
+    Exclude,
+}
+
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct CategoryFilter {
+    /// When not empty, only questions of these categories are drawn.
+    pub include: Vec<QuestionCategory>,
+    /// Questions of these categories are never drawn.
+    pub exclude: Vec<QuestionCategory>,
+}
+
+/// Parse a comma separated list of category names, ignoring unknown names.
+fn parse_categories(query: &str) -> Vec<QuestionCategory> {
+    QuestionCategory::ALL
+        .into_iter()
+        .filter(|category| {
+            query
+                .split(',')
+                .any(|name| name.trim() == category.as_str_he())
+        })
+        .collect()
+}
+
+fn categories_query(categories: &[QuestionCategory]) -> String {
+    categories
+        .iter()
+        .map(|category| category.as_str_he())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn categories_description(categories: &[QuestionCategory]) -> String {
+    categories
+        .iter()
+        .map(|category| category.as_str_he())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl CategoryFilter {
+    /// The filter of the `category` and `exclude` route parameters.
+    pub fn from_query(include: &str, exclude: &str) -> Self {
+        Self {
+            include: parse_categories(include),
+            exclude: parse_categories(exclude),
+        }
+    }
+
+    pub fn include_query(&self) -> String {
+        categories_query(&self.include)
+    }
+
+    pub fn exclude_query(&self) -> String {
+        categories_query(&self.exclude)
+    }
+
+    pub fn choice(&self, category: QuestionCategory) -> CategoryChoice {
+        if self.include.contains(&category) {
+            CategoryChoice::Include
+        } else if self.exclude.contains(&category) {
+            CategoryChoice::Exclude
+        } else {
+            CategoryChoice::Neutral
+        }
+    }
+
+    /// Move a category to its next choice: neutral, included, excluded and back to neutral.
+    pub fn cycle(&mut self, category: QuestionCategory) {
+        match self.choice(category) {
+            CategoryChoice::Neutral => self.include.push(category),
+            CategoryChoice::Include => {
+                self.include.retain(|c| *c != category);
+                self.exclude.push(category);
+            }
+            CategoryChoice::Exclude => self.exclude.retain(|c| *c != category),
+        }
+        // keep the order of the categories independent of the order they were clicked in
+        self.include = parse_categories(&self.include_query());
+        self.exclude = parse_categories(&self.exclude_query());
+    }
+
+    /// The categories the filter lets through, as in [`ExamSpec::categories`]: none when it lets
+    /// every category through, and so also when it lets none through.
+    ///
+    /// [`ExamSpec::categories`]: theory_test_parser::exam::ExamSpec::categories
+    pub fn categories(&self) -> Vec<QuestionCategory> {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return Vec::new();
+        }
+        QuestionCategory::ALL
+            .into_iter()
+            .filter(|category| {
+                (self.include.is_empty() || self.include.contains(category))
+                    && !self.exclude.contains(category)
+            })
+            .collect()
+    }
+
+    pub fn matches(&self, question: &Question) -> bool {
+        (self.include.is_empty() || self.include.contains(&question.category))
+            && !self.exclude.contains(&question.category)
+    }
+
+    /// A description of the filter, `None` when it doesn't filter anything.
+    pub fn description_he(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if !self.include.is_empty() {
+            parts.push(format!("רק: {}", categories_description(&self.include)));
+        }
+        if !self.exclude.is_empty() {
+            parts.push(format!("ללא: {}", categories_description(&self.exclude)));
+        }
+        (!parts.is_empty()).then(|| parts.join(" · "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cycling_choices() {
+        let mut filter = CategoryFilter::default();
+        assert_eq!(filter.description_he(), None);
+        filter.cycle(QuestionCategory::Safety);
+        filter.cycle(QuestionCategory::TrafficLaws);
+        assert_eq!(
+            filter.choice(QuestionCategory::Safety),
+            CategoryChoice::Include
+        );
+        assert_eq!(
+            filter.include,
+            vec![QuestionCategory::TrafficLaws, QuestionCategory::Safety]
+        );
+        filter.cycle(QuestionCategory::Safety);
+        assert_eq!(
+            filter.choice(QuestionCategory::Safety),
+            CategoryChoice::Exclude
+        );
+        assert_eq!(filter.include, vec![QuestionCategory::TrafficLaws]);
+        assert_eq!(filter.exclude, vec![QuestionCategory::Safety]);
+        filter.cycle(QuestionCategory::Safety);
+        assert_eq!(
+            filter.choice(QuestionCategory::Safety),
+            CategoryChoice::Neutral
+        );
+        assert!(filter.exclude.is_empty());
+    }
+
+    #[test]
+    fn categories() {
+        assert!(CategoryFilter::default().categories().is_empty());
+        let filter = CategoryFilter {
+            include: vec![QuestionCategory::Safety, QuestionCategory::RoadSigns],
+            exclude: vec![QuestionCategory::RoadSigns],
+        };
+        assert_eq!(filter.categories(), vec![QuestionCategory::Safety]);
+        let filter = CategoryFilter {
+            include: Vec::new(),
+            exclude: vec![QuestionCategory::Safety],
+        };
+        assert_eq!(filter.categories().len(), QuestionCategory::ALL.len() - 1);
+        assert!(!filter.categories().contains(&QuestionCategory::Safety));
+    }
+
+    #[test]
+    fn query_round_trip() {
+        let filter = CategoryFilter {
+            include: Vec::new(),
+            exclude: vec![QuestionCategory::RoadSigns, QuestionCategory::CarKnowledge],
+        };
+        let parsed = CategoryFilter::from_query(&filter.include_query(), &filter.exclude_query());
+        assert_eq!(parsed, filter);
+        // a single category, as in links from before exclusions existed
+        let single = CategoryFilter::from_query(QuestionCategory::Safety.as_str_he(), "");
+        assert_eq!(single.include, vec![QuestionCategory::Safety]);
+        assert_eq!(
+            CategoryFilter::from_query("nonsense", ""),
+            CategoryFilter::default()
+        );
+        assert_eq!(
+            filter.description_he().unwrap(),
+            format!(
+                "ללא: {}, {}",
+                QuestionCategory::RoadSigns.as_str_he(),
+                QuestionCategory::CarKnowledge.as_str_he()
+            )
+        );
+    }
+}
@@ -0,0 +1,448 @@
+//! The results of exams downloaded by students, aggregated for their teacher.
+//!
+//! Each result is a file of its own, which the teacher collects and imports together. The files
+//! are checked against the bank, as the numbers of the questions of another bank are other
+//! questions.
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::{ExamQuestions, QuestionCategory};
+use thiserror::Error;
+
+use crate::{exam_state::QuestionOutcome, history::Attempt};
+
+/// The version of the result format. Bumped on changes which older versions can't read.
+pub const RESULT_VERSION: u32 = 1;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ResultImportError {
+    #[error("הקובץ אינו קובץ תוצאה תקין")]
+    Corrupt,
+    #[error("הקובץ נוצר בגרסה חדשה יותר של האפליקציה (גרסה {0}), יש לעדכן את האפליקציה")]
+    FutureVersion(u32),
+    #[error("הקובץ נוצר עם מאגר שאלות אחר")]
+    OtherBank,
+}
+
+/// A single graded exam of a student, as downloaded for their teacher.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ResultFile {
+    version: u32,
+    /// The content hash of the bank the question numbers refer to.
+    bank_hash: u64,
+    /// The name the student gave, which tells their results apart from the rest of the class.
+    pub student: String,
+    pub attempt: Attempt,
+}
+
+impl ResultFile {
+    pub fn new(bank_hash: u64, student: &str, attempt: Attempt) -> Self {
+        Self {
+            version: RESULT_VERSION,
+            bank_hash,
+            student: student.trim().to_string(),
+            attempt,
+        }
+    }
+
+    /// Parse a downloaded result, checking its questions are of the bank with `bank_hash`.
+    pub fn parse(json: &str, bank_hash: u64) -> Result<Self, ResultImportError> {
+        // the version is checked first, a newer format may not deserialize at all
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|_| ResultImportError::Corrupt)?;
+        let version = value
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or(ResultImportError::Corrupt)?;
+        if version > u64::from(RESULT_VERSION) {
+            return Err(ResultImportError::FutureVersion(
+                version.try_into().unwrap_or(u32::MAX),
+            ));
+        }
+        let result: ResultFile =
+            serde_json::from_value(value).map_err(|_| ResultImportError::Corrupt)?;
+        if result.bank_hash != bank_hash {
+            return Err(ResultImportError::OtherBank);
+        }
+        Ok(result)
+    }
+}
+
+/// How a single student did, over all of their results.
+#[derive(Clone, PartialEq, Debug)]
+pub struct StudentSummary {
+    pub student: String,
+    pub exams: usize,
+    pub passed: usize,
+    /// The questions answered correctly, of `answered`, over all of the exams.
+    pub correct: usize,
+    /// The questions which counted, that is which weren't void.
+    pub answered: usize,
+}
+
+impl StudentSummary {
+    /// The share of the exams which passed, between 0 and 1.
+    pub fn pass_rate(&self) -> f64 {
+        if self.exams == 0 {
+            return 0.0;
+        }
+        self.passed as f64 / self.exams as f64
+    }
+}
+
+/// A question the class answered wrong, or didn't answer.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MissedQuestion {
+    pub num: usize,
+    pub missed: usize,
+    /// How many times it was asked, void times aside.
+    pub asked: usize,
+}
+
+/// The accuracy of the class in a category.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CategoryAverage {
+    pub category: QuestionCategory,
+    /// The mean of the accuracies of the students who were asked questions of the category,
+    /// between 0 and 1, so a student who took more exams doesn't weigh more.
+    pub average: f64,
+    pub students: usize,
+}
+
+/// The results imported by a teacher.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct ClassResults {
+    results: Vec<ResultFile>,
+}
+
+impl ClassResults {
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Add a result, unless the same exam of the same student was already added. Returns
+    /// whether it was added.
+    pub fn add(&mut self, result: ResultFile) -> bool {
+        let duplicate = self.results.iter().any(|r| {
+            r.student == result.student && r.attempt.started_at == result.attempt.started_at
+        });
+        if !duplicate {
+            self.results.push(result);
+        }
+        !duplicate
+    }
+
+    /// Parse a downloaded result and add it, see [`ResultFile::parse`] and [`Self::add`].
+    pub fn import(&mut self, json: &str, bank_hash: u64) -> Result<bool, ResultImportError> {
+        Ok(self.add(ResultFile::parse(json, bank_hash)?))
+    }
+
+    /// The graded exams of each student, by name.
+    fn by_student(&self) -> BTreeMap<&str, Vec<&Attempt>> {
+        let mut students = BTreeMap::<_, Vec<_>>::new();
+        for result in self.results.iter().filter(|r| !r.attempt.abandoned) {
+            students
+                .entry(result.student.as_str())
+                .or_default()
+                .push(&result.attempt);
+        }
+        students
+    }
+
+    /// How each student did, ordered by name.
+    pub fn students(&self) -> Vec<StudentSummary> {
+        self.by_student()
+            .into_iter()
+            .map(|(student, attempts)| {
+                let counted = attempts
+                    .iter()
+                    .flat_map(|a| &a.questions)
+                    .filter(|q| q.outcome != QuestionOutcome::Void);
+                let (answered, correct) = counted.fold((0, 0), |(answered, correct), q| {
+                    (
+                        answered + 1,
+                        correct + usize::from(q.outcome == QuestionOutcome::Correct),
+                    )
+                });
+                StudentSummary {
+                    student: student.to_string(),
+                    exams: attempts.len(),
+                    passed: attempts.iter().filter(|a| a.passed()).count(),
+                    correct,
+                    answered,
+                }
+            })
+            .collect()
+    }
+
+    /// The `limit` questions missed most often across the class, the most missed first and the
+    /// lower numbers first among equals. Questions which were never missed are left out.
+    pub fn most_missed(&self, limit: usize) -> Vec<MissedQuestion> {
+        let mut questions = HashMap::<usize, MissedQuestion>::new();
+        for question in self
+            .by_student()
+            .into_values()
+            .flatten()
+            .flat_map(|a| &a.questions)
+            .filter(|q| q.outcome != QuestionOutcome::Void)
+        {
+            let entry = questions.entry(question.num).or_insert(MissedQuestion {
+                num: question.num,
+                missed: 0,
+                asked: 0,
+            });
+            entry.asked += 1;
+            entry.missed += usize::from(question.outcome != QuestionOutcome::Correct);
+        }
+        let mut missed = questions
+            .into_values()
+            .filter(|q| q.missed > 0)
+            .collect::<Vec<_>>();
+        missed.sort_by_key(|q| (std::cmp::Reverse(q.missed), q.num));
+        missed.truncate(limit);
+        missed
+    }
+
+    /// The average accuracy of the class in each category with questions asked, in the order of
+    /// [`QuestionCategory::ALL`]. Questions which aren't in `bank` are left out.
+    pub fn category_averages(&self, bank: &ExamQuestions) -> Vec<CategoryAverage> {
+        let categories = bank
+            .questions
+            .iter()
+            .map(|q| (q.num, q.category))
+            .collect::<HashMap<_, _>>();
+        let students = self.by_student();
+        QuestionCategory::ALL
+            .into_iter()
+            .filter_map(|category| {
+                let accuracies = students
+                    .values()
+                    .filter_map(|attempts| {
+                        let (total, correct) = attempts
+                            .iter()
+                            .flat_map(|a| &a.questions)
+                            .filter(|q| {
+                                q.outcome != QuestionOutcome::Void
+                                    && categories.get(&q.num) == Some(&category)
+                            })
+                            .fold((0, 0), |(total, correct), q| {
+                                (
+                                    total + 1,
+                                    correct + usize::from(q.outcome == QuestionOutcome::Correct),
+                                )
+                            });
+                        (total > 0).then(|| correct as f64 / total as f64)
+                    })
+                    .collect::<Vec<_>>();
+                (!accuracies.is_empty()).then(|| CategoryAverage {
+                    category,
+                    average: accuracies.iter().sum::<f64>() / accuracies.len() as f64,
+                    students: accuracies.len(),
+                })
+            })
+            .collect()
+    }
+
+    /// The aggregate as a CSV table, a row for each student, each missed question and each
+    /// category. `count` and `total` are the exams passed of those taken for students, the times
+    /// missed of those asked for questions, and the students asked questions of the category of
+    /// the whole class for categories, whose `percent` is the class average.
+    pub fn to_csv(&self, bank: &ExamQuestions) -> String {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let mut rows = vec![[
+            "section".to_string(),
+            "name".to_string(),
+            "count".to_string(),
+            "total".to_string(),
+            "percent".to_string(),
+        ]];
+        let percent = |share: f64| format!("{:.0}", share * 100.0);
+        for student in self.students() {
+            rows.push([
+                "student".to_string(),
+                student.student.clone(),
+                student.passed.to_string(),
+                student.exams.to_string(),
+                percent(student.pass_rate()),
+            ]);
+        }
+        for question in self.most_missed(usize::MAX) {
+            rows.push([
+                "question".to_string(),
+                format!("{:04}", question.num),
+                question.missed.to_string(),
+                question.asked.to_string(),
+                percent(question.missed as f64 / question.asked as f64),
+            ]);
+        }
+        let num_students = self.by_student().len();
+        for category in self.category_averages(bank) {
+            rows.push([
+                "category".to_string(),
+                category.category.code().to_string(),
+                category.students.to_string(),
+                num_students.to_string(),
+                percent(category.average),
+            ]);
+        }
+        for row in rows {
+            // writing into memory can't fail
+            writer.write_record(&row).expect("writes to a vec");
+        }
+        String::from_utf8(writer.into_inner().expect("writes to a vec")).expect("written as utf-8")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    use theory_test_parser::question_parser::{Answers, Question};
+
+    const BANK_HASH: u64 = 42;
+
+    fn bank() -> ExamQuestions {
+        let question = |num, category| Question {
+            answers: Answers {
+                possible_answers: vec!["a".to_string(), "b".to_string()],
+                correct_answer: 0,
+            },
+            category,
+            ..Question::sample(num, 0)
+        };
+        ExamQuestions {
+            questions: vec![
+                question(1, QuestionCategory::TrafficLaws),
+                question(2, QuestionCategory::TrafficLaws),
+                question(3, QuestionCategory::RoadSigns),
+                question(4, QuestionCategory::Safety),
+            ],
+        }
+    }
+
+    /// The result of an exam of `student` started at `started_at`, with `outcomes` of the
+    /// questions numbered from 1.
+    fn result(student: &str, started_at: u64, outcomes: &[QuestionOutcome]) -> String {
+        let outcomes: Vec<_> = (1..).zip(outcomes.iter().copied()).collect();
+        let attempt = Attempt {
+            started_at: Duration::from_secs(started_at),
+            ..Attempt::sample(started_at + 60, &outcomes)
+        };
+        serde_json::to_string(&ResultFile::new(BANK_HASH, student, attempt)).unwrap()
+    }
+
+    use QuestionOutcome::{Correct, Unanswered, Void, Wrong};
+
+    fn class() -> ClassResults {
+        let mut class = ClassResults::default();
+        for json in [
+            result("דנה", 1, &[Correct, Correct, Correct, Correct]),
+            result("דנה", 2, &[Correct, Wrong, Correct, Correct]),
+            result("יואב", 3, &[Wrong, Wrong, Correct, Void]),
+            result("אבי", 4, &[Correct, Unanswered, Wrong, Correct]),
+        ] {
+            assert_eq!(class.import(&json, BANK_HASH), Ok(true));
+        }
+        class
+    }
+
+    #[test]
+    fn imports() {
+        let mut class = class();
+        assert_eq!(class.len(), 4);
+        // the same file again, and the same exam under a name with spaces around it
+        let again = result(" דנה ", 1, &[Correct; 4]);
+        assert_eq!(class.import(&again, BANK_HASH), Ok(false));
+        // the same start time of another student is another exam
+        let other = result("נועה", 1, &[Correct; 4]);
+        assert_eq!(class.import(&other, BANK_HASH), Ok(true));
+
+        assert_eq!(
+            class.import(&result("רון", 9, &[Correct]), BANK_HASH + 1),
+            Err(ResultImportError::OtherBank)
+        );
+        assert_eq!(
+            class.import("{\"version\": 1}", BANK_HASH),
+            Err(ResultImportError::Corrupt)
+        );
+        assert_eq!(
+            class.import("not json", BANK_HASH),
+            Err(ResultImportError::Corrupt)
+        );
+        let future = result("רון", 9, &[Correct]).replacen("\"version\":1", "\"version\":2", 1);
+        assert_eq!(
+            class.import(&future, BANK_HASH),
+            Err(ResultImportError::FutureVersion(2))
+        );
+        // the rejected files added nothing
+        assert_eq!(class.len(), 5);
+    }
+
+    #[test]
+    fn students() {
+        let students = class().students();
+        let names = students
+            .iter()
+            .map(|s| s.student.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["אבי", "דנה", "יואב"]);
+        let dana = &students[1];
+        assert_eq!((dana.exams, dana.correct, dana.answered), (2, 7, 8));
+        // an exam of 4 questions allows no mistakes
+        assert_eq!(dana.passed, 1);
+        assert_eq!(dana.pass_rate(), 0.5);
+        // the void question doesn't count
+        assert_eq!((students[2].correct, students[2].answered), (1, 3));
+        assert_eq!(ClassResults::default().students(), []);
+    }
+
+    #[test]
+    fn most_missed() {
+        let missed = class().most_missed(10);
+        let missed = missed
+            .iter()
+            .map(|q| (q.num, q.missed, q.asked))
+            .collect::<Vec<_>>();
+        // question 4 was missed by no one, its void outcome isn't asked
+        assert_eq!(missed, [(2, 3, 4), (1, 1, 4), (3, 1, 4)]);
+        assert_eq!(class().most_missed(1).len(), 1);
+    }
+
+    #[test]
+    fn category_averages() {
+        let averages = class().category_averages(&bank());
+        let averages = averages
+            .iter()
+            .map(|c| (c.category, (c.average * 100.0).round(), c.students))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            averages,
+            [
+                // אבי 1/2, דנה 3/4, יואב 0/2
+                (QuestionCategory::TrafficLaws, 42.0, 3),
+                // אבי 0/1, דנה 2/2, יואב 1/1
+                (QuestionCategory::RoadSigns, 67.0, 3),
+                // יואב's is void
+                (QuestionCategory::Safety, 100.0, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let csv = class().to_csv(&bank());
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "section,name,count,total,percent");
+        assert_eq!(lines[1], "student,אבי,0,1,0");
+        assert_eq!(lines[2], "student,דנה,1,2,50");
+        assert_eq!(lines[4], "question,0002,3,4,75");
+        assert_eq!(lines.last(), Some(&"category,safety,2,3,100"));
+        assert_eq!(lines.len(), 1 + 3 + 3 + 3);
+    }
+}
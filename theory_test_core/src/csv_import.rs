@@ -0,0 +1,510 @@
+//! Answers given in another app, imported from a CSV file with a row for each answered question.
+//!
+//! Such files differ in their columns and in how they write dates and answers, so the columns
+//! are given by a [`CsvMapping`] and the values are parsed leniently. The answers of each day
+//! become a single attempt of kind [`ExamKind::Imported`], identified by the time of its first
+//! answer, so importing the same file twice adds nothing.
+use std::{fmt, io::Read, str::FromStr, time::Duration};
+
+use theory_test_parser::question_parser::ExamQuestions;
+use thiserror::Error;
+
+use crate::{
+    exam_state::{ExamKind, PassThreshold, QuestionOutcome},
+    history::{Attempt, History, QuestionAttempt},
+    time,
+};
+
+/// A column of the file, by its position or by its name in the header.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Column {
+    /// 0-based.
+    Index(usize),
+    Header(String),
+}
+
+impl FromStr for Column {
+    type Err = std::convert::Infallible;
+
+    /// A 1-based position, e.g. "2", or else the name of a column.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().parse::<usize>() {
+            Ok(position) if position > 0 => Column::Index(position - 1),
+            _ => Column::Header(s.trim().to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Column::Index(idx) => write!(f, "{}", idx + 1),
+            Column::Header(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Which columns of the file hold what.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CsvMapping {
+    /// The official number of the question, e.g. "0862" or "862".
+    pub number: Column,
+    /// Whether it was answered correctly, e.g. "1", "true", "correct" or "נכון".
+    pub correct: Column,
+    /// When it was answered, e.g. "2024-03-05 14:30", "05/03/2024" or seconds since the epoch.
+    pub timestamp: Column,
+    /// Whether the first row names the columns rather than holding an answer.
+    pub has_header: bool,
+}
+
+impl CsvMapping {
+    /// The mapping of a file whose header names its columns in English or Hebrew, e.g.
+    /// "question", "correct" and "date". `None` unless all three are found.
+    pub fn guess(headers: &[String]) -> Option<Self> {
+        let find = |names: &[&str]| {
+            headers.iter().position(|header| {
+                let header = header.trim().to_lowercase();
+                names.iter().any(|name| header.contains(name))
+            })
+        };
+        let timestamp = find(&["date", "time", "answered", "תאריך", "זמן"])?;
+        let correct = find(&["correct", "result", "outcome", "right", "נכון", "תוצאה"])?;
+        let number = find(&["num", "question", "id", "מספר", "שאלה"])?;
+        let distinct = number != correct && number != timestamp && correct != timestamp;
+        distinct.then_some(Self {
+            number: Column::Index(number),
+            correct: Column::Index(correct),
+            timestamp: Column::Index(timestamp),
+            has_header: true,
+        })
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CsvImportError {
+    #[error("בקובץ אין עמודה \"{0}\"")]
+    MissingColumn(String),
+    #[error("הקובץ אינו קובץ CSV תקין: {0}")]
+    Corrupt(String),
+}
+
+/// What an import read, by rows of the file.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct CsvImportReport {
+    /// Rows which were added to the history.
+    pub imported: usize,
+    /// Rows which couldn't be read, e.g. without a date.
+    pub skipped: usize,
+    /// Rows of questions which aren't in the bank.
+    pub unmatched: usize,
+    /// Rows of days which were already imported.
+    pub duplicates: usize,
+    /// The attempts which were added, one for each day.
+    pub attempts: usize,
+}
+
+impl CsvImportReport {
+    pub fn description_he(&self) -> String {
+        let mut description = format!(
+            "יובאו {} תשובות מ־{} ימים, {} שורות לא נקראו ו־{} שאלות אינן במאגר",
+            self.imported, self.attempts, self.skipped, self.unmatched
+        );
+        if self.duplicates > 0 {
+            description += &format!(", {} תשובות כבר יובאו בעבר", self.duplicates);
+        }
+        description
+    }
+}
+
+/// The delimiter of the first line, for files exported with a comma, a semicolon or a tab.
+fn delimiter(text: &[u8]) -> u8 {
+    let first_line = text.split(|b| *b == b'\n').next().unwrap_or_default();
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|delimiter| first_line.iter().filter(|b| *b == delimiter).count())
+        .filter(|delimiter| first_line.contains(delimiter))
+        .unwrap_or(b',')
+}
+
+fn csv_reader(text: &[u8]) -> csv::Reader<&[u8]> {
+    let text = text.strip_prefix("\u{feff}".as_bytes()).unwrap_or(text);
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter(text))
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text)
+}
+
+/// The fields of the first row of a file, e.g. to choose its [`CsvMapping`].
+pub fn csv_headers(text: &[u8]) -> Vec<String> {
+    csv_reader(text)
+        .records()
+        .next()
+        .and_then(Result::ok)
+        .map(|record| record.iter().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// The official number at the start of `text`, ignoring what comes before its first digit, e.g.
+/// "Q0862".
+fn parse_number(text: &str) -> Option<usize> {
+    let digits = text.trim_start_matches(|c: char| !c.is_ascii_digit());
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    digits[..end].parse().ok()
+}
+
+fn parse_correct(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "y" | "correct" | "right" | "v" | "✓" | "✔" | "נכון" | "כן" => {
+            Some(true)
+        }
+        "0" | "false" | "no" | "n" | "wrong" | "incorrect" | "x" | "✗" | "✘" | "שגוי" | "לא" => {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+/// The day of a date, year first (`2024-03-05`, `2024/3/5`) or else day first as dates are
+/// written in Israel (`05/03/2024`, `5.3.24`).
+fn parse_day(date: &str) -> Option<i64> {
+    let separator = date.chars().find(|c| matches!(c, '-' | '/' | '.'))?;
+    let parts = date.split(separator).collect::<Vec<_>>();
+    let [first, second, third] = parts[..] else {
+        return None;
+    };
+    let (year, month, day) = if first.len() == 4 {
+        (first, second, third)
+    } else {
+        (third, second, first)
+    };
+    let (year, month, day) = (
+        year.parse::<i64>().ok()?,
+        month.parse().ok()?,
+        day.parse().ok()?,
+    );
+    let year = if year < 100 { 2000 + year } else { year };
+    // the file is untrusted, and days too far out would overflow the arithmetic of dates
+    if !(1970..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = time::days_from_civil(year, month, day);
+    (time::civil_from_days(days) == (year, month, day)).then_some(days)
+}
+
+/// The seconds into the day of `hh:mm[:ss[.fff]]`, and the offset from UTC of its time zone if it
+/// has one, e.g. `Z` or `+03:00`.
+fn parse_time_of_day(text: &str) -> Option<(i64, Option<i64>)> {
+    let (text, offset) = if let Some(text) = text.strip_suffix(['Z', 'z']) {
+        (text, Some(0))
+    } else if let Some(sign_at) = text.rfind(['+', '-']) {
+        let (text, zone) = text.split_at(sign_at);
+        let sign = if zone.starts_with('-') { -1 } else { 1 };
+        let digits = zone[1..].replace(':', "");
+        if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let (hours, minutes) = (
+            digits[..2].parse::<i64>().ok()?,
+            digits[2..].parse::<i64>().ok()?,
+        );
+        (text, Some(sign * (hours * 3600 + minutes * 60)))
+    } else {
+        (text, None)
+    };
+    let mut parts = text.split(':');
+    let hours = parts.next()?.parse::<i64>().ok()?;
+    let minutes = parts.next()?.parse::<i64>().ok()?;
+    let seconds = match parts.next() {
+        Some(seconds) => seconds.split('.').next()?.parse::<i64>().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    Some((hours * 3600 + minutes * 60 + seconds, offset))
+}
+
+/// When a question was answered, as a duration since the unix epoch. Times without a time zone
+/// are local to `utc_offset_secs`, dates without a time are at their start. A number alone is
+/// seconds since the epoch, or milliseconds when it is too large for seconds.
+fn parse_timestamp(text: &str, utc_offset_secs: i64) -> Option<Duration> {
+    let text = text.trim();
+    if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) {
+        let value = text.parse::<u64>().ok()?;
+        // milliseconds have 13 digits since 2001, seconds won't have 12 until the year 5138
+        return Some(if text.len() >= 12 {
+            Duration::from_millis(value)
+        } else {
+            Duration::from_secs(value)
+        });
+    }
+    let (date, time_of_day) = match text.split_once(['T', ' ']) {
+        Some((date, time_of_day)) => (date, time_of_day.trim()),
+        None => (text, ""),
+    };
+    let day = parse_day(date)?;
+    let (secs, offset) = match time_of_day {
+        "" => (0, None),
+        time_of_day => parse_time_of_day(time_of_day)?,
+    };
+    let secs = day * 86_400 + secs - offset.unwrap_or(utc_offset_secs);
+    u64::try_from(secs).ok().map(Duration::from_secs)
+}
+
+impl History {
+    /// Add the answers of a CSV file to the history, a single attempt for the answers of each
+    /// local day in the time zone of `utc_offset_secs`. Rows which can't be read and questions
+    /// which aren't in `bank` are skipped and counted in the report.
+    pub fn import_csv(
+        &mut self,
+        mut reader: impl Read,
+        mapping: &CsvMapping,
+        bank: &ExamQuestions,
+        utc_offset_secs: i64,
+    ) -> Result<CsvImportReport, CsvImportError> {
+        let mut text = Vec::new();
+        reader
+            .read_to_end(&mut text)
+            .map_err(|e| CsvImportError::Corrupt(e.to_string()))?;
+        let mut records = csv_reader(&text).into_records();
+        let headers = if mapping.has_header {
+            match records.next() {
+                Some(headers) => headers.map_err(|e| CsvImportError::Corrupt(e.to_string()))?,
+                None => csv::StringRecord::new(),
+            }
+        } else {
+            csv::StringRecord::new()
+        };
+        let index = |column: &Column| match column {
+            Column::Index(idx) => Ok(*idx),
+            Column::Header(name) => headers
+                .iter()
+                .position(|header| header.eq_ignore_ascii_case(name))
+                .ok_or_else(|| CsvImportError::MissingColumn(name.clone())),
+        };
+        let (number, correct, timestamp) = (
+            index(&mapping.number)?,
+            index(&mapping.correct)?,
+            index(&mapping.timestamp)?,
+        );
+
+        let mut report = CsvImportReport::default();
+        let mut answers = Vec::new();
+        for record in records {
+            let answer = record.ok().and_then(|record| {
+                Some((
+                    parse_timestamp(record.get(timestamp)?, utc_offset_secs)?,
+                    parse_number(record.get(number)?)?,
+                    parse_correct(record.get(correct)?)?,
+                ))
+            });
+            match answer {
+                None => report.skipped += 1,
+                Some((_, num, _)) if !bank.questions.iter().any(|q| q.num == num) => {
+                    report.unmatched += 1
+                }
+                Some(answer) => answers.push(answer),
+            }
+        }
+
+        // stable, so answers at the same time keep the order of the file
+        answers.sort_by_key(|(answered_at, _, _)| *answered_at);
+        for day in answers.chunk_by(|(a, _, _), (b, _, _)| {
+            time::local_day(*a, utc_offset_secs) == time::local_day(*b, utc_offset_secs)
+        }) {
+            let started_at = day[0].0;
+            if self.attempts.iter().any(|a| a.started_at == started_at) {
+                report.duplicates += day.len();
+                continue;
+            }
+            self.attempts.push(Attempt {
+                started_at,
+                graded_at: day[day.len() - 1].0,
+                kind: ExamKind::Imported,
+                strict: false,
+                abandoned: false,
+                questions: day
+                    .iter()
+                    .map(|&(_, num, correct)| {
+                        QuestionAttempt::new(
+                            num,
+                            if correct {
+                                QuestionOutcome::Correct
+                            } else {
+                                QuestionOutcome::Wrong
+                            },
+                        )
+                    })
+                    .collect(),
+                duration: None,
+                pass_threshold: PassThreshold::default(),
+                rules: None,
+            });
+            report.imported += day.len();
+            report.attempts += 1;
+        }
+        self.attempts.sort_by_key(|a| a.graded_at);
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use theory_test_parser::question_parser::Question;
+
+    use super::*;
+
+    fn bank() -> ExamQuestions {
+        ExamQuestions {
+            questions: [1, 12, 862]
+                .into_iter()
+                .map(|num| Question::sample(num, 0))
+                .collect(),
+        }
+    }
+
+    /// Exported from another app: mixed date formats, a question which isn't in the bank, a row
+    /// without a date and a row which isn't an answer at all.
+    const FIXTURE: &str = "\u{feff}Answered at;Question ID;Result;Notes
+2024-03-05 14:30;0862;correct;
+2024-03-05T12:45:10Z;12;0;late
+05/03/2024 18:00;Q1;נכון;\"with; a delimiter\"
+2024-03-06;862;wrong;
+1709719200;12;1;
+1709722800000;1;false;
+2024-03-07 10:00;9999;1;
+;1;1;
+bogus row
+";
+
+    fn mapping() -> CsvMapping {
+        CsvMapping {
+            number: "Question ID".parse().unwrap(),
+            correct: "3".parse().unwrap(),
+            timestamp: Column::Header("answered at".to_string()),
+            has_header: true,
+        }
+    }
+
+    #[test]
+    fn imports_a_day_per_attempt() {
+        let mut history = History::default();
+        let report = history
+            .import_csv(FIXTURE.as_bytes(), &mapping(), &bank(), 2 * 3600)
+            .unwrap();
+        assert_eq!(
+            report,
+            CsvImportReport {
+                imported: 6,
+                skipped: 2,
+                unmatched: 1,
+                duplicates: 0,
+                attempts: 2,
+            }
+        );
+        assert!(
+            history
+                .attempts
+                .iter()
+                .all(|a| a.kind == ExamKind::Imported)
+        );
+        let days = history
+            .attempts
+            .iter()
+            .map(|a| {
+                (
+                    time::format_date(time::local_day(a.started_at, 2 * 3600)),
+                    a.questions.iter().map(|q| q.num).collect::<Vec<_>>(),
+                    a.num_correct(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            days,
+            vec![
+                // 12:45 UTC is 14:45 local, after the local 14:30
+                ("2024-03-05".to_string(), vec![862, 12, 1], 2),
+                ("2024-03-06".to_string(), vec![862, 12, 1], 1),
+            ]
+        );
+        let stats = history.question_stats();
+        assert_eq!((stats[&862].attempts, stats[&862].correct), (2, 1));
+        assert_eq!((stats[&12].attempts, stats[&12].correct), (2, 1));
+        assert_eq!((stats[&1].attempts, stats[&1].correct), (2, 1));
+
+        // importing the file again adds nothing
+        let again = history
+            .import_csv(FIXTURE.as_bytes(), &mapping(), &bank(), 2 * 3600)
+            .unwrap();
+        assert_eq!((again.imported, again.duplicates), (0, 6));
+        assert_eq!(history.attempts.len(), 2);
+    }
+
+    #[test]
+    fn skips_dates_out_of_range() {
+        let csv = "Answered at;Question ID;Result;Notes
+01/01/9223372036854775807;862;correct;
+2024-03-05 14:30;12;correct;
+";
+        let mut history = History::default();
+        let report = history
+            .import_csv(csv.as_bytes(), &mapping(), &bank(), 0)
+            .unwrap();
+        assert_eq!((report.imported, report.skipped), (1, 1));
+        assert_eq!(history.attempts[0].questions[0].num, 12);
+    }
+
+    #[test]
+    fn mappings() {
+        let headers = csv_headers(FIXTURE.as_bytes());
+        assert_eq!(headers, ["Answered at", "Question ID", "Result", "Notes"]);
+        assert_eq!(
+            CsvMapping::guess(&headers),
+            Some(CsvMapping {
+                number: Column::Index(1),
+                correct: Column::Index(2),
+                timestamp: Column::Index(0),
+                has_header: true,
+            })
+        );
+        assert_eq!(CsvMapping::guess(&["a".to_string(), "b".to_string()]), None);
+
+        let missing = CsvMapping {
+            number: Column::Header("num".to_string()),
+            ..mapping()
+        };
+        assert_eq!(
+            History::default().import_csv(FIXTURE.as_bytes(), &missing, &bank(), 0),
+            Err(CsvImportError::MissingColumn("num".to_string()))
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        let at = |text| parse_timestamp(text, 3 * 3600).map(|time| time.as_secs());
+        // 2024-03-05T12:00:00Z
+        let noon = Some(1_709_640_000);
+        assert_eq!(at("2024-03-05 15:00"), noon);
+        assert_eq!(at("2024-03-05T15:00:00.250"), noon);
+        assert_eq!(at("2024-03-05T12:00:00Z"), noon);
+        assert_eq!(at("2024-03-05T14:00:00+02:00"), noon);
+        assert_eq!(at("05/03/2024 15:00"), noon);
+        assert_eq!(at("5.3.24 15:00"), noon);
+        assert_eq!(at("2024/03/05 15:00:00"), noon);
+        assert_eq!(at("1709640000"), noon);
+        assert_eq!(at("1709640000000"), noon);
+        assert_eq!(at("2024-03-05"), Some(1_709_640_000 - 15 * 3600));
+        assert_eq!(at("31/02/2024"), None);
+        assert_eq!(at("01/01/9223372036854775807"), None);
+        assert_eq!(at("9223372036854775807-01-01"), None);
+        assert_eq!(at("2024-9223372036854775807-01"), None);
+        assert_eq!(at("1969-12-31"), None);
+        assert_eq!(at("2024-03-05 25:00"), None);
+        assert_eq!(at("yesterday"), None);
+        assert_eq!(at(""), None);
+    }
+}
@@ -0,0 +1,47 @@
+//! The direction of text, by its language.
+//!
+//! The direction of the UI is set once, at the root, and inherited by every page. Content in
+//! another language, e.g. the questions of a bank in Russian, sets its own direction, and short
+//! runs of the other direction inside a sentence, e.g. the license class codes, are isolated so
+//! they don't reorder the punctuation and numbers around them.
+
+/// The language of the UI's strings.
+pub const UI_LANGUAGE: &str = "he";
+
+/// Languages written right to left, by their code.
+const RTL_LANGUAGES: &[&str] = &["he", "ar", "fa", "ur", "yi"];
+
+/// The value of the `dir` attribute for text in `language`.
+pub fn direction(language: &str) -> &'static str {
+    let primary = language.split('-').next().unwrap_or_default();
+    if RTL_LANGUAGES.contains(&primary) {
+        "rtl"
+    } else {
+        "ltr"
+    }
+}
+
+/// Isolate `text` from the direction of the sentence around it, for plain strings. Inside rsx
+/// prefer wrapping with `bdi`.
+pub fn isolate(text: &str) -> String {
+    format!("\u{2068}{text}\u{2069}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn directions() {
+        assert_eq!(direction(UI_LANGUAGE), "rtl");
+        assert_eq!(direction("ar"), "rtl");
+        assert_eq!(direction("ru"), "ltr");
+        assert_eq!(direction("en-US"), "ltr");
+        assert_eq!(direction("he-IL"), "rtl");
+        // the code stays a single run, e.g. the "1" of "C1" isn't moved before the "C"
+        assert_eq!(
+            format!("דרגה {}.", isolate("C1")),
+            "דרגה \u{2068}C1\u{2069}."
+        );
+    }
+}
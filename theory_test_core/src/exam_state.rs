@@ -0,0 +1,1400 @@
+//! The state of a single exam, independent of how it is rendered.
+//!
+//! Time is never read here: methods which depend on it take the current time,
+//! as a duration since the unix epoch, from the caller.
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use theory_test_parser::{
+    exam::{ExamRules, OFFICIAL_MAX_MISTAKES, OFFICIAL_NUM_QUESTIONS},
+    question_parser::Question,
+};
+use thiserror::Error;
+
+use crate::time_warnings::{TimeWarning, default_warnings, num_reached};
+
+/// The number of questions of the official exam.
+pub const REAL_EXAM_NUM_QUESTIONS: usize = OFFICIAL_NUM_QUESTIONS;
+/// The most mistakes, wrong or unanswered, which still pass the official exam.
+pub const REAL_EXAM_MAX_MISTAKES: usize = OFFICIAL_MAX_MISTAKES;
+
+/// The most mistakes which still pass an exam of `num_questions`, scaled down from the
+/// official exam.
+pub fn allowed_mistakes(num_questions: usize) -> usize {
+    num_questions * REAL_EXAM_MAX_MISTAKES / REAL_EXAM_NUM_QUESTIONS
+}
+
+/// Whether an exam simulates the real exam or is for practicing.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub enum ExamKind {
+    Real,
+    #[default]
+    Practice,
+    /// The single question of the day, answered on the main page.
+    DailyQuestion,
+    /// A drill with a countdown for each question.
+    Drill,
+    /// Memorization of the road signs which have an image.
+    SignDrill,
+    /// Answers given in another app, a day of them in each attempt, see
+    /// [`History::import_csv`](crate::history::History::import_csv).
+    Imported,
+}
+
+/// The rule by which an exam is passed. It is kept with each result, so changing the rule never
+/// changes past results.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub enum PassThreshold {
+    /// The official rule, scaled to the number of questions: of the [`ExamRules`] the exam was
+    /// taken by, or of class B, see [`allowed_mistakes`].
+    #[default]
+    Official,
+    /// At most this many mistakes.
+    MaxMistakes(usize),
+    /// At least this percentage of the questions answered correctly.
+    MinPercent(usize),
+}
+
+impl PassThreshold {
+    /// The thresholds which can be chosen for practice.
+    pub const CHOICES: [PassThreshold; 7] = [
+        PassThreshold::Official,
+        PassThreshold::MaxMistakes(0),
+        PassThreshold::MaxMistakes(2),
+        PassThreshold::MaxMistakes(6),
+        PassThreshold::MinPercent(90),
+        PassThreshold::MinPercent(80),
+        PassThreshold::MinPercent(70),
+    ];
+
+    /// The most mistakes which still pass an exam of `num_questions`.
+    pub fn allowed_mistakes(&self, num_questions: usize) -> usize {
+        match *self {
+            PassThreshold::Official => allowed_mistakes(num_questions),
+            PassThreshold::MaxMistakes(max) => max.min(num_questions),
+            PassThreshold::MinPercent(percent) => {
+                num_questions.saturating_sub((num_questions * percent).div_ceil(100))
+            }
+        }
+    }
+
+    /// The most mistakes which still pass an exam of `num_questions` taken by `rules`, which
+    /// only change the official rule.
+    pub fn allowed_mistakes_by(&self, rules: Option<&ExamRules>, num_questions: usize) -> usize {
+        match (self, rules) {
+            (PassThreshold::Official, Some(rules)) => rules.allowed_mistakes(num_questions),
+            _ => self.allowed_mistakes(num_questions),
+        }
+    }
+
+    pub fn description_he(&self) -> String {
+        match self {
+            PassThreshold::Official => "הכלל הרשמי".to_string(),
+            PassThreshold::MaxMistakes(max) => format!("עד {} טעויות", max),
+            PassThreshold::MinPercent(percent) => format!("לפחות {}% תשובות נכונות", percent),
+        }
+    }
+}
+
+/// What happened with a single question once the exam was graded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum QuestionOutcome {
+    Correct,
+    Wrong,
+    Unanswered,
+    /// The question is malformed and couldn't be shown, so it doesn't count at all.
+    Void,
+}
+
+/// Why a question can't be shown.
+#[derive(Debug, Error, PartialEq)]
+pub enum MalformedQuestion {
+    #[error("לשאלה {0} אין תשובות")]
+    NoAnswers(usize),
+    #[error("התשובה הנכונה של שאלה {0} אינה אחת מהתשובות")]
+    CorrectAnswerOutOfRange(usize),
+    #[error("הטקסט של שאלה {0} אינו מתחיל במספר השאלה")]
+    NoNumberPrefix(usize),
+}
+
+/// Check that a question can be shown and graded.
+pub fn check_question(question: &Question) -> Result<(), MalformedQuestion> {
+    let answers = &question.answers;
+    if answers.possible_answers.is_empty() {
+        return Err(MalformedQuestion::NoAnswers(question.num));
+    }
+    if answers.correct_answer >= answers.possible_answers.len() {
+        return Err(MalformedQuestion::CorrectAnswerOutOfRange(question.num));
+    }
+    if question
+        .question
+        .get(QUESTION_NUMBER_PREFIX_LEN..)
+        .is_none()
+    {
+        return Err(MalformedQuestion::NoNumberPrefix(question.num));
+    }
+    Ok(())
+}
+
+/// The length of the number the text of a question starts with, e.g. "0862. ".
+const QUESTION_NUMBER_PREFIX_LEN: usize = 6;
+
+/// The text of a question without the number it starts with, to be numbered by the exam. Empty
+/// for a question [`check_question`] rejects. Line breaks are kept, so a number put before the
+/// text only numbers its first line.
+pub fn question_body(question: &Question) -> &str {
+    question
+        .question
+        .get(QUESTION_NUMBER_PREFIX_LEN..)
+        .unwrap_or_default()
+}
+
+/// How sure the user was of their answer to a question.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Confidence {
+    Confident,
+    Guess,
+}
+
+/// How the answers marked with a confidence turned out.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ConfidenceBreakdown {
+    pub confident_correct: usize,
+    pub confident_wrong: usize,
+    pub guess_correct: usize,
+    pub guess_wrong: usize,
+}
+
+impl ConfidenceBreakdown {
+    pub fn total(&self) -> usize {
+        self.confident_correct + self.confident_wrong + self.guess_correct + self.guess_wrong
+    }
+}
+
+/// The graded outcome of an exam, one entry per question in exam order.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExamResult {
+    pub outcomes: Vec<QuestionOutcome>,
+    /// The confidence the user marked for each question when it was graded.
+    pub confidences: Vec<Option<Confidence>>,
+    /// Whether each question was flagged when it was graded.
+    pub flags: Vec<bool>,
+    /// Whether the exam ended because its time ran out, rather than being submitted.
+    pub timed_out: bool,
+    /// How long the exam took, see [`ExamState::elapsed`].
+    pub elapsed: Duration,
+    /// The rule the exam was graded by.
+    pub pass_threshold: PassThreshold,
+    /// The official rules of the class the exam was taken by, if it simulated the real exam.
+    pub rules: Option<ExamRules>,
+}
+
+impl ExamResult {
+    /// The result of an exam answered all at once, e.g. in a terminal or by a client of a
+    /// server, by the selected answer of each question in exam order, `None` for unanswered
+    /// ones. Missing selections are unanswered. It is graded by the official rule, as an
+    /// [`ExamState`] would grade it.
+    pub fn of_selections(questions: &[Question], selections: &[Option<usize>]) -> Self {
+        let mut exam = ExamState::new(questions.to_vec(), Duration::ZERO);
+        for (question, answer) in selections.iter().enumerate() {
+            if let Some(answer) = answer {
+                exam.select(question, *answer, Duration::ZERO);
+            }
+        }
+        exam.grade(Duration::ZERO).clone()
+    }
+
+    /// The questions which count, void questions don't.
+    pub fn num_questions(&self) -> usize {
+        self.outcomes.len() - self.count(QuestionOutcome::Void)
+    }
+
+    pub fn count(&self, outcome: QuestionOutcome) -> usize {
+        self.outcomes.iter().filter(|o| **o == outcome).count()
+    }
+
+    pub fn correct(&self) -> usize {
+        self.count(QuestionOutcome::Correct)
+    }
+
+    /// Questions which were answered wrong or not at all.
+    pub fn mistakes(&self) -> usize {
+        self.num_questions() - self.correct()
+    }
+
+    /// The most mistakes which still pass, by the exam's threshold.
+    pub fn allowed_mistakes(&self) -> usize {
+        self.pass_threshold
+            .allowed_mistakes_by(self.rules.as_ref(), self.num_questions())
+    }
+
+    pub fn passed(&self) -> bool {
+        self.mistakes() <= self.allowed_mistakes()
+    }
+
+    /// Indices (in exam order) of the questions which were not answered correctly.
+    pub fn missed(&self) -> Vec<usize> {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| !matches!(o, QuestionOutcome::Correct | QuestionOutcome::Void))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Indices (in exam order) of the questions to go over after grading: the missed ones and
+    /// the flagged ones.
+    pub fn to_review(&self) -> Vec<usize> {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter(|(i, o)| match o {
+                QuestionOutcome::Void => false,
+                QuestionOutcome::Correct => self.flags.get(*i).copied().unwrap_or(false),
+                QuestionOutcome::Wrong | QuestionOutcome::Unanswered => true,
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Only answered questions count, a confidence on an unanswered question means nothing.
+    pub fn confidence_breakdown(&self) -> ConfidenceBreakdown {
+        let mut breakdown = ConfidenceBreakdown::default();
+        for (outcome, confidence) in self.outcomes.iter().zip(self.confidences.iter()) {
+            let Some(confidence) = confidence else {
+                continue;
+            };
+            let count = match (confidence, outcome) {
+                (_, QuestionOutcome::Unanswered | QuestionOutcome::Void) => continue,
+                (Confidence::Confident, QuestionOutcome::Correct) => {
+                    &mut breakdown.confident_correct
+                }
+                (Confidence::Confident, QuestionOutcome::Wrong) => &mut breakdown.confident_wrong,
+                (Confidence::Guess, QuestionOutcome::Correct) => &mut breakdown.guess_correct,
+                (Confidence::Guess, QuestionOutcome::Wrong) => &mut breakdown.guess_wrong,
+            };
+            *count += 1;
+        }
+        breakdown
+    }
+
+    /// Indices of the questions which were answered wrong while marked as confident,
+    /// the most dangerous kind of mistake.
+    pub fn confident_wrong(&self) -> Vec<usize> {
+        self.outcomes
+            .iter()
+            .zip(self.confidences.iter())
+            .enumerate()
+            .filter(|(_, (o, c))| {
+                **o == QuestionOutcome::Wrong && **c == Some(Confidence::Confident)
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+/// How many answer changes can be undone.
+pub const UNDO_DEPTH: usize = 50;
+
+/// What the user set for a question, as it was around a change of its answer.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct AnswerSnapshot {
+    selection: Option<usize>,
+    confidence: Option<Confidence>,
+    flag: bool,
+    skips: u8,
+}
+
+/// A change of the answer or the flag of a question, which can be undone and redone.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct AnswerChange {
+    question: usize,
+    before: AnswerSnapshot,
+    after: AnswerSnapshot,
+}
+
+#[derive(Clone)]
+pub struct ExamState {
+    questions: Vec<Question>,
+    selections: Vec<Option<usize>>,
+    confidences: Vec<Option<Confidence>>,
+    flags: Vec<bool>,
+    /// How many times each question was skipped, answering it clears the count.
+    skips: Vec<u8>,
+    /// Whether the pass over the skipped questions before submitting was started.
+    revisit_started: bool,
+    /// Malformed questions, which can't be answered and don't count.
+    void: Vec<bool>,
+    started_at: Duration,
+    /// The total time the exam was paused, not counting the current pause.
+    paused_for: Duration,
+    /// When the current pause started, if the exam is paused.
+    paused_at: Option<Duration>,
+    time_limit: Option<Duration>,
+    /// Warnings as the time runs low, ordered from the earliest.
+    time_warnings: Vec<TimeWarning>,
+    /// How many of the time warnings were given, each is given once.
+    time_warnings_given: usize,
+    /// Whether each question is revealed, and locked, as soon as it is answered.
+    immediate_feedback: bool,
+    pass_threshold: PassThreshold,
+    rules: Option<ExamRules>,
+    /// Set once the time ran out: no answer can change anymore, and the exam only waits to be
+    /// graded.
+    frozen: bool,
+    result: Option<ExamResult>,
+    /// The changes which can be undone, the latest last, only of questions which aren't locked.
+    undo: Vec<AnswerChange>,
+    /// The undone changes, the latest undone last, until another change is made.
+    redo: Vec<AnswerChange>,
+}
+
+impl ExamState {
+    pub fn new(questions: Vec<Question>, now: Duration) -> Self {
+        let selections = vec![None; questions.len()];
+        let confidences = vec![None; questions.len()];
+        let flags = vec![false; questions.len()];
+        let skips = vec![0; questions.len()];
+        let void = questions
+            .iter()
+            .map(|q| check_question(q).is_err())
+            .collect();
+        Self {
+            questions,
+            selections,
+            confidences,
+            flags,
+            skips,
+            revisit_started: false,
+            void,
+            started_at: now,
+            paused_for: Duration::ZERO,
+            paused_at: None,
+            time_limit: None,
+            time_warnings: Vec::new(),
+            time_warnings_given: 0,
+            immediate_feedback: false,
+            pass_threshold: PassThreshold::Official,
+            rules: None,
+            frozen: false,
+            result: None,
+            undo: Vec::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Limit the time of the exam, counting from when it started, with the default warnings for
+    /// the limit.
+    pub fn with_time_limit(mut self, time_limit: Option<Duration>) -> Self {
+        self.time_limit = time_limit;
+        self.time_warnings = time_limit.map(default_warnings).unwrap_or_default();
+        self
+    }
+
+    pub fn with_immediate_feedback(mut self, immediate_feedback: bool) -> Self {
+        self.immediate_feedback = immediate_feedback;
+        self
+    }
+
+    pub fn with_pass_threshold(mut self, pass_threshold: PassThreshold) -> Self {
+        self.pass_threshold = pass_threshold;
+        self
+    }
+
+    pub fn pass_threshold(&self) -> PassThreshold {
+        self.pass_threshold
+    }
+
+    /// Take the exam by the official rules of a class, whose threshold the official rule
+    /// follows.
+    pub fn with_rules(mut self, rules: Option<ExamRules>) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    pub fn rules(&self) -> Option<ExamRules> {
+        self.rules
+    }
+
+    /// Whether the correct answer of a question is shown, which it is once the exam was graded,
+    /// or once the question was answered with immediate feedback.
+    pub fn is_revealed(&self, question: usize) -> bool {
+        self.is_graded() || (self.immediate_feedback && self.selection(question).is_some())
+    }
+
+    /// How many more mistakes can be made before the exam is failed, `None` once it is.
+    /// Before the exam is graded only the revealed wrong answers count.
+    pub fn mistakes_left(&self) -> Option<usize> {
+        let mistakes = match &self.result {
+            Some(result) => result.mistakes(),
+            None => (0..self.questions.len())
+                .filter(|&i| {
+                    self.is_revealed(i)
+                        && self.selection(i) != Some(self.questions[i].answers.correct_answer)
+                })
+                .count(),
+        };
+        // out of the questions which count, as the result's allowance is
+        let num_questions = self.void.iter().filter(|void| !**void).count();
+        self.pass_threshold
+            .allowed_mistakes_by(self.rules.as_ref(), num_questions)
+            .checked_sub(mistakes)
+    }
+
+    /// When the time of the exam runs out, if it is timed.
+    pub fn deadline(&self) -> Option<Duration> {
+        self.time_limit.map(|limit| self.started_at + limit)
+    }
+
+    pub fn started_at(&self) -> Duration {
+        self.started_at
+    }
+
+    /// Pause the exam while it isn't shown, e.g. when navigating away from it. The time of a
+    /// timed exam runs regardless, as in the real exam, so it isn't paused.
+    pub fn pause(&mut self, now: Duration) {
+        if self.time_limit.is_none() && !self.is_graded() && self.paused_at.is_none() {
+            self.paused_at = Some(now);
+        }
+    }
+
+    pub fn resume(&mut self, now: Duration) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_for += now.saturating_sub(paused_at);
+        }
+    }
+
+    /// How long the exam has been running, excluding the time it was paused. A timed exam
+    /// never runs longer than its limit, even if it is graded a bit after the time ran out.
+    pub fn elapsed(&self, now: Duration) -> Duration {
+        let now = self.paused_at.unwrap_or(now);
+        let elapsed = now
+            .saturating_sub(self.started_at)
+            .saturating_sub(self.paused_for);
+        match self.time_limit {
+            Some(limit) => elapsed.min(limit),
+            None => elapsed,
+        }
+    }
+
+    pub fn time_warnings(&self) -> &[TimeWarning] {
+        &self.time_warnings
+    }
+
+    /// The latest time warning reached at `now` which wasn't given yet, marking it and the ones
+    /// before it as given.
+    pub fn take_time_warning(&mut self, now: Duration) -> Option<TimeWarning> {
+        let remaining = self.deadline()?.saturating_sub(now);
+        let reached = num_reached(&self.time_warnings, remaining);
+        if self.is_graded() || reached <= self.time_warnings_given {
+            return None;
+        }
+        self.time_warnings_given = reached;
+        Some(self.time_warnings[reached - 1])
+    }
+
+    /// Whether the time of a timed exam ran out.
+    pub fn is_expired(&self, now: Duration) -> bool {
+        self.deadline().is_some_and(|deadline| now >= deadline)
+    }
+
+    /// Lock every answer once the time ran out, until the exam is graded.
+    pub fn freeze(&mut self) {
+        if !self.is_graded() {
+            self.frozen = true;
+            self.clear_undo();
+        }
+    }
+
+    /// Whether the time ran out and the exam waits to be graded.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen && !self.is_graded()
+    }
+
+    /// Whether the answers are locked at `now` because the time ran out, freezing the exam if
+    /// it ran out since it was last checked, so nothing slips in before the exam is frozen.
+    fn is_locked_at(&mut self, now: Duration) -> bool {
+        if self.is_expired(now) {
+            self.freeze();
+        }
+        self.is_frozen()
+    }
+
+    pub fn num_answered(&self) -> usize {
+        self.selections.iter().filter(|s| s.is_some()).count()
+    }
+
+    pub fn all_answered(&self) -> bool {
+        self.selections
+            .iter()
+            .zip(&self.void)
+            .all(|(s, void)| s.is_some() || *void)
+    }
+
+    pub fn questions(&self) -> &[Question] {
+        &self.questions
+    }
+
+    pub fn selection(&self, question: usize) -> Option<usize> {
+        self.selections.get(question).copied().flatten()
+    }
+
+    /// Select an answer for a question at `now`. Returns whether the selection was accepted,
+    /// which it is not for an answer the question doesn't have, once the question was revealed or
+    /// once the time ran out.
+    pub fn select(&mut self, question: usize, answer: usize, now: Duration) -> bool {
+        if self.is_locked_at(now)
+            || self.is_revealed(question)
+            || self.void.get(question).is_none_or(|void| *void)
+            || answer >= self.questions[question].answers.possible_answers.len()
+        {
+            return false;
+        }
+        let before = self.snapshot(question);
+        self.selections[question] = Some(answer);
+        self.skips[question] = 0;
+        self.record(question, before);
+        true
+    }
+
+    /// Leave a question unanswered again. Its confidence is cleared with it, as a confidence
+    /// only means something for an answered question.
+    pub fn clear_selection(&mut self, question: usize, now: Duration) -> bool {
+        if self.is_locked_at(now) || self.is_revealed(question) || question >= self.selections.len()
+        {
+            return false;
+        }
+        let before = self.snapshot(question);
+        self.selections[question] = None;
+        self.confidences[question] = None;
+        self.record(question, before);
+        true
+    }
+
+    pub fn confidence(&self, question: usize) -> Option<Confidence> {
+        self.confidences.get(question).copied().flatten()
+    }
+
+    /// Mark how sure the user is of their answer, `None` clears the mark.
+    /// Like selections, confidences can't be changed once the exam was frozen or graded.
+    pub fn set_confidence(
+        &mut self,
+        question: usize,
+        confidence: Option<Confidence>,
+        now: Duration,
+    ) -> bool {
+        if self.is_locked_at(now) || self.is_graded() || question >= self.confidences.len() {
+            return false;
+        }
+        self.confidences[question] = confidence;
+        true
+    }
+
+    pub fn is_flagged(&self, question: usize) -> bool {
+        self.flags.get(question).copied().unwrap_or(false)
+    }
+
+    /// Flag a question to come back to it later, or remove its flag. Flags are locked with the
+    /// answers once the time ran out, until the exam is graded.
+    pub fn toggle_flag(&mut self, question: usize, now: Duration) {
+        if self.is_locked_at(now) || question >= self.flags.len() {
+            return;
+        }
+        let before = self.snapshot(question);
+        self.flags[question] = !self.flags[question];
+        self.record(question, before);
+    }
+
+    fn snapshot(&self, question: usize) -> AnswerSnapshot {
+        AnswerSnapshot {
+            selection: self.selections[question],
+            confidence: self.confidences[question],
+            flag: self.flags[question],
+            skips: self.skips[question],
+        }
+    }
+
+    /// Change `question` from `from` to `to`, only in what differs between them, so what was
+    /// set in between without being recorded, e.g. a confidence, is kept.
+    fn restore(&mut self, question: usize, from: AnswerSnapshot, to: AnswerSnapshot) {
+        if from.selection != to.selection {
+            self.selections[question] = to.selection;
+        }
+        if from.confidence != to.confidence {
+            self.confidences[question] = to.confidence;
+        }
+        if from.flag != to.flag {
+            self.flags[question] = to.flag;
+        }
+        if from.skips != to.skips {
+            self.skips[question] = to.skips;
+        }
+    }
+
+    /// Record the change of `question` from `before`, so it can be undone. A question which got
+    /// locked by the change can't be changed back, so none of its changes can be undone anymore.
+    fn record(&mut self, question: usize, before: AnswerSnapshot) {
+        if self.is_revealed(question) {
+            self.undo.retain(|change| change.question != question);
+            self.redo.retain(|change| change.question != question);
+            return;
+        }
+        let after = self.snapshot(question);
+        if before == after {
+            return;
+        }
+        self.redo.clear();
+        if self.undo.len() == UNDO_DEPTH {
+            self.undo.remove(0);
+        }
+        self.undo.push(AnswerChange {
+            question,
+            before,
+            after,
+        });
+    }
+
+    /// Whether there is a change of an answer or a flag to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.frozen && !self.is_graded() && !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.frozen && !self.is_graded() && !self.redo.is_empty()
+    }
+
+    /// Undo the latest change of an answer or a flag, returning the question it changed.
+    pub fn undo(&mut self, now: Duration) -> Option<usize> {
+        if self.is_locked_at(now) || !self.can_undo() {
+            return None;
+        }
+        let change = self.undo.pop()?;
+        self.restore(change.question, change.after, change.before);
+        self.redo.push(change);
+        Some(change.question)
+    }
+
+    /// Make the latest undone change again, returning the question it changed.
+    pub fn redo(&mut self, now: Duration) -> Option<usize> {
+        if self.is_locked_at(now) || !self.can_redo() {
+            return None;
+        }
+        let change = self.redo.pop()?;
+        self.restore(change.question, change.before, change.after);
+        self.undo.push(change);
+        Some(change.question)
+    }
+
+    /// Forget the changes which can be undone and redone, e.g. before the exam is kept to be
+    /// restored later: only its current answers are kept.
+    pub fn clear_undo(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// The first question after `question` which is still to be answered, wrapping around to
+    /// the start. `None` once every question is answered.
+    pub fn next_unanswered(&self, question: usize) -> Option<usize> {
+        let len = self.selections.len();
+        (1..len)
+            .map(|offset| (question + offset) % len)
+            .find(|&idx| self.selections[idx].is_none() && !self.void[idx])
+    }
+
+    /// Skip a question, which unlike leaving it unanswered puts it aside to come back to before
+    /// submitting. A question skipped a second time is flagged for the review instead.
+    pub fn skip(&mut self, question: usize, now: Duration) -> bool {
+        if self.is_locked_at(now)
+            || self.is_revealed(question)
+            || self.selection(question).is_some()
+            || self.void.get(question).is_none_or(|void| *void)
+        {
+            return false;
+        }
+        self.skips[question] = self.skips[question].saturating_add(1);
+        if self.skips[question] == 2 {
+            self.flags[question] = true;
+        }
+        true
+    }
+
+    /// How many times an unanswered question was skipped.
+    pub fn times_skipped(&self, question: usize) -> usize {
+        match self.selection(question) {
+            Some(_) => 0,
+            None => self.skips.get(question).copied().unwrap_or(0).into(),
+        }
+    }
+
+    /// Where to go after skipping `question`: the next question after it which was neither
+    /// answered nor skipped, and once the end is reached each skipped question once more.
+    /// `None` when nothing is left and the exam can be submitted.
+    pub fn next_after_skip(&self, question: usize) -> Option<usize> {
+        let is_open = |idx: usize, skips| {
+            self.selections[idx].is_none() && !self.void[idx] && self.skips[idx] == skips
+        };
+        (question + 1..self.questions.len())
+            .find(|&idx| is_open(idx, 0))
+            .or_else(|| (0..self.questions.len()).find(|&idx| is_open(idx, 1)))
+    }
+
+    /// Start the pass over the skipped questions, returning the first of them, before the exam is
+    /// submitted. It is only started once, submitting again doesn't return to them.
+    pub fn start_revisit(&mut self) -> Option<usize> {
+        if self.revisit_started || self.frozen || self.is_graded() {
+            return None;
+        }
+        self.revisit_started = true;
+        (0..self.questions.len()).find(|&idx| self.times_skipped(idx) == 1)
+    }
+
+    pub fn num_flagged(&self) -> usize {
+        self.flags.iter().filter(|f| **f).count()
+    }
+
+    pub fn is_graded(&self) -> bool {
+        self.result.is_some()
+    }
+
+    pub fn result(&self) -> Option<&ExamResult> {
+        self.result.as_ref()
+    }
+
+    /// Grade the exam at `now`. Grading an already graded exam returns the existing result.
+    pub fn grade(&mut self, now: Duration) -> &ExamResult {
+        self.clear_undo();
+        let elapsed = self.elapsed(now);
+        let outcomes = self
+            .questions
+            .iter()
+            .zip(self.selections.iter())
+            .zip(self.void.iter())
+            .map(|((question, selection), void)| match selection {
+                _ if *void => QuestionOutcome::Void,
+                None => QuestionOutcome::Unanswered,
+                Some(s) if *s == question.answers.correct_answer => QuestionOutcome::Correct,
+                Some(_) => QuestionOutcome::Wrong,
+            })
+            .collect();
+        self.result.get_or_insert(ExamResult {
+            outcomes,
+            confidences: self.confidences.clone(),
+            flags: self.flags.clone(),
+            timed_out: self.frozen,
+            elapsed,
+            pass_threshold: self.pass_threshold,
+            rules: self.rules,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::time_warnings::TimeWarningLevel;
+    use theory_test_parser::question_parser::LicenseClass;
+
+    #[test]
+    fn grading() {
+        let mut state = ExamState::new(
+            vec![
+                Question::sample(1, 0),
+                Question::sample(2, 1),
+                Question::sample(3, 2),
+            ],
+            Duration::ZERO,
+        );
+        assert!(state.select(0, 0, Duration::ZERO));
+        assert!(state.select(1, 3, Duration::ZERO));
+        assert!(!state.select(5, 0, Duration::ZERO));
+        let result = state.grade(Duration::ZERO).clone();
+        assert_eq!(
+            result.outcomes,
+            vec![
+                QuestionOutcome::Correct,
+                QuestionOutcome::Wrong,
+                QuestionOutcome::Unanswered
+            ]
+        );
+        assert_eq!(result.correct(), 1);
+        assert_eq!(result.missed(), vec![1, 2]);
+        // selections are locked once graded
+        assert!(!state.select(2, 2, Duration::ZERO));
+        assert_eq!(state.grade(Duration::ZERO), &result);
+    }
+
+    #[test]
+    fn grading_selections() {
+        let questions = [
+            Question::sample(1, 0),
+            Question::sample(2, 1),
+            Question::sample(3, 7),
+        ];
+        let result = ExamResult::of_selections(&questions, &[Some(0), Some(3), Some(0)]);
+        assert_eq!(
+            result.outcomes,
+            vec![
+                QuestionOutcome::Correct,
+                QuestionOutcome::Wrong,
+                QuestionOutcome::Void
+            ]
+        );
+        assert_eq!(result.pass_threshold, PassThreshold::Official);
+        // missing selections are unanswered
+        let result = ExamResult::of_selections(&questions, &[Some(0)]);
+        assert_eq!(result.count(QuestionOutcome::Unanswered), 1);
+        assert_eq!(result.mistakes(), 1);
+    }
+
+    #[test]
+    fn confidence() {
+        let mut state = ExamState::new(
+            (1..=5).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        );
+        state.select(0, 0, Duration::ZERO);
+        state.select(1, 1, Duration::ZERO);
+        state.select(2, 0, Duration::ZERO);
+        state.select(3, 1, Duration::ZERO);
+        state.set_confidence(0, Some(Confidence::Confident), Duration::ZERO);
+        state.set_confidence(1, Some(Confidence::Confident), Duration::ZERO);
+        state.set_confidence(2, Some(Confidence::Guess), Duration::ZERO);
+        state.set_confidence(3, Some(Confidence::Guess), Duration::ZERO);
+        // unanswered questions are not part of the breakdown
+        state.set_confidence(4, Some(Confidence::Guess), Duration::ZERO);
+        let result = state.grade(Duration::ZERO).clone();
+        assert_eq!(
+            result.confidence_breakdown(),
+            ConfidenceBreakdown {
+                confident_correct: 1,
+                confident_wrong: 1,
+                guess_correct: 1,
+                guess_wrong: 1,
+            }
+        );
+        assert_eq!(result.confident_wrong(), vec![1]);
+        assert!(!state.set_confidence(0, None, Duration::ZERO));
+    }
+
+    #[test]
+    fn clear_selection() {
+        let mut state = ExamState::new(
+            vec![Question::sample(1, 0), Question::sample(2, 0)],
+            Duration::ZERO,
+        );
+        state.select(0, 0, Duration::ZERO);
+        state.set_confidence(0, Some(Confidence::Guess), Duration::ZERO);
+        assert!(state.clear_selection(0, Duration::ZERO));
+        assert_eq!(state.selection(0), None);
+        assert_eq!(state.confidence(0), None);
+        state.select(1, 0, Duration::ZERO);
+        state.grade(Duration::ZERO);
+        assert!(!state.clear_selection(1, Duration::ZERO));
+        assert_eq!(state.selection(1), Some(0));
+    }
+
+    #[test]
+    fn time_limit() {
+        let start = Duration::from_secs(1000);
+        let state = ExamState::new(vec![Question::sample(1, 0)], start);
+        assert_eq!(state.deadline(), None);
+        let state = state.with_time_limit(Some(Duration::from_secs(60)));
+        assert_eq!(state.deadline(), Some(Duration::from_secs(1060)));
+        assert!(!state.is_expired(Duration::from_secs(1059)));
+        assert!(state.is_expired(Duration::from_secs(1060)));
+    }
+
+    #[test]
+    fn expiry_freezes_until_graded() {
+        let start = Duration::from_secs(1000);
+        let mut state = ExamState::new(vec![Question::sample(1, 0), Question::sample(2, 1)], start)
+            .with_time_limit(Some(Duration::from_secs(60)));
+        assert!(state.select(0, 0, Duration::ZERO));
+        assert!(state.set_confidence(0, Some(Confidence::Guess), Duration::ZERO));
+        assert!(state.is_expired(Duration::from_secs(1060)));
+        state.freeze();
+        assert!(state.is_frozen());
+        // nothing can slip in after the time ran out
+        assert!(!state.select(1, 1, Duration::ZERO));
+        assert!(!state.select(0, 2, Duration::ZERO));
+        assert!(!state.clear_selection(0, Duration::ZERO));
+        assert!(!state.set_confidence(0, None, Duration::ZERO));
+        // graded after a grace period, the exam still took only its time limit
+        let result = state.grade(Duration::from_secs(1065)).clone();
+        assert!(result.timed_out);
+        assert_eq!(result.elapsed, Duration::from_secs(60));
+        assert_eq!(
+            result.outcomes,
+            vec![QuestionOutcome::Correct, QuestionOutcome::Unanswered]
+        );
+        assert!(!state.is_frozen());
+        // a graded exam isn't frozen again
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], start);
+        assert!(!state.grade(Duration::ZERO).timed_out);
+        state.freeze();
+        assert!(!state.is_frozen());
+        assert!(!state.result().unwrap().timed_out);
+    }
+
+    #[test]
+    fn expiry_locks_without_freezing() {
+        let start = Duration::from_secs(1000);
+        let deadline = Duration::from_secs(1060);
+        let mut state = ExamState::new(vec![Question::sample(1, 0), Question::sample(2, 1)], start)
+            .with_time_limit(Some(Duration::from_secs(60)));
+        assert!(state.select(0, 1, Duration::from_secs(1059)));
+        // the time ran out before the exam was frozen by anyone
+        assert!(!state.is_frozen());
+        assert!(!state.select(1, 1, deadline));
+        assert!(state.is_frozen());
+        let mut state = ExamState::new(vec![Question::sample(1, 0), Question::sample(2, 1)], start)
+            .with_time_limit(Some(Duration::from_secs(60)));
+        state.select(0, 1, start);
+        let late = Duration::from_secs(1061);
+        assert!(!state.clear_selection(0, late));
+        assert!(!state.set_confidence(0, Some(Confidence::Guess), late));
+        assert!(!state.skip(1, late));
+        state.toggle_flag(1, late);
+        assert!(!state.is_flagged(1));
+        assert_eq!(state.undo(late), None);
+        assert_eq!(state.selection(0), Some(1));
+        let result = state.grade(late).clone();
+        assert!(result.timed_out);
+        assert_eq!(
+            result.outcomes,
+            vec![QuestionOutcome::Wrong, QuestionOutcome::Unanswered]
+        );
+    }
+
+    #[test]
+    fn elapsed_time() {
+        let start = Duration::from_secs(1000);
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], start);
+        assert_eq!(
+            state.elapsed(Duration::from_secs(1100)),
+            Duration::from_secs(100)
+        );
+        // away from the exam for 50 seconds
+        state.pause(Duration::from_secs(1100));
+        assert_eq!(
+            state.elapsed(Duration::from_secs(1130)),
+            Duration::from_secs(100)
+        );
+        // pausing again while paused keeps the first pause
+        state.pause(Duration::from_secs(1120));
+        state.resume(Duration::from_secs(1150));
+        state.resume(Duration::from_secs(1160));
+        assert_eq!(
+            state.elapsed(Duration::from_secs(1200)),
+            Duration::from_secs(150)
+        );
+        assert_eq!(
+            state.grade(Duration::from_secs(1210)).elapsed,
+            Duration::from_secs(160)
+        );
+        // a graded exam isn't paused
+        state.pause(Duration::from_secs(1300));
+        assert_eq!(
+            state.elapsed(Duration::from_secs(1400)),
+            Duration::from_secs(350)
+        );
+
+        // the time of a timed exam runs while it is away
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], start)
+            .with_time_limit(Some(Duration::from_secs(600)));
+        state.pause(Duration::from_secs(1100));
+        state.resume(Duration::from_secs(1200));
+        assert_eq!(
+            state.elapsed(Duration::from_secs(1300)),
+            Duration::from_secs(300)
+        );
+    }
+
+    #[test]
+    fn time_warnings_are_given_once() {
+        let start = Duration::from_secs(1000);
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], start)
+            .with_time_limit(Some(Duration::from_secs(20 * 60)));
+        let minutes = |m: u64| start + Duration::from_secs(m * 60);
+        assert_eq!(state.take_time_warning(minutes(10)), None);
+        let low = state.take_time_warning(minutes(15)).unwrap();
+        assert_eq!(low.level, TimeWarningLevel::Low);
+        // e.g. when the exam is shown again
+        assert_eq!(state.take_time_warning(minutes(16)), None);
+        // only the latest of the warnings reached together is given
+        let mut late = state.clone();
+        let critical = late.take_time_warning(minutes(19)).unwrap();
+        assert_eq!(critical.level, TimeWarningLevel::Critical);
+        assert_eq!(late.take_time_warning(minutes(19)), None);
+        state.grade(minutes(17));
+        assert_eq!(state.take_time_warning(minutes(19)), None);
+        // untimed exams have no warnings
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], start);
+        assert_eq!(state.take_time_warning(minutes(100)), None);
+    }
+
+    #[test]
+    fn pass_threshold() {
+        assert_eq!(
+            allowed_mistakes(REAL_EXAM_NUM_QUESTIONS),
+            REAL_EXAM_MAX_MISTAKES
+        );
+        assert_eq!(allowed_mistakes(15), 2);
+        assert_eq!(allowed_mistakes(5), 0);
+        let result = |correct: usize| ExamResult {
+            outcomes: (0..30)
+                .map(|i| {
+                    if i < correct {
+                        QuestionOutcome::Correct
+                    } else {
+                        QuestionOutcome::Wrong
+                    }
+                })
+                .collect(),
+            confidences: vec![None; 30],
+            flags: vec![false; 30],
+            timed_out: false,
+            elapsed: Duration::ZERO,
+            pass_threshold: PassThreshold::Official,
+            rules: None,
+        };
+        assert!(result(26).passed());
+        assert!(!result(25).passed());
+        let stricter = ExamResult {
+            pass_threshold: PassThreshold::MaxMistakes(2),
+            ..result(27)
+        };
+        assert!(!stricter.passed());
+        assert_eq!(PassThreshold::MaxMistakes(2).allowed_mistakes(1), 1);
+        // 90% of 30 questions is 27
+        assert_eq!(PassThreshold::MinPercent(90).allowed_mistakes(30), 3);
+        // 80% of 12 questions is 9.6, so 10 are needed
+        assert_eq!(PassThreshold::MinPercent(80).allowed_mistakes(12), 2);
+        assert_eq!(PassThreshold::MinPercent(100).allowed_mistakes(12), 0);
+        let mut state = ExamState::new(
+            (1..=10).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        )
+        .with_immediate_feedback(true)
+        .with_pass_threshold(PassThreshold::MaxMistakes(1));
+        assert_eq!(state.mistakes_left(), Some(1));
+        state.select(0, 1, Duration::ZERO);
+        assert_eq!(state.mistakes_left(), Some(0));
+        assert_eq!(
+            state.grade(Duration::ZERO).pass_threshold,
+            PassThreshold::MaxMistakes(1)
+        );
+    }
+
+    #[test]
+    fn grading_by_rules() {
+        let exam = |rules: ExamRules, mistakes: usize| {
+            let mut state = ExamState::new(
+                (1..=rules.num_questions)
+                    .map(|num| Question::sample(num, 0))
+                    .collect(),
+                Duration::ZERO,
+            )
+            .with_rules(Some(rules));
+            for idx in 0..rules.num_questions {
+                state.select(idx, usize::from(idx < mistakes), Duration::ZERO);
+            }
+            state.grade(Duration::ZERO).clone()
+        };
+        let rules = ExamRules::official(LicenseClass::C);
+        let result = exam(rules, rules.max_mistakes);
+        assert_eq!(result.rules, Some(rules));
+        assert_eq!(result.allowed_mistakes(), rules.max_mistakes);
+        assert!(result.passed());
+        assert!(!exam(rules, rules.max_mistakes + 1).passed());
+        let stricter = ExamRules {
+            num_questions: 10,
+            max_mistakes: 1,
+            ..rules
+        };
+        assert!(exam(stricter, 1).passed());
+        assert!(!exam(stricter, 2).passed());
+        // a threshold of the user's own takes over the rules
+        let lenient = ExamResult {
+            pass_threshold: PassThreshold::MaxMistakes(2),
+            ..exam(stricter, 2)
+        };
+        assert!(lenient.passed());
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut state = ExamState::new(
+            (1..=3).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        );
+        assert!(!state.can_undo());
+        assert_eq!(state.undo(Duration::ZERO), None);
+        state.select(0, 1, Duration::ZERO);
+        state.select(0, 2, Duration::ZERO);
+        state.set_confidence(0, Some(Confidence::Guess), Duration::ZERO);
+        state.toggle_flag(1, Duration::ZERO);
+        state.select(2, 3, Duration::ZERO);
+        // selecting the same answer again changes nothing to undo, nor does an answer which the
+        // question doesn't have, as it is refused
+        state.select(2, 3, Duration::ZERO);
+        assert!(!state.select(2, 4, Duration::ZERO));
+        assert_eq!(state.selection(2), Some(3));
+
+        assert_eq!(state.undo(Duration::ZERO), Some(2));
+        assert_eq!(state.selection(2), None);
+        assert_eq!(state.undo(Duration::ZERO), Some(1));
+        assert!(!state.is_flagged(1));
+        assert_eq!(state.redo(Duration::ZERO), Some(1));
+        assert!(state.is_flagged(1));
+        assert_eq!(state.undo(Duration::ZERO), Some(1));
+        assert_eq!(state.undo(Duration::ZERO), Some(0));
+        assert_eq!(state.selection(0), Some(1));
+        assert_eq!(state.redo(Duration::ZERO), Some(0));
+        assert_eq!(state.selection(0), Some(2));
+
+        // clearing takes the confidence with it, and undoing it brings it back
+        state.clear_selection(0, Duration::ZERO);
+        assert!(!state.can_redo());
+        assert_eq!(state.redo(Duration::ZERO), None);
+        assert_eq!(state.confidence(0), None);
+        assert_eq!(state.undo(Duration::ZERO), Some(0));
+        assert_eq!(state.selection(0), Some(2));
+        assert_eq!(state.confidence(0), Some(Confidence::Guess));
+        assert_eq!(state.undo(Duration::ZERO), Some(0));
+        assert_eq!(state.undo(Duration::ZERO), Some(0));
+        assert_eq!(state.selection(0), None);
+        assert_eq!(state.undo(Duration::ZERO), None);
+        assert_eq!(state.redo(Duration::ZERO), Some(0));
+        assert_eq!(state.selection(0), Some(1));
+
+        // a skipped question is skipped again once its answer is undone
+        state.skip(2, Duration::ZERO);
+        state.select(2, 0, Duration::ZERO);
+        assert_eq!(state.times_skipped(2), 0);
+        state.undo(Duration::ZERO);
+        assert_eq!(state.times_skipped(2), 1);
+
+        // only the latest changes are kept
+        for change in 0..UNDO_DEPTH + 10 {
+            // the 4 answers of the question in turn
+            state.select(1, change % 4, Duration::ZERO);
+        }
+        let mut undone = 0;
+        while state.undo(Duration::ZERO).is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, UNDO_DEPTH);
+        // as it was after the 10th change
+        assert_eq!(state.selection(1), Some(9 % 4));
+
+        // a kept exam has its answers without what can be undone
+        state.redo(Duration::ZERO);
+        let mut kept = state.clone();
+        kept.clear_undo();
+        assert!(!kept.can_undo() && !kept.can_redo());
+        assert_eq!(kept.selection(1), Some(10 % 4));
+
+        state.select(1, 0, Duration::ZERO);
+        state.grade(Duration::ZERO);
+        assert!(!state.can_undo());
+        assert_eq!(state.undo(Duration::ZERO), None);
+        assert_eq!(state.selection(1), Some(0));
+    }
+
+    #[test]
+    fn undo_with_immediate_feedback() {
+        let mut state = ExamState::new(
+            (1..=3).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        )
+        .with_immediate_feedback(true);
+        state.toggle_flag(0, Duration::ZERO);
+        state.toggle_flag(1, Duration::ZERO);
+        // answering reveals, and locks, the question
+        state.select(0, 1, Duration::ZERO);
+        state.toggle_flag(0, Duration::ZERO);
+        assert_eq!(state.undo(Duration::ZERO), Some(1));
+        assert!(!state.is_flagged(1));
+        assert_eq!(state.undo(Duration::ZERO), None);
+        assert_eq!(state.selection(0), Some(1));
+        assert!(!state.is_flagged(0));
+
+        state.toggle_flag(2, Duration::ZERO);
+        state.freeze();
+        assert!(!state.can_undo());
+        assert!(state.is_flagged(2));
+    }
+
+    #[test]
+    fn immediate_feedback() {
+        let mut state = ExamState::new(
+            (1..=15).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        )
+        .with_immediate_feedback(true);
+        assert_eq!(state.mistakes_left(), Some(2));
+        assert!(state.select(0, 1, Duration::ZERO));
+        assert!(state.is_revealed(0));
+        // answers are locked once revealed
+        assert!(!state.select(0, 0, Duration::ZERO));
+        assert!(!state.clear_selection(0, Duration::ZERO));
+        assert_eq!(state.mistakes_left(), Some(1));
+        state.select(1, 0, Duration::ZERO);
+        assert_eq!(state.mistakes_left(), Some(1));
+        state.select(2, 2, Duration::ZERO);
+        assert_eq!(state.mistakes_left(), Some(0));
+        state.select(3, 3, Duration::ZERO);
+        assert_eq!(state.mistakes_left(), None);
+        // without immediate feedback nothing is revealed before grading
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], Duration::ZERO);
+        state.select(0, 1, Duration::ZERO);
+        assert!(!state.is_revealed(0));
+        assert!(state.select(0, 0, Duration::ZERO));
+    }
+
+    #[test]
+    fn mistakes_left_without_void_questions() {
+        let mut questions: Vec<_> = (1..=9).map(|num| Question::sample(num, 0)).collect();
+        questions.push(Question::sample(10, 7));
+        let mut state = ExamState::new(questions, Duration::ZERO)
+            .with_immediate_feedback(true)
+            .with_pass_threshold(PassThreshold::MinPercent(80));
+        // 80% of the 9 questions which count, not of all 10
+        assert_eq!(state.mistakes_left(), Some(1));
+        let allowed = state.grade(Duration::ZERO).allowed_mistakes();
+        assert_eq!(allowed, 1);
+    }
+
+    #[test]
+    fn flags() {
+        let mut state = ExamState::new(
+            vec![Question::sample(1, 0), Question::sample(2, 0)],
+            Duration::ZERO,
+        );
+        state.toggle_flag(1, Duration::ZERO);
+        assert!(state.is_flagged(1));
+        assert_eq!(state.num_flagged(), 1);
+        state.toggle_flag(1, Duration::ZERO);
+        assert_eq!(state.num_flagged(), 0);
+    }
+
+    #[test]
+    fn next_unanswered() {
+        let mut state = ExamState::new(
+            vec![
+                Question::sample(1, 0),
+                Question::sample(2, 0),
+                Question::sample(3, 7),
+                Question::sample(4, 0),
+            ],
+            Duration::ZERO,
+        );
+        state.select(1, 0, Duration::ZERO);
+        // answered and void questions are jumped over
+        assert_eq!(state.next_unanswered(0), Some(3));
+        assert_eq!(state.next_unanswered(3), Some(0));
+        state.select(0, 0, Duration::ZERO);
+        assert_eq!(state.next_unanswered(0), Some(3));
+        state.select(3, 0, Duration::ZERO);
+        assert_eq!(state.next_unanswered(3), None);
+    }
+
+    #[test]
+    fn reviewing() {
+        let mut state = ExamState::new(
+            (1..=5).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        );
+        for idx in 0..4 {
+            state.select(idx, 0, Duration::ZERO);
+        }
+        state.toggle_flag(2, Duration::ZERO);
+        // the flagged one is reviewed even though it was answered correctly
+        assert_eq!(state.grade(Duration::ZERO).to_review(), vec![2, 4]);
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], Duration::ZERO);
+        state.select(0, 0, Duration::ZERO);
+        assert!(state.grade(Duration::ZERO).to_review().is_empty());
+    }
+
+    #[test]
+    fn skipping() {
+        let mut state = ExamState::new(
+            (1..=5).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        );
+        assert!(state.skip(0, Duration::ZERO));
+        assert_eq!(state.next_after_skip(0), Some(1));
+        state.select(1, 0, Duration::ZERO);
+        assert!(state.skip(2, Duration::ZERO));
+        assert!(!state.skip(1, Duration::ZERO));
+        // skipped questions are still unanswered
+        assert_eq!(state.num_answered(), 1);
+        assert!(!state.all_answered());
+        assert_eq!(state.next_after_skip(2), Some(3));
+        state.select(3, 0, Duration::ZERO);
+        assert!(state.skip(4, Duration::ZERO));
+        // past the end the skipped questions are revisited in order
+        assert_eq!(state.next_after_skip(4), Some(0));
+        // answering clears the skip
+        state.select(0, 0, Duration::ZERO);
+        assert_eq!(state.times_skipped(0), 0);
+        assert_eq!(state.next_after_skip(4), Some(2));
+        // skipped twice, it is flagged and left for the review
+        assert!(state.skip(2, Duration::ZERO));
+        assert_eq!(state.times_skipped(2), 2);
+        assert!(state.is_flagged(2));
+        assert_eq!(state.next_after_skip(2), Some(4));
+        assert!(state.skip(4, Duration::ZERO));
+        assert_eq!(state.next_after_skip(4), None);
+    }
+
+    #[test]
+    fn revisiting() {
+        let mut state = ExamState::new(
+            (1..=3).map(|num| Question::sample(num, 0)).collect(),
+            Duration::ZERO,
+        );
+        state.skip(1, Duration::ZERO);
+        state.skip(2, Duration::ZERO);
+        state.skip(2, Duration::ZERO);
+        // only the questions skipped once are revisited, and only once
+        assert_eq!(state.start_revisit(), Some(1));
+        assert_eq!(state.start_revisit(), None);
+        let mut state = ExamState::new(vec![Question::sample(1, 0)], Duration::ZERO);
+        assert_eq!(state.start_revisit(), None);
+    }
+
+    #[test]
+    fn malformed_questions() {
+        let out_of_range = Question::sample(2, 7);
+        assert_eq!(
+            check_question(&out_of_range),
+            Err(MalformedQuestion::CorrectAnswerOutOfRange(2))
+        );
+        let mut no_prefix = Question::sample(3, 0);
+        no_prefix.question = "3. א".to_string();
+        assert_eq!(
+            check_question(&no_prefix),
+            Err(MalformedQuestion::NoNumberPrefix(3))
+        );
+        assert_eq!(check_question(&Question::sample(1, 0)), Ok(()));
+        assert_eq!(question_body(&no_prefix), "");
+        let mut multi_line = Question::sample(4, 0);
+        multi_line.question = "0004. first\nsecond".to_string();
+        assert_eq!(question_body(&multi_line), "first\nsecond");
+
+        let mut state = ExamState::new(vec![Question::sample(1, 0), out_of_range], Duration::ZERO);
+        // the void question can't be answered, and isn't needed to finish
+        assert!(!state.select(1, 0, Duration::ZERO));
+        assert!(state.select(0, 1, Duration::ZERO));
+        assert!(state.all_answered());
+        let result = state.grade(Duration::ZERO).clone();
+        assert_eq!(
+            result.outcomes,
+            vec![QuestionOutcome::Wrong, QuestionOutcome::Void]
+        );
+        assert_eq!(result.num_questions(), 1);
+        assert_eq!(result.missed(), vec![0]);
+    }
+}
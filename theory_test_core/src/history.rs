@@ -0,0 +1,351 @@
+//! The history of graded exams.
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use theory_test_parser::exam::ExamRules;
+
+use crate::exam_state::{
+    Confidence, ExamKind, ExamState, PassThreshold, QuestionOutcome, check_question,
+};
+
+/// The outcome of a single question of an attempt.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct QuestionAttempt {
+    /// The official number of the question.
+    pub num: usize,
+    pub outcome: QuestionOutcome,
+    /// How sure the user was of their answer, if they marked it. Attempts recorded before it
+    /// was kept have none.
+    #[serde(default)]
+    pub confidence: Option<Confidence>,
+}
+
+impl QuestionAttempt {
+    /// The outcome of a question without a confidence.
+    pub fn new(num: usize, outcome: QuestionOutcome) -> Self {
+        Self {
+            num,
+            outcome,
+            confidence: None,
+        }
+    }
+}
+
+/// A graded exam, or a strict exam which was abandoned before it was graded.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Attempt {
+    /// When the exam started, as a duration since the unix epoch. Identifies the attempt.
+    #[serde(default)]
+    pub started_at: Duration,
+    /// When the exam was graded, or when it started if it was abandoned.
+    pub graded_at: Duration,
+    #[serde(default)]
+    pub kind: ExamKind,
+    #[serde(default)]
+    pub strict: bool,
+    #[serde(default)]
+    pub abandoned: bool,
+    pub questions: Vec<QuestionAttempt>,
+    /// How long the exam took, unknown for abandoned exams and for attempts recorded before it
+    /// was.
+    #[serde(default)]
+    pub duration: Option<Duration>,
+    /// The rule the exam was graded by, attempts recorded before it was were graded by the
+    /// official one.
+    #[serde(default)]
+    pub pass_threshold: PassThreshold,
+    /// The official rules of the class the exam was taken by, if it simulated the real exam.
+    /// Real exams recorded before they were kept were taken by those of class B.
+    #[serde(default)]
+    pub rules: Option<ExamRules>,
+}
+
+impl Attempt {
+    /// The attempt of a graded exam, or `None` if it wasn't graded.
+    pub fn from_exam(
+        exam: &ExamState,
+        kind: ExamKind,
+        strict: bool,
+        graded_at: Duration,
+    ) -> Option<Self> {
+        let result = exam.result()?;
+        Some(Self {
+            started_at: exam.started_at(),
+            graded_at,
+            kind,
+            strict,
+            abandoned: false,
+            questions: exam
+                .questions()
+                .iter()
+                .zip(result.outcomes.iter().zip(&result.confidences))
+                // void questions were never shown
+                .filter(|(_, (outcome, _))| **outcome != QuestionOutcome::Void)
+                .map(|(question, (outcome, confidence))| QuestionAttempt {
+                    num: question.num,
+                    outcome: *outcome,
+                    confidence: *confidence,
+                })
+                .collect(),
+            duration: Some(result.elapsed),
+            pass_threshold: result.pass_threshold,
+            rules: result.rules,
+        })
+    }
+
+    /// The attempt of a strict exam which is recorded when it starts, and is replaced once it is
+    /// graded. If it never is, it stays in the history as abandoned.
+    pub fn abandoned(exam: &ExamState, kind: ExamKind) -> Self {
+        Self {
+            started_at: exam.started_at(),
+            graded_at: exam.started_at(),
+            kind,
+            strict: true,
+            abandoned: true,
+            questions: exam
+                .questions()
+                .iter()
+                .filter(|question| check_question(question).is_ok())
+                .map(|question| QuestionAttempt::new(question.num, QuestionOutcome::Unanswered))
+                .collect(),
+            duration: None,
+            pass_threshold: exam.pass_threshold(),
+            rules: exam.rules(),
+        }
+    }
+
+    pub fn num_correct(&self) -> usize {
+        self.questions
+            .iter()
+            .filter(|q| q.outcome == QuestionOutcome::Correct)
+            .count()
+    }
+
+    /// Whether the attempt passed, by the threshold it was graded by, as [`ExamResult::passed`].
+    ///
+    /// [`ExamResult::passed`]: crate::exam_state::ExamResult::passed
+    pub fn passed(&self) -> bool {
+        !self.abandoned
+            && self.questions.len() - self.num_correct()
+                <= self
+                    .pass_threshold
+                    .allowed_mistakes_by(self.rules.as_ref(), self.questions.len())
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Attempt {
+    /// An attempt for tests: a graded practice exam which started and was graded `graded_at`
+    /// seconds after the unix epoch, with the given outcomes. Tests set what they care about with
+    /// the struct update syntax, e.g. `Attempt { abandoned: true, ..Attempt::sample(1, &[]) }`.
+    pub fn sample(graded_at: u64, questions: &[(usize, QuestionOutcome)]) -> Self {
+        Self {
+            started_at: Duration::from_secs(graded_at),
+            graded_at: Duration::from_secs(graded_at),
+            kind: ExamKind::Practice,
+            strict: false,
+            abandoned: false,
+            questions: questions
+                .iter()
+                .map(|&(num, outcome)| QuestionAttempt::new(num, outcome))
+                .collect(),
+            duration: None,
+            pass_threshold: PassThreshold::Official,
+            rules: None,
+        }
+    }
+}
+
+/// How a single question was answered across the history.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct QuestionStats {
+    pub attempts: usize,
+    pub correct: usize,
+}
+
+impl QuestionStats {
+    /// The share of the attempts which were answered correctly, between 0 and 1.
+    pub fn accuracy(&self) -> f64 {
+        if self.attempts == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.attempts as f64
+    }
+
+    pub fn description_he(&self) -> String {
+        format!(
+            "ענית נכון על שאלה זו ב־{} מתוך {} ניסיונות",
+            self.correct, self.attempts
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+    /// Oldest first.
+    pub attempts: Vec<Attempt>,
+}
+
+impl History {
+    /// Record an attempt, replacing the attempt of the same exam if there is one.
+    pub fn record(&mut self, attempt: Attempt) {
+        match self
+            .attempts
+            .iter_mut()
+            .find(|a| a.started_at == attempt.started_at)
+        {
+            Some(existing) => *existing = attempt,
+            None => self.attempts.push(attempt),
+        }
+    }
+
+    /// Add the attempts of `other` which aren't already recorded, keeping the attempts ordered by
+    /// when they were graded. Returns the number of added attempts.
+    pub fn merge(&mut self, other: History) -> usize {
+        let before = self.attempts.len();
+        for attempt in other.attempts {
+            if !self
+                .attempts
+                .iter()
+                .any(|a| a.started_at == attempt.started_at)
+            {
+                self.attempts.push(attempt);
+            }
+        }
+        self.attempts.sort_by_key(|a| a.graded_at);
+        self.attempts.len() - before
+    }
+
+    /// The questions of the attempts, skipping abandoned attempts as their questions weren't
+    /// graded.
+    pub fn question_attempts(&self) -> impl Iterator<Item = &QuestionAttempt> {
+        self.attempts
+            .iter()
+            .filter(|a| !a.abandoned)
+            .flat_map(|a| a.questions.iter())
+    }
+
+    /// How each question which appeared in any attempt was answered, by its official number.
+    pub fn question_stats(&self) -> HashMap<usize, QuestionStats> {
+        let mut stats = HashMap::<usize, QuestionStats>::new();
+        for question in self.question_attempts() {
+            let entry = stats.entry(question.num).or_default();
+            entry.attempts += 1;
+            if question.outcome == QuestionOutcome::Correct {
+                entry.correct += 1;
+            }
+        }
+        stats
+    }
+
+    /// The questions which appeared in any attempt.
+    pub fn seen(&self) -> HashSet<usize> {
+        self.question_attempts().map(|q| q.num).collect()
+    }
+
+    /// The questions which were answered correctly in any attempt.
+    pub fn answered_correctly(&self) -> HashSet<usize> {
+        self.question_attempts()
+            .filter(|q| q.outcome == QuestionOutcome::Correct)
+            .map(|q| q.num)
+            .collect()
+    }
+
+    /// The questions which were answered wrong in any attempt.
+    pub fn missed(&self) -> HashSet<usize> {
+        self.question_attempts()
+            .filter(|q| q.outcome == QuestionOutcome::Wrong)
+            .map(|q| q.num)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use theory_test_parser::question_parser::Question;
+
+    use super::*;
+
+    #[test]
+    fn seen_and_missed() {
+        let mut history = History::default();
+        history.record(Attempt::sample(
+            1,
+            &[
+                (1, QuestionOutcome::Correct),
+                (2, QuestionOutcome::Wrong),
+                (3, QuestionOutcome::Unanswered),
+            ],
+        ));
+        history.record(Attempt::sample(
+            2,
+            &[(2, QuestionOutcome::Correct), (4, QuestionOutcome::Wrong)],
+        ));
+        let abandoned = Attempt {
+            abandoned: true,
+            ..Attempt::sample(3, &[(5, QuestionOutcome::Wrong)])
+        };
+        history.record(abandoned);
+        assert_eq!(history.seen(), HashSet::from([1, 2, 3, 4]));
+        assert_eq!(history.missed(), HashSet::from([2, 4]));
+        let stats = history.question_stats();
+        assert_eq!(
+            stats[&2],
+            QuestionStats {
+                attempts: 2,
+                correct: 1
+            }
+        );
+        assert_eq!(stats[&2].accuracy(), 0.5);
+        assert_eq!(stats[&3].accuracy(), 0.0);
+        // only graded attempts count
+        assert!(!stats.contains_key(&5));
+    }
+
+    #[test]
+    fn confidences() {
+        let questions = vec![Question::sample(1, 0), Question::sample(2, 0)];
+        let mut exam = ExamState::new(questions, Duration::ZERO);
+        exam.select(0, 0, Duration::ZERO);
+        exam.set_confidence(0, Some(Confidence::Guess), Duration::ZERO);
+        exam.select(1, 1, Duration::ZERO);
+        exam.grade(Duration::ZERO);
+        let attempt = Attempt::from_exam(&exam, ExamKind::Practice, false, Duration::ZERO).unwrap();
+        assert_eq!(
+            attempt.questions,
+            vec![
+                QuestionAttempt {
+                    num: 1,
+                    outcome: QuestionOutcome::Correct,
+                    confidence: Some(Confidence::Guess),
+                },
+                QuestionAttempt::new(2, QuestionOutcome::Wrong),
+            ]
+        );
+        let json = serde_json::to_string(&attempt).unwrap();
+        assert_eq!(serde_json::from_str::<Attempt>(&json).unwrap(), attempt);
+        // attempts recorded before confidences were kept
+        let old: QuestionAttempt =
+            serde_json::from_str(r#"{"num": 2, "outcome": "Wrong"}"#).unwrap();
+        assert_eq!(old, QuestionAttempt::new(2, QuestionOutcome::Wrong));
+    }
+
+    #[test]
+    fn grading_replaces_the_abandoned_attempt() {
+        let mut history = History::default();
+        let graded = Attempt::sample(1, &[(1, QuestionOutcome::Correct)]);
+        history.record(Attempt {
+            abandoned: true,
+            ..graded.clone()
+        });
+        history.record(Attempt::sample(2, &[(2, QuestionOutcome::Correct)]));
+        history.record(graded.clone());
+        assert_eq!(history.attempts.len(), 2);
+        assert_eq!(history.attempts[0], graded);
+        assert_eq!(graded.num_correct(), 1);
+    }
+}
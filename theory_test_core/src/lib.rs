@@ -0,0 +1,25 @@
+//! What an exam and the progress made through exams mean, shared by the app, the command line
+//! tools and the servers so they all grade and record exams alike.
+//!
+//! Nothing here renders or stores anything, and time is never read: functions which depend on
+//! it take the current time, as a duration since the unix epoch, from the caller.
+pub mod achievements;
+pub mod backup;
+pub mod bank_versions;
+pub mod category_filter;
+pub mod class_results;
+pub mod csv_import;
+pub mod direction;
+pub mod exam_state;
+pub mod history;
+pub mod migration;
+pub mod notebook;
+pub mod notes;
+pub mod personal_best;
+pub mod practice_config;
+pub mod preferences;
+pub mod scheduler;
+pub mod simulation;
+pub mod tags;
+pub mod time;
+pub mod time_warnings;
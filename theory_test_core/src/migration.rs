@@ -0,0 +1,215 @@
+//! Versions of the shapes of the persisted values, and the migrations which upgrade the values
+//! stored in older shapes when they are loaded.
+//!
+//! Every value is stored in an envelope with its version, `{"schema": 1, "data": ...}`. Values
+//! stored before there were versions have no envelope and are version 0. The version of a key is
+//! the number of its migrations, so changing the shape of a value is adding a migration to the
+//! [`REGISTRY`], never editing one which shipped.
+use serde_json::{Value, json};
+use thiserror::Error;
+
+/// Upgrades a value from the version it's at to the next one.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+/// The migrations of each key, in order: the first upgrades version 0 to 1, and so on. Keys
+/// which aren't listed never changed shape, and are at version 0.
+pub const REGISTRY: &[(&str, &[Migration])] = &[("history", &[history_started_at])];
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MigrationError {
+    #[error("the stored value isn't valid json")]
+    Corrupt,
+    /// Stored by a newer version of the app, which this one can't read. It should be kept as is.
+    #[error("the stored value is of version {0}, newer than this version of the app")]
+    FutureVersion(u32),
+    #[error("upgrading the stored value from version {from} failed: {reason}")]
+    Failed { from: u32, reason: String },
+}
+
+/// A stored value, upgraded to the current version.
+#[derive(Debug, PartialEq)]
+pub struct Upgraded {
+    /// The version it was stored at.
+    pub from: u32,
+    pub value: Value,
+}
+
+impl Upgraded {
+    /// Whether it was stored at an older version, so it should be stored again.
+    pub fn migrated(&self, key: &str) -> bool {
+        self.from != version(key)
+    }
+}
+
+fn migrations(key: &str) -> &'static [Migration] {
+    REGISTRY
+        .iter()
+        .find(|(registered, _)| *registered == key)
+        .map_or(&[], |(_, migrations)| *migrations)
+}
+
+/// The current version of the values of `key`.
+pub fn version(key: &str) -> u32 {
+    migrations(key).len() as u32
+}
+
+/// `value` in the envelope of the current version of `key`, to be stored.
+pub fn wrap(key: &str, value: Value) -> Value {
+    json!({ "schema": version(key), "data": value })
+}
+
+/// Where the value of `key` is kept as it was before it was upgraded from `version`, in case the
+/// migration got it wrong.
+pub fn backup_key(key: &str, version: u32) -> String {
+    format!("{key}.v{version}-backup")
+}
+
+/// The version of a stored value and the value out of its envelope.
+fn unwrap(stored: Value) -> (u32, Value) {
+    match stored {
+        Value::Object(mut envelope)
+            if envelope.len() == 2
+                && envelope.contains_key("data")
+                && envelope.get("schema").is_some_and(Value::is_u64) =>
+        {
+            let version = envelope["schema"].as_u64().unwrap_or_default();
+            let version = version.try_into().unwrap_or(u32::MAX);
+            (version, envelope.remove("data").unwrap_or_default())
+        }
+        unversioned => (0, unversioned),
+    }
+}
+
+/// Parse the value stored at `key` and upgrade it to the current version of the key.
+pub fn upgrade(key: &str, stored: &str) -> Result<Upgraded, MigrationError> {
+    let stored = serde_json::from_str(stored).map_err(|_| MigrationError::Corrupt)?;
+    let (from, mut value) = unwrap(stored);
+    let migrations = migrations(key);
+    if from as usize > migrations.len() {
+        return Err(MigrationError::FutureVersion(from));
+    }
+    for migration in &migrations[from as usize..] {
+        value = migration(value).map_err(|reason| MigrationError::Failed { from, reason })?;
+    }
+    Ok(Upgraded { from, value })
+}
+
+/// History v0 → v1: attempts recorded before exams had a start time all got the start time 0,
+/// which identifies an attempt, so they were taken for the same attempt. They start when they
+/// were graded instead, which is unique as well.
+fn history_started_at(mut history: Value) -> Result<Value, String> {
+    let Some(attempts) = history.get_mut("attempts") else {
+        return Ok(history);
+    };
+    let attempts = attempts
+        .as_array_mut()
+        .ok_or("the attempts aren't a list")?;
+    for attempt in attempts {
+        let attempt = attempt
+            .as_object_mut()
+            .ok_or("an attempt isn't an object")?;
+        let started = attempt
+            .get("started_at")
+            .is_some_and(|started_at| started_at != &json!({ "secs": 0, "nanos": 0 }));
+        if !started {
+            let graded_at = attempt
+                .get("graded_at")
+                .cloned()
+                .ok_or("an attempt has no grading time")?;
+            attempt.insert("started_at".to_string(), graded_at);
+        }
+    }
+    Ok(history)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::History;
+
+    /// The history as stored by each version, the same exams in each.
+    const HISTORY_FIXTURES: [&str; 2] = [
+        include_str!("fixtures/history-v0.json"),
+        include_str!("fixtures/history-v1.json"),
+    ];
+
+    #[test]
+    fn every_history_version_loads() {
+        assert_eq!(HISTORY_FIXTURES.len() as u32, version("history") + 1);
+        let current = upgrade("history", HISTORY_FIXTURES[1]).unwrap();
+        assert!(!current.migrated("history"));
+        let current: History = serde_json::from_value(current.value).unwrap();
+        assert_eq!(current.attempts.len(), 3);
+        for (version, fixture) in HISTORY_FIXTURES.iter().enumerate() {
+            let upgraded = upgrade("history", fixture).unwrap();
+            assert_eq!(upgraded.from, version as u32);
+            let history: History = serde_json::from_value(upgraded.value).unwrap();
+            assert_eq!(history, current, "version {version}");
+        }
+    }
+
+    #[test]
+    fn history_attempts_get_a_start() {
+        let upgraded = upgrade("history", HISTORY_FIXTURES[0]).unwrap();
+        assert!(upgraded.migrated("history"));
+        let history: History = serde_json::from_value(upgraded.value).unwrap();
+        let starts = history
+            .attempts
+            .iter()
+            .map(|attempt| attempt.started_at.as_secs())
+            .collect::<Vec<_>>();
+        // the attempt which had a start keeps it
+        assert_eq!(starts, [1_700_000_100, 1_700_000_500, 1_700_000_800]);
+        assert_eq!(
+            upgrade("history", r#"{"attempts": 3}"#),
+            Err(MigrationError::Failed {
+                from: 0,
+                reason: "the attempts aren't a list".to_string()
+            })
+        );
+        // a history stored by the defaults has nothing to upgrade
+        assert_eq!(upgrade("history", "{}").unwrap().value, json!({}));
+    }
+
+    #[test]
+    fn envelopes() {
+        assert_eq!(
+            wrap("history", json!({ "attempts": [] })),
+            json!({ "schema": 1, "data": { "attempts": [] } })
+        );
+        assert_eq!(wrap("notes", json!({})), json!({ "schema": 0, "data": {} }));
+        let notes = upgrade("notes", r#"{"schema": 0, "data": {"7": "note"}}"#).unwrap();
+        assert_eq!(
+            notes,
+            Upgraded {
+                from: 0,
+                value: json!({ "7": "note" })
+            }
+        );
+        // values stored before there were versions
+        assert_eq!(upgrade("notes", r#"{"7": "note"}"#).unwrap(), notes);
+        assert_eq!(
+            upgrade("order", r#""random""#).unwrap().value,
+            json!("random")
+        );
+        // an object which only looks like an envelope in part is a value
+        assert_eq!(
+            upgrade("notes", r#"{"schema": 0}"#).unwrap().value,
+            json!({ "schema": 0 })
+        );
+    }
+
+    #[test]
+    fn newer_and_corrupt_values() {
+        assert_eq!(
+            upgrade("history", r#"{"schema": 2, "data": {}}"#),
+            Err(MigrationError::FutureVersion(2))
+        );
+        assert_eq!(
+            upgrade("notes", r#"{"schema": 1, "data": {}}"#),
+            Err(MigrationError::FutureVersion(1))
+        );
+        assert_eq!(upgrade("notes", "{"), Err(MigrationError::Corrupt));
+        assert_eq!(backup_key("history", 0), "history.v0-backup");
+    }
+}
@@ -0,0 +1,148 @@
+//! The mistake notebook: every question which was answered wrong, until it is answered right
+//! enough times in a row, or removed by hand.
+//!
+//! The notebook is derived from the history, only the manual removals are stored separately.
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{exam_state::QuestionOutcome, history::History};
+
+/// How many correct answers in a row take a question out of the notebook.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct GraduationStreak(pub usize);
+
+impl Default for GraduationStreak {
+    fn default() -> Self {
+        Self(2)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct NotebookEntry {
+    /// The official number of the question.
+    pub num: usize,
+    pub misses: usize,
+    /// When the question was last answered wrong, as a duration since the unix epoch.
+    pub last_missed: Duration,
+}
+
+/// Questions removed from the notebook by hand, with when they were. A question which is missed
+/// again after it was removed comes back.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Removals(HashMap<usize, Duration>);
+
+impl Removals {
+    pub fn remove(&mut self, num: usize, now: Duration) {
+        self.0.insert(num, now);
+    }
+
+    /// Add the removals of `newer`, replacing the removals of the same questions.
+    pub fn extend(&mut self, newer: Removals) {
+        self.0.extend(newer.0);
+    }
+}
+
+/// The questions of the notebook, ordered by their official number.
+pub fn notebook(
+    history: &History,
+    removals: &Removals,
+    graduation: GraduationStreak,
+) -> Vec<NotebookEntry> {
+    // (misses, last missed, correct answers since)
+    let mut questions = BTreeMap::<usize, (usize, Duration, usize)>::new();
+    for attempt in history.attempts.iter().filter(|a| !a.abandoned) {
+        for question in &attempt.questions {
+            match question.outcome {
+                QuestionOutcome::Wrong => {
+                    let entry = questions.entry(question.num).or_default();
+                    *entry = (entry.0 + 1, attempt.graded_at, 0);
+                }
+                QuestionOutcome::Correct => {
+                    if let Some(entry) = questions.get_mut(&question.num) {
+                        entry.2 += 1;
+                    }
+                }
+                QuestionOutcome::Unanswered | QuestionOutcome::Void => {}
+            }
+        }
+    }
+    questions
+        .into_iter()
+        .filter(|(num, (_, last_missed, streak))| {
+            *streak < graduation.0
+                && removals
+                    .0
+                    .get(num)
+                    .is_none_or(|removed_at| removed_at < last_missed)
+        })
+        .map(|(num, (misses, last_missed, _))| NotebookEntry {
+            num,
+            misses,
+            last_missed,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::Attempt;
+
+    fn nums(entries: &[NotebookEntry]) -> Vec<usize> {
+        entries.iter().map(|e| e.num).collect()
+    }
+
+    #[test]
+    fn graduation() {
+        use QuestionOutcome::*;
+        let mut history = History::default();
+        history.record(Attempt::sample(1, &[(1, Wrong), (2, Correct), (3, Wrong)]));
+        history.record(Attempt::sample(
+            2,
+            &[(1, Correct), (3, Wrong), (4, Unanswered)],
+        ));
+        let removals = Removals::default();
+        let entries = notebook(&history, &removals, GraduationStreak::default());
+        assert_eq!(nums(&entries), vec![1, 3]);
+        assert_eq!(entries[1].misses, 2);
+        assert_eq!(entries[1].last_missed, Duration::from_secs(2));
+        history.record(Attempt::sample(3, &[(1, Correct), (3, Correct)]));
+        // 1 was answered right twice in a row
+        assert_eq!(
+            nums(&notebook(&history, &removals, GraduationStreak::default())),
+            vec![3]
+        );
+        assert_eq!(
+            nums(&notebook(&history, &removals, GraduationStreak(1))),
+            Vec::<usize>::new()
+        );
+        // a miss resets the streak
+        history.record(Attempt::sample(4, &[(3, Wrong), (3, Correct)]));
+        assert_eq!(
+            nums(&notebook(&history, &removals, GraduationStreak(1))),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            nums(&notebook(&history, &removals, GraduationStreak(2))),
+            vec![3]
+        );
+    }
+
+    #[test]
+    fn removal_until_missed_again() {
+        use QuestionOutcome::*;
+        let mut history = History::default();
+        history.record(Attempt::sample(1, &[(1, Wrong), (2, Wrong)]));
+        let mut removals = Removals::default();
+        removals.remove(1, Duration::from_secs(5));
+        let graduation = GraduationStreak::default();
+        assert_eq!(nums(&notebook(&history, &removals, graduation)), vec![2]);
+        history.record(Attempt::sample(6, &[(1, Wrong)]));
+        assert_eq!(nums(&notebook(&history, &removals, graduation)), vec![1, 2]);
+    }
+}
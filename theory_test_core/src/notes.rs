@@ -0,0 +1,74 @@
+//! Private notes on questions, kept by their official number.
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+/// The longest note, in characters.
+pub const MAX_NOTE_CHARS: usize = 500;
+
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Notes(BTreeMap<usize, String>);
+
+impl Notes {
+    pub fn get(&self, num: usize) -> Option<&str> {
+        self.0.get(&num).map(String::as_str)
+    }
+
+    /// Set the note of a question, truncated to [`MAX_NOTE_CHARS`]. An empty note deletes it.
+    pub fn set(&mut self, num: usize, note: &str) {
+        let note = note.trim().chars().take(MAX_NOTE_CHARS).collect::<String>();
+        if note.is_empty() {
+            self.0.remove(&num);
+        } else {
+            self.0.insert(num, note);
+        }
+    }
+
+    /// The questions which have a note.
+    pub fn nums(&self) -> HashSet<usize> {
+        self.0.keys().copied().collect()
+    }
+
+    /// Add the notes of `newer`, replacing the notes of the same questions.
+    pub fn extend(&mut self, newer: Notes) {
+        self.0.extend(newer.0);
+    }
+
+    /// Add the notes of `other` to the questions which don't have one, returning the number of
+    /// added notes.
+    pub fn merge(&mut self, other: Notes) -> usize {
+        let mut added = 0;
+        for (num, note) in other.0 {
+            if !self.0.contains_key(&num) {
+                self.set(num, &note);
+                added += 1;
+            }
+        }
+        added
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn editing_notes() {
+        let mut notes = Notes::default();
+        notes.set(7, "  בלבלתי עם תמרור 302 ");
+        assert_eq!(notes.get(7), Some("בלבלתי עם תמרור 302"));
+        notes.set(8, &"א".repeat(MAX_NOTE_CHARS + 10));
+        assert_eq!(notes.get(8).unwrap().chars().count(), MAX_NOTE_CHARS);
+        notes.set(7, " ");
+        assert_eq!(notes.get(7), None);
+        assert_eq!(notes.nums(), HashSet::from([8]));
+
+        let mut other = Notes::default();
+        other.set(8, "other");
+        other.set(9, "new");
+        assert_eq!(notes.merge(other), 1);
+        assert_eq!(notes.get(9), Some("new"));
+        assert_ne!(notes.get(8), Some("other"));
+    }
+}
@@ -0,0 +1,192 @@
+//! The best score of each exam configuration.
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::LicenseClass;
+
+use crate::{direction::isolate, exam_state::ExamKind};
+
+/// Exams of the same configuration have comparable scores.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ExamConfig {
+    pub kind: ExamKind,
+    pub num_questions: usize,
+    pub class: LicenseClass,
+}
+
+impl ExamConfig {
+    fn key(&self) -> String {
+        let kind = match self.kind {
+            ExamKind::Real => "real",
+            ExamKind::Practice => "practice",
+            ExamKind::DailyQuestion => "daily",
+            ExamKind::Drill => "drill",
+            ExamKind::SignDrill => "signs",
+            ExamKind::Imported => "imported",
+        };
+        format!("{}-{}-{}", kind, self.num_questions, self.class.code())
+    }
+
+    /// The inverse of [`ExamConfig::key`].
+    fn from_key(key: &str) -> Option<Self> {
+        let mut parts = key.split('-');
+        let kind = match parts.next()? {
+            "real" => ExamKind::Real,
+            "practice" => ExamKind::Practice,
+            "daily" => ExamKind::DailyQuestion,
+            "drill" => ExamKind::Drill,
+            "signs" => ExamKind::SignDrill,
+            "imported" => ExamKind::Imported,
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            num_questions: parts.next()?.parse().ok()?,
+            class: LicenseClass::from_code(parts.next()?)?,
+        })
+    }
+
+    pub fn description_he(&self) -> String {
+        let kind = match self.kind {
+            ExamKind::Real => "מבחן אמיתי",
+            ExamKind::Practice => "מבחן תרגול",
+            ExamKind::DailyQuestion => "שאלת היום",
+            ExamKind::Drill => "תרגול מהיר",
+            ExamKind::SignDrill => "תרגול תמרורים",
+            ExamKind::Imported => "תרגול מיובא",
+        };
+        format!(
+            "{}, {} שאלות, דרגה {}",
+            kind,
+            self.num_questions,
+            isolate(self.class.code())
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BestScore {
+    pub correct: usize,
+    /// When the exam was graded, as a duration since the unix epoch.
+    pub graded_at: Duration,
+}
+
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonalBests(HashMap<String, BestScore>);
+
+impl PersonalBests {
+    pub fn get(&self, config: &ExamConfig) -> Option<BestScore> {
+        self.0.get(&config.key()).copied()
+    }
+
+    /// Record a score, returning whether it is a new best. A tie keeps the earlier record.
+    pub fn record(&mut self, config: &ExamConfig, score: BestScore) -> bool {
+        let best = self.0.entry(config.key()).or_insert(score);
+        if score.correct > best.correct {
+            *best = score;
+        }
+        *best == score
+    }
+
+    /// The bests of every configuration, ordered by configuration.
+    pub fn all(&self) -> Vec<(ExamConfig, BestScore)> {
+        let mut all = self
+            .0
+            .iter()
+            .filter_map(|(key, score)| Some((ExamConfig::from_key(key)?, *score)))
+            .collect::<Vec<_>>();
+        all.sort_by_key(|(config, _)| config.key());
+        all
+    }
+
+    /// Record every score of `other`, returning the number of new bests.
+    pub fn merge(&mut self, other: PersonalBests) -> usize {
+        let mut improved = 0;
+        for (key, score) in other.0 {
+            let Some(config) = ExamConfig::from_key(&key) else {
+                continue;
+            };
+            if self.get(&config) != Some(score) && self.record(&config, score) {
+                improved += 1;
+            }
+        }
+        improved
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn score(correct: usize, graded_at: u64) -> BestScore {
+        BestScore {
+            correct,
+            graded_at: Duration::from_secs(graded_at),
+        }
+    }
+
+    #[test]
+    fn bests() {
+        let real = ExamConfig {
+            kind: ExamKind::Real,
+            num_questions: 30,
+            class: LicenseClass::B,
+        };
+        let practice = ExamConfig {
+            kind: ExamKind::Practice,
+            ..real
+        };
+        let mut bests = PersonalBests::default();
+        assert!(bests.record(&real, score(25, 1)));
+        assert!(bests.record(&practice, score(20, 2)));
+        assert!(!bests.record(&real, score(24, 3)));
+        // ties keep the earliest record
+        assert!(!bests.record(&real, score(25, 4)));
+        assert_eq!(bests.get(&real), Some(score(25, 1)));
+        assert!(bests.record(&real, score(28, 5)));
+        assert_eq!(bests.get(&real), Some(score(28, 5)));
+        assert_eq!(
+            bests.all(),
+            vec![(practice, score(20, 2)), (real, score(28, 5))]
+        );
+        bests.clear();
+        assert_eq!(bests.get(&real), None);
+    }
+
+    #[test]
+    fn merge() {
+        let real = ExamConfig {
+            kind: ExamKind::Real,
+            num_questions: 30,
+            class: LicenseClass::B,
+        };
+        let practice = ExamConfig {
+            kind: ExamKind::Practice,
+            ..real
+        };
+        let mut bests = PersonalBests::default();
+        bests.record(&real, score(25, 1));
+        let mut other = PersonalBests::default();
+        other.record(&real, score(25, 2));
+        other.record(&practice, score(10, 3));
+        assert_eq!(bests.merge(other.clone()), 1);
+        assert_eq!(bests.get(&real), Some(score(25, 1)));
+        assert_eq!(bests.merge(other), 0);
+    }
+
+    #[test]
+    fn config_keys() {
+        let config = ExamConfig {
+            kind: ExamKind::Practice,
+            num_questions: 12,
+            class: LicenseClass::C1,
+        };
+        assert_eq!(ExamConfig::from_key(&config.key()), Some(config));
+        assert_eq!(ExamConfig::from_key("practice-x-B"), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! The configuration of the last practice exam, to start the next one from.
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::Question;
+
+use crate::category_filter::CategoryFilter;
+
+pub const DEFAULT_NUM_QUESTIONS: usize = 30;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PracticeConfig {
+    pub num_questions: usize,
+    pub feedback: bool,
+    /// The included categories, as in the `category` route parameter.
+    pub category: String,
+    /// The excluded categories, as in the `exclude` route parameter.
+    pub exclude: String,
+    /// The tag of the questions, as in the `tag` route parameter.
+    pub tag: String,
+}
+
+impl Default for PracticeConfig {
+    fn default() -> Self {
+        Self {
+            num_questions: DEFAULT_NUM_QUESTIONS,
+            feedback: false,
+            category: String::new(),
+            exclude: String::new(),
+            tag: String::new(),
+        }
+    }
+}
+
+impl PracticeConfig {
+    pub fn new(num_questions: usize, feedback: bool, filter: &CategoryFilter) -> Self {
+        Self {
+            num_questions,
+            feedback,
+            category: filter.include_query(),
+            exclude: filter.exclude_query(),
+            tag: String::new(),
+        }
+    }
+
+    /// The configuration drawing only questions with `tag`, any question when empty.
+    pub fn with_tag(self, tag: &str) -> Self {
+        Self {
+            tag: tag.to_string(),
+            ..self
+        }
+    }
+
+    pub fn filter(&self) -> CategoryFilter {
+        CategoryFilter::from_query(&self.category, &self.exclude)
+    }
+
+    /// The configuration fitted to `pool`, which may have changed since it was stored: unknown
+    /// categories are dropped, a filter no question matches is cleared, and the number of
+    /// questions is clamped to the questions which match. The tag is kept, the pool doesn't know
+    /// the tags of its questions.
+    pub fn validated(&self, pool: &[Question]) -> Self {
+        let mut filter = self.filter();
+        let mut matching = pool.iter().filter(|q| filter.matches(q)).count();
+        if matching == 0 {
+            filter = CategoryFilter::default();
+            matching = pool.len();
+        }
+        Self::new(
+            self.num_questions.clamp(1, matching.max(1)),
+            self.feedback,
+            &filter,
+        )
+        .with_tag(&self.tag)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use theory_test_parser::question_parser::QuestionCategory;
+
+    #[test]
+    fn validation() {
+        let pool = vec![
+            Question::sample(1, 0),
+            Question::sample(2, 0),
+            Question {
+                category: QuestionCategory::RoadSigns,
+                ..Question::sample(3, 0)
+            },
+        ];
+        let filter = CategoryFilter {
+            include: vec![QuestionCategory::Safety],
+            exclude: Vec::new(),
+        };
+        let config = PracticeConfig::new(20, true, &filter);
+        let validated = config.validated(&pool);
+        assert_eq!(validated, PracticeConfig::new(2, true, &filter));
+        assert_eq!(validated.category, QuestionCategory::Safety.as_str_he());
+        let fitting = PracticeConfig::new(1, false, &filter);
+        assert_eq!(fitting.validated(&pool), fitting);
+        let tagged = fitting.with_tag("מבלבל");
+        assert_eq!(tagged.validated(&pool), tagged);
+
+        // a filter which no longer matches anything is cleared
+        let stale = PracticeConfig {
+            category: QuestionCategory::CarKnowledge.as_str_he().to_string(),
+            ..PracticeConfig::default()
+        };
+        assert_eq!(
+            stale.validated(&pool),
+            PracticeConfig {
+                num_questions: 3,
+                ..PracticeConfig::default()
+            }
+        );
+        assert_eq!(
+            PracticeConfig {
+                num_questions: 0,
+                ..PracticeConfig::default()
+            }
+            .validated(&pool)
+            .num_questions,
+            1
+        );
+    }
+}
@@ -0,0 +1,221 @@
+//! User preferences, as they are persisted.
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+use crate::{
+    exam_state::PassThreshold, notebook::GraduationStreak, practice_config::PracticeConfig,
+};
+
+/// The set of colors the categories are drawn with.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Colors told apart with every common kind of color blindness (Okabe–Ito).
+    Colorblind,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 2] = [Palette::Standard, Palette::Colorblind];
+}
+
+/// The language of the app's own texts. Questions are in the language of their bank.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug, Serialize, Deserialize)]
+pub enum Locale {
+    /// The baseline, which has every text.
+    #[default]
+    He,
+    En,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::He, Locale::En];
+
+    /// The code of the language, e.g. "he".
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::He => "he",
+            Locale::En => "en",
+        }
+    }
+}
+
+/// How questions are numbered in exams and their results.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum QuestionNumbering {
+    /// 1, 2, 3... in the order of the exam.
+    #[default]
+    Sequential,
+    /// The official number of the question in the bank, e.g. 0862.
+    Canonical,
+}
+
+impl QuestionNumbering {
+    /// The label of the question at `idx` in the exam.
+    pub fn label(&self, idx: usize, question: &Question) -> String {
+        match self {
+            QuestionNumbering::Sequential => (idx + 1).to_string(),
+            QuestionNumbering::Canonical => format!("{:04}", question.num),
+        }
+    }
+}
+
+/// The order in which the questions picked for an exam are presented.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub enum QuestionOrder {
+    /// The order in which they were picked.
+    #[default]
+    Random,
+    /// The questions of each category together, in the order the categories are listed.
+    ByCategory,
+    /// By the official number of the question in the bank.
+    ByNumber,
+}
+
+impl QuestionOrder {
+    /// Reorder `questions`. The sort is stable, so the same picked questions are always presented
+    /// in the same order.
+    pub fn arrange(&self, questions: &mut [Question]) {
+        match self {
+            QuestionOrder::Random => {}
+            QuestionOrder::ByCategory => questions.sort_by_key(|q| {
+                QuestionCategory::ALL
+                    .iter()
+                    .position(|category| *category == q.category)
+            }),
+            QuestionOrder::ByNumber => questions.sort_by_key(|q| q.num),
+        }
+    }
+}
+
+/// The zoom of the question text and answers, as an index into [`TextZoom::LEVELS`].
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextZoom(usize);
+
+impl TextZoom {
+    pub const LEVELS: [f32; 5] = [0.85, 1.0, 1.2, 1.4, 1.6];
+    const DEFAULT_LEVEL: usize = 1;
+
+    /// The factor by which the text is scaled.
+    pub fn scale(&self) -> f32 {
+        Self::LEVELS[self.0.min(Self::LEVELS.len() - 1)]
+    }
+
+    pub fn zoom_in(self) -> Self {
+        Self((self.0 + 1).min(Self::LEVELS.len() - 1))
+    }
+
+    pub fn zoom_out(self) -> Self {
+        Self(self.0.saturating_sub(1))
+    }
+
+    pub fn can_zoom_in(&self) -> bool {
+        self.0 < Self::LEVELS.len() - 1
+    }
+
+    pub fn can_zoom_out(&self) -> bool {
+        self.0 > 0
+    }
+}
+
+impl Default for TextZoom {
+    fn default() -> Self {
+        Self(Self::DEFAULT_LEVEL)
+    }
+}
+
+#[derive(Clone, PartialEq, Default, Serialize, Deserialize)]
+// fields added in later versions are filled with their defaults
+#[serde(default)]
+pub struct Preferences {
+    /// Hide the category of questions until the exam is graded, as in the real exam.
+    pub hide_category_during_exam: bool,
+    pub question_numbering: QuestionNumbering,
+    /// Zoom of the question text and answers, independent of the rest of the page.
+    pub question_zoom: TextZoom,
+    /// Play sounds for answers with immediate feedback and for the exam timer.
+    pub sound_feedback: bool,
+    pub notebook_graduation: GraduationStreak,
+    pub question_order: QuestionOrder,
+    /// Move on to the next unanswered question once a question is answered.
+    pub auto_advance: bool,
+    /// The threshold of practice exams, the real exam always uses the official one.
+    pub pass_threshold: PassThreshold,
+    /// The colors of the question categories.
+    pub category_palette: Palette,
+    /// The configuration of the last practice exam which was started.
+    pub last_practice: Option<PracticeConfig>,
+    /// The language of the texts of the app.
+    pub locale: Locale,
+    /// Read each question aloud when it's shown, where questions are shown one at a time.
+    pub read_aloud: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use theory_test_parser::question_parser::QuestionCategory;
+
+    #[test]
+    fn numbering_labels() {
+        let question = Question {
+            question: "0062. question".to_string(),
+            ..Question::sample(62, 0)
+        };
+        assert_eq!(QuestionNumbering::Sequential.label(4, &question), "5");
+        assert_eq!(QuestionNumbering::Canonical.label(4, &question), "0062");
+    }
+
+    #[test]
+    fn missing_fields_are_defaulted() {
+        let preferences: Preferences =
+            serde_json::from_str(r#"{"hide_category_during_exam":true}"#).unwrap();
+        assert!(preferences.hide_category_during_exam);
+        assert!(preferences.question_numbering == QuestionNumbering::Sequential);
+        assert!(preferences.question_zoom == TextZoom::default());
+        assert!(!preferences.sound_feedback);
+        assert!(preferences.notebook_graduation == GraduationStreak(2));
+        assert!(preferences.question_order == QuestionOrder::Random);
+        assert!(preferences.last_practice.is_none());
+        assert!(!preferences.auto_advance);
+        assert_eq!(preferences.pass_threshold, PassThreshold::Official);
+        assert_eq!(preferences.category_palette, Palette::Standard);
+        assert!(!preferences.read_aloud);
+    }
+
+    #[test]
+    fn question_orders() {
+        let question = |num, category| Question {
+            category,
+            ..Question::sample(num, 0)
+        };
+        let questions = vec![
+            question(5, QuestionCategory::Safety),
+            question(3, QuestionCategory::TrafficLaws),
+            question(9, QuestionCategory::Safety),
+            question(1, QuestionCategory::RoadSigns),
+        ];
+        let arranged = |order: QuestionOrder| {
+            let mut questions = questions.clone();
+            order.arrange(&mut questions);
+            questions.iter().map(|q| q.num).collect::<Vec<_>>()
+        };
+        assert_eq!(arranged(QuestionOrder::Random), vec![5, 3, 9, 1]);
+        assert_eq!(arranged(QuestionOrder::ByCategory), vec![3, 1, 5, 9]);
+        assert_eq!(arranged(QuestionOrder::ByNumber), vec![1, 3, 5, 9]);
+    }
+
+    #[test]
+    fn zoom_levels() {
+        let zoom = TextZoom::default();
+        assert_eq!(zoom.scale(), 1.0);
+        let min = (0..10).fold(zoom, |zoom, _| zoom.zoom_out());
+        assert!(!min.can_zoom_out());
+        assert_eq!(min.scale(), TextZoom::LEVELS[0]);
+        let max = (0..10).fold(zoom, |zoom, _| zoom.zoom_in());
+        assert!(!max.can_zoom_in());
+        assert_eq!(max.scale(), TextZoom::LEVELS[TextZoom::LEVELS.len() - 1]);
+        // a level stored by a version with more levels is clamped
+        assert_eq!(TextZoom(100).scale(), max.scale());
+    }
+}
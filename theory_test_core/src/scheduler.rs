@@ -0,0 +1,509 @@
+//! Spaced repetition of the questions seen in exams, and a plan of the days left until the exam.
+//!
+//! The schedule is a Leitner system derived from the history rather than stored: each correct
+//! answer moves a question to a longer interval, each wrong one back to the first.
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use theory_test_parser::question_parser::{ExamQuestions, QuestionCategory};
+
+use crate::{
+    exam_state::QuestionOutcome,
+    history::History,
+    time::{self, CivilDateTime},
+};
+
+/// The days until a question is reviewed again, by how many times in a row it was answered
+/// correctly.
+pub const INTERVALS_DAYS: [i64; 5] = [1, 2, 4, 8, 16];
+
+/// When a question which was seen in an exam should be reviewed.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Card {
+    /// The index of its interval in [`INTERVALS_DAYS`].
+    pub level: usize,
+    /// The local day it is due on, as a number of days since the unix epoch.
+    pub due_day: i64,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Scheduler {
+    cards: HashMap<usize, Card>,
+    now: Duration,
+    today: i64,
+}
+
+impl Scheduler {
+    /// The schedule of the questions of `history`, at `now` in the time zone of
+    /// `utc_offset_secs`.
+    pub fn new(history: &History, now: Duration, utc_offset_secs: i64) -> Self {
+        let mut attempts = history
+            .attempts
+            .iter()
+            .filter(|a| !a.abandoned)
+            .collect::<Vec<_>>();
+        attempts.sort_by_key(|a| a.graded_at);
+        let mut cards = HashMap::<usize, Card>::new();
+        for attempt in attempts {
+            let day = time::local_day(attempt.graded_at, utc_offset_secs);
+            for question in &attempt.questions {
+                let level = match (cards.get(&question.num), question.outcome) {
+                    (_, QuestionOutcome::Void) => continue,
+                    (Some(card), QuestionOutcome::Correct) => {
+                        (card.level + 1).min(INTERVALS_DAYS.len() - 1)
+                    }
+                    (None, QuestionOutcome::Correct) => 1,
+                    (_, QuestionOutcome::Wrong | QuestionOutcome::Unanswered) => 0,
+                };
+                cards.insert(
+                    question.num,
+                    Card {
+                        level,
+                        due_day: day + INTERVALS_DAYS[level],
+                    },
+                );
+            }
+        }
+        Self {
+            cards,
+            now,
+            today: time::local_day(now, utc_offset_secs),
+        }
+    }
+
+    /// The card of the question numbered `num`, `None` if it was never seen.
+    pub fn card(&self, num: usize) -> Option<Card> {
+        self.cards.get(&num).copied()
+    }
+
+    /// The questions due today or earlier, the longest overdue first.
+    pub fn due(&self) -> Vec<usize> {
+        let mut due = self
+            .cards
+            .iter()
+            .filter(|(_, card)| card.due_day <= self.today)
+            .map(|(num, card)| (card.due_day, *num))
+            .collect::<Vec<_>>();
+        due.sort_unstable();
+        due.into_iter().map(|(_, num)| num).collect()
+    }
+
+    /// Spread the reviews due before `exam_day` and the questions of `bank` which were never
+    /// seen over the days from today until the exam, at most `daily_capacity` questions a day.
+    ///
+    /// Reviews come first, on the day they are due or on the first later day with room. The new
+    /// questions fill the rest evenly, except that no day is left with room while questions
+    /// remain for the days after it which can't take them.
+    pub fn study_plan(
+        &self,
+        bank: &ExamQuestions,
+        exam_day: i64,
+        daily_capacity: usize,
+    ) -> StudyPlan {
+        let questions = bank
+            .questions
+            .iter()
+            .map(|q| (q.num, q))
+            .collect::<HashMap<_, _>>();
+        let mut days = (self.today..exam_day)
+            .map(|day| StudyDay {
+                day,
+                reviews: Vec::new(),
+                new: Vec::new(),
+                categories: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut reviews = self
+            .cards
+            .iter()
+            .filter(|(num, card)| card.due_day < exam_day && questions.contains_key(num))
+            .map(|(num, card)| (card.due_day, *num))
+            .collect::<Vec<_>>();
+        reviews.sort_unstable();
+        let mut reviews = reviews.into_iter().peekable();
+        let mut backlog = VecDeque::new();
+        for day in &mut days {
+            while let Some((_, num)) = reviews.next_if(|(due_day, _)| *due_day <= day.day) {
+                backlog.push_back(num);
+            }
+            let take = backlog.len().min(daily_capacity);
+            day.reviews.extend(backlog.drain(..take));
+        }
+
+        let mut unseen = questions
+            .keys()
+            .copied()
+            .filter(|num| !self.cards.contains_key(num))
+            .collect::<Vec<_>>();
+        unseen.sort_unstable();
+        let mut unseen = unseen.into_iter();
+        let room = |day: &StudyDay| daily_capacity - day.reviews.len();
+        let mut room_after = days.iter().map(room).sum::<usize>();
+        let num_days = days.len();
+        for (i, day) in days.iter_mut().enumerate() {
+            room_after -= room(day);
+            let left = unseen.len();
+            let even = left.div_ceil(num_days - i);
+            let take = even.max(left.saturating_sub(room_after)).min(room(day));
+            day.new.extend(unseen.by_ref().take(take));
+        }
+
+        for day in &mut days {
+            let count = |category: QuestionCategory| {
+                day.reviews
+                    .iter()
+                    .chain(&day.new)
+                    .filter(|num| questions.get(num).is_some_and(|q| q.category == category))
+                    .count()
+            };
+            let mut counts = QuestionCategory::ALL.map(|category| (category, count(category)));
+            // stable, so categories with as many questions keep their usual order
+            counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+            day.categories = counts
+                .into_iter()
+                .filter(|(_, count)| *count > 0)
+                .map(|(category, _)| category)
+                .collect();
+        }
+
+        StudyPlan {
+            days,
+            exam_day,
+            shortfall: backlog.len() + reviews.count() + unseen.len(),
+            created_at: self.now,
+        }
+    }
+}
+
+/// What to study on a single day of a [`StudyPlan`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct StudyDay {
+    /// The local day, as a number of days since the unix epoch.
+    pub day: i64,
+    /// The official numbers of the questions to review.
+    pub reviews: Vec<usize>,
+    /// The official numbers of the questions which were never seen.
+    pub new: Vec<usize>,
+    /// The categories of the questions, those with the most questions first.
+    pub categories: Vec<QuestionCategory>,
+}
+
+impl StudyDay {
+    pub fn num_questions(&self) -> usize {
+        self.reviews.len() + self.new.len()
+    }
+
+    /// The title of the day in a calendar, e.g. "תיאוריה: 40 שאלות — תמרורים ובטיחות".
+    pub fn summary_he(&self) -> String {
+        let questions = match self.num_questions() {
+            1 => "שאלה אחת".to_string(),
+            n => format!("{n} שאלות"),
+        };
+        let categories = self
+            .categories
+            .iter()
+            .map(|category| category.as_str_he())
+            .collect::<Vec<_>>();
+        match categories.split_last() {
+            None => format!("תיאוריה: {questions}"),
+            Some((only, [])) => format!("תיאוריה: {questions} — {only}"),
+            Some((last, rest)) => format!("תיאוריה: {questions} — {} ו{last}", rest.join(", ")),
+        }
+    }
+
+    pub fn description_he(&self) -> String {
+        format!(
+            "{} שאלות לחזרה, {} שאלות חדשות",
+            self.reviews.len(),
+            self.new.len()
+        )
+    }
+}
+
+/// The days from today until the exam, see [`Scheduler::study_plan`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct StudyPlan {
+    /// Every day before the exam, including days with nothing to study.
+    pub days: Vec<StudyDay>,
+    pub exam_day: i64,
+    /// How many questions didn't fit before the exam at the daily capacity.
+    pub shortfall: usize,
+    created_at: Duration,
+}
+
+impl StudyPlan {
+    pub fn num_questions(&self) -> usize {
+        self.days.iter().map(StudyDay::num_questions).sum()
+    }
+
+    /// A warning of the questions which don't fit before the exam, if any.
+    pub fn shortfall_he(&self) -> Option<String> {
+        (self.shortfall > 0).then(|| {
+            format!(
+                "{} שאלות לא נכנסות לתוכנית עד המבחן, כדאי להגדיל את מספר השאלות ביום",
+                self.shortfall
+            )
+        })
+    }
+
+    /// The plan as an iCalendar file, with an all day event for each day with questions to
+    /// study.
+    pub fn to_ics(&self) -> String {
+        let created = CivilDateTime::new(self.created_at, 0);
+        let stamp = format!(
+            "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+            created.year,
+            created.month,
+            created.day,
+            created.hour,
+            created.minute,
+            self.created_at.as_secs() % 60
+        );
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//theory_test_exam//study plan//HE".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+        ];
+        let last_day = self.days.iter().rposition(|day| day.num_questions() > 0);
+        for (i, day) in self.days.iter().enumerate() {
+            if day.num_questions() == 0 {
+                continue;
+            }
+            let mut description = day.description_he();
+            if let Some(shortfall) = self.shortfall_he().filter(|_| Some(i) == last_day) {
+                description = format!("{description}\n{shortfall}");
+            }
+            lines.extend([
+                "BEGIN:VEVENT".to_string(),
+                format!(
+                    "UID:study-{}-{}@theory-test-exam",
+                    ics_date(day.day),
+                    self.created_at.as_secs()
+                ),
+                format!("DTSTAMP:{stamp}"),
+                format!("DTSTART;VALUE=DATE:{}", ics_date(day.day)),
+                format!("DTEND;VALUE=DATE:{}", ics_date(day.day + 1)),
+                format!("SUMMARY:{}", escape_ics_text(&day.summary_he())),
+                format!("DESCRIPTION:{}", escape_ics_text(&description)),
+                "END:VEVENT".to_string(),
+            ]);
+        }
+        lines.push("END:VCALENDAR".to_string());
+        lines.iter().map(|line| fold_ics_line(line)).collect()
+    }
+}
+
+/// A day as an iCalendar date, e.g. "20261020".
+fn ics_date(day: i64) -> String {
+    let (year, month, day) = time::civil_from_days(day);
+    format!("{year:04}{month:02}{day:02}")
+}
+
+/// Escape the characters which separate values in iCalendar text.
+fn escape_ics_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a line into lines of at most 75 bytes, as iCalendar requires, never splitting a
+/// character, and end it with CRLF.
+fn fold_ics_line(line: &str) -> String {
+    const MAX_LINE: usize = 75;
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_len = 0;
+    for c in line.chars() {
+        if line_len + c.len_utf8() > MAX_LINE {
+            folded.push_str("\r\n ");
+            // the space which continues the line counts towards it
+            line_len = 1;
+        }
+        folded.push(c);
+        line_len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod test {
+    use theory_test_parser::question_parser::Question;
+
+    use super::*;
+    use crate::history::Attempt;
+
+    const DAY: u64 = 86_400;
+
+    fn bank(nums: std::ops::RangeInclusive<usize>) -> ExamQuestions {
+        ExamQuestions {
+            questions: nums
+                .map(|num| Question {
+                    category: QuestionCategory::ALL[num % 4],
+                    ..Question::sample(num, 0)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn leitner_levels() {
+        use QuestionOutcome::*;
+        let history = History {
+            attempts: vec![
+                Attempt::sample(10 * DAY, &[(1, Correct), (2, Wrong), (3, Correct)]),
+                Attempt::sample(12 * DAY, &[(1, Correct), (2, Correct), (3, Unanswered)]),
+            ],
+        };
+        let scheduler = Scheduler::new(&history, Duration::from_secs(13 * DAY), 0);
+        assert_eq!(
+            scheduler.card(1),
+            Some(Card {
+                level: 2,
+                due_day: 16
+            })
+        );
+        assert_eq!(
+            scheduler.card(2),
+            Some(Card {
+                level: 1,
+                due_day: 14
+            })
+        );
+        assert_eq!(
+            scheduler.card(3),
+            Some(Card {
+                level: 0,
+                due_day: 13
+            })
+        );
+        assert_eq!(scheduler.card(4), None);
+        assert_eq!(scheduler.due(), vec![3]);
+    }
+
+    #[test]
+    fn distribution() {
+        use QuestionOutcome::*;
+        // 1 to 3 are due on the first day, 4 on the second, 5 after the exam
+        let history = History {
+            attempts: vec![
+                Attempt::sample(19 * DAY, &[(4, Correct), (5, Correct)]),
+                Attempt::sample(19 * DAY, &[(5, Correct)]),
+                Attempt::sample(20 * DAY - 1, &[(1, Wrong), (2, Wrong), (3, Wrong)]),
+            ],
+        };
+        let scheduler = Scheduler::new(&history, Duration::from_secs(20 * DAY), 0);
+        let bank = bank(1..=15);
+
+        let plan = scheduler.study_plan(&bank, 23, 6);
+        assert_eq!(
+            plan.days.iter().map(|day| day.day).collect::<Vec<_>>(),
+            vec![20, 21, 22]
+        );
+        assert_eq!(plan.days[0].reviews, vec![1, 2, 3]);
+        assert_eq!(plan.days[1].reviews, vec![4]);
+        // the 10 new questions evenly, as far as the reviews leave room
+        assert_eq!(
+            plan.days
+                .iter()
+                .map(|day| day.new.len())
+                .collect::<Vec<_>>(),
+            vec![3, 4, 3]
+        );
+        assert_eq!(plan.shortfall, 0);
+        assert_eq!(plan.num_questions(), 14);
+        assert_eq!(plan.shortfall_he(), None);
+
+        // too little room: every day is full and the rest is flagged
+        let plan = scheduler.study_plan(&bank, 23, 3);
+        assert_eq!(
+            plan.days
+                .iter()
+                .map(|day| (day.reviews.len(), day.new.len()))
+                .collect::<Vec<_>>(),
+            vec![(3, 0), (1, 2), (0, 3)]
+        );
+        assert_eq!(plan.shortfall, 5);
+        assert!(plan.shortfall_he().unwrap().starts_with("5 שאלות"));
+
+        // reviews which wait for room come before the later ones, and before new questions
+        let plan = scheduler.study_plan(&bank, 23, 2);
+        assert_eq!(plan.days[0].reviews, vec![1, 2]);
+        assert_eq!(plan.days[1].reviews, vec![3, 4]);
+        assert!(plan.days[2].reviews.is_empty());
+        assert_eq!(plan.shortfall, 10 - 2);
+
+        // the exam is today, nothing is due before it but everything new is missed
+        let plan = scheduler.study_plan(&bank, 20, 40);
+        assert!(plan.days.is_empty());
+        assert_eq!(plan.shortfall, 10);
+    }
+
+    #[test]
+    fn summaries() {
+        let day = |nums: &[usize], categories: &[QuestionCategory]| StudyDay {
+            day: 0,
+            reviews: Vec::new(),
+            new: nums.to_vec(),
+            categories: categories.to_vec(),
+        };
+        use QuestionCategory::*;
+        assert_eq!(
+            day(&[1; 40], &[RoadSigns, Safety]).summary_he(),
+            "תיאוריה: 40 שאלות — תמרורים ובטיחות"
+        );
+        assert_eq!(
+            day(&[1], &[Safety]).summary_he(),
+            "תיאוריה: שאלה אחת — בטיחות"
+        );
+        assert_eq!(
+            day(&[1; 3], &[TrafficLaws, RoadSigns, CarKnowledge]).summary_he(),
+            "תיאוריה: 3 שאלות — חוקי התנועה, תמרורים והכרת הרכב"
+        );
+    }
+
+    #[test]
+    fn ics() {
+        let scheduler = Scheduler::new(
+            &History::default(),
+            // 2026-10-17T09:30:05Z
+            Duration::from_secs(1_792_229_405),
+            3 * 3600,
+        );
+        let bank = bank(1..=60);
+        let today = time::parse_date("2026-10-17").unwrap();
+        let plan = scheduler.study_plan(&bank, today + 2, 25);
+        let ics = plan.to_ics();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTAMP:20261017T093005Z\r\n"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20261017\r\nDTEND;VALUE=DATE:20261018\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        // lines are folded within 75 bytes, between characters
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        let unfolded = ics.replace("\r\n ", "");
+        // the commas of the Hebrew text are escaped, the rest of it is kept as is
+        assert!(unfolded.contains(
+            "SUMMARY:תיאוריה: 25 שאלות — תמרורים\\, חוקי התנועה\\, בטיחות והכרת הרכב\r\n"
+        ));
+        assert!(unfolded.contains(
+            "DESCRIPTION:0 שאלות לחזרה\\, 25 שאלות חדשות\\n10 שאלות לא נכנסות לתוכנית עד המבחן\\, כדאי להגדיל את מספר השאלות ביום\r\n"
+        ));
+
+        assert_eq!(escape_ics_text("a;b,c\\d\r\ne"), "a\\;b\\,c\\\\d\\ne");
+    }
+}
@@ -0,0 +1,372 @@
+//! Exams taken by a simulated user, without a browser, e.g. to test flows of the app or to play
+//! exams in bots.
+//!
+//! The simulation drives the same [`ExamState`] the app does: each question is shown in turn,
+//! skipped questions are revisited before submitting, and the time of a timed exam runs on a
+//! [`MockClock`], so a whole exam takes no time and always ends the same way.
+use std::{ops::ControlFlow, time::Duration};
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use theory_test_parser::{
+    exam::ExamSpec,
+    question_parser::{ExamQuestions, Question},
+};
+
+use crate::{
+    exam_state::{ExamResult, ExamState, PassThreshold, check_question},
+    time_warnings::TimeWarning,
+};
+
+/// A clock which only moves when it is told to.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct MockClock {
+    now: Duration,
+}
+
+impl MockClock {
+    pub fn new(now: Duration) -> Self {
+        Self { now }
+    }
+
+    /// The time, as a duration since the unix epoch like the app's clock.
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    pub fn advance(&mut self, by: Duration) {
+        self.now += by;
+    }
+}
+
+/// What the simulated user does with a question when it is shown.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Move {
+    Answer(usize),
+    /// Answer and flag the question, to go over it in the review.
+    AnswerFlagged(usize),
+    /// Put the question aside to come back to it before submitting, see [`ExamState::skip`].
+    Skip,
+    /// Move on leaving the question unanswered.
+    Leave,
+}
+
+/// Decides what to do with a question, from the question and the number of times it was skipped.
+pub type Decide = Box<dyn FnMut(&Question, usize) -> Move>;
+
+/// How the simulated user answers.
+pub enum AnswerStrategy {
+    AlwaysCorrect,
+    AlwaysFirst,
+    /// A uniformly random answer, the same ones for the same seed.
+    Random(u64),
+    Custom(Decide),
+}
+
+/// How a simulated exam is generated and taken.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SimulationSpec {
+    pub exam: ExamSpec,
+    pub time_limit: Option<Duration>,
+    pub immediate_feedback: bool,
+    pub pass_threshold: PassThreshold,
+    /// The time spent on each question which is shown, before deciding what to do with it.
+    pub time_per_question: Duration,
+}
+
+impl Default for SimulationSpec {
+    fn default() -> Self {
+        Self {
+            exam: ExamSpec::default(),
+            time_limit: None,
+            immediate_feedback: false,
+            pass_threshold: PassThreshold::Official,
+            time_per_question: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A simulated exam once it was graded.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    /// The questions in exam order, as the outcomes of the result are.
+    pub questions: Vec<Question>,
+    pub result: ExamResult,
+    /// The time warnings in the order they were given.
+    pub time_warnings: Vec<TimeWarning>,
+}
+
+/// Generate the exam of `spec` out of `bank`, and take it with `strategy`.
+pub fn simulate_exam(
+    bank: &ExamQuestions,
+    spec: &SimulationSpec,
+    strategy: AnswerStrategy,
+) -> ExamResult {
+    let questions = spec.exam.generate(bank).questions;
+    run_simulation(questions, spec, strategy).result
+}
+
+/// Take an exam of `questions` with `strategy`, with the time limit, feedback and threshold of
+/// `spec`. Its exam spec isn't used, e.g. to retake the missed questions of another simulation.
+pub fn run_simulation(
+    questions: Vec<Question>,
+    spec: &SimulationSpec,
+    strategy: AnswerStrategy,
+) -> Simulation {
+    let clock = MockClock::default();
+    let exam = ExamState::new(questions, clock.now())
+        .with_time_limit(spec.time_limit)
+        .with_immediate_feedback(spec.immediate_feedback)
+        .with_pass_threshold(spec.pass_threshold);
+    let seed = match strategy {
+        AnswerStrategy::Random(seed) => seed,
+        _ => 0,
+    };
+    let mut run = Run {
+        exam,
+        clock,
+        time_per_question: spec.time_per_question,
+        strategy,
+        rng: Pcg64::seed_from_u64(seed),
+        time_warnings: Vec::new(),
+    };
+    // a timed out exam is frozen, which its result tells
+    let _ = run.take();
+    let result = run.exam.grade(run.clock.now()).clone();
+    Simulation {
+        questions: run.exam.questions().to_vec(),
+        result,
+        time_warnings: run.time_warnings,
+    }
+}
+
+struct Run {
+    exam: ExamState,
+    clock: MockClock,
+    time_per_question: Duration,
+    strategy: AnswerStrategy,
+    rng: Pcg64,
+    time_warnings: Vec<TimeWarning>,
+}
+
+impl Run {
+    /// Go over the questions in order, then over the skipped ones, until they are done or the
+    /// time runs out.
+    fn take(&mut self) -> ControlFlow<()> {
+        for question in 0..self.exam.questions().len() {
+            self.show(question)?;
+        }
+        if let Some(first) = self.exam.start_revisit() {
+            let skipped = (first..self.exam.questions().len())
+                .filter(|&question| self.exam.times_skipped(question) == 1)
+                .collect::<Vec<_>>();
+            for question in skipped {
+                self.show(question)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Breaks once the time ran out while the question was shown, leaving it as it was.
+    fn show(&mut self, question: usize) -> ControlFlow<()> {
+        // malformed questions are never shown
+        if check_question(&self.exam.questions()[question]).is_err() {
+            return ControlFlow::Continue(());
+        }
+        self.clock.advance(self.time_per_question);
+        let now = self.clock.now();
+        self.time_warnings.extend(self.exam.take_time_warning(now));
+        if self.exam.is_expired(now) {
+            self.exam.freeze();
+            return ControlFlow::Break(());
+        }
+
+        let shown = &self.exam.questions()[question];
+        let times_skipped = self.exam.times_skipped(question);
+        let play = match &mut self.strategy {
+            AnswerStrategy::AlwaysCorrect => Move::Answer(shown.answers.correct_answer),
+            AnswerStrategy::AlwaysFirst => Move::Answer(0),
+            AnswerStrategy::Random(_) => Move::Answer(
+                self.rng
+                    .random_range(0..shown.answers.possible_answers.len()),
+            ),
+            AnswerStrategy::Custom(decide) => decide(shown, times_skipped),
+        };
+        match play {
+            Move::Answer(answer) => {
+                self.exam.select(question, answer, now);
+            }
+            Move::AnswerFlagged(answer) => {
+                self.exam.select(question, answer, now);
+                if !self.exam.is_flagged(question) {
+                    self.exam.toggle_flag(question, now);
+                }
+            }
+            Move::Skip => {
+                self.exam.skip(question, now);
+            }
+            Move::Leave => (),
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{exam_state::QuestionOutcome, time_warnings::TimeWarningLevel};
+    use theory_test_parser::question_parser::{LicenseClass, QuestionCategory};
+
+    /// A bank of `size` questions of class B, whose correct answers take turns.
+    fn bank(size: usize) -> ExamQuestions {
+        let questions = (1..=size)
+            .map(|num| Question {
+                category: QuestionCategory::ALL[num % QuestionCategory::ALL.len()],
+                license_classes: vec![LicenseClass::B],
+                ..Question::sample(num, num % 4)
+            })
+            .collect();
+        ExamQuestions { questions }
+    }
+
+    #[test]
+    fn perfect_run() {
+        let spec = SimulationSpec::default();
+        let result = simulate_exam(&bank(100), &spec, AnswerStrategy::AlwaysCorrect);
+        assert_eq!(result.num_questions(), 30);
+        assert_eq!(result.correct(), 30);
+        assert!(result.passed());
+        assert!(!result.timed_out);
+        assert!(result.to_review().is_empty());
+        assert_eq!(result.elapsed, Duration::from_secs(30 * 30));
+    }
+
+    #[test]
+    fn grading_matches_the_selections() {
+        let spec = SimulationSpec::default();
+        let questions = spec.exam.generate(&bank(100)).questions;
+        let simulation = run_simulation(questions.clone(), &spec, AnswerStrategy::AlwaysFirst);
+        let selections = vec![Some(0); questions.len()];
+        assert_eq!(
+            simulation.result.outcomes,
+            ExamResult::of_selections(&questions, &selections).outcomes
+        );
+        let correct = questions
+            .iter()
+            .filter(|q| q.answers.correct_answer == 0)
+            .count();
+        assert_eq!(simulation.result.correct(), correct);
+        assert_eq!(simulation.result.passed(), 30 - correct <= 4);
+    }
+
+    #[test]
+    fn timed_out() {
+        let spec = SimulationSpec {
+            time_limit: Some(Duration::from_secs(5 * 60)),
+            ..SimulationSpec::default()
+        };
+        let simulation = run_simulation(
+            spec.exam.generate(&bank(100)).questions,
+            &spec,
+            AnswerStrategy::AlwaysCorrect,
+        );
+        let result = simulation.result;
+        // the time ran out while the 10th question was shown
+        assert!(result.timed_out);
+        assert_eq!(result.elapsed, Duration::from_secs(5 * 60));
+        assert_eq!(result.correct(), 9);
+        assert_eq!(result.count(QuestionOutcome::Unanswered), 21);
+        assert!(!result.passed());
+        // with a minute and then half a minute left
+        assert_eq!(
+            simulation
+                .time_warnings
+                .iter()
+                .map(|warning| warning.level)
+                .collect::<Vec<_>>(),
+            vec![TimeWarningLevel::Low, TimeWarningLevel::Critical]
+        );
+    }
+
+    #[test]
+    fn retake_wrong() {
+        let spec = SimulationSpec::default();
+        let questions = spec.exam.generate(&bank(100)).questions;
+        let first = run_simulation(questions, &spec, AnswerStrategy::Random(7));
+        // the same seed answers alike
+        let again = run_simulation(first.questions.clone(), &spec, AnswerStrategy::Random(7));
+        assert_eq!(again.result, first.result);
+        let missed = first.result.missed();
+        assert!(!missed.is_empty());
+
+        let wrong = missed
+            .iter()
+            .map(|&idx| first.questions[idx].clone())
+            .collect::<Vec<_>>();
+        let retake = run_simulation(wrong, &spec, AnswerStrategy::AlwaysCorrect);
+        assert_eq!(retake.result.num_questions(), missed.len());
+        assert_eq!(retake.result.mistakes(), 0);
+        assert!(retake.result.passed());
+    }
+
+    #[test]
+    fn skipped_questions_are_revisited() {
+        let spec = SimulationSpec {
+            exam: ExamSpec {
+                num_questions: 10,
+                ..ExamSpec::default()
+            },
+            ..SimulationSpec::default()
+        };
+        // everything is skipped at first, the last question twice
+        let strategy = AnswerStrategy::Custom(Box::new(|question, times_skipped| {
+            match (question.num, times_skipped) {
+                (_, 0) => Move::Skip,
+                (_, 1) if question.num % 2 == 0 => Move::Skip,
+                _ => Move::Answer(question.answers.correct_answer),
+            }
+        }));
+        let simulation = run_simulation(spec.exam.generate(&bank(10)).questions, &spec, strategy);
+        let result = simulation.result;
+        let twice_skipped = simulation
+            .questions
+            .iter()
+            .enumerate()
+            .filter(|(_, q)| q.num % 2 == 0)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        assert_eq!(result.correct(), 5);
+        assert_eq!(result.count(QuestionOutcome::Unanswered), 5);
+        // skipped twice, they are flagged and left for the review
+        assert_eq!(result.to_review(), twice_skipped);
+        assert!(twice_skipped.iter().all(|&idx| result.flags[idx]));
+        // each question was shown twice
+        assert_eq!(result.elapsed, Duration::from_secs(20 * 30));
+    }
+
+    #[test]
+    fn immediate_feedback() {
+        let spec = SimulationSpec {
+            immediate_feedback: true,
+            pass_threshold: PassThreshold::MaxMistakes(0),
+            ..SimulationSpec::default()
+        };
+        // answers the first question wrong and flags it, and the rest correctly
+        let mut shown = 0;
+        let strategy = AnswerStrategy::Custom(Box::new(move |question, _| {
+            shown += 1;
+            let wrong = (question.answers.correct_answer + 1) % 4;
+            match shown {
+                1 => Move::AnswerFlagged(wrong),
+                _ => Move::Answer(question.answers.correct_answer),
+            }
+        }));
+        let simulation = run_simulation(spec.exam.generate(&bank(100)).questions, &spec, strategy);
+        let result = simulation.result;
+        assert_eq!(result.outcomes[0], QuestionOutcome::Wrong);
+        assert!(result.flags[0]);
+        assert_eq!(result.mistakes(), 1);
+        assert!(!result.passed());
+        assert_eq!(result.to_review(), vec![0]);
+    }
+}
@@ -0,0 +1,237 @@
+//! Labels of the user's own on questions, beyond the built-in categories, kept by the official
+//! number of the question.
+//!
+//! Tags are normalized before they are stored, so " מבלבל " and "מבלבל", or "Distance" and
+//! "distance", are the same tag.
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+/// The longest tag, in characters.
+pub const MAX_TAG_CHARS: usize = 30;
+
+/// The form a tag is stored in: trimmed, with runs of whitespace as a single space, lowercase and
+/// truncated to [`MAX_TAG_CHARS`]. `None` if nothing is left.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .filter(|c| !c.is_control())
+        .flat_map(char::to_lowercase)
+        .take(MAX_TAG_CHARS)
+        .collect::<String>();
+    let tag = tag.trim_end();
+    (!tag.is_empty()).then(|| tag.to_string())
+}
+
+type ByQuestion = BTreeMap<usize, BTreeSet<String>>;
+
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(from = "ByQuestion", into = "ByQuestion")]
+pub struct Tags {
+    /// The tags of each question which has any.
+    by_question: ByQuestion,
+    /// The questions of each tag, kept in step with `by_question`.
+    by_tag: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl From<ByQuestion> for Tags {
+    /// The tags as stored, normalized again in case they were written by hand or by an older
+    /// version.
+    fn from(by_question: ByQuestion) -> Self {
+        let mut tags = Tags::default();
+        for (num, question_tags) in by_question {
+            for tag in question_tags {
+                tags.add(num, &tag);
+            }
+        }
+        tags
+    }
+}
+
+impl From<Tags> for ByQuestion {
+    fn from(tags: Tags) -> Self {
+        tags.by_question
+    }
+}
+
+impl Tags {
+    /// The tags of a question, in alphabetical order.
+    pub fn get(&self, num: usize) -> Vec<&str> {
+        self.by_question
+            .get(&num)
+            .map(|tags| tags.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Tag a question, returning whether it wasn't tagged so already. Tags which are empty once
+    /// normalized aren't added.
+    pub fn add(&mut self, num: usize, tag: &str) -> bool {
+        let Some(tag) = normalize_tag(tag) else {
+            return false;
+        };
+        self.by_tag.entry(tag.clone()).or_default().insert(num);
+        self.by_question.entry(num).or_default().insert(tag)
+    }
+
+    /// Remove a tag of a question, returning whether it had it. A tag left without questions is
+    /// gone.
+    pub fn remove(&mut self, num: usize, tag: &str) -> bool {
+        let Some(tag) = normalize_tag(tag) else {
+            return false;
+        };
+        let Some(question_tags) = self.by_question.get_mut(&num) else {
+            return false;
+        };
+        let removed = question_tags.remove(&tag);
+        if question_tags.is_empty() {
+            self.by_question.remove(&num);
+        }
+        if let Some(nums) = self.by_tag.get_mut(&tag) {
+            nums.remove(&num);
+            if nums.is_empty() {
+                self.by_tag.remove(&tag);
+            }
+        }
+        removed
+    }
+
+    /// Every tag with the number of questions it is on, in alphabetical order.
+    pub fn all(&self) -> Vec<(&str, usize)> {
+        self.by_tag
+            .iter()
+            .map(|(tag, nums)| (tag.as_str(), nums.len()))
+            .collect()
+    }
+
+    /// The questions with `tag`.
+    pub fn nums(&self, tag: &str) -> HashSet<usize> {
+        normalize_tag(tag)
+            .and_then(|tag| self.by_tag.get(&tag))
+            .map(|nums| nums.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Up to `limit` existing tags containing what was typed, for completing it, leaving out the
+    /// tags question `num` already has. Tags starting with it come first, then the tags on more
+    /// questions.
+    pub fn suggestions(&self, typed: &str, num: usize, limit: usize) -> Vec<&str> {
+        let typed = normalize_tag(typed).unwrap_or_default();
+        let own = self.by_question.get(&num);
+        let mut matching = self
+            .by_tag
+            .iter()
+            .filter(|(tag, _)| tag.contains(&typed) && own.is_none_or(|own| !own.contains(*tag)))
+            .map(|(tag, nums)| (!tag.starts_with(&typed), std::cmp::Reverse(nums.len()), tag))
+            .collect::<Vec<_>>();
+        matching.sort();
+        matching
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, tag)| tag.as_str())
+            .collect()
+    }
+
+    /// Add the tags of `other`, returning the number of tags added to questions.
+    pub fn merge(&mut self, other: Tags) -> usize {
+        let mut added = 0;
+        for (num, tags) in other.by_question {
+            for tag in tags {
+                added += usize::from(self.add(num, &tag));
+            }
+        }
+        added
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalization() {
+        assert_eq!(
+            normalize_tag("  חישובי   מרחק "),
+            Some("חישובי מרחק".to_string())
+        );
+        assert_eq!(
+            normalize_tag("Ask\tTHE teacher"),
+            Some("ask the teacher".to_string())
+        );
+        assert_eq!(normalize_tag(" \n "), None);
+        assert_eq!(
+            normalize_tag(&"א".repeat(MAX_TAG_CHARS + 5))
+                .unwrap()
+                .chars()
+                .count(),
+            MAX_TAG_CHARS
+        );
+        // truncating doesn't leave a space at the end
+        let cut_at_space = format!("{} ב", "א".repeat(MAX_TAG_CHARS - 1));
+        assert_eq!(
+            normalize_tag(&cut_at_space),
+            Some("א".repeat(MAX_TAG_CHARS - 1))
+        );
+    }
+
+    #[test]
+    fn tagging() {
+        let mut tags = Tags::default();
+        assert!(tags.add(7, "מבלבל"));
+        assert!(!tags.add(7, " מבלבל "));
+        assert!(tags.add(8, "מבלבל"));
+        assert!(tags.add(8, "Distance"));
+        assert!(!tags.add(8, " "));
+        assert_eq!(tags.get(8), ["distance", "מבלבל"]);
+        assert_eq!(tags.all(), [("distance", 1), ("מבלבל", 2)]);
+        assert_eq!(tags.nums("מבלבל "), HashSet::from([7, 8]));
+        assert_eq!(tags.nums("DISTANCE"), HashSet::from([8]));
+
+        assert!(tags.remove(7, "מבלבל"));
+        assert!(!tags.remove(7, "מבלבל"));
+        assert_eq!(tags.get(7), Vec::<&str>::new());
+        assert!(tags.remove(8, "distance"));
+        // the index forgets tags without questions
+        assert_eq!(tags.all(), [("מבלבל", 1)]);
+        assert_eq!(tags.nums("distance"), HashSet::new());
+    }
+
+    #[test]
+    fn suggestions() {
+        let mut tags = Tags::default();
+        tags.add(1, "חישובי מרחק");
+        tags.add(2, "מרחק עצירה");
+        tags.add(3, "מרחק עצירה");
+        tags.add(3, "לשאול את המורה");
+        // starting with the typed text first, then by use
+        assert_eq!(
+            tags.suggestions("מרחק", 9, 10),
+            ["מרחק עצירה", "חישובי מרחק"]
+        );
+        assert_eq!(tags.suggestions("", 9, 2), ["מרחק עצירה", "חישובי מרחק"]);
+        // not the tags the question already has
+        assert_eq!(tags.suggestions("מרחק", 3, 10), ["חישובי מרחק"]);
+        assert_eq!(tags.suggestions("xyz", 9, 10), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn storage() {
+        let mut tags = Tags::default();
+        tags.add(5, "מבלבל");
+        let json = serde_json::to_string(&tags).unwrap();
+        assert_eq!(json, r#"{"5":["מבלבל"]}"#);
+        assert_eq!(serde_json::from_str::<Tags>(&json).unwrap(), tags);
+        // stored tags are normalized and indexed when read
+        let read = serde_json::from_str::<Tags>(r#"{"5":["A ", "a", " "]}"#).unwrap();
+        assert_eq!(read.get(5), ["a"]);
+        assert_eq!(read.nums("a"), HashSet::from([5]));
+
+        let mut other = Tags::default();
+        other.add(5, "מבלבל");
+        other.add(6, "מבלבל");
+        assert_eq!(tags.merge(other), 1);
+        assert_eq!(tags.nums("מבלבל"), HashSet::from([5, 6]));
+    }
+}
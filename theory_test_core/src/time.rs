@@ -0,0 +1,136 @@
+//! Days and formatting of times, given as durations since the unix epoch.
+use std::time::Duration;
+
+/// The local day of a time, as a number of days since the unix epoch.
+pub fn local_day(time: Duration, utc_offset_secs: i64) -> i64 {
+    (time.as_secs() as i64 + utc_offset_secs).div_euclid(86_400)
+}
+
+/// The `(year, month, day)` of a number of days since the unix epoch.
+pub fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// The number of days since the unix epoch of a `(year, month, day)`, the inverse of
+/// [`civil_from_days`].
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// The day of a `yyyy-mm-dd` date, e.g. of a date input, as a number of days since the unix
+/// epoch.
+pub fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date
+        .trim()
+        .splitn(3, '-')
+        .map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let days = days_from_civil(year, month, day);
+    // rejects days which don't exist, e.g. the 30th of February
+    (civil_from_days(days) == (year, month, day)).then_some(days)
+}
+
+/// Format a number of days since the unix epoch as `yyyy-mm-dd`.
+pub fn format_date(days: i64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// A time in a time zone, by its calendar date and time of day.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CivilDateTime {
+    pub year: i64,
+    pub month: i64,
+    pub day: i64,
+    pub hour: i64,
+    pub minute: i64,
+}
+
+impl CivilDateTime {
+    /// A time, as a duration since the unix epoch, in the time zone of `utc_offset_secs`.
+    pub fn new(time: Duration, utc_offset_secs: i64) -> Self {
+        let (year, month, day) = civil_from_days(local_day(time, utc_offset_secs));
+        let secs_of_day = (time.as_secs() as i64 + utc_offset_secs).rem_euclid(86_400);
+        Self {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3600,
+            minute: secs_of_day / 60 % 60,
+        }
+    }
+}
+
+/// Format a time, as a duration since the unix epoch, as `d/m/yyyy hh:mm` in the time zone
+/// of `utc_offset_secs`.
+pub fn format_date_time(time: Duration, utc_offset_secs: i64) -> String {
+    let civil = CivilDateTime::new(time, utc_offset_secs);
+    format!(
+        "{}/{}/{} {:02}:{:02}",
+        civil.day, civil.month, civil.year, civil.hour, civil.minute
+    )
+}
+
+/// Format a duration as `mm:ss`, or `h:mm:ss` when it is an hour or longer.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "00:00");
+        assert_eq!(format_duration(Duration::from_secs(40 * 60)), "40:00");
+        assert_eq!(format_duration(Duration::from_millis(61_900)), "01:01");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+    }
+
+    #[test]
+    fn date_formatting() {
+        assert_eq!(format_date_time(Duration::ZERO, 0), "1/1/1970 00:00");
+        // 2024-02-29T22:30:00Z
+        let leap_day = Duration::from_secs(1_709_245_800);
+        assert_eq!(format_date_time(leap_day, 0), "29/2/2024 22:30");
+        assert_eq!(format_date_time(leap_day, 2 * 3600), "1/3/2024 00:30");
+        assert_eq!(format_date_time(leap_day, -23 * 3600), "28/2/2024 23:30");
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        let leap_day = parse_date("2024-02-29").unwrap();
+        assert_eq!(leap_day, 1_709_245_800 / 86_400);
+        assert_eq!(format_date(leap_day), "2024-02-29");
+        assert_eq!(format_date(leap_day + 1), "2024-03-01");
+        assert_eq!(parse_date("1969-12-31"), Some(-1));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("tomorrow"), None);
+    }
+}
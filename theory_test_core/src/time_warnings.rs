@@ -0,0 +1,101 @@
+//! Warnings as the time of a timed exam runs low.
+//!
+//! The thresholds are data rather than branches: the default ones are set for the official time
+//! limit and scaled to the limit of the exam, and an exam may replace them altogether.
+use std::time::Duration;
+
+/// How urgent a warning is, which colors the timer.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TimeWarningLevel {
+    Low,
+    Critical,
+}
+
+impl TimeWarningLevel {
+    pub fn class(&self) -> &'static str {
+        match self {
+            TimeWarningLevel::Low => "time-low",
+            TimeWarningLevel::Critical => "time-critical",
+        }
+    }
+}
+
+/// A warning given once only `remaining` of the time is left.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TimeWarning {
+    pub remaining: Duration,
+    pub level: TimeWarningLevel,
+}
+
+impl TimeWarning {
+    /// The minutes which are left, to a tenth of a minute.
+    pub fn minutes(&self) -> f64 {
+        (self.remaining.as_secs_f64() / 6.0).round() / 10.0
+    }
+}
+
+/// The time limit the default warnings are set for, the official one.
+const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(40 * 60);
+
+/// The default warnings, for [`DEFAULT_TIME_LIMIT`].
+const DEFAULT_WARNINGS: &[TimeWarning] = &[
+    TimeWarning {
+        remaining: Duration::from_secs(10 * 60),
+        level: TimeWarningLevel::Low,
+    },
+    TimeWarning {
+        remaining: Duration::from_secs(5 * 60),
+        level: TimeWarningLevel::Critical,
+    },
+];
+
+/// The default warnings scaled to `time_limit`, e.g. a 20 minutes exam warns at 5 and 2.5
+/// minutes.
+pub fn default_warnings(time_limit: Duration) -> Vec<TimeWarning> {
+    let scale = time_limit.as_secs_f64() / DEFAULT_TIME_LIMIT.as_secs_f64();
+    DEFAULT_WARNINGS
+        .iter()
+        .map(|warning| TimeWarning {
+            remaining: warning.remaining.mul_f64(scale),
+            ..*warning
+        })
+        .collect()
+}
+
+/// How many of `warnings` were reached with `remaining` time left. Warnings must be ordered from
+/// the earliest.
+pub fn num_reached(warnings: &[TimeWarning], remaining: Duration) -> usize {
+    warnings
+        .iter()
+        .take_while(|warning| remaining <= warning.remaining)
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn scaling() {
+        assert_eq!(default_warnings(DEFAULT_TIME_LIMIT), DEFAULT_WARNINGS);
+        let warnings = default_warnings(Duration::from_secs(20 * 60));
+        assert_eq!(warnings[0].remaining, Duration::from_secs(5 * 60));
+        assert_eq!(warnings[1].remaining, Duration::from_secs(150));
+        assert_eq!(warnings[0].minutes(), 5.0);
+        assert_eq!(warnings[1].minutes(), 2.5);
+        // a 25 minutes exam warns at 6.25 minutes
+        assert_eq!(
+            default_warnings(Duration::from_secs(25 * 60))[0].minutes(),
+            6.3
+        );
+    }
+
+    #[test]
+    fn reaching() {
+        let warnings = default_warnings(DEFAULT_TIME_LIMIT);
+        assert_eq!(num_reached(&warnings, Duration::from_secs(11 * 60)), 0);
+        assert_eq!(num_reached(&warnings, Duration::from_secs(10 * 60)), 1);
+        assert_eq!(num_reached(&warnings, Duration::from_secs(60)), 2);
+        assert_eq!(num_reached(&[], Duration::ZERO), 0);
+    }
+}
@@ -0,0 +1,533 @@
+//! Anki packages of the bank, which unlike the TSV export carry their images.
+//!
+//! A package is a zip of an Anki collection, a sqlite database in the format of Anki 2.1, the
+//! image files numbered from 0 and a `media` file mapping the numbers to their names.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{Connection, MAIN_DB, params};
+use serde_json::{Value, json};
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+use crate::{
+    export::{html_escape, question_text},
+    question_parser::{ExamQuestions, LicenseClass, Question, QuestionCategory},
+};
+
+/// The id of the note type, fixed so re-importing a package updates the notes of the last one.
+const MODEL_ID: i64 = 1_718_000_000_000;
+/// The first id of the decks, in the order of their names.
+const FIRST_DECK_ID: i64 = 1_718_000_000_100;
+/// The ids of the notes and cards are these plus the number of their question.
+const NOTE_ID_BASE: i64 = 1_718_100_000_000;
+const CARD_ID_BASE: i64 = 1_718_200_000_000;
+const FIELDS: [&str; 4] = ["Question", "Answers", "Correct", "Category"];
+
+const QUESTION_TEMPLATE: &str = "{{Question}}<br>{{Answers}}";
+const ANSWER_TEMPLATE: &str =
+    "{{FrontSide}}<hr id=answer>{{Correct}}<div class=category>{{Category}}</div>";
+const CSS: &str =
+    ".card { font-family: arial; font-size: 20px; direction: rtl; text-align: right; }
+.category { font-size: 14px; color: gray; }
+img { max-width: 100%; }";
+
+/// The tables of a collection of Anki 2.1, as Anki creates them.
+const SCHEMA: &str = "
+CREATE TABLE col (
+    id integer primary key, crt integer not null, mod integer not null, scm integer not null,
+    ver integer not null, dty integer not null, usn integer not null, ls integer not null,
+    conf text not null, models text not null, decks text not null, dconf text not null,
+    tags text not null
+);
+CREATE TABLE notes (
+    id integer primary key, guid text not null, mid integer not null, mod integer not null,
+    usn integer not null, tags text not null, flds text not null, sfld integer not null,
+    csum integer not null, flags integer not null, data text not null
+);
+CREATE TABLE cards (
+    id integer primary key, nid integer not null, did integer not null, ord integer not null,
+    mod integer not null, usn integer not null, type integer not null, queue integer not null,
+    due integer not null, ivl integer not null, factor integer not null, reps integer not null,
+    lapses integer not null, left integer not null, odue integer not null,
+    odid integer not null, flags integer not null, data text not null
+);
+CREATE TABLE revlog (
+    id integer primary key, cid integer not null, usn integer not null, ease integer not null,
+    ivl integer not null, lastIvl integer not null, factor integer not null,
+    time integer not null, type integer not null
+);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+";
+
+#[derive(Debug, Error)]
+pub enum ApkgError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("can't write the collection: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("can't write the package: {0}")]
+    Zip(#[from] zip::result::ZipError),
+}
+
+/// What goes in a package.
+#[derive(Clone, Debug)]
+pub struct ApkgOptions {
+    /// The name of the deck, e.g. "Theory test".
+    pub deck_name: String,
+    /// Put each category in a subdeck of its own, e.g. "Theory test::תמרורים".
+    pub subdeck_per_category: bool,
+    /// Only the questions of this class, all of them when `None`.
+    pub class: Option<LicenseClass>,
+    /// The local image files by the number of their question, e.g. from the manifest of the
+    /// downloaded images. They are embedded in the package, the other images are linked to.
+    pub images: HashMap<usize, PathBuf>,
+}
+
+impl Default for ApkgOptions {
+    fn default() -> Self {
+        Self {
+            deck_name: "Theory test".to_string(),
+            subdeck_per_category: false,
+            class: None,
+            images: HashMap::new(),
+        }
+    }
+}
+
+/// The text of a field without its markup, as Anki sorts and compares notes by.
+fn strip_html(field: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in field.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+/// The first 8 hex digits of the SHA-1 of the sort field, which Anki finds duplicates by.
+fn checksum(field: &str) -> i64 {
+    let digest = Sha1::digest(field.as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]).into()
+}
+
+/// The name of the deck of questions of `category`.
+fn deck_name(opts: &ApkgOptions, category: QuestionCategory) -> String {
+    if opts.subdeck_per_category {
+        format!("{}::{}", opts.deck_name, category.as_str_he())
+    } else {
+        opts.deck_name.clone()
+    }
+}
+
+fn deck(id: i64, name: &str, now: i64) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "desc": "",
+        "mod": now,
+        "usn": -1,
+        "collapsed": false,
+        "browserCollapsed": false,
+        "newToday": [0, 0],
+        "revToday": [0, 0],
+        "lrnToday": [0, 0],
+        "timeToday": [0, 0],
+        "dyn": 0,
+        "conf": 1,
+        "extendNew": 10,
+        "extendRev": 50,
+    })
+}
+
+fn note_type(now: i64, deck_id: i64) -> Value {
+    let fields = FIELDS
+        .iter()
+        .enumerate()
+        .map(|(ord, name)| {
+            json!({
+                "name": name,
+                "ord": ord,
+                "sticky": false,
+                "rtl": true,
+                "font": "Arial",
+                "size": 20,
+                "media": [],
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "id": MODEL_ID,
+        "name": "Theory test question",
+        "type": 0,
+        "mod": now,
+        "usn": -1,
+        "sortf": 0,
+        "did": deck_id,
+        "tmpls": [{
+            "name": "Card 1",
+            "ord": 0,
+            "qfmt": QUESTION_TEMPLATE,
+            "afmt": ANSWER_TEMPLATE,
+            "bqfmt": "",
+            "bafmt": "",
+            "did": null,
+        }],
+        "flds": fields,
+        "css": CSS,
+        "latexPre": "",
+        "latexPost": "",
+        "tags": [],
+        "vers": [],
+        "req": [[0, "any", [0]]],
+    })
+}
+
+/// The options of the decks, Anki's defaults.
+fn deck_options(now: i64) -> Value {
+    json!({
+        "1": {
+            "id": 1,
+            "name": "Default",
+            "mod": now,
+            "usn": -1,
+            "maxTaken": 60,
+            "autoplay": true,
+            "timer": 0,
+            "replayq": true,
+            "dyn": false,
+            "new": {
+                "delays": [1, 10],
+                "ints": [1, 4, 7],
+                "initialFactor": 2500,
+                "order": 1,
+                "perDay": 20,
+                "bury": true,
+                "separate": true,
+            },
+            "rev": {
+                "perDay": 100,
+                "ease4": 1.3,
+                "fuzz": 0.05,
+                "maxIvl": 36500,
+                "bury": true,
+                "minSpace": 1,
+                "ivlFct": 1,
+            },
+            "lapse": {
+                "delays": [10],
+                "mult": 0,
+                "minInt": 1,
+                "leechFails": 8,
+                "leechAction": 0,
+            },
+        }
+    })
+}
+
+/// The fields of the note of `question`, with its image as `image_src`.
+fn note_fields(question: &Question, image_src: Option<&str>) -> [String; 4] {
+    let mut front = html_escape(question_text(question));
+    if let Some(src) = image_src {
+        front += &format!("<br><img src=\"{}\">", html_escape(src));
+    }
+    let answers = question
+        .answers
+        .possible_answers
+        .iter()
+        .map(|answer| format!("<li>{}</li>", html_escape(answer)))
+        .collect::<String>();
+    let correct = question
+        .answers
+        .possible_answers
+        .get(question.answers.correct_answer)
+        .map(|answer| html_escape(answer))
+        .unwrap_or_default();
+    [
+        front,
+        format!("<ol>{answers}</ol>"),
+        correct,
+        question.category.as_str_he().to_string(),
+    ]
+}
+
+/// A collection of `questions`, with the names of their embedded images by question.
+fn collection(
+    questions: &[&Question],
+    media: &HashMap<usize, String>,
+    opts: &ApkgOptions,
+) -> Result<Vec<u8>, ApkgError> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs() as i64)
+        .unwrap_or_default();
+    let mut deck_names = questions
+        .iter()
+        .map(|question| deck_name(opts, question.category))
+        .collect::<Vec<_>>();
+    deck_names.push(opts.deck_name.clone());
+    deck_names.sort();
+    deck_names.dedup();
+    let deck_ids = deck_names
+        .iter()
+        .enumerate()
+        .map(|(idx, name)| (name.clone(), FIRST_DECK_ID + idx as i64))
+        .collect::<BTreeMap<_, _>>();
+    let mut decks = serde_json::Map::new();
+    // Anki expects its default deck in every collection
+    decks.insert("1".to_string(), deck(1, "Default", now));
+    for (name, id) in &deck_ids {
+        decks.insert(id.to_string(), deck(*id, name, now));
+    }
+
+    let db = Connection::open_in_memory()?;
+    db.execute_batch(SCHEMA)?;
+    db.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![
+            now,
+            now * 1000,
+            now * 1000,
+            json!({ "nextPos": questions.len() + 1, "curModel": MODEL_ID }).to_string(),
+            json!({ MODEL_ID.to_string(): note_type(now, deck_ids[&opts.deck_name]) }).to_string(),
+            Value::Object(decks).to_string(),
+            deck_options(now).to_string(),
+        ],
+    )?;
+    for (due, question) in questions.iter().enumerate() {
+        let image_src = media
+            .get(&question.num)
+            .map(String::as_str)
+            .or(question.image_url.as_deref());
+        let fields = note_fields(question, image_src);
+        let sort_field = strip_html(&fields[0]);
+        let note_id = NOTE_ID_BASE + question.num as i64;
+        db.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            params![
+                note_id,
+                format!("theory_test:{:04}", question.num),
+                MODEL_ID,
+                now,
+                format!(" theory_test::{} ", question.category.code()),
+                fields.join("\x1f"),
+                sort_field,
+                checksum(&sort_field),
+            ],
+        )?;
+        db.execute(
+            "INSERT INTO cards VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![
+                CARD_ID_BASE + question.num as i64,
+                note_id,
+                deck_ids[&deck_name(opts, question.category)],
+                now,
+                due as i64 + 1,
+            ],
+        )?;
+    }
+    Ok(db.serialize(MAIN_DB)?.to_vec())
+}
+
+impl ExamQuestions {
+    /// Write the questions of `opts.class` as an Anki package to `path`: a note per question
+    /// with its Question, Answers, Correct and Category fields, and a card of it.
+    pub fn export_apkg(&self, path: impl AsRef<Path>, opts: &ApkgOptions) -> Result<(), ApkgError> {
+        let questions = self
+            .questions
+            .iter()
+            .filter(|q| {
+                opts.class
+                    .is_none_or(|class| q.license_classes.contains(&class))
+            })
+            .collect::<Vec<_>>();
+        // the images are embedded under their own names, once each
+        let mut media = HashMap::new();
+        let mut files = BTreeMap::new();
+        for question in &questions {
+            let Some(path) = opts.images.get(&question.num) else {
+                continue;
+            };
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            files.entry(name.to_string()).or_insert(path);
+            media.insert(question.num, name.to_string());
+        }
+        let collection = collection(&questions, &media, opts)?;
+
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let options = SimpleFileOptions::default();
+        zip.start_file("collection.anki2", options)?;
+        zip.write_all(&collection)?;
+        let mut media_names = serde_json::Map::new();
+        for (idx, (name, path)) in files.iter().enumerate() {
+            zip.start_file(idx.to_string(), options)?;
+            zip.write_all(&fs::read(path)?)?;
+            media_names.insert(idx.to_string(), Value::String(name.clone()));
+        }
+        zip.start_file("media", options)?;
+        zip.write_all(Value::Object(media_names).to_string().as_bytes())?;
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use super::*;
+    use crate::question_parser::Answers;
+
+    fn question(num: usize, category: QuestionCategory, image: Option<&str>) -> Question {
+        Question {
+            question: format!("{num:04}. שאלה <{num}>"),
+            answers: Answers {
+                possible_answers: vec!["כן".to_string(), "לא".to_string()],
+                correct_answer: 1,
+            },
+            category,
+            license_classes: vec![LicenseClass::B],
+            image_url: image.map(str::to_string),
+            ..Question::sample(num, 1)
+        }
+    }
+
+    #[test]
+    fn package() {
+        let dir = std::env::temp_dir().join("theory_test_apkg");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("sign.jpg"), b"an image").unwrap();
+        let mut bank = ExamQuestions {
+            questions: vec![
+                question(
+                    1,
+                    QuestionCategory::RoadSigns,
+                    Some("https://a.co/sign.jpg"),
+                ),
+                question(
+                    2,
+                    QuestionCategory::RoadSigns,
+                    Some("https://a.co/other.jpg"),
+                ),
+                question(3, QuestionCategory::Safety, None),
+            ],
+        };
+        bank.questions.push(Question {
+            license_classes: vec![LicenseClass::D],
+            ..question(4, QuestionCategory::Safety, None)
+        });
+        let opts = ApkgOptions {
+            subdeck_per_category: true,
+            class: Some(LicenseClass::B),
+            images: HashMap::from([(1, dir.join("sign.jpg"))]),
+            ..Default::default()
+        };
+        let path = dir.join("bank.apkg");
+        bank.export_apkg(&path, &opts).unwrap();
+
+        let mut zip = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let mut media = String::new();
+        zip.by_name("media")
+            .unwrap()
+            .read_to_string(&mut media)
+            .unwrap();
+        assert_eq!(media, r#"{"0":"sign.jpg"}"#);
+        let mut image = Vec::new();
+        zip.by_name("0").unwrap().read_to_end(&mut image).unwrap();
+        assert_eq!(image, b"an image");
+        let mut collection = Vec::new();
+        zip.by_name("collection.anki2")
+            .unwrap()
+            .read_to_end(&mut collection)
+            .unwrap();
+        fs::write(dir.join("collection.anki2"), collection).unwrap();
+
+        let db = Connection::open(dir.join("collection.anki2")).unwrap();
+        let notes = db
+            .prepare("SELECT flds, tags FROM notes ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(notes.len(), 3);
+        let fields = notes[0].0.split('\x1f').collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                "שאלה &lt;1&gt;<br><img src=\"sign.jpg\">",
+                "<ol><li>כן</li><li>לא</li></ol>",
+                "לא",
+                "תמרורים",
+            ]
+        );
+        assert_eq!(notes[0].1, " theory_test::road_signs ");
+        // images which weren't downloaded are linked to
+        assert!(notes[1].0.contains("<img src=\"https://a.co/other.jpg\">"));
+
+        let decks: String = db
+            .query_row("SELECT decks FROM col", [], |row| row.get(0))
+            .unwrap();
+        let decks: Value = serde_json::from_str(&decks).unwrap();
+        let mut names = decks
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|deck| deck["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "Default",
+                "Theory test",
+                "Theory test::בטיחות",
+                "Theory test::תמרורים",
+            ]
+        );
+        let card_decks = db
+            .prepare("SELECT did FROM cards ORDER BY nid")
+            .unwrap()
+            .query_map([], |row| row.get::<_, i64>(0))
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(card_decks[0], card_decks[1]);
+        assert_ne!(card_decks[0], card_decks[2]);
+    }
+
+    #[test]
+    fn checksums() {
+        // the first 8 hex digits of sha1("hello")
+        assert_eq!(checksum("hello"), 0xaaf4c61d);
+        assert_eq!(
+            strip_html("שאלה &lt;1&gt;<br><img src=\"sign.jpg\">"),
+            "שאלה <1>"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Banks parsed ahead of time, to embed in an app instead of their spreadsheets: they are several
+//! times smaller once compressed, and there is nothing left to parse when loading them.
+//!
+//! A bundle is the JSON of a [`Bundle`], compressed with brotli, which decompresses in pure Rust,
+//! e.g. in the browser.
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    observer::{ParseWarning, WarningCollector},
+    question_parser::{ExamQuestions, ParseOptions, Question},
+    trace::span,
+};
+
+/// The questions of a bank and the warnings of parsing it.
+pub type Parsed = (Vec<Question>, Vec<ParseWarning>);
+/// Why a bank couldn't be parsed, and the warnings until then.
+pub type Failed = (String, Vec<ParseWarning>);
+
+/// A bank parsed leniently, as the app parses banks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    /// The hash of the spreadsheet, see [`content_hash`].
+    pub content_hash: u64,
+    pub parsed: Result<Parsed, Failed>,
+}
+
+impl Bundle {
+    /// Parse the spreadsheet of a bank, skipping the rows which can't be parsed.
+    pub fn parse_xlsx(bytes: &[u8]) -> Self {
+        let collector = WarningCollector::default();
+        let options = ParseOptions {
+            lenient: true,
+            observer: &collector,
+        };
+        let parsed = ExamQuestions::parse_from_xlsx_with_options(bytes, options)
+            .map(|(bank, warnings)| (bank.questions, warnings))
+            .map_err(|e| (e.to_string(), collector.into_warnings()));
+        Self {
+            content_hash: content_hash(bytes),
+            parsed,
+        }
+    }
+
+    /// The JSON of the bundle, compressed as much as brotli can.
+    pub fn compress(&self) -> Vec<u8> {
+        let json = serde_json::to_vec(self).expect("bundles serialize");
+        let mut compressed = Vec::new();
+        let params = brotli::enc::BrotliEncoderParams {
+            quality: 11,
+            ..Default::default()
+        };
+        brotli::CompressorWriter::with_params(&mut compressed, 4096, &params)
+            .write_all(&json)
+            .expect("writing to a vector can't fail");
+        compressed
+    }
+
+    /// Read the bundle out of its JSON, see [`decompress`].
+    pub fn from_json(json: &[u8]) -> serde_json::Result<Self> {
+        let _span = span!(INFO, "deserialize_bundle");
+        serde_json::from_slice(json)
+    }
+}
+
+/// The JSON of a compressed bundle.
+pub fn decompress(compressed: &[u8]) -> std::io::Result<Vec<u8>> {
+    let _span = span!(INFO, "decompress_bundle");
+    let mut json = Vec::new();
+    brotli::Decompressor::new(compressed, 4096).read_to_end(&mut json)?;
+    Ok(json)
+}
+
+/// The 64 bit FNV-1a hash of `bytes`. Unlike std's hashers it is guaranteed to be stable, so it
+/// can identify the content of a bank across sessions and versions.
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stable_content_hash() {
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn round_trip() {
+        let xlsx = std::fs::read("test.xlsx").unwrap();
+        let bundle = Bundle::parse_xlsx(&xlsx);
+        let compressed = bundle.compress();
+        let json = decompress(&compressed).unwrap();
+        // the bank text is repetitive enough to compress several times
+        assert!(compressed.len() * 4 < json.len());
+        assert!(compressed.len() < xlsx.len());
+        let read = Bundle::from_json(&json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&read).unwrap(),
+            serde_json::to_value(&bundle).unwrap()
+        );
+        assert!(decompress(b"not brotli").is_err());
+    }
+}
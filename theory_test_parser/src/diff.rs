@@ -0,0 +1,148 @@
+//! Comparing two versions of a bank, question by question, matched by their official numbers.
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::question_parser::{ExamQuestions, Question};
+
+/// How the texts of questions are compared.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum CompareMode {
+    #[default]
+    Exact,
+    /// Runs of whitespace count as a single space, and leading and trailing whitespace is
+    /// ignored, e.g. for a bank re-exported by another spreadsheet program.
+    IgnoreWhitespace,
+}
+
+impl CompareMode {
+    fn normalize<'a>(&self, text: &'a str) -> std::borrow::Cow<'a, str> {
+        match self {
+            CompareMode::Exact => text.into(),
+            CompareMode::IgnoreWhitespace => {
+                text.split_whitespace().collect::<Vec<_>>().join(" ").into()
+            }
+        }
+    }
+
+    fn same_text(&self, a: &str, b: &str) -> bool {
+        self.normalize(a) == self.normalize(b)
+    }
+
+    /// Whether two versions of a question are the same.
+    pub fn same_question(&self, old: &Question, new: &Question) -> bool {
+        self.same_text(&old.question, &new.question)
+            && old.answers.possible_answers.len() == new.answers.possible_answers.len()
+            && old
+                .answers
+                .possible_answers
+                .iter()
+                .zip(&new.answers.possible_answers)
+                .all(|(a, b)| self.same_text(a, b))
+            && old.answers.correct_answer == new.answers.correct_answer
+            && old.category == new.category
+            && old.license_classes == new.license_classes
+            && old.image_url == new.image_url
+            && old.explanation == new.explanation
+    }
+}
+
+/// A question which exists in both versions, but differs.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct ModifiedQuestion {
+    pub num: usize,
+    pub old: Question,
+    pub new: Question,
+}
+
+/// The differences between two versions of a bank, each list ordered by question number.
+#[derive(Clone, PartialEq, Debug, Default, Serialize)]
+pub struct BankDiff {
+    pub added: Vec<usize>,
+    pub removed: Vec<usize>,
+    pub modified: Vec<ModifiedQuestion>,
+}
+
+impl BankDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+fn by_num(bank: &ExamQuestions) -> BTreeMap<usize, &Question> {
+    bank.questions.iter().map(|q| (q.num, q)).collect()
+}
+
+/// Compare the `old` version of a bank with the `new` one.
+pub fn diff(old: &ExamQuestions, new: &ExamQuestions, mode: CompareMode) -> BankDiff {
+    let (old, new) = (by_num(old), by_num(new));
+    let mut diff = BankDiff::default();
+    for (num, old_question) in &old {
+        match new.get(num) {
+            None => diff.removed.push(*num),
+            Some(new_question) if !mode.same_question(old_question, new_question) => {
+                diff.modified.push(ModifiedQuestion {
+                    num: *num,
+                    old: (*old_question).clone(),
+                    new: (*new_question).clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added = new
+        .keys()
+        .filter(|num| !old.contains_key(num))
+        .copied()
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn question(num: usize, text: &str) -> Question {
+        Question {
+            question: format!("{:04}. {}", num, text),
+            ..Question::sample(num, 0)
+        }
+    }
+
+    fn bank(questions: Vec<Question>) -> ExamQuestions {
+        ExamQuestions { questions }
+    }
+
+    #[test]
+    fn differences() {
+        let old = bank(vec![
+            question(1, "one"),
+            question(2, "two"),
+            question(3, "three"),
+        ]);
+        let mut moved_answer = question(3, "three");
+        moved_answer.answers.correct_answer = 1;
+        let new = bank(vec![
+            question(4, "four"),
+            question(2, "two  "),
+            moved_answer,
+        ]);
+        let exact = diff(&old, &new, CompareMode::Exact);
+        assert_eq!(exact.added, vec![4]);
+        assert_eq!(exact.removed, vec![1]);
+        assert_eq!(
+            exact.modified.iter().map(|m| m.num).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        let normalized = diff(&old, &new, CompareMode::IgnoreWhitespace);
+        assert_eq!(
+            normalized
+                .modified
+                .iter()
+                .map(|m| m.num)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+        assert!(diff(&old, &old, CompareMode::Exact).is_empty());
+    }
+}
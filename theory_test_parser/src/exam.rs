@@ -0,0 +1,589 @@
+//! Generating exams out of a bank and grading them, reproducibly from a seed.
+use std::time::Duration;
+
+use rand::{SeedableRng, seq::IndexedRandom, seq::SliceRandom};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    question_parser::{ExamQuestions, LicenseClass, Question, QuestionCategory},
+    trace::{event, span},
+};
+
+/// The number of questions of the official exam.
+pub const OFFICIAL_NUM_QUESTIONS: usize = 30;
+/// The most mistakes, wrong or unanswered, which still pass the official exam.
+pub const OFFICIAL_MAX_MISTAKES: usize = 4;
+
+/// The parameters of the official exam of a license class.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ExamRules {
+    pub class: LicenseClass,
+    pub num_questions: usize,
+    /// The most mistakes, wrong or unanswered, which still pass.
+    pub max_mistakes: usize,
+    pub duration: Duration,
+}
+
+impl ExamRules {
+    /// The rules of the official exam of `class`. Every class has the same length, time and
+    /// threshold.
+    pub const fn official(class: LicenseClass) -> Self {
+        Self {
+            class,
+            num_questions: OFFICIAL_NUM_QUESTIONS,
+            max_mistakes: OFFICIAL_MAX_MISTAKES,
+            duration: Duration::from_secs(40 * 60),
+        }
+    }
+
+    /// The most mistakes which still pass an exam of `num_questions`, scaled down from these
+    /// rules.
+    pub fn allowed_mistakes(&self, num_questions: usize) -> usize {
+        num_questions * self.max_mistakes / self.num_questions.max(1)
+    }
+}
+
+/// What an exam is made of. The same spec always generates the same exam out of the same bank.
+/// Missing fields take their default when deserialized.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExamSpec {
+    pub num_questions: usize,
+    pub class: LicenseClass,
+    /// The categories the questions are drawn from, all of them when empty.
+    pub categories: Vec<QuestionCategory>,
+    /// Draw from each category in proportion to its share of the pool, as the official exam
+    /// does, rather than uniformly from the whole pool.
+    pub official_distribution: bool,
+    pub seed: u64,
+}
+
+impl Default for ExamSpec {
+    fn default() -> Self {
+        Self {
+            num_questions: OFFICIAL_NUM_QUESTIONS,
+            class: LicenseClass::B,
+            categories: Vec::new(),
+            official_distribution: false,
+            seed: 0,
+        }
+    }
+}
+
+/// An exam generated from an [`ExamSpec`], its questions with their answers shuffled.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GeneratedExam {
+    pub seed: u64,
+    /// The class whose official rules grade the exam.
+    pub class: LicenseClass,
+    pub questions: Vec<Question>,
+}
+
+impl ExamSpec {
+    /// The spec of an official exam of `class`, by its [`ExamRules`].
+    pub fn official(class: LicenseClass, seed: u64) -> Self {
+        Self {
+            num_questions: ExamRules::official(class).num_questions,
+            class,
+            categories: Vec::new(),
+            official_distribution: true,
+            seed,
+        }
+    }
+
+    /// A short id the spec can be rebuilt from with [`ExamSpec::from_id`], e.g. "B.30.all.u.99",
+    /// to refer to an exam without storing it.
+    pub fn id(&self) -> String {
+        let categories = if self.categories.is_empty() {
+            "all".to_string()
+        } else {
+            self.categories
+                .iter()
+                .map(|category| category.code())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let distribution = if self.official_distribution { "o" } else { "u" };
+        format!(
+            "{}.{}.{}.{}.{}",
+            self.class.code(),
+            self.num_questions,
+            categories,
+            distribution,
+            self.seed
+        )
+    }
+
+    /// The inverse of [`ExamSpec::id`], `None` for a malformed id.
+    pub fn from_id(id: &str) -> Option<Self> {
+        let mut parts = id.split('.');
+        let class = LicenseClass::from_code(parts.next()?)?;
+        let num_questions = parts.next()?.parse().ok()?;
+        let categories = match parts.next()? {
+            "all" => Vec::new(),
+            codes => codes
+                .split(',')
+                .map(QuestionCategory::from_code)
+                .collect::<Option<_>>()?,
+        };
+        let official_distribution = match parts.next()? {
+            "o" => true,
+            "u" => false,
+            _ => return None,
+        };
+        let seed = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            num_questions,
+            class,
+            categories,
+            official_distribution,
+            seed,
+        })
+    }
+
+    /// Whether `question` can be drawn for the exam.
+    pub fn matches(&self, question: &Question) -> bool {
+        question.license_classes.contains(&self.class)
+            && (self.categories.is_empty() || self.categories.contains(&question.category))
+    }
+
+    /// Draw the questions out of `bank`. When fewer questions match than asked for, all of them
+    /// are drawn.
+    pub fn generate(&self, bank: &ExamQuestions) -> GeneratedExam {
+        let _span = span!(INFO, "sample", seed = self.seed);
+        let mut rng = Pcg64::seed_from_u64(self.seed);
+        let pool = bank
+            .questions
+            .iter()
+            .filter(|q| self.matches(q))
+            .collect::<Vec<_>>();
+        let mut questions = if self.official_distribution {
+            let mut questions = category_quotas(&pool, self.num_questions)
+                .into_iter()
+                .flat_map(|(category, quota)| {
+                    let category_pool = pool
+                        .iter()
+                        .filter(|q| q.category == category)
+                        .collect::<Vec<_>>();
+                    category_pool
+                        .choose_multiple(&mut rng, quota)
+                        .map(|q| (**q).clone())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            questions.shuffle(&mut rng);
+            questions
+        } else {
+            pool.choose_multiple(&mut rng, self.num_questions)
+                .map(|q| (*q).clone())
+                .collect::<Vec<_>>()
+        };
+        for question in questions.iter_mut() {
+            shuffle_answers(question, &mut rng);
+        }
+        event!(
+            DEBUG,
+            pool = pool.len(),
+            questions = questions.len(),
+            "sampled the exam"
+        );
+        GeneratedExam {
+            seed: self.seed,
+            class: self.class,
+            questions,
+        }
+    }
+}
+
+/// How many of `num_questions` to draw from each category of `pool`, in proportion to the size
+/// of the category and in the order of [`QuestionCategory::ALL`]. The questions left over after
+/// rounding down go to the categories with the largest remainders.
+fn category_quotas(pool: &[&Question], num_questions: usize) -> Vec<(QuestionCategory, usize)> {
+    let num_questions = num_questions.min(pool.len());
+    let sizes = QuestionCategory::ALL.map(|category| {
+        (
+            category,
+            pool.iter().filter(|q| q.category == category).count(),
+        )
+    });
+    let mut quotas = sizes.map(|(category, size)| {
+        let share = size * num_questions;
+        (
+            category,
+            share / pool.len().max(1),
+            share % pool.len().max(1),
+        )
+    });
+    let assigned = quotas.iter().map(|(_, quota, _)| quota).sum::<usize>();
+    let mut by_remainder = (0..quotas.len()).collect::<Vec<_>>();
+    by_remainder.sort_by_key(|idx| std::cmp::Reverse(quotas[*idx].2));
+    for idx in by_remainder.into_iter().take(num_questions - assigned) {
+        quotas[idx].1 += 1;
+    }
+    quotas
+        .into_iter()
+        .map(|(category, quota, _)| (category, quota))
+        .collect()
+}
+
+/// Shuffle the answers of `question`, keeping track of the correct one. A question whose correct
+/// answer is missing is left as is.
+fn shuffle_answers(question: &mut Question, rng: &mut Pcg64) {
+    let answers = &mut question.answers;
+    if answers.correct_answer >= answers.possible_answers.len() {
+        return;
+    }
+    let mut order = (0..answers.possible_answers.len()).collect::<Vec<_>>();
+    order.shuffle(rng);
+    answers.correct_answer = order
+        .iter()
+        .position(|idx| *idx == answers.correct_answer)
+        .unwrap();
+    answers.possible_answers = order
+        .into_iter()
+        .map(|idx| answers.possible_answers[idx].clone())
+        .collect();
+}
+
+/// How many questions of a category were answered correctly.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CategoryScore {
+    pub category: QuestionCategory,
+    pub correct: usize,
+    pub total: usize,
+}
+
+/// The grade of a generated exam.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExamGrade {
+    /// Whether each question was answered correctly, in exam order.
+    pub correct: Vec<bool>,
+    /// The categories the exam has questions of, in the order of [`QuestionCategory::ALL`].
+    pub per_category: Vec<CategoryScore>,
+    /// The official rules of the class of the exam, which grade it.
+    pub rules: ExamRules,
+}
+
+/// A graded exam as it is exported, e.g. by the server to clients which don't grade exams
+/// themselves.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ExamResult {
+    /// The id of the exam's spec, see [`ExamSpec::id`].
+    pub id: String,
+    pub num_correct: usize,
+    pub mistakes: usize,
+    pub allowed_mistakes: usize,
+    pub passed: bool,
+    /// Whether each question was answered correctly, in exam order.
+    pub correct: Vec<bool>,
+    /// The 0-based correct answer of each question, now that the exam is over.
+    pub correct_answers: Vec<usize>,
+    pub per_category: Vec<CategoryScore>,
+}
+
+impl ExamGrade {
+    pub fn num_correct(&self) -> usize {
+        self.correct.iter().filter(|c| **c).count()
+    }
+
+    /// Questions which were answered wrong or not at all.
+    pub fn mistakes(&self) -> usize {
+        self.correct.len() - self.num_correct()
+    }
+
+    /// The most mistakes which still pass, scaled down from the official exam of its class.
+    pub fn allowed_mistakes(&self) -> usize {
+        self.rules.allowed_mistakes(self.correct.len())
+    }
+
+    pub fn passed(&self) -> bool {
+        self.mistakes() <= self.allowed_mistakes()
+    }
+}
+
+impl GeneratedExam {
+    /// Grade the exam by the selected answer of each question, `None` for unanswered ones.
+    /// Missing selections count as unanswered.
+    pub fn grade(&self, selections: &[Option<usize>]) -> ExamGrade {
+        let _span = span!(DEBUG, "grade");
+        let correct = self
+            .questions
+            .iter()
+            .enumerate()
+            .map(|(idx, q)| {
+                selections.get(idx).copied().flatten() == Some(q.answers.correct_answer)
+            })
+            .collect::<Vec<_>>();
+        let per_category = QuestionCategory::ALL
+            .into_iter()
+            .filter_map(|category| {
+                let (total, num_correct) = self
+                    .questions
+                    .iter()
+                    .zip(&correct)
+                    .filter(|(q, _)| q.category == category)
+                    .fold((0, 0), |(total, num_correct), (_, correct)| {
+                        (total + 1, num_correct + usize::from(*correct))
+                    });
+                (total > 0).then_some(CategoryScore {
+                    category,
+                    correct: num_correct,
+                    total,
+                })
+            })
+            .collect();
+        let grade = ExamGrade {
+            correct,
+            per_category,
+            rules: ExamRules::official(self.class),
+        };
+        event!(
+            DEBUG,
+            correct = grade.num_correct(),
+            passed = grade.passed(),
+            "graded the exam"
+        );
+        grade
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    fn question(num: usize, category: QuestionCategory, classes: Vec<LicenseClass>) -> Question {
+        Question {
+            category,
+            license_classes: classes,
+            ..Question::sample(num, 0)
+        }
+    }
+
+    fn bank() -> ExamQuestions {
+        ExamQuestions {
+            questions: (1..=20)
+                .map(|num| {
+                    let category = QuestionCategory::ALL[num % 4];
+                    let classes = if num % 5 == 0 {
+                        vec![LicenseClass::C]
+                    } else {
+                        vec![LicenseClass::B]
+                    };
+                    question(num, category, classes)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn generation() {
+        let spec = ExamSpec {
+            num_questions: 5,
+            seed: 42,
+            ..Default::default()
+        };
+        let exam = spec.generate(&bank());
+        let nums = exam.questions.iter().map(|q| q.num).collect::<Vec<_>>();
+        assert_eq!(nums.len(), 5);
+        assert!(exam.questions.iter().all(|q| spec.matches(q)));
+        // reproducible, answers included
+        let again = spec.generate(&bank());
+        assert_eq!(
+            again.questions.iter().map(|q| q.num).collect::<Vec<_>>(),
+            nums
+        );
+        for (a, b) in exam.questions.iter().zip(&again.questions) {
+            assert_eq!(a.answers.possible_answers, b.answers.possible_answers);
+        }
+        // the correct answer follows the shuffle
+        for q in &exam.questions {
+            assert_eq!(
+                q.answers.possible_answers[q.answers.correct_answer],
+                "answer 0"
+            );
+        }
+        let road_signs = ExamSpec {
+            num_questions: 100,
+            categories: vec![QuestionCategory::RoadSigns],
+            ..Default::default()
+        }
+        .generate(&bank());
+        // every one of the 4 B road signs questions, as fewer than asked for match
+        assert_eq!(road_signs.questions.len(), 4);
+    }
+
+    #[test]
+    fn ids() {
+        let spec = ExamSpec {
+            seed: 99,
+            ..Default::default()
+        };
+        assert_eq!(spec.id(), "B.30.all.u.99");
+        assert_eq!(ExamSpec::from_id(&spec.id()), Some(spec));
+        let spec = ExamSpec {
+            num_questions: 10,
+            class: LicenseClass::C1,
+            categories: vec![QuestionCategory::RoadSigns, QuestionCategory::Safety],
+            official_distribution: true,
+            seed: u64::MAX,
+        };
+        assert_eq!(ExamSpec::from_id(&spec.id()), Some(spec));
+        for id in [
+            "",
+            "X.30.all.u.1",
+            "B.30.signs.u.1",
+            "B.30.all.x.1",
+            "B.30.all.u.1.2",
+        ] {
+            assert_eq!(ExamSpec::from_id(id), None, "{id}");
+        }
+    }
+
+    #[test]
+    fn official_distribution() {
+        let bank = bank();
+        let pool = bank.questions.iter().collect::<Vec<_>>();
+        // 5 of each category out of 20 questions, which divides evenly
+        assert_eq!(
+            category_quotas(&pool, 8),
+            QuestionCategory::ALL.map(|category| (category, 2))
+        );
+        let quotas = category_quotas(&pool[..7], 5);
+        assert_eq!(quotas.iter().map(|(_, quota)| quota).sum::<usize>(), 5);
+        assert_eq!(
+            category_quotas(&pool, 100)
+                .iter()
+                .map(|(_, q)| q)
+                .sum::<usize>(),
+            20
+        );
+        let spec = ExamSpec {
+            num_questions: 8,
+            official_distribution: true,
+            seed: 7,
+            ..Default::default()
+        };
+        let exam = spec.generate(&bank);
+        // 16 B questions, 4 of each category
+        for category in QuestionCategory::ALL {
+            assert_eq!(
+                exam.questions
+                    .iter()
+                    .filter(|q| q.category == category)
+                    .count(),
+                2
+            );
+        }
+        assert_eq!(
+            spec.generate(&bank)
+                .questions
+                .iter()
+                .map(|q| q.num)
+                .collect::<Vec<_>>(),
+            exam.questions.iter().map(|q| q.num).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn official_rules() {
+        for class in LicenseClass::ALL {
+            let rules = ExamRules::official(class);
+            assert_eq!(rules.class, class);
+            assert!(rules.max_mistakes < rules.num_questions);
+            assert_eq!(
+                rules.allowed_mistakes(rules.num_questions),
+                rules.max_mistakes
+            );
+        }
+        let b = ExamRules::official(LicenseClass::B);
+        assert_eq!(
+            (b.num_questions, b.max_mistakes, b.duration),
+            (30, 4, Duration::from_secs(40 * 60))
+        );
+        assert_eq!(b.allowed_mistakes(15), 2);
+    }
+
+    #[test]
+    fn official_exams_follow_the_rules() {
+        let bank = ExamQuestions {
+            questions: (1..=80)
+                .map(|num| {
+                    question(
+                        num,
+                        QuestionCategory::ALL[num % 4],
+                        vec![LicenseClass::B, LicenseClass::C],
+                    )
+                })
+                .collect(),
+        };
+        for class in [LicenseClass::B, LicenseClass::C] {
+            let rules = ExamRules::official(class);
+            let exam = ExamSpec::official(class, 3).generate(&bank);
+            assert_eq!(exam.questions.len(), rules.num_questions);
+            assert_eq!(exam.class, class);
+            let pool = bank.questions.iter().collect::<Vec<_>>();
+            for (category, count) in category_quotas(&pool, rules.num_questions) {
+                assert_eq!(
+                    exam.questions
+                        .iter()
+                        .filter(|q| q.category == category)
+                        .count(),
+                    count,
+                    "{class:?} {category:?}"
+                );
+            }
+        }
+        // the categories which are short are drawn from less
+        let mut short = bank.clone();
+        short
+            .questions
+            .retain(|q| q.category != QuestionCategory::RoadSigns || q.num < 20);
+        let exam = ExamSpec::official(LicenseClass::B, 3).generate(&short);
+        assert_eq!(exam.questions.len(), 30);
+        let mut nums = exam.questions.iter().map(|q| q.num).collect::<Vec<_>>();
+        nums.sort();
+        nums.dedup();
+        assert_eq!(nums.len(), 30);
+    }
+
+    #[test]
+    fn grading() {
+        let exam = GeneratedExam {
+            seed: 0,
+            class: LicenseClass::C,
+            questions: vec![
+                question(1, QuestionCategory::Safety, Vec::new()),
+                question(2, QuestionCategory::RoadSigns, Vec::new()),
+                question(3, QuestionCategory::Safety, Vec::new()),
+            ],
+        };
+        let grade = exam.grade(&[Some(0), Some(1)]);
+        assert_eq!(grade.correct, vec![true, false, false]);
+        assert_eq!(
+            grade.per_category,
+            vec![
+                CategoryScore {
+                    category: QuestionCategory::RoadSigns,
+                    correct: 0,
+                    total: 1,
+                },
+                CategoryScore {
+                    category: QuestionCategory::Safety,
+                    correct: 1,
+                    total: 2,
+                },
+            ]
+        );
+        assert_eq!(grade.mistakes(), 2);
+        assert_eq!(
+            grade.allowed_mistakes(),
+            ExamRules::official(LicenseClass::C).allowed_mistakes(3)
+        );
+        assert!(!grade.passed());
+    }
+}
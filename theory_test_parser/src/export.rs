@@ -0,0 +1,313 @@
+//! Exporting questions to the formats of other tools.
+use std::fmt::Write;
+
+use crate::question_parser::{Question, QuestionCategory};
+
+/// The version of the content of the exports, bumped when they change incompatibly. The JSON
+/// Schema documents of the exports are published under it.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A format questions can be exported to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ExportFormat {
+    /// The serde representation of the questions, as an array.
+    Json { pretty: bool },
+    /// A row per question, with a column per answer.
+    Csv,
+    /// Anki's text import: a tab separated note per question, tagged by category.
+    Anki,
+    /// Moodle's GIFT quiz format.
+    Gift,
+    /// A readable document, e.g. for printing.
+    Markdown,
+}
+
+/// Export `questions` in `format`.
+pub fn export(questions: &[Question], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json { pretty } => to_json(questions, pretty),
+        ExportFormat::Csv => to_csv(questions),
+        ExportFormat::Anki => to_anki(questions),
+        ExportFormat::Gift => to_gift(questions),
+        ExportFormat::Markdown => to_markdown(questions),
+    }
+}
+
+/// The text of a question without the number it starts with, e.g. "0862. ".
+pub(crate) fn question_text(question: &Question) -> &str {
+    question.question.get(6..).unwrap_or(&question.question)
+}
+
+fn to_json(questions: &[Question], pretty: bool) -> String {
+    let json = if pretty {
+        serde_json::to_string_pretty(questions)
+    } else {
+        serde_json::to_string(questions)
+    };
+    // questions are plain data, serializing them can't fail
+    json.expect("questions serialize") + "\n"
+}
+
+/// Quote a CSV field when it needs to be.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Answers beyond the usual 4 are left out, the columns are the same for every question.
+const CSV_ANSWERS: usize = 4;
+
+fn to_csv(questions: &[Question]) -> String {
+    let mut csv = String::from(
+        "num,question,answer_1,answer_2,answer_3,answer_4,correct_answer,category,license_classes,image_url\n",
+    );
+    for question in questions {
+        let mut fields = vec![question.num.to_string(), csv_field(question_text(question))];
+        fields.extend((0..CSV_ANSWERS).map(|idx| {
+            csv_field(
+                question
+                    .answers
+                    .possible_answers
+                    .get(idx)
+                    .map(String::as_str)
+                    .unwrap_or_default(),
+            )
+        }));
+        fields.push((question.answers.correct_answer + 1).to_string());
+        fields.push(question.category.code().to_string());
+        fields.push(
+            question
+                .license_classes
+                .iter()
+                .map(|class| class.code())
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        fields.push(csv_field(question.image_url.as_deref().unwrap_or_default()));
+        csv += &fields.join(",");
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Escape text for an HTML field of Anki, which can't contain tabs or new lines either.
+fn anki_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace(['\t', '\n'], " ")
+}
+
+fn to_anki(questions: &[Question]) -> String {
+    let mut anki = String::from("#separator:tab\n#html:true\n#tags column:3\n");
+    for question in questions {
+        let mut front = anki_html(&question.question);
+        if let Some(image_url) = &question.image_url {
+            write!(front, "<br><img src=\"{}\">", anki_html(image_url)).unwrap();
+        }
+        front += "<ol>";
+        for answer in &question.answers.possible_answers {
+            write!(front, "<li>{}</li>", anki_html(answer)).unwrap();
+        }
+        front += "</ol>";
+        let back = question
+            .answers
+            .possible_answers
+            .get(question.answers.correct_answer)
+            .map(|answer| anki_html(answer))
+            .unwrap_or_default();
+        writeln!(anki, "{front}\t{back}\t{}", category_tag(question.category)).unwrap();
+    }
+    anki
+}
+
+fn category_tag(category: QuestionCategory) -> String {
+    format!("theory_test::{}", category.code())
+}
+
+/// Escape the characters GIFT gives a meaning to.
+fn gift_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '~' | '=' | '#' | '{' | '}' | ':' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn to_gift(questions: &[Question]) -> String {
+    let mut gift = String::new();
+    for question in questions {
+        writeln!(gift, "// {}", question.category.as_str_he()).unwrap();
+        write!(
+            gift,
+            "::{:04}::{} {{",
+            question.num,
+            gift_escape(question_text(question))
+        )
+        .unwrap();
+        for (idx, answer) in question.answers.possible_answers.iter().enumerate() {
+            let mark = if idx == question.answers.correct_answer {
+                '='
+            } else {
+                '~'
+            };
+            write!(gift, "\n\t{mark}{}", gift_escape(answer)).unwrap();
+        }
+        gift += "\n}\n\n";
+    }
+    gift
+}
+
+fn to_markdown(questions: &[Question]) -> String {
+    let mut markdown = String::new();
+    for question in questions {
+        writeln!(
+            markdown,
+            "## {:04}. {}\n",
+            question.num,
+            question_text(question)
+        )
+        .unwrap();
+        writeln!(markdown, "*{}*\n", question.category.as_str_he()).unwrap();
+        if let Some(image_url) = &question.image_url {
+            writeln!(markdown, "![{:04}]({image_url})\n", question.num).unwrap();
+        }
+        for (idx, answer) in question.answers.possible_answers.iter().enumerate() {
+            writeln!(markdown, "{}. {answer}", idx + 1).unwrap();
+        }
+        writeln!(
+            markdown,
+            "\n**תשובה נכונה:** {}\n",
+            question.answers.correct_answer + 1
+        )
+        .unwrap();
+    }
+    markdown
+}
+
+/// Escape text for HTML, in content or in a quoted attribute.
+pub fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A question as an HTML fragment, e.g. for a page of its own. The correct answer has the
+/// `correct` class.
+pub fn question_html(question: &Question) -> String {
+    let mut html = format!("<article class=\"question\" id=\"q{:04}\">\n", question.num);
+    writeln!(html, "<h1>{}</h1>", html_escape(&question.question)).unwrap();
+    writeln!(
+        html,
+        "<p class=\"category\">{}</p>",
+        html_escape(question.category.as_str_he())
+    )
+    .unwrap();
+    if let Some(image_url) = &question.image_url {
+        writeln!(
+            html,
+            "<img src=\"{}\" alt=\"{:04}\">",
+            html_escape(image_url),
+            question.num
+        )
+        .unwrap();
+    }
+    html += "<ol class=\"answers\">\n";
+    for (idx, answer) in question.answers.possible_answers.iter().enumerate() {
+        let class = if idx == question.answers.correct_answer {
+            " class=\"correct\""
+        } else {
+            ""
+        };
+        writeln!(html, "<li{class}>{}</li>", html_escape(answer)).unwrap();
+    }
+    html += "</ol>\n</article>\n";
+    html
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::question_parser::{Answers, LicenseClass};
+
+    fn question() -> Question {
+        Question {
+            question: "0007. מה {זה}: \"שלט\", נכון?".to_string(),
+            answers: Answers {
+                possible_answers: vec!["a=b".to_string(), "<c>".to_string()],
+                correct_answer: 1,
+            },
+            category: QuestionCategory::RoadSigns,
+            license_classes: vec![LicenseClass::B, LicenseClass::C1],
+            image_url: Some("https://x/7.png".to_string()),
+            ..Question::sample(7, 1)
+        }
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            export(&[question()], ExportFormat::Csv).lines().nth(1),
+            Some("7,\"מה {זה}: \"\"שלט\"\", נכון?\",a=b,<c>,,,2,road_signs,B C1,https://x/7.png")
+        );
+    }
+
+    #[test]
+    fn anki() {
+        let anki = export(&[question()], ExportFormat::Anki);
+        assert_eq!(
+            anki.lines().nth(3),
+            Some(
+                "0007. מה {זה}: \"שלט\", נכון?<br><img src=\"https://x/7.png\"><ol><li>a=b</li><li>&lt;c&gt;</li></ol>\t&lt;c&gt;\ttheory_test::road_signs"
+            )
+        );
+    }
+
+    #[test]
+    fn gift() {
+        assert_eq!(
+            export(&[question()], ExportFormat::Gift),
+            "// תמרורים\n::0007::מה \\{זה\\}\\: \"שלט\", נכון? {\n\t~a\\=b\n\t=<c>\n}\n\n"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let json = export(&[question()], ExportFormat::Json { pretty: false });
+        assert!(json.starts_with("[{\"num\":7,"));
+        assert!(json.contains("\"category\":\"road_signs\",\"license_classes\":[\"B\",\"C1\"]"));
+        let parsed: Vec<Question> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0].answers.correct_answer, 1);
+    }
+
+    #[test]
+    fn html() {
+        assert_eq!(
+            question_html(&question()),
+            "<article class=\"question\" id=\"q0007\">\n\
+             <h1>0007. מה {זה}: &quot;שלט&quot;, נכון?</h1>\n\
+             <p class=\"category\">תמרורים</p>\n\
+             <img src=\"https://x/7.png\" alt=\"0007\">\n\
+             <ol class=\"answers\">\n<li>a=b</li>\n<li class=\"correct\">&lt;c&gt;</li>\n</ol>\n\
+             </article>\n"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let markdown = export(&[question()], ExportFormat::Markdown);
+        assert!(markdown.starts_with("## 0007. מה {זה}: \"שלט\", נכון?\n\n*תמרורים*\n\n![0007](https://x/7.png)\n\n1. a=b\n2. <c>\n"));
+        assert!(markdown.ends_with("**תשובה נכונה:** 2\n\n"));
+    }
+}
@@ -1 +1,22 @@
+#[cfg(feature = "apkg")]
+pub mod apkg;
+#[cfg(feature = "bundle")]
+pub mod bundle;
+pub mod diff;
+pub mod exam;
+pub mod export;
+pub mod observer;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+#[cfg(feature = "python")]
+mod python;
 pub mod question_parser;
+#[cfg(feature = "schema")]
+mod schema;
+#[cfg(feature = "toml")]
+pub mod toml_bank;
+pub mod trace;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "xlsx")]
+mod xlsx;
@@ -0,0 +1,92 @@
+//! Following the parsing of a bank as it goes, e.g. to show its warnings as they happen.
+use std::{cell::RefCell, io::Write};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::question_parser::RowError;
+
+/// Something wrong in the bank which doesn't stop its parsing.
+#[derive(Debug, Error, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ParseWarning {
+    /// The html of the answers of a row isn't well formed. What could be read of it is kept.
+    #[error("Row {row}: malformed answers html: {message}")]
+    MalformedHtml { row: usize, message: String },
+    /// A row which couldn't be parsed, skipped when parsing leniently.
+    #[error("{0}, skipped")]
+    SkippedRow(RowError),
+}
+
+/// Told about the rows of a bank as they are parsed, given with
+/// [`ParseOptions::observer`](crate::question_parser::ParseOptions::observer).
+pub trait ParseObserver {
+    fn warning(&self, warning: &ParseWarning);
+    /// The question numbered `num` was parsed.
+    fn row_parsed(&self, num: usize);
+}
+
+/// An observer which ignores everything, the default.
+pub struct NoopObserver;
+
+impl ParseObserver for NoopObserver {
+    fn warning(&self, _warning: &ParseWarning) {}
+
+    fn row_parsed(&self, _num: usize) {}
+}
+
+/// An observer which keeps the warnings, e.g. to show them once parsing failed.
+#[derive(Default)]
+pub struct WarningCollector {
+    warnings: RefCell<Vec<ParseWarning>>,
+}
+
+impl WarningCollector {
+    pub fn into_warnings(self) -> Vec<ParseWarning> {
+        self.warnings.into_inner()
+    }
+}
+
+impl ParseObserver for WarningCollector {
+    fn warning(&self, warning: &ParseWarning) {
+        self.warnings.borrow_mut().push(warning.clone());
+    }
+
+    fn row_parsed(&self, _num: usize) {}
+}
+
+/// An observer which writes each warning as a line as soon as it happens, e.g. to stderr.
+pub struct LogObserver<W: Write> {
+    out: RefCell<W>,
+}
+
+impl<W: Write> LogObserver<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: RefCell::new(out),
+        }
+    }
+}
+
+impl<W: Write> ParseObserver for LogObserver<W> {
+    fn warning(&self, warning: &ParseWarning) {
+        // a warning which can't be written is lost, like the ones nobody observes
+        let _ = writeln!(self.out.borrow_mut(), "warning: {warning}");
+    }
+
+    fn row_parsed(&self, _num: usize) {}
+}
+
+/// Tells each of the observers.
+pub(crate) struct Both<'a>(pub &'a dyn ParseObserver, pub &'a dyn ParseObserver);
+
+impl ParseObserver for Both<'_> {
+    fn warning(&self, warning: &ParseWarning) {
+        self.0.warning(warning);
+        self.1.warning(warning);
+    }
+
+    fn row_parsed(&self, num: usize) {
+        self.0.row_parsed(num);
+        self.1.row_parsed(num);
+    }
+}
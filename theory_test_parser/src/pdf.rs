@@ -0,0 +1,925 @@
+//! Exams as PDF documents to print. The text is shaped with an embedded Hebrew font and laid out
+//! right to left, line by line, with the runs of other directions (numbers, latin) reordered.
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+};
+
+use pdf_writer::{
+    Content, Filter, Finish, Name, Pdf, Rect, Ref, Str,
+    types::{CidFontType, FontFlags, SystemInfo, UnicodeCmap},
+};
+use rustybuzz::{Direction, Face, UnicodeBuffer, ttf_parser::GlyphId};
+use thiserror::Error;
+use unicode_bidi::{BidiInfo, Level};
+
+use crate::{
+    exam::GeneratedExam,
+    export::question_text,
+    question_parser::{LicenseClass, Question, QuestionCategory},
+};
+
+/// DejaVu Sans, which has Hebrew glyphs and may be embedded, see the license next to it.
+const FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+const FONT_NAME: Name = Name(b"DejaVuSans");
+const FONT_RESOURCE: Name = Name(b"F1");
+const SYSTEM_INFO: SystemInfo = SystemInfo {
+    registry: Str(b"Adobe"),
+    ordering: Str(b"Identity"),
+    supplement: 0,
+};
+
+/// A4, in points.
+const PAGE_WIDTH: f32 = 595.28;
+const PAGE_HEIGHT: f32 = 841.89;
+const MARGIN: f32 = 50.0;
+const TEXT_SIZE: f32 = 11.0;
+const TITLE_SIZE: f32 = 16.0;
+/// The height of a line, relative to the size of its text.
+const LINE_HEIGHT: f32 = 1.4;
+/// The space between questions.
+const QUESTION_GAP: f32 = 10.0;
+/// How far answers are indented from the right margin.
+const ANSWER_INDENT: f32 = 16.0;
+const CARD_TEXT_SIZE: f32 = 10.0;
+/// The size of the category of a card and the number of its question.
+const CARD_LABEL_SIZE: f32 = 8.0;
+/// The space between the edges of a card and what's on it.
+const CARD_PADDING: f32 = 10.0;
+/// The gray of the lines to cut the cards along, and of the labels.
+const CARD_GRAY: f32 = 0.6;
+
+/// The letters answers are marked with, in order.
+const ANSWER_LETTERS: [char; 10] = ['א', 'ב', 'ג', 'ד', 'ה', 'ו', 'ז', 'ח', 'ט', 'י'];
+
+#[derive(Debug, Error)]
+pub enum PdfError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("the image of question {0} isn't a JPEG")]
+    UnsupportedImage(usize),
+}
+
+/// How an exam is printed.
+#[derive(Clone, Debug)]
+pub struct PdfOptions {
+    /// The license class the exam is for, printed in the header.
+    pub class: LicenseClass,
+    /// The date printed in the header, e.g. "2025-03-01".
+    pub date: String,
+    /// The width images are scaled to, in points.
+    pub image_width: f32,
+    /// The JPEG image of each question, by number. Questions with an image which isn't given
+    /// show its url instead.
+    pub images: HashMap<usize, Vec<u8>>,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            class: LicenseClass::B,
+            date: String::new(),
+            image_width: 200.0,
+            images: HashMap::new(),
+        }
+    }
+}
+
+/// How flashcards are printed.
+#[derive(Clone, Debug)]
+pub struct FlashcardOptions {
+    /// The categories of the questions to make cards of, all of them when empty.
+    pub categories: Vec<QuestionCategory>,
+    /// The number of cards across a page.
+    pub columns: usize,
+    /// The number of cards down a page.
+    pub rows: usize,
+    /// Print the category of each question at the top of its card.
+    pub show_category: bool,
+    /// The JPEG image of each question, by number, as in [`PdfOptions::images`].
+    pub images: HashMap<usize, Vec<u8>>,
+}
+
+impl Default for FlashcardOptions {
+    fn default() -> Self {
+        Self {
+            categories: Vec::new(),
+            columns: 2,
+            rows: 4,
+            show_category: true,
+            images: HashMap::new(),
+        }
+    }
+}
+
+impl FlashcardOptions {
+    /// The columns and rows of a page, at least one of each.
+    fn grid(&self) -> (usize, usize) {
+        (self.columns.max(1), self.rows.max(1))
+    }
+}
+
+/// The letter of the `idx`th answer, or its number past the letters.
+fn answer_letter(idx: usize) -> String {
+    ANSWER_LETTERS
+        .get(idx)
+        .map_or_else(|| (idx + 1).to_string(), char::to_string)
+}
+
+/// The width, height and number of color components of a JPEG, read from its frame header.
+fn jpeg_info(data: &[u8]) -> Option<(u16, u16, u8)> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return None;
+    }
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        if marker == 0xFF {
+            // padding before a marker
+            pos += 1;
+            continue;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        // the start of frame markers, except those which are something else in their range
+        if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+            let frame = data.get(pos + 4..pos + 10)?;
+            let height = u16::from_be_bytes([frame[1], frame[2]]);
+            let width = u16::from_be_bytes([frame[3], frame[4]]);
+            return Some((width, height, frame[5]));
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// A glyph on a line, placed in font units from the left end of the line.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct PlacedGlyph {
+    id: u16,
+    x: i32,
+    y: i32,
+}
+
+/// Shapes lines of text, keeping track of the glyphs used for the font's tables.
+struct Shaper {
+    face: Face<'static>,
+    /// The text of each glyph used, for copying text out of the document.
+    used: BTreeMap<u16, char>,
+}
+
+impl Shaper {
+    fn new() -> Self {
+        Self {
+            face: Face::from_slice(FONT, 0).expect("the embedded font is valid"),
+            used: BTreeMap::new(),
+        }
+    }
+
+    /// Points per font unit at `size`.
+    fn scale(&self, size: f32) -> f32 {
+        size / self.face.units_per_em() as f32
+    }
+
+    /// The glyphs of `line` from left to right, and the width of the line in font units. The
+    /// line is right to left, with the runs of the other direction in it reordered.
+    fn layout(&mut self, line: &str) -> (Vec<PlacedGlyph>, i32) {
+        let bidi = BidiInfo::new(line, Some(Level::rtl()));
+        let mut glyphs = Vec::new();
+        let mut x = 0;
+        let Some(paragraph) = bidi.paragraphs.first() else {
+            return (glyphs, x);
+        };
+        let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+        for run in runs {
+            let text = &line[run.clone()];
+            let mut buffer = UnicodeBuffer::new();
+            buffer.push_str(text);
+            buffer.set_direction(if levels[run.start].is_rtl() {
+                Direction::RightToLeft
+            } else {
+                Direction::LeftToRight
+            });
+            // right to left runs come out shaped from left to right too
+            let shaped = rustybuzz::shape(&self.face, &[], buffer);
+            for (info, position) in shaped.glyph_infos().iter().zip(shaped.glyph_positions()) {
+                let id = info.glyph_id as u16;
+                if let Some(c) = text[info.cluster as usize..].chars().next() {
+                    self.used.entry(id).or_insert(c);
+                }
+                glyphs.push(PlacedGlyph {
+                    id,
+                    x: x + position.x_offset,
+                    y: position.y_offset,
+                });
+                x += position.x_advance;
+            }
+        }
+        (glyphs, x)
+    }
+
+    /// The width of `line` at `size`, in points.
+    fn width(&mut self, line: &str, size: f32) -> f32 {
+        self.layout(line).1 as f32 * self.scale(size)
+    }
+
+    /// Break `text` into lines no wider than `width` at `size`, between words. A word wider
+    /// than a line gets a line of its own.
+    fn wrap(&mut self, text: &str, size: f32, width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{line} {word}")
+                };
+                if !line.is_empty() && self.width(&candidate, size) > width {
+                    lines.push(std::mem::replace(&mut line, word.to_string()));
+                } else {
+                    line = candidate;
+                }
+            }
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+        lines
+    }
+}
+
+/// Draw `line` on `content` with its right end at `right` and its baseline at `baseline`.
+fn show_line(
+    content: &mut Content,
+    shaper: &mut Shaper,
+    line: &str,
+    size: f32,
+    right: f32,
+    baseline: f32,
+) {
+    let (glyphs, width) = shaper.layout(line);
+    let scale = shaper.scale(size);
+    let left = right - width as f32 * scale;
+    content.begin_text();
+    content.set_font(FONT_RESOURCE, size);
+    for glyph in glyphs {
+        content.set_text_matrix([
+            1.0,
+            0.0,
+            0.0,
+            1.0,
+            left + glyph.x as f32 * scale,
+            baseline + glyph.y as f32 * scale,
+        ]);
+        content.show(Str(&glyph.id.to_be_bytes()));
+    }
+    content.end_text();
+}
+
+/// An image placed in the document.
+struct PlacedImage<'a> {
+    name: String,
+    data: &'a [u8],
+    width: u16,
+    height: u16,
+    components: u8,
+}
+
+/// The JPEG of `question` among `images`, to place in the document.
+fn question_image<'a>(
+    question: &Question,
+    images: &'a HashMap<usize, Vec<u8>>,
+) -> Result<Option<PlacedImage<'a>>, PdfError> {
+    let Some(data) = images.get(&question.num) else {
+        return Ok(None);
+    };
+    let (width, height, components) =
+        jpeg_info(data).ok_or(PdfError::UnsupportedImage(question.num))?;
+    Ok(Some(PlacedImage {
+        name: format!("Im{}", question.num),
+        data,
+        width,
+        height,
+        components,
+    }))
+}
+
+/// Lays the document out page by page, from the top of each page down.
+struct Layout<'a> {
+    shaper: Shaper,
+    pages: Vec<Content>,
+    /// The top of the space left on the current page.
+    y: f32,
+    images: Vec<PlacedImage<'a>>,
+}
+
+impl<'a> Layout<'a> {
+    fn new() -> Self {
+        let mut layout = Self {
+            shaper: Shaper::new(),
+            pages: Vec::new(),
+            y: 0.0,
+            images: Vec::new(),
+        };
+        layout.new_page();
+        layout
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Content::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    /// Start a new page unless `height` fits on the current one, or wouldn't fit on a new one
+    /// either.
+    fn keep_together(&mut self, height: f32) {
+        let available = self.y - MARGIN;
+        if height > available && height <= PAGE_HEIGHT - 2.0 * MARGIN {
+            self.new_page();
+        }
+    }
+
+    /// Draw `line` with its right end at `right`, on the next line of the page.
+    fn line(&mut self, line: &str, size: f32, right: f32) {
+        if self.y - size * LINE_HEIGHT < MARGIN {
+            self.new_page();
+        }
+        self.y -= size;
+        let content = self.pages.last_mut().expect("there's always a page");
+        show_line(content, &mut self.shaper, line, size, right, self.y);
+        self.y -= size * (LINE_HEIGHT - 1.0);
+    }
+
+    /// The lines of `text` wrapped to the width left of `indent`.
+    fn wrap(&mut self, text: &str, size: f32, indent: f32) -> Vec<String> {
+        self.shaper
+            .wrap(text, size, PAGE_WIDTH - 2.0 * MARGIN - indent)
+    }
+
+    /// Draw `text` wrapped, right aligned at `indent` from the margin.
+    fn paragraph(&mut self, text: &str, size: f32, indent: f32) {
+        for line in self.wrap(text, size, indent) {
+            self.line(&line, size, PAGE_WIDTH - MARGIN - indent);
+        }
+    }
+
+    /// Draw a JPEG `width` points wide, centered.
+    fn image(&mut self, image: PlacedImage<'a>, width: f32) {
+        let height = width * image.height as f32 / image.width.max(1) as f32;
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+        self.y -= height;
+        let content = self.pages.last_mut().expect("there's always a page");
+        content.save_state();
+        content.transform([width, 0.0, 0.0, height, (PAGE_WIDTH - width) / 2.0, self.y]);
+        content.x_object(Name(image.name.as_bytes()));
+        content.restore_state();
+        self.y -= TEXT_SIZE * (LINE_HEIGHT - 1.0);
+        self.images.push(image);
+    }
+
+    fn question(
+        &mut self,
+        idx: usize,
+        question: &Question,
+        opts: &'a PdfOptions,
+    ) -> Result<(), PdfError> {
+        let text = format!("{}. {}", idx + 1, question_text(question));
+        let image = question_image(question, &opts.images)?;
+        let missing_image = match (&question.image_url, &image) {
+            (Some(url), None) => Some(format!("תמונה: {url}")),
+            _ => None,
+        };
+        let answers = question
+            .answers
+            .possible_answers
+            .iter()
+            .enumerate()
+            .map(|(idx, answer)| format!("{}. {answer}", answer_letter(idx)))
+            .collect::<Vec<_>>();
+
+        // a question and its answers stay on the same page when they can
+        let mut num_lines = self.wrap(&text, TEXT_SIZE, 0.0).len();
+        num_lines += usize::from(missing_image.is_some());
+        for answer in &answers {
+            num_lines += self.wrap(answer, TEXT_SIZE, ANSWER_INDENT).len();
+        }
+        let image_height = image.as_ref().map_or(0.0, |image| {
+            opts.image_width * image.height as f32 / image.width.max(1) as f32
+                + TEXT_SIZE * (LINE_HEIGHT - 1.0)
+        });
+        self.keep_together(num_lines as f32 * TEXT_SIZE * LINE_HEIGHT + image_height);
+
+        self.paragraph(&text, TEXT_SIZE, 0.0);
+        if let Some(image) = image {
+            self.image(image, opts.image_width);
+        }
+        if let Some(missing_image) = missing_image {
+            self.paragraph(&missing_image, TEXT_SIZE, 0.0);
+        }
+        for answer in &answers {
+            self.paragraph(answer, TEXT_SIZE, ANSWER_INDENT);
+        }
+        self.y -= QUESTION_GAP;
+        Ok(())
+    }
+}
+
+/// Compress `data` for a stream with the FlateDecode filter.
+fn deflate(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, 6)
+}
+
+/// Write the laid out pages, the font and the images as a PDF document.
+fn write_pdf(layout: Layout) -> Vec<u8> {
+    let mut next_ref = Ref::new(1);
+    let mut alloc = || next_ref.bump();
+    let catalog_id = alloc();
+    let page_tree_id = alloc();
+    let font_id = alloc();
+    let cid_font_id = alloc();
+    let descriptor_id = alloc();
+    let font_file_id = alloc();
+    let cmap_id = alloc();
+    let image_ids = layout.images.iter().map(|_| alloc()).collect::<Vec<_>>();
+    let page_ids = layout
+        .pages
+        .iter()
+        .map(|_| (alloc(), alloc()))
+        .collect::<Vec<_>>();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id)
+        .kids(page_ids.iter().map(|(page_id, _)| *page_id))
+        .count(page_ids.len() as i32);
+    for ((page_id, content_id), content) in page_ids.iter().zip(layout.pages) {
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .parent(page_tree_id)
+            .contents(*content_id);
+        let mut resources = page.resources();
+        resources.fonts().pair(FONT_RESOURCE, font_id);
+        let mut x_objects = resources.x_objects();
+        for (image, image_id) in layout.images.iter().zip(&image_ids) {
+            x_objects.pair(Name(image.name.as_bytes()), *image_id);
+        }
+        x_objects.finish();
+        resources.finish();
+        page.finish();
+        pdf.stream(*content_id, &deflate(&content.finish()))
+            .filter(Filter::FlateDecode);
+    }
+
+    let face = &layout.shaper.face;
+    let to_pdf_units = |value: i16| value as f32 * 1000.0 / face.units_per_em() as f32;
+    pdf.type0_font(font_id)
+        .base_font(FONT_NAME)
+        .encoding_predefined(Name(b"Identity-H"))
+        .descendant_font(cid_font_id)
+        .to_unicode(cmap_id);
+    let mut cid_font = pdf.cid_font(cid_font_id);
+    cid_font
+        .subtype(CidFontType::Type2)
+        .base_font(FONT_NAME)
+        .system_info(SYSTEM_INFO)
+        .font_descriptor(descriptor_id)
+        .default_width(0.0)
+        .cid_to_gid_map_predefined(Name(b"Identity"));
+    let mut widths = cid_font.widths();
+    for id in layout.shaper.used.keys() {
+        let advance = face.glyph_hor_advance(GlyphId(*id)).unwrap_or(0);
+        widths.consecutive(*id, [advance as f32 * 1000.0 / face.units_per_em() as f32]);
+    }
+    widths.finish();
+    cid_font.finish();
+    let bbox = face.global_bounding_box();
+    pdf.font_descriptor(descriptor_id)
+        .name(FONT_NAME)
+        .flags(FontFlags::SYMBOLIC)
+        .bbox(Rect::new(
+            to_pdf_units(bbox.x_min),
+            to_pdf_units(bbox.y_min),
+            to_pdf_units(bbox.x_max),
+            to_pdf_units(bbox.y_max),
+        ))
+        .italic_angle(0.0)
+        .ascent(to_pdf_units(face.ascender()))
+        .descent(to_pdf_units(face.descender()))
+        .cap_height(to_pdf_units(
+            face.capital_height().unwrap_or(face.ascender()),
+        ))
+        .stem_v(80.0)
+        .font_file2(font_file_id);
+    pdf.stream(font_file_id, &deflate(FONT))
+        .filter(Filter::FlateDecode)
+        .pair(Name(b"Length1"), FONT.len() as i32);
+    let mut cmap = UnicodeCmap::new(Name(b"Custom"), SYSTEM_INFO);
+    for (id, c) in &layout.shaper.used {
+        cmap.pair(*id, *c);
+    }
+    pdf.stream(cmap_id, &cmap.finish());
+
+    for (image, image_id) in layout.images.iter().zip(image_ids) {
+        let color_space = match image.components {
+            1 => Name(b"DeviceGray"),
+            4 => Name(b"DeviceCMYK"),
+            _ => Name(b"DeviceRGB"),
+        };
+        let mut xobject = pdf.image_xobject(image_id, image.data);
+        xobject.filter(Filter::DctDecode);
+        xobject
+            .width(image.width.into())
+            .height(image.height.into())
+            .color_space_name(color_space)
+            .bits_per_component(8);
+    }
+    pdf.finish()
+}
+
+/// The slot on the back of a sheet which is behind `slot` on its front, slots counted row by
+/// row. Printed on both sides and flipped on its long edge, the back of a sheet is its front
+/// mirrored left to right, so the column of a card is mirrored while its row stays.
+pub fn back_slot(slot: usize, columns: usize) -> usize {
+    let (row, column) = (slot / columns, slot % columns);
+    row * columns + columns - 1 - column
+}
+
+impl<'a> Layout<'a> {
+    /// The left, bottom, width and height of `slot` on a page of cards. Slots are counted row by
+    /// row from the top, each row from the right as the cards are read.
+    fn card_cell(slot: usize, opts: &FlashcardOptions) -> (f32, f32, f32, f32) {
+        let (columns, rows) = opts.grid();
+        let width = (PAGE_WIDTH - 2.0 * MARGIN) / columns as f32;
+        let height = (PAGE_HEIGHT - 2.0 * MARGIN) / rows as f32;
+        let (row, column) = (slot / columns, slot % columns);
+        let left = PAGE_WIDTH - MARGIN - (column + 1) as f32 * width;
+        let bottom = PAGE_HEIGHT - MARGIN - (row + 1) as f32 * height;
+        (left, bottom, width, height)
+    }
+
+    /// Draw the outline of a card to cut along, returning the right end and the top of its text.
+    fn card_outline(&mut self, slot: usize, opts: &FlashcardOptions) -> (f32, f32, f32) {
+        let (left, bottom, width, height) = Self::card_cell(slot, opts);
+        let content = self.pages.last_mut().expect("there's always a page");
+        content.save_state();
+        content.set_stroke_gray(CARD_GRAY);
+        content.set_line_width(0.5);
+        content.rect(left, bottom, width, height);
+        content.stroke();
+        content.restore_state();
+        (
+            left + width - CARD_PADDING,
+            bottom + height - CARD_PADDING,
+            bottom + CARD_PADDING,
+        )
+    }
+
+    /// Draw `text` wrapped to the width of a card from `top` down, leaving out the lines which
+    /// reach `bottom`. Returns the top of the space left.
+    fn card_text(
+        &mut self,
+        text: &str,
+        size: f32,
+        gray: f32,
+        (right, mut top, bottom): (f32, f32, f32),
+        width: f32,
+    ) -> f32 {
+        let lines = self.shaper.wrap(text, size, width);
+        let content = self.pages.last_mut().expect("there's always a page");
+        content.save_state();
+        content.set_fill_gray(gray);
+        for line in lines {
+            if top - size * LINE_HEIGHT < bottom {
+                break;
+            }
+            show_line(content, &mut self.shaper, &line, size, right, top - size);
+            top -= size * LINE_HEIGHT;
+        }
+        content.restore_state();
+        top
+    }
+
+    /// Draw the front of the card of `question`: its category, its text and its image.
+    fn card_front(
+        &mut self,
+        slot: usize,
+        question: &Question,
+        opts: &'a FlashcardOptions,
+    ) -> Result<(), PdfError> {
+        let (right, mut top, bottom) = self.card_outline(slot, opts);
+        let width = Self::card_cell(slot, opts).2 - 2.0 * CARD_PADDING;
+        if opts.show_category {
+            top = self.card_text(
+                question.category.as_str_he(),
+                CARD_LABEL_SIZE,
+                CARD_GRAY,
+                (right, top, bottom),
+                width,
+            );
+        }
+        top = self.card_text(
+            question_text(question),
+            CARD_TEXT_SIZE,
+            0.0,
+            (right, top, bottom),
+            width,
+        );
+        match (question_image(question, &opts.images)?, &question.image_url) {
+            (Some(image), _) => {
+                // scaled down to fit the space left, and centered in it
+                let available = top - CARD_TEXT_SIZE * (LINE_HEIGHT - 1.0) - bottom;
+                let scale =
+                    (width / image.width.max(1) as f32).min(available / image.height.max(1) as f32);
+                if scale > 0.0 {
+                    let (image_width, image_height) =
+                        (image.width as f32 * scale, image.height as f32 * scale);
+                    let content = self.pages.last_mut().expect("there's always a page");
+                    content.save_state();
+                    content.transform([
+                        image_width,
+                        0.0,
+                        0.0,
+                        image_height,
+                        right - (width + image_width) / 2.0,
+                        bottom + (available - image_height) / 2.0,
+                    ]);
+                    content.x_object(Name(image.name.as_bytes()));
+                    content.restore_state();
+                    self.images.push(image);
+                }
+            }
+            (None, Some(url)) => {
+                self.card_text(
+                    &format!("תמונה: {url}"),
+                    CARD_LABEL_SIZE,
+                    0.0,
+                    (right, top, bottom),
+                    width,
+                );
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    /// Draw the back of the card of `question`: the number of the question and its correct
+    /// answer.
+    fn card_back(&mut self, slot: usize, question: &Question, opts: &FlashcardOptions) {
+        let (right, mut top, bottom) = self.card_outline(slot, opts);
+        let width = Self::card_cell(slot, opts).2 - 2.0 * CARD_PADDING;
+        top = self.card_text(
+            &format!("שאלה {:04}", question.num),
+            CARD_LABEL_SIZE,
+            CARD_GRAY,
+            (right, top, bottom),
+            width,
+        );
+        if let Some(answer) = question
+            .answers
+            .possible_answers
+            .get(question.answers.correct_answer)
+        {
+            self.card_text(answer, CARD_TEXT_SIZE, 0.0, (right, top, bottom), width);
+        }
+    }
+}
+
+/// Write flashcards of `questions` as an A4 PDF document to print on both sides, flipped on the
+/// long edge: each page of questions is followed by the page of their answers, each placed to
+/// land behind its question. Text which doesn't fit on a card is cut off.
+pub fn export_flashcards_pdf(
+    questions: &[Question],
+    mut writer: impl Write,
+    opts: &FlashcardOptions,
+) -> Result<(), PdfError> {
+    let cards = questions
+        .iter()
+        .filter(|q| opts.categories.is_empty() || opts.categories.contains(&q.category))
+        .collect::<Vec<_>>();
+    let (columns, rows) = opts.grid();
+    let mut layout = Layout {
+        shaper: Shaper::new(),
+        pages: Vec::new(),
+        y: 0.0,
+        images: Vec::new(),
+    };
+    for sheet in cards.chunks(columns * rows) {
+        layout.pages.push(Content::new());
+        for (slot, question) in sheet.iter().enumerate() {
+            layout.card_front(slot, question, opts)?;
+        }
+        layout.pages.push(Content::new());
+        for (slot, question) in sheet.iter().enumerate() {
+            layout.card_back(back_slot(slot, columns), question, opts);
+        }
+    }
+    if layout.pages.is_empty() {
+        layout.new_page();
+    }
+    writer.write_all(&write_pdf(layout))?;
+    Ok(())
+}
+
+impl GeneratedExam {
+    /// Write the exam as an A4 PDF document: a header, the questions with their answers
+    /// lettered from א, and the answer key on a page of its own at the end.
+    pub fn to_pdf(&self, mut writer: impl Write, opts: &PdfOptions) -> Result<(), PdfError> {
+        let mut layout = Layout::new();
+        layout.paragraph(
+            &format!("מבחן תאוריה - דרגה {}", opts.class.code()),
+            TITLE_SIZE,
+            0.0,
+        );
+        let mut details = format!("מספר מבחן: {}", self.seed);
+        if !opts.date.is_empty() {
+            details = format!("תאריך: {} · {details}", opts.date);
+        }
+        layout.paragraph(&details, TEXT_SIZE, 0.0);
+        layout.y -= QUESTION_GAP;
+        for (idx, question) in self.questions.iter().enumerate() {
+            layout.question(idx, question, opts)?;
+        }
+
+        layout.new_page();
+        layout.paragraph("מפתח תשובות", TITLE_SIZE, 0.0);
+        for (idx, question) in self.questions.iter().enumerate() {
+            let line = format!(
+                "{}. {}",
+                idx + 1,
+                answer_letter(question.answers.correct_answer)
+            );
+            layout.paragraph(&line, TEXT_SIZE, 0.0);
+        }
+        writer.write_all(&write_pdf(layout))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::question_parser::Answers;
+
+    /// The start of a JPEG whose frame header says it's 40x30 and in color.
+    const JPEG: &[u8] = &[
+        0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0x1E,
+        0x00, 0x28, 0x03,
+    ];
+
+    fn exam(num_questions: usize) -> GeneratedExam {
+        GeneratedExam {
+            seed: 99,
+            class: LicenseClass::B,
+            questions: (1..=num_questions)
+                .map(|num| Question {
+                    question: format!(
+                        "{num:04}. מה המהירות המותרת בדרך עירונית שאין בה תמרור, לרכב פרטי (B) שמשקלו עד 3,500 ק\"ג?"
+                    ),
+                    answers: Answers {
+                        possible_answers: vec![
+                            "50 קמ\"ש".to_string(),
+                            "70 קמ\"ש".to_string(),
+                            "90 קמ\"ש".to_string(),
+                            "110 קמ\"ש".to_string(),
+                        ],
+                        correct_answer: num % 4,
+                    },
+                    category: QuestionCategory::TrafficLaws,
+                    license_classes: vec![LicenseClass::B],
+                    image_url: (num == 1).then(|| "https://example.com/1.jpg".to_string()),
+                    ..Question::sample(num, 0)
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn back_slots() {
+        // a full page of 2 columns: the cards swap sides in each row
+        let backs = (0..8).map(|slot| back_slot(slot, 2)).collect::<Vec<_>>();
+        assert_eq!(backs, [1, 0, 3, 2, 5, 4, 7, 6]);
+        // a page partly filled, ending in the middle of a row: the last card is behind the empty
+        // cell next to it on the back
+        let backs = (0..3).map(|slot| back_slot(slot, 2)).collect::<Vec<_>>();
+        assert_eq!(backs, [1, 0, 3]);
+        // an odd number of columns keeps the middle column in place
+        let backs = (0..6).map(|slot| back_slot(slot, 3)).collect::<Vec<_>>();
+        assert_eq!(backs, [2, 1, 0, 5, 4, 3]);
+        let backs = (0..5).map(|slot| back_slot(slot, 3)).collect::<Vec<_>>();
+        assert_eq!(backs, [2, 1, 0, 5, 4]);
+        // a single column is the same on both sides
+        assert_eq!(back_slot(3, 1), 3);
+
+        // the back of a card is exactly behind its front, mirrored across the page
+        let opts = FlashcardOptions {
+            columns: 3,
+            rows: 2,
+            ..Default::default()
+        };
+        for slot in 0..6 {
+            let (left, bottom, width, height) = Layout::card_cell(slot, &opts);
+            let (back_left, back_bottom, back_width, back_height) =
+                Layout::card_cell(back_slot(slot, 3), &opts);
+            assert!((PAGE_WIDTH - (left + width) - back_left).abs() < 0.01);
+            assert_eq!(
+                (bottom, width, height),
+                (back_bottom, back_width, back_height)
+            );
+        }
+    }
+
+    fn flashcard_pages(questions: &[Question], opts: &FlashcardOptions) -> usize {
+        let mut pdf = Vec::new();
+        export_flashcards_pdf(questions, &mut pdf, opts).unwrap();
+        lopdf::Document::load_mem(&pdf).unwrap().get_pages().len()
+    }
+
+    #[test]
+    fn flashcards() {
+        let questions = exam(11).questions;
+        let opts = FlashcardOptions {
+            images: HashMap::from([(1, JPEG.to_vec())]),
+            ..Default::default()
+        };
+        // 8 cards a sheet, each with a front and a back page
+        assert_eq!(flashcard_pages(&questions, &opts), 4);
+        assert_eq!(flashcard_pages(&questions[..8], &opts), 2);
+        let opts = FlashcardOptions {
+            columns: 3,
+            rows: 3,
+            show_category: false,
+            ..Default::default()
+        };
+        assert_eq!(flashcard_pages(&questions, &opts), 4);
+        let opts = FlashcardOptions {
+            categories: vec![QuestionCategory::RoadSigns],
+            ..Default::default()
+        };
+        assert_eq!(flashcard_pages(&questions, &opts), 1);
+    }
+
+    fn page_count(exam: &GeneratedExam, opts: &PdfOptions) -> usize {
+        let mut pdf = Vec::new();
+        exam.to_pdf(&mut pdf, opts).unwrap();
+        lopdf::Document::load_mem(&pdf).unwrap().get_pages().len()
+    }
+
+    #[test]
+    fn pages() {
+        // the questions, then the answer key
+        assert_eq!(page_count(&exam(3), &PdfOptions::default()), 2);
+        let opts = PdfOptions {
+            date: "2025-03-01".to_string(),
+            images: HashMap::from([(1, JPEG.to_vec())]),
+            ..Default::default()
+        };
+        assert_eq!(page_count(&exam(3), &opts), 2);
+        // 30 questions take several pages
+        assert!(page_count(&exam(30), &opts) > 3);
+        let opts = PdfOptions {
+            images: HashMap::from([(1, b"GIF89a".to_vec())]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            exam(1).to_pdf(Vec::new(), &opts),
+            Err(PdfError::UnsupportedImage(1))
+        ));
+    }
+
+    #[test]
+    fn jpegs() {
+        assert_eq!(jpeg_info(JPEG), Some((40, 30, 3)));
+        assert_eq!(jpeg_info(b"GIF89a"), None);
+        assert_eq!(jpeg_info(&JPEG[..12]), None);
+    }
+
+    #[test]
+    fn right_to_left() {
+        let mut shaper = Shaper::new();
+        let glyph = |shaper: &Shaper, c| shaper.face.glyph_index(c).unwrap().0;
+        let (glyphs, width) = shaper.layout("אבג abc");
+        assert!(width > 0);
+        // the first word is on the right, read from right to left
+        let ids = glyphs.iter().map(|g| g.id).collect::<Vec<_>>();
+        assert_eq!(ids[0], glyph(&shaper, 'a'));
+        assert_eq!(ids[2], glyph(&shaper, 'c'));
+        assert_eq!(ids[4], glyph(&shaper, 'ג'));
+        assert_eq!(ids[6], glyph(&shaper, 'א'));
+        assert!(glyphs.windows(2).all(|pair| pair[0].x < pair[1].x));
+        assert_eq!(shaper.used[&glyph(&shaper, 'א')], 'א');
+
+        let lines = shaper.wrap(&"מילה ".repeat(40), TEXT_SIZE, 200.0);
+        assert!(lines.len() > 1);
+        assert!(
+            lines
+                .iter()
+                .all(|line| shaper.width(line, TEXT_SIZE) <= 200.0)
+        );
+        assert_eq!(lines.concat().matches("מילה").count(), 40);
+    }
+}
@@ -0,0 +1,167 @@
+//! Python bindings, e.g. for analyzing the bank with pandas. Questions and stats are given as
+//! dicts of plain values.
+use std::{collections::BTreeMap, path::PathBuf};
+
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyDict};
+
+use crate::{
+    exam::ExamSpec,
+    question_parser::{ExamQuestions, LicenseClass, Question, QuestionCategory},
+};
+
+create_exception!(
+    theory_test_parser,
+    ParseError,
+    PyException,
+    "The file isn't a bank, with the message of the parser."
+);
+create_exception!(
+    theory_test_parser,
+    UnknownCodeError,
+    PyException,
+    "A license class or category code which doesn't exist."
+);
+
+fn parse_class(code: &str) -> PyResult<LicenseClass> {
+    LicenseClass::from_code(code)
+        .ok_or_else(|| UnknownCodeError::new_err(format!("unknown license class \"{code}\"")))
+}
+
+fn parse_category(code: &str) -> PyResult<QuestionCategory> {
+    QuestionCategory::from_code(code)
+        .ok_or_else(|| UnknownCodeError::new_err(format!("unknown category \"{code}\"")))
+}
+
+/// `question` as a dict, with the correct answer as an index into its answers.
+fn question_dict<'py>(py: Python<'py>, question: &Question) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("num", question.num)?;
+    dict.set_item("question", &question.question)?;
+    dict.set_item("answers", &question.answers.possible_answers)?;
+    dict.set_item("correct", question.answers.correct_answer)?;
+    dict.set_item("category", question.category.code())?;
+    dict.set_item(
+        "classes",
+        question
+            .license_classes
+            .iter()
+            .map(LicenseClass::code)
+            .collect::<Vec<_>>(),
+    )?;
+    dict.set_item("image_url", &question.image_url)?;
+    dict.set_item("explanation", &question.explanation)?;
+    Ok(dict)
+}
+
+fn question_dicts<'py>(
+    py: Python<'py>,
+    questions: &[Question],
+) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    questions.iter().map(|q| question_dict(py, q)).collect()
+}
+
+/// A parsed bank, or a part of one.
+#[pyclass(frozen)]
+struct Bank {
+    bank: ExamQuestions,
+}
+
+#[pymethods]
+impl Bank {
+    /// Every question, as dicts.
+    #[getter]
+    fn questions<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        question_dicts(py, &self.bank.questions)
+    }
+
+    /// Counts of the questions, of license class `class_` or of all of them.
+    #[pyo3(signature = (class_=None))]
+    fn stats<'py>(&self, py: Python<'py>, class_: Option<&str>) -> PyResult<Bound<'py, PyDict>> {
+        let class = class_.map(parse_class).transpose()?;
+        let stats = self.bank.pool_stats(class);
+        let dict = PyDict::new(py);
+        dict.set_item("total", stats.total)?;
+        dict.set_item("class_total", stats.class_total)?;
+        dict.set_item(
+            "per_category",
+            stats
+                .per_category
+                .iter()
+                .map(|(category, count)| (category.code(), *count))
+                .collect::<BTreeMap<_, _>>(),
+        )?;
+        dict.set_item(
+            "per_class",
+            stats
+                .per_class
+                .iter()
+                .map(|(class, count)| (class.code(), *count))
+                .collect::<BTreeMap<_, _>>(),
+        )?;
+        dict.set_item("with_image", stats.with_image)?;
+        dict.set_item(
+            "answer_counts",
+            stats.answer_counts.into_iter().collect::<BTreeMap<_, _>>(),
+        )?;
+        Ok(dict)
+    }
+
+    /// The questions of license class `class_` and of `category`, as a bank of their own.
+    #[pyo3(signature = (class_=None, category=None))]
+    fn filter(&self, class_: Option<&str>, category: Option<&str>) -> PyResult<Bank> {
+        let class = class_.map(parse_class).transpose()?;
+        let category = category.map(parse_category).transpose()?;
+        let questions = self
+            .bank
+            .questions
+            .iter()
+            .filter(|q| class.is_none_or(|class| q.license_classes.contains(&class)))
+            .filter(|q| category.is_none_or(|category| q.category == category))
+            .cloned()
+            .collect();
+        Ok(Bank {
+            bank: ExamQuestions { questions },
+        })
+    }
+
+    /// `n` questions of license class `class_` drawn with `seed`, as dicts. The same seed draws
+    /// the same questions, with their answers in the same order.
+    #[pyo3(signature = (n, seed, class_="B"))]
+    fn sample<'py>(
+        &self,
+        py: Python<'py>,
+        n: usize,
+        seed: u64,
+        class_: &str,
+    ) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let exam = ExamSpec {
+            num_questions: n,
+            class: parse_class(class_)?,
+            seed,
+            ..Default::default()
+        }
+        .generate(&self.bank);
+        question_dicts(py, &exam.questions)
+    }
+
+    fn __len__(&self) -> usize {
+        self.bank.questions.len()
+    }
+}
+
+/// Parse the bank in the xlsx file at `path`.
+#[pyfunction]
+fn parse_xlsx(path: PathBuf) -> PyResult<Bank> {
+    let bank = ExamQuestions::parse_from_xlsx_file(&path)
+        .map_err(|error| ParseError::new_err(error.to_string()))?;
+    Ok(Bank { bank })
+}
+
+#[pymodule]
+fn theory_test_parser(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(parse_xlsx, m)?)?;
+    m.add_class::<Bank>()?;
+    m.add("ParseError", m.py().get_type::<ParseError>())?;
+    m.add("UnknownCodeError", m.py().get_type::<UnknownCodeError>())?;
+    Ok(())
+}
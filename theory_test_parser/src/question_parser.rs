@@ -1,14 +1,23 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     fs::File,
     io::{BufReader, Read, Seek},
     path::Path,
 };
 
-use calamine::{DataType, Reader, Xlsx, XlsxError};
+use calamine::{DataType, Range, Reader, Xlsx, XlsxError};
 use quick_xml::events::Event;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Clone, Copy, PartialEq)]
+use crate::{
+    observer::{Both, NoopObserver, ParseObserver, ParseWarning, WarningCollector},
+    trace::{event, span},
+};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub enum QuestionCategory {
     Safety,
     TrafficLaws,
@@ -17,6 +26,12 @@ pub enum QuestionCategory {
 }
 
 impl QuestionCategory {
+    pub const ALL: [QuestionCategory; 4] = [
+        QuestionCategory::TrafficLaws,
+        QuestionCategory::RoadSigns,
+        QuestionCategory::Safety,
+        QuestionCategory::CarKnowledge,
+    ];
     const SAFETY_HE: &str = "בטיחות";
     const TRAFFIC_LAWS_HE: &str = "חוקי התנועה";
     const CAR_KNOWLEDGE_HE: &str = "הכרת הרכב";
@@ -39,9 +54,27 @@ impl QuestionCategory {
             QuestionCategory::RoadSigns => Self::ROAD_SIGNS_HE,
         }
     }
+
+    /// A code of the category for command lines and urls, e.g. "road_signs".
+    pub fn code(&self) -> &'static str {
+        match self {
+            QuestionCategory::Safety => "safety",
+            QuestionCategory::TrafficLaws => "traffic_laws",
+            QuestionCategory::CarKnowledge => "car_knowledge",
+            QuestionCategory::RoadSigns => "road_signs",
+        }
+    }
+
+    /// The inverse of [`QuestionCategory::code`].
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.code() == code)
+    }
 }
 
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum LicenseClass {
     C1,
     C,
@@ -50,7 +83,46 @@ pub enum LicenseClass {
     B,
 }
 
-#[derive(Clone)]
+impl LicenseClass {
+    pub const ALL: [LicenseClass; 5] = [
+        LicenseClass::A,
+        LicenseClass::B,
+        LicenseClass::C1,
+        LicenseClass::C,
+        LicenseClass::D,
+    ];
+
+    /// The code of the class as it appears on a license, e.g. "C1".
+    pub fn code(&self) -> &'static str {
+        match self {
+            LicenseClass::C1 => "C1",
+            LicenseClass::C => "C",
+            LicenseClass::D => "D",
+            LicenseClass::A => "A",
+            LicenseClass::B => "B",
+        }
+    }
+
+    /// The inverse of [`LicenseClass::code`].
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|class| class.code() == code)
+    }
+
+    /// How the bank marks a question as being for the class, in the list of classes under its
+    /// answers. B is written with a Cyrillic "В".
+    pub(crate) fn bank_marker(&self) -> &'static str {
+        match self {
+            LicenseClass::C1 => "«C1»",
+            LicenseClass::C => "«C»",
+            LicenseClass::D => "«D»",
+            LicenseClass::A => "«A»",
+            LicenseClass::B => "«В»",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Question {
     pub num: usize,
     /// the question
@@ -63,6 +135,32 @@ pub struct Question {
     pub license_classes: Vec<LicenseClass>,
     /// optional image url if there is any
     pub image_url: Option<String>,
+    /// why the correct answer is correct, the official bank doesn't explain its answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Question {
+    /// A question for tests: "0001. question" of the safety category and no license class,
+    /// answered by one of "answer 0" to "answer 3", without an image. Tests set what they care
+    /// about with the struct update syntax, e.g. `Question { category, ..Question::sample(1, 0) }`.
+    pub fn sample(num: usize, correct_answer: usize) -> Self {
+        Self {
+            num,
+            question: format!("{num:04}. question"),
+            answers: Answers {
+                possible_answers: (0..POSSIBLE_ANSWERS_NUM)
+                    .map(|idx| format!("answer {idx}"))
+                    .collect(),
+                correct_answer,
+            },
+            category: QuestionCategory::Safety,
+            license_classes: Vec::new(),
+            image_url: None,
+            explanation: None,
+        }
+    }
 }
 
 impl PartialEq for Question {
@@ -73,13 +171,19 @@ impl PartialEq for Question {
 
 const POSSIBLE_ANSWERS_NUM: usize = 4;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Answers {
     pub possible_answers: Vec<String>,
     pub correct_answer: usize,
 }
 
-fn parse_answers(xml: &str) -> (Answers, Vec<LicenseClass>, Option<String>) {
+/// Parse the html of the answers, calling `on_error` with what is malformed in it.
+pub(crate) fn parse_answers(
+    xml: &str,
+    on_error: &mut dyn FnMut(String),
+) -> (Answers, Vec<LicenseClass>, Option<String>) {
+    let _span = span!(TRACE, "parse_answers");
     let mut reader = quick_xml::Reader::from_str(xml);
     let mut possible_answers = Vec::new();
     let mut license_classes = Vec::new();
@@ -87,7 +191,7 @@ fn parse_answers(xml: &str) -> (Answers, Vec<LicenseClass>, Option<String>) {
     let mut image_url = None;
     loop {
         match reader.read_event() {
-            Err(e) => println!("error: {}", e),
+            Err(e) => on_error(e.to_string()),
             Ok(Event::Eof) => break,
             Ok(Event::Text(text)) => {
                 let text = String::from_utf8(text.to_vec()).unwrap();
@@ -96,45 +200,24 @@ fn parse_answers(xml: &str) -> (Answers, Vec<LicenseClass>, Option<String>) {
                     possible_answers.push(text);
                 } else if text.starts_with("|") && text.trim_end().ends_with("|") {
                     // we have the classes which this belong
-                    if text.contains("«A»") {
-                        license_classes.push(LicenseClass::A);
-                    }
-                    if text.contains("«В»") {
-                        license_classes.push(LicenseClass::B);
-                    }
-                    if text.contains("«C1»") {
-                        license_classes.push(LicenseClass::C);
-                    }
-                    if text.contains("«C»") {
-                        license_classes.push(LicenseClass::C1);
-                    }
-                    if text.contains("«D»") {
-                        license_classes.push(LicenseClass::D);
-                    }
+                    license_classes.extend(
+                        LicenseClass::ALL
+                            .into_iter()
+                            .filter(|class| text.contains(class.bank_marker())),
+                    );
                 }
             }
-            Ok(Event::Start(start)) => {
-                if start.name().0 == b"span" {
-                    for attribute in start.attributes() {
-                        if let Ok(attribute) = attribute {
-                            if attribute.key.0 == b"id"
-                                && attribute.value.starts_with(b"correctAnswer")
-                            {
-                                correct_answer = possible_answers.len();
-                            }
-                        }
+            Ok(Event::Start(start)) if start.name().0 == b"span" => {
+                for attribute in start.attributes().flatten() {
+                    if attribute.key.0 == b"id" && attribute.value.starts_with(b"correctAnswer") {
+                        correct_answer = possible_answers.len();
                     }
                 }
             }
-            Ok(Event::Empty(tag)) => {
-                if tag.name().0 == b"img" {
-                    for attribute in tag.attributes() {
-                        if let Ok(attribute) = attribute {
-                            if attribute.key.0 == b"src" {
-                                image_url =
-                                    Some(String::from_utf8(attribute.value.to_vec()).unwrap())
-                            }
-                        }
+            Ok(Event::Empty(tag)) if tag.name().0 == b"img" => {
+                for attribute in tag.attributes().flatten() {
+                    if attribute.key.0 == b"src" {
+                        image_url = Some(String::from_utf8(attribute.value.to_vec()).unwrap())
                     }
                 }
             }
@@ -158,83 +241,360 @@ pub enum Error {
     #[error("Xlsx error: {}", .0)]
     Xlsx(#[from] XlsxError),
 
+    #[error("The xlsx file has no worksheets")]
+    NoWorksheet,
+    #[error("The worksheet has no header row")]
+    NoHeaders,
     #[error("Did not find description4 header (answers) in the xlsx file")]
     NoDescription4Header,
     #[error("Did not find title2 header (questions) in the xlsx file")]
     NoTitle2Header,
     #[error("Did not find category header in the xlsx file")]
     NoCategoryHeader,
+    #[error(transparent)]
+    Row(#[from] RowError),
+    #[cfg(feature = "toml")]
+    #[error("Can't read the file: {}", .0)]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "toml")]
+    #[error("Invalid TOML bank: {}", .0)]
+    InvalidToml(#[from] toml::de::Error),
+    #[cfg(feature = "toml")]
+    #[error(transparent)]
+    TomlQuestion(#[from] crate::toml_bank::TomlError),
+    #[cfg(feature = "xlsx")]
+    #[error("Can't write the xlsx file: {}", .0)]
+    Write(#[from] rust_xlsxwriter::XlsxError),
+}
+
+/// An error in a single row of the xlsx file.
+#[derive(Debug, Error, Clone, PartialEq, Serialize, Deserialize)]
+#[error("Row {row}: {kind}")]
+pub struct RowError {
+    /// The row number as shown in a spreadsheet program, i.e. the header row is row 1.
+    pub row: usize,
+    pub kind: RowErrorKind,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RowErrorKind {
+    #[error("the question (title2) is not a string")]
+    NoQuestion,
+    #[error("the answers (description4) are not a string")]
+    NoAnswers,
+    #[error("the category is not a string")]
+    NoCategory,
+    #[error("unknown category \"{0}\"")]
+    UnknownCategory(String),
+    #[error("the question doesn't start with its number: \"{0}\"")]
+    NoQuestionNumber(String),
+    #[error("no possible answers were found")]
+    EmptyAnswers,
+}
+
+/// Options which control how the questions are parsed.
+#[derive(Clone, Copy)]
+pub struct ParseOptions<'a> {
+    /// Skip rows which can't be parsed instead of failing, reporting them as warnings.
+    pub lenient: bool,
+    /// Told about the rows as they are parsed, nothing by default.
+    pub observer: &'a dyn ParseObserver,
 }
 
-#[derive(Clone)]
+impl Default for ParseOptions<'_> {
+    fn default() -> Self {
+        Self {
+            lenient: false,
+            observer: &NoopObserver,
+        }
+    }
+}
+
+impl std::fmt::Debug for ParseOptions<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseOptions")
+            .field("lenient", &self.lenient)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The columns of the parsed fields.
+#[derive(Clone, Copy)]
+struct Columns {
+    question: usize,
+    answers: usize,
+    category: usize,
+}
+
+#[derive(Clone, Debug)]
 pub struct ExamQuestions {
     pub questions: Vec<Question>,
 }
 
-impl ExamQuestions {
-    pub fn parse_from_workbook<RS: Read + Seek>(mut workbook: Xlsx<RS>) -> Result<Self> {
-        let worksheets = workbook.worksheets();
-        let mut questions = Vec::new();
-        // we only expect one worksheet
-        let (_sheet_name, sheet_data) = worksheets
-            .first()
-            .expect("There should be a sheet in the xlsx file...");
+/// A bank in a second language, aligned with a primary bank by the official numbers of the
+/// questions, which are the same in every language.
+#[derive(Clone, Debug)]
+pub struct BilingualAlignment {
+    secondary: ExamQuestions,
+    /// The index in `secondary` of each question number.
+    by_num: HashMap<usize, usize>,
+}
 
-        let (answers_column, _column_name) = sheet_data
-            .headers()
-            .unwrap()
+impl BilingualAlignment {
+    pub fn new(secondary: ExamQuestions) -> Self {
+        let by_num = secondary
+            .questions
             .iter()
             .enumerate()
-            .find(|(_, h)| h.as_str() == "description4")
-            .ok_or(Error::NoDescription4Header)?;
-        let (question_column, _column_name) = sheet_data
-            .headers()
-            .unwrap()
-            .iter()
-            .enumerate()
-            .find(|(_, h)| h.as_str() == "title2")
-            .ok_or(Error::NoTitle2Header)?;
-        let (category_column, _column_name) = sheet_data
-            .headers()
-            .unwrap()
-            .iter()
-            .enumerate()
-            .find(|(_, h)| h.as_str() == "category")
-            .ok_or(Error::NoCategoryHeader)?;
-        for row in sheet_data.rows().skip(1) {
-            let question = &row[question_column]
-                .as_string()
-                .expect("question should be a string");
-            let answers = &row[answers_column]
-                .as_string()
-                .expect("answers should be a string");
-            let (answers, license_classes, image_url) = parse_answers(answers);
-            let category = row[category_column]
-                .as_string()
-                .expect("category should be a string");
-            let category = QuestionCategory::from_str_he(&category).unwrap();
-            let num = question[0..=3].parse().unwrap();
-            questions.push(Question {
-                num,
-                question: question.clone(),
-                answers,
-                license_classes,
-                image_url,
-                category,
-            });
+            .map(|(idx, question)| (question.num, idx))
+            .collect();
+        Self { secondary, by_num }
+    }
+
+    /// The question numbered `num` in the second language, if it was translated.
+    pub fn translation(&self, num: usize) -> Option<&Question> {
+        self.by_num
+            .get(&num)
+            .map(|idx| &self.secondary.questions[*idx])
+    }
+}
+
+/// Counts of the questions of a bank.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct BankStats {
+    /// All of the questions in the bank, of every license class.
+    pub total: usize,
+    /// The questions of the license class the stats are for, all of them when they aren't for
+    /// a class.
+    pub class_total: usize,
+    /// The questions of the license class in each category, in the order of
+    /// [`QuestionCategory::ALL`].
+    pub per_category: Vec<(QuestionCategory, usize)>,
+    /// The questions of the license class which are also for each class, in the order of
+    /// [`LicenseClass::ALL`].
+    pub per_class: Vec<(LicenseClass, usize)>,
+    /// The questions of the license class which have an image.
+    pub with_image: usize,
+    /// The questions of the license class by their number of answers, fewest answers first.
+    pub answer_counts: Vec<(usize, usize)>,
+}
+
+/// Parse the row numbered `row_num`, as shown in a spreadsheet program.
+fn parse_row(
+    row: &[calamine::Data],
+    row_num: usize,
+    columns: Columns,
+    observer: &dyn ParseObserver,
+) -> std::result::Result<Question, RowErrorKind> {
+    let _span = span!(DEBUG, "parse_row", row = row_num);
+    let cell = |column: usize| row.get(column).and_then(|cell| cell.as_string());
+    let question = cell(columns.question).ok_or(RowErrorKind::NoQuestion)?;
+    let answers = cell(columns.answers).ok_or(RowErrorKind::NoAnswers)?;
+    let (answers, license_classes, image_url) = parse_answers(&answers, &mut |message| {
+        let warning = ParseWarning::MalformedHtml {
+            row: row_num,
+            message,
+        };
+        event!(WARN, %warning);
+        observer.warning(&warning);
+    });
+    if answers.possible_answers.is_empty() {
+        return Err(RowErrorKind::EmptyAnswers);
+    }
+    let category = cell(columns.category).ok_or(RowErrorKind::NoCategory)?;
+    let category = QuestionCategory::from_str_he(&category)
+        .ok_or_else(|| RowErrorKind::UnknownCategory(category.clone()))?;
+    let num = question
+        .get(0..4)
+        .and_then(|num| num.parse().ok())
+        .ok_or_else(|| RowErrorKind::NoQuestionNumber(question.clone()))?;
+    event!(TRACE, num, "parsed the row");
+    Ok(Question {
+        num,
+        question,
+        answers,
+        license_classes,
+        image_url,
+        category,
+        explanation: None,
+    })
+}
+
+impl ExamQuestions {
+    pub fn parse_from_workbook<RS: Read + Seek>(workbook: Xlsx<RS>) -> Result<Self> {
+        Self::parse_from_workbook_with_options(workbook, ParseOptions::default())
+            .map(|(questions, _warnings)| questions)
+    }
+
+    /// Parse the exam questions, returning the warnings, which include the rows which were
+    /// skipped when parsing leniently. The warnings are also given to the options' observer as
+    /// they happen.
+    pub fn parse_from_workbook_with_options<RS: Read + Seek>(
+        mut workbook: Xlsx<RS>,
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let worksheets = {
+            let _span = span!(INFO, "read_worksheet");
+            let worksheets = workbook.worksheets();
+            event!(DEBUG, worksheets = worksheets.len(), "read the worksheets");
+            worksheets
+        };
+        // we only expect one worksheet
+        let (_sheet_name, sheet_data) = worksheets.first().ok_or(Error::NoWorksheet)?;
+        Self::parse_sheet(sheet_data, options)
+    }
+
+    fn parse_sheet(
+        sheet_data: &Range<calamine::Data>,
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let _span = span!(INFO, "parse_bank");
+        let mut questions = Vec::new();
+        let collector = WarningCollector::default();
+        let observer = Both(&collector, options.observer);
+
+        let columns = {
+            let _span = span!(DEBUG, "resolve_headers");
+            let headers = sheet_data.headers().ok_or(Error::NoHeaders)?;
+            let find_column = |name: &str| headers.iter().position(|h| h.as_str() == name);
+            let columns = Columns {
+                answers: find_column("description4").ok_or(Error::NoDescription4Header)?,
+                question: find_column("title2").ok_or(Error::NoTitle2Header)?,
+                category: find_column("category").ok_or(Error::NoCategoryHeader)?,
+            };
+            event!(
+                DEBUG,
+                question = columns.question,
+                answers = columns.answers,
+                category = columns.category,
+                "resolved the columns"
+            );
+            columns
+        };
+        for (row_idx, row) in sheet_data.rows().enumerate().skip(1) {
+            match parse_row(row, row_idx + 1, columns, &observer) {
+                Ok(question) => {
+                    observer.row_parsed(question.num);
+                    questions.push(question);
+                }
+                Err(kind) => {
+                    let error = RowError {
+                        row: row_idx + 1,
+                        kind,
+                    };
+                    if !options.lenient {
+                        return Err(error.into());
+                    }
+                    let warning = ParseWarning::SkippedRow(error);
+                    event!(WARN, %warning);
+                    observer.warning(&warning);
+                }
+            }
         }
 
-        Ok(ExamQuestions { questions })
+        let warnings = collector.into_warnings();
+        event!(
+            INFO,
+            questions = questions.len(),
+            warnings = warnings.len(),
+            "parsed the bank"
+        );
+        Ok((ExamQuestions { questions }, warnings))
     }
     pub fn parse_from_xlsx(bytes: &[u8]) -> Result<Self> {
-        let rs = BufReader::new(std::io::Cursor::new(bytes));
-        let workbook = calamine::open_workbook_from_rs(rs)?;
-        Self::parse_from_workbook(workbook)
+        Self::parse_from_xlsx_with_options(bytes, ParseOptions::default())
+            .map(|(questions, _warnings)| questions)
+    }
+    pub fn parse_from_xlsx_with_options(
+        bytes: &[u8],
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let workbook = {
+            let _span = span!(INFO, "open_workbook");
+            let rs = BufReader::new(std::io::Cursor::new(bytes));
+            let workbook = calamine::open_workbook_from_rs(rs)?;
+            event!(DEBUG, bytes = bytes.len(), "opened the workbook");
+            workbook
+        };
+        Self::parse_from_workbook_with_options(workbook, options)
+    }
+    /// Count the questions of the bank, and of `class` by category.
+    pub fn stats(&self, class: LicenseClass) -> BankStats {
+        self.pool_stats(Some(class))
+    }
+    /// Count the questions of the bank, and of `class` by category, or of the whole bank when
+    /// there's no class.
+    pub fn pool_stats(&self, class: Option<LicenseClass>) -> BankStats {
+        let class_questions = self
+            .questions
+            .iter()
+            .filter(|q| class.is_none_or(|class| q.license_classes.contains(&class)))
+            .collect::<Vec<_>>();
+        let mut answer_counts = BTreeMap::new();
+        for question in &class_questions {
+            *answer_counts
+                .entry(question.answers.possible_answers.len())
+                .or_default() += 1;
+        }
+        BankStats {
+            total: self.questions.len(),
+            class_total: class_questions.len(),
+            per_category: QuestionCategory::ALL
+                .into_iter()
+                .map(|category| {
+                    let count = class_questions
+                        .iter()
+                        .filter(|q| q.category == category)
+                        .count();
+                    (category, count)
+                })
+                .collect(),
+            per_class: LicenseClass::ALL
+                .into_iter()
+                .map(|other| {
+                    let count = class_questions
+                        .iter()
+                        .filter(|q| q.license_classes.contains(&other))
+                        .count();
+                    (other, count)
+                })
+                .collect(),
+            with_image: class_questions
+                .iter()
+                .filter(|q| q.image_url.is_some())
+                .count(),
+            answer_counts: answer_counts.into_iter().collect(),
+        }
+    }
+    /// The questions whose text or one of whose answers contains `query`, ignoring case.
+    pub fn search(&self, query: &str) -> Vec<&Question> {
+        let query = query.trim().to_lowercase();
+        self.questions
+            .iter()
+            .filter(|q| {
+                std::iter::once(&q.question)
+                    .chain(&q.answers.possible_answers)
+                    .any(|text| text.to_lowercase().contains(&query))
+            })
+            .collect()
     }
     /// Parse the exam questions from an Xlsx file.
     pub fn parse_from_xlsx_file(path: impl AsRef<Path>) -> Result<Self> {
-        let workbook = calamine::open_workbook::<Xlsx<BufReader<File>>, _>(path)?;
-        Self::parse_from_workbook(workbook)
+        Self::parse_from_xlsx_file_with_options(path, ParseOptions::default())
+            .map(|(questions, _warnings)| questions)
+    }
+    pub fn parse_from_xlsx_file_with_options(
+        path: impl AsRef<Path>,
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let workbook = {
+            let _span = span!(INFO, "open_workbook");
+            let path = path.as_ref();
+            let workbook = calamine::open_workbook::<Xlsx<BufReader<File>>, _>(path)?;
+            event!(DEBUG, path = %path.display(), "opened the workbook");
+            workbook
+        };
+        Self::parse_from_workbook_with_options(workbook, options)
     }
 }
 
@@ -244,7 +604,8 @@ mod test {
     #[test]
     fn question_parse() {
         let question_xml = r#"<div dir="rtl" style="text-align: right"><ul><li><span id="correctAnswer0862">שאנו בקיאים בהפעלתו ובשימוש בו.</span></li><li><span>שברכב בוצעו הטיפולים הדרושים לתחזוקתו השוטפת.</span></li><li><span>שברכב נמצאים נורות ונתיכים (פיוזים) חלופיים.</span></li><li><span>שהדלק והשמנים הם מהסוג המתאים להפעלתו התקינה של הרכב.</span></li></ul><div style="padding-top: 4px;"><span><button type="button" onclick="var correctAnswer=document.getElementById('correctAnswer0862');correctAnswer.style.background='yellow'">הצג תשובה נכונה</button></span><br/><span style="float: left;">| «C1» | «C» | «D» | «A» | «1» | «В» | </span></div></div>"#;
-        let (answers, license_classes, image_url) = parse_answers(question_xml);
+        let (answers, license_classes, image_url) =
+            parse_answers(question_xml, &mut |e| panic!("{e}"));
         let possible_answers = answers.possible_answers;
         assert_eq!(possible_answers[0], r#"שאנו בקיאים בהפעלתו ובשימוש בו."#);
         assert_eq!(
@@ -268,7 +629,8 @@ mod test {
         assert!(license_classes.contains(&LicenseClass::D));
 
         let question_xml = r#"<div dir="rtl" style="text-align: right"><ul><li><span id="correctAnswer0667">עצור לפני הצומת, אלא אם כן אינך יכול לעצור בבטחה.</span></li><li><span>היכון לנסיעה. מיד יתחלף האור ברמזור לירוק.</span></li><li><span>המשך בנסיעה. האור ברמזור יתחלף מיד לאור ירוק.</span></li><li><span>מותר לנסוע ישר, ימינה ושמאלה.</span></li></ul><img src="https://www.gov.il/BlobFolder/generalpage/tq_pic_02/he/TQ_PIC_3667.jpg" style="width: 100%; padding: 0pt; border: 0pt none; outline: 0pt none;" alt="yellow_traffic_light" title="yellow_traffic_light" /><div style="padding-top: 4px;"><span><button type="button" onclick="var correctAnswer=document.getElementById('correctAnswer0667');correctAnswer.style.background='yellow'">הצג תשובה נכונה</button></span><br/><span style="float: left;">| «C1» | «C» | «D» | «A» | «1» | «В» | </span></div></div>"#;
-        let (answers, license_classes, image_url) = parse_answers(question_xml);
+        let (answers, license_classes, image_url) =
+            parse_answers(question_xml, &mut |e| panic!("{e}"));
         let possible_answers = answers.possible_answers;
         assert_eq!(
             possible_answers[0],
@@ -297,4 +659,254 @@ mod test {
         assert!(license_classes.contains(&LicenseClass::C1));
         assert!(license_classes.contains(&LicenseClass::D));
     }
+
+    #[test]
+    fn license_classes() {
+        let xml = r#"<ul><li><span id="correctAnswer0001">a</span></li><li><span>b</span></li><li><span>c</span></li><li><span>d</span></li></ul><span>| «C1» | «В» | </span>"#;
+        let (_, license_classes, _) = parse_answers(xml, &mut |e| panic!("{e}"));
+        assert_eq!(license_classes, vec![LicenseClass::B, LicenseClass::C1]);
+        for class in LicenseClass::ALL {
+            assert_eq!(LicenseClass::from_code(class.code()), Some(class));
+        }
+        assert_eq!(LicenseClass::from_code("Z"), None);
+    }
+
+    #[test]
+    fn category_codes() {
+        for category in QuestionCategory::ALL {
+            assert_eq!(QuestionCategory::from_code(category.code()), Some(category));
+        }
+        assert_eq!(QuestionCategory::from_code("תמרורים"), None);
+    }
+
+    #[test]
+    fn stats() {
+        let question = |num, category, license_classes, image_url: Option<&str>| Question {
+            category,
+            license_classes,
+            image_url: image_url.map(str::to_string),
+            ..Question::sample(num, 0)
+        };
+        let bank = ExamQuestions {
+            questions: vec![
+                question(1, QuestionCategory::Safety, vec![LicenseClass::B], None),
+                question(
+                    2,
+                    QuestionCategory::RoadSigns,
+                    vec![LicenseClass::B, LicenseClass::C],
+                    Some("sign.jpg"),
+                ),
+                question(3, QuestionCategory::Safety, vec![LicenseClass::C], None),
+            ],
+        };
+        let stats = bank.stats(LicenseClass::B);
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.class_total, 2);
+        assert_eq!(stats.with_image, 1);
+        assert_eq!(
+            stats.per_category,
+            vec![
+                (QuestionCategory::TrafficLaws, 0),
+                (QuestionCategory::RoadSigns, 1),
+                (QuestionCategory::Safety, 1),
+                (QuestionCategory::CarKnowledge, 0),
+            ]
+        );
+        assert_eq!(stats.per_class[1], (LicenseClass::B, 2));
+        assert_eq!(stats.per_class[3], (LicenseClass::C, 1));
+        assert_eq!(stats.answer_counts, vec![(4, 2)]);
+        assert_eq!(bank.stats(LicenseClass::D).class_total, 0);
+        assert_eq!(
+            bank.search(" QUESTION ")
+                .iter()
+                .map(|q| q.num)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(bank.search("0002.").iter().all(|q| q.num == 2));
+        let whole = bank.pool_stats(None);
+        assert_eq!(whole.class_total, 3);
+        assert_eq!(whole.per_class[3], (LicenseClass::C, 2));
+    }
+
+    #[test]
+    fn alignment() {
+        let question = |num, text: &str| Question {
+            question: format!("{:04}. {}", num, text),
+            ..Question::sample(num, 0)
+        };
+        let alignment = BilingualAlignment::new(ExamQuestions {
+            questions: vec![question(7, "семь"), question(3, "три")],
+        });
+        assert_eq!(alignment.translation(3).unwrap().question, "0003. три");
+        assert_eq!(alignment.translation(7).unwrap().question, "0007. семь");
+        assert!(alignment.translation(5).is_none());
+    }
+
+    #[test]
+    fn bundled_bank_skips_no_rows() {
+        let bytes = std::fs::read("test.xlsx").unwrap();
+        let (questions, warnings) = ExamQuestions::parse_from_xlsx_with_options(
+            &bytes,
+            ParseOptions {
+                lenient: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // the html of one row is cut short, the answers before the cut are still read
+        assert!(
+            matches!(
+                &warnings[..],
+                [ParseWarning::MalformedHtml { row: 720, .. }]
+            ),
+            "{warnings:#?}"
+        );
+        assert!(!questions.questions.is_empty());
+    }
+
+    const COLUMNS: Columns = Columns {
+        question: 0,
+        answers: 1,
+        category: 2,
+    };
+
+    #[test]
+    fn row_errors() {
+        use calamine::Data;
+        let answers =
+            r#"<ul><li><span id="correctAnswer0001">a</span></li><li><span>b</span></li></ul>"#;
+        let row = |question: &str, answers: &str, category: &str| {
+            vec![
+                Data::String(question.to_string()),
+                Data::String(answers.to_string()),
+                Data::String(category.to_string()),
+            ]
+        };
+        let question = parse_row(
+            &row("0001. question", answers, "בטיחות"),
+            2,
+            COLUMNS,
+            &NoopObserver,
+        )
+        .unwrap();
+        assert_eq!(question.num, 1);
+        assert_eq!(question.category, QuestionCategory::Safety);
+
+        assert_eq!(
+            parse_row(
+                &row("0001. question", answers, "unknown"),
+                2,
+                COLUMNS,
+                &NoopObserver
+            )
+            .err(),
+            Some(RowErrorKind::UnknownCategory("unknown".to_string()))
+        );
+        assert_eq!(
+            parse_row(
+                &row("question", answers, "בטיחות"),
+                2,
+                COLUMNS,
+                &NoopObserver
+            )
+            .err(),
+            Some(RowErrorKind::NoQuestionNumber("question".to_string()))
+        );
+        assert_eq!(
+            parse_row(
+                &row("0001. question", "<ul></ul>", "בטיחות"),
+                2,
+                COLUMNS,
+                &NoopObserver
+            )
+            .err(),
+            Some(RowErrorKind::EmptyAnswers)
+        );
+        assert_eq!(
+            parse_row(&[Data::Empty], 2, COLUMNS, &NoopObserver).err(),
+            Some(RowErrorKind::NoQuestion)
+        );
+    }
+
+    /// Keeps everything it's told, in order.
+    #[derive(Default)]
+    struct Recorder(std::cell::RefCell<Vec<String>>);
+
+    impl ParseObserver for Recorder {
+        fn warning(&self, warning: &ParseWarning) {
+            self.0.borrow_mut().push(warning.to_string());
+        }
+
+        fn row_parsed(&self, num: usize) {
+            self.0.borrow_mut().push(format!("parsed {num}"));
+        }
+    }
+
+    #[test]
+    fn observed_warnings() {
+        use calamine::Data;
+        let answers = |num: &str| {
+            format!(
+                r#"<ul><li><span id="correctAnswer{num}">a</span></li><li><span>b</span></li></ul>"#
+            )
+        };
+        let rows = [
+            ["title2", "description4", "category"].map(str::to_string),
+            [
+                "0001. fine".to_string(),
+                answers("0001"),
+                "בטיחות".to_string(),
+            ],
+            // the list is closed by the wrong tag, the answers before it are kept
+            [
+                "0002. broken html".to_string(),
+                answers("0002").replace("</ul>", "</ol>"),
+                "בטיחות".to_string(),
+            ],
+            [
+                "0003. unknown category".to_string(),
+                answers("0003"),
+                "parking".to_string(),
+            ],
+        ];
+        let mut sheet = Range::new((0, 0), (rows.len() as u32 - 1, 2));
+        for (row, cells) in rows.into_iter().enumerate() {
+            for (column, cell) in cells.into_iter().enumerate() {
+                sheet.set_value((row as u32, column as u32), Data::String(cell));
+            }
+        }
+
+        let recorder = Recorder::default();
+        let options = ParseOptions {
+            lenient: true,
+            observer: &recorder,
+        };
+        let (bank, warnings) = ExamQuestions::parse_sheet(&sheet, options).unwrap();
+        assert_eq!(
+            bank.questions.iter().map(|q| q.num).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(bank.questions[1].answers.possible_answers, ["a", "b"]);
+        assert!(matches!(
+            &warnings[..],
+            [
+                ParseWarning::MalformedHtml { row: 3, .. },
+                ParseWarning::SkippedRow(RowError {
+                    row: 4,
+                    kind: RowErrorKind::UnknownCategory(_)
+                }),
+            ]
+        ));
+        let told = recorder.0.into_inner();
+        assert_eq!(told.len(), 4);
+        assert_eq!(told[0], "parsed 1");
+        assert!(told[1].starts_with("Row 3: malformed answers html: "));
+        assert_eq!(told[2], "parsed 2");
+        assert_eq!(told[3], warnings[1].to_string());
+
+        // without leniency the skipped row fails the parsing, the html is still only a warning
+        let error = ExamQuestions::parse_sheet(&sheet, ParseOptions::default()).unwrap_err();
+        assert!(matches!(error, Error::Row(RowError { row: 4, .. })));
+    }
 }
@@ -0,0 +1,120 @@
+//! JSON Schema documents of the exported formats, for tools which consume the exports.
+use schemars::{JsonSchema, schema_for};
+use serde_json::Value;
+
+use crate::{
+    exam::ExamResult,
+    export::FORMAT_VERSION,
+    question_parser::{ExamQuestions, Question},
+};
+
+/// Where the documents are published, each under the version of the format it describes.
+const BASE_URL: &str = "https://usering-around.github.io/theory_test_exam/schema";
+
+/// The schema of `T` as a document named `name`, e.g. "questions".
+fn document<T: JsonSchema>(name: &str, title: &str) -> Value {
+    let mut schema = schema_for!(T);
+    schema.insert(
+        "$id".to_string(),
+        format!("{BASE_URL}/v{FORMAT_VERSION}/{name}.schema.json").into(),
+    );
+    schema.insert("title".to_string(), title.into());
+    schema.to_value()
+}
+
+impl ExamQuestions {
+    /// The JSON Schema of the questions as exported by
+    /// [`ExportFormat::Json`](crate::export::ExportFormat::Json).
+    pub fn json_schema() -> Value {
+        document::<Vec<Question>>("questions", "Questions")
+    }
+}
+
+impl ExamResult {
+    /// The JSON Schema of a graded exam.
+    pub fn json_schema() -> Value {
+        document::<ExamResult>("result", "ExamResult")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        exam::ExamSpec,
+        export::{ExportFormat, export},
+    };
+
+    fn assert_valid(schema: &Value, instance: &Value) {
+        let validator = jsonschema::validator_for(schema).unwrap();
+        let errors = validator
+            .iter_errors(instance)
+            .map(|error| format!("{} at {}", error, error.instance_path()))
+            .collect::<Vec<_>>();
+        assert!(errors.is_empty(), "{errors:#?}");
+    }
+
+    fn bank() -> ExamQuestions {
+        ExamQuestions::parse_from_xlsx(&std::fs::read("test.xlsx").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn versioned() {
+        for (schema, name) in [
+            (ExamQuestions::json_schema(), "questions"),
+            (ExamResult::json_schema(), "result"),
+        ] {
+            assert_eq!(
+                schema["$id"],
+                format!("{BASE_URL}/v{FORMAT_VERSION}/{name}.schema.json")
+            );
+            assert!(jsonschema::meta::is_valid(&schema));
+        }
+    }
+
+    #[test]
+    fn exports_validate() {
+        let bank = bank();
+        let schema = ExamQuestions::json_schema();
+        for pretty in [false, true] {
+            let json = export(&bank.questions, ExportFormat::Json { pretty });
+            assert_valid(&schema, &serde_json::from_str(&json).unwrap());
+        }
+
+        let exam = ExamSpec::default().generate(&bank);
+        let mut selections = vec![Some(0); exam.questions.len()];
+        selections[1] = None;
+        let grade = exam.grade(&selections);
+        let result = ExamResult {
+            id: ExamSpec::default().id(),
+            num_correct: grade.num_correct(),
+            mistakes: grade.mistakes(),
+            allowed_mistakes: grade.allowed_mistakes(),
+            passed: grade.passed(),
+            correct_answers: exam
+                .questions
+                .iter()
+                .map(|q| q.answers.correct_answer)
+                .collect(),
+            correct: grade.correct,
+            per_category: grade.per_category,
+        };
+        assert_valid(
+            &ExamResult::json_schema(),
+            &serde_json::to_value(&result).unwrap(),
+        );
+    }
+
+    #[test]
+    fn rejects_other_shapes() {
+        let validator = jsonschema::validator_for(&ExamQuestions::json_schema()).unwrap();
+        let question = serde_json::to_value(&bank().questions[0]).unwrap();
+        assert!(validator.is_valid(&Value::Array(vec![question.clone()])));
+        let mut unknown_category = question.clone();
+        unknown_category["category"] = "parking".into();
+        assert!(!validator.is_valid(&Value::Array(vec![unknown_category])));
+        let mut without_answers = question;
+        without_answers.as_object_mut().unwrap().remove("answers");
+        assert!(!validator.is_valid(&Value::Array(vec![without_answers])));
+    }
+}
@@ -0,0 +1,304 @@
+//! Banks written by hand in TOML, e.g. an instructor's supplemental questions, without the xlsx
+//! and html of the official bank.
+//!
+//! Each question is a `[[question]]` table:
+//!
+//! ```toml
+//! [[question]]
+//! # the number of the question, unique in the bank, at most 9999
+//! num = 5001
+//! text = "מה פירוש התמרור?"
+//! answers = ["עצור", "האט", "תן זכות קדימה", "אין כניסה"]
+//! # the number of the correct answer, counting from 1
+//! correct = 3
+//! # one of safety, traffic_laws, road_signs, car_knowledge
+//! category = "road_signs"
+//! # the license classes the question is for, as on a license
+//! classes = ["B", "C1"]
+//! # optional
+//! image = "images/5001.png"
+//! explanation = "התמרור מורה לתת זכות קדימה."
+//! ```
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    export::question_text,
+    observer::ParseWarning,
+    question_parser::{
+        Answers, ExamQuestions, LicenseClass, ParseOptions, Question, QuestionCategory, Result,
+    },
+};
+
+/// The largest question number, numbers are written with 4 digits before the text.
+const MAX_NUM: usize = 9999;
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlBank {
+    #[serde(default)]
+    question: Vec<TomlQuestion>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlQuestion {
+    num: usize,
+    text: String,
+    answers: Vec<String>,
+    correct: usize,
+    category: String,
+    classes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation: Option<String>,
+}
+
+/// An invalid `[[question]]` table of a TOML bank.
+#[derive(Debug, Error, Clone, PartialEq)]
+#[error("[[question]] {table} (num = {num}): {kind}")]
+pub struct TomlError {
+    /// The position of the table in the file, the first one is 1.
+    pub table: usize,
+    pub num: usize,
+    pub kind: TomlErrorKind,
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum TomlErrorKind {
+    #[error("the number must be between 1 and {MAX_NUM}")]
+    InvalidNum,
+    #[error("the number is taken by [[question]] {0}")]
+    DuplicateNum(usize),
+    #[error("the question has no answers")]
+    NoAnswers,
+    #[error("the correct answer {correct} is not one of the {answers} answers")]
+    CorrectOutOfRange { correct: usize, answers: usize },
+    #[error("unknown category \"{0}\"")]
+    UnknownCategory(String),
+    #[error("unknown license class \"{0}\"")]
+    UnknownClass(String),
+}
+
+impl TomlQuestion {
+    fn into_question(
+        self,
+        table: usize,
+        first_table_of: &mut HashMap<usize, usize>,
+    ) -> std::result::Result<Question, TomlError> {
+        let error = |kind| TomlError {
+            table,
+            num: self.num,
+            kind,
+        };
+        if !(1..=MAX_NUM).contains(&self.num) {
+            return Err(error(TomlErrorKind::InvalidNum));
+        }
+        if let Some(first) = first_table_of.insert(self.num, table) {
+            return Err(error(TomlErrorKind::DuplicateNum(first)));
+        }
+        if self.answers.is_empty() {
+            return Err(error(TomlErrorKind::NoAnswers));
+        }
+        if !(1..=self.answers.len()).contains(&self.correct) {
+            return Err(error(TomlErrorKind::CorrectOutOfRange {
+                correct: self.correct,
+                answers: self.answers.len(),
+            }));
+        }
+        let category = QuestionCategory::from_code(&self.category)
+            .ok_or_else(|| error(TomlErrorKind::UnknownCategory(self.category.clone())))?;
+        let license_classes = self
+            .classes
+            .iter()
+            .map(|code| {
+                LicenseClass::from_code(code)
+                    .ok_or_else(|| error(TomlErrorKind::UnknownClass(code.clone())))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Question {
+            num: self.num,
+            question: format!("{:04}. {}", self.num, self.text),
+            answers: Answers {
+                possible_answers: self.answers,
+                correct_answer: self.correct - 1,
+            },
+            category,
+            license_classes,
+            image_url: self.image,
+            explanation: self.explanation,
+        })
+    }
+}
+
+impl From<&Question> for TomlQuestion {
+    fn from(question: &Question) -> Self {
+        Self {
+            num: question.num,
+            text: question_text(question).to_string(),
+            answers: question.answers.possible_answers.clone(),
+            correct: question.answers.correct_answer + 1,
+            category: question.category.code().to_string(),
+            classes: question
+                .license_classes
+                .iter()
+                .map(|class| class.code().to_string())
+                .collect(),
+            image: question.image_url.clone(),
+            explanation: question.explanation.clone(),
+        }
+    }
+}
+
+impl ExamQuestions {
+    /// Parse a bank written in TOML, see the [module docs](self) for the format.
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        let bank = toml::from_str::<TomlBank>(toml)?;
+        let mut first_table_of = HashMap::new();
+        let questions = bank
+            .question
+            .into_iter()
+            .enumerate()
+            .map(|(idx, question)| question.into_question(idx + 1, &mut first_table_of))
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self { questions })
+    }
+
+    /// Parse a bank from a file, in TOML when its extension is `.toml` and xlsx otherwise.
+    pub fn parse_from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse_from_file_with_options(path, ParseOptions::default())
+            .map(|(questions, _warnings)| questions)
+    }
+
+    /// Like [`ExamQuestions::parse_from_file`], returning the warnings of an xlsx bank. TOML
+    /// banks have none, their invalid tables are errors.
+    pub fn parse_from_file_with_options(
+        path: impl AsRef<Path>,
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>)> {
+        let path = path.as_ref();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            Ok((
+                Self::from_toml(&std::fs::read_to_string(path)?)?,
+                Vec::new(),
+            ))
+        } else {
+            Self::parse_from_xlsx_file_with_options(path, options)
+        }
+    }
+
+    /// The bank in the format of [`ExamQuestions::from_toml`].
+    pub fn to_toml(&self) -> String {
+        let bank = TomlBank {
+            question: self.questions.iter().map(TomlQuestion::from).collect(),
+        };
+        // questions are plain data, serializing them can't fail
+        toml::to_string(&bank).expect("questions serialize")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::question_parser::Error;
+
+    const VALID: &str = r#"
+[[question]]
+num = 5001
+text = "מה פירוש התמרור?"
+answers = ["עצור", "האט", "תן זכות קדימה", "אין כניסה"]
+correct = 3
+category = "road_signs"
+classes = ["B", "C1"]
+image = "images/5001.png"
+explanation = "התמרור מורה לתת זכות קדימה."
+
+[[question]]
+num = 5002
+text = "האם מותר לעקוף?"
+answers = ["כן", "לא"]
+correct = 2
+category = "traffic_laws"
+classes = ["A"]
+"#;
+
+    #[test]
+    fn valid() {
+        let bank = ExamQuestions::from_toml(VALID).unwrap();
+        let [sign, law] = &bank.questions[..] else {
+            panic!("expected 2 questions");
+        };
+        assert_eq!(sign.question, "5001. מה פירוש התמרור?");
+        assert_eq!(sign.answers.correct_answer, 2);
+        assert_eq!(sign.category, QuestionCategory::RoadSigns);
+        assert_eq!(sign.license_classes, [LicenseClass::B, LicenseClass::C1]);
+        assert_eq!(sign.image_url.as_deref(), Some("images/5001.png"));
+        assert!(sign.explanation.is_some());
+        assert_eq!(law.answers.possible_answers.len(), 2);
+        assert_eq!(law.answers.correct_answer, 1);
+        assert_eq!(law.image_url, None);
+        assert_eq!(law.explanation, None);
+    }
+
+    #[test]
+    fn invalid_tables_are_cited() {
+        let error = |toml: &str| match ExamQuestions::from_toml(toml) {
+            Err(Error::TomlQuestion(error)) => error,
+            other => panic!("expected a table error, got {other:?}"),
+        };
+        let out_of_range = VALID.replace("correct = 2", "correct = 3");
+        assert_eq!(
+            error(&out_of_range),
+            TomlError {
+                table: 2,
+                num: 5002,
+                kind: TomlErrorKind::CorrectOutOfRange {
+                    correct: 3,
+                    answers: 2
+                },
+            }
+        );
+        assert_eq!(
+            error(&out_of_range).to_string(),
+            "[[question]] 2 (num = 5002): the correct answer 3 is not one of the 2 answers"
+        );
+        assert_eq!(
+            error(&VALID.replace("correct = 3", "correct = 0")).kind,
+            TomlErrorKind::CorrectOutOfRange {
+                correct: 0,
+                answers: 4
+            }
+        );
+        assert_eq!(
+            error(&VALID.replace("num = 5002", "num = 5001")).kind,
+            TomlErrorKind::DuplicateNum(1)
+        );
+        assert_eq!(
+            error(&VALID.replace("\"A\"", "\"B2\"")).kind,
+            TomlErrorKind::UnknownClass("B2".to_string())
+        );
+        // syntax errors and unknown fields are reported by the TOML parser, with their line
+        assert!(matches!(
+            ExamQuestions::from_toml(&VALID.replace("correct = 2", "corect = 2")),
+            Err(Error::InvalidToml(_))
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bank = ExamQuestions::parse_from_xlsx_file("test.xlsx").unwrap();
+        bank.questions[0].explanation = Some("explained".to_string());
+        let written = ExamQuestions::from_toml(&bank.to_toml()).unwrap();
+        // questions compare by their numbers, compare all of their fields
+        assert_eq!(
+            serde_json::to_value(&written.questions).unwrap(),
+            serde_json::to_value(&bank.questions).unwrap()
+        );
+    }
+}
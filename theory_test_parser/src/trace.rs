@@ -0,0 +1,108 @@
+//! Spans and events of parsing banks and of exams, with the `tracing` feature.
+//!
+//! The names of the spans are kept across versions, so that filters and tools which time them
+//! keep working. Their fields and the events inside them may change.
+//!
+//! | Span                 | Level | Fields | Covers                                             |
+//! |----------------------|-------|--------|----------------------------------------------------|
+//! | `open_workbook`      | INFO  |        | opening an xlsx file, or its bytes                 |
+//! | `read_worksheet`     | INFO  |        | reading the cells of the worksheets                |
+//! | `parse_bank`         | INFO  |        | parsing the rows, ends with the counts of the bank |
+//! | `resolve_headers`    | DEBUG |        | finding the columns of the fields                  |
+//! | `parse_row`          | DEBUG | `row`  | parsing one row, numbered as in a spreadsheet      |
+//! | `parse_answers`      | TRACE |        | parsing the html of the answers of a row           |
+//! | `sample`             | INFO  | `seed` | drawing the questions of an exam                   |
+//! | `grade`              | DEBUG |        | grading an exam                                    |
+//! | `decompress_bundle`  | INFO  |        | decompressing a bank parsed ahead of time          |
+//! | `deserialize_bundle` | INFO  |        | reading the bank out of the decompressed bundle    |
+//!
+//! `parse_bank` ends with an INFO event of the counts of the bank, its `questions` and its
+//! `warnings`. Each warning is also a WARN event, in the span of its row.
+//!
+//! Without the feature nothing is traced, and `tracing` isn't a dependency.
+
+/// Enters a span of the level until the end of the scope, e.g.
+/// `let _span = span!(DEBUG, "parse_row", row = row_num);`.
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($level:ident, $($args:tt)+) => {
+        tracing::span!(tracing::Level::$level, $($args)+).entered()
+    };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($level:ident, $($args:tt)+) => {
+        $crate::trace::Disabled
+    };
+}
+
+/// An event of the level, with the syntax of `tracing::event!`.
+#[cfg(feature = "tracing")]
+macro_rules! event {
+    ($level:ident, $($args:tt)+) => {
+        tracing::event!(tracing::Level::$level, $($args)+)
+    };
+}
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+    ($level:ident, $($args:tt)+) => {};
+}
+
+pub(crate) use event;
+pub(crate) use span;
+
+/// The span entered when nothing is traced.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Disabled;
+
+#[cfg(all(test, feature = "tracing"))]
+mod test {
+    use tracing_test::traced_test;
+
+    use crate::{
+        exam::ExamSpec,
+        question_parser::{ExamQuestions, ParseOptions},
+    };
+
+    #[test]
+    #[traced_test]
+    fn spans() {
+        let bytes = std::fs::read("test.xlsx").unwrap();
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (bank, warnings) =
+            ExamQuestions::parse_from_xlsx_with_options(&bytes, options).unwrap();
+        let exam = ExamSpec::default().generate(&bank);
+        exam.grade(&[]);
+
+        assert!(logs_contain(
+            "open_workbook: theory_test_parser::question_parser: opened the workbook"
+        ));
+        assert!(logs_contain(
+            "read_worksheet: theory_test_parser::question_parser: read the worksheets worksheets=1"
+        ));
+        assert!(logs_contain(
+            "parse_bank:resolve_headers: theory_test_parser::question_parser: resolved the columns"
+        ));
+        assert!(logs_contain(
+            "parse_bank:parse_row{row=2}: theory_test_parser::question_parser: parsed the row num="
+        ));
+        // the one warning of the bundled bank, see `bundled_bank_skips_no_rows`
+        assert!(logs_contain(&format!(
+            "parse_bank:parse_row{{row=720}}:parse_answers: theory_test_parser::question_parser: warning={}",
+            warnings[0]
+        )));
+        assert!(logs_contain(&format!(
+            "parse_bank: theory_test_parser::question_parser: parsed the bank questions={} warnings=1",
+            bank.questions.len()
+        )));
+        assert!(logs_contain(
+            "sample{seed=0}: theory_test_parser::exam: sampled the exam"
+        ));
+        assert!(logs_contain(
+            "grade: theory_test_parser::exam: graded the exam correct=0 passed=false"
+        ));
+    }
+}
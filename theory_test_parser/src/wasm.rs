@@ -0,0 +1,67 @@
+//! JavaScript bindings, for sites which aren't built with Rust. Questions and stats are given as
+//! plain objects, in the shape of their JSON export.
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    exam::ExamSpec,
+    question_parser::{ExamQuestions, LicenseClass},
+};
+
+/// A parsed bank.
+#[wasm_bindgen]
+pub struct QuestionBank {
+    bank: ExamQuestions,
+}
+
+/// `value` as a plain JavaScript object, e.g. maps as objects rather than `Map`s.
+fn to_js(value: &impl Serialize) -> Result<JsValue, JsError> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Parse a bank out of the bytes of its xlsx file. Throws an `Error` with the message of the
+/// parser when the file isn't a bank.
+#[wasm_bindgen(js_name = parseXlsx)]
+pub fn parse_xlsx(bytes: &[u8]) -> Result<QuestionBank, JsError> {
+    let bank =
+        ExamQuestions::parse_from_xlsx(bytes).map_err(|error| JsError::new(&error.to_string()))?;
+    Ok(QuestionBank { bank })
+}
+
+#[wasm_bindgen]
+impl QuestionBank {
+    /// Every question of the bank.
+    pub fn questions(&self) -> Result<JsValue, JsError> {
+        to_js(&self.bank.questions)
+    }
+
+    /// `n` questions of license `class`, "B" by default, drawn with `seed`. The same seed
+    /// draws the same questions, with their answers in the same order.
+    pub fn sample(&self, n: usize, seed: u32, class: Option<String>) -> Result<JsValue, JsError> {
+        let class = match class {
+            Some(code) => LicenseClass::from_code(&code)
+                .ok_or_else(|| JsError::new(&format!("unknown license class \"{code}\"")))?,
+            None => LicenseClass::B,
+        };
+        let exam = ExamSpec {
+            num_questions: n,
+            class,
+            seed: seed.into(),
+            ..Default::default()
+        }
+        .generate(&self.bank);
+        to_js(&exam.questions)
+    }
+
+    /// The questions whose text or one of whose answers contains `query`, ignoring case.
+    pub fn search(&self, query: &str) -> Result<JsValue, JsError> {
+        to_js(&self.bank.search(query))
+    }
+
+    /// Counts of the questions of the whole bank.
+    pub fn stats(&self) -> Result<JsValue, JsError> {
+        to_js(&self.bank.pool_stats(None))
+    }
+}
@@ -0,0 +1,125 @@
+//! Writing a bank back to an xlsx file in the format of the official one, e.g. after correcting
+//! a question.
+use std::path::Path;
+
+use rust_xlsxwriter::Workbook;
+
+use crate::question_parser::{ExamQuestions, LicenseClass, Question, Result};
+
+/// The columns the parser reads, in the order they're written.
+const HEADERS: [&str; 3] = ["title2", "description4", "category"];
+
+/// The classes in the order the official bank lists them under the answers.
+const FOOTER_CLASSES: [LicenseClass; 5] = [
+    LicenseClass::C1,
+    LicenseClass::C,
+    LicenseClass::D,
+    LicenseClass::A,
+    LicenseClass::B,
+];
+
+const IMAGE_STYLE: &str = "width: 100%; padding: 0pt; border: 0pt none; outline: 0pt none;";
+
+/// The answers cell of `question` in the html of the official bank: the answers with the
+/// correct one marked, the image, and the classes the question is for.
+fn answers_html(question: &Question) -> String {
+    let answers = question
+        .answers
+        .possible_answers
+        .iter()
+        .enumerate()
+        .map(|(idx, answer)| {
+            if idx == question.answers.correct_answer {
+                format!(
+                    "<li><span id=\"correctAnswer{:04}\">{answer}</span></li>",
+                    question.num
+                )
+            } else {
+                format!("<li><span>{answer}</span></li>")
+            }
+        })
+        .collect::<String>();
+    let image = question
+        .image_url
+        .as_ref()
+        .map(|url| format!("<img src=\"{url}\" style=\"{IMAGE_STYLE}\" />"))
+        .unwrap_or_default();
+    let classes = FOOTER_CLASSES
+        .iter()
+        .filter(|class| question.license_classes.contains(class))
+        .map(|class| format!("{} | ", class.bank_marker()))
+        .collect::<String>();
+    format!(
+        "<div dir=\"rtl\" style=\"text-align: right\"><ul>{answers}</ul>{image}\
+         <div style=\"padding-top: 4px;\"><span><button type=\"button\" \
+         onclick=\"var correctAnswer=document.getElementById('correctAnswer{num:04}');\
+         correctAnswer.style.background='yellow'\">הצג תשובה נכונה</button></span><br/>\
+         <span style=\"float: left;\">| {classes}</span></div></div>",
+        num = question.num
+    )
+}
+
+impl ExamQuestions {
+    /// Write the bank to an xlsx file at `path`, which parses back to the same questions. The
+    /// texts are written as they were parsed, so they must stay valid in html.
+    ///
+    /// The button after the answers is written as in the official bank, which the parser only
+    /// skips after 4 answers, as every question of the official bank has.
+    pub fn write_to_xlsx(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut workbook = Workbook::new();
+        let sheet = workbook.add_worksheet();
+        sheet.set_right_to_left(true);
+        for (column, header) in HEADERS.into_iter().enumerate() {
+            sheet.write_string(0, column as u16, header)?;
+        }
+        for (idx, question) in self.questions.iter().enumerate() {
+            let row = idx as u32 + 1;
+            sheet.write_string(row, 0, &question.question)?;
+            sheet.write_string(row, 1, answers_html(question))?;
+            sheet.write_string(row, 2, question.category.as_str_he())?;
+        }
+        workbook.save(path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::question_parser::{Answers, QuestionCategory, parse_answers};
+
+    #[test]
+    fn answers_parse_back() {
+        let question = Question {
+            answers: Answers {
+                possible_answers: (1..=4).map(|i| format!("answer {i}")).collect(),
+                correct_answer: 2,
+            },
+            category: QuestionCategory::RoadSigns,
+            license_classes: vec![LicenseClass::B, LicenseClass::C1],
+            image_url: Some("https://example.com/TQ_PIC_3590.jpg".to_string()),
+            ..Question::sample(591, 2)
+        };
+        let html = answers_html(&question);
+        assert!(html.contains("<li><span id=\"correctAnswer0591\">answer 3</span></li>"));
+        assert!(html.contains("| «C1» | «В» | </span>"));
+        let (answers, classes, image_url) = parse_answers(&html, &mut |e| panic!("{e}"));
+        assert_eq!(answers.possible_answers, question.answers.possible_answers);
+        assert_eq!(answers.correct_answer, 2);
+        assert_eq!(classes, question.license_classes);
+        assert_eq!(image_url, question.image_url);
+    }
+
+    #[test]
+    fn bundled_bank_round_trips() {
+        let bank = ExamQuestions::parse_from_xlsx_file("test.xlsx").unwrap();
+        let path = std::env::temp_dir().join("theory_test_parser_round_trip.xlsx");
+        bank.write_to_xlsx(&path).unwrap();
+        let written = ExamQuestions::parse_from_xlsx_file(&path).unwrap();
+        // questions compare by their numbers, compare all of their fields
+        assert_eq!(
+            serde_json::to_value(&written.questions).unwrap(),
+            serde_json::to_value(&bank.questions).unwrap()
+        );
+    }
+}
@@ -0,0 +1,59 @@
+"""Run with `maturin develop && pytest tests/python` from the parser's directory."""
+from pathlib import Path
+
+import pytest
+
+import theory_test_parser
+
+BANK = Path(__file__).parent.parent.parent / "test.xlsx"
+
+
+@pytest.fixture(scope="module")
+def bank():
+    return theory_test_parser.parse_xlsx(str(BANK))
+
+
+def test_questions(bank):
+    questions = bank.questions
+    assert len(questions) == len(bank) > 1000
+    question = questions[0]
+    assert set(question) == {
+        "num",
+        "question",
+        "answers",
+        "correct",
+        "category",
+        "classes",
+        "image_url",
+    }
+    assert question["question"].startswith(f"{question['num']:04}.")
+    assert 0 <= question["correct"] < len(question["answers"])
+    assert all(q["category"] in bank.stats()["per_category"] for q in questions)
+
+
+def test_filter_and_stats(bank):
+    road_signs = bank.filter(class_="B", category="road_signs")
+    assert len(road_signs) == bank.stats(class_="B")["per_category"]["road_signs"]
+    assert all(
+        q["category"] == "road_signs" and "B" in q["classes"]
+        for q in road_signs.questions
+    )
+    stats = road_signs.stats()
+    assert stats["total"] == stats["class_total"] == len(road_signs)
+
+
+def test_sample(bank):
+    sample = bank.sample(30, 99)
+    assert len(sample) == 30
+    assert sample == bank.sample(30, 99)
+    assert sample != bank.sample(30, 100)
+    assert all("B" in q["classes"] for q in sample)
+
+
+def test_errors(bank, tmp_path):
+    with pytest.raises(theory_test_parser.UnknownCodeError, match="unknown license class"):
+        bank.filter(class_="X")
+    broken = tmp_path / "broken.xlsx"
+    broken.write_bytes(b"not a workbook")
+    with pytest.raises(theory_test_parser.ParseError):
+        theory_test_parser.parse_xlsx(str(broken))
@@ -0,0 +1,38 @@
+//! Run with `wasm-pack test --node -- --features wasm`.
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use theory_test_parser::wasm::parse_xlsx;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const BANK: &[u8] = include_bytes!("../test.xlsx");
+
+/// The `num` fields of an array of questions.
+fn nums(questions: &JsValue) -> Vec<f64> {
+    js_sys::Array::from(questions)
+        .iter()
+        .map(|q| {
+            js_sys::Reflect::get(&q, &"num".into())
+                .unwrap()
+                .as_f64()
+                .unwrap()
+        })
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn parse_and_sample() {
+    let bank = parse_xlsx(BANK).unwrap();
+    let questions = bank.questions().unwrap();
+    assert!(js_sys::Array::from(&questions).length() > 1000);
+    let sample = bank.sample(30, 7, None).unwrap();
+    assert_eq!(nums(&sample).len(), 30);
+    assert_eq!(nums(&bank.sample(30, 7, None).unwrap()), nums(&sample));
+    assert_ne!(nums(&bank.sample(30, 8, None).unwrap()), nums(&sample));
+    assert!(bank.sample(30, 7, Some("X".to_string())).is_err());
+}
+
+#[wasm_bindgen_test]
+fn errors() {
+    assert!(parse_xlsx(b"not a workbook").is_err());
+}
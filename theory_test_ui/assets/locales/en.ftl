@@ -0,0 +1,23 @@
+### The English texts of the app, see he.ftl.
+
+## Timed exams
+
+minutes-left = { $minutes ->
+    [one] { $minutes } minute left
+   *[other] { $minutes } minutes left
+}
+answered-count = Answered { $answered }/{ $total }
+mistakes-left = Mistakes left: { $left }
+
+## Results
+
+correct-count = Correct answers { $correct }/{ $total }
+mistakes-of-allowed = { $mistakes ->
+    [one] (1 mistake of { $allowed } allowed)
+   *[other] ({ $mistakes } mistakes of { $allowed } allowed)
+}
+mistakes-left-at-end = Mistakes left at the end: { $left }
+
+## Dates
+
+date-time = { $month }/{ $day }/{ $year } { $hour }:{ $minute }
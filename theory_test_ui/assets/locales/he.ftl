@@ -0,0 +1,28 @@
+### The Hebrew texts of the app. This is the baseline: every key used in the code must be here,
+### the texts missing from other languages fall back to it.
+
+## Timed exams
+
+# $minutes (Number) - the minutes left, to a tenth of a minute
+minutes-left = { $minutes ->
+    [one] נותרה דקה
+    [two] נותרו שתי דקות
+   *[other] נותרו { $minutes } דקות
+}
+answered-count = נענו { $answered }/{ $total }
+mistakes-left = טעויות שנותרו: { $left }
+
+## Results
+
+correct-count = שאלות נכונות { $correct }/{ $total }
+# $mistakes (Number), $allowed (Number)
+mistakes-of-allowed = { $mistakes ->
+    [one] (טעות אחת מתוך { $allowed } מותרות)
+   *[other] ({ $mistakes } טעויות מתוך { $allowed } מותרות)
+}
+mistakes-left-at-end = טעויות שנותרו בסיום: { $left }
+
+## Dates
+
+# $hour and $minute are padded to 2 digits
+date-time = { $day }/{ $month }/{ $year } { $hour }:{ $minute }
@@ -0,0 +1,94 @@
+//! Generates the check that the keys given to `tr!` exist in the Hebrew baseline, so that a
+//! missing text fails the build rather than showing its key, and the list of the bundled sign
+//! images, failing the build when they grow past their budget.
+use std::{collections::BTreeSet, env, fs, path::Path};
+
+const BASELINE: &str = "assets/locales/he.ftl";
+/// Written by `theory-test-cli fetch-images --signs`.
+const SIGN_IMAGES_DIR: &str = "assets/sign_images";
+/// The most the sign images may add to the app, in KiB. Overridden by the environment variable of
+/// the same name.
+const SIGN_IMAGES_BUDGET_KIB: u64 = 1024;
+
+fn main() {
+    check_key();
+    sign_images();
+}
+
+fn check_key() {
+    println!("cargo::rerun-if-changed={BASELINE}");
+    let ftl = fs::read_to_string(BASELINE).expect("the Hebrew baseline is readable");
+    // messages start at the beginning of a line with their id, e.g. "minutes-left ="
+    let arms = ftl
+        .lines()
+        .filter_map(|line| {
+            let (id, _) = line.split_once('=')?;
+            let id = id.trim_end();
+            let is_id = id.starts_with(|c: char| c.is_ascii_alphabetic())
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            is_id.then(|| format!("    ({id:?}) => {{}};\n"))
+        })
+        .collect::<String>();
+    let check = format!(
+        "macro_rules! check_key {{\n{arms}    ($key:literal) => {{\n        \
+         compile_error!(concat!(\"the text \", $key, \" is missing from {BASELINE}\"))\n    \
+         }};\n}}\n"
+    );
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("check_key.rs");
+    fs::write(out, check).unwrap();
+}
+
+/// `SIGN_IMAGES`, the bundled image of each question in the manifest of the sign images.
+fn sign_images() {
+    let manifest_path = format!("{SIGN_IMAGES_DIR}/manifest.json");
+    println!("cargo::rerun-if-changed={SIGN_IMAGES_DIR}");
+    println!("cargo::rerun-if-env-changed=SIGN_IMAGES_BUDGET_KIB");
+    let manifest: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(&manifest_path).expect("the manifest of the sign images is readable"),
+    )
+    .expect("the manifest of the sign images is valid json");
+    let images = manifest["images"]
+        .as_object()
+        .expect("the manifest of the sign images has images");
+
+    let mut entries = String::new();
+    let mut files = BTreeSet::new();
+    for (num, image) in images {
+        let num: usize = num.parse().expect("the manifest is by question number");
+        let (Some(path), Some(url)) = (image["path"].as_str(), image["url"].as_str()) else {
+            panic!("the image of question {num} in {manifest_path} has no path or url");
+        };
+        let file = format!("{SIGN_IMAGES_DIR}/{path}");
+        assert!(
+            Path::new(&file).is_file(),
+            "{file} is in {manifest_path} but missing, run `theory-test-cli fetch-images --signs` again"
+        );
+        entries.push_str(&format!(
+            "    SignImage {{\n        num: {num},\n        url: {url:?},\n        \
+             file: {file:?},\n        asset: asset!({:?}),\n    }},\n",
+            format!("/{file}")
+        ));
+        files.insert(file);
+    }
+
+    let size = files
+        .iter()
+        .map(|file| fs::metadata(file).map_or(0, |metadata| metadata.len()))
+        .sum::<u64>();
+    let budget = env::var("SIGN_IMAGES_BUDGET_KIB").map_or(SIGN_IMAGES_BUDGET_KIB, |budget| {
+        budget
+            .parse()
+            .expect("SIGN_IMAGES_BUDGET_KIB is a number of KiB")
+    });
+    assert!(
+        size <= budget * 1024,
+        "the sign images in {SIGN_IMAGES_DIR} take {} KiB, over their budget of {budget} KiB",
+        size.div_ceil(1024)
+    );
+
+    let list = format!("pub const SIGN_IMAGES: &[SignImage] = &[\n{entries}];\n");
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("sign_images.rs");
+    fs::write(out, list).unwrap();
+}
@@ -0,0 +1,75 @@
+// The service worker of the app, which makes it usable offline.
+//
+// Pages are served network first, so a new version of the app is picked up as soon as it is
+// online. Everything else is content hashed by the build and served cache first.
+const VERSION = new URL(self.location).searchParams.get('v') || 'dev';
+const CACHE_PREFIX = 'theory-test-';
+const CACHE = CACHE_PREFIX + VERSION;
+
+self.addEventListener('install', () => {
+    self.skipWaiting();
+});
+
+self.addEventListener('activate', (event) => {
+    event.waitUntil(
+        caches
+            .keys()
+            .then((keys) =>
+                Promise.all(
+                    keys
+                        .filter((key) => key.startsWith(CACHE_PREFIX) && key !== CACHE)
+                        .map((key) => caches.delete(key)),
+                ),
+            )
+            .then(() => self.clients.claim()),
+    );
+});
+
+// The page sends the urls it loaded, which are cached so the app can start offline.
+self.addEventListener('message', (event) => {
+    if (event.data?.type !== 'precache') {
+        return;
+    }
+    event.waitUntil(
+        caches
+            .open(CACHE)
+            .then((cache) => cache.addAll(event.data.urls))
+            .then(() => event.source.postMessage({ type: 'cached' }))
+            .catch(() => {}),
+    );
+});
+
+async function networkFirst(request) {
+    const cache = await caches.open(CACHE);
+    try {
+        const response = await fetch(request);
+        if (response.ok) {
+            cache.put(request, response.clone());
+        }
+        return response;
+    } catch (e) {
+        // every route of the app is served by the same page
+        return (await cache.match(request)) ?? (await cache.match(self.registration.scope));
+    }
+}
+
+async function cacheFirst(request) {
+    const cache = await caches.open(CACHE);
+    const cached = await cache.match(request);
+    if (cached) {
+        return cached;
+    }
+    const response = await fetch(request);
+    if (response.ok) {
+        cache.put(request, response.clone());
+    }
+    return response;
+}
+
+self.addEventListener('fetch', (event) => {
+    const request = event.request;
+    if (request.method !== 'GET' || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+    event.respondWith(request.mode === 'navigate' ? networkFirst(request) : cacheFirst(request));
+});
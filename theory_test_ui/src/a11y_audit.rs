@@ -0,0 +1,517 @@
+//! An overlay listing the accessibility issues of the rendered page, for catching regressions
+//! while developing. Toggled with Alt+Shift+A, and only built into debug builds.
+//!
+//! The page is measured by a script, and the measurements are checked here: images without alt
+//! text, radio buttons outside of a labelled group, controls smaller than a touch target, and text
+//! whose contrast with its background is too low. Each issue points to the question it is in.
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+/// The smallest width and height of a control, in CSS pixels, as in WCAG 2.2 "Target Size
+/// (Minimum)".
+pub const MIN_TARGET_PX: f64 = 24.0;
+/// The least contrast of normal text with its background, WCAG AA.
+pub const MIN_CONTRAST: f64 = 4.5;
+/// The least contrast of large text with its background, WCAG AA.
+pub const MIN_CONTRAST_LARGE: f64 = 3.0;
+
+/// A color with its opacity, the channels between 0 and 1.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rgba {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    pub a: f64,
+}
+
+impl Rgba {
+    pub const WHITE: Rgba = Rgba {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+        a: 1.0,
+    };
+
+    /// A color as resolved by `getComputedStyle`, e.g. "rgb(0, 0, 0)", "rgba(0, 0, 0, 0.5)" or
+    /// "rgb(0 0 0 / 50%)", or a hex color, e.g. "#777".
+    pub fn parse(css: &str) -> Option<Self> {
+        let css = css.trim();
+        if let Some(hex) = css.strip_prefix('#') {
+            let channel = |i: usize, len: usize| {
+                let digits = hex.get(i * len..(i + 1) * len)?;
+                let value = u8::from_str_radix(&digits.repeat(3 - len), 16).ok()?;
+                Some(f64::from(value) / 255.0)
+            };
+            let len = match hex.len() {
+                3 => 1,
+                6 => 2,
+                _ => return None,
+            };
+            return Some(Rgba {
+                r: channel(0, len)?,
+                g: channel(1, len)?,
+                b: channel(2, len)?,
+                a: 1.0,
+            });
+        }
+        if css == "transparent" {
+            return Some(Rgba {
+                a: 0.0,
+                ..Rgba::WHITE
+            });
+        }
+        let args = css
+            .strip_prefix("rgba(")
+            .or_else(|| css.strip_prefix("rgb("))?
+            .strip_suffix(')')?;
+        let values = args
+            .split([',', ' ', '/'])
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>();
+        let number = |value: &str, max: f64| match value.strip_suffix('%') {
+            Some(percent) => percent.parse::<f64>().ok().map(|p| p / 100.0),
+            None => value.parse::<f64>().ok().map(|v| v / max),
+        };
+        let (r, g, b, a) = match values[..] {
+            [r, g, b] => (r, g, b, "1"),
+            [r, g, b, a] => (r, g, b, a),
+            _ => return None,
+        };
+        Some(Rgba {
+            r: number(r, 255.0)?,
+            g: number(g, 255.0)?,
+            b: number(b, 255.0)?,
+            a: number(a, 1.0)?.clamp(0.0, 1.0),
+        })
+    }
+
+    /// The color seen when this color is painted over `background`.
+    pub fn over(self, background: Rgba) -> Rgba {
+        let blend = |top: f64, bottom: f64| top * self.a + bottom * (1.0 - self.a);
+        Rgba {
+            r: blend(self.r, background.r),
+            g: blend(self.g, background.g),
+            b: blend(self.b, background.b),
+            a: 1.0,
+        }
+    }
+
+    /// The relative luminance of the color, as defined by WCAG.
+    pub fn luminance(self) -> f64 {
+        let linear = |channel: f64| {
+            if channel <= 0.04045 {
+                channel / 12.92
+            } else {
+                ((channel + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+}
+
+/// The contrast ratio of two opaque colors, from 1 for the same color to 21 for black on white.
+pub fn contrast_ratio(a: Rgba, b: Rgba) -> f64 {
+    let (a, b) = (a.luminance(), b.luminance());
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// The least contrast of text of `font_size_px` and `font_weight`. Large text, 18pt or 14pt
+/// bold, can have less.
+pub fn min_contrast(font_size_px: f64, font_weight: u32) -> f64 {
+    // 1pt is 4/3 of a CSS pixel
+    let large = font_size_px >= 24.0 || (font_weight >= 700 && font_size_px >= 18.66);
+    if large {
+        MIN_CONTRAST_LARGE
+    } else {
+        MIN_CONTRAST
+    }
+}
+
+pub fn is_too_small(width: f64, height: f64) -> bool {
+    width < MIN_TARGET_PX || height < MIN_TARGET_PX
+}
+
+/// The measurements of the page, as sent by [`SCAN_SCRIPT`].
+#[derive(Clone, PartialEq, Default, Debug, Deserialize)]
+pub struct Scan {
+    images: Vec<ImageScan>,
+    radios: Vec<RadioScan>,
+    targets: Vec<TargetScan>,
+    texts: Vec<TextScan>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct ImageScan {
+    question: Option<usize>,
+    element: String,
+    alt: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct RadioScan {
+    question: Option<usize>,
+    element: String,
+    /// The label of the radio group or fieldset the radio button is in, if it has one.
+    group_label: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct TargetScan {
+    question: Option<usize>,
+    element: String,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+struct TextScan {
+    question: Option<usize>,
+    element: String,
+    color: String,
+    /// The background colors of the element and of its ancestors, the element's first.
+    backgrounds: Vec<String>,
+    font_size: f64,
+    font_weight: u32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Problem {
+    MissingAlt,
+    UnlabelledRadioGroup,
+    SmallTarget { width: f64, height: f64 },
+    LowContrast { ratio: f64, required: f64 },
+}
+
+impl Problem {
+    pub fn description(&self) -> String {
+        match self {
+            Problem::MissingAlt => "image without alt text".to_string(),
+            Problem::UnlabelledRadioGroup => {
+                "radio button outside of a labelled radiogroup or fieldset".to_string()
+            }
+            Problem::SmallTarget { width, height } => format!(
+                "target of {width:.0}×{height:.0}px, less than {MIN_TARGET_PX:.0}×{MIN_TARGET_PX:.0}px"
+            ),
+            Problem::LowContrast { ratio, required } => {
+                format!("contrast of {ratio:.2}:1, less than {required}:1")
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Issue {
+    /// The official number of the question the element is in, if any.
+    pub question: Option<usize>,
+    /// The tag, id and classes of the element, e.g. "img.question-image".
+    pub element: String,
+    pub problem: Problem,
+}
+
+/// The contrast of a text with the background it is painted on, its ancestors' backgrounds
+/// composited over the white of the page.
+fn text_contrast(text: &TextScan) -> Option<f64> {
+    let background = text
+        .backgrounds
+        .iter()
+        .rev()
+        .map(|color| Rgba::parse(color))
+        .try_fold(Rgba::WHITE, |below, color| Some(color?.over(below)))?;
+    let color = Rgba::parse(&text.color)?.over(background);
+    Some(contrast_ratio(color, background))
+}
+
+/// The issues of the measured page, in the order of the checks.
+pub fn audit(scan: &Scan) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for image in &scan.images {
+        if image.alt.as_deref().is_none_or(|alt| alt.trim().is_empty()) {
+            issues.push(Issue {
+                question: image.question,
+                element: image.element.clone(),
+                problem: Problem::MissingAlt,
+            });
+        }
+    }
+    for radio in &scan.radios {
+        if radio.group_label.is_none() {
+            issues.push(Issue {
+                question: radio.question,
+                element: radio.element.clone(),
+                problem: Problem::UnlabelledRadioGroup,
+            });
+        }
+    }
+    for target in &scan.targets {
+        if is_too_small(target.width, target.height) {
+            issues.push(Issue {
+                question: target.question,
+                element: target.element.clone(),
+                problem: Problem::SmallTarget {
+                    width: target.width,
+                    height: target.height,
+                },
+            });
+        }
+    }
+    for text in &scan.texts {
+        let required = min_contrast(text.font_size, text.font_weight);
+        match text_contrast(text) {
+            Some(ratio) if ratio < required => issues.push(Issue {
+                question: text.question,
+                element: text.element.clone(),
+                problem: Problem::LowContrast { ratio, required },
+            }),
+            // colors which can't be parsed, e.g. of a newer syntax, aren't reported
+            _ => {}
+        }
+    }
+    issues
+}
+
+/// Send the shortcut which toggles the overlay.
+const SHORTCUT_SCRIPT: &str = r#"
+    window.addEventListener('keydown', (e) => {
+        if (e.altKey && e.shiftKey && e.code === 'KeyA') {
+            e.preventDefault();
+            dioxus.send(true);
+        }
+    });
+    await new Promise(() => {});
+"#;
+
+/// Measure the page, outside of the overlay, and send it as a [`Scan`].
+const SCAN_SCRIPT: &str = r#"
+    const outside = (el) => !el.closest('.a11y-audit');
+    const shown = (el) => {
+        const rect = el.getBoundingClientRect();
+        return rect.width > 0 || rect.height > 0;
+    };
+    const question = (el) => {
+        const container = el.closest('[data-question]');
+        return container ? Number(container.dataset.question) : null;
+    };
+    const describe = (el) => {
+        const classes = typeof el.className === 'string' ? el.className.trim().split(/\s+/).filter(Boolean) : [];
+        return el.tagName.toLowerCase() + (el.id ? '#' + el.id : '') + classes.map((c) => '.' + c).join('');
+    };
+    const all = (selector) => [...document.querySelectorAll(selector)].filter(outside);
+
+    const images = all('img').filter(shown).map((el) => ({
+        question: question(el),
+        element: describe(el),
+        alt: el.getAttribute('alt'),
+    }));
+    const radios = all('input[type=radio]').map((el) => {
+        const group = el.closest('[role=radiogroup], fieldset');
+        let label = null;
+        if (group && group.tagName === 'FIELDSET') {
+            const legend = group.querySelector('legend');
+            label = legend && legend.textContent;
+        } else if (group) {
+            const labelledBy = group.getAttribute('aria-labelledby');
+            const labelElement = labelledBy && document.getElementById(labelledBy);
+            label = group.getAttribute('aria-label') || (labelElement && labelElement.textContent);
+        }
+        return {
+            question: question(el),
+            element: describe(el),
+            group_label: label && label.trim() ? label.trim() : null,
+        };
+    });
+    // a control inside a label is hit anywhere in the label
+    const targets = [...new Set(all('button, a[href], select, textarea, input:not([type=hidden])')
+        .map((el) => el.closest('label') || el))]
+        .filter(shown)
+        .map((el) => {
+            const rect = el.getBoundingClientRect();
+            return { question: question(el), element: describe(el), width: rect.width, height: rect.height };
+        });
+    const texts = all('body *')
+        .filter((el) => shown(el) && [...el.childNodes].some((node) => node.nodeType === Node.TEXT_NODE && node.textContent.trim()))
+        .map((el) => {
+            const style = getComputedStyle(el);
+            const backgrounds = [];
+            for (let ancestor = el; ancestor; ancestor = ancestor.parentElement) {
+                backgrounds.push(getComputedStyle(ancestor).backgroundColor);
+            }
+            return {
+                question: question(el),
+                element: describe(el),
+                color: style.color,
+                backgrounds,
+                font_size: parseFloat(style.fontSize),
+                font_weight: parseInt(style.fontWeight, 10) || 400,
+            };
+        });
+    dioxus.send({ images, radios, targets, texts });
+"#;
+
+async fn scan_page() -> Vec<Issue> {
+    match document::eval(SCAN_SCRIPT).recv::<Scan>().await {
+        Ok(scan) => audit(&scan),
+        Err(error) => {
+            dioxus::logger::tracing::warn!("couldn't scan the page: {error}");
+            Vec::new()
+        }
+    }
+}
+
+/// The overlay, hidden until the shortcut is pressed. Each opening scans the current page.
+#[component]
+pub fn A11yAudit() -> Element {
+    let mut open = use_signal(|| false);
+    let mut issues = use_signal(|| None::<Vec<Issue>>);
+    let mut rescan = move || {
+        issues.set(None);
+        spawn(async move { issues.set(Some(scan_page().await)) });
+    };
+    use_future(move || async move {
+        let mut eval = document::eval(SHORTCUT_SCRIPT);
+        while eval.recv::<bool>().await.is_ok() {
+            open.toggle();
+            if open() {
+                rescan();
+            }
+        }
+    });
+
+    rsx! {
+        if open() {
+            div { class: "a11y-audit", dir: "ltr", lang: "en", role: "dialog",
+                div { class: "a11y-audit-header",
+                    strong { "Accessibility audit" }
+                    button { onclick: move |_| rescan(), "Rescan" }
+                    button { onclick: move |_| open.set(false), "Close" }
+                }
+                match issues() {
+                    None => rsx! { div { "Scanning..." } },
+                    Some(issues) if issues.is_empty() => rsx! { div { "No issues" } },
+                    Some(issues) => rsx! {
+                        div { {format!("{} issues", issues.len())} }
+                        ul {
+                            for issue in issues {
+                                li {
+                                    if let Some(num) = issue.question {
+                                        {format!("question {num:04} · ")}
+                                    }
+                                    code { {issue.element.clone()} }
+                                    {format!(": {}", issue.problem.description())}
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rgb(css: &str) -> Rgba {
+        Rgba::parse(css).unwrap()
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(rgb("rgb(255, 255, 255)"), Rgba::WHITE);
+        assert_eq!(rgb("#fff"), Rgba::WHITE);
+        assert_eq!(rgb("#FFFFFF"), Rgba::WHITE);
+        assert_eq!(rgb("rgba(0, 0, 0, 0)").a, 0.0);
+        assert_eq!(rgb("rgb(0 0 0 / 50%)").a, 0.5);
+        assert_eq!(rgb("transparent").a, 0.0);
+        assert_eq!(Rgba::parse("hsl(0, 0%, 0%)"), None);
+        assert_eq!(Rgba::parse("#12345"), None);
+        // half transparent black over white is middle grey
+        let grey = rgb("rgba(0, 0, 0, 0.5)").over(Rgba::WHITE);
+        assert!((grey.r - 0.5).abs() < 1e-9 && grey.a == 1.0);
+    }
+
+    #[test]
+    fn contrast() {
+        let (black, white) = (rgb("#000"), Rgba::WHITE);
+        assert!((contrast_ratio(black, white) - 21.0).abs() < 1e-9);
+        assert_eq!(contrast_ratio(white, black), contrast_ratio(black, white));
+        assert_eq!(contrast_ratio(white, white), 1.0);
+        // the lightest grey which still passes on white
+        assert!(contrast_ratio(rgb("#767676"), white) >= MIN_CONTRAST);
+        assert!(contrast_ratio(rgb("#777777"), white) < MIN_CONTRAST);
+        assert_eq!(min_contrast(16.0, 400), MIN_CONTRAST);
+        assert_eq!(min_contrast(24.0, 400), MIN_CONTRAST_LARGE);
+        assert_eq!(min_contrast(19.0, 700), MIN_CONTRAST_LARGE);
+        assert_eq!(min_contrast(19.0, 400), MIN_CONTRAST);
+    }
+
+    #[test]
+    fn target_sizes() {
+        assert!(!is_too_small(24.0, 24.0));
+        assert!(!is_too_small(200.0, 44.0));
+        assert!(is_too_small(23.9, 100.0));
+        assert!(is_too_small(100.0, 13.0));
+    }
+
+    #[test]
+    fn issues() {
+        let scan: Scan = serde_json::from_value(serde_json::json!({
+            "images": [
+                { "question": 862, "element": "img.question-image", "alt": "תמונה לשאלה 862" },
+                { "question": 863, "element": "img.question-image", "alt": " " },
+                { "question": null, "element": "img", "alt": null },
+            ],
+            "radios": [
+                { "question": 862, "element": "input.answer_input", "group_label": "מהי המהירות?" },
+                { "question": 863, "element": "input.answer_input", "group_label": null },
+            ],
+            "targets": [
+                { "question": 862, "element": "label.answer", "width": 300.0, "height": 40.0 },
+                { "question": null, "element": "button.note-button", "width": 60.0, "height": 18.0 },
+            ],
+            "texts": [
+                {
+                    "question": 862, "element": "label.answer", "color": "rgb(0, 0, 0)",
+                    "backgrounds": ["rgba(0, 0, 0, 0)", "rgb(255, 255, 255)"],
+                    "font_size": 16.0, "font_weight": 400,
+                },
+                {
+                    // light grey on the grey of a translucent overlay
+                    "question": 863, "element": "span.hint", "color": "rgb(150, 150, 150)",
+                    "backgrounds": ["rgba(0, 0, 0, 0)", "rgba(0, 0, 0, 0.5)"],
+                    "font_size": 16.0, "font_weight": 400,
+                },
+                {
+                    // passes only as large text
+                    "question": null, "element": "h1", "color": "rgb(130, 130, 130)",
+                    "backgrounds": ["rgb(255, 255, 255)"],
+                    "font_size": 32.0, "font_weight": 700,
+                },
+            ],
+        }))
+        .unwrap();
+        let issues = audit(&scan);
+        assert_eq!(
+            issues
+                .iter()
+                .map(|issue| (issue.question, issue.element.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(863), "img.question-image"),
+                (None, "img"),
+                (Some(863), "input.answer_input"),
+                (None, "button.note-button"),
+                (Some(863), "span.hint"),
+            ]
+        );
+        assert_eq!(issues[0].problem, Problem::MissingAlt);
+        assert_eq!(
+            issues[3].problem.description(),
+            "target of 60×18px, less than 24×24px"
+        );
+        let Problem::LowContrast { ratio, required } = issues[4].problem else {
+            panic!("expected a contrast issue, got {:?}", issues[4].problem);
+        };
+        assert!(ratio < 2.0 && required == MIN_CONTRAST);
+    }
+}
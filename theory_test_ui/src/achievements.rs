@@ -0,0 +1,33 @@
+//! Badges which are unlocked by milestones in the attempt history, persisted across sessions.
+use dioxus::prelude::*;
+pub use theory_test_core::achievements::{Achievement, AchievementContext, Unlocked};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "achievements";
+
+/// Provide the unlocked achievements to the whole app, loading the stored ones and saving every
+/// change.
+pub fn provide_achievements() {
+    let mut unlocked = use_context_provider(|| Signal::new(Unlocked::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<Unlocked>(STORAGE_KEY).await {
+            let recorded = std::mem::replace(&mut *unlocked.write(), stored);
+            for (achievement, at) in recorded.0 {
+                unlocked.write().0.entry(achievement).or_insert(at);
+            }
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let unlocked = unlocked.read();
+        if loaded() {
+            storage::save(STORAGE_KEY, &*unlocked);
+        }
+    });
+}
+
+pub fn use_achievements() -> Signal<Unlocked> {
+    use_context()
+}
@@ -0,0 +1,101 @@
+//! The hooks and components exporting and importing a backup, see [`theory_test_core::backup`].
+use dioxus::prelude::*;
+pub use theory_test_core::backup::{Backup, ImportError};
+
+use crate::{
+    achievements::use_achievements, bank::use_bank, csv_import::CsvImportAction,
+    history::use_history, notes::use_notes, personal_best::use_personal_bests,
+    preferences::use_preferences, tags::use_tags, toast::use_toast,
+};
+
+/// The name of an exported file, by the id of its bank.
+fn file_name(bank_id: &str) -> String {
+    format!("theory-test-backup-{}.json", bank_id)
+}
+
+/// Save `contents` as a file in the downloads of the browser.
+pub fn download(file_name: &str, mime_type: &str, contents: &str) {
+    document::eval(&format!(
+        r#"
+        const blob = new Blob([{:?}], {{ type: {:?} }});
+        const link = document.createElement('a');
+        link.href = URL.createObjectURL(blob);
+        link.download = {:?};
+        link.click();
+        URL.revokeObjectURL(link.href);
+        "#,
+        contents, mime_type, file_name
+    ));
+}
+
+/// Buttons to export the local data into a file, and to import such a file.
+#[component]
+pub fn BackupActions() -> Element {
+    let bank = use_bank();
+    let bank_hash = bank.content_hash;
+    let bank_id = bank.id;
+    let mut history = use_history();
+    let mut preferences = use_preferences();
+    let mut achievements = use_achievements();
+    let mut personal_bests = use_personal_bests();
+    let mut notes = use_notes();
+    let mut tags = use_tags();
+    let mut toasts = use_toast();
+
+    rsx! {
+        div { class: "setting backup",
+            button {
+                class: "button-primary",
+                onclick: move |_| {
+                    let backup = Backup::new(
+                        bank_hash,
+                        bank_id,
+                        history.read().clone(),
+                        preferences.read().clone(),
+                        achievements.read().clone(),
+                        personal_bests.read().clone(),
+                        notes.read().clone(),
+                    )
+                    .with_tags(tags.read().clone());
+                    if let Ok(json) = serde_json::to_string(&backup) {
+                        download(&file_name(bank_id), "application/json", &json);
+                    }
+                },
+                "ייצוא נתונים"
+            }
+            label { class: "button-primary",
+                "ייבוא נתונים"
+                input {
+                    r#type: "file",
+                    accept: ".json,application/json",
+                    hidden: true,
+                    onchange: move |e| async move {
+                        let Some(files) = e.files() else {
+                            return;
+                        };
+                        let Some(name) = files.files().into_iter().next() else {
+                            return;
+                        };
+                        let json = files.read_file_to_string(&name).await.unwrap_or_default();
+                        match Backup::parse(&json, bank_hash) {
+                            Ok(backup) => {
+                                let report = backup
+                                    .merge_into(
+                                        &mut history.write(),
+                                        &mut preferences.write(),
+                                        &mut achievements.write(),
+                                        &mut personal_bests.write(),
+                                        &mut notes.write(),
+                                        &mut tags.write(),
+                                    );
+                                toasts.success(report.description_he());
+                            }
+                            Err(e) => toasts.error(e.to_string()),
+                        }
+                    },
+                }
+            }
+            CsvImportAction {}
+        }
+    }
+}
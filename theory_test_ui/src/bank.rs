@@ -0,0 +1,438 @@
+//! Loading of the question bank, shared by the whole app.
+//!
+//! Several banks can be bundled with the app, one of them is active at a time. The progress
+//! stored for each bank is kept apart, see [`storage`].
+use std::{
+    cell::OnceCell,
+    collections::BTreeMap,
+    rc::Rc,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use dioxus::{logger::tracing, prelude::*};
+use theory_test_parser::{
+    bundle::{self, Bundle},
+    observer::ParseWarning,
+    question_parser::{BilingualAlignment, ExamQuestions, LicenseClass, ParseOptions, Question},
+};
+use web_time::Instant;
+
+use crate::{remote, storage};
+
+/// A question bank which is embedded in the app, parsed ahead of time.
+pub struct BundledBank {
+    /// Identifies the bank in stored keys, must never change once released.
+    pub id: &'static str,
+    pub name_he: &'static str,
+    /// The code of the language of the questions, e.g. "he".
+    pub language: &'static str,
+    /// The license class whose questions the app draws out of the bank, e.g. for exams.
+    pub class: LicenseClass,
+    /// The bank parsed with `theory-test-cli bundle`, decompressed when it is first needed. The
+    /// client of the fullstack app gets it from the server instead, see [`remote::load_bank`].
+    #[cfg(any(not(feature = "fullstack"), feature = "server", test))]
+    pub bundle: &'static [u8],
+}
+
+/// The official spreadsheet, which has the questions of every class.
+#[cfg(any(not(feature = "fullstack"), feature = "server", test))]
+const OFFICIAL_BUNDLE: &[u8] = include_bytes!("../banks/b.json.br");
+
+/// The banks which are bundled with the app, the first one is the default. Adding a bank only
+/// requires bundling its spreadsheet into `banks` and adding an entry here.
+pub const BUNDLED_BANKS: &[BundledBank] = &[
+    BundledBank {
+        id: storage::DEFAULT_BANK,
+        name_he: "רכב פרטי (B)",
+        language: "he",
+        class: LicenseClass::B,
+        #[cfg(any(not(feature = "fullstack"), feature = "server", test))]
+        bundle: OFFICIAL_BUNDLE,
+    },
+    BundledBank {
+        id: "a",
+        name_he: "אופנוע (A)",
+        language: "he",
+        class: LicenseClass::A,
+        #[cfg(any(not(feature = "fullstack"), feature = "server", test))]
+        bundle: OFFICIAL_BUNDLE,
+    },
+];
+
+/// The bundled bank of `id`, or the default one for unknown ids, e.g. of a bank which was
+/// removed from the app.
+pub fn bundled(id: &str) -> &'static BundledBank {
+    BUNDLED_BANKS
+        .iter()
+        .find(|bank| bank.id == id)
+        .unwrap_or(&BUNDLED_BANKS[0])
+}
+
+/// A bank in a second language which is embedded in the app, translating a bundled bank.
+pub struct BundledTranslation {
+    /// The id of the translated bank, see [`BundledBank::id`].
+    pub bank_id: &'static str,
+    /// The code of the language, e.g. "ru".
+    pub language: &'static str,
+    pub name_he: &'static str,
+    bytes: &'static [u8],
+}
+
+/// Translations of the bundled banks, at most one per bank. Adding a translation only requires
+/// adding its file and an entry here.
+pub const BUNDLED_TRANSLATIONS: &[BundledTranslation] = &[];
+
+/// A parsed translation of the loaded bank.
+pub struct Translation {
+    pub language: &'static str,
+    pub name_he: &'static str,
+    pub alignment: BilingualAlignment,
+}
+
+/// Where a question bank is loaded from.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BankSource {
+    /// A bank which is embedded in the app, by its id.
+    Bundled(&'static str),
+}
+
+impl BankSource {
+    pub fn description_he(&self) -> String {
+        match self {
+            BankSource::Bundled(id) => format!("המאגר המובנה \"{}\"", bundled(id).name_he),
+        }
+    }
+}
+
+/// A successfully loaded question bank.
+pub struct Bank {
+    pub questions: ExamQuestions,
+    pub source: BankSource,
+    /// The id of the bundled bank, see [`BundledBank::id`].
+    pub id: &'static str,
+    pub name_he: &'static str,
+    /// The code of the language of the questions, see [`BundledBank::language`].
+    pub language: &'static str,
+    /// The class whose questions the app draws, see [`BundledBank::class`].
+    pub class: LicenseClass,
+    /// Identifies the content of the bank, stable across sessions and versions of the app.
+    pub content_hash: u64,
+    /// What was wrong in the bank, e.g. rows which were skipped because they couldn't be parsed.
+    pub warnings: Vec<ParseWarning>,
+    translation_source: Option<&'static BundledTranslation>,
+    /// Parsed the first time it is needed, `None` inside if it couldn't be parsed.
+    translation: OnceCell<Option<Translation>>,
+}
+
+impl Bank {
+    /// The questions of the bank which are relevant for the class the app draws out of it.
+    pub fn class_questions(&self) -> Vec<Question> {
+        self.questions
+            .questions
+            .iter()
+            .filter(|q| q.license_classes.contains(&self.class))
+            .cloned()
+            .collect()
+    }
+
+    pub fn has_translation(&self) -> bool {
+        self.translation_source.is_some()
+    }
+
+    /// The translation of the bank, parsing it the first time it is needed.
+    pub fn translation(&self) -> Option<&Translation> {
+        self.translation
+            .get_or_init(|| {
+                let source = self.translation_source?;
+                let (questions, _warnings) = ExamQuestions::parse_from_xlsx_with_options(
+                    source.bytes,
+                    ParseOptions {
+                        lenient: true,
+                        ..Default::default()
+                    },
+                )
+                .ok()?;
+                Some(Translation {
+                    language: source.language,
+                    name_he: source.name_he,
+                    alignment: BilingualAlignment::new(questions),
+                })
+            })
+            .as_ref()
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BankError {
+    pub source: BankSource,
+    pub message: String,
+    /// The warnings until the bank failed to load, which may explain why it did.
+    pub warnings: Vec<ParseWarning>,
+}
+
+pub type BankResult = Result<Rc<Bank>, BankError>;
+
+/// A bundled bank once it was decompressed: its questions, warnings and content hash, or why it
+/// failed to parse and the warnings until then.
+type Parsed = Result<(ExamQuestions, Vec<ParseWarning>, u64), (String, Vec<ParseWarning>)>;
+
+/// Decompress and deserialize the `compressed` bundle of a bank, tracing how long each took.
+fn decompress(bundled: &BundledBank, compressed: &[u8]) -> Parsed {
+    let start = Instant::now();
+    let json = bundle::decompress(compressed);
+    let decompressed_at = Instant::now();
+    let read = json
+        .as_deref()
+        .map_err(ToString::to_string)
+        .and_then(|json| Bundle::from_json(json).map_err(|e| e.to_string()));
+    tracing::info!(
+        bank = bundled.id,
+        compressed = compressed.len(),
+        decompressed = json.as_ref().map_or(0, Vec::len),
+        decompress_ms = (decompressed_at - start).as_secs_f64() * 1000.0,
+        deserialize_ms = decompressed_at.elapsed().as_secs_f64() * 1000.0,
+        "decompressed the bank"
+    );
+    let bundle = read.map_err(|e| (format!("the bundled bank is corrupt: {e}"), Vec::new()))?;
+    bundle
+        .parsed
+        .map(|(questions, warnings)| (ExamQuestions { questions }, warnings, bundle.content_hash))
+}
+
+/// Decompress a bundled bank the first time it is needed, and only then. A server rendering
+/// pages decompresses each bank once for all of the requests.
+fn parse(bundled: &'static BundledBank, compressed: &[u8]) -> Parsed {
+    static PARSED: Mutex<BTreeMap<&str, Parsed>> = Mutex::new(BTreeMap::new());
+    let mut parsed = PARSED.lock().unwrap_or_else(PoisonError::into_inner);
+    parsed
+        .entry(bundled.id)
+        .or_insert_with(|| decompress(bundled, compressed))
+        .clone()
+}
+
+/// Parse the bundled banks ahead of the first page which needs them, e.g. when a server starts.
+#[cfg(feature = "server")]
+pub fn preload() {
+    for bank in BUNDLED_BANKS {
+        let _ = parse(bank, bank.bundle);
+    }
+}
+
+/// Load an embedded bank, skipping the rows which can't be parsed.
+#[cfg(any(not(feature = "fullstack"), feature = "server", test))]
+pub fn load(source: BankSource) -> BankResult {
+    let BankSource::Bundled(id) = source;
+    load_bundle(source, bundled(id).bundle)
+}
+
+/// Load a bank out of its `compressed` bundle, skipping the rows which can't be parsed.
+pub fn load_bundle(source: BankSource, compressed: &[u8]) -> BankResult {
+    let bundled = match source {
+        BankSource::Bundled(id) => bundled(id),
+    };
+    parse(bundled, compressed)
+        .map(|(questions, warnings, content_hash)| {
+            Rc::new(Bank {
+                questions,
+                source,
+                id: bundled.id,
+                name_he: bundled.name_he,
+                language: bundled.language,
+                class: bundled.class,
+                content_hash,
+                warnings,
+                translation_source: BUNDLED_TRANSLATIONS
+                    .iter()
+                    .find(|translation| translation.bank_id == bundled.id),
+                translation: OnceCell::new(),
+            })
+        })
+        .map_err(|(message, warnings)| BankError {
+            source,
+            message,
+            warnings,
+        })
+}
+
+/// The source of a bank which was loaded or failed to.
+fn source(result: &BankResult) -> BankSource {
+    match result {
+        Ok(bank) => bank.source,
+        Err(error) => error.source,
+    }
+}
+
+/// Whether the bank is loaded once the page was first painted, showing that it is loading until
+/// then, rather than before. Pages rendered on the server need it from the start, to render the
+/// same when they are hydrated, and so do the tests, which render without a browser.
+const LOAD_AFTER_FIRST_PAINT: bool = cfg!(all(target_arch = "wasm32", not(feature = "fullstack")));
+
+/// Provide the active bank to the whole app.
+///
+/// Unless it is loaded after the first paint, the default bank is loaded right away. Another
+/// active bank replaces it once the stored choice is read. The fullstack app suspends until the
+/// default bank came with the page, which the browser hydrates with the same bank.
+pub fn provide_bank() -> Result<(), RenderError> {
+    #[cfg(feature = "fullstack")]
+    let default_bundle = remote::use_default_bank_bundle()?;
+    let mut bank = use_context_provider(|| {
+        let default = BankSource::Bundled(BUNDLED_BANKS[0].id);
+        #[cfg(feature = "fullstack")]
+        let load = |source| remote::loaded_bundle(source, &default_bundle.peek());
+        Signal::new((!LOAD_AFTER_FIRST_PAINT).then(|| load(default)))
+    });
+    use_future(move || async move {
+        let active = BankSource::Bundled(bundled(&storage::active_bank().await).id);
+        if bank.peek().as_ref().map(source) != Some(active) {
+            // leaves the browser a chance to paint that the bank is loading
+            futures_timer::Delay::new(Duration::ZERO).await;
+            bank.set(Some(remote::load_bank(active).await));
+        }
+    });
+    Ok(())
+}
+
+/// The loaded bank. Must only be used below [`BankGate`], which guarantees it loaded.
+pub fn use_bank() -> Rc<Bank> {
+    let bank = use_context::<Signal<Option<BankResult>>>();
+    let bank = bank.read();
+    bank.as_ref()
+        .and_then(|bank| bank.as_ref().ok())
+        .expect("use_bank is only used once the bank was loaded")
+        .clone()
+}
+
+/// Renders its children once the bank loaded, and an error screen if it failed to.
+#[component]
+pub fn BankGate(children: Element) -> Element {
+    let mut bank = use_context::<Signal<Option<BankResult>>>();
+    let error = match &*bank.read() {
+        None => {
+            return rsx! {
+                div { class: "bank-loading", "טוען את מאגר השאלות..." }
+            }
+        }
+        Some(Ok(_)) => None,
+        Some(Err(error)) => Some(error.clone()),
+    };
+    let Some(error) = error else {
+        return children;
+    };
+    let default = BankSource::Bundled(BUNDLED_BANKS[0].id);
+
+    rsx! {
+        div { class: "bank-error",
+            h1 { "טעינת מאגר השאלות נכשלה" }
+            div { {format!("מקור: {}", error.source.description_he())} }
+            div { class: "bank-error-message", dir: "ltr", {error.message} }
+            if !error.warnings.is_empty() {
+                ul { class: "bank-error-warnings", dir: "ltr",
+                    for warning in error.warnings.iter() {
+                        li { {warning.to_string()} }
+                    }
+                }
+            }
+            div { class: "bank-error-actions",
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        spawn(async move { bank.set(Some(remote::load_bank(error.source).await)) });
+                    },
+                    "נסה שוב"
+                }
+                if error.source != default {
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| storage::switch_bank(BUNDLED_BANKS[0].id),
+                        "טען את מאגר ברירת המחדל"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A collapsible list of the warnings of loading the bank, e.g. the rows which were skipped.
+#[component]
+pub fn BankWarnings() -> Element {
+    let bank = use_bank();
+    if bank.warnings.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        details { class: "bank-warnings",
+            summary {
+                {
+                    format!(
+                        "נטענו {} שאלות מ{}, עם {} אזהרות",
+                        bank.questions.questions.len(),
+                        bank.source.description_he(),
+                        bank.warnings.len(),
+                    )
+                }
+            }
+            ul { dir: "ltr",
+                for warning in bank.warnings.iter() {
+                    li { {warning.to_string()} }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use theory_test_parser::exam::ExamRules;
+
+    use super::*;
+
+    #[test]
+    fn bundled_banks() {
+        assert_eq!(BUNDLED_BANKS[0].id, storage::DEFAULT_BANK);
+        for (idx, bank) in BUNDLED_BANKS.iter().enumerate() {
+            // ids are part of stored keys
+            assert!(!bank.id.contains('/'));
+            assert!(BUNDLED_BANKS[..idx].iter().all(|other| other.id != bank.id));
+            assert_eq!(bundled(bank.id).id, bank.id);
+        }
+        assert_eq!(bundled("removed").id, storage::DEFAULT_BANK);
+        for (idx, translation) in BUNDLED_TRANSLATIONS.iter().enumerate() {
+            assert!(BUNDLED_BANKS
+                .iter()
+                .any(|bank| bank.id == translation.bank_id));
+            assert!(BUNDLED_TRANSLATIONS[..idx]
+                .iter()
+                .all(|other| other.bank_id != translation.bank_id));
+        }
+    }
+
+    #[test]
+    fn class_questions() {
+        for bundled in BUNDLED_BANKS {
+            let bank = load(BankSource::Bundled(bundled.id)).unwrap();
+            let questions = bank.class_questions();
+            assert!(questions.len() >= ExamRules::official(bank.class).num_questions);
+            assert!(questions
+                .iter()
+                .all(|q| q.license_classes.contains(&bundled.class)));
+        }
+    }
+
+    #[test]
+    fn decompressed_bank_is_the_parsed_spreadsheet() {
+        // when this fails, the bundle is out of date, run
+        // `theory-test-cli bundle theory_test_parser/test.xlsx --out theory_test_ui/banks/b.json.br`
+        let xlsx = include_bytes!("../../theory_test_parser/test.xlsx");
+        let (parsed, warnings) = Bundle::parse_xlsx(xlsx).parsed.unwrap();
+        let bank = load(BankSource::Bundled(storage::DEFAULT_BANK)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&bank.questions.questions).unwrap(),
+            serde_json::to_value(&parsed).unwrap()
+        );
+        assert_eq!(bank.warnings, warnings);
+        // the progress stored for the bank is kept by the hash of its spreadsheet
+        assert_eq!(bank.content_hash, bundle::content_hash(xlsx));
+    }
+}
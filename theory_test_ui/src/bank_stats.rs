@@ -0,0 +1,118 @@
+//! A strip of counts of the bank's questions on the main page.
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::{ExamQuestions, LicenseClass};
+
+use crate::{
+    bank::use_bank, category_badge::CategoryBadge, category_filter::CategoryFilter,
+    history::use_history, practice_config::DEFAULT_NUM_QUESTIONS, Route,
+};
+
+/// How many of the questions of `class` were seen, in percent.
+fn coverage_percent(bank: &ExamQuestions, class: LicenseClass, seen: &HashSet<usize>) -> usize {
+    let (total, covered) = bank
+        .questions
+        .iter()
+        .filter(|q| q.license_classes.contains(&class))
+        .fold((0, 0), |(total, covered), q| {
+            (total + 1, covered + usize::from(seen.contains(&q.num)))
+        });
+    if total == 0 {
+        return 0;
+    }
+    covered * 100 / total
+}
+
+#[component]
+pub fn BankStatsStrip() -> Element {
+    let bank = use_bank();
+    let history = use_history();
+    let mut class = use_signal(|| bank.class);
+    let stats_bank = bank.clone();
+    let stats = use_memo(move || stats_bank.questions.stats(class()));
+    let coverage = use_memo(move || {
+        let history = history.read();
+        (!history.attempts.is_empty())
+            .then(|| coverage_percent(&bank.questions, class(), &history.seen()))
+    });
+
+    rsx! {
+        div { class: "bank-stats",
+            span { {format!("{} שאלות במאגר", stats.read().total)} }
+            label {
+                "דרגה "
+                select {
+                    onchange: move |e| {
+                        if let Some(selected) = LicenseClass::from_code(&e.value()) {
+                            class.set(selected);
+                        }
+                    },
+                    for option_class in LicenseClass::ALL {
+                        option {
+                            value: option_class.code(),
+                            selected: option_class == class(),
+                            {option_class.code()}
+                        }
+                    }
+                }
+                {format!(": {} שאלות", stats.read().class_total)}
+            }
+            for (category , count) in stats.read().per_category.clone() {
+                if count == 0 {
+                    span {
+                        CategoryBadge { category }
+                        " 0"
+                    }
+                } else {
+                    Link {
+                    class: "main-page-link",
+                    to: Route::PracticeExam {
+                        num_questions: count.min(DEFAULT_NUM_QUESTIONS),
+                        feedback: false,
+                        category: CategoryFilter {
+                            include: vec![category],
+                            exclude: Vec::new(),
+                        }
+                            .include_query(),
+                        exclude: String::new(),
+                        tag: String::new(),
+                    },
+                    CategoryBadge { category }
+                    {format!(" {}", count)}
+                }
+                }
+            }
+            span { {format!("{} עם תמונה", stats.read().with_image)} }
+            if let Some(coverage) = coverage() {
+                span { {format!("ראית {}% מהמאגר", coverage)} }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use theory_test_parser::question_parser::Question;
+
+    #[test]
+    fn coverage() {
+        let question = |num, license_classes| Question {
+            license_classes,
+            ..Question::sample(num, 0)
+        };
+        let bank = ExamQuestions {
+            questions: vec![
+                question(1, vec![LicenseClass::B]),
+                question(2, vec![LicenseClass::B]),
+                question(3, vec![LicenseClass::B, LicenseClass::C]),
+                question(4, vec![LicenseClass::C]),
+            ],
+        };
+        let seen = HashSet::from([1, 4]);
+        assert_eq!(coverage_percent(&bank, LicenseClass::B, &seen), 33);
+        assert_eq!(coverage_percent(&bank, LicenseClass::C, &seen), 50);
+        assert_eq!(coverage_percent(&bank, LicenseClass::D, &seen), 0);
+    }
+}
@@ -0,0 +1,176 @@
+//! What changed in the question bank since the version the app had before, shown once a release
+//! brings a new one.
+use dioxus::prelude::*;
+use theory_test_core::bank_versions::BankVersions;
+
+use crate::{bank::use_bank, challenge, direction::isolate, storage, ExamQuestion, Route};
+
+const STORAGE_KEY: &str = "bank-versions";
+
+/// The stored versions of the active bank, once they loaded and the loaded bank was recorded in
+/// them.
+fn use_bank_versions() -> Signal<Option<BankVersions>> {
+    let bank = use_bank();
+    let mut versions = use_signal(|| None);
+    use_future(move || {
+        let bank = bank.clone();
+        async move {
+            let stored = storage::load::<BankVersions>(STORAGE_KEY)
+                .await
+                .unwrap_or_default();
+            let mut observed = stored.clone();
+            observed.observe(bank.content_hash, &bank.questions);
+            if observed != stored {
+                storage::save(STORAGE_KEY, &observed);
+            }
+            versions.set(Some(observed));
+        }
+    });
+    versions
+}
+
+fn mark_seen(mut versions: Signal<Option<BankVersions>>) {
+    if let Some(versions) = versions.write().as_mut() {
+        versions.seen = true;
+        storage::save(STORAGE_KEY, versions);
+    }
+}
+
+/// A notice on the main page that the bank changed, until what changed was seen.
+#[component]
+pub fn BankUpdateNotice() -> Element {
+    let versions = use_bank_versions();
+    if !versions.read().as_ref().is_some_and(BankVersions::unseen) {
+        return rsx! {};
+    }
+    rsx! {
+        div { class: "bank-update-notice",
+            "מאגר השאלות עודכן. "
+            Link { to: Route::BankChanges {}, "מה חדש במאגר" }
+            button {
+                class: "note-button",
+                onclick: move |_| mark_seen(versions),
+                "הסתר"
+            }
+        }
+    }
+}
+
+/// The questions which were added, removed or changed in the last update of the bank.
+#[component]
+pub fn BankChanges() -> Element {
+    let bank = use_bank();
+    let nav = navigator();
+    let versions = use_bank_versions();
+    // the page shows what changed, so it was seen
+    use_effect(move || {
+        if versions.read().as_ref().is_some_and(BankVersions::unseen) {
+            mark_seen(versions);
+        }
+    });
+    let update = versions
+        .read()
+        .as_ref()
+        .map(|versions| versions.update(bank.content_hash, &bank.questions));
+
+    rsx! {
+        div { class: "bank-changes",
+            h1 { "מה חדש במאגר" }
+            match update {
+                None => rsx! {
+                    div { "טוען..." }
+                },
+                Some(None) => rsx! {
+                    div { "לא זוהו שינויים במאגר מאז שהאפליקציה נפתחה לראשונה במכשיר זה." }
+                },
+                Some(Some(update)) => rsx! {
+                    div {
+                        {
+                            format!(
+                                "המאגר עודכן מ־{} שאלות ל־{} שאלות (הגרסה {} הוחלפה בגרסה {}).",
+                                update.previous_num_questions,
+                                update.current_num_questions,
+                                isolate(&format!("{:016x}", update.previous_hash)),
+                                isolate(&format!("{:016x}", update.current_hash)),
+                            )
+                        }
+                    }
+                    if let Some(diff) = update.diff {
+                        div { class: "bank-changes-counts",
+                            {
+                                format!(
+                                    "{} שאלות חדשות, {} הוסרו, {} השתנו",
+                                    diff.added.len(),
+                                    diff.removed.len(),
+                                    diff.modified.len(),
+                                )
+                            }
+                        }
+                        if !diff.added.is_empty() {
+                            button {
+                                class: "button-primary",
+                                onclick: {
+                                    let added = diff.added.clone();
+                                    move |_| {
+                                        nav.push(Route::Challenge {
+                                            qs: challenge::encode(&added),
+                                        });
+                                    }
+                                },
+                                "מבחן תרגול בשאלות החדשות"
+                            }
+                            h2 { "שאלות חדשות" }
+                            for question in bank.questions.questions.iter().filter(|q| diff.added.contains(&q.num)) {
+                                div { key: "{question.num}", class: "browse-question",
+                                    ExamQuestion {
+                                        question: question.clone(),
+                                        language: bank.language,
+                                        show_correct_answer: true,
+                                        user_selection: None,
+                                        on_select: |_| {},
+                                        show_license_classes: true,
+                                        category_link: true,
+                                        question_num: question.num,
+                                        show_question_num: true,
+                                        use_canonical_question_num: true,
+                                    }
+                                }
+                            }
+                        }
+                        if !diff.modified.is_empty() {
+                            h2 { "שאלות שהשתנו" }
+                            div { class: "bank-changes-nums",
+                                for modified in diff.modified {
+                                    Link {
+                                        key: "{modified.num}",
+                                        to: Route::QuestionPage { num: modified.num },
+                                        {format!("{:04}", modified.num)}
+                                    }
+                                }
+                            }
+                        }
+                        if !diff.removed.is_empty() {
+                            h2 { "שאלות שהוסרו" }
+                            div { class: "bank-changes-nums",
+                                for num in diff.removed {
+                                    span { key: "{num}", {format!("{num:04}")} }
+                                }
+                            }
+                        }
+                    } else {
+                        div { "השאלות של הגרסה הקודמת לא נשמרו במכשיר, לכן אי אפשר להציג מה השתנה." }
+                    }
+                },
+            }
+            div {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,486 @@
+use std::{collections::HashSet, ops::Range};
+
+use dioxus::prelude::*;
+use theory_test_core::tags::normalize_tag;
+use theory_test_parser::question_parser::{LicenseClass, Question, QuestionCategory};
+
+use crate::{
+    bank::use_bank,
+    category_badge::CategoryBadge,
+    direction::isolate,
+    history::use_history,
+    notes::use_notes,
+    remote::{use_bank_metadata, use_questions_page, QuestionFilter, QuestionsPage},
+    tags::{use_tags, Tags},
+    ExamQuestion, Route,
+};
+
+/// How many questions are shown on each page.
+const PAGE_SIZE: usize = 20;
+
+/// The number of pages needed for `num_questions`, at least one.
+pub fn num_pages(num_questions: usize) -> usize {
+    num_questions.div_ceil(PAGE_SIZE).max(1)
+}
+
+/// The indices of the questions shown on a 1-based `page`, clamped to the existing pages.
+pub fn page_range(num_questions: usize, page: usize) -> Range<usize> {
+    let page = page.clamp(1, num_pages(num_questions));
+    let start = (page - 1) * PAGE_SIZE;
+    start..(start + PAGE_SIZE).min(num_questions)
+}
+
+/// The browse page showing every question of `category`.
+pub fn category_route(category: QuestionCategory) -> Route {
+    BrowseFilters {
+        category: Some(category),
+        ..Default::default()
+    }
+    .route(1)
+}
+
+/// Filters questions by how they appear in the attempt history.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum HistoryFilter {
+    /// Questions which were answered wrong at least once.
+    Missed,
+    /// Questions which never appeared in a graded exam.
+    Unseen,
+}
+
+impl HistoryFilter {
+    fn code(&self) -> &'static str {
+        match self {
+            HistoryFilter::Missed => "missed",
+            HistoryFilter::Unseen => "unseen",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        [HistoryFilter::Missed, HistoryFilter::Unseen]
+            .into_iter()
+            .find(|filter| filter.code() == code)
+    }
+
+    fn label_he(&self) -> &'static str {
+        match self {
+            HistoryFilter::Missed => "שאלות שטעיתי בהן",
+            HistoryFilter::Unseen => "שאלות שטרם ראיתי",
+        }
+    }
+}
+
+/// The filters of the browse page, which all have to match for a question to be shown.
+#[derive(Clone, PartialEq, Default, Debug)]
+struct BrowseFilters {
+    class: Option<LicenseClass>,
+    category: Option<QuestionCategory>,
+    with_image: bool,
+    history: Option<HistoryFilter>,
+    with_note: bool,
+    /// A tag of the user's, normalized.
+    tag: Option<String>,
+}
+
+impl BrowseFilters {
+    /// Parse the filters from the query of the browse route, ignoring unknown values.
+    fn from_query(
+        class: &str,
+        category: &str,
+        with_image: bool,
+        history: &str,
+        with_note: bool,
+        tag: &str,
+    ) -> Self {
+        Self {
+            class: LicenseClass::from_code(class),
+            category: QuestionCategory::from_str_he(category),
+            with_image,
+            history: HistoryFilter::from_code(history),
+            with_note,
+            tag: normalize_tag(tag),
+        }
+    }
+
+    fn route(&self, page: usize) -> Route {
+        Route::Browse {
+            class: self.class.map(|c| c.code().to_string()).unwrap_or_default(),
+            category: self
+                .category
+                .map(|c| c.as_str_he().to_string())
+                .unwrap_or_default(),
+            with_image: self.with_image,
+            history: self
+                .history
+                .map(|h| h.code().to_string())
+                .unwrap_or_default(),
+            with_note: self.with_note,
+            tag: self.tag.clone().unwrap_or_default(),
+            page,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The filters which only depend on the bank, `None` when the others are set too.
+    fn bank_filter(&self) -> Option<QuestionFilter> {
+        (self.history.is_none() && !self.with_note && self.tag.is_none()).then_some(
+            QuestionFilter {
+                class: self.class,
+                category: self.category,
+                with_image: self.with_image,
+            },
+        )
+    }
+
+    fn matches(
+        &self,
+        question: &Question,
+        seen: &HashSet<usize>,
+        missed: &HashSet<usize>,
+        noted: &HashSet<usize>,
+        tags: &Tags,
+    ) -> bool {
+        QuestionFilter {
+            class: self.class,
+            category: self.category,
+            with_image: self.with_image,
+        }
+        .matches(question)
+            && self.history.is_none_or(|history| match history {
+                HistoryFilter::Missed => missed.contains(&question.num),
+                HistoryFilter::Unseen => !seen.contains(&question.num),
+            })
+            && (!self.with_note || noted.contains(&question.num))
+            && self
+                .tag
+                .as_ref()
+                .is_none_or(|tag| tags.get(question.num).contains(&tag.as_str()))
+    }
+
+    /// Descriptions of the filters which are set, for the active filters bar.
+    fn descriptions_he(&self) -> Vec<String> {
+        let mut descriptions = Vec::new();
+        if let Some(class) = self.class {
+            descriptions.push(format!("דרגה {}", isolate(class.code())));
+        }
+        if let Some(category) = self.category {
+            descriptions.push(category.as_str_he().to_string());
+        }
+        if self.with_image {
+            descriptions.push("עם תמונה".to_string());
+        }
+        if let Some(history) = self.history {
+            descriptions.push(history.label_he().to_string());
+        }
+        if self.with_note {
+            descriptions.push("שאלות עם הערות".to_string());
+        }
+        if let Some(tag) = &self.tag {
+            descriptions.push(format!("תגית: {}", isolate(tag)));
+        }
+        descriptions
+    }
+}
+
+/// All of the questions of the bank with their correct answers, optionally filtered.
+#[component]
+pub fn Browse(
+    class: String,
+    category: String,
+    with_image: bool,
+    history: String,
+    with_note: bool,
+    tag: String,
+    page: usize,
+) -> Element {
+    let bank = use_bank();
+    let attempts = use_history();
+    let nav = navigator();
+    let notes = use_notes();
+    let tags = use_tags();
+    // a memo rather than the filters themselves, which aren't `Copy`, for the buttons to build on
+    let filters =
+        use_memo(use_reactive!(|class,
+                                category,
+                                with_image,
+                                history,
+                                with_note,
+                                tag| {
+            BrowseFilters::from_query(&class, &category, with_image, &history, with_note, &tag)
+        }));
+    let metadata = use_bank_metadata(bank.clone())?;
+    // the pages filtered by the history, the notes or the tags are filtered in the browser, which
+    // has them
+    let questions = match use_questions_page(bank.clone(), filters.read().bank_filter(), page)? {
+        Some(questions) => questions,
+        None => {
+            let noted = notes.read().nums();
+            let tags = tags.read();
+            let filters = filters.read();
+            let (seen, missed) = {
+                let attempts = attempts.read();
+                (attempts.seen(), attempts.missed())
+            };
+            QuestionsPage::new(
+                bank.questions
+                    .questions
+                    .iter()
+                    .filter(|q| filters.matches(q, &seen, &missed, &noted, &tags)),
+                page,
+            )
+        }
+    };
+    let num_pages = questions.num_pages();
+    let page = questions.page;
+    // changing the filters goes back to the first page
+    let filter = move |filters: BrowseFilters| {
+        nav.push(filters.route(1));
+    };
+
+    rsx! {
+        div { class: "browse",
+            h1 { "מאגר השאלות" }
+            div { class: "browse-bank",
+                {format!("{}: {} שאלות", metadata.name_he, metadata.num_questions)}
+            }
+            div { class: "browse-filters",
+                div { class: "license-classes",
+                    button {
+                        class: "license-class",
+                        class: if filters.read().class.is_none() { "selected" },
+                        onclick: move |_| filter(BrowseFilters { class: None, ..filters() }),
+                        "כל הדרגות"
+                    }
+                    for class in LicenseClass::ALL {
+                        button {
+                            class: "license-class",
+                            class: if filters.read().class == Some(class) { "selected" },
+                            onclick: move |_| filter(BrowseFilters { class: Some(class), ..filters() }),
+                            {format!("«{}»", class.code())}
+                        }
+                    }
+                }
+                div { class: "filter-chips",
+                    for category in QuestionCategory::ALL {
+                        button {
+                            class: "filter-chip",
+                            class: if filters.read().category == Some(category) { "selected" },
+                            onclick: move |_| {
+                                let category = (filters.read().category != Some(category)).then_some(category);
+                                filter(BrowseFilters { category, ..filters() })
+                            },
+                            CategoryBadge { category }
+                        }
+                    }
+                }
+                div { class: "filter-chips",
+                    button {
+                        class: "filter-chip",
+                        class: if filters.read().with_image { "selected" },
+                        onclick: move |_| filter(BrowseFilters { with_image: !filters.read().with_image, ..filters() }),
+                        "רק עם תמונה"
+                    }
+                    for history in [HistoryFilter::Missed, HistoryFilter::Unseen] {
+                        button {
+                            class: "filter-chip",
+                            class: if filters.read().history == Some(history) { "selected" },
+                            onclick: move |_| {
+                                let history = (filters.read().history != Some(history)).then_some(history);
+                                filter(BrowseFilters { history, ..filters() })
+                            },
+                            {history.label_he()}
+                        }
+                    }
+                    button {
+                        class: "filter-chip",
+                        class: if filters.read().with_note { "selected" },
+                        onclick: move |_| filter(BrowseFilters { with_note: !filters.read().with_note, ..filters() }),
+                        "שאלות עם הערות"
+                    }
+                }
+                if !tags.read().all().is_empty() {
+                    div { class: "filter-chips",
+                        for (tag , count) in tags.read().all().into_iter().map(|(tag, count)| (tag.to_string(), count)) {
+                            button {
+                                key: "{tag}",
+                                class: "filter-chip",
+                                class: if filters.read().tag.as_ref() == Some(&tag) { "selected" },
+                                onclick: {
+                                    let tag = tag.clone();
+                                    move |_| {
+                                        let tag = (filters.read().tag.as_ref() != Some(&tag)).then(|| tag.clone());
+                                        filter(BrowseFilters { tag, ..filters() })
+                                    }
+                                },
+                                {format!("🏷 {tag} ({count})")}
+                            }
+                        }
+                    }
+                }
+            }
+            if !filters.read().is_empty() {
+                div { class: "active-filters",
+                    {format!("מסננים: {}", filters.read().descriptions_he().join(", "))}
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| filter(BrowseFilters::default()),
+                        "נקה הכל"
+                    }
+                }
+            }
+            div { {format!("{} שאלות", questions.total)} }
+            for question in questions.questions {
+                div { key: "{question.num}", class: "browse-question",
+                    ExamQuestion {
+                        question: question.clone(),
+                        language: bank.language,
+                        show_correct_answer: true,
+                        user_selection: None,
+                        on_select: |_| {},
+                        show_license_classes: true,
+                        selected_license_class: filters.read().class,
+                        category_link: true,
+                        on_license_class_click: move |class| filter(BrowseFilters { class: Some(class), ..filters() }),
+                        show_note: true,
+                        show_history: true,
+                        show_translation: true,
+                        question_num: question.num,
+                        show_question_num: true,
+                        use_canonical_question_num: true,
+                    }
+                    Link {
+                        class: "question-link",
+                        to: Route::QuestionPage { num: question.num },
+                        "קישור לשאלה"
+                    }
+                }
+            }
+            div { class: "browse-pages",
+                button {
+                    class: "button-primary",
+                    disabled: page == 1,
+                    onclick: move |_| {
+                        nav.push(filters.read().route(page - 1));
+                    },
+                    "הקודם"
+                }
+                {format!("עמוד {} מתוך {}", page, num_pages)}
+                button {
+                    class: "button-primary",
+                    disabled: page == num_pages,
+                    onclick: move |_| {
+                        nav.push(filters.read().route(page + 1));
+                    },
+                    "הבא"
+                }
+            }
+            div {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn paging() {
+        assert_eq!(num_pages(0), 1);
+        assert_eq!(num_pages(20), 1);
+        assert_eq!(num_pages(21), 2);
+        assert_eq!(page_range(0, 1), 0..0);
+        assert_eq!(page_range(45, 0), 0..20);
+        assert_eq!(page_range(45, 2), 20..40);
+        assert_eq!(page_range(45, 3), 40..45);
+        assert_eq!(page_range(45, 9), 40..45);
+    }
+
+    #[test]
+    fn filters() {
+        let question = Question {
+            question: "0007. question".to_string(),
+            category: QuestionCategory::RoadSigns,
+            license_classes: vec![LicenseClass::B],
+            image_url: Some("sign.png".to_string()),
+            ..Question::sample(7, 0)
+        };
+        let none = HashSet::new();
+        let seven = HashSet::from([7]);
+        let no_tags = Tags::default();
+        let matches = |filters: &BrowseFilters, seen, missed| {
+            filters.matches(&question, seen, missed, &none, &no_tags)
+        };
+
+        assert!(matches(&BrowseFilters::default(), &none, &none));
+        let filters = BrowseFilters::from_query("B", "תמרורים", true, "unseen", false, "");
+        assert!(matches(&filters, &none, &none));
+        assert!(!matches(&filters, &seven, &none));
+        assert!(!matches(
+            &BrowseFilters {
+                class: Some(LicenseClass::D),
+                ..filters.clone()
+            },
+            &none,
+            &none
+        ));
+        assert!(!matches(
+            &BrowseFilters {
+                category: Some(QuestionCategory::Safety),
+                ..filters.clone()
+            },
+            &none,
+            &none
+        ));
+        let missed = BrowseFilters::from_query("", "", false, "missed", false, "");
+        assert!(!matches(&missed, &seven, &none));
+        assert!(matches(&missed, &seven, &seven));
+        // unknown values are ignored
+        assert!(BrowseFilters::from_query("Z", "x", false, "y", false, " ").is_empty());
+        let with_note = BrowseFilters::from_query("", "", false, "", true, "");
+        assert!(!with_note.matches(&question, &none, &none, &none, &no_tags));
+        assert!(with_note.matches(&question, &none, &none, &seven, &no_tags));
+        let tagged = BrowseFilters::from_query("", "", false, "", false, " Confusing");
+        assert_eq!(tagged.tag.as_deref(), Some("confusing"));
+        assert_eq!(tagged.bank_filter(), None);
+        let mut tags = Tags::default();
+        assert!(!tagged.matches(&question, &none, &none, &none, &tags));
+        tags.add(7, "confusing");
+        assert!(tagged.matches(&question, &none, &none, &none, &tags));
+    }
+
+    #[test]
+    fn filters_round_trip_through_the_route() {
+        let filters = BrowseFilters::from_query("C1", "בטיחות", true, "missed", true, "מבלבל");
+        match filters.route(3) {
+            Route::Browse {
+                class,
+                category,
+                with_image,
+                history,
+                with_note,
+                tag,
+                page,
+            } => {
+                assert_eq!(
+                    BrowseFilters::from_query(
+                        &class, &category, with_image, &history, with_note, &tag
+                    ),
+                    filters
+                );
+                assert_eq!(page, 3);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
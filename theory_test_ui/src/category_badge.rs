@@ -0,0 +1,63 @@
+//! The category of a question as a small colored badge, colored the same wherever it appears.
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::QuestionCategory;
+
+use crate::{
+    browse,
+    preferences::{use_preferences, Palette},
+};
+
+/// The accent color of `category` in `palette`. The only place the colors are decided, so a
+/// palette overrides all of them at once.
+pub fn category_color(category: QuestionCategory, palette: Palette) -> &'static str {
+    match (palette, category) {
+        (Palette::Standard, QuestionCategory::TrafficLaws) => "#4a90d9",
+        (Palette::Standard, QuestionCategory::RoadSigns) => "#d9534f",
+        (Palette::Standard, QuestionCategory::Safety) => "#f0ad4e",
+        (Palette::Standard, QuestionCategory::CarKnowledge) => "#5cb85c",
+        (Palette::Colorblind, QuestionCategory::TrafficLaws) => "#0072b2",
+        (Palette::Colorblind, QuestionCategory::RoadSigns) => "#d55e00",
+        (Palette::Colorblind, QuestionCategory::Safety) => "#e69f00",
+        (Palette::Colorblind, QuestionCategory::CarKnowledge) => "#009e73",
+    }
+}
+
+/// The badge of `category`. A `clickable` badge opens the browse page filtered to the category,
+/// which it never should while answering an exam.
+#[component]
+pub fn CategoryBadge(category: QuestionCategory, #[props(default)] clickable: bool) -> Element {
+    let palette = use_preferences().read().category_palette;
+    let style = format!("--category-color: {}", category_color(category, palette));
+
+    rsx! {
+        if clickable {
+            Link {
+                class: "category-badge clickable",
+                style,
+                title: "הצג את כל השאלות בקטגוריה",
+                to: browse::category_route(category),
+                {category.as_str_he()}
+            }
+        } else {
+            span { class: "category-badge", style, {category.as_str_he()} }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn distinct_colors() {
+        for palette in Palette::ALL {
+            let colors = QuestionCategory::ALL
+                .into_iter()
+                .map(|category| category_color(category, palette))
+                .collect::<HashSet<_>>();
+            assert_eq!(colors.len(), QuestionCategory::ALL.len(), "{palette:?}");
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! A printable certificate of a passed real exam simulation, drawn as an SVG like the result
+//! card. It says plainly that it is a practice simulation rather than an official document, and
+//! has a verification code which reproduces the exact exam, to check what it claims.
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use theory_test_core::time::{civil_from_days, format_duration, local_day};
+use theory_test_parser::question_parser::LicenseClass;
+
+use crate::{
+    backup::download,
+    bank::use_bank,
+    exam_state::ExamResult,
+    result_card::{escape, rasterize, APP_NAME, FONT, MUTED, PASSED},
+    timer,
+    toast::use_toast,
+};
+
+const WIDTH: u32 = 1400;
+const HEIGHT: u32 = 990;
+/// A white page, so it prints well.
+const PAPER: &str = "#ffffff";
+const INK: &str = "#1a1a1a";
+const NOTICE: &str = "#fff4d6";
+const FILE_NAME: &str = "theory-test-certificate";
+
+/// What a certificate shows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Certificate {
+    pub name: String,
+    /// The local day the exam was graded, as a number of days since the unix epoch.
+    pub day: i64,
+    pub correct: usize,
+    pub num_questions: usize,
+    pub class: LicenseClass,
+    pub elapsed: Duration,
+    pub bank_hash: u64,
+    /// The id of the spec the exam was generated from, see [`ExamSpec::id`].
+    ///
+    /// [`ExamSpec::id`]: theory_test_parser::exam::ExamSpec::id
+    pub exam_id: String,
+}
+
+impl Certificate {
+    /// The id of the exam, which generates it again along with its seed, and the hash of the bank
+    /// it was generated from, e.g. "B.30.all.o.99/00000000075bcd15".
+    pub fn verification_code(&self) -> String {
+        format!("{}/{:016x}", self.exam_id, self.bank_hash)
+    }
+}
+
+/// The certificate as an SVG document of the proportions of an A4 page on its side.
+pub fn certificate_svg(certificate: &Certificate) -> String {
+    let (year, month, day) = civil_from_days(certificate.day);
+    let class = certificate.class.code();
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" direction="rtl" xml:lang="he" font-family="{FONT}">
+<rect width="{WIDTH}" height="{HEIGHT}" fill="{PAPER}"/>
+<rect x="30" y="30" width="1340" height="930" rx="24" fill="none" stroke="{PASSED}" stroke-width="8"/>
+<text x="700" y="120" font-size="52" font-weight="bold" fill="{INK}" text-anchor="middle">אישור מעבר סימולציה של מבחן התאוריה</text>
+<text x="700" y="170" font-size="30" fill="{MUTED}" text-anchor="middle" direction="ltr" xml:lang="en">Theory Test Simulation Certificate</text>
+<rect x="150" y="200" width="1100" height="100" rx="16" fill="{NOTICE}"/>
+<text x="700" y="240" font-size="28" font-weight="bold" fill="{INK}" text-anchor="middle">סימולציית תרגול בלבד, אינו מסמך רשמי ואינו מחליף את מבחן התאוריה</text>
+<text x="700" y="282" font-size="26" fill="{INK}" text-anchor="middle" direction="ltr" xml:lang="en">Practice simulation only, not an official document nor a substitute for the theory test</text>
+<text x="700" y="390" font-size="32" fill="{MUTED}" text-anchor="middle">מאשרים כי</text>
+<text x="700" y="470" font-size="64" font-weight="bold" fill="{INK}" text-anchor="middle">{}</text>
+<text x="700" y="540" font-size="32" fill="{INK}" text-anchor="middle">עבר/ה סימולציה של מבחן התאוריה לדרגה {class}</text>
+<text x="700" y="582" font-size="26" fill="{MUTED}" text-anchor="middle" direction="ltr" xml:lang="en">passed a theory test simulation for license class {class}</text>
+"#,
+        escape(&certificate.name),
+    );
+    let details = [
+        (
+            "ציון",
+            "Score",
+            format!("{}/{}", certificate.correct, certificate.num_questions),
+        ),
+        ("תאריך", "Date", format!("{day}/{month}/{year}")),
+        (
+            "משך המבחן",
+            "Duration",
+            format_duration(certificate.elapsed),
+        ),
+        (
+            "גרסת המאגר",
+            "Bank version",
+            format!("{:016x}", certificate.bank_hash),
+        ),
+    ];
+    // laid out from the right, the direction they are read in
+    for (idx, (label_he, label_en, value)) in details.iter().enumerate() {
+        let x = 1175 - idx * 316;
+        svg += &format!(
+            r#"<text x="{x}" y="680" font-size="26" fill="{MUTED}" text-anchor="middle">{label_he} · <tspan direction="ltr" xml:lang="en">{label_en}</tspan></text>
+<text x="{x}" y="730" font-size="36" font-weight="bold" fill="{INK}" text-anchor="middle" direction="ltr" unicode-bidi="embed">{}</text>
+"#,
+            escape(value),
+        );
+    }
+    svg += &format!(
+        r#"<text x="700" y="830" font-size="24" fill="{MUTED}" text-anchor="middle">קוד האימות משחזר את אותו המבחן בדיוק, כדי לבדוק את הציון</text>
+<text x="700" y="870" font-size="26" fill="{INK}" text-anchor="middle" direction="ltr" unicode-bidi="embed" xml:lang="en">Verification code: {}</text>
+<text x="700" y="930" font-size="22" fill="{MUTED}" text-anchor="middle">{}</text>
+</svg>
+"#,
+        escape(&certificate.verification_code()),
+        escape(APP_NAME),
+    );
+    svg
+}
+
+/// Prints only the certificate, out of the whole page.
+const PRINT_SCRIPT: &str = r#"
+document.body.classList.add("printing-certificate");
+window.print();
+document.body.classList.remove("printing-certificate");
+"#;
+
+/// A "הפק אישור" button for a passed real exam, asking for the name to put on the certificate,
+/// then showing it with its print and downloads.
+#[component]
+pub fn CertificateButton(result: ExamResult, exam_id: String) -> Element {
+    let bank = use_bank();
+    let bank_hash = bank.content_hash;
+    let mut toasts = use_toast();
+    let mut asking = use_signal(|| false);
+    let mut name = use_signal(String::new);
+    // the SVG of the certificate and the data URL of its PNG
+    let mut created = use_signal(|| None::<(String, String)>);
+    let class = result.rules.map(|rules| rules.class).unwrap_or(bank.class);
+
+    rsx! {
+        button {
+            class: "button-primary",
+            onclick: move |_| asking.toggle(),
+            "הפק אישור"
+        }
+        if asking() {
+            div { class: "certificate-form",
+                label {
+                    "השם שיופיע באישור "
+                    input {
+                        r#type: "text",
+                        value: name(),
+                        oninput: move |e| name.set(e.value()),
+                    }
+                }
+                button {
+                    class: "button-primary",
+                    disabled: name.read().trim().is_empty(),
+                    onclick: move |_| {
+                        let name = name.read().trim().to_string();
+                        let (correct, num_questions) = (result.correct(), result.num_questions());
+                        let elapsed = result.elapsed;
+                        let exam_id = exam_id.clone();
+                        async move {
+                            let utc_offset = timer::utc_offset_secs().await;
+                            let certificate = Certificate {
+                                name,
+                                day: local_day(timer::now(), utc_offset),
+                                correct,
+                                num_questions,
+                                class,
+                                elapsed,
+                                bank_hash,
+                                exam_id,
+                            };
+                            let svg = certificate_svg(&certificate);
+                            match rasterize(svg.clone()).await {
+                                Some((png, _)) => {
+                                    created.set(Some((svg, png)));
+                                    asking.set(false);
+                                }
+                                None => toasts.error("לא ניתן ליצור את האישור"),
+                            }
+                        }
+                    },
+                    "צור אישור"
+                }
+            }
+        }
+        if let Some((svg, png)) = created() {
+            div { class: "certificate",
+                div { class: "certificate-image", dangerous_inner_html: svg.clone() }
+                div { class: "no-print",
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| {
+                            document::eval(PRINT_SCRIPT);
+                        },
+                        "הדפס"
+                    }
+                    a {
+                        class: "button-primary",
+                        href: "{png}",
+                        download: "{FILE_NAME}.png",
+                        "הורדת PNG"
+                    }
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| download(&format!("{FILE_NAME}.svg"), "image/svg+xml", &svg),
+                        "הורדת SVG"
+                    }
+                    button { class: "button-primary", onclick: move |_| created.set(None), "סגור" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use theory_test_parser::exam::ExamSpec;
+
+    use super::*;
+
+    #[test]
+    fn certificate() {
+        let spec = ExamSpec::official(LicenseClass::C, 99);
+        let certificate = Certificate {
+            name: "ישראל <ישראלי> & \"בנו\"".to_string(),
+            // 2026-10-17
+            day: 20_743,
+            correct: 28,
+            num_questions: 30,
+            class: LicenseClass::C,
+            elapsed: Duration::from_secs(23 * 60 + 5),
+            bank_hash: 123_456_789,
+            exam_id: spec.id(),
+        };
+        let code = certificate.verification_code();
+        assert_eq!(code, "C.30.all.o.99/00000000075bcd15");
+        // the code generates the same exam again
+        let (id, _) = code.split_once('/').unwrap();
+        assert_eq!(ExamSpec::from_id(id), Some(spec));
+
+        let svg = certificate_svg(&certificate);
+        assert!(svg.contains(">ישראל &lt;ישראלי&gt; &amp; &quot;בנו&quot;<"));
+        assert!(svg.contains("אינו מסמך רשמי"));
+        assert!(svg.contains("not an official document"));
+        assert!(svg.contains("לדרגה C<"));
+        for value in ["28/30", "17/10/2026", "23:05", "00000000075bcd15"] {
+            assert!(svg.contains(&format!(">{value}<")), "{value}");
+        }
+        assert!(svg.contains(&format!("Verification code: {code}<")));
+    }
+}
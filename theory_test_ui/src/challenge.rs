@@ -0,0 +1,141 @@
+//! Encoding of challenge links, i.e. links to an exam built from an explicit list of questions.
+//!
+//! A list of official question numbers has two textual forms:
+//! - the readable comma form, e.g. `0862,0667,1203`
+//! - a compact form, `~` followed by the url-safe base64 of the numbers encoded as varints.
+//!
+//! [`encode`] picks whichever is shorter once placed in a url, [`decode`] accepts both.
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use thiserror::Error;
+
+const COMPACT_PREFIX: char = '~';
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DecodeError {
+    #[error("לא נבחרו שאלות")]
+    Empty,
+    #[error("מספר שאלה לא תקין: {0}")]
+    InvalidNumber(String),
+    #[error("קוד האתגר פגום")]
+    Malformed,
+}
+
+/// Encode question numbers into the shortest form accepted by [`decode`].
+pub fn encode(nums: &[usize]) -> String {
+    let comma = encode_comma(nums);
+    let compact = encode_compact(nums);
+    // commas are percent encoded in the url, so they cost 3 characters each
+    let comma_url_len = comma.len() + 2 * nums.len().saturating_sub(1);
+    if compact.len() < comma_url_len {
+        compact
+    } else {
+        comma
+    }
+}
+
+fn encode_comma(nums: &[usize]) -> String {
+    nums.iter()
+        .map(|num| format!("{:04}", num))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn encode_compact(nums: &[usize]) -> String {
+    let mut bytes = Vec::with_capacity(nums.len() * 2);
+    for &num in nums {
+        let mut num = num as u64;
+        loop {
+            let byte = (num & 0x7f) as u8;
+            num >>= 7;
+            if num == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+    format!("{}{}", COMPACT_PREFIX, URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// Decode the question numbers of a challenge, keeping their order.
+pub fn decode(qs: &str) -> Result<Vec<usize>, DecodeError> {
+    let qs = qs.trim();
+    let nums = if let Some(compact) = qs.strip_prefix(COMPACT_PREFIX) {
+        decode_compact(compact)?
+    } else {
+        qs.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                s.parse()
+                    .map_err(|_| DecodeError::InvalidNumber(s.to_string()))
+            })
+            .collect::<Result<Vec<usize>, _>>()?
+    };
+    if nums.is_empty() {
+        return Err(DecodeError::Empty);
+    }
+    Ok(nums)
+}
+
+fn decode_compact(compact: &str) -> Result<Vec<usize>, DecodeError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(compact)
+        .map_err(|_| DecodeError::Malformed)?;
+    let mut nums = Vec::new();
+    let mut num: u64 = 0;
+    let mut shift = 0;
+    for byte in bytes.iter() {
+        if shift > 28 {
+            return Err(DecodeError::Malformed);
+        }
+        num |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            nums.push(num as usize);
+            num = 0;
+            shift = 0;
+        } else {
+            shift += 7;
+        }
+    }
+    // the last varint was cut in the middle
+    if shift != 0 {
+        return Err(DecodeError::Malformed);
+    }
+    Ok(nums)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let nums = vec![862, 667, 1203, 1, 1802, 30, 4];
+        assert_eq!(decode(&encode(&nums)).unwrap(), nums);
+        assert_eq!(decode(&encode_comma(&nums)).unwrap(), nums);
+        assert_eq!(decode(&encode_compact(&nums)).unwrap(), nums);
+        assert_eq!(decode("0862,0667,1203").unwrap(), vec![862, 667, 1203]);
+    }
+
+    #[test]
+    fn compact_is_shorter_for_many_questions() {
+        let nums = (1..=15).map(|n| n * 97).collect::<Vec<_>>();
+        let encoded = encode(&nums);
+        assert!(encoded.starts_with(COMPACT_PREFIX));
+        assert!(encoded.len() < 45);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(decode(""), Err(DecodeError::Empty));
+        assert_eq!(decode("~"), Err(DecodeError::Empty));
+        assert_eq!(
+            decode("0862,abc"),
+            Err(DecodeError::InvalidNumber("abc".to_string()))
+        );
+        assert_eq!(decode("~!!"), Err(DecodeError::Malformed));
+        // a single byte with the continuation bit set
+        assert_eq!(decode("~gA"), Err(DecodeError::Malformed));
+    }
+}
@@ -0,0 +1,60 @@
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::Question;
+
+use crate::{
+    exam_state::ExamResult, preferences::QuestionNumbering, results_grid::scroll_to_question,
+};
+
+/// A 2×2 breakdown of confidence against correctness, listing the questions which were
+/// answered wrong with confidence. Renders nothing if no confidence was marked.
+#[component]
+pub fn ConfidenceReport(
+    questions: ReadOnlySignal<Vec<Question>>,
+    result: ExamResult,
+    numbering: QuestionNumbering,
+) -> Element {
+    let breakdown = result.confidence_breakdown();
+    if breakdown.total() == 0 {
+        return rsx! {};
+    }
+    let confident_wrong = result.confident_wrong();
+
+    rsx! {
+        div { class: "confidence-report",
+            table {
+                tr {
+                    th {}
+                    th { "נכון" }
+                    th { "שגוי" }
+                }
+                tr {
+                    th { "בטוח" }
+                    td { "{breakdown.confident_correct}" }
+                    td { class: "confident-wrong", "{breakdown.confident_wrong}" }
+                }
+                tr {
+                    th { "ניחוש" }
+                    td { "{breakdown.guess_correct}" }
+                    td { "{breakdown.guess_wrong}" }
+                }
+            }
+            if !confident_wrong.is_empty() {
+                div { class: "confident-wrong-list",
+                    "שאלות שהיית בטוח בהן וטעית: "
+                    for idx in confident_wrong {
+                        a {
+                            href: "#",
+                            title: questions.read()[idx].question.clone(),
+                            onclick: move |e| {
+                                e.prevent_default();
+                                scroll_to_question(idx);
+                            },
+                            {numbering.label(idx, &questions.read()[idx])}
+                        }
+                        " "
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Import of the answers given in another app from a CSV file, choosing which of its columns hold
+//! what.
+use dioxus::prelude::*;
+use theory_test_core::csv_import::{csv_headers, Column, CsvMapping};
+
+use crate::{bank::use_bank, history::use_history, timer, toast::use_toast};
+
+/// A file which was chosen, until its columns are confirmed.
+#[derive(Clone, PartialEq)]
+struct ChosenFile {
+    text: String,
+    headers: Vec<String>,
+}
+
+/// A select of one of the columns of `headers`.
+#[component]
+fn ColumnSelect(
+    label: &'static str,
+    headers: Vec<String>,
+    has_header: bool,
+    selected: Signal<usize>,
+) -> Element {
+    rsx! {
+        label {
+            "{label}: "
+            select {
+                onchange: move |e| {
+                    if let Ok(idx) = e.value().parse() {
+                        selected.set(idx);
+                    }
+                },
+                for (idx , header) in headers.iter().enumerate() {
+                    option { value: "{idx}", selected: idx == selected(),
+                        if has_header {
+                            {header.clone()}
+                        } else {
+                            {format!("עמודה {} ({})", idx + 1, header)}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A button to choose a CSV file of answers, and the choice of its columns before importing it.
+#[component]
+pub fn CsvImportAction() -> Element {
+    let bank = use_bank();
+    let mut history = use_history();
+    let mut toasts = use_toast();
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let mut chosen = use_signal(|| None::<ChosenFile>);
+    let mut number = use_signal(|| 0);
+    let mut correct = use_signal(|| 1);
+    let mut timestamp = use_signal(|| 2);
+    let mut has_header = use_signal(|| true);
+
+    rsx! {
+        label { class: "button-primary",
+            "ייבוא היסטוריה מקובץ CSV"
+            input {
+                r#type: "file",
+                accept: ".csv,text/csv",
+                hidden: true,
+                onchange: move |e| async move {
+                    let Some(files) = e.files() else {
+                        return;
+                    };
+                    let Some(name) = files.files().into_iter().next() else {
+                        return;
+                    };
+                    let text = files.read_file_to_string(&name).await.unwrap_or_default();
+                    let headers = csv_headers(text.as_bytes());
+                    let guessed = CsvMapping::guess(&headers);
+                    let index = |column: Option<&Column>, default| match column {
+                        Some(Column::Index(idx)) => *idx,
+                        _ => default,
+                    };
+                    number.set(index(guessed.as_ref().map(|m| &m.number), 0));
+                    correct.set(index(guessed.as_ref().map(|m| &m.correct), 1));
+                    timestamp.set(index(guessed.as_ref().map(|m| &m.timestamp), 2));
+                    has_header.set(true);
+                    chosen.set(Some(ChosenFile { text, headers }));
+                },
+            }
+        }
+        if let Some(file) = chosen() {
+            div { class: "csv-import",
+                ColumnSelect {
+                    label: "מספר השאלה",
+                    headers: file.headers.clone(),
+                    has_header: has_header(),
+                    selected: number,
+                }
+                ColumnSelect {
+                    label: "תשובה נכונה",
+                    headers: file.headers.clone(),
+                    has_header: has_header(),
+                    selected: correct,
+                }
+                ColumnSelect {
+                    label: "מועד התשובה",
+                    headers: file.headers.clone(),
+                    has_header: has_header(),
+                    selected: timestamp,
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: has_header(),
+                        oninput: move |e| has_header.set(e.checked()),
+                    }
+                    "השורה הראשונה היא כותרת"
+                }
+                div {
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| {
+                            let mapping = CsvMapping {
+                                number: Column::Index(number()),
+                                correct: Column::Index(correct()),
+                                timestamp: Column::Index(timestamp()),
+                                has_header: has_header(),
+                            };
+                            let imported = history
+                                .write()
+                                .import_csv(
+                                    file.text.as_bytes(),
+                                    &mapping,
+                                    &bank.questions,
+                                    utc_offset().unwrap_or(0),
+                                );
+                            match imported {
+                                Ok(report) => toasts.success(report.description_he()),
+                                Err(e) => toasts.error(e.to_string()),
+                            }
+                            chosen.set(None);
+                        },
+                        "ייבוא"
+                    }
+                    button { class: "button-primary", onclick: move |_| chosen.set(None), "ביטול" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! A question of the day which is the same for everyone, without a server: it is picked by the
+//! local date and the content of the bank.
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bank::use_bank,
+    exam_state::{PassThreshold, QuestionOutcome, REAL_EXAM_NUM_QUESTIONS},
+    exam_store::use_exam_store,
+    history::{use_history, Attempt, QuestionAttempt},
+    storage, timer, ExamKind, ExamQuestion, Route,
+};
+
+const STORAGE_KEY: &str = "daily-question";
+
+/// The answer given to the question of a day.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct DailyAnswer {
+    day: i64,
+    num: usize,
+    selection: usize,
+}
+
+/// The index in a pool of `pool_len` questions of the question of `day`.
+fn daily_question_index(bank_hash: u64, day: i64, pool_len: usize) -> usize {
+    // mix the day into the hash so consecutive days don't pick neighbouring questions
+    let mut hash = bank_hash ^ (day as u64);
+    hash = (hash ^ (hash >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash = (hash ^ (hash >> 33)).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+    (hash % pool_len.max(1) as u64) as usize
+}
+
+/// A card with the question of the day, which can be answered once a day.
+#[component]
+pub fn DailyQuestion() -> Element {
+    let bank = use_bank();
+    let mut history = use_history();
+    let mut exam_store = use_exam_store();
+    let nav = navigator();
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let mut answer = use_signal(|| None::<DailyAnswer>);
+    let stored = use_resource(|| storage::load::<DailyAnswer>(STORAGE_KEY));
+    let (Some(utc_offset), Some(stored)) = (utc_offset(), stored()) else {
+        return rsx! {};
+    };
+    let pool = bank.class_questions();
+    if pool.is_empty() {
+        return rsx! {};
+    }
+    let day = timer::local_day(timer::now(), utc_offset);
+    let question = pool[daily_question_index(bank.content_hash, day, pool.len())].clone();
+    let num = question.num;
+    // answered today, either in this visit or in an earlier one
+    let today = answer()
+        .or(stored)
+        .filter(|answer| answer.day == day && answer.num == num);
+    let category = question.category;
+    let correct_answer = question.answers.correct_answer;
+
+    rsx! {
+        div { class: "daily-question",
+            h2 { "שאלת היום" }
+            ExamQuestion {
+                question,
+                language: bank.language,
+                show_correct_answer: today.is_some(),
+                user_selection: today.map(|answer| answer.selection),
+                on_select: move |selection| {
+                    if today.is_some() {
+                        return;
+                    }
+                    let today = DailyAnswer { day, num, selection };
+                    storage::save(STORAGE_KEY, &today);
+                    answer.set(Some(today));
+                    let now = timer::now();
+                    let outcome = if selection == correct_answer {
+                        QuestionOutcome::Correct
+                    } else {
+                        QuestionOutcome::Wrong
+                    };
+                    history
+                        .write()
+                        .record(Attempt {
+                            started_at: now,
+                            graded_at: now,
+                            kind: ExamKind::DailyQuestion,
+                            strict: false,
+                            abandoned: false,
+                            questions: vec![QuestionAttempt::new(num, outcome)],
+                            duration: None,
+                            pass_threshold: PassThreshold::Official,
+                            rules: None,
+                        });
+                },
+                question_num: 1,
+                show_question_num: true,
+                use_canonical_question_num: true,
+            }
+            if today.is_some() {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        let route = Route::PracticeExam {
+                            num_questions: REAL_EXAM_NUM_QUESTIONS,
+                            feedback: false,
+                            category: category.as_str_he().to_string(),
+                            exclude: String::new(),
+                            tag: String::new(),
+                        };
+                        exam_store.forget(&route);
+                        nav.push(route);
+                    },
+                    {format!("מבחן תרגול ב{}", category.as_str_he())}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn daily_index() {
+        let index = |day| daily_question_index(42, day, 1000);
+        // the same question for the whole day
+        assert_eq!(index(20_000), index(20_000));
+        // and a different one (almost always) the next
+        assert_ne!(index(20_000), index(20_001));
+        assert!((0..365).all(|day| index(day) < 1000));
+        // another bank picks another question
+        assert_ne!(daily_question_index(43, 20_000, 1000), index(20_000));
+        assert_eq!(daily_question_index(42, 20_000, 0), 0);
+    }
+}
@@ -0,0 +1,326 @@
+//! A drill which gives each question a short countdown of its own, to train quick recognition.
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use rand::SeedableRng;
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+use crate::{
+    bank::use_bank,
+    exam_state::{PassThreshold, QuestionOutcome},
+    generate_questions,
+    history::{use_history, Attempt, QuestionAttempt},
+    preferences::use_preferences,
+    timer, ExamKind, ExamQuestion, QuestionSelection, Route,
+};
+
+pub const DEFAULT_SECONDS_PER_QUESTION: u64 = 20;
+pub const DEFAULT_NUM_QUESTIONS: usize = 20;
+/// How long the correct answer is shown before moving to the next question.
+const REVEAL_DURATION: Duration = Duration::from_millis(1500);
+const TICK: Duration = Duration::from_millis(100);
+
+/// The state of a drill, advanced by [`DrillState::tick`] as time passes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DrillState {
+    questions: Vec<Question>,
+    selections: Vec<Option<usize>>,
+    current: usize,
+    per_question: Duration,
+    /// Time left to answer the current question, or to show its correct answer once it is
+    /// revealed.
+    remaining: Duration,
+    revealed: bool,
+}
+
+impl DrillState {
+    pub fn new(questions: Vec<Question>, per_question: Duration) -> Self {
+        let selections = vec![None; questions.len()];
+        Self {
+            questions,
+            selections,
+            current: 0,
+            per_question,
+            remaining: per_question,
+            revealed: false,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.questions.len()
+    }
+
+    pub fn current(&self) -> Option<(usize, &Question)> {
+        self.questions.get(self.current).map(|q| (self.current, q))
+    }
+
+    pub fn selection(&self, question: usize) -> Option<usize> {
+        self.selections.get(question).copied().flatten()
+    }
+
+    pub fn is_revealed(&self) -> bool {
+        self.revealed
+    }
+
+    /// Time left to answer the current question.
+    pub fn remaining(&self) -> Duration {
+        if self.revealed {
+            Duration::ZERO
+        } else {
+            self.remaining
+        }
+    }
+
+    /// Answer the current question, which locks and reveals it.
+    pub fn answer(&mut self, answer: usize) -> bool {
+        if self.revealed || self.is_finished() {
+            return false;
+        }
+        self.selections[self.current] = Some(answer);
+        self.reveal();
+        true
+    }
+
+    fn reveal(&mut self) {
+        self.revealed = true;
+        self.remaining = REVEAL_DURATION;
+    }
+
+    /// Move to the next question, without waiting for the correct answer to stop being shown.
+    pub fn advance(&mut self) {
+        if self.is_finished() {
+            return;
+        }
+        self.current += 1;
+        self.remaining = self.per_question;
+        self.revealed = false;
+    }
+
+    /// Let time pass. A question whose time runs out is locked as unanswered, and a revealed
+    /// question is advanced from once it was shown for long enough.
+    pub fn tick(&mut self, elapsed: Duration) {
+        if self.is_finished() {
+            return;
+        }
+        self.remaining = self.remaining.saturating_sub(elapsed);
+        if !self.remaining.is_zero() {
+            return;
+        }
+        if self.revealed {
+            self.advance();
+        } else {
+            self.reveal();
+        }
+    }
+
+    pub fn outcomes(&self) -> Vec<QuestionOutcome> {
+        self.questions
+            .iter()
+            .zip(self.selections.iter())
+            .map(|(question, selection)| match selection {
+                None => QuestionOutcome::Unanswered,
+                Some(s) if *s == question.answers.correct_answer => QuestionOutcome::Correct,
+                Some(_) => QuestionOutcome::Wrong,
+            })
+            .collect()
+    }
+
+    /// The number of correct answers, and the number of questions done so far.
+    pub fn score(&self) -> (usize, usize) {
+        let done = if self.revealed {
+            self.current + 1
+        } else {
+            self.current
+        };
+        let outcomes = self.outcomes();
+        let correct = outcomes[..done.min(outcomes.len())]
+            .iter()
+            .filter(|o| **o == QuestionOutcome::Correct)
+            .count();
+        (correct, done.min(outcomes.len()))
+    }
+}
+
+#[component]
+pub fn Drill(category: String, seconds: u64, num_questions: usize) -> Element {
+    let bank = use_bank();
+    let mut history = use_history();
+    let nav = navigator();
+    let category = QuestionCategory::from_str_he(&category);
+    let seconds = if seconds == 0 {
+        DEFAULT_SECONDS_PER_QUESTION
+    } else {
+        seconds
+    };
+    let num_questions = if num_questions == 0 {
+        DEFAULT_NUM_QUESTIONS
+    } else {
+        num_questions
+    };
+    let preferences = use_preferences();
+    let mut drill = use_signal(|| {
+        let mut pool = bank.class_questions();
+        if let Some(category) = category {
+            pool.retain(|q| q.category == category);
+        }
+        let questions = generate_questions(
+            &pool,
+            QuestionSelection::Random { num_questions },
+            preferences.peek().question_order,
+            &mut rand_pcg::Pcg64::from_os_rng(),
+        );
+        DrillState::new(questions, Duration::from_secs(seconds))
+    });
+    let started_at = use_hook(timer::now);
+    use_future(move || async move {
+        let mut last = timer::now();
+        while !drill.peek().is_finished() {
+            timer::sleep(TICK).await;
+            let now = timer::now();
+            // the countdown pauses while the page is hidden instead of expiring everything
+            let hidden = document::eval("return document.hidden;")
+                .join::<bool>()
+                .await
+                .unwrap_or(false);
+            if !hidden {
+                drill.write().tick(now.saturating_sub(last));
+            }
+            last = now;
+        }
+    });
+    let finished = drill.read().is_finished();
+    use_effect(use_reactive!(|(finished)| {
+        if !finished {
+            return;
+        }
+        let drill = drill.peek();
+        let now = timer::now();
+        history.write().record(Attempt {
+            started_at,
+            graded_at: now,
+            kind: ExamKind::Drill,
+            strict: false,
+            abandoned: false,
+            questions: drill
+                .questions
+                .iter()
+                .zip(drill.outcomes())
+                .map(|(question, outcome)| QuestionAttempt::new(question.num, outcome))
+                .collect(),
+            duration: Some(now.saturating_sub(started_at)),
+            pass_threshold: PassThreshold::Official,
+            rules: None,
+        });
+    }));
+    let (correct, done) = drill.read().score();
+    let total = drill.read().questions.len();
+    let current = drill
+        .read()
+        .current()
+        .map(|(idx, question)| (idx, question.clone()));
+
+    rsx! {
+        div { class: "drill",
+            div { class: "drill-status",
+                div { {format!("ניקוד: {}/{}", correct, done)} }
+                div { {format!("שאלה {} מתוך {}", (done + 1).min(total), total)} }
+                if !finished {
+                    div {
+                        class: "drill-countdown",
+                        class: if drill.read().remaining() <= Duration::from_secs(5) { "urgent" },
+                        {format!("{}", drill.read().remaining().as_secs_f32().ceil())}
+                    }
+                }
+            }
+            if let Some((idx, question)) = current {
+                ExamQuestion {
+                    key: "{idx}",
+                    question,
+                    language: bank.language,
+                    show_correct_answer: drill.read().is_revealed(),
+                    user_selection: drill.read().selection(idx),
+                    on_select: move |answer| {
+                        drill.write().answer(answer);
+                    },
+                    question_num: idx + 1,
+                    show_question_num: true,
+                    use_canonical_question_num: false,
+                    auto_read: preferences.read().read_aloud,
+                }
+                if drill.read().is_revealed() {
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| drill.write().advance(),
+                        "הבא"
+                    }
+                }
+            } else {
+                div { class: "drill-summary",
+                    h2 { "סיום התרגול" }
+                    div { {format!("ענית נכון על {} מתוך {} שאלות", correct, total)} }
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| {
+                            nav.push(Route::MainPage);
+                        },
+                        "חזרה לדף הראשי"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn drill(num_questions: usize) -> DrillState {
+        let questions = (1..=num_questions)
+            .map(|num| Question {
+                category: QuestionCategory::RoadSigns,
+                ..Question::sample(num, 0)
+            })
+            .collect();
+        DrillState::new(questions, Duration::from_secs(20))
+    }
+
+    #[test]
+    fn expiry_locks_and_advances() {
+        let mut drill = drill(2);
+        drill.tick(Duration::from_secs(19));
+        assert!(!drill.is_revealed());
+        drill.tick(Duration::from_secs(1));
+        // locked as unanswered
+        assert!(drill.is_revealed());
+        assert!(!drill.answer(0));
+        drill.tick(REVEAL_DURATION);
+        assert_eq!(drill.current().map(|(i, _)| i), Some(1));
+        assert_eq!(drill.remaining(), Duration::from_secs(20));
+        assert_eq!(drill.outcomes()[0], QuestionOutcome::Unanswered);
+    }
+
+    #[test]
+    fn advancing_early_resets_the_countdown() {
+        let mut drill = drill(3);
+        drill.tick(Duration::from_secs(15));
+        assert!(drill.answer(0));
+        assert_eq!(drill.score(), (1, 1));
+        drill.advance();
+        assert_eq!(drill.remaining(), Duration::from_secs(20));
+        drill.tick(Duration::from_secs(10));
+        assert!(drill.answer(1));
+        drill.tick(REVEAL_DURATION);
+        assert_eq!(drill.current().map(|(i, _)| i), Some(2));
+        assert_eq!(drill.remaining(), Duration::from_secs(20));
+        assert_eq!(drill.score(), (1, 2));
+        drill.answer(0);
+        drill.advance();
+        assert!(drill.is_finished());
+        assert_eq!(drill.score(), (2, 3));
+        // nothing changes once finished
+        drill.tick(Duration::from_secs(100));
+        drill.advance();
+        assert_eq!(drill.score(), (2, 3));
+    }
+}
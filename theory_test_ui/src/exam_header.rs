@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::{
+    i18n::tr,
+    preferences::{QuestionNumbering, TextZoom},
+    sound,
+    time_warnings::{num_reached, TimeWarning},
+    timer,
+};
+
+/// Collapse the header while scrolling down and expand it back on scroll up.
+/// The listener removes itself once the header is gone.
+const COLLAPSE_ON_SCROLL_JS: &str = r#"
+    let lastY = window.scrollY;
+    const listener = () => {
+        const header = document.getElementById("exam-header");
+        if (!header) {
+            window.removeEventListener('scroll', listener);
+            return;
+        }
+        const y = window.scrollY;
+        if (Math.abs(y - lastY) > 10) {
+            header.classList.toggle('collapsed', y > lastY && y > header.offsetHeight);
+            lastY = y;
+        }
+    };
+    window.addEventListener('scroll', listener, { passive: true });
+"#;
+
+/// Send "undo" on Ctrl+Z and "redo" on Ctrl+Shift+Z or Ctrl+Y, except while typing, where they
+/// undo the typing. The listener removes itself once the header is gone.
+const UNDO_SHORTCUTS_JS: &str = r#"
+    const listener = (e) => {
+        if (!document.getElementById("exam-header")) {
+            window.removeEventListener('keydown', listener);
+            return;
+        }
+        if (!(e.ctrlKey || e.metaKey) || e.altKey || e.target.closest('input[type=text], textarea, [contenteditable]')) {
+            return;
+        }
+        if (e.code === 'KeyZ') {
+            e.preventDefault();
+            dioxus.send(e.shiftKey ? "redo" : "undo");
+        } else if (e.code === 'KeyY' && !e.shiftKey) {
+            e.preventDefault();
+            dioxus.send("redo");
+        }
+    };
+    window.addEventListener('keydown', listener);
+    await new Promise(() => {});
+"#;
+
+/// A slim header which sticks to the top of the exam, showing its progress and
+/// allowing it to be submitted from anywhere.
+#[component]
+pub fn ExamHeader(
+    num_answered: usize,
+    num_questions: usize,
+    num_flagged: usize,
+    /// When the time of the exam runs out, as a duration since the unix epoch.
+    deadline: Option<Duration>,
+    /// Warnings as the time runs low, ordered from the earliest.
+    #[props(default)]
+    time_warnings: Vec<TimeWarning>,
+    graded: bool,
+    /// Whether the exam can be submitted before its time runs out.
+    #[props(default = true)]
+    can_submit: bool,
+    /// With immediate feedback, how many more mistakes can be made before failing, `None` once
+    /// failed.
+    mistakes_left: Option<Option<usize>>,
+    /// What the questions of the exam were drawn from, when not from the whole bank.
+    filter_description: Option<String>,
+    /// The name of the question bank the exam is from.
+    bank_name: &'static str,
+    /// Play a sound when the time runs out.
+    #[props(default)]
+    sounds: bool,
+    numbering: QuestionNumbering,
+    on_numbering_change: EventHandler<QuestionNumbering>,
+    zoom: TextZoom,
+    on_zoom_change: EventHandler<TextZoom>,
+    on_submit: EventHandler<()>,
+    /// Called once the time of the exam runs out.
+    on_time_up: EventHandler<()>,
+    /// Called whenever the time reaches one of `time_warnings`.
+    on_time_warning: Option<EventHandler<()>>,
+    #[props(default)] can_undo: bool,
+    #[props(default)] can_redo: bool,
+    /// Undo the latest change of an answer, from a button or with Ctrl+Z. Without it there is
+    /// no undo.
+    on_undo: Option<EventHandler<()>>,
+    on_redo: Option<EventHandler<()>>,
+) -> Element {
+    let mut now = use_signal(timer::now);
+    use_future(move || async move {
+        if deadline.is_none() {
+            return;
+        }
+        loop {
+            timer::sleep(Duration::from_secs(1)).await;
+            now.set(timer::now());
+        }
+    });
+    use_effect(|| {
+        document::eval(COLLAPSE_ON_SCROLL_JS);
+    });
+    let undoable = on_undo.is_some();
+    use_future(move || async move {
+        if !undoable {
+            return;
+        }
+        let mut eval = document::eval(UNDO_SHORTCUTS_JS);
+        while let Ok(action) = eval.recv::<String>().await {
+            let handler = match action.as_str() {
+                "undo" => on_undo,
+                _ => on_redo,
+            };
+            if let Some(handler) = handler {
+                handler(());
+            }
+        }
+    });
+    let remaining = deadline.map(|deadline| deadline.saturating_sub(now()));
+    use_effect(use_reactive!(|(remaining, graded)| {
+        if remaining == Some(Duration::ZERO) && !graded {
+            if sounds {
+                sound::play(sound::Cue::TimeUp);
+            }
+            on_time_up(());
+        }
+    }));
+    let reached = remaining.map_or(0, |remaining| num_reached(&time_warnings, remaining));
+    use_effect(use_reactive!(|(reached, graded)| {
+        if reached > 0 && !graded {
+            if let Some(on_time_warning) = on_time_warning {
+                on_time_warning(());
+            }
+        }
+    }));
+    let warning_class = reached
+        .checked_sub(1)
+        .filter(|_| !graded)
+        .map_or("", |idx| time_warnings[idx].level.class());
+
+    rsx! {
+        div { id: "exam-header", class: "exam-header",
+            if let Some(remaining) = remaining {
+                div {
+                    class: "exam-timer {warning_class}",
+                    {timer::format_duration(remaining)}
+                }
+            }
+            div { class: "exam-header-details",
+                div { {tr!("answered-count", answered = num_answered, total = num_questions)} }
+                div { class: "exam-bank", {bank_name} }
+                if let Some(filter_description) = filter_description {
+                    div { class: "exam-filter", {filter_description} }
+                }
+                if let Some(mistakes_left) = mistakes_left {
+                    div {
+                        class: "mistakes-left",
+                        class: if mistakes_left.is_none() { "failed" },
+                        title: "טעויות שעוד אפשר לעשות ולעבור את המבחן",
+                        match mistakes_left {
+                            Some(left) => tr!("mistakes-left", left = left),
+                            None => "נכשלת".to_string(),
+                        }
+                    }
+                }
+                if num_flagged > 0 {
+                    div { class: "flag-badge", title: "שאלות מסומנות", {format!("🚩 {}", num_flagged)} }
+                }
+                if let Some(on_undo) = on_undo.filter(|_| !graded) {
+                    div { class: "undo-controls",
+                        button {
+                            class: "undo-button",
+                            title: "בטל את שינוי התשובה האחרון (Ctrl+Z)",
+                            disabled: !can_undo,
+                            onclick: move |_| on_undo(()),
+                            "↶"
+                        }
+                        if let Some(on_redo) = on_redo {
+                            button {
+                                class: "undo-button",
+                                title: "בצע שוב את השינוי שבוטל (Ctrl+Shift+Z)",
+                                disabled: !can_redo,
+                                onclick: move |_| on_redo(()),
+                                "↷"
+                            }
+                        }
+                    }
+                }
+                div { class: "zoom-controls",
+                    button {
+                        class: "zoom-button",
+                        title: "הקטן טקסט",
+                        disabled: !zoom.can_zoom_out(),
+                        onclick: move |_| on_zoom_change(zoom.zoom_out()),
+                        "A−"
+                    }
+                    button {
+                        class: "zoom-button",
+                        title: "הגדל טקסט",
+                        disabled: !zoom.can_zoom_in(),
+                        onclick: move |_| on_zoom_change(zoom.zoom_in()),
+                        "A+"
+                    }
+                }
+                button {
+                    class: "numbering-toggle",
+                    title: "החלף בין מספור לפי הסדר למספור הרשמי",
+                    onclick: move |_| {
+                        on_numbering_change(
+                            match numbering {
+                                QuestionNumbering::Sequential => QuestionNumbering::Canonical,
+                                QuestionNumbering::Canonical => QuestionNumbering::Sequential,
+                            },
+                        )
+                    },
+                    match numbering {
+                        QuestionNumbering::Sequential => "#1",
+                        QuestionNumbering::Canonical => "#0862",
+                    }
+                }
+                if !graded {
+                    button {
+                        class: "button-primary",
+                        disabled: !can_submit,
+                        onclick: move |_| on_submit(()),
+                        "הגש"
+                    }
+                }
+            }
+        }
+    }
+}
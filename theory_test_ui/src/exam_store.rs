@@ -0,0 +1,351 @@
+//! Exams which are in progress in the current session.
+//!
+//! Navigating away from an exam unmounts it, so its state is kept here keyed by the exam's route,
+//! which lets going back to the route restore the exam (and its scroll offset) instead of
+//! generating a new one.
+use std::{cell::Cell, collections::HashMap, rc::Rc, sync::Arc, time::Duration};
+
+use dioxus::{history::History, prelude::*};
+
+use crate::{exam_state::ExamState, Route};
+
+#[derive(Clone, Copy)]
+pub struct ExamStore(Signal<HashMap<String, ExamState>>);
+
+impl ExamStore {
+    /// Provide the store to the whole app.
+    pub fn provide() -> Self {
+        use_context_provider(|| Self(Signal::new(HashMap::new())))
+    }
+
+    pub fn get(&self, key: &str) -> Option<ExamState> {
+        self.0.peek().get(key).cloned()
+    }
+
+    /// Keep the state of the exam at `key`, only its answers without what can be undone.
+    pub fn save(&mut self, key: String, mut state: ExamState) {
+        state.clear_undo();
+        self.0.write().insert(key, state);
+    }
+
+    /// Pause the exam of a route while it isn't shown, if it is still kept.
+    pub fn pause(&mut self, key: &str, now: Duration) {
+        if let Some(state) = self.0.write().get_mut(key) {
+            state.pause(now);
+        }
+    }
+
+    /// Forget the exam of a route, so the next visit to it starts a new exam.
+    pub fn forget(&mut self, route: &Route) {
+        let key = route.to_string();
+        self.0.write().remove(&key);
+        document::eval(&format!(
+            "sessionStorage.removeItem({:?});",
+            scroll_storage_key(&key)
+        ));
+    }
+}
+
+pub fn use_exam_store() -> ExamStore {
+    use_context()
+}
+
+fn scroll_storage_key(key: &str) -> String {
+    format!("exam-scroll:{}", key)
+}
+
+/// Keep the scroll offset of the exam at `key` up to date while its route is the current one.
+pub fn track_scroll(key: &str) {
+    document::eval(&format!(
+        r#"
+        const key = {:?};
+        const path = window.location.pathname + window.location.search + window.location.hash;
+        window.__examScrollListeners ??= {{}};
+        if (window.__examScrollListeners[key]) {{
+            window.removeEventListener('scroll', window.__examScrollListeners[key]);
+        }}
+        const listener = () => {{
+            // the exam was navigated away from
+            if (window.location.pathname + window.location.search + window.location.hash !== path) {{
+                window.removeEventListener('scroll', listener);
+                delete window.__examScrollListeners[key];
+                return;
+            }}
+            sessionStorage.setItem(key, window.scrollY);
+        }};
+        window.__examScrollListeners[key] = listener;
+        window.addEventListener('scroll', listener, {{ passive: true }});
+        "#,
+        scroll_storage_key(key)
+    ));
+}
+
+/// Scroll back to the last offset recorded by [`track_scroll`].
+pub fn restore_scroll(key: &str) {
+    document::eval(&format!(
+        r#"
+        const y = sessionStorage.getItem({:?});
+        if (y !== null) {{
+            requestAnimationFrame(() => window.scrollTo(0, Number(y)));
+        }}
+        "#,
+        scroll_storage_key(key)
+    ));
+}
+
+/// What to ask before leaving an exam.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LeaveWarning {
+    /// When navigating away within the app, which keeps the exam in the store.
+    pub navigation: &'static str,
+    /// When reloading or closing the page, which loses the exam along with the store.
+    pub unload: &'static str,
+}
+
+/// What to ask before leaving an exam, `None` when it can be left without asking: once it is
+/// graded, or before anything was answered in it.
+pub fn leave_warning(state: &ExamState, strict: bool) -> Option<LeaveWarning> {
+    if state.is_graded() {
+        None
+    } else if strict {
+        // leaving is recorded even before anything was answered, however the exam is left
+        let message = "המבחן עדיין לא הוגש. יציאה ממנו תירשם בהיסטוריה כמבחן שננטש.";
+        Some(LeaveWarning {
+            navigation: message,
+            unload: message,
+        })
+    } else if state.num_answered() > 0 {
+        Some(LeaveWarning {
+            navigation: "לעזוב את המבחן? ההתקדמות תישמר, וחזרה לעמוד זה תמשיך אותו.",
+            unload: "לעזוב את המבחן? ההתקדמות לא תישמר והתשובות שסימנת יאבדו.",
+        })
+    } else {
+        None
+    }
+}
+
+/// The warning of the exam being taken, asked before leaving it in any way.
+#[derive(Clone, Default)]
+pub struct LeaveGuard(Rc<Cell<Option<LeaveWarning>>>);
+
+impl LeaveGuard {
+    /// Provide the guard to the whole app, and to the history of its router through
+    /// [`GuardedHistory`].
+    pub fn provide() -> Self {
+        use_context_provider(Self::default)
+    }
+
+    /// Ask for confirmation before leaving the current route, by following a link within the
+    /// app, navigating back or closing the page, until [`LeaveGuard::stop`] is called.
+    pub fn guard(&self, warning: LeaveWarning) {
+        self.0.set(Some(warning));
+        guard_leave(warning);
+    }
+
+    pub fn stop(&self) {
+        self.0.set(None);
+        stop_guarding_leave();
+    }
+}
+
+pub fn use_leave_guard() -> LeaveGuard {
+    use_context()
+}
+
+/// The history of the router, which asks the warning of the [`LeaveGuard`] before navigating
+/// to another route within the app, from a `Link` or a `nav.push`. Going back and forth, which
+/// the browser does before the app hears of it, is asked by [`guard_leave`].
+pub struct GuardedHistory {
+    inner: Rc<dyn History>,
+    guard: LeaveGuard,
+    /// Asks the user the warning, returning whether to leave.
+    confirm: fn(&str) -> bool,
+}
+
+impl GuardedHistory {
+    pub fn new(inner: Rc<dyn History>, guard: LeaveGuard) -> Self {
+        Self {
+            inner,
+            guard,
+            confirm,
+        }
+    }
+
+    fn may_leave_for(&self, route: &str) -> bool {
+        match self.guard.0.get() {
+            Some(warning) if route != self.inner.current_route() => {
+                (self.confirm)(warning.navigation)
+            }
+            _ => true,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn confirm(message: &str) -> bool {
+    web_sys::window()
+        .and_then(|window| window.confirm_with_message(message).ok())
+        .unwrap_or(true)
+}
+
+/// Only a browser can be asked while navigating.
+#[cfg(not(target_arch = "wasm32"))]
+fn confirm(_message: &str) -> bool {
+    true
+}
+
+impl History for GuardedHistory {
+    fn current_route(&self) -> String {
+        self.inner.current_route()
+    }
+
+    fn current_prefix(&self) -> Option<String> {
+        self.inner.current_prefix()
+    }
+
+    fn can_go_back(&self) -> bool {
+        self.inner.can_go_back()
+    }
+
+    fn go_back(&self) {
+        self.inner.go_back();
+    }
+
+    fn can_go_forward(&self) -> bool {
+        self.inner.can_go_forward()
+    }
+
+    fn go_forward(&self) {
+        self.inner.go_forward();
+    }
+
+    fn push(&self, route: String) {
+        if self.may_leave_for(&route) {
+            self.inner.push(route);
+        }
+    }
+
+    fn replace(&self, route: String) {
+        if self.may_leave_for(&route) {
+            self.inner.replace(route);
+        }
+    }
+
+    fn external(&self, url: String) -> bool {
+        self.inner.external(url)
+    }
+
+    fn updater(&self, callback: Arc<dyn Fn() + Send + Sync>) {
+        self.inner.updater(callback);
+    }
+
+    fn include_prevent_default(&self) -> bool {
+        self.inner.include_prevent_default()
+    }
+}
+
+/// Ask for confirmation before leaving the current route, either by navigating back or by
+/// closing the page, until [`stop_guarding_leave`] is called.
+fn guard_leave(warning: LeaveWarning) {
+    document::eval(&format!(
+        r#"
+        window.__stopGuardingLeave?.();
+        const navigationMessage = {:?};
+        const unloadMessage = {:?};
+        const path = window.location.pathname + window.location.search + window.location.hash;
+        let returning = false;
+        const onBeforeUnload = (e) => {{
+            e.preventDefault();
+            e.returnValue = unloadMessage;
+        }};
+        const onPopState = () => {{
+            if (returning) {{
+                returning = false;
+                return;
+            }}
+            if (window.location.pathname + window.location.search + window.location.hash === path) {{
+                return;
+            }}
+            if (confirm(navigationMessage)) {{
+                stop();
+            }} else {{
+                // go back to the guarded route
+                returning = true;
+                history.forward();
+            }}
+        }};
+        const stop = () => {{
+            window.removeEventListener('beforeunload', onBeforeUnload);
+            window.removeEventListener('popstate', onPopState);
+            delete window.__stopGuardingLeave;
+        }};
+        window.__stopGuardingLeave = stop;
+        window.addEventListener('beforeunload', onBeforeUnload);
+        window.addEventListener('popstate', onPopState);
+        "#,
+        warning.navigation, warning.unload,
+    ));
+}
+
+fn stop_guarding_leave() {
+    document::eval("window.__stopGuardingLeave?.();");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use dioxus::history::MemoryHistory;
+    use std::time::Duration;
+    use theory_test_parser::question_parser::Question;
+
+    #[test]
+    fn leave_warnings() {
+        let question = Question::sample(1, 0);
+        let mut state = ExamState::new(vec![question.clone(), question], Duration::ZERO);
+        assert_eq!(leave_warning(&state, false), None);
+        assert!(leave_warning(&state, true).is_some());
+        state.select(0, 1, Duration::ZERO);
+        let practice = leave_warning(&state, false).unwrap();
+        assert_ne!(Some(practice), leave_warning(&state, true));
+        // only navigating within the app keeps the progress
+        assert!(practice.navigation.contains("ההתקדמות תישמר"));
+        assert!(practice.unload.contains("ההתקדמות לא תישמר"));
+        state.grade(Duration::ZERO);
+        assert_eq!(leave_warning(&state, false), None);
+        assert_eq!(leave_warning(&state, true), None);
+    }
+
+    #[test]
+    fn navigation_within_the_app_is_guarded() {
+        let exam = Route::RealExam {
+            strict: true,
+            class: String::new(),
+        }
+        .to_string();
+        let history = |confirm| GuardedHistory {
+            inner: Rc::new(MemoryHistory::with_initial_path(&exam)),
+            guard: LeaveGuard::default(),
+            confirm,
+        };
+        let warning = LeaveWarning {
+            navigation: "navigation",
+            unload: "unload",
+        };
+        let staying = history(|message| {
+            assert_eq!(message, "navigation");
+            false
+        });
+        // nothing to ask before the exam is guarded
+        staying.push(Route::Settings.to_string());
+        assert_eq!(staying.current_route(), Route::Settings.to_string());
+        staying.go_back();
+        staying.guard.0.set(Some(warning));
+        staying.push(Route::MainPage.to_string());
+        staying.replace(Route::AttemptHistory.to_string());
+        assert_eq!(staying.current_route(), exam);
+        let leaving = history(|_| true);
+        leaving.guard.0.set(Some(warning));
+        leaving.push(Route::MainPage.to_string());
+        assert_eq!(leaving.current_route(), Route::MainPage.to_string());
+    }
+}
@@ -0,0 +1,31 @@
+//! The history of graded exams, persisted across sessions.
+use dioxus::prelude::*;
+pub use theory_test_core::history::{Attempt, History, QuestionAttempt, QuestionStats};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "history";
+
+/// Provide the history to the whole app, loading the stored one and saving every change.
+pub fn provide_history() {
+    let mut history = use_context_provider(|| Signal::new(History::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<History>(STORAGE_KEY).await {
+            // keep attempts which were graded before the stored history loaded
+            let recorded = std::mem::replace(&mut *history.write(), stored);
+            history.write().attempts.extend(recorded.attempts);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let history = history.read();
+        if loaded() {
+            storage::save(STORAGE_KEY, &*history);
+        }
+    });
+}
+
+pub fn use_history() -> Signal<History> {
+    use_context()
+}
@@ -0,0 +1,96 @@
+use dioxus::prelude::*;
+
+use crate::{
+    exam_state::PassThreshold,
+    history::{use_history, Attempt, History},
+    personal_best::use_personal_bests,
+    timer, ExamKind, Route,
+};
+
+fn attempt_description(attempt: &Attempt) -> String {
+    let kind = match attempt.kind {
+        ExamKind::Real => "מבחן אמיתי",
+        ExamKind::Practice => "מבחן תרגול",
+        ExamKind::DailyQuestion => "שאלת היום",
+        ExamKind::Drill => "תרגול מהיר",
+        ExamKind::SignDrill => "תרגול תמרורים",
+        ExamKind::Imported => "תרגול מיובא",
+    };
+    let mut description = if attempt.strict {
+        format!("{} (קפדני)", kind)
+    } else {
+        kind.to_string()
+    };
+    if let Some(rules) = attempt.rules {
+        description += &format!(", דרגה {}", rules.class.code());
+    }
+    if attempt.pass_threshold != PassThreshold::Official {
+        description += &format!(", סף מעבר: {}", attempt.pass_threshold.description_he());
+    }
+    description
+}
+
+/// The attempts of the history, newest first.
+#[component]
+pub fn AttemptHistory() -> Element {
+    let mut history = use_history();
+    let mut bests = use_personal_bests();
+    let nav = navigator();
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let utc_offset = utc_offset().unwrap_or(0);
+
+    rsx! {
+        div { class: "attempt-history",
+            h1 { "היסטוריית מבחנים" }
+            if history.read().attempts.is_empty() {
+                div { "עדיין לא נבדקו מבחנים." }
+            }
+            table {
+                for attempt in history.read().attempts.iter().rev() {
+                    tr { class: if attempt.abandoned { "abandoned" },
+                        td { {timer::format_date_time(attempt.graded_at, utc_offset)} }
+                        td { {attempt_description(attempt)} }
+                        td {
+                            if attempt.abandoned {
+                                "ננטש"
+                            } else {
+                                {format!("{}/{}", attempt.num_correct(), attempt.questions.len())}
+                            }
+                        }
+                        td {
+                            if let Some(duration) = attempt.duration {
+                                {timer::format_duration(duration)}
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                if !history.read().attempts.is_empty() {
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| async move {
+                            let confirmed = document::eval("return confirm('למחוק את כל היסטוריית המבחנים?');")
+                                .join::<bool>()
+                                .await
+                                .unwrap_or(false);
+                            if confirmed {
+                                // the bests are records of the history, they go with it
+                                history.set(History::default());
+                                bests.write().clear();
+                            }
+                        },
+                        "נקה היסטוריה"
+                    }
+                }
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        }
+    }
+}
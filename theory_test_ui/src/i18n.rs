@@ -0,0 +1,163 @@
+//! The texts of the app in each language, as Fluent messages, see `assets/locales`.
+//!
+//! Texts are looked up with [`tr!`] in the language of the preferences, falling back to the
+//! Hebrew baseline. Every key given to [`tr!`] is checked against the baseline when building.
+use dioxus::prelude::*;
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+use crate::preferences::{Locale, Preferences};
+
+/// The language every text exists in.
+const BASELINE: Locale = Locale::He;
+
+const RESOURCES: [(Locale, &str); 2] = [
+    (Locale::He, include_str!("../assets/locales/he.ftl")),
+    (Locale::En, include_str!("../assets/locales/en.ftl")),
+];
+
+// defines `check_key!`, see build.rs
+include!(concat!(env!("OUT_DIR"), "/check_key.rs"));
+
+/// The text of `key` in the current language, with the named arguments, e.g.
+/// `tr!("minutes-left", minutes = 5)`. The key must be a literal which is in the baseline.
+macro_rules! tr {
+    ($key:tt $(, $name:ident = $value:expr)* $(,)?) => {{
+        $crate::i18n::check_key!($key);
+        #[allow(unused_mut)]
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)*
+        $crate::i18n::translate($key, &args)
+    }};
+}
+pub(crate) use check_key;
+pub(crate) use tr;
+
+/// The messages of each language.
+pub struct Localizer {
+    bundles: Vec<(Locale, FluentBundle<FluentResource>)>,
+}
+
+impl Localizer {
+    /// A localizer of the Fluent `resources` of each language. Messages which don't parse are
+    /// left out.
+    pub fn new(resources: &[(Locale, &str)]) -> Self {
+        let bundles = resources
+            .iter()
+            .map(|(locale, source)| {
+                let language = locale
+                    .code()
+                    .parse::<LanguageIdentifier>()
+                    .expect("locale codes are valid");
+                let mut bundle = FluentBundle::new(vec![language]);
+                // the texts are shown as they are, like the ones which aren't translated yet
+                bundle.set_use_isolating(false);
+                let resource = FluentResource::try_new(source.to_string())
+                    .unwrap_or_else(|(resource, _errors)| resource);
+                bundle.add_resource_overriding(resource);
+                (*locale, bundle)
+            })
+            .collect();
+        Self { bundles }
+    }
+
+    /// The text of `key` in `locale`, or in the baseline when `locale` doesn't have it. The key
+    /// itself when neither has it.
+    pub fn format(&self, locale: Locale, key: &str, args: &FluentArgs) -> String {
+        [locale, BASELINE]
+            .into_iter()
+            .find_map(|locale| {
+                let (_, bundle) = self.bundles.iter().find(|(l, _)| *l == locale)?;
+                let pattern = bundle.get_message(key)?.value()?;
+                let mut errors = Vec::new();
+                Some(
+                    bundle
+                        .format_pattern(pattern, Some(args), &mut errors)
+                        .into_owned(),
+                )
+            })
+            .unwrap_or_else(|| key.to_string())
+    }
+}
+
+thread_local! {
+    static LOCALIZER: Localizer = Localizer::new(&RESOURCES);
+}
+
+/// The language of the preferences, which re-renders the calling component when it changes. The
+/// baseline outside of the app, e.g. in tests.
+pub fn current_locale() -> Locale {
+    try_consume_context::<Signal<Preferences>>()
+        .map(|preferences| preferences.read().locale)
+        .unwrap_or(BASELINE)
+}
+
+/// The text of `key` in the current language, use [`tr!`] instead.
+pub fn translate(key: &str, args: &FluentArgs) -> String {
+    LOCALIZER.with(|localizer| localizer.format(current_locale(), key, args))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn minutes_left(locale: Locale, minutes: f64) -> String {
+        let mut args = FluentArgs::new();
+        args.set("minutes", minutes);
+        LOCALIZER.with(|localizer| localizer.format(locale, "minutes-left", &args))
+    }
+
+    #[test]
+    fn plurals() {
+        assert_eq!(minutes_left(Locale::He, 1.0), "נותרה דקה");
+        assert_eq!(minutes_left(Locale::He, 2.0), "נותרו שתי דקות");
+        assert_eq!(minutes_left(Locale::He, 5.0), "נותרו 5 דקות");
+        assert_eq!(minutes_left(Locale::He, 2.5), "נותרו 2.5 דקות");
+        assert_eq!(minutes_left(Locale::En, 1.0), "1 minute left");
+        assert_eq!(minutes_left(Locale::En, 2.0), "2 minutes left");
+        // outside of the app, the macro uses the baseline
+        assert_eq!(tr!("mistakes-left", left = 3), "טעויות שנותרו: 3");
+    }
+
+    #[test]
+    fn fallback() {
+        let localizer = Localizer::new(&[
+            (Locale::He, "greeting = שלום\nfarewell = להתראות { $name }"),
+            (Locale::En, "greeting = Hello\nbroken = {"),
+        ]);
+        let mut args = FluentArgs::new();
+        assert_eq!(localizer.format(Locale::En, "greeting", &args), "Hello");
+        args.set("name", "דנה");
+        assert_eq!(
+            localizer.format(Locale::En, "farewell", &args),
+            "להתראות דנה"
+        );
+        assert_eq!(localizer.format(Locale::En, "broken", &args), "broken");
+        assert_eq!(localizer.format(Locale::En, "unknown", &args), "unknown");
+    }
+
+    #[test]
+    fn translations_are_in_the_baseline() {
+        let ids = |source: &str| {
+            FluentResource::try_new(source.to_string())
+                .unwrap_or_else(|(_, errors)| panic!("{errors:?}"))
+                .entries()
+                .filter_map(|entry| match entry {
+                    fluent_syntax::ast::Entry::Message(message) => {
+                        Some(message.id.name.to_string())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let baseline = ids(RESOURCES[0].1);
+        for (locale, source) in &RESOURCES[1..] {
+            for id in ids(source) {
+                assert!(
+                    baseline.contains(&id),
+                    "{id} of {locale:?} isn't in the baseline"
+                );
+            }
+        }
+    }
+}
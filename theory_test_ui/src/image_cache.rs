@@ -0,0 +1,464 @@
+//! The images of questions which were shown, kept in the browser's Cache Storage so they show
+//! offline the next time, with or without the service worker.
+//!
+//! The index of what is kept is stored with the rest of the app's data, and evicts the images
+//! used least recently once they take more than [`CAP_BYTES`]. Failing to keep or read an image
+//! never keeps it from loading from the network.
+use std::{cell::RefCell, collections::BTreeMap, future::Future, rc::Rc};
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "image-cache";
+/// The name of the cache in Cache Storage.
+const CACHE_NAME: &str = "question-images";
+/// The most the kept images take.
+pub const CAP_BYTES: u64 = 20 * 1024 * 1024;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    size: u64,
+    /// When it was last used, by the count of uses of the cache.
+    last_used: u64,
+}
+
+/// What is kept in the cache, by the URL of each image.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheIndex {
+    entries: BTreeMap<String, CacheEntry>,
+    uses: u64,
+}
+
+impl CacheIndex {
+    pub fn contains(&self, url: &str) -> bool {
+        self.entries.contains_key(url)
+    }
+
+    /// Mark the image at `url` as used, returning whether it is kept.
+    fn touch(&mut self, url: &str) -> bool {
+        let Some(entry) = self.entries.get_mut(url) else {
+            return false;
+        };
+        self.uses += 1;
+        entry.last_used = self.uses;
+        true
+    }
+
+    /// Keep the image at `url`, of `size` bytes, returning the images evicted to stay within
+    /// `cap`, least recently used first. `None` if the image alone is larger than `cap`, and
+    /// isn't kept.
+    fn insert(&mut self, url: &str, size: u64, cap: u64) -> Option<Vec<String>> {
+        if size > cap {
+            return None;
+        }
+        self.entries.remove(url);
+        let mut evicted = Vec::new();
+        while self.total_bytes() + size > cap {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone())
+            else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            evicted.push(oldest);
+        }
+        self.uses += 1;
+        self.entries.insert(
+            url.to_string(),
+            CacheEntry {
+                size,
+                last_used: self.uses,
+            },
+        );
+        Some(evicted)
+    }
+
+    fn remove(&mut self, url: &str) -> bool {
+        self.entries.remove(url).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+}
+
+/// Where the images are kept.
+///
+/// Like [`storage::Storage`], writes are issued when they are called, and their futures don't
+/// borrow the store or the arguments.
+pub trait ImageStore {
+    /// An object URL of the image kept for `url`, `None` if it isn't kept.
+    fn get(&self, url: &str) -> impl Future<Output = Option<String>>;
+    /// Keep the image loaded from `from` for `url`, returning its size, `None` if it couldn't be
+    /// kept.
+    fn put(&self, url: &str, from: &str) -> impl Future<Output = Option<u64>> + use<Self>;
+    fn delete(&self, url: &str) -> impl Future<Output = ()> + use<Self>;
+    fn clear(&self) -> impl Future<Output = ()> + use<Self>;
+}
+
+/// The browser's Cache Storage. Only images which allow reading them from another origin can be
+/// kept, the others stay with the browser's own cache.
+pub struct CacheStorage;
+
+impl ImageStore for CacheStorage {
+    fn get(&self, url: &str) -> impl Future<Output = Option<String>> {
+        let eval = document::eval(&format!(
+            r#"
+            try {{
+                const response = await (await caches.open({CACHE_NAME:?})).match({url:?});
+                return response ? URL.createObjectURL(await response.blob()) : null;
+            }} catch (e) {{
+                return null;
+            }}
+            "#
+        ));
+        async move { eval.join::<Option<String>>().await.ok().flatten() }
+    }
+
+    fn put(&self, url: &str, from: &str) -> impl Future<Output = Option<u64>> + use<> {
+        let eval = document::eval(&format!(
+            r#"
+            try {{
+                const response = await fetch({from:?}, {{ mode: 'cors' }});
+                if (!response.ok) {{
+                    return null;
+                }}
+                const size = (await response.clone().blob()).size;
+                await (await caches.open({CACHE_NAME:?})).put({url:?}, response);
+                return size;
+            }} catch (e) {{
+                return null;
+            }}
+            "#
+        ));
+        async move { eval.join::<Option<u64>>().await.ok().flatten() }
+    }
+
+    fn delete(&self, url: &str) -> impl Future<Output = ()> + use<> {
+        let eval = document::eval(&format!(
+            "try {{ await (await caches.open({CACHE_NAME:?})).delete({url:?}); }} catch (e) {{}}"
+        ));
+        async move {
+            let _ = eval.await;
+        }
+    }
+
+    fn clear(&self) -> impl Future<Output = ()> + use<> {
+        let eval = document::eval(&format!(
+            "try {{ await caches.delete({CACHE_NAME:?}); }} catch (e) {{}}"
+        ));
+        async move {
+            let _ = eval.await;
+        }
+    }
+}
+
+/// The kept images and their index, kept in step.
+pub struct ImageCache<S> {
+    store: S,
+    index: RefCell<CacheIndex>,
+    cap: u64,
+}
+
+impl<S: ImageStore> ImageCache<S> {
+    pub fn new(store: S, cap: u64) -> Self {
+        Self {
+            store,
+            index: RefCell::new(CacheIndex::default()),
+            cap,
+        }
+    }
+
+    pub fn index(&self) -> CacheIndex {
+        self.index.borrow().clone()
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.index.borrow().contains(url)
+    }
+
+    /// Take the stored index, keeping the images kept since the cache was created.
+    pub async fn restore(&self, stored: CacheIndex) {
+        let kept = std::mem::replace(&mut *self.index.borrow_mut(), stored);
+        for (url, entry) in kept.entries {
+            let evicted = self.index.borrow_mut().insert(&url, entry.size, self.cap);
+            for evicted in evicted.unwrap_or_default() {
+                self.store.delete(&evicted).await;
+            }
+        }
+    }
+
+    /// An object URL of the kept image at `url`, `None` if it isn't kept. An image the browser
+    /// dropped is forgotten.
+    pub async fn lookup(&self, url: &str) -> Option<String> {
+        if !self.contains(url) {
+            return None;
+        }
+        let object_url = self.store.get(url).await;
+        if object_url.is_some() {
+            self.index.borrow_mut().touch(url);
+        } else {
+            self.index.borrow_mut().remove(url);
+        }
+        object_url
+    }
+
+    /// Keep the image at `url`, which loaded from `from`, evicting the images used least
+    /// recently to make room. Returns whether the index changed.
+    pub async fn record(&self, url: &str, from: &str) -> bool {
+        if self.index.borrow_mut().touch(url) {
+            return true;
+        }
+        let Some(size) = self.store.put(url, from).await else {
+            return false;
+        };
+        let evicted = self.index.borrow_mut().insert(url, size, self.cap);
+        let Some(evicted) = evicted else {
+            self.store.delete(url).await;
+            return false;
+        };
+        for evicted in evicted {
+            self.store.delete(&evicted).await;
+        }
+        true
+    }
+
+    pub async fn clear(&self) {
+        *self.index.borrow_mut() = CacheIndex::default();
+        self.store.clear().await;
+    }
+}
+
+/// The cache of the app, with how much it takes for showing it.
+#[derive(Clone)]
+pub struct SharedImageCache {
+    cache: Rc<ImageCache<CacheStorage>>,
+    /// The number of kept images and their size.
+    usage: Signal<(usize, u64)>,
+}
+
+impl SharedImageCache {
+    pub fn contains(&self, url: &str) -> bool {
+        self.cache.contains(url)
+    }
+
+    pub async fn lookup(&self, url: &str) -> Option<String> {
+        let indexed = self.contains(url);
+        let object_url = self.cache.lookup(url).await;
+        if indexed {
+            self.changed();
+        }
+        object_url
+    }
+
+    pub async fn record(&self, url: &str, from: &str) {
+        if self.cache.record(url, from).await {
+            self.changed();
+        }
+    }
+
+    async fn clear(&self) {
+        self.cache.clear().await;
+        self.changed();
+    }
+
+    fn changed(&self) {
+        let index = self.cache.index();
+        storage::save_global(STORAGE_KEY, &index);
+        let mut usage = self.usage;
+        usage.set((index.len(), index.total_bytes()));
+    }
+}
+
+/// Provide the cache of images to the whole app, loading its stored index.
+pub fn provide_image_cache() {
+    let usage = use_signal(|| (0, 0));
+    let cache = use_context_provider(|| SharedImageCache {
+        cache: Rc::new(ImageCache::new(CacheStorage, CAP_BYTES)),
+        usage,
+    });
+    use_future(move || {
+        let cache = cache.clone();
+        async move {
+            // the index is kept for every bank, like the images
+            if let Some(stored) = storage::load_global::<CacheIndex>(STORAGE_KEY).await {
+                cache.cache.restore(stored).await;
+                cache.changed();
+            }
+        }
+    });
+}
+
+/// The cache of images, `None` outside of the app, e.g. in tests.
+pub fn use_image_cache() -> Option<SharedImageCache> {
+    use_hook(try_consume_context::<SharedImageCache>)
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1}", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// How much the kept images take, with clearing them.
+#[component]
+pub fn ImageCacheSettings() -> Element {
+    let Some(cache) = use_image_cache() else {
+        return rsx! {};
+    };
+    let (images, bytes) = (cache.usage)();
+
+    rsx! {
+        div { class: "setting",
+            {
+                format!(
+                    "תמונות שנשמרו לשימוש לא מקוון: {images} ({} מתוך {} MB)",
+                    megabytes(bytes),
+                    megabytes(CAP_BYTES),
+                )
+            }
+            " "
+            button {
+                class: "button-primary",
+                disabled: images == 0,
+                onclick: move |_| {
+                    let cache = cache.clone();
+                    async move { cache.clear().await }
+                },
+                "ניקוי"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+    use futures_executor::block_on;
+
+    use super::*;
+
+    /// Images kept in memory, by URL, with their size.
+    #[derive(Clone, Default)]
+    struct MemoryStore {
+        images: Rc<RefCell<BTreeMap<String, u64>>>,
+        /// The size of the image at each URL, the others can't be loaded.
+        sizes: BTreeMap<String, u64>,
+    }
+
+    impl ImageStore for MemoryStore {
+        fn get(&self, url: &str) -> impl Future<Output = Option<String>> {
+            let kept = self.images.borrow().contains_key(url);
+            std::future::ready(kept.then(|| format!("blob:{url}")))
+        }
+
+        fn put(&self, url: &str, from: &str) -> impl Future<Output = Option<u64>> + use<> {
+            let size = self.sizes.get(from).copied();
+            if let Some(size) = size {
+                self.images.borrow_mut().insert(url.to_string(), size);
+            }
+            std::future::ready(size)
+        }
+
+        fn delete(&self, url: &str) -> impl Future<Output = ()> + use<> {
+            self.images.borrow_mut().remove(url);
+            std::future::ready(())
+        }
+
+        fn clear(&self) -> impl Future<Output = ()> + use<> {
+            self.images.borrow_mut().clear();
+            std::future::ready(())
+        }
+    }
+
+    fn store(sizes: &[(&str, u64)]) -> MemoryStore {
+        MemoryStore {
+            sizes: sizes
+                .iter()
+                .map(|(url, size)| (url.to_string(), *size))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn least_recently_used_are_evicted() {
+        let mut index = CacheIndex::default();
+        assert_eq!(index.insert("a", 40, 100), Some(vec![]));
+        assert_eq!(index.insert("b", 40, 100), Some(vec![]));
+        assert!(index.touch("a"));
+        assert_eq!(index.insert("c", 40, 100), Some(vec!["b".to_string()]));
+        assert_eq!(index.total_bytes(), 80);
+        // replacing an image counts its new size only
+        assert_eq!(index.insert("c", 60, 100), Some(vec![]));
+        assert_eq!(
+            index.insert("d", 90, 100),
+            Some(vec!["a".to_string(), "c".to_string()])
+        );
+        assert_eq!(index.insert("e", 101, 100), None);
+        assert!(!index.contains("e"));
+        assert!(!index.touch("e"));
+        assert_eq!((index.len(), index.total_bytes()), (1, 90));
+        // the order of use survives storing the index
+        let json = serde_json::to_string(&index).unwrap();
+        assert_eq!(serde_json::from_str::<CacheIndex>(&json).unwrap(), index);
+    }
+
+    #[test]
+    fn cache_follows_its_index() {
+        let store = store(&[("a", 40), ("b", 40), ("c", 40), ("huge", 500)]);
+        let images = store.images.clone();
+        let cache = ImageCache::new(store, 100);
+        block_on(async {
+            assert_eq!(cache.lookup("a").await, None);
+            assert!(cache.record("a", "a").await);
+            assert!(cache.record("b", "b").await);
+            assert_eq!(cache.lookup("a").await, Some("blob:a".to_string()));
+            // b was used least recently
+            assert!(cache.record("c", "c").await);
+            assert_eq!(
+                images.borrow().keys().collect::<Vec<_>>(),
+                ["a", "c"].iter().collect::<Vec<_>>()
+            );
+            assert!(!cache.contains("b"));
+            // what can't be loaded or is larger than the cap isn't kept
+            assert!(!cache.record("x", "x").await);
+            assert!(!cache.record("huge", "huge").await);
+            assert!(!cache.contains("huge"));
+            assert!(!images.borrow().contains_key("huge"));
+            // an image the browser dropped is forgotten
+            images.borrow_mut().remove("a");
+            assert_eq!(cache.lookup("a").await, None);
+            assert!(!cache.contains("a"));
+
+            cache.clear().await;
+            assert!(images.borrow().is_empty());
+            assert_eq!(cache.index(), CacheIndex::default());
+        });
+    }
+
+    #[test]
+    fn restoring_keeps_new_images() {
+        let cache = ImageCache::new(store(&[("new", 60)]), 100);
+        block_on(async {
+            cache.record("new", "new").await;
+            let mut stored = CacheIndex::default();
+            stored.insert("old", 30, 100);
+            stored.insert("older", 30, 100);
+            stored.touch("old");
+            cache.restore(stored).await;
+        });
+        // the new image is the most recent, the least recent stored one made room for it
+        assert!(cache.contains("new") && cache.contains("old"));
+        assert!(!cache.contains("older"));
+    }
+}
@@ -1,38 +1,314 @@
 use dioxus::prelude::*;
+use std::{rc::Rc, time::Duration};
+
 use rand::{
     seq::{IndexedRandom, SliceRandom},
-    SeedableRng,
+    Rng, SeedableRng,
+};
+use theory_test_parser::{
+    exam::{ExamRules, ExamSpec},
+    question_parser::{LicenseClass, Question, QuestionCategory},
 };
-use theory_test_parser::question_parser::{ExamQuestions, Question};
+
+#[cfg(debug_assertions)]
+mod a11y_audit;
+mod achievements;
+mod backup;
+mod bank;
+mod bank_stats;
+mod bank_update;
+mod browse;
+mod category_badge;
+mod certificate;
+mod challenge;
+mod confidence_report;
+mod csv_import;
+mod daily;
+mod drill;
+mod exam_header;
+mod exam_store;
+mod history;
+mod history_page;
+mod i18n;
+mod image_cache;
+mod marathon;
+mod not_found;
+mod notebook;
+mod notes;
+mod offline;
+mod personal_best;
+mod preferences;
+mod print;
+mod profile;
+mod qr_code;
+mod question_boundary;
+mod question_history;
+mod question_image;
+mod question_navigator;
+mod question_page;
+mod remote;
+mod result_card;
+mod results_grid;
+mod review_stepper;
+mod routing;
+mod settings;
+mod share;
+mod sign_drill;
+mod sound;
+mod speech;
+mod ssr;
+mod statistics;
+mod storage;
+mod study;
+mod study_plan;
+mod sync;
+mod tags;
+mod teacher;
+mod timer;
+mod toast;
+mod translation;
+mod trend_chart;
+mod virtual_list;
+
+use theory_test_core::{category_filter, direction, exam_state, practice_config, time_warnings};
+
+use achievements::{provide_achievements, use_achievements, AchievementContext};
+use bank::{provide_bank, use_bank, BankGate, BankWarnings};
+use bank_stats::BankStatsStrip;
+use bank_update::{BankChanges, BankUpdateNotice};
+use browse::Browse;
+use category_badge::CategoryBadge;
+use category_filter::{CategoryChoice, CategoryFilter};
+use certificate::CertificateButton;
+use confidence_report::ConfidenceReport;
+use daily::DailyQuestion;
+use direction::{direction, UI_LANGUAGE};
+use drill::Drill;
+use exam_header::ExamHeader;
+use exam_state::{
+    check_question, question_body, Confidence, ExamKind, ExamResult, ExamState, PassThreshold,
+};
+use exam_store::{use_exam_store, use_leave_guard, ExamStore, GuardedHistory, LeaveGuard};
+use history::{provide_history, use_history, Attempt, QuestionStats};
+use history_page::AttemptHistory;
+use i18n::tr;
+use marathon::Marathon;
+use not_found::NotFound;
+use notebook::{provide_notebook_removals, MistakeNotebook};
+use notes::{provide_notes, QuestionNote};
+use offline::{ConnectivityBanner, OfflineSupport};
+use personal_best::{provide_personal_bests, use_personal_bests, BestScore, ExamConfig};
+use practice_config::PracticeConfig;
+use preferences::{provide_preferences, use_preferences, QuestionNumbering, QuestionOrder};
+use print::{PrintAnswerSheet, PrintExam};
+use profile::{provide_profiles, ProfileSwitcher};
+use question_boundary::QuestionBoundary;
+use question_history::QuestionHistory;
+use question_image::QuestionImage;
+use question_navigator::QuestionNavigator;
+use question_page::QuestionPage;
+use remote::use_generated_exam;
+use results_grid::ResultsGrid;
+use review_stepper::ReviewStepper;
+use settings::{PassThresholdSelect, Settings};
+use share::ShareResult;
+use sign_drill::SignDrill;
+use ssr::Hydrated;
+use statistics::Statistics;
+use study::{provide_study_positions, Study, StudyLinks};
+use tags::{provide_tags, use_tags, QuestionTags};
+use teacher::{DownloadResult, TeacherDashboard};
+use toast::{use_toast, ToastList, Toasts};
+use translation::TranslationButton;
+use virtual_list::use_virtual_window;
 
 const MAIN_CSS: Asset = asset!("/assets/main.css");
 
-#[derive(Routable, Clone)]
+#[derive(Routable, Clone, PartialEq)]
 pub enum Route {
+    #[layout(Hydrated)]
     #[route("/")]
     MainPage,
-    #[route("/real_exam")]
-    RealExam,
-    #[route("/pratice_exam?:num_questions")]
-    PracticeExam { num_questions: usize },
+    #[route("/real_exam?:strict&:class")]
+    RealExam {
+        strict: bool,
+        /// The code of the license class whose official exam is taken, B when empty.
+        class: String,
+    },
+    #[route("/pratice_exam?:num_questions&:feedback&:category&:exclude&:tag")]
+    PracticeExam {
+        num_questions: usize,
+        feedback: bool,
+        category: String,
+        /// Categories whose questions are never drawn.
+        exclude: String,
+        /// Only questions the user tagged so, any question when empty.
+        tag: String,
+    },
+    #[route("/marathon?:count&:step")]
+    Marathon { count: usize, step: usize },
+    #[route("/challenge?:qs")]
+    Challenge { qs: String },
+    #[route("/drill?:category&:seconds&:num_questions")]
+    Drill {
+        category: String,
+        seconds: u64,
+        num_questions: usize,
+    },
+    #[route("/sign_drill?:not_yet_correct")]
+    SignDrill { not_yet_correct: bool },
+    #[route("/study?:class&:from")]
+    Study { class: String, from: usize },
+    #[route("/browse?:class&:category&:with_image&:history&:with_note&:tag&:page")]
+    Browse {
+        class: String,
+        category: String,
+        with_image: bool,
+        history: String,
+        with_note: bool,
+        tag: String,
+        page: usize,
+    },
+    #[route("/question/:num")]
+    QuestionPage { num: usize },
+    #[route("/print?:seed")]
+    PrintExam { seed: u32 },
+    #[route("/print/answer_sheet?:seed")]
+    PrintAnswerSheet { seed: u32 },
+    #[route("/notebook")]
+    MistakeNotebook {},
+    #[route("/history")]
+    AttemptHistory,
+    #[route("/statistics")]
+    Statistics,
+    #[route("/settings")]
+    Settings,
+    #[route("/teacher")]
+    TeacherDashboard,
+    #[route("/bank_changes")]
+    BankChanges {},
+    #[route("/:..segments")]
+    NotFound { segments: Vec<String> },
+}
+
+impl From<&PracticeConfig> for Route {
+    /// The route of a practice exam of `config`.
+    fn from(config: &PracticeConfig) -> Self {
+        Route::PracticeExam {
+            num_questions: config.num_questions,
+            feedback: config.feedback,
+            category: config.category.clone(),
+            exclude: config.exclude.clone(),
+            tag: config.tag.clone(),
+        }
+    }
 }
 
 fn main() {
+    // the spans of loading the bank and of the exams, in the console of the browser
+    #[cfg(debug_assertions)]
+    dioxus::logger::init(dioxus::logger::tracing::Level::DEBUG).expect("the logger is set once");
+    // parsed once for all of the requests, before the first one
+    #[cfg(feature = "server")]
+    bank::preload();
     dioxus::launch(App);
 }
 
 #[component]
 fn App() -> Element {
+    // the state shared by the whole app, provided once, here
+    ExamStore::provide();
+    provide_profiles();
+    provide_preferences();
+    provide_history();
+    provide_achievements();
+    provide_personal_bests();
+    provide_study_positions();
+    provide_notebook_removals();
+    provide_notes();
+    provide_tags();
+    speech::provide_speech();
+    image_cache::provide_image_cache();
+    Toasts::provide();
+    provide_bank()?;
+    let leave_guard = LeaveGuard::provide();
+    use_context_provider(|| {
+        #[cfg(all(feature = "hash-routing", target_arch = "wasm32"))]
+        let history =
+            Rc::new(routing::hash::HashHistory::new()) as Rc<dyn dioxus::history::History>;
+        #[cfg(not(all(feature = "hash-routing", target_arch = "wasm32")))]
+        let history = dioxus::history::history();
+        Rc::new(GuardedHistory::new(history, leave_guard)) as Rc<dyn dioxus::history::History>
+    });
+    // a tool for developing, never in release builds
+    #[cfg(debug_assertions)]
+    let a11y_audit = rsx! {
+        a11y_audit::A11yAudit {}
+    };
+    #[cfg(not(debug_assertions))]
+    let a11y_audit = rsx! {};
     rsx! {
         document::Link { rel: "stylesheet", href: MAIN_CSS }
-        Router::<Route> {}
+        // every page inherits the direction of the UI
+        div { lang: UI_LANGUAGE, dir: direction(UI_LANGUAGE),
+            BankGate {
+                ConnectivityBanner {}
+                Router::<Route> {}
+                OfflineSupport {}
+            }
+            ToastList {}
+            storage::NewerDataWarning {}
+            {a11y_audit}
+        }
     }
 }
 
 #[component]
 pub fn MainPage() -> Element {
-    let mut num_questions = use_signal(|| 30);
+    let bank = use_bank();
+    let pool = use_hook(|| Rc::new(bank.class_questions()));
+    let mut preferences = use_preferences();
+    // the last practice exam, fitted to the current bank
+    let last_practice = use_memo({
+        let pool = pool.clone();
+        move || {
+            preferences
+                .read()
+                .last_practice
+                .as_ref()
+                .map(|config| config.validated(&pool))
+        }
+    });
+    let initial_practice = last_practice.peek().clone().unwrap_or_default();
+    let mut num_questions = use_signal(|| initial_practice.num_questions);
+    let mut strict = use_signal(|| false);
+    let mut real_exam_class = use_signal(|| bank.class);
+    let mut feedback = use_signal(|| initial_practice.feedback);
+    let mut category_filter = use_signal(|| initial_practice.filter());
+    let mut practice_tag = use_signal(|| initial_practice.tag.clone());
+    // the preferences may load after the page was shown
+    use_effect(move || {
+        if let Some(config) = last_practice() {
+            num_questions.set(config.num_questions);
+            feedback.set(config.feedback);
+            category_filter.set(config.filter());
+            practice_tag.set(config.tag);
+        }
+    });
+    let tags = use_tags();
+    let pool_size = use_memo(move || {
+        let tag = practice_tag.read();
+        let tagged = (!tag.is_empty()).then(|| tags.read().nums(&tag));
+        pool.iter()
+            .filter(|q| category_filter.read().matches(q))
+            .filter(|q| tagged.as_ref().is_none_or(|nums| nums.contains(&q.num)))
+            .count()
+    });
+    let mut marathon_count = use_signal(|| marathon::DEFAULT_EXAMS);
+    let mut drill_category = use_signal(String::new);
+    let mut drill_seconds = use_signal(|| drill::DEFAULT_SECONDS_PER_QUESTION);
     let nav = navigator();
+    let mut exam_store = use_exam_store();
 
     rsx! {
         div {
@@ -44,16 +320,30 @@ pub fn MainPage() -> Element {
             div {
                 h1 { "מבחן תאוריה" }
             }
+            BankStatsStrip {}
+            ProfileSwitcher {}
+            BankWarnings {}
+            BankUpdateNotice {}
+            DailyQuestion {}
+            StudyLinks {}
 
-            div { dir: "rtl",
+            div {
                 button {
 
                     onclick: move |_| {
-                        nav.push(Route::PracticeExam {
-                            num_questions: num_questions.read().clone(),
-                        });
+                        let config = PracticeConfig::new(
+                                num_questions(),
+                                feedback(),
+                                &category_filter.read(),
+                            )
+                            .with_tag(&practice_tag.read());
+                        let route = Route::from(&config);
+                        preferences.write().last_practice = Some(config);
+                        exam_store.forget(&route);
+                        nav.push(route);
                     },
                     class: "button-primary",
+                    disabled: pool_size() == 0,
                     "מבחן תרגול"
 
                 }
@@ -67,28 +357,254 @@ pub fn MainPage() -> Element {
                     min: "1",
 
                 }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: feedback(),
+                        oninput: move |e| feedback.set(e.checked()),
+                    }
+                    "משוב מיידי"
+                }
+                PassThresholdSelect {}
+                if last_practice().is_some() {
+                    button {
+                        class: "link-button",
+                        onclick: move |_| {
+                            preferences.write().last_practice = None;
+                            let defaults = PracticeConfig::default();
+                            num_questions.set(defaults.num_questions);
+                            feedback.set(defaults.feedback);
+                            category_filter.set(defaults.filter());
+                            practice_tag.set(defaults.tag);
+                        },
+                        "אפס להגדרות ברירת מחדל"
+                    }
+                }
+            }
+            div { class: "filter-chips",
+                for category in QuestionCategory::ALL {
+                    button {
+                        class: "filter-chip",
+                        class: if category_filter.read().choice(category) == CategoryChoice::Include { "selected" },
+                        class: if category_filter.read().choice(category) == CategoryChoice::Exclude { "excluded" },
+                        title: "לחיצה עוברת בין: הכל, רק קטגוריה זו, ללא קטגוריה זו",
+                        onclick: move |_| category_filter.write().cycle(category),
+                        match category_filter.read().choice(category) {
+                            CategoryChoice::Exclude => format!("✕ {}", category.as_str_he()),
+                            _ => category.as_str_he().to_string(),
+                        }
+                    }
+                }
+            }
+            if !tags.read().all().is_empty() || !practice_tag.read().is_empty() {
+                label { class: "practice-tag",
+                    "תגית "
+                    select {
+                        value: practice_tag(),
+                        onchange: move |e| practice_tag.set(e.value()),
+                        option { value: "", "כל השאלות" }
+                        for (tag , count) in tags.read().all() {
+                            option { key: "{tag}", value: "{tag}", {format!("{tag} ({count})")} }
+                        }
+                    }
+                }
+            }
+            if pool_size() == 0 {
+                div { class: "pool-warning", "אין שאלות שמתאימות לסינון." }
+            } else if pool_size() < num_questions() {
+                div { class: "pool-warning",
+                    {format!("רק {} שאלות מתאימות לסינון, כולן ייכללו במבחן.", pool_size())}
+                }
             }
 
             div {
                 button {
                     onclick: move |_| {
-                        nav.push(Route::RealExam);
+                        let route = Route::RealExam {
+                            strict: strict(),
+                            class: real_exam_class().code().to_string(),
+                        };
+                        exam_store.forget(&route);
+                        nav.push(route);
                     },
                     class: "button-primary",
                     "מבחן אמיתי"
                 }
+                label {
+                    "דרגה "
+                    select {
+                        onchange: move |e| {
+                            if let Some(selected) = LicenseClass::from_code(&e.value()) {
+                                real_exam_class.set(selected);
+                            }
+                        },
+                        for option_class in LicenseClass::ALL {
+                            option {
+                                value: option_class.code(),
+                                selected: option_class == real_exam_class(),
+                                {option_class.code()}
+                            }
+                        }
+                    }
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: strict(),
+                        oninput: move |e| strict.set(e.checked()),
+                    }
+                    "מצב מבחן קפדני"
+                }
+            }
+
+            div {
+                button {
+                    onclick: move |_| {
+                        let count = marathon_count();
+                        for step in 0..count {
+                            exam_store.forget(&Route::Marathon { count, step });
+                        }
+                        nav.push(Route::Marathon { count, step: 0 });
+                    },
+                    class: "button-primary",
+                    "מרתון"
+                }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    max: "{marathon::MAX_EXAMS}",
+                    value: marathon_count,
+                    oninput: move |e| {
+                        if let Ok(count) = e.value().parse::<usize>() {
+                            marathon_count.set(count.clamp(1, marathon::MAX_EXAMS));
+                        }
+                    },
+                }
+                "מבחנים אמיתיים ברצף"
             }
+
+            div {
+                button {
+                    onclick: move |_| {
+                        nav.push(Route::Drill {
+                            category: drill_category(),
+                            seconds: drill_seconds(),
+                            num_questions: drill::DEFAULT_NUM_QUESTIONS,
+                        });
+                    },
+                    class: "button-primary",
+                    "תרגול מהיר"
+                }
+                select {
+                    onchange: move |e| drill_category.set(e.value()),
+                    option { value: "", "כל הקטגוריות" }
+                    for category in QuestionCategory::ALL {
+                        option { value: category.as_str_he(), {category.as_str_he()} }
+                    }
+                }
+                input {
+                    r#type: "number",
+                    min: "3",
+                    value: drill_seconds,
+                    oninput: move |e| {
+                        if let Ok(seconds) = e.value().parse() {
+                            drill_seconds.set(seconds);
+                        }
+                    },
+                }
+                "שניות לשאלה"
+            }
+
+            div {
+                button {
+                    onclick: move |_| {
+                        nav.push(Route::PrintExam {
+                            seed: rand_pcg::Pcg64::from_os_rng().random(),
+                        });
+                    },
+                    class: "button-primary",
+                    "מבחן להדפסה"
+                }
+            }
+
+            div {
+                Link {
+                    class: "main-page-link",
+                    to: Route::Browse {
+                        class: String::new(),
+                        category: String::new(),
+                        with_image: false,
+                        history: String::new(),
+                        with_note: false,
+                        tag: String::new(),
+                        page: 1,
+                    },
+                    "מאגר השאלות"
+                }
+                " "
+                Link {
+                    class: "main-page-link",
+                    to: Route::SignDrill {
+                        not_yet_correct: false,
+                    },
+                    "תרגול תמרורים עם תמונה"
+                }                " "
+                Link { class: "main-page-link", to: Route::MistakeNotebook {}, "מחברת טעויות" }
+                " "
+                Link { class: "main-page-link", to: Route::AttemptHistory, "היסטוריה" }
+                " "
+                Link { class: "main-page-link", to: Route::Statistics, "סטטיסטיקה" }
+                " "
+                Link { class: "main-page-link", to: Route::Settings, "הגדרות" }
+                " "
+                Link { class: "main-page-link", to: Route::TeacherDashboard, "לוח מורה" }
+            }
+        }
+    }
+}
+
+/// How long the exam waits to be submitted once its time ran out, before it is graded anyway.
+const TIME_UP_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+#[component]
+pub fn RealExam(strict: bool, class: String) -> Element {
+    let bank = use_bank();
+    let class = LicenseClass::from_code(&class).unwrap_or(bank.class);
+    let rules = ExamRules::official(class);
+    let mut restarts = use_signal(|| 0);
+    // switching between strict and regular exams or restarting draws a new exam
+    let spec = use_memo(use_reactive!(|strict, class| {
+        let _ = (strict, restarts());
+        ExamSpec::official(class, rand_pcg::Pcg64::from_os_rng().random())
+    }));
+    let Some(exam) = use_generated_exam(bank, spec) else {
+        return rsx! {
+            ExamLoading {}
+        };
+    };
+    rsx! {
+        Exam {
+            // switching between strict and regular exams starts a new exam
+            key: "{strict}-{spec.read().id()}",
+            questions: Unchangable(exam.questions),
+            kind: ExamKind::Real,
+            strict,
+            selection: QuestionSelection::Random {
+                num_questions: rules.num_questions,
+            },
+            time_limit: rules.duration,
+            rules,
+            exam_id: spec.read().id(),
+            on_restart: move |_| restarts += 1,
         }
     }
 }
 
+/// Shown in place of an exam until its questions arrived from the server.
 #[component]
-pub fn RealExam() -> Element {
-    let exam_questions =
-        ExamQuestions::parse_from_xlsx(include_bytes!("../../theory_test_parser/test.xlsx"))
-            .unwrap();
+fn ExamLoading() -> Element {
     rsx! {
-        Exam { exam_questions: Unchangable(exam_questions), num_questions: 30 }
+        div { class: "exam-loading", "המבחן נטען..." }
     }
 }
 
@@ -107,119 +623,691 @@ impl<T: Clone> Clone for Unchangable<T> {
 }
 
 #[component]
-pub fn PracticeExam(num_questions: usize) -> Element {
-    let exam_questions =
-        ExamQuestions::parse_from_xlsx(include_bytes!("../../theory_test_parser/test.xlsx"))
-            .unwrap();
+pub fn PracticeExam(
+    num_questions: usize,
+    feedback: bool,
+    category: String,
+    exclude: String,
+    tag: String,
+) -> Element {
+    let bank = use_bank();
+    let tags = use_tags();
+    // unknown categories are ignored rather than leaving nothing to practice
+    let filter = CategoryFilter::from_query(&category, &exclude);
+    let class = bank.class;
+    let mut restarts = use_signal(|| 0);
+    let spec = use_memo(use_reactive!(|num_questions, category, exclude, class| {
+        let _ = restarts();
+        ExamSpec {
+            class,
+            num_questions,
+            categories: CategoryFilter::from_query(&category, &exclude).categories(),
+            seed: rand_pcg::Pcg64::from_os_rng().random(),
+            ..Default::default()
+        }
+    }));
+    let exam = use_generated_exam(bank.clone(), spec);
+    if num_questions == 0 {
+        return rsx! {
+            NotFound { segments: vec!["pratice_exam".to_string()] }
+        };
+    }
+    let tagged = (!tag.is_empty()).then(|| tags.read().nums(&tag));
+    let mut questions = bank.class_questions();
+    questions.retain(|q| filter.matches(q));
+    questions.retain(|q| tagged.as_ref().is_none_or(|nums| nums.contains(&q.num)));
+    if questions.is_empty() {
+        return rsx! {
+            div { class: "empty-exam",
+                "אין שאלות שמתאימות לסינון הקטגוריות."
+                Link { to: Route::MainPage, "חזרה לדף הראשי" }
+            }
+        };
+    }
+    // the tags are the user's, so a tagged exam is drawn here rather than by the server
+    let questions = if tagged.is_some() {
+        questions
+    } else {
+        let Some(exam) = exam else {
+            return rsx! {
+                ExamLoading {}
+            };
+        };
+        exam.questions
+    };
+    let filter_description = [
+        filter.description_he(),
+        (!tag.is_empty()).then(|| format!("תגית: {tag}")),
+    ]
+    .into_iter()
+    .flatten()
+    .reduce(|all, description| format!("{all} · {description}"));
     rsx! {
-        Exam { exam_questions: Unchangable(exam_questions), num_questions }
+        Exam {
+            key: "{spec.read().id()}",
+            kind: ExamKind::Practice,
+            questions: Unchangable(questions),
+            selection: QuestionSelection::Random { num_questions },
+            immediate_feedback: feedback,
+            filter_description,
+            on_restart: move |_| restarts += 1,
+        }
     }
 }
 
+/// An exam made of an explicit list of questions, given by their official numbers.
 #[component]
-fn Exam(exam_questions: Unchangable<ExamQuestions>, num_questions: usize) -> Element {
-    // it's in a signal to prevent regenerating a new rng.
-    let mut rng = use_signal(|| rand_pcg::Pcg64::from_os_rng());
-    let mut show_correct_answers = use_signal(|| false);
-    let b_questions = exam_questions
-        .0
-        .questions
-        .iter()
-        .filter(|s| {
-            s.license_classes
-                .contains(&theory_test_parser::question_parser::LicenseClass::B)
-        })
-        .cloned()
-        .collect::<Vec<Question>>();
-    let questions = use_memo(move || {
-        let mut questions = b_questions
-            .choose_multiple(&mut rng(), num_questions)
+pub fn Challenge(qs: String) -> Element {
+    let nav = navigator();
+    let bank = use_bank();
+    let exam_questions = &bank.questions;
+    let questions = challenge::decode(&qs)
+        .map_err(|e| e.to_string())
+        .and_then(|nums| {
+            let mut unknown = Vec::new();
+            let mut questions = Vec::with_capacity(nums.len());
+            for num in nums {
+                match exam_questions.questions.iter().find(|q| q.num == num) {
+                    Some(question) => questions.push(question.clone()),
+                    None => unknown.push(format!("{:04}", num)),
+                }
+            }
+            if unknown.is_empty() {
+                Ok(questions)
+            } else {
+                Err(format!("שאלות לא קיימות: {}", unknown.join(", ")))
+            }
+        });
+
+    match questions {
+        Ok(questions) => rsx! {
+            Exam {
+                // a new challenge is a new exam, not an update of the current one
+                key: "{qs}",
+                kind: ExamKind::Practice,
+                questions: Unchangable(questions),
+                selection: QuestionSelection::Fixed,
+            }
+        },
+        Err(error) => rsx! {
+            div { class: "exam-body",
+                h1 { "קישור האתגר אינו תקין" }
+                div { {error} }
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        },
+    }
+}
+
+/// How the questions of an exam are picked out of the questions given to it.
+#[derive(Clone, Copy, PartialEq)]
+enum QuestionSelection {
+    /// A random sample of `num_questions` questions.
+    Random { num_questions: usize },
+    /// All of the questions, in the given order.
+    Fixed,
+}
+
+/// Pick the questions of an exam out of `pool`, present randomly picked ones in `order`, and
+/// shuffle their answers.
+fn generate_questions(
+    pool: &[Question],
+    selection: QuestionSelection,
+    order: QuestionOrder,
+    rng: &mut impl rand::Rng,
+) -> Vec<Question> {
+    let mut questions = match selection {
+        QuestionSelection::Random { num_questions } => {
+            let mut questions = pool
+                .choose_multiple(rng, num_questions)
+                .cloned()
+                .collect::<Vec<Question>>();
+            order.arrange(&mut questions);
+            questions
+        }
+        QuestionSelection::Fixed => pool.to_vec(),
+    };
+    // shuffle questions
+    for question in questions.iter_mut() {
+        // a malformed question is kept as is, and shows as void in the exam
+        let Some(correct_answer_str) = question
+            .answers
+            .possible_answers
+            .get(question.answers.correct_answer)
             .cloned()
-            .collect::<Vec<Question>>();
-        // shuffle questions
-        for question in questions.iter_mut() {
-            let correct_answer_str = question
-                .answers
-                .possible_answers
-                .get(question.answers.correct_answer)
-                .unwrap()
-                .clone();
-            question.answers.possible_answers.shuffle(&mut rng());
-            question.answers.correct_answer = question
-                .answers
-                .possible_answers
-                .iter()
-                .enumerate()
-                .find(|(_, q)| q.as_str() == correct_answer_str.as_str())
-                .unwrap()
-                .0;
+        else {
+            continue;
+        };
+        question.answers.possible_answers.shuffle(rng);
+        question.answers.correct_answer = question
+            .answers
+            .possible_answers
+            .iter()
+            .enumerate()
+            .find(|(_, q)| q.as_str() == correct_answer_str.as_str())
+            .unwrap()
+            .0;
+    }
+    questions
+}
+
+#[component]
+fn Exam(
+    kind: ExamKind,
+    questions: Unchangable<Vec<Question>>,
+    selection: QuestionSelection,
+    time_limit: Option<Duration>,
+    /// The exam can't be graded before every question is answered or the time runs out, and
+    /// leaving it before it is graded records it as abandoned.
+    #[props(default)]
+    strict: bool,
+    /// Reveal each question as soon as it is answered.
+    #[props(default)]
+    immediate_feedback: bool,
+    /// What the questions were drawn from, when not from the whole bank.
+    filter_description: Option<String>,
+    /// Called with the questions and the result once the exam is graded.
+    on_graded: Option<EventHandler<(Vec<Question>, ExamResult)>>,
+    /// The official rules the exam is taken by, which grade it.
+    rules: Option<ExamRules>,
+    /// The id of the spec the questions were generated from, which a certificate of a passed
+    /// real exam refers to.
+    exam_id: Option<String>,
+    /// Restarting draws a new exam through this instead of out of `questions`, for exams whose
+    /// questions are already the drawn ones.
+    on_restart: Option<EventHandler>,
+) -> Element {
+    // it's in a signal to prevent regenerating a new rng.
+    let mut rng = use_signal(rand_pcg::Pcg64::from_os_rng);
+    let pool = questions.0;
+    let mut exam_store = use_exam_store();
+    let route = use_route::<Route>();
+    let store_key = route.to_string();
+    let restored = use_hook(|| exam_store.get(&store_key));
+    let is_restored = restored.is_some();
+    let mut preferences = use_preferences();
+    let bank = use_bank();
+    // the class whose questions the exam has, the bank's unless it is taken by another's rules
+    let class = rules.map_or(bank.class, |rules| rules.class);
+    // the real exam is always graded by the official rule
+    let pass_threshold = if kind == ExamKind::Real {
+        PassThreshold::Official
+    } else {
+        preferences.peek().pass_threshold
+    };
+    let mut exam_state = use_signal(|| {
+        restored
+            .map(|mut restored| {
+                restored.resume(timer::now());
+                restored
+            })
+            .unwrap_or_else(|| {
+                let order = preferences.peek().question_order;
+                ExamState::new(
+                    generate_questions(&pool, selection, order, &mut *rng.write()),
+                    timer::now(),
+                )
+                .with_time_limit(time_limit)
+                .with_immediate_feedback(immediate_feedback)
+                .with_pass_threshold(pass_threshold)
+                .with_rules(rules)
+            })
+    });
+    let scroll_key = store_key.clone();
+    use_effect(move || {
+        exam_store::track_scroll(&scroll_key);
+        if is_restored {
+            exam_store::restore_scroll(&scroll_key);
         }
-        questions
     });
-
-    let mut user_selections = Vec::with_capacity(num_questions);
-    for _ in 1..=num_questions {
-        // NOTE: THIS IS ONLY FINE BECAUSE NUM_QUESTIONS DOES NOT CHANGE WITHIN THE COMPONENT.
-        user_selections.push(use_signal(|| None));
-    }
-    let user_selections = std::rc::Rc::new(user_selections);
-    let user_selections_clone = user_selections.clone();
-    let questions_clone = questions.clone();
-    let correct_answers = use_memo(move || {
-        let mut sum = 0;
-        for (question, user_selection) in questions_clone.iter().zip(user_selections_clone.iter()) {
-            if user_selection().is_some_and(|s| s == question.answers.correct_answer) {
-                sum += 1;
-            }
+    let pause_key = store_key.clone();
+    use_effect(move || {
+        exam_store.save(store_key.clone(), exam_state.read().clone());
+    });
+    // the time away from the exam isn't part of it
+    use_drop(move || exam_store.pause(&pause_key, timer::now()));
+    let exam_questions = use_memo(move || exam_state.read().questions().to_vec());
+    let result = exam_state.read().result().cloned();
+    let num_questions = exam_state.read().questions().len();
+    // the selections live in the exam state, so unmounting a question loses nothing
+    let virtual_window = use_virtual_window(num_questions);
+    let is_mounted = move |idx| virtual_window.is_none_or(|window| window.read().is_mounted(idx));
+    let placeholder_height = move |idx| {
+        virtual_window
+            .map(|window| window.read().placeholder_height(idx))
+            .unwrap_or_default()
+    };
+    let numbering = preferences.read().question_numbering;
+    // the category is a hint which the real exam doesn't give
+    let hide_category = match kind {
+        ExamKind::Real => true,
+        ExamKind::Practice
+        | ExamKind::DailyQuestion
+        | ExamKind::Drill
+        | ExamKind::SignDrill
+        | ExamKind::Imported => preferences.read().hide_category_during_exam,
+    };
+    // only immediate feedback gives cues, they would give away answers when grading at the end
+    let sounds = immediate_feedback && kind != ExamKind::Real && preferences.read().sound_feedback;
+    let auto_advance = preferences.read().auto_advance;
+    // the time gives nothing away, unlike answer cues
+    let timer_sounds = preferences.read().sound_feedback;
+    let mut history = use_history();
+    let question_stats = use_memo(move || history.read().question_stats());
+    // recorded up front so leaving the exam in any way leaves it abandoned in the history,
+    // grading replaces it
+    use_effect(move || {
+        if strict && !is_restored {
+            history
+                .write()
+                .record(Attempt::abandoned(&exam_state.peek(), kind));
         }
-        sum
     });
+    let graded = result.is_some();
+    let leave_warning = exam_store::leave_warning(&exam_state.read(), strict);
+    let leave_guard = use_leave_guard();
+    use_effect({
+        let leave_guard = leave_guard.clone();
+        use_reactive!(|(leave_warning)| {
+            match leave_warning {
+                Some(message) => leave_guard.guard(message),
+                None => leave_guard.stop(),
+            }
+        })
+    });
+    use_drop(move || leave_guard.stop());
+    let can_submit = !strict || exam_state.read().all_answered();
+    // every question which can appear in exams, to evaluate achievements against
+    let exam_pool = use_signal(|| bank.class_questions());
+    let mut unlocked = use_achievements();
+    let mut toasts = use_toast();
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let mut bests = use_personal_bests();
+    // whether the graded exam is a new personal best, and the best before it
+    let mut best_outcome = use_signal(|| None::<(bool, Option<BestScore>)>);
+    let mut reviewing = use_signal(|| false);
+    let mut submit = move || {
+        let now = timer::now();
+        {
+            let exam_state = exam_state.read();
+            if exam_state.is_graded()
+                || (strict && !exam_state.all_answered() && !exam_state.is_expired(now))
+            {
+                return;
+            }
+        }
+        let correct = exam_state.write().grade(now).correct();
+        if let Some(on_graded) = on_graded {
+            let exam_state = exam_state.read();
+            if let Some(result) = exam_state.result() {
+                on_graded((exam_state.questions().to_vec(), result.clone()));
+            }
+        }
+        if let Some(attempt) = Attempt::from_exam(&exam_state.read(), kind, strict, now) {
+            history.write().record(attempt);
+        }
+        let config = ExamConfig {
+            kind,
+            num_questions,
+            class,
+        };
+        let previous = bests.peek().get(&config);
+        let is_best = bests.write().record(
+            &config,
+            BestScore {
+                correct,
+                graded_at: now,
+            },
+        );
+        best_outcome.set(Some((is_best, previous)));
+        let newly_unlocked = unlocked.write().update(
+            &AchievementContext {
+                history: &history.read(),
+                pool: &exam_pool.read(),
+                utc_offset_secs: utc_offset().unwrap_or(0),
+            },
+            now,
+        );
+        for achievement in newly_unlocked {
+            toasts.success(format!("🏆 הישג חדש: {}", achievement.title_he()));
+        }
+    };
+
+    // the skipped questions are gone over once more before the exam is submitted
+    let mut request_submit = move || {
+        if let Some(skipped) = exam_state.write().start_revisit() {
+            toasts.info("לפני ההגשה, חזרה לשאלות שדילגת עליהן");
+            results_grid::focus_question(skipped);
+        } else {
+            submit();
+        }
+    };
+
+    // once the time runs out the answers are frozen, and the exam is graded after a grace period
+    let frozen = exam_state.read().is_frozen();
+    use_effect(use_reactive!(|(frozen)| {
+        if frozen {
+            spawn(async move {
+                timer::sleep(TIME_UP_GRACE_PERIOD).await;
+                submit();
+            });
+        }
+    }));
 
     rsx! {
 
-        div { dir: "rtl", class: "exam-body",
-            for (question_num , (question , user_selection)) in questions.iter().zip(user_selections.iter().cloned()).enumerate() {
-                div { margin_bottom: "100px",
-                    ExamQuestion {
-                        question: question.clone(),
-                        show_correct_answer: show_correct_answers.read().clone(),
-                        user_selection,
-                        question_num: question_num + 1,
-                        show_question_num: true,
-                        use_canonical_question_num: false,
+        div {
+            class: "exam-body",
+            style: "--question-zoom: {preferences.read().question_zoom.scale()}",
+            ExamHeader {
+                num_answered: exam_state.read().num_answered(),
+                num_questions,
+                num_flagged: exam_state.read().num_flagged(),
+                deadline: exam_state.read().deadline(),
+                time_warnings: exam_state.read().time_warnings().to_vec(),
+                graded,
+                can_submit,
+                mistakes_left: immediate_feedback.then(|| exam_state.read().mistakes_left()),
+                filter_description,
+                bank_name: bank.name_he,
+                sounds: timer_sounds,
+                numbering,
+                on_numbering_change: move |numbering| {
+                    preferences.write().question_numbering = numbering;
+                },
+                zoom: preferences.read().question_zoom,
+                on_zoom_change: move |zoom| {
+                    preferences.write().question_zoom = zoom;
+                },
+                on_submit: move |_| request_submit(),
+                on_time_up: move |_| exam_state.write().freeze(),
+                on_time_warning: move |_| {
+                    // given once, even if the header is shown again
+                    if let Some(warning) = exam_state.write().take_time_warning(timer::now()) {
+                        toasts.info(tr!("minutes-left", minutes = warning.minutes()));
+                        if timer_sounds {
+                            sound::play(sound::Cue::TimeWarning);
+                        }
+                    }
+                },
+                can_undo: exam_state.read().can_undo(),
+                can_redo: exam_state.read().can_redo(),
+                on_undo: move |_| {
+                    let undone = exam_state.write().undo(timer::now());
+                    if let Some(question) = undone {
+                        results_grid::focus_question(question);
+                    }
+                },
+                on_redo: move |_| {
+                    let redone = exam_state.write().redo(timer::now());
+                    if let Some(question) = redone {
+                        results_grid::focus_question(question);
+                    }
+                },
+            }
+            if exam_state.read().is_frozen() {
+                div { class: "time-up-overlay",
+                    div { class: "time-up-dialog",
+                        h2 { "הזמן נגמר — המבחן יוגש אוטומטית" }
+                        button {
+                            class: "button-primary",
+                            font_size: "large",
+                            onclick: move |_| submit(),
+                            "הגש עכשיו"
+                        }
                     }
-
                 }
             }
-            button {
-                class: "button-primary",
-                font_size: "large",
-                onclick: move |_| {
-                    *show_correct_answers.write() = true;
-                },
-                "בדוק מבחן"
+            if let Some(result) = result.as_ref().filter(|_| reviewing()) {
+                div { class: "review-overlay",
+                    ReviewStepper {
+                        questions: exam_questions,
+                        result: result.clone(),
+                        selections: (0..num_questions).map(|i| exam_state.read().selection(i)).collect::<Vec<_>>(),
+                        language: bank.language,
+                        numbering,
+                        on_exit: move |_| reviewing.set(false),
+                    }
+                }
             }
-            if show_correct_answers() {
-                div {
+            div { class: "exam-layout",
+                // only shown on wide screens, the questions stay in the same container
+                // on every width so they are never remounted when the layout changes
+                aside { class: "exam-sidebar",
+                    QuestionNavigator {
+                        questions: exam_questions,
+                        answered: (0..num_questions).map(|i| exam_state.read().selection(i).is_some()).collect::<Vec<_>>(),
+                        flagged: (0..num_questions).map(|i| exam_state.read().is_flagged(i)).collect::<Vec<_>>(),
+                        numbering,
+                    }
+                    div { {format!("נענו {} מתוך {}", exam_state.read().num_answered(), num_questions)} }
+                    if exam_state.read().num_flagged() > 0 {
+                        div { {format!("מסומנות: {}", exam_state.read().num_flagged())} }
+                    }
+                    if let Some(result) = &result {
+                        div { {tr!("correct-count", correct = result.correct(), total = result.num_questions())} }
+                        div { {format!("זמן: {}", timer::format_duration(result.elapsed))} }
+                        if result.timed_out {
+                            div { class: "timed-out", "המבחן הוגש אוטומטית בתום הזמן" }
+                        }
+                    }
+                }
+                div { class: "exam-questions",
+                    for (question_idx , question) in exam_questions.read().iter().enumerate() {
+                        div {
+                            id: results_grid::question_element_id(question_idx),
+                            margin_bottom: "100px",
+                            "data-virtual-idx": "{question_idx}",
+                            "data-virtual-mounted": "{is_mounted(question_idx)}",
+                            if !is_mounted(question_idx) {
+                                div {
+                                    class: "question-placeholder",
+                                    height: "{placeholder_height(question_idx)}px",
+                                }
+                            } else {
+                                QuestionBoundary { num: question.num, question_num: question_idx + 1,
+                                    ExamQuestion {
+                                        question: question.clone(),
+                                        language: bank.language,
+                                        show_correct_answer: exam_state.read().is_revealed(question_idx),
+                                        user_selection: exam_state.read().selection(question_idx),
+                                        on_select: move |answer| {
+                                            let accepted = exam_state.write().select(question_idx, answer, timer::now());
+                                            // a revealed answer is left in view to be read
+                                            if accepted && auto_advance && !exam_state.read().is_revealed(question_idx) {
+                                                match exam_state.read().next_unanswered(question_idx) {
+                                                    Some(next) => results_grid::focus_question(next),
+                                                    None => results_grid::focus_submit(),
+                                                }
+                                            }
+                                            if accepted && sounds && exam_state.read().is_revealed(question_idx) {
+                                                sound::play(
+                                                    if answer == exam_questions.read()[question_idx].answers.correct_answer {
+                                                        sound::Cue::Correct
+                                                    } else {
+                                                        sound::Cue::Wrong
+                                                    },
+                                                );
+                                            }
+                                        },
+                                        on_clear: move |_| {
+                                            exam_state.write().clear_selection(question_idx, timer::now());
+                                        },
+                                        confidence: exam_state.read().confidence(question_idx),
+                                        on_confidence: move |confidence| {
+                                            exam_state.write().set_confidence(question_idx, confidence, timer::now());
+                                        },
+                                        flagged: exam_state.read().is_flagged(question_idx),
+                                        on_toggle_flag: move |_| {
+                                            exam_state.write().toggle_flag(question_idx, timer::now());
+                                        },
+                                        times_skipped: exam_state.read().times_skipped(question_idx),
+                                        on_skip: move |_| {
+                                            if exam_state.write().skip(question_idx, timer::now()) {
+                                                match exam_state.read().next_after_skip(question_idx) {
+                                                    Some(next) => results_grid::focus_question(next),
+                                                    None => results_grid::focus_submit(),
+                                                }
+                                            }
+                                        },
+                                        question_num: question_idx + 1,
+                                        show_category: !hide_category || result.is_some(),
+                                        category_link: result.is_some(),
+                                        show_license_classes: kind == ExamKind::Practice || result.is_some(),
+                                        selected_license_class: class,
+                                        show_note: result.is_some(),
+                                        show_tags: result.is_some(),
+                                        show_history: result.is_some(),
+                                        // an aid the real exam doesn't give
+                                        show_translation: !(kind == ExamKind::Real && strict),
+                                        self_stats: result
+                                            .is_some()
+                                            .then(|| question_stats.read().get(&question.num).copied())
+                                            .flatten(),
+                                        show_question_num: true,
+                                        use_canonical_question_num: numbering == QuestionNumbering::Canonical,
+                                    }
+                                }
+                            }
+                        }
+                    }
                     button {
+                        id: results_grid::SUBMIT_ELEMENT_ID,
                         class: "button-primary",
                         font_size: "large",
-                        onclick: move |_| {
-                            // reset all states
-                            rng.set(rand_pcg::Pcg64::from_os_rng());
-                            for mut signal in user_selections.iter().cloned() {
-                                signal.set(None);
-                            }
-                            show_correct_answers.set(false);
-                            document::eval(r#"window.scrollTo(0, 0);"#);
+                        disabled: !can_submit,
+                        title: if !can_submit { "יש לענות על כל השאלות לפני הגשת המבחן" },
+                        onclick: move |_| request_submit(),
+                        "בדוק מבחן"
+                    }
+                    if let Some(result) = result {
+                        div {
+                            button {
+                                class: "button-primary",
+                                font_size: "large",
+                                onclick: move |_| {
+                                    document::eval(r#"window.scrollTo(0, 0);"#);
+                                    // the new exam replaces this one, which is left as graded
+                                    if let Some(on_restart) = on_restart {
+                                        exam_store.forget(&route);
+                                        on_restart(());
+                                        return;
+                                    }
+                                    // reset all states
+                                    rng.set(rand_pcg::Pcg64::from_os_rng());
+                                    let order = preferences.peek().question_order;
+                                    let questions = generate_questions(
+                                        &pool,
+                                        selection,
+                                        order,
+                                        &mut *rng.write(),
+                                    );
+                                    let state = ExamState::new(questions, timer::now())
+                                        .with_time_limit(time_limit)
+                                        .with_immediate_feedback(immediate_feedback)
+                                        .with_pass_threshold(pass_threshold)
+                                        .with_rules(rules);
+                                    // the new exam is left abandoned until graded, as the first was
+                                    if strict {
+                                        history.write().record(Attempt::abandoned(&state, kind));
+                                    }
+                                    exam_state.set(state);
+                                    best_outcome.set(None);
+                                    reviewing.set(false);
+                                },
+                                "התחל מבחן מחדש"
 
-                        },
-                        "התחל מבחן מחדש"
+                            }
+                            ShareResult {
+                                kind,
+                                bank_name: bank.name_he,
+                                questions: exam_questions,
+                                result: result.clone(),
+                            }
+                            DownloadResult { started_at: exam_state.read().started_at() }
+                            if let Some(exam_id) = exam_id.clone().filter(|_| kind == ExamKind::Real && result.passed()) {
+                                CertificateButton { result: result.clone(), exam_id }
+                            }
+                        }
+                        div { {tr!("correct-count", correct = result.correct(), total = result.num_questions())} }
+                        div { class: "pass-threshold",
+                            if let Some(rules) = result.rules {
+                                {
+                                    format!(
+                                        "כללי המבחן הרשמי לדרגה {}: {} שאלות, עד {} טעויות, {} דקות",
+                                        rules.class.code(),
+                                        rules.num_questions,
+                                        rules.max_mistakes,
+                                        rules.duration.as_secs() / 60,
+                                    )
+                                }
+                            } else if kind == ExamKind::Real {
+                                {format!("סף מעבר: {} (עד {} טעויות)", result.pass_threshold.description_he(), result.allowed_mistakes())}
+                            } else {
+                                {format!("סף מעבר: {}", result.pass_threshold.description_he())}
+                            }
+                        }
+                        div { class: if result.passed() { "verdict passed" } else { "verdict failed" },
+                            if result.passed() {
+                                "עברת"
+                            } else {
+                                "נכשלת"
+                            }
+                            {
+                                format!(
+                                    " {}",
+                                    tr!(
+                                        "mistakes-of-allowed",
+                                        mistakes = result.mistakes(),
+                                        allowed = result.allowed_mistakes(),
+                                    ),
+                                )
+                            }
+                        }
+                        if immediate_feedback {
+                            div {
+                                {
+                                    match exam_state.read().mistakes_left() {
+                                        Some(left) => tr!("mistakes-left-at-end", left = left),
+                                        None => "מונה הטעויות נגמר במהלך התרגול".to_string(),
+                                    }
+                                }
+                            }
+                        }
+                        if let Some((is_best, previous)) = best_outcome() {
+                            div { class: "personal-best",
+                                match previous {
+                                    None => "זהו הציון הראשון שלך בתצורה הזו".to_string(),
+                                    Some(previous) => {
+                                        format!(
+                                            "{}{}/{} ב־{}",
+                                            if is_best { "🎉 שיא אישי חדש! השיא הקודם: " } else { "השיא שלך: " },
+                                            previous.correct,
+                                            num_questions,
+                                            timer::format_date_time(previous.graded_at, utc_offset().unwrap_or(0)),
+                                        )
+                                    }
+                                }
+                            }
+                        }
+                        if !result.to_review().is_empty() {
+                            button {
+                                class: "button-primary",
+                                onclick: move |_| reviewing.set(true),
+                                "עבור על הטעויות"
+                            }
+                        }
+                        ConfidenceReport { questions: exam_questions, result: result.clone(), numbering }
+                        ResultsGrid { questions: exam_questions, result: result.clone(), numbering }
 
                     }
                 }
-                div { {format!("שאלות נכונות {}/{}", correct_answers(), num_questions)} }
-
             }
         }
     }
@@ -228,42 +1316,134 @@ fn Exam(exam_questions: Unchangable<ExamQuestions>, num_questions: usize) -> Ele
 #[component]
 pub fn ExamQuestion(
     question: Question,
+    /// The language of the question's text, which may differ from the UI's.
+    #[props(default = UI_LANGUAGE)]
+    language: &'static str,
     show_correct_answer: bool,
-    mut user_selection: Signal<Option<usize>>,
+    user_selection: Option<usize>,
+    on_select: EventHandler<usize>,
+    /// Allows clearing the selected answer while the correct answer is not shown.
+    on_clear: Option<EventHandler<()>>,
+    /// The confidence marked for the answer, only relevant with `on_confidence`.
+    confidence: Option<Confidence>,
+    /// Allows marking the confidence of the answer, while an answer is selected
+    /// and the correct answer is not shown.
+    on_confidence: Option<EventHandler<Option<Confidence>>>,
+    #[props(default)] flagged: bool,
+    /// Allows flagging the question to come back to it later.
+    on_toggle_flag: Option<EventHandler<()>>,
+    /// How many times the question was skipped, only relevant with `on_skip`.
+    #[props(default)]
+    times_skipped: usize,
+    /// Allows skipping the question while it is unanswered.
+    on_skip: Option<EventHandler<()>>,
     question_num: usize,
+    #[props(default = true)] show_category: bool,
+    /// Makes the category badge open the browse page of the category. Never while answering.
+    #[props(default)]
+    category_link: bool,
+    /// Show the license classes the question applies to.
+    #[props(default)]
+    show_license_classes: bool,
+    /// The class to highlight among the license classes.
+    selected_license_class: Option<LicenseClass>,
+    /// Makes the license class badges clickable.
+    on_license_class_click: Option<EventHandler<LicenseClass>>,
+    /// Called when the image of the question fails to load.
+    on_image_error: Option<EventHandler<()>>,
+    /// Show the private note of the question, with editing it. Never while answering a real exam.
+    #[props(default)]
+    show_note: bool,
+    /// Show the user's tags of the question, with editing them. Never while answering an exam.
+    #[props(default)]
+    show_tags: bool,
+    /// Allows peeking at the question in a second language, when the bank has a translation.
+    #[props(default)]
+    show_translation: bool,
+    /// How the question was answered in the history, shown under it.
+    self_stats: Option<QuestionStats>,
+    /// Show a button opening the history of the question. Never while answering an exam.
+    #[props(default)]
+    show_history: bool,
     show_question_num: bool,
     use_canonical_question_num: bool,
+    /// Read the question aloud when it's shown, for views which show one question at a time.
+    #[props(default)]
+    auto_read: bool,
 ) -> Element {
+    // shows in the enclosing QuestionBoundary, if any
+    check_question(&question)?;
     let correct_color = if show_correct_answer { "green" } else { "" };
     let wrong_color = if show_correct_answer { "red" } else { "" };
+    let speech_text = speech::speech_text(
+        question_body(&question),
+        &question.answers.possible_answers,
+        language,
+    );
     let question_str = if show_question_num {
         if use_canonical_question_num {
             question.question
         } else {
-            format!("{}. ", question_num) + &question.question.as_str()[6..]
+            format!("{}. {}", question_num, question_body(&question))
         }
     } else {
-        question.question.as_str()[6..].to_string()
+        question_body(&question).to_string()
     };
 
     rsx! {
-        div { class: "question-container",
+        div { class: "question-container", "data-question": question.num,
+            if let Some(on_toggle_flag) = on_toggle_flag {
+                button {
+                    class: "flag-button",
+                    class: if flagged { "active" },
+                    title: "סמן לבדיקה",
+                    onclick: move |_| on_toggle_flag(()),
+                    if flagged {
+                        "🚩 מסומנת"
+                    } else {
+                        "🏳 סמן"
+                    }
+                }
+            }
+            if show_translation {
+                TranslationButton { num: question.num }
+            }
+            speech::SpeakButton {
+                num: question.num,
+                text: speech_text,
+                language,
+                auto_read,
+            }
             h1 {
                 class: "question",
-                 {question_str} }
+                id: format!("question-title{}", question.num),
+                lang: language,
+                dir: direction(language),
+                {question_str}
+            }
             div {
-                if let Some(img) = question.image_url {
-                    img { src: img, margin_bottom: "20px" }
+                if let Some(url) = question.image_url {
+                    QuestionImage {
+                        num: question.num,
+                        url,
+                        alt: format!("תמונה לשאלה {}", question.num),
+                        on_error: on_image_error,
+                    }
                 }
 
-                div { class: "answers-container",
+                div {
+                    class: "answers-container",
+                    role: "radiogroup",
+                    aria_labelledby: format!("question-title{}", question.num),
+                    lang: language,
+                    dir: direction(language),
                     for (answer_num , answer) in question.answers.possible_answers.iter().enumerate() {
 
                         {
                             // for some reason naming it color makes dx fmt get rid of it
                             let colorr = if answer_num == question.answers.correct_answer {
                                 correct_color
-                            } else if user_selection().is_some_and(|s| s == answer_num) {
+                            } else if user_selection.is_some_and(|s| s == answer_num) {
                                 wrong_color
                             } else {
                                 ""
@@ -272,13 +1452,13 @@ pub fn ExamQuestion(
                                 label { color: colorr, class: "answer",
                                     input {
                                         oninput: move |_| {
-                                            user_selection.set(Some(answer_num));
+                                            on_select(answer_num);
                                         },
                                         r#type: "radio",
                                         class: "answer_input",
                                         id: format!("answer_input{}{}", question.num, answer_num),
                                         name: format!("{}", question.num),
-                                        checked: user_selection() == Some(answer_num),
+                                        checked: user_selection == Some(answer_num),
                                     }
                                     "{answer}"
 
@@ -290,8 +1470,86 @@ pub fn ExamQuestion(
 
                 }
 
-                div { class: "category",
-                    {format!("קטגוריה: {}", question.category.as_str_he())}
+                if let Some(on_skip) = on_skip.filter(|_| user_selection.is_none() && !show_correct_answer) {
+                    button { class: "skip-question", onclick: move |_| on_skip(()),
+                        "דלג"
+                    }
+                    if times_skipped > 0 {
+                        span { class: "skipped-badge",
+                            if times_skipped == 1 {
+                                "דילגת על שאלה זו"
+                            } else {
+                                "דילגת על שאלה זו פעמיים"
+                            }
+                        }
+                    }
+                }
+
+                if let Some(on_clear) = on_clear.filter(|_| user_selection.is_some() && !show_correct_answer) {
+                    button { class: "clear-selection", onclick: move |_| on_clear(()), "נקה בחירה" }
+                }
+
+                if let Some(on_confidence) = on_confidence.filter(|_| user_selection.is_some() && !show_correct_answer) {
+                    div { class: "confidence-chips",
+                        for (chip_confidence , label) in [(Confidence::Confident, "בטוח"), (Confidence::Guess, "ניחוש")] {
+                            button {
+                                class: "confidence-chip",
+                                class: if confidence == Some(chip_confidence) { "active" },
+                                onclick: move |_| {
+                                    // clicking the active chip clears the mark
+                                    if confidence == Some(chip_confidence) {
+                                        on_confidence(None);
+                                    } else {
+                                        on_confidence(Some(chip_confidence));
+                                    }
+                                },
+                                {label}
+                            }
+                        }
+                    }
+                }
+
+                if show_category {
+                    div { class: "category",
+                        "קטגוריה: "
+                        CategoryBadge { category: question.category, clickable: category_link }
+                    }
+                }
+
+                if show_license_classes {
+                    div { class: "license-classes",
+                        for class in question.license_classes.iter().copied() {
+                            button {
+                                class: "license-class",
+                                class: if selected_license_class == Some(class) { "selected" },
+                                disabled: on_license_class_click.is_none(),
+                                onclick: move |_| {
+                                    if let Some(on_license_class_click) = on_license_class_click {
+                                        on_license_class_click(class);
+                                    }
+                                },
+                                {format!("«{}»", class.code())}
+                            }
+                        }
+                        if question.license_classes.is_empty() {
+                            span { class: "license-class-missing", "ללא סיווג" }
+                        }
+                    }
+                }
+
+                if show_note {
+                    QuestionNote { num: question.num }
+                }
+                if show_tags {
+                    QuestionTags { num: question.num }
+                }
+
+                if let Some(self_stats) = self_stats {
+                    div { class: "self-stats", {self_stats.description_he()} }
+                }
+
+                if show_history {
+                    QuestionHistory { num: question.num, flagged, on_toggle_flag }
                 }
 
             }
@@ -299,3 +1557,118 @@ pub fn ExamQuestion(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use theory_test_parser::question_parser::Answers;
+
+    #[test]
+    fn unknown_paths_are_not_found() {
+        assert!(matches!(Route::from_str("/"), Ok(Route::MainPage)));
+        assert!(matches!(
+            Route::from_str("/pratice_exam?num_questions=20"),
+            Ok(Route::PracticeExam {
+                num_questions: 20,
+                feedback: false,
+                category,
+                exclude,
+                tag,
+            }) if category.is_empty() && exclude.is_empty() && tag.is_empty()
+        ));
+        // a stored practice configuration reruns as the exam it was
+        let config = PracticeConfig::new(
+            12,
+            true,
+            &CategoryFilter {
+                include: vec![QuestionCategory::Safety],
+                exclude: Vec::new(),
+            },
+        );
+        assert!(
+            Route::from(&config)
+                == Route::PracticeExam {
+                    num_questions: 12,
+                    feedback: true,
+                    category: QuestionCategory::Safety.as_str_he().to_string(),
+                    exclude: String::new(),
+                    tag: String::new(),
+                }
+        );
+        match Route::from_str("/old/route") {
+            Ok(Route::NotFound { segments }) => assert_eq!(segments, vec!["old", "route"]),
+            _ => panic!("an unknown path should map to NotFound"),
+        }
+    }
+
+    #[test]
+    fn broken_question_is_isolated() {
+        fn question(num: usize, correct_answer: usize) -> Question {
+            Question {
+                question: format!("{:04}. question {}", num, num),
+                answers: Answers {
+                    possible_answers: vec!["yes".to_string(), "no".to_string()],
+                    correct_answer,
+                },
+                ..Question::sample(num, correct_answer)
+            }
+        }
+        fn app() -> Element {
+            let questions = vec![question(1, 0), question(2, 5), question(3, 1)];
+            rsx! {
+                for (idx , question) in questions.into_iter().enumerate() {
+                    QuestionBoundary { num: question.num, question_num: idx + 1,
+                        ExamQuestion {
+                            question,
+                            show_correct_answer: false,
+                            user_selection: None,
+                            on_select: |_| {},
+                            question_num: idx + 1,
+                            show_question_num: true,
+                            use_canonical_question_num: false,
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut dom = VirtualDom::new(app);
+        let mut edits = dom.rebuild_to_vec().edits;
+        // the boundary renders the error card in the next pass
+        edits.extend(dom.render_immediate_to_vec().edits);
+        let texts = edits
+            .into_iter()
+            .filter_map(|edit| match edit {
+                dioxus::dioxus_core::Mutation::CreateTextNode { value, .. } => Some(value),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(texts.iter().any(|t| t == "שאלה 2 לא ניתנת להצגה"));
+        // the questions around it still render
+        assert!(texts.iter().any(|t| t == "1. question 1"));
+        assert!(texts.iter().any(|t| t == "3. question 3"));
+        assert!(!texts.iter().any(|t| t.contains("question 2")));
+
+        // the exam is graded as one of the questions around it
+        let questions = vec![question(1, 0), question(2, 5), question(3, 1)];
+        let mut exam = ExamState::new(questions, Duration::ZERO);
+        exam.select(0, 0, Duration::ZERO);
+        exam.select(2, 0, Duration::ZERO);
+        assert!(exam.all_answered());
+        let result = exam.grade(Duration::ZERO);
+        assert_eq!(
+            result.outcomes,
+            vec![
+                exam_state::QuestionOutcome::Correct,
+                exam_state::QuestionOutcome::Void,
+                exam_state::QuestionOutcome::Wrong,
+            ]
+        );
+        assert_eq!(result.num_questions(), 2);
+        assert_eq!(result.mistakes(), 1);
+        assert_eq!(result.allowed_mistakes(), exam_state::allowed_mistakes(2));
+        assert!(!result.passed());
+    }
+}
@@ -0,0 +1,234 @@
+//! A marathon: several full exams back to back, with the running totals between them.
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+use theory_test_parser::{
+    exam::ExamRules,
+    question_parser::{Question, QuestionCategory},
+};
+
+use crate::{
+    bank::use_bank,
+    exam_state::{ExamResult, QuestionOutcome, REAL_EXAM_NUM_QUESTIONS},
+    not_found::NotFound,
+    Exam, ExamKind, QuestionSelection, Route, Unchangable,
+};
+
+/// The most exams in a marathon.
+pub const MAX_EXAMS: usize = 5;
+pub const DEFAULT_EXAMS: usize = 3;
+
+/// A graded exam of the marathon.
+#[derive(Clone, PartialEq)]
+struct MarathonExam {
+    questions: Vec<Question>,
+    result: ExamResult,
+}
+
+/// The questions to draw the next exam from: those no earlier exam of the marathon had, or the
+/// whole pool once too few of them are left.
+fn marathon_pool(pool: &[Question], used: &HashSet<usize>, num_questions: usize) -> Vec<Question> {
+    let unused = pool
+        .iter()
+        .filter(|q| !used.contains(&q.num))
+        .cloned()
+        .collect::<Vec<_>>();
+    if unused.len() >= num_questions {
+        unused
+    } else {
+        pool.to_vec()
+    }
+}
+
+/// The category answered correctly the least across `exams`, with its percent of correct
+/// answers.
+fn weakest_category(exams: &[MarathonExam]) -> Option<(QuestionCategory, usize)> {
+    QuestionCategory::ALL
+        .into_iter()
+        .filter_map(|category| {
+            let (total, correct) = exams
+                .iter()
+                .flat_map(|exam| exam.questions.iter().zip(&exam.result.outcomes))
+                .filter(|(q, outcome)| q.category == category && **outcome != QuestionOutcome::Void)
+                .fold((0, 0), |(total, correct), (_, outcome)| {
+                    (
+                        total + 1,
+                        correct + usize::from(*outcome == QuestionOutcome::Correct),
+                    )
+                });
+            (total > 0).then(|| (category, correct * 100 / total))
+        })
+        .min_by_key(|(_, percent)| *percent)
+}
+
+/// The exam numbered `step` (from 0) of a marathon of `count` exams.
+#[component]
+pub fn Marathon(count: usize, step: usize) -> Element {
+    let bank = use_bank();
+    let nav = navigator();
+    // kept while moving between the exams, which only changes the props
+    let mut exams = use_signal(Vec::<Option<MarathonExam>>::new);
+    if count == 0 || count > MAX_EXAMS || step >= count {
+        return rsx! {
+            NotFound { segments: vec!["marathon".to_string()] }
+        };
+    }
+    let used = exams
+        .read()
+        .iter()
+        .take(step)
+        .flatten()
+        .flat_map(|exam| exam.questions.iter().map(|q| q.num))
+        .collect::<HashSet<_>>();
+    let pool = marathon_pool(&bank.class_questions(), &used, REAL_EXAM_NUM_QUESTIONS);
+    let graded = exams.read().iter().flatten().cloned().collect::<Vec<_>>();
+    let is_done = exams.read().get(step).is_some_and(Option::is_some);
+    let total_correct = graded
+        .iter()
+        .map(|exam| exam.result.correct())
+        .sum::<usize>();
+    let total_questions = graded
+        .iter()
+        .map(|exam| exam.result.num_questions())
+        .sum::<usize>();
+
+    rsx! {
+        div { class: "marathon",
+            h2 { {format!("מרתון: מבחן {} מתוך {}", step + 1, count)} }
+            if is_done {
+                div { class: "marathon-summary",
+                    if step + 1 == count {
+                        h2 { "סיכום המרתון" }
+                    }
+                    for (idx , exam) in graded.iter().enumerate() {
+                        div {
+                            {
+                                format!(
+                                    "מבחן {}: {}/{} {}",
+                                    idx + 1,
+                                    exam.result.correct(),
+                                    exam.result.num_questions(),
+                                    if exam.result.passed() { "✅ עבר" } else { "❌ נכשל" },
+                                )
+                            }
+                        }
+                    }
+                    div { {format!("סך הכל נכונות: {}/{}", total_correct, total_questions)} }
+                    if step + 1 == count {
+                        if let Some((category, percent)) = weakest_category(&graded) {
+                            div { class: "marathon-weakest",
+                                {format!("הקטגוריה החלשה ביותר: {} ({}% נכונות)", category.as_str_he(), percent)}
+                            }
+                        }
+                        Link { class: "main-page-link", to: Route::MainPage, "חזרה לדף הראשי" }
+                    } else {
+                        button {
+                            class: "button-primary",
+                            font_size: "large",
+                            onclick: move |_| {
+                                nav.push(Route::Marathon {
+                                    count,
+                                    step: step + 1,
+                                });
+                            },
+                            "למבחן הבא"
+                        }
+                    }
+                }
+            }
+        }
+        Exam {
+            key: "{step}",
+            kind: ExamKind::Real,
+            questions: Unchangable(pool),
+            selection: QuestionSelection::Random {
+                num_questions: REAL_EXAM_NUM_QUESTIONS,
+            },
+            // marathons are of exams of the bank's class, like their pool
+            time_limit: ExamRules::official(bank.class).duration,
+            on_graded: move |(questions, result)| {
+                let mut exams = exams.write();
+                if exams.len() <= step {
+                    exams.resize(step + 1, None);
+                }
+                exams[step] = Some(MarathonExam { questions, result });
+                document::eval("window.scrollTo(0, 0);");
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::exam_state::PassThreshold;
+
+    fn exam(questions: Vec<Question>, outcomes: Vec<QuestionOutcome>) -> MarathonExam {
+        let len = outcomes.len();
+        MarathonExam {
+            questions,
+            result: ExamResult {
+                outcomes,
+                confidences: vec![None; len],
+                flags: vec![false; len],
+                timed_out: false,
+                elapsed: Duration::ZERO,
+                pass_threshold: PassThreshold::Official,
+                rules: None,
+            },
+        }
+    }
+
+    #[test]
+    fn pools() {
+        let pool = (1..=4)
+            .map(|num| Question::sample(num, 0))
+            .collect::<Vec<_>>();
+        let used = HashSet::from([1, 2]);
+        let nums = |questions: Vec<Question>| questions.iter().map(|q| q.num).collect::<Vec<_>>();
+        assert_eq!(nums(marathon_pool(&pool, &used, 2)), vec![3, 4]);
+        // too few unused questions, repeating is unavoidable
+        assert_eq!(nums(marathon_pool(&pool, &used, 3)), vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn weakest() {
+        use QuestionOutcome::*;
+        let first = exam(
+            vec![
+                Question::sample(1, 0),
+                Question {
+                    category: QuestionCategory::RoadSigns,
+                    ..Question::sample(2, 0)
+                },
+            ],
+            vec![Correct, Wrong],
+        );
+        let second = exam(
+            vec![
+                Question {
+                    category: QuestionCategory::RoadSigns,
+                    ..Question::sample(3, 0)
+                },
+                Question::sample(4, 0),
+                Question {
+                    category: QuestionCategory::CarKnowledge,
+                    ..Question::sample(5, 0)
+                },
+            ],
+            vec![Correct, Wrong, Void],
+        );
+        // 1 of 2 road signs and of 2 safety, road signs come first in the list of categories
+        assert_eq!(
+            weakest_category(&[first.clone(), second]),
+            Some((QuestionCategory::RoadSigns, 50))
+        );
+        assert_eq!(
+            weakest_category(&[first]),
+            Some((QuestionCategory::RoadSigns, 0))
+        );
+        assert_eq!(weakest_category(&[]), None);
+    }
+}
@@ -0,0 +1,49 @@
+use dioxus::{logger::tracing, prelude::*};
+
+use crate::Route;
+
+/// The page of any path which doesn't match a route, or of a route with invalid parameters.
+#[component]
+pub fn NotFound(segments: Vec<String>) -> Element {
+    let nav = navigator();
+    use_hook(|| tracing::warn!("no page at /{}", segments.join("/")));
+
+    rsx! {
+        div { class: "not-found",
+            h1 { "הדף לא נמצא" }
+            div { "ייתכן שהקישור ישן או שגוי." }
+            div { class: "not-found-actions",
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "לדף הראשי"
+                }
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::PracticeExam {
+                            num_questions: 30,
+                            feedback: false,
+                            category: String::new(),
+                            exclude: String::new(),
+                            tag: String::new(),
+                        });
+                    },
+                    "מבחן תרגול"
+                }
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::RealExam {
+                            strict: false,
+                            class: String::new(),
+                        });
+                    },
+                    "מבחן אמיתי"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! The hooks and components of the mistake notebook, see [`theory_test_core::notebook`].
+use dioxus::prelude::*;
+pub use theory_test_core::notebook::{notebook, GraduationStreak, NotebookEntry, Removals};
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+use crate::{
+    bank::use_bank, challenge, history::use_history, preferences::use_preferences, storage, timer,
+    Route,
+};
+
+const STORAGE_KEY: &str = "notebook-removals";
+
+/// Provide the removals from the notebook to the whole app, loading the stored ones and saving
+/// every change.
+pub fn provide_notebook_removals() {
+    let mut removals = use_context_provider(|| Signal::new(Removals::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<Removals>(STORAGE_KEY).await {
+            let removed = std::mem::replace(&mut *removals.write(), stored);
+            removals.write().extend(removed);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let removals = removals.read();
+        if loaded() {
+            storage::save(STORAGE_KEY, &*removals);
+        }
+    });
+}
+
+pub fn use_notebook_removals() -> Signal<Removals> {
+    use_context()
+}
+
+#[component]
+pub fn MistakeNotebook() -> Element {
+    let bank = use_bank();
+    let history = use_history();
+    let mut removals = use_notebook_removals();
+    let preferences = use_preferences();
+    let nav = navigator();
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let utc_offset = utc_offset().unwrap_or(0);
+    let graduation = preferences.read().notebook_graduation;
+    // questions which are no longer in the bank can't be shown or practiced
+    let entries = notebook(&history.read(), &removals.read(), graduation)
+        .into_iter()
+        .filter_map(|entry| {
+            let question = bank
+                .questions
+                .questions
+                .iter()
+                .find(|q| q.num == entry.num)?;
+            Some((entry, question.clone()))
+        })
+        .collect::<Vec<(NotebookEntry, Question)>>();
+    let nums = entries
+        .iter()
+        .map(|(entry, _)| entry.num)
+        .collect::<Vec<_>>();
+
+    rsx! {
+        div { class: "notebook",
+            h1 { "מחברת טעויות" }
+            div {
+                {
+                    format!(
+                        "שאלות שטעית בהן נכנסות למחברת, ויוצאות ממנה אחרי {} תשובות נכונות ברצף.",
+                        graduation.0,
+                    )
+                }
+            }
+            if entries.is_empty() {
+                div { "המחברת ריקה." }
+            } else {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::Challenge {
+                            qs: challenge::encode(&nums),
+                        });
+                    },
+                    {format!("תרגול על {} השאלות במחברת", entries.len())}
+                }
+            }
+            for category in QuestionCategory::ALL {
+                if entries.iter().any(|(_, q)| q.category == category) {
+                    h2 { {category.as_str_he()} }
+                    table {
+                        for (entry , question) in entries.iter().filter(|(_, q)| q.category == category) {
+                            tr { key: "{entry.num}",
+                                td { {question.question.clone()} }
+                                td { {format!("{} טעויות", entry.misses)} }
+                                td {
+                                    {
+                                        format!(
+                                            "לאחרונה ב־{}",
+                                            timer::format_date_time(entry.last_missed, utc_offset),
+                                        )
+                                    }
+                                }
+                                td {
+                                    button {
+                                        class: "button-primary",
+                                        onclick: {
+                                            let num = entry.num;
+                                            move |_| removals.write().remove(num, timer::now())
+                                        },
+                                        "הסר"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Private notes on questions, persisted across sessions.
+use dioxus::prelude::*;
+pub use theory_test_core::notes::{Notes, MAX_NOTE_CHARS};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "notes";
+/// Provide the notes to the whole app, loading the stored ones and saving every change.
+pub fn provide_notes() {
+    let mut notes = use_context_provider(|| Signal::new(Notes::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<Notes>(STORAGE_KEY).await {
+            // notes written before the stored ones loaded are newer
+            let written = std::mem::replace(&mut *notes.write(), stored);
+            notes.write().extend(written);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let notes = notes.read();
+        if loaded() {
+            storage::save(STORAGE_KEY, &*notes);
+        }
+    });
+}
+
+pub fn use_notes() -> Signal<Notes> {
+    use_context()
+}
+
+/// The note of a question, with editing it.
+#[component]
+pub fn QuestionNote(num: usize) -> Element {
+    let mut notes = use_notes();
+    let mut editing = use_signal(|| None::<String>);
+    let note = notes.read().get(num).map(str::to_string);
+
+    rsx! {
+        div { class: "question-note",
+            if let Some(draft) = editing() {
+                textarea {
+                    rows: 3,
+                    maxlength: MAX_NOTE_CHARS as i64,
+                    value: draft,
+                    oninput: move |e| editing.set(Some(e.value())),
+                }
+                div { class: "question-note-actions",
+                    {format!("{}/{}", editing().unwrap_or_default().chars().count(), MAX_NOTE_CHARS)}
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| {
+                            if let Some(draft) = editing.take() {
+                                notes.write().set(num, &draft);
+                            }
+                        },
+                        "שמור"
+                    }
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| editing.set(None),
+                        "ביטול"
+                    }
+                    if note.is_some() {
+                        button {
+                            class: "button-primary",
+                            onclick: move |_| {
+                                notes.write().set(num, "");
+                                editing.set(None);
+                            },
+                            "מחק הערה"
+                        }
+                    }
+                }
+            } else if let Some(note) = note {
+                div { class: "question-note-text",
+                    "📝 {note} "
+                    button {
+                        class: "note-button",
+                        onclick: move |_| editing.set(Some(note.clone())),
+                        "ערוך"
+                    }
+                }
+            } else {
+                button {
+                    class: "note-button",
+                    onclick: move |_| editing.set(Some(String::new())),
+                    "📝 הוסף הערה"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Offline support: registration of the service worker in `public/sw.js`, which caches the app
+//! once it loaded, and the indicators of its state and of the connection.
+//!
+//! The bank is embedded in the wasm bundle, so caching the bundle caches the bank as well.
+use dioxus::prelude::*;
+
+use crate::{
+    bank::use_bank,
+    routing::base_path,
+    toast::{use_toast, ToastAction, ToastKind},
+};
+
+/// Messages the registration script sends back.
+const CACHED: &str = "cached";
+const UPDATED: &str = "updated";
+
+/// Register the service worker and report when the app is cached, and when a new version of it
+/// took over.
+fn register_script(version: &str) -> String {
+    format!(
+        r#"
+        if (!('serviceWorker' in navigator)) {{
+            return;
+        }}
+        const hadController = navigator.serviceWorker.controller !== null;
+        navigator.serviceWorker.addEventListener('controllerchange', () => {{
+            if (hadController) {{
+                dioxus.send({updated:?});
+            }}
+        }});
+        navigator.serviceWorker.addEventListener('message', (event) => {{
+            if (event.data?.type === 'cached') {{
+                dioxus.send({cached:?});
+            }}
+        }});
+        try {{
+            const registration = await navigator.serviceWorker.register(
+                '{base}/sw.js?v={version}',
+                {{ scope: '{base}/' }},
+            );
+            await navigator.serviceWorker.ready;
+            const urls = [registration.scope, location.href]
+                .concat(performance.getEntriesByType('resource').map((entry) => entry.name))
+                .filter((url) => new URL(url).origin === location.origin);
+            (registration.active ?? navigator.serviceWorker.controller)?.postMessage({{
+                type: 'precache',
+                urls: [...new Set(urls)],
+            }});
+        }} catch (e) {{}}
+        await new Promise(() => {{}});
+        "#,
+        updated = UPDATED,
+        cached = CACHED,
+        base = base_path(),
+        version = version,
+    )
+}
+
+/// Report whether the browser is online, now and whenever it changes.
+const CONNECTIVITY_SCRIPT: &str = r#"
+    dioxus.send(navigator.onLine);
+    window.addEventListener('online', () => dioxus.send(true));
+    window.addEventListener('offline', () => dioxus.send(false));
+    await new Promise(() => {});
+"#;
+
+/// A dismissible banner shown while the browser is offline, as images of questions are loaded
+/// from the network. Only shows the state, answers are never touched by it.
+#[component]
+pub fn ConnectivityBanner() -> Element {
+    let mut online = use_signal(|| true);
+    let mut dismissed = use_signal(|| false);
+    use_future(move || async move {
+        let mut eval = document::eval(CONNECTIVITY_SCRIPT);
+        while let Ok(is_online) = eval.recv::<bool>().await {
+            online.set(is_online);
+            // going offline again shows the banner again
+            if is_online {
+                dismissed.set(false);
+            }
+        }
+    });
+
+    rsx! {
+        if !online() && !dismissed() {
+            div { class: "connectivity-banner no-print", role: "status",
+                "אין חיבור – ייתכן שתמונות לא יוצגו"
+                button {
+                    class: "connectivity-banner-close",
+                    title: "סגור",
+                    onclick: move |_| dismissed.set(true),
+                    "✕"
+                }
+            }
+        }
+    }
+}
+
+/// Registers the service worker, and shows whether the app is available offline.
+#[component]
+pub fn OfflineSupport() -> Element {
+    let bank = use_bank();
+    let mut toasts = use_toast();
+    let mut cached = use_signal(|| false);
+    // a new bank or app version gets a new cache, and the old one is removed
+    let version = format!("{:x}-{}", bank.content_hash, env!("CARGO_PKG_VERSION"));
+    use_future(move || {
+        let version = version.clone();
+        async move {
+            let mut eval = document::eval(&register_script(&version));
+            while let Ok(message) = eval.recv::<String>().await {
+                match message.as_str() {
+                    CACHED => cached.set(true),
+                    UPDATED => toasts.show(
+                        ToastKind::Info,
+                        "גרסה חדשה של האפליקציה זמינה".to_string(),
+                        Some(ToastAction::new("רענן", || {
+                            document::eval("location.reload();");
+                        })),
+                    ),
+                    _ => {}
+                }
+            }
+        }
+    });
+
+    rsx! {
+        document::Link { rel: "manifest", href: "{base_path()}/manifest.webmanifest" }
+        document::Link { rel: "icon", href: "{base_path()}/icon.svg" }
+        if cached() {
+            div { class: "offline-ready no-print", "זמין במצב לא מקוון" }
+        }
+    }
+}
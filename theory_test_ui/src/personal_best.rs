@@ -0,0 +1,30 @@
+//! The best score of each exam configuration, persisted across sessions.
+use dioxus::prelude::*;
+pub use theory_test_core::personal_best::{BestScore, ExamConfig, PersonalBests};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "personal-bests";
+
+/// Provide the personal bests to the whole app, loading the stored ones and saving every change.
+pub fn provide_personal_bests() {
+    let mut bests = use_context_provider(|| Signal::new(PersonalBests::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<PersonalBests>(STORAGE_KEY).await {
+            let recorded = std::mem::replace(&mut *bests.write(), stored);
+            bests.write().merge(recorded);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let bests = bests.read();
+        if loaded() {
+            storage::save(STORAGE_KEY, &*bests);
+        }
+    });
+}
+
+pub fn use_personal_bests() -> Signal<PersonalBests> {
+    use_context()
+}
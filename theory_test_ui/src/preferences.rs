@@ -0,0 +1,33 @@
+//! User preferences, persisted across sessions.
+use dioxus::prelude::*;
+pub use theory_test_core::preferences::{
+    Locale, Palette, Preferences, QuestionNumbering, QuestionOrder, TextZoom,
+};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "preferences";
+
+/// Provide the preferences to the whole app, loading the stored ones and saving every change.
+pub fn provide_preferences() {
+    let mut preferences = use_context_provider(|| Signal::new(Preferences::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        // the preferences of a profile apply to all banks
+        if let Some(stored) = storage::load_profile(STORAGE_KEY).await {
+            preferences.set(stored);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let preferences = preferences.read();
+        // don't overwrite the stored preferences with the defaults before they were loaded
+        if loaded() {
+            storage::save_profile(STORAGE_KEY, &*preferences);
+        }
+    });
+}
+
+pub fn use_preferences() -> Signal<Preferences> {
+    use_context()
+}
@@ -0,0 +1,163 @@
+//! Printable exams for taking on paper, generated deterministically from a seed so the
+//! question paper, its answer key and the blank answer sheet all correspond.
+use dioxus::prelude::*;
+use rand::SeedableRng;
+use theory_test_parser::question_parser::Question;
+
+use crate::{
+    bank::use_bank,
+    exam_state::{question_body, REAL_EXAM_NUM_QUESTIONS},
+    generate_questions,
+    preferences::{use_preferences, QuestionOrder},
+    QuestionSelection, Route,
+};
+
+const ANSWER_LETTERS: [&str; 4] = ["א", "ב", "ג", "ד"];
+
+fn answer_letter(answer_idx: usize) -> &'static str {
+    ANSWER_LETTERS.get(answer_idx).copied().unwrap_or("?")
+}
+
+/// The questions of the printable exam of `seed`, presented in `order`.
+fn seeded_questions(pool: &[Question], seed: u32, order: QuestionOrder) -> Vec<Question> {
+    let mut rng = rand_pcg::Pcg64::seed_from_u64(seed.into());
+    generate_questions(
+        pool,
+        QuestionSelection::Random {
+            num_questions: REAL_EXAM_NUM_QUESTIONS,
+        },
+        order,
+        &mut rng,
+    )
+}
+
+#[component]
+fn PrintActions(other: Route, other_label: &'static str) -> Element {
+    let nav = navigator();
+    rsx! {
+        div { class: "print-actions no-print",
+            button {
+                class: "button-primary",
+                onclick: move |_| {
+                    document::eval("window.print();");
+                },
+                "הדפס"
+            }
+            Link { class: "main-page-link", to: other, {other_label} }
+            button {
+                class: "button-primary",
+                onclick: move |_| {
+                    nav.push(Route::MainPage);
+                },
+                "חזרה לדף הראשי"
+            }
+        }
+    }
+}
+
+#[component]
+fn PrintFooter(seed: u32) -> Element {
+    rsx! {
+        div { class: "print-footer", {format!("מבחן מספר {}", seed)} }
+    }
+}
+
+/// The question paper of a printable exam, followed by its answer key on a separate page.
+#[component]
+pub fn PrintExam(seed: u32) -> Element {
+    let order = use_preferences().read().question_order;
+    let questions = seeded_questions(&use_bank().class_questions(), seed, order);
+
+    rsx! {
+        div { class: "print-page",
+            PrintActions {
+                other: Route::PrintAnswerSheet { seed },
+                other_label: "דף תשובות ריק",
+            }
+            h1 { "מבחן תאוריה" }
+            for (idx , question) in questions.iter().enumerate() {
+                div { class: "print-question",
+                    div { class: "question", {format!("{}. {}", idx + 1, question_body(question))} }
+                    if let Some(img) = &question.image_url {
+                        img { src: "{img}" }
+                    }
+                    ol { class: "print-answers",
+                        for (answer_idx , answer) in question.answers.possible_answers.iter().enumerate() {
+                            li { {format!("{}. {}", answer_letter(answer_idx), answer)} }
+                        }
+                    }
+                }
+            }
+            div { class: "print-answer-key",
+                h2 { "מפתח תשובות" }
+                div { class: "print-answer-key-grid",
+                    for (idx , question) in questions.iter().enumerate() {
+                        div { {format!("{}. {}", idx + 1, answer_letter(question.answers.correct_answer))} }
+                    }
+                }
+            }
+            PrintFooter { seed }
+        }
+    }
+}
+
+/// A blank answer sheet for the printable exam of `seed`, fitting on a single A4 page.
+#[component]
+pub fn PrintAnswerSheet(seed: u32) -> Element {
+    rsx! {
+        div { class: "print-page",
+            PrintActions {
+                other: Route::PrintExam { seed },
+                other_label: "טופס המבחן",
+            }
+            h2 { "דף תשובות" }
+            div { class: "answer-sheet-name", "שם: ____________________" }
+            table { class: "answer-sheet",
+                for num in 1..=REAL_EXAM_NUM_QUESTIONS {
+                    tr {
+                        th { "{num}" }
+                        for letter in ANSWER_LETTERS {
+                            td {
+                                span { class: "answer-sheet-circle", {letter} }
+                            }
+                        }
+                    }
+                }
+            }
+            PrintFooter { seed }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use theory_test_parser::question_parser::Answers;
+
+    #[test]
+    fn same_seed_same_exam() {
+        let pool = (1..=100)
+            .map(|num| Question {
+                answers: Answers {
+                    possible_answers: ["a", "b", "c", "d"].map(String::from).to_vec(),
+                    correct_answer: 0,
+                },
+                ..Question::sample(num, 0)
+            })
+            .collect::<Vec<_>>();
+        let nums = |seed| {
+            seeded_questions(&pool, seed, QuestionOrder::Random)
+                .iter()
+                .map(|q| (q.num, q.answers.correct_answer))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(nums(7).len(), REAL_EXAM_NUM_QUESTIONS);
+        assert_eq!(nums(7), nums(7));
+        assert_ne!(nums(7), nums(8));
+        // the order is applied to the same picked questions
+        let by_number = seeded_questions(&pool, 7, QuestionOrder::ByNumber);
+        let mut sorted = nums(7).iter().map(|(num, _)| *num).collect::<Vec<_>>();
+        sorted.sort();
+        assert_eq!(by_number.iter().map(|q| q.num).collect::<Vec<_>>(), sorted);
+    }
+}
@@ -0,0 +1,245 @@
+//! Local profiles, so several people sharing a device keep separate progress.
+//!
+//! Every persisted value except the list of profiles is stored per profile, see [`storage`].
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "profiles";
+pub const DEFAULT_PROFILE_NAME: &str = "ברירת מחדל";
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Profile {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+    next_id: u32,
+}
+
+impl Default for Profiles {
+    /// The profile which existing data is migrated into, see [`storage`].
+    fn default() -> Self {
+        Self {
+            profiles: vec![Profile {
+                id: 0,
+                name: DEFAULT_PROFILE_NAME.to_string(),
+            }],
+            next_id: 1,
+        }
+    }
+}
+
+/// Trim a profile name, rejecting empty names.
+fn valid_name(name: &str) -> Option<String> {
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+impl Profiles {
+    pub fn get(&self, id: u32) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    /// Create a profile, returning its id.
+    pub fn create(&mut self, name: &str) -> Option<u32> {
+        let name = valid_name(name)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.profiles.push(Profile { id, name });
+        Some(id)
+    }
+
+    pub fn rename(&mut self, id: u32, name: &str) -> bool {
+        let (Some(name), Some(profile)) = (
+            valid_name(name),
+            self.profiles.iter_mut().find(|p| p.id == id),
+        ) else {
+            return false;
+        };
+        profile.name = name;
+        true
+    }
+
+    /// Remove a profile, returning the profile which remains first. The last profile can't be
+    /// removed.
+    pub fn remove(&mut self, id: u32) -> Option<u32> {
+        if self.profiles.len() <= 1 {
+            return None;
+        }
+        let before = self.profiles.len();
+        self.profiles.retain(|p| p.id != id);
+        (self.profiles.len() < before).then(|| self.profiles[0].id)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ProfileStore {
+    pub profiles: Signal<Profiles>,
+    /// `None` until it is loaded.
+    pub active: Signal<Option<u32>>,
+}
+
+/// Provide the profiles to the whole app, loading the stored ones and saving every change.
+pub fn provide_profiles() {
+    let mut store = use_context_provider(|| ProfileStore {
+        profiles: Signal::new(Profiles::default()),
+        active: Signal::new(None),
+    });
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        store.active.set(storage::active_profile().await);
+        if let Some(stored) = storage::load_global(STORAGE_KEY).await {
+            store.profiles.set(stored);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let profiles = store.profiles.read();
+        if loaded() {
+            storage::save_global(STORAGE_KEY, &*profiles);
+        }
+    });
+}
+
+pub fn use_profiles() -> ProfileStore {
+    use_context()
+}
+
+/// Switch to another profile, saving the profiles first as switching reloads the page before
+/// changes are saved by [`provide_profiles`].
+fn switch_to(profiles: Signal<Profiles>, id: u32) {
+    storage::save_global(STORAGE_KEY, &*profiles.peek());
+    storage::switch_profile(id);
+}
+
+async fn prompt(message: &str, default: &str) -> Option<String> {
+    document::eval(&format!("return prompt({:?}, {:?});", message, default))
+        .join::<Option<String>>()
+        .await
+        .ok()
+        .flatten()
+}
+
+async fn confirm(message: &str) -> bool {
+    document::eval(&format!("return confirm({:?});", message))
+        .join::<bool>()
+        .await
+        .unwrap_or(false)
+}
+
+/// Switching between the profiles, and managing them.
+#[component]
+pub fn ProfileSwitcher() -> Element {
+    let ProfileStore {
+        mut profiles,
+        active,
+    } = use_profiles();
+    let Some(active) = active() else {
+        return rsx! {};
+    };
+    let active_name = profiles
+        .read()
+        .get(active)
+        .map(|p| p.name.clone())
+        .unwrap_or_default();
+
+    rsx! {
+        div { class: "profile-switcher",
+            "פרופיל: "
+            select {
+                onchange: move |e| {
+                    if let Ok(id) = e.value().parse() {
+                        storage::switch_profile(id);
+                    }
+                },
+                for profile in profiles.read().profiles.iter() {
+                    option {
+                        value: "{profile.id}",
+                        selected: profile.id == active,
+                        "{profile.name}"
+                    }
+                }
+            }
+            button {
+                class: "button-primary",
+                onclick: move |_| async move {
+                    if let Some(name) = prompt("שם הפרופיל החדש:", "").await {
+                        let id = profiles.write().create(&name);
+                        if let Some(id) = id {
+                            switch_to(profiles, id);
+                        }
+                    }
+                },
+                "פרופיל חדש"
+            }
+            button {
+                class: "button-primary",
+                onclick: move |_| {
+                    let active_name = active_name.clone();
+                    async move {
+                        if let Some(name) = prompt("שם חדש לפרופיל:", &active_name).await {
+                            profiles.write().rename(active, &name);
+                        }
+                    }
+                },
+                "שינוי שם"
+            }
+            if profiles.read().profiles.len() > 1 {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| async move {
+                        let name = profiles
+                            .read()
+                            .get(active)
+                            .map(|p| p.name.clone())
+                            .unwrap_or_default();
+                        let message = format!(
+                            "למחוק את הפרופיל \"{}\" ואת כל הנתונים שלו?",
+                            name,
+                        );
+                        if !confirm(&message).await {
+                            return;
+                        }
+                        let remaining = profiles.write().remove(active);
+                        if let Some(remaining) = remaining {
+                            storage::save_global(STORAGE_KEY, &*profiles.peek());
+                            storage::remove_profile_data(active).await;
+                            storage::switch_profile(remaining);
+                        }
+                    },
+                    "מחיקת פרופיל"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn managing_profiles() {
+        let mut profiles = Profiles::default();
+        assert_eq!(profiles.get(0).unwrap().name, DEFAULT_PROFILE_NAME);
+        // the last profile can't be removed
+        assert_eq!(profiles.remove(0), None);
+        assert_eq!(profiles.create("  "), None);
+        let id = profiles.create(" דנה ").unwrap();
+        assert_eq!(profiles.get(id).unwrap().name, "דנה");
+        assert!(profiles.rename(id, "יוסי"));
+        assert!(!profiles.rename(id, ""));
+        assert!(!profiles.rename(100, "יוסי"));
+        assert_eq!(profiles.get(id).unwrap().name, "יוסי");
+        assert_eq!(profiles.remove(0), Some(id));
+        assert_eq!(profiles.remove(0), None);
+        // ids aren't reused, so a new profile never sees data of a removed one
+        assert_eq!(profiles.create("דנה"), Some(id + 1));
+    }
+}
@@ -0,0 +1,87 @@
+//! QR codes of links, for opening them on another phone. Encoded and drawn client side.
+use dioxus::prelude::*;
+use qrcodegen::{QrCode, QrCodeEcc};
+
+/// The blank margin around the code, in modules, as required by the standard.
+const QUIET_ZONE: i32 = 4;
+/// The width of the drawn code, large enough to scan from another phone.
+const DISPLAY_SIZE: &str = "min(280px, 80vw)";
+
+/// The modules of a QR code of `text`: the size of a side including the quiet zone, and an SVG
+/// path of its dark modules. `None` if the text is too long for a QR code.
+fn qr_path(text: &str) -> Option<(i32, String)> {
+    let qr = QrCode::encode_text(text, QrCodeEcc::Medium).ok()?;
+    let mut path = String::new();
+    for y in 0..qr.size() {
+        for x in 0..qr.size() {
+            if qr.get_module(x, y) {
+                path += &format!("M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE);
+            }
+        }
+    }
+    Some((qr.size() + 2 * QUIET_ZONE, path))
+}
+
+/// The QR code of `link` with the link under it.
+#[component]
+pub fn LinkQrCode(link: String) -> Element {
+    let code = qr_path(&link);
+
+    rsx! {
+        div { class: "qr-code",
+            if let Some((size, path)) = code {
+                svg {
+                    width: DISPLAY_SIZE,
+                    view_box: "0 0 {size} {size}",
+                    shape_rendering: "crispEdges",
+                    role: "img",
+                    "aria-label": "קוד QR של הקישור",
+                    rect { width: "100%", height: "100%", fill: "#ffffff" }
+                    path { d: path, fill: "#000000" }
+                }
+            } else {
+                div { "הקישור ארוך מדי לקוד QR" }
+            }
+            div { class: "qr-code-link", dir: "ltr", "{link}" }
+        }
+    }
+}
+
+/// A "הצג QR" toggle showing the QR code of `link`, which is `None` while it is being built.
+#[component]
+pub fn QrToggle(link: Option<String>) -> Element {
+    let mut open = use_signal(|| false);
+
+    rsx! {
+        button { class: "button-primary", onclick: move |_| open.toggle(),
+            if open() {
+                "הסתר QR"
+            } else {
+                "הצג QR"
+            }
+        }
+        if open() {
+            if let Some(link) = link {
+                LinkQrCode { link }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        // the smallest version is 21 modules wide
+        let (size, path) = qr_path("https://a.co").unwrap();
+        assert_eq!(size, 21 + 2 * QUIET_ZONE);
+        // the top left finder pattern starts at the quiet zone
+        assert!(path.starts_with("M4,4h1v1h-1z"));
+        let (longer, _) =
+            qr_path(&format!("https://example.com/?qs={}", "0862,".repeat(40))).unwrap();
+        assert!(longer > size);
+        assert_eq!(qr_path(&"a".repeat(5000)), None);
+    }
+}
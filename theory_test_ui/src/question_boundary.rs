@@ -0,0 +1,72 @@
+//! Keeps a question which fails to render from taking the rest of the exam down with it.
+use dioxus::prelude::*;
+
+const ISSUES_URL: &str = "https://github.com/usering-around/theory_test_exam/issues/new";
+
+/// A link opening an issue about the question numbered `num`, with `error` in its body.
+fn report_url(num: usize, error: &str) -> String {
+    let body = format!("Question {num} can't be shown: {error}");
+    format!(
+        "{ISSUES_URL}?title={}&body={}",
+        encode_query(&format!("Question {num} can't be shown")),
+        encode_query(&body),
+    )
+}
+
+/// Percent-encode `value` for a query parameter.
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Renders its children, which show the question numbered `num`, or a compact card in their
+/// place if they fail to. `question_num` is the position of the question in the exam.
+#[component]
+pub fn QuestionBoundary(num: usize, question_num: usize, children: Element) -> Element {
+    rsx! {
+        ErrorBoundary {
+            handle_error: move |errors: ErrorContext| {
+                let error = errors
+                    .errors()
+                    .first()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                rsx! {
+                    div { class: "question-error", role: "alert",
+                        div { class: "question-error-title", {format!("שאלה {question_num} לא ניתנת להצגה")} }
+                        div { "השאלה לא תיחשב בציון." }
+                        div { class: "question-error-message", "{error}" }
+                        a {
+                            href: report_url(num, &error),
+                            target: "_blank",
+                            rel: "noopener",
+                            "דווח"
+                        }
+                    }
+                }
+            },
+            {children}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn report_urls() {
+        assert_eq!(encode_query("a b/ש"), "a%20b%2F%D7%A9");
+        assert_eq!(
+            report_url(7, "x"),
+            format!("{ISSUES_URL}?title=Question%207%20can%27t%20be%20shown&body=Question%207%20can%27t%20be%20shown%3A%20x")
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! The history of a single question, by its official number.
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::{
+    exam_state::QuestionOutcome,
+    history::{use_history, History},
+    timer,
+};
+
+/// How many of the last attempts of a question are listed.
+const LAST_ATTEMPTS: usize = 5;
+
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct QuestionRecord {
+    /// How many graded attempts the question appeared in.
+    pub seen: usize,
+    pub correct: usize,
+    /// When the last attempts were graded and how the question was answered in them, the most
+    /// recent first.
+    pub last: Vec<(Duration, QuestionOutcome)>,
+}
+
+impl QuestionRecord {
+    /// The share of the attempts which were answered correctly, in percent.
+    pub fn accuracy_percent(&self) -> Option<usize> {
+        (self.seen > 0).then(|| self.correct * 100 / self.seen)
+    }
+}
+
+/// The record of the question numbered `num`, keeping at most `last` of its attempts.
+pub fn question_record(history: &History, num: usize, last: usize) -> QuestionRecord {
+    let mut record = QuestionRecord::default();
+    for attempt in history.attempts.iter().filter(|a| !a.abandoned) {
+        for question in attempt.questions.iter().filter(|q| q.num == num) {
+            record.seen += 1;
+            if question.outcome == QuestionOutcome::Correct {
+                record.correct += 1;
+            }
+            record.last.push((attempt.graded_at, question.outcome));
+        }
+    }
+    record
+        .last
+        .sort_by_key(|(graded_at, _)| std::cmp::Reverse(*graded_at));
+    record.last.truncate(last);
+    record
+}
+
+fn outcome_description(outcome: QuestionOutcome) -> &'static str {
+    match outcome {
+        QuestionOutcome::Correct => "✓ נכון",
+        QuestionOutcome::Wrong => "✗ שגוי",
+        QuestionOutcome::Unanswered => "– ללא תשובה",
+        QuestionOutcome::Void => "– בוטלה",
+    }
+}
+
+/// A button opening the history of a question. The history is only gathered while it is open.
+#[component]
+pub fn QuestionHistory(
+    num: usize,
+    #[props(default)] flagged: bool,
+    /// Allows flagging the question from the history.
+    on_toggle_flag: Option<EventHandler<()>>,
+) -> Element {
+    let history = use_history();
+    let mut open = use_signal(|| false);
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let record = open().then(|| question_record(&history.read(), num, LAST_ATTEMPTS));
+
+    rsx! {
+        div { class: "question-history",
+            button {
+                class: "note-button",
+                onclick: move |_| open.toggle(),
+                "היסטוריה"
+            }
+            if let Some(record) = record {
+                div { class: "question-history-popover",
+                    if let Some(accuracy) = record.accuracy_percent() {
+                        div {
+                            {format!("ראית שאלה זו {} פעמים, ענית נכון ב־{}%", record.seen, accuracy)}
+                        }
+                        ul {
+                            for (graded_at , outcome) in record.last {
+                                li {
+                                    {
+                                        format!(
+                                            "{} {}",
+                                            timer::format_date_time(graded_at, utc_offset().unwrap_or(0)),
+                                            outcome_description(outcome),
+                                        )
+                                    }
+                                }
+                            }
+                        }
+                    } else {
+                        div { "עוד לא ראית שאלה זו במבחן." }
+                    }
+                    if let Some(on_toggle_flag) = on_toggle_flag.filter(|_| !flagged) {
+                        button {
+                            class: "note-button",
+                            onclick: move |_| on_toggle_flag(()),
+                            "🚩 סמן שאלה"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::history::Attempt;
+
+    fn attempt(graded_at: u64, abandoned: bool, questions: &[(usize, QuestionOutcome)]) -> Attempt {
+        Attempt {
+            abandoned,
+            ..Attempt::sample(graded_at, questions)
+        }
+    }
+
+    #[test]
+    fn records() {
+        use QuestionOutcome::*;
+        let mut history = History::default();
+        history.record(attempt(1, false, &[(1, Correct), (2, Wrong)]));
+        history.record(attempt(2, true, &[(1, Wrong)]));
+        history.record(attempt(3, false, &[(1, Wrong)]));
+        history.record(attempt(4, false, &[(1, Correct), (3, Unanswered)]));
+        let record = question_record(&history, 1, 2);
+        assert_eq!(record.seen, 3);
+        assert_eq!(record.correct, 2);
+        assert_eq!(record.accuracy_percent(), Some(66));
+        assert_eq!(
+            record.last,
+            vec![
+                (Duration::from_secs(4), Correct),
+                (Duration::from_secs(3), Wrong)
+            ]
+        );
+        let never = question_record(&history, 9, 2);
+        assert_eq!(never, QuestionRecord::default());
+        assert_eq!(never.accuracy_percent(), None);
+    }
+}
@@ -0,0 +1,204 @@
+//! The image of a question, falling back to a local copy and then to a placeholder when it
+//! can't be loaded, e.g. while offline.
+//!
+//! The images of the most common road signs are bundled with the app and shown before their
+//! original, so the questions seen most need no network. The other images are kept in the
+//! browser once they were shown, see [`crate::image_cache`].
+use std::{cell::RefCell, rc::Rc};
+
+use dioxus::prelude::*;
+
+use crate::{image_cache::use_image_cache, routing::base_path};
+
+/// Where the images are looked for when their original can't be loaded. Served only when the
+/// images were bundled with the app.
+const LOCAL_IMAGES_DIR: &str = "images";
+
+/// A sign image bundled with the app, see `build.rs`.
+pub struct SignImage {
+    pub num: usize,
+    /// The original the bundled image is a copy of.
+    pub url: &'static str,
+    /// The path of the image in the crate.
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub file: &'static str,
+    pub asset: Asset,
+}
+
+include!(concat!(env!("OUT_DIR"), "/sign_images.rs"));
+
+/// The bundled copy of the image at `url` of question `num`, unless the bank has another image
+/// for it than the one bundled.
+fn bundled_image(num: usize, url: &str) -> Option<Asset> {
+    SIGN_IMAGES
+        .iter()
+        .find(|image| image.num == num && image.url == url)
+        .map(|image| image.asset)
+}
+
+/// The URLs to try for the image at `url` of question `num`, in order: the copy bundled with the
+/// app, the copy the browser kept at `cached`, the original, then the copy served next to the
+/// app.
+fn image_sources(num: usize, url: &str, cached: Option<String>) -> Vec<String> {
+    let mut sources = bundled_image(num, url)
+        .map(|asset| asset.to_string())
+        .into_iter()
+        .chain(cached)
+        .collect::<Vec<_>>();
+    sources.push(url.to_string());
+    let file_name = url.rsplit('/').next().unwrap_or_default();
+    if !file_name.is_empty() && url.contains("://") {
+        sources.push(format!("{}/{LOCAL_IMAGES_DIR}/{file_name}", base_path()));
+    }
+    sources
+}
+
+/// The image at `url` of question `num`, or a frame with `alt` and a retry button once every
+/// source failed to load, at which point `on_error` is called.
+#[component]
+pub fn QuestionImage(
+    num: usize,
+    url: String,
+    alt: String,
+    on_error: Option<EventHandler<()>>,
+) -> Element {
+    // a bundled image needs no other copy
+    let cache = use_image_cache().filter(|_| bundled_image(num, &url).is_none());
+    // the object URLs of the kept copies, released with the image
+    let object_urls = use_hook(|| Rc::new(RefCell::new(Vec::<String>::new())));
+    let cached = use_resource({
+        let cache = cache.clone();
+        let object_urls = object_urls.clone();
+        use_reactive!(|(url)| {
+            let cache = cache.clone();
+            let object_urls = object_urls.clone();
+            async move {
+                let object_url = match cache {
+                    Some(cache) => cache.lookup(&url).await,
+                    None => None,
+                };
+                object_urls.borrow_mut().extend(object_url.clone());
+                (url, object_url)
+            }
+        })
+    });
+    use_drop(move || {
+        for object_url in object_urls.borrow().iter() {
+            document::eval(&format!("URL.revokeObjectURL({object_url:?});"));
+        }
+    });
+    // the failed sources are counted per image, so a drill moving on to the next question
+    // starts over
+    let mut failed = use_signal(|| (url.clone(), 0));
+
+    // the network is asked only once the browser didn't have a kept copy
+    let looked_up = cached
+        .read()
+        .as_ref()
+        .filter(|(looked_up, _)| *looked_up == url)
+        .map(|(_, object_url)| object_url.clone());
+    let indexed = cache.as_ref().is_some_and(|cache| cache.contains(&url));
+    let Some(cached_url) = looked_up.or((!indexed).then_some(None)) else {
+        return rsx! {};
+    };
+    let sources = image_sources(num, &url, cached_url.clone());
+    let source = if failed.read().0 == url {
+        failed.read().1
+    } else {
+        0
+    };
+    let num_sources = sources.len();
+
+    rsx! {
+        if let Some(src) = sources.get(source) {
+            img {
+                src: src.clone(),
+                alt: alt.clone(),
+                margin_bottom: "20px",
+                onload: {
+                    let url = url.clone();
+                    let src = src.clone();
+                    move |_| {
+                        // an image loaded from the network is kept for the next time
+                        if let Some(cache) = cache.clone().filter(|_| cached_url.as_ref() != Some(&src)) {
+                            let (url, src) = (url.clone(), src.clone());
+                            spawn(async move { cache.record(&url, &src).await });
+                        }
+                    }
+                },
+                onerror: {
+                    let url = url.clone();
+                    move |_| {
+                        failed.set((url.clone(), source + 1));
+                        if source + 1 == num_sources {
+                            if let Some(on_error) = on_error {
+                                on_error(());
+                            }
+                        }
+                    }
+                },
+            }
+        } else {
+            div { class: "image-fallback", role: "img", "aria-label": alt.clone(),
+                div { "🖼 {alt}" }
+                div { class: "image-fallback-note", "לא ניתן לטעון את התמונה" }
+                button {
+                    class: "button-primary",
+                    onclick: move |_| failed.set((url.clone(), 0)),
+                    "נסה שוב"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sources() {
+        assert_eq!(
+            image_sources(3001, "https://www.gov.il/BlobFolder/3001.jpg", None),
+            vec![
+                "https://www.gov.il/BlobFolder/3001.jpg".to_string(),
+                format!("{}/images/3001.jpg", base_path()),
+            ]
+        );
+        // a local image has nothing to fall back to
+        assert_eq!(image_sources(1, "/a.png", None), vec!["/a.png".to_string()]);
+        // the copy the browser kept comes before the network
+        assert_eq!(
+            image_sources(1, "/a.png", Some("blob:a".to_string())),
+            vec!["blob:a".to_string(), "/a.png".to_string()]
+        );
+    }
+
+    #[test]
+    fn bundled_sign_images() {
+        // when this fails, the bank changed since the images were fetched, run
+        // `theory-test-cli fetch-images theory_test_parser/test.xlsx --out theory_test_ui/assets/sign_images --signs 50`
+        let bank = crate::bank::load(crate::bank::BankSource::Bundled(
+            crate::storage::DEFAULT_BANK,
+        ))
+        .unwrap();
+        for image in SIGN_IMAGES {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(image.file);
+            assert!(path.is_file(), "{} is missing", image.file);
+            let question = bank.questions.questions.iter().find(|q| q.num == image.num);
+            assert!(question.is_some(), "{:04} isn't in the bank", image.num);
+            assert_eq!(question.unwrap().image_url.as_deref(), Some(image.url));
+            assert_eq!(
+                image_sources(image.num, image.url, None)[0],
+                image.asset.to_string()
+            );
+        }
+        // another image than the bundled one is loaded from its original
+        if let Some(image) = SIGN_IMAGES.first() {
+            assert_eq!(
+                image_sources(image.num, "https://example.com/other.jpg", None)[0],
+                "https://example.com/other.jpg"
+            );
+        }
+    }
+}
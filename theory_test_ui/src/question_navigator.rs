@@ -0,0 +1,28 @@
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::Question;
+
+use crate::{preferences::QuestionNumbering, results_grid::scroll_to_question};
+
+/// A grid with a cell per question of an exam, showing which questions were answered or flagged.
+/// Clicking a cell jumps to the question.
+#[component]
+pub fn QuestionNavigator(
+    questions: ReadOnlySignal<Vec<Question>>,
+    answered: Vec<bool>,
+    flagged: Vec<bool>,
+    numbering: QuestionNumbering,
+) -> Element {
+    rsx! {
+        div { class: "question-navigator",
+            for (idx , question) in questions.read().iter().enumerate() {
+                button {
+                    class: "grid-cell",
+                    class: if answered[idx] { "answered" } else { "unanswered" },
+                    class: if flagged[idx] { "flagged" },
+                    onclick: move |_| scroll_to_question(idx),
+                    {numbering.label(idx, question)}
+                }
+            }
+        }
+    }
+}
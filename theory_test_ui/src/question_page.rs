@@ -0,0 +1,49 @@
+use dioxus::prelude::*;
+
+use crate::{bank::use_bank, not_found::NotFound, ExamQuestion, Route};
+
+/// A single question of the bank with its correct answer, by its number, e.g. `/question/0862`.
+#[component]
+pub fn QuestionPage(num: usize) -> Element {
+    let bank = use_bank();
+    let Some(question) = bank.questions.questions.iter().find(|q| q.num == num) else {
+        return rsx! {
+            NotFound { segments: vec!["question".to_string(), num.to_string()] }
+        };
+    };
+
+    rsx! {
+        div { class: "question-page",
+            document::Title { {format!("שאלה {:04}", num)} }
+            ExamQuestion {
+                question: question.clone(),
+                language: bank.language,
+                show_correct_answer: true,
+                user_selection: None,
+                on_select: |_| {},
+                show_license_classes: true,
+                category_link: true,
+                show_note: true,
+                show_tags: true,
+                show_history: true,
+                show_translation: true,
+                question_num: num,
+                show_question_num: true,
+                use_canonical_question_num: true,
+            }
+            Link {
+                class: "button-primary",
+                to: Route::Browse {
+                    class: String::new(),
+                    category: String::new(),
+                    with_image: false,
+                    history: String::new(),
+                    with_note: false,
+                    tag: String::new(),
+                    page: 1,
+                },
+                "לכל השאלות"
+            }
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Fetching what the pages show from the server, in the fullstack app, rather than reading it
+//! from the bank which is embedded in the app.
+//!
+//! The server answers with only what a page renders: the metadata of a bank, a page of its
+//! questions, or the questions of an exam generated from an [`ExamSpec`]. Each answer can also be
+//! computed in the browser, which is what the pure client build always does, e.g. when hosted on
+//! GitHub Pages, and what the fullstack app falls back to when the server can't be reached.
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use theory_test_parser::{
+    exam::{ExamSpec, GeneratedExam},
+    question_parser::{ExamQuestions, LicenseClass, Question, QuestionCategory},
+};
+
+use crate::{
+    bank::{Bank, BankResult, BankSource},
+    browse::{num_pages, page_range},
+};
+
+/// What is known about a bank without its questions.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BankMetadata {
+    pub id: String,
+    pub name_he: String,
+    pub language: String,
+    pub content_hash: u64,
+    pub num_questions: usize,
+    pub num_warnings: usize,
+}
+
+impl BankMetadata {
+    pub fn of(bank: &Bank) -> Self {
+        Self {
+            id: bank.id.to_string(),
+            name_he: bank.name_he.to_string(),
+            language: bank.language.to_string(),
+            content_hash: bank.content_hash,
+            num_questions: bank.questions.questions.len(),
+            num_warnings: bank.warnings.len(),
+        }
+    }
+}
+
+/// The filters of the browse page which only depend on the bank, so the server can apply them.
+#[derive(Clone, Copy, PartialEq, Default, Debug, Serialize, Deserialize)]
+pub struct QuestionFilter {
+    pub class: Option<LicenseClass>,
+    pub category: Option<QuestionCategory>,
+    pub with_image: bool,
+}
+
+impl QuestionFilter {
+    pub fn matches(&self, question: &Question) -> bool {
+        self.class
+            .is_none_or(|class| question.license_classes.contains(&class))
+            && self
+                .category
+                .is_none_or(|category| question.category == category)
+            && (!self.with_image || question.image_url.is_some())
+    }
+}
+
+/// A page of the questions which matched a filter.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuestionsPage {
+    pub questions: Vec<Question>,
+    /// The 1-based page, clamped to the existing pages.
+    pub page: usize,
+    /// How many questions matched, on every page.
+    pub total: usize,
+}
+
+impl QuestionsPage {
+    /// The 1-based `page` of `questions`.
+    pub fn new<'a>(questions: impl IntoIterator<Item = &'a Question>, page: usize) -> Self {
+        let questions = questions.into_iter().collect::<Vec<_>>();
+        let total = questions.len();
+        Self {
+            questions: questions[page_range(total, page)]
+                .iter()
+                .map(|question| (*question).clone())
+                .collect(),
+            page: page.clamp(1, num_pages(total)),
+            total,
+        }
+    }
+
+    pub fn num_pages(&self) -> usize {
+        num_pages(self.total)
+    }
+}
+
+fn questions_page(bank: &ExamQuestions, filter: QuestionFilter, page: usize) -> QuestionsPage {
+    QuestionsPage::new(bank.questions.iter().filter(|q| filter.matches(q)), page)
+}
+
+/// The answer of the server, or the same answer computed in the browser when the server couldn't
+/// give it, e.g. when it can't be reached.
+#[cfg(any(feature = "fullstack", test))]
+async fn or_local<T, E: std::fmt::Display>(
+    remote: impl std::future::Future<Output = Result<T, E>>,
+    local: impl FnOnce() -> T,
+) -> T {
+    match remote.await {
+        Ok(answer) => answer,
+        Err(error) => {
+            dioxus::logger::tracing::warn!(
+                "the server didn't answer, answering in the browser: {error}"
+            );
+            local()
+        }
+    }
+}
+
+#[cfg(feature = "fullstack")]
+mod server {
+    use dioxus::prelude::{server_fn::codec::Json, *};
+    use theory_test_parser::exam::{ExamSpec, GeneratedExam};
+
+    use super::{BankMetadata, QuestionFilter, QuestionsPage};
+    // the bodies of the functions are only built into the server
+    #[cfg(feature = "server")]
+    use {
+        super::questions_page,
+        crate::bank::{bundled, load, Bank, BankSource},
+    };
+
+    /// The bundled bank of `id`, parsed once for all of the requests.
+    #[cfg(feature = "server")]
+    fn bank(id: &str) -> Result<std::rc::Rc<Bank>, ServerFnError> {
+        load(BankSource::Bundled(bundled(id).id)).map_err(|error| {
+            ServerFnError::ServerError(format!(
+                "{}: {}",
+                error.source.description_he(),
+                error.message
+            ))
+        })
+    }
+
+    /// The compressed bundle of the bundled bank of `id`, which the client doesn't embed.
+    #[server(input = Json)]
+    pub async fn get_bank_bundle(bank_id: String) -> Result<Vec<u8>, ServerFnError> {
+        Ok(bundled(&bank_id).bundle.to_vec())
+    }
+
+    #[server(input = Json)]
+    pub async fn get_bank_meta(bank_id: String) -> Result<BankMetadata, ServerFnError> {
+        let bank = bank(&bank_id)?;
+        Ok(BankMetadata::of(&bank))
+    }
+
+    #[server(input = Json)]
+    pub async fn get_questions_page(
+        bank_id: String,
+        filter: QuestionFilter,
+        page: usize,
+    ) -> Result<QuestionsPage, ServerFnError> {
+        Ok(questions_page(&bank(&bank_id)?.questions, filter, page))
+    }
+
+    #[server(input = Json)]
+    pub async fn generate_exam(
+        bank_id: String,
+        spec: ExamSpec,
+    ) -> Result<GeneratedExam, ServerFnError> {
+        Ok(spec.generate(&bank(&bank_id)?.questions))
+    }
+}
+
+/// Load the bank of `source`, from the server in the client of the fullstack app, which doesn't
+/// embed the banks. Unlike the other answers, it can't be computed in the browser instead.
+pub async fn load_bank(source: BankSource) -> BankResult {
+    #[cfg(all(feature = "fullstack", not(feature = "server"), not(test)))]
+    {
+        let BankSource::Bundled(id) = source;
+        loaded_bundle(source, &Some(bank_bundle(id).await))
+    }
+    #[cfg(any(not(feature = "fullstack"), feature = "server", test))]
+    crate::bank::load(source)
+}
+
+/// The compressed default bank, from the server along with the page it rendered, so the browser
+/// hydrates the page with the bank it was rendered with.
+#[cfg(feature = "fullstack")]
+pub fn use_default_bank_bundle() -> Result<Resource<Result<Vec<u8>, ServerFnError>>, RenderError> {
+    use_server_future(|| bank_bundle(crate::bank::BUNDLED_BANKS[0].id))
+}
+
+/// The compressed bundle of the bank of `id`, from the server unless it is embedded in the app.
+#[cfg(feature = "fullstack")]
+async fn bank_bundle(id: &'static str) -> Result<Vec<u8>, ServerFnError> {
+    #[cfg(all(not(feature = "server"), not(test)))]
+    {
+        server::get_bank_bundle(id.to_string()).await
+    }
+    #[cfg(any(feature = "server", test))]
+    Ok(crate::bank::bundled(id).bundle.to_vec())
+}
+
+/// The bank of `source` out of the `compressed` bundle the server answered with.
+#[cfg(feature = "fullstack")]
+pub fn loaded_bundle(
+    source: BankSource,
+    compressed: &Option<Result<Vec<u8>, ServerFnError>>,
+) -> BankResult {
+    match compressed {
+        Some(Ok(compressed)) => crate::bank::load_bundle(source, compressed),
+        Some(Err(error)) => Err(crate::bank::BankError {
+            source,
+            message: format!("the server didn't send the bank: {error}"),
+            warnings: Vec::new(),
+        }),
+        None => unreachable!("the suspended future resolved once it returned"),
+    }
+}
+
+/// The metadata of `bank`, from the server in the fullstack app.
+pub fn use_bank_metadata(bank: Rc<Bank>) -> Result<BankMetadata, RenderError> {
+    #[cfg(feature = "fullstack")]
+    {
+        let metadata = use_server_future(move || {
+            let bank = bank.clone();
+            async move {
+                or_local(server::get_bank_meta(bank.id.to_string()), || {
+                    BankMetadata::of(&bank)
+                })
+                .await
+            }
+        })?;
+        let metadata = metadata.read();
+        Ok(metadata
+            .clone()
+            .expect("the suspended future resolved once it returned"))
+    }
+    #[cfg(not(feature = "fullstack"))]
+    Ok(BankMetadata::of(&bank))
+}
+
+/// The `page` of the questions of `bank` which match `filter`, from the server in the fullstack
+/// app. `None` without a filter, for the pages which filter by what only the browser knows.
+pub fn use_questions_page(
+    bank: Rc<Bank>,
+    filter: Option<QuestionFilter>,
+    page: usize,
+) -> Result<Option<QuestionsPage>, RenderError> {
+    #[cfg(feature = "fullstack")]
+    {
+        let questions = use_server_future(use_reactive!(|filter, page| {
+            let bank = bank.clone();
+            async move {
+                let filter = filter?;
+                let remote = server::get_questions_page(bank.id.to_string(), filter, page);
+                Some(or_local(remote, || questions_page(&bank.questions, filter, page)).await)
+            }
+        }))?;
+        let questions = questions.read();
+        Ok(questions
+            .clone()
+            .expect("the suspended future resolved once it returned"))
+    }
+    #[cfg(not(feature = "fullstack"))]
+    Ok(filter.map(|filter| questions_page(&bank.questions, filter, page)))
+}
+
+/// The exam of `spec`, generated by the server in the fullstack app. `None` until it arrived,
+/// and again whenever `spec` changes.
+pub fn use_generated_exam(bank: Rc<Bank>, spec: Memo<ExamSpec>) -> Option<GeneratedExam> {
+    #[cfg(feature = "fullstack")]
+    {
+        let exam = use_resource(move || {
+            let bank = bank.clone();
+            let spec = spec();
+            async move {
+                let remote = server::generate_exam(bank.id.to_string(), spec.clone());
+                or_local(remote, || spec.generate(&bank.questions)).await
+            }
+        });
+        let exam = exam.read();
+        exam.clone()
+    }
+    // the same spec always generates the same exam, so it doesn't need to be kept
+    #[cfg(not(feature = "fullstack"))]
+    Some(spec.read().generate(&bank.questions))
+}
+
+#[cfg(test)]
+mod test {
+    use futures_executor::block_on;
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::bank::{load, BankSource, BUNDLED_BANKS};
+
+    /// `value` once it was sent to the other side, as the server functions send it.
+    fn sent<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    fn same<T: Serialize>(a: &T, b: &T) -> bool {
+        serde_json::to_value(a).unwrap() == serde_json::to_value(b).unwrap()
+    }
+
+    #[test]
+    fn serialization() {
+        let bank = load(BankSource::Bundled(BUNDLED_BANKS[0].id)).unwrap();
+        let metadata = BankMetadata::of(&bank);
+        assert_eq!(sent(&metadata), metadata);
+        assert_eq!(metadata.num_questions, bank.questions.questions.len());
+
+        let filter = QuestionFilter {
+            class: Some(LicenseClass::B),
+            category: Some(QuestionCategory::RoadSigns),
+            with_image: true,
+        };
+        assert_eq!(sent(&filter), filter);
+        let page = questions_page(&bank.questions, filter, 2);
+        assert_eq!(page.page, 2);
+        assert_eq!(page.questions.len(), 20);
+        assert!(page.questions.iter().all(|q| filter.matches(q)));
+        assert!(same(&sent(&page), &page));
+
+        let spec = ExamSpec {
+            categories: vec![QuestionCategory::Safety],
+            seed: 7,
+            ..Default::default()
+        };
+        assert_eq!(sent(&spec), spec);
+        let exam = spec.generate(&bank.questions);
+        assert!(same(&sent(&exam), &exam));
+    }
+
+    #[test]
+    fn paging() {
+        let question = |num| Question {
+            license_classes: vec![LicenseClass::B],
+            ..Question::sample(num, 0)
+        };
+        let questions = (1..=45).map(question).collect::<Vec<_>>();
+        let page = QuestionsPage::new(&questions, 9);
+        assert_eq!(page.page, 3);
+        assert_eq!(page.total, 45);
+        assert_eq!(page.num_pages(), 3);
+        assert_eq!(
+            page.questions.iter().map(|q| q.num).collect::<Vec<_>>(),
+            (41..=45).collect::<Vec<_>>()
+        );
+        assert_eq!(QuestionsPage::new(&[], 1).num_pages(), 1);
+    }
+
+    #[test]
+    fn falls_back_when_unreachable() {
+        let unreachable = async { Err::<usize, _>("error sending request: connection refused") };
+        assert_eq!(block_on(or_local(unreachable, || 3)), 3);
+        let answered = async { Ok::<_, String>(5) };
+        assert_eq!(
+            block_on(or_local(answered, || unreachable!("the server answered"))),
+            5
+        );
+    }
+}
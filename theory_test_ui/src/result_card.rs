@@ -0,0 +1,292 @@
+//! The result of a graded exam as an image, to share where a link or a screenshot wouldn't fit.
+//! Drawn as an SVG, which the browser rasterizes into a PNG.
+use dioxus::prelude::*;
+use theory_test_core::time::{civil_from_days, local_day};
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+use crate::{
+    category_badge::category_color,
+    exam_state::ExamResult,
+    preferences::{use_preferences, Palette},
+    share::{category_scores, kind_description},
+    timer,
+    toast::use_toast,
+    ExamKind,
+};
+
+const WIDTH: u32 = 1080;
+const HEIGHT: u32 = 1080;
+/// The colors of the page and of the cards of the app, so the image looks like the app.
+const BACKGROUND: &str = "#0f1116";
+const CARD: &str = "#171a21";
+const TEXT: &str = "#ffffff";
+pub(crate) const MUTED: &str = "#9aa0ab";
+const TRACK: &str = "#2a2f3a";
+pub(crate) const PASSED: &str = "#2e7d32";
+const FAILED: &str = "#c62828";
+pub(crate) const FONT: &str = "Arial, Helvetica, sans-serif";
+pub(crate) const APP_NAME: &str = "מבחן תאוריה";
+const FILE_NAME: &str = "theory-test-result.png";
+
+/// What the image of a result shows.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultCard {
+    pub kind: ExamKind,
+    pub bank_name: String,
+    pub correct: usize,
+    pub num_questions: usize,
+    pub passed: bool,
+    /// `(category, correct, total)`, as in [`category_scores`].
+    pub categories: Vec<(QuestionCategory, usize, usize)>,
+    /// The local day the exam was graded, as a number of days since the unix epoch.
+    pub day: i64,
+}
+
+impl ResultCard {
+    pub fn new(
+        kind: ExamKind,
+        bank_name: &str,
+        questions: &[Question],
+        result: &ExamResult,
+        day: i64,
+    ) -> Self {
+        Self {
+            kind,
+            bank_name: bank_name.to_owned(),
+            correct: result.correct(),
+            num_questions: result.num_questions(),
+            passed: result.passed(),
+            categories: category_scores(questions, result),
+            day,
+        }
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The image of `card` as an SVG document. The texts are right to left, anchored at their right
+/// end, except for the numbers which are laid out left to right.
+pub fn result_card_svg(card: &ResultCard, palette: Palette) -> String {
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" direction="rtl" xml:lang="he" font-family="{FONT}">
+<rect width="{WIDTH}" height="{HEIGHT}" fill="{BACKGROUND}"/>
+<rect x="60" y="60" width="960" height="960" rx="32" fill="{CARD}"/>
+<text x="960" y="150" font-size="48" font-weight="bold" fill="{TEXT}" text-anchor="start">{}</text>
+<text x="960" y="210" font-size="32" fill="{MUTED}" text-anchor="start">{}</text>
+<text x="540" y="420" font-size="160" font-weight="bold" fill="{TEXT}" text-anchor="middle" direction="ltr" unicode-bidi="embed">{}/{}</text>
+"#,
+        escape(APP_NAME),
+        escape(kind_description(card.kind)),
+        card.correct,
+        card.num_questions,
+    );
+    let (verdict, verdict_color) = if card.passed {
+        ("עבר", PASSED)
+    } else {
+        ("נכשל", FAILED)
+    };
+    svg += &format!(
+        r#"<rect x="440" y="460" width="200" height="70" rx="35" fill="{verdict_color}"/>
+<text x="540" y="508" font-size="36" font-weight="bold" fill="{TEXT}" text-anchor="middle">{verdict}</text>
+"#
+    );
+
+    // the bars fill from the right, the direction the labels are read in
+    const BAR_RIGHT: usize = 720;
+    const BAR_WIDTH: usize = 420;
+    for (idx, (category, correct, total)) in card.categories.iter().enumerate() {
+        let y = 620 + idx * 80;
+        let filled = BAR_WIDTH * correct / total.max(&1);
+        let color = category_color(*category, palette);
+        svg += &format!(
+            r#"<text x="960" y="{}" font-size="30" fill="{TEXT}" text-anchor="start">{}</text>
+<rect x="{}" y="{}" width="{BAR_WIDTH}" height="24" rx="12" fill="{TRACK}"/>
+<rect x="{}" y="{}" width="{filled}" height="24" rx="12" fill="{color}"/>
+<text x="260" y="{}" font-size="30" fill="{TEXT}" text-anchor="end" direction="ltr" unicode-bidi="embed">{correct}/{total}</text>
+"#,
+            y + 10,
+            escape(category.as_str_he()),
+            BAR_RIGHT - BAR_WIDTH,
+            y - 10,
+            BAR_RIGHT - filled,
+            y - 10,
+            y + 10,
+        );
+    }
+
+    let (year, month, day) = civil_from_days(card.day);
+    svg += &format!(
+        r#"<text x="960" y="990" font-size="24" fill="{MUTED}" text-anchor="start">מאגר: {}</text>
+<text x="120" y="990" font-size="24" fill="{MUTED}" text-anchor="start" direction="ltr" unicode-bidi="embed">{day}/{month}/{year}</text>
+</svg>
+"#,
+        escape(&card.bank_name),
+    );
+    svg
+}
+
+/// Rasterizes the SVG it is sent into a PNG, returning its data URL and whether the browser can
+/// share it as a file.
+const RASTERIZE_SCRIPT: &str = r#"
+const svg = await dioxus.recv();
+const image = new Image();
+image.src = "data:image/svg+xml;charset=utf-8," + encodeURIComponent(svg);
+await image.decode();
+const canvas = document.createElement("canvas");
+canvas.width = image.naturalWidth;
+canvas.height = image.naturalHeight;
+canvas.getContext("2d").drawImage(image, 0, 0);
+const url = canvas.toDataURL("image/png");
+let shareable = false;
+try {
+    const blob = await (await fetch(url)).blob();
+    const file = new File([blob], "FILE_NAME", { type: "image/png" });
+    shareable = !!navigator.canShare && navigator.canShare({ files: [file] });
+} catch (e) {}
+return [url, shareable];
+"#;
+
+/// Shares the PNG of the data URL it is sent, returning whether it was shared or dismissed.
+const SHARE_SCRIPT: &str = r#"
+const url = await dioxus.recv();
+try {
+    const blob = await (await fetch(url)).blob();
+    const file = new File([blob], "FILE_NAME", { type: "image/png" });
+    await navigator.share({ files: [file], title: "APP_NAME" });
+    return true;
+} catch (e) {
+    return e.name === "AbortError";
+}
+"#;
+
+pub(crate) async fn rasterize(svg: String) -> Option<(String, bool)> {
+    let eval = document::eval(&RASTERIZE_SCRIPT.replace("FILE_NAME", FILE_NAME));
+    eval.send(svg).ok()?;
+    eval.await
+        .ok()
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// A "צור תמונת תוצאה" button, and a preview of the image with its download and share once it is
+/// created.
+#[component]
+pub fn ResultCardButton(
+    kind: ExamKind,
+    bank_name: &'static str,
+    questions: ReadOnlySignal<Vec<Question>>,
+    result: ExamResult,
+) -> Element {
+    let preferences = use_preferences();
+    let mut toasts = use_toast();
+    let mut image = use_signal(|| None::<(String, bool)>);
+
+    rsx! {
+        button {
+            class: "button-primary",
+            onclick: move |_| {
+                let result = result.clone();
+                async move {
+                    let utc_offset = timer::utc_offset_secs().await;
+                    let card = ResultCard::new(
+                        kind,
+                        bank_name,
+                        &questions.read(),
+                        &result,
+                        local_day(timer::now(), utc_offset),
+                    );
+                    let svg = result_card_svg(&card, preferences.read().category_palette);
+                    match rasterize(svg).await {
+                        Some(created) => image.set(Some(created)),
+                        None => toasts.error("לא ניתן ליצור את התמונה"),
+                    }
+                }
+            },
+            "צור תמונת תוצאה"
+        }
+        if let Some((url, shareable)) = image() {
+            div { class: "result-card",
+                img { src: "{url}", alt: "תמונת התוצאה" }
+                div {
+                    a {
+                        class: "button-primary",
+                        href: "{url}",
+                        download: FILE_NAME,
+                        "הורדה"
+                    }
+                    if shareable {
+                        button {
+                            class: "button-primary",
+                            onclick: move |_| async move {
+                                let Some((url, _)) = image() else {
+                                    return;
+                                };
+                                    let eval = document::eval(
+                                        &SHARE_SCRIPT
+                                            .replace("FILE_NAME", FILE_NAME)
+                                            .replace("APP_NAME", APP_NAME),
+                                    );
+                                    let shared = eval.send(url).is_ok()
+                                        && eval.join::<bool>().await.unwrap_or(false);
+                                if !shared {
+                                    toasts.error("לא ניתן לשתף את התמונה");
+                                }
+                            },
+                            "שיתוף"
+                        }
+                    }
+                    button { class: "button-primary", onclick: move |_| image.set(None), "סגור" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn snapshot() {
+        let card = ResultCard {
+            kind: ExamKind::Real,
+            bank_name: "רכב <פרטי> & \"קל\"".to_owned(),
+            correct: 27,
+            num_questions: 30,
+            passed: true,
+            categories: vec![
+                (QuestionCategory::TrafficLaws, 12, 13),
+                (QuestionCategory::RoadSigns, 7, 9),
+                (QuestionCategory::Safety, 5, 5),
+                (QuestionCategory::CarKnowledge, 3, 3),
+            ],
+            // 2026-10-17
+            day: 20_743,
+        };
+        assert_eq!(
+            result_card_svg(&card, Palette::Standard),
+            include_str!("snapshots/result_card.svg")
+        );
+    }
+
+    #[test]
+    fn failed() {
+        let card = ResultCard {
+            kind: ExamKind::Practice,
+            bank_name: String::new(),
+            correct: 0,
+            num_questions: 30,
+            passed: false,
+            categories: vec![(QuestionCategory::RoadSigns, 0, 30)],
+            day: 0,
+        };
+        let svg = result_card_svg(&card, Palette::Colorblind);
+        assert!(svg.contains(FAILED));
+        assert!(svg.contains(r#"width="0" height="24""#));
+        assert!(svg.contains(">1/1/1970<"));
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeSet;
+
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::Question;
+
+use crate::{
+    challenge,
+    exam_state::{ExamResult, QuestionOutcome},
+    exam_store::use_exam_store,
+    preferences::QuestionNumbering,
+    qr_code::QrToggle,
+    routing::absolute_url,
+    Route,
+};
+
+/// How many words of the question are shown in a cell's tooltip.
+const TOOLTIP_WORDS: usize = 8;
+
+/// The opening words of a question, without its official number prefix.
+fn opening_words(question: &Question) -> String {
+    let text = question.question.get(6..).unwrap_or(&question.question);
+    let mut words = text.split_whitespace();
+    let mut opening = words
+        .by_ref()
+        .take(TOOLTIP_WORDS)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if words.next().is_some() {
+        opening.push('…');
+    }
+    opening
+}
+
+fn outcome_class(outcome: QuestionOutcome) -> &'static str {
+    match outcome {
+        QuestionOutcome::Correct => "correct",
+        QuestionOutcome::Wrong => "wrong",
+        QuestionOutcome::Unanswered => "unanswered",
+        QuestionOutcome::Void => "void",
+    }
+}
+
+/// The id of the element wrapping a question in the exam, used to scroll to it.
+pub fn question_element_id(question_idx: usize) -> String {
+    format!("question-{}", question_idx + 1)
+}
+
+pub fn scroll_to_question(question_idx: usize) {
+    // a question which isn't mounted is mounted by scrolling to its placeholder, then scrolled to
+    // again once its real height is known
+    document::eval(&format!(
+        r#"
+        const question = document.getElementById({:?});
+        if (question?.dataset.virtualMounted === 'false') {{
+            question.scrollIntoView();
+            setTimeout(() => question.scrollIntoView(), 100);
+        }} else {{
+            question?.scrollIntoView({{behavior: 'smooth'}});
+        }}
+        "#,
+        question_element_id(question_idx)
+    ));
+}
+
+/// The id of the button submitting the exam.
+pub const SUBMIT_ELEMENT_ID: &str = "submit-exam";
+
+/// Scroll to a question and move the focus to its answers, for answering it from the keyboard.
+pub fn focus_question(question_idx: usize) {
+    scroll_to_question(question_idx);
+    // after a question which wasn't mounted is
+    document::eval(&format!(
+        "setTimeout(() => document.getElementById({:?})?.querySelector('input')?.focus({{preventScroll: true}}), 150);",
+        question_element_id(question_idx)
+    ));
+}
+
+/// Scroll to the button submitting the exam and move the focus to it.
+pub fn focus_submit() {
+    document::eval(&format!(
+        "const submit = document.getElementById({:?}); submit?.scrollIntoView({{behavior: 'smooth'}}); submit?.focus({{preventScroll: true}});",
+        SUBMIT_ELEMENT_ID
+    ));
+}
+
+/// A grid with a cell per question of a graded exam, colored by its outcome.
+///
+/// Clicking a cell jumps to the question. In selection mode clicking toggles the question instead,
+/// and the selected questions can be retaken or shared as a challenge.
+#[component]
+pub fn ResultsGrid(
+    questions: ReadOnlySignal<Vec<Question>>,
+    result: ExamResult,
+    numbering: QuestionNumbering,
+) -> Element {
+    let nav = navigator();
+    let mut exam_store = use_exam_store();
+    let missed = result.missed();
+    let mut selecting = use_signal(|| false);
+    let mut selected = use_signal(move || missed.into_iter().collect::<BTreeSet<usize>>());
+    let mut link_copied = use_signal(|| false);
+    let selected_nums = move || {
+        let questions = questions.read();
+        selected
+            .read()
+            .iter()
+            .map(|i| questions[*i].num)
+            .collect::<Vec<_>>()
+    };
+    // follows the selection, so the shown link and QR code are never stale
+    let challenge_link = use_resource(move || {
+        let route = Route::Challenge {
+            qs: challenge::encode(&selected_nums()),
+        };
+        async move { absolute_url(&route).await }
+    });
+
+    rsx! {
+        div { class: "results-grid-container",
+            div { class: "results-grid",
+                for (idx , (question , outcome)) in questions.read().iter().zip(result.outcomes.iter()).enumerate() {
+                    button {
+                        class: "grid-cell {outcome_class(*outcome)}",
+                        class: if selecting() && selected.read().contains(&idx) { "selected" },
+                        title: opening_words(question),
+                        onclick: move |_| {
+                            if selecting() {
+                                let mut selected = selected.write();
+                                if !selected.remove(&idx) {
+                                    selected.insert(idx);
+                                }
+                                // the copied link is of the previous selection
+                                link_copied.set(false);
+                            } else {
+                                scroll_to_question(idx);
+                            }
+                        },
+                        {numbering.label(idx, question)}
+                    }
+                }
+            }
+            div { class: "results-grid-actions",
+                button {
+                    class: "button-primary",
+                    onclick: move |_| selecting.toggle(),
+                    if selecting() {
+                        "סיום בחירה"
+                    } else {
+                        "בחר שאלות"
+                    }
+                }
+                if !selected.read().is_empty() {
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| {
+                            let route = Route::Challenge {
+                                qs: challenge::encode(&selected_nums()),
+                            };
+                            exam_store.forget(&route);
+                            nav.push(route);
+                        },
+                        {format!("תרגל שוב {} שאלות", selected.read().len())}
+                    }
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| {
+                            if let Some(link) = challenge_link.cloned() {
+                                document::eval(&format!("navigator.clipboard?.writeText({:?});", link));
+                                link_copied.set(true);
+                            }
+                        },
+                        "צור אתגר"
+                    }
+                    QrToggle { link: challenge_link.cloned() }
+                }
+            }
+            if let Some(link) = challenge_link.cloned().filter(|_| link_copied()) {
+                div { class: "challenge-link",
+                    "הקישור לאתגר הועתק: "
+                    a { href: "{link}", dir: "ltr", "{link}" }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tooltip_words() {
+        let mut question = Question {
+            question: "0862. מה עליך לוודא לפני נסיעה ברכב שאינו מוכר לך?".to_string(),
+            ..Question::sample(862, 0)
+        };
+        assert_eq!(
+            opening_words(&question),
+            "מה עליך לוודא לפני נסיעה ברכב שאינו מוכר…"
+        );
+        question.question = "0001. קצר".to_string();
+        assert_eq!(opening_words(&question), "קצר");
+    }
+}
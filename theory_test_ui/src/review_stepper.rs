@@ -0,0 +1,85 @@
+//! Going over the questions to learn from after an exam was graded, one at a time.
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::Question;
+
+use crate::{
+    exam_state::{ExamResult, QuestionOutcome},
+    preferences::{use_preferences, QuestionNumbering},
+    ExamQuestion,
+};
+
+/// Why a question is reviewed.
+fn review_reason(result: &ExamResult, idx: usize) -> &'static str {
+    match result.outcomes[idx] {
+        QuestionOutcome::Wrong => "תשובה שגויה",
+        QuestionOutcome::Unanswered => "לא נענתה",
+        _ => "מסומנת",
+    }
+}
+
+/// Steps through the questions of [`ExamResult::to_review`], showing the user's answer against
+/// the correct one. `selections` are the answers of the exam, one per question.
+#[component]
+pub fn ReviewStepper(
+    questions: ReadOnlySignal<Vec<Question>>,
+    result: ExamResult,
+    selections: Vec<Option<usize>>,
+    language: &'static str,
+    numbering: QuestionNumbering,
+    on_exit: EventHandler<()>,
+) -> Element {
+    let to_review = result.to_review();
+    let mut position = use_signal(|| 0);
+    let preferences = use_preferences();
+    let Some(&idx) = to_review.get(position()) else {
+        return rsx! {};
+    };
+    let question = questions.read()[idx].clone();
+    let selection = selections.get(idx).copied().flatten();
+    let answer_text = |answer: usize| question.answers.possible_answers[answer].clone();
+
+    rsx! {
+        div { class: "review-stepper",
+            div { class: "review-stepper-header",
+                span { class: "review-position", {format!("{} מתוך {}", position() + 1, to_review.len())} }
+                span { class: "review-reason", {review_reason(&result, idx)} }
+                button { class: "button-primary", onclick: move |_| on_exit(()), "חזרה לתוצאות" }
+            }
+            ExamQuestion {
+                question: question.clone(),
+                language,
+                show_correct_answer: true,
+                user_selection: selection,
+                on_select: |_| {},
+                question_num: idx + 1,
+                category_link: true,
+                show_question_num: true,
+                use_canonical_question_num: numbering == QuestionNumbering::Canonical,
+                auto_read: preferences.read().read_aloud,
+            }
+            div { class: "review-answers",
+                div {
+                    match selection {
+                        Some(answer) => format!("התשובה שלך: {}", answer_text(answer)),
+                        None => "לא בחרת תשובה".to_string(),
+                    }
+                }
+                div { {format!("התשובה הנכונה: {}", answer_text(question.answers.correct_answer))} }
+            }
+            div { class: "review-stepper-controls",
+                button {
+                    class: "button-primary",
+                    disabled: position() == 0,
+                    onclick: move |_| position -= 1,
+                    "הקודמת"
+                }
+                button {
+                    class: "button-primary",
+                    disabled: position() + 1 == to_review.len(),
+                    onclick: move |_| position += 1,
+                    "הבאה"
+                }
+            }
+        }
+    }
+}
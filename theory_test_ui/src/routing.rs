@@ -0,0 +1,225 @@
+//! How routes appear in URLs.
+//!
+//! The app is served under a base path, `base_path` in Dioxus.toml or `dx build --base-path`,
+//! e.g. `/theory_test_exam` on GitHub Pages. By default routes follow it as paths,
+//! `/theory_test_exam/real_exam`, which the host must answer with the app. With the
+//! `hash-routing` feature they follow it after a `#`, `/theory_test_exam/#/real_exam`, so only
+//! the index page is ever requested, e.g. when refreshing a page on a static host which answers
+//! every other path with a 404.
+use dioxus::prelude::*;
+
+use crate::Route;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RoutingMode {
+    /// Routes are the path of the URL, after the base path.
+    Path,
+    /// Routes are the fragment of the URL of the index page.
+    Hash,
+}
+
+impl RoutingMode {
+    /// The mode the app was built with.
+    pub const CURRENT: Self = if cfg!(feature = "hash-routing") {
+        Self::Hash
+    } else {
+        Self::Path
+    };
+
+    /// What comes before each route in the URLs of the app served under `base_path`.
+    pub fn prefix(self, base_path: &str) -> String {
+        match self {
+            RoutingMode::Path => base_path.to_string(),
+            RoutingMode::Hash => format!("{base_path}/#"),
+        }
+    }
+}
+
+/// The base path the app is served under, e.g. "/theory_test_exam", empty at the root of its
+/// origin.
+pub fn base_path() -> String {
+    dioxus::cli_config::base_path()
+        .map(|path| path.trim_matches('/').to_string())
+        .filter(|path| !path.is_empty())
+        .map(|path| format!("/{path}"))
+        .unwrap_or_default()
+}
+
+/// The URL of `route` on `origin`, e.g. "https://example.com".
+pub fn url(origin: &str, base_path: &str, mode: RoutingMode, route: &Route) -> String {
+    format!("{origin}{}{route}", mode.prefix(base_path))
+}
+
+/// The absolute URL of `route`, for links which are copied or shared.
+pub async fn absolute_url(route: &Route) -> String {
+    let origin = document::eval("return location.origin;")
+        .join::<String>()
+        .await
+        .unwrap_or_default();
+    url(&origin, &base_path(), RoutingMode::CURRENT, route)
+}
+
+/// The route in the fragment of a URL, e.g. "/real_exam" of "#/real_exam". The first page for
+/// a fragment which isn't a route, e.g. of a link to the index page.
+#[cfg(any(all(feature = "hash-routing", target_arch = "wasm32"), test))]
+pub fn route_of_fragment(fragment: &str) -> &str {
+    fragment
+        .strip_prefix('#')
+        .filter(|route| route.starts_with('/'))
+        .unwrap_or("/")
+}
+
+/// Routing through the fragment of the URL, see [`RoutingMode::Hash`].
+#[cfg(all(feature = "hash-routing", target_arch = "wasm32"))]
+pub mod hash {
+    use std::sync::Arc;
+
+    use dioxus::history::History;
+    use wasm_bindgen::{prelude::Closure, JsCast, JsValue};
+    use web_sys::Window;
+
+    use super::{base_path, route_of_fragment, RoutingMode};
+
+    pub struct HashHistory {
+        window: Window,
+        prefix: String,
+    }
+
+    impl HashHistory {
+        pub fn new() -> Self {
+            Self {
+                window: web_sys::window().expect("the app runs in a window"),
+                prefix: RoutingMode::Hash.prefix(&base_path()),
+            }
+        }
+
+        fn history(&self) -> web_sys::History {
+            self.window.history().expect("the window has a history")
+        }
+
+        fn log(&self, result: Result<(), JsValue>) {
+            if let Err(error) = result {
+                web_sys::console::error_2(&JsValue::from_str("failed to navigate: "), &error);
+            }
+        }
+    }
+
+    impl History for HashHistory {
+        fn current_route(&self) -> String {
+            let fragment = self.window.location().hash().unwrap_or_default();
+            route_of_fragment(&fragment).to_string()
+        }
+
+        fn current_prefix(&self) -> Option<String> {
+            Some(self.prefix.clone())
+        }
+
+        fn go_back(&self) {
+            self.log(self.history().back());
+        }
+
+        fn go_forward(&self) {
+            self.log(self.history().forward());
+        }
+
+        fn push(&self, route: String) {
+            if route == self.current_route() {
+                return;
+            }
+            let url = format!("{}{route}", self.prefix);
+            self.log(
+                self.history()
+                    .push_state_with_url(&JsValue::NULL, "", Some(&url)),
+            );
+            self.window.scroll_to_with_x_and_y(0.0, 0.0);
+        }
+
+        fn replace(&self, route: String) {
+            let url = format!("{}{route}", self.prefix);
+            self.log(
+                self.history()
+                    .replace_state_with_url(&JsValue::NULL, "", Some(&url)),
+            );
+        }
+
+        fn external(&self, url: String) -> bool {
+            self.window.location().set_href(&url).is_ok()
+        }
+
+        fn updater(&self, callback: Arc<dyn Fn() + Send + Sync>) {
+            // going back and forth, and editing the fragment in the address bar
+            let listener = Closure::<dyn FnMut()>::new(move || callback());
+            self.window
+                .add_event_listener_with_callback(
+                    "popstate",
+                    listener.into_js_value().unchecked_ref(),
+                )
+                .expect("listening to the window can't fail");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn links() {
+        let route = Route::RealExam {
+            strict: true,
+            class: "C".to_string(),
+        };
+        assert_eq!(
+            url(
+                "https://example.com",
+                "/theory_test_exam",
+                RoutingMode::Path,
+                &route
+            ),
+            "https://example.com/theory_test_exam/real_exam?strict=true&class=C"
+        );
+        assert_eq!(
+            url(
+                "https://example.com",
+                "/theory_test_exam",
+                RoutingMode::Hash,
+                &route
+            ),
+            "https://example.com/theory_test_exam/#/real_exam?strict=true&class=C"
+        );
+        assert_eq!(
+            url(
+                "http://localhost:8080",
+                "",
+                RoutingMode::Hash,
+                &Route::MainPage
+            ),
+            "http://localhost:8080/#/"
+        );
+        assert_eq!(
+            url(
+                "http://localhost:8080",
+                "",
+                RoutingMode::Path,
+                &Route::MainPage
+            ),
+            "http://localhost:8080/"
+        );
+    }
+
+    #[test]
+    fn routes_of_fragments() {
+        let route = Route::RealExam {
+            strict: true,
+            class: "C".to_string(),
+        }
+        .to_string();
+        let prefix = RoutingMode::Hash.prefix("/theory_test_exam");
+        let link = format!("{prefix}{route}");
+        let (_, fragment) = link.split_once('#').unwrap();
+        assert_eq!(route_of_fragment(&format!("#{fragment}")), route);
+        assert_eq!(route_of_fragment(""), "/");
+        assert_eq!(route_of_fragment("#"), "/");
+        assert_eq!(route_of_fragment("#top"), "/");
+    }
+}
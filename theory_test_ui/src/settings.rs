@@ -0,0 +1,216 @@
+use dioxus::prelude::*;
+
+use crate::{
+    backup::BackupActions,
+    bank::{use_bank, BUNDLED_BANKS},
+    exam_state::PassThreshold,
+    image_cache::ImageCacheSettings,
+    notebook::GraduationStreak,
+    preferences::{use_preferences, Palette, QuestionNumbering, QuestionOrder, TextZoom},
+    storage,
+    sync::SyncSettingsSection,
+    Route,
+};
+
+/// The choice of the threshold of practice exams, shared with the practice exam builder.
+#[component]
+pub fn PassThresholdSelect() -> Element {
+    let mut preferences = use_preferences();
+    let current = preferences.read().pass_threshold;
+
+    rsx! {
+        label { class: "setting",
+            "סף מעבר בתרגול: "
+            select {
+                onchange: move |e| {
+                    if let Some(threshold) = e
+                        .value()
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|idx| PassThreshold::CHOICES.get(idx))
+                    {
+                        preferences.write().pass_threshold = *threshold;
+                    }
+                },
+                for (idx , threshold) in PassThreshold::CHOICES.iter().enumerate() {
+                    option { value: "{idx}", selected: *threshold == current, {threshold.description_he()} }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Settings() -> Element {
+    let mut preferences = use_preferences();
+    let bank = use_bank();
+    let nav = navigator();
+
+    rsx! {
+        div { class: "settings",
+            h1 { "הגדרות" }
+            label { class: "setting",
+                "מאגר שאלות: "
+                select {
+                    onchange: move |e| {
+                        if e.value() != bank.id {
+                            storage::switch_bank(&e.value());
+                        }
+                    },
+                    for bundled in BUNDLED_BANKS {
+                        option {
+                            value: bundled.id,
+                            selected: bundled.id == bank.id,
+                            {bundled.name_he}
+                        }
+                    }
+                }
+                " "
+                Link { to: Route::BankChanges {}, "מה חדש במאגר" }
+            }
+            PassThresholdSelect {}
+            label { class: "setting",
+                input {
+                    r#type: "checkbox",
+                    checked: preferences.read().hide_category_during_exam,
+                    oninput: move |e| {
+                        preferences.write().hide_category_during_exam = e.checked();
+                    },
+                }
+                "הסתר קטגוריה בזמן מבחן"
+            }
+            label { class: "setting",
+                input {
+                    r#type: "checkbox",
+                    checked: preferences.read().sound_feedback,
+                    oninput: move |e| {
+                        preferences.write().sound_feedback = e.checked();
+                    },
+                }
+                "צלילים במשוב מיידי"
+            }
+            label { class: "setting",
+                input {
+                    r#type: "checkbox",
+                    checked: preferences.read().category_palette == Palette::Colorblind,
+                    oninput: move |e| {
+                        preferences.write().category_palette = if e.checked() {
+                            Palette::Colorblind
+                        } else {
+                            Palette::Standard
+                        };
+                    },
+                }
+                "צבעי קטגוריות מותאמים לעיוורון צבעים"
+            }
+            label { class: "setting",
+                input {
+                    r#type: "checkbox",
+                    checked: preferences.read().auto_advance,
+                    oninput: move |e| {
+                        preferences.write().auto_advance = e.checked();
+                    },
+                }
+                "מעבר אוטומטי לשאלה הבאה שלא נענתה"
+            }
+            label { class: "setting",
+                input {
+                    r#type: "checkbox",
+                    checked: preferences.read().read_aloud,
+                    oninput: move |e| {
+                        preferences.write().read_aloud = e.checked();
+                    },
+                }
+                "הקראה אוטומטית של כל שאלה בתרגול שאלה אחר שאלה"
+            }
+            label { class: "setting",
+                "תשובות נכונות ברצף להוצאת שאלה ממחברת הטעויות: "
+                input {
+                    r#type: "number",
+                    min: "1",
+                    max: "10",
+                    value: "{preferences.read().notebook_graduation.0}",
+                    oninput: move |e| {
+                        if let Ok(streak) = e.value().parse::<usize>() {
+                            preferences.write().notebook_graduation = GraduationStreak(streak.clamp(1, 10));
+                        }
+                    },
+                }
+            }
+            label { class: "setting",
+                "מספור שאלות: "
+                select {
+                    onchange: move |e| {
+                        preferences.write().question_numbering = match e.value().as_str() {
+                            "canonical" => QuestionNumbering::Canonical,
+                            _ => QuestionNumbering::Sequential,
+                        };
+                    },
+                    option {
+                        value: "sequential",
+                        selected: preferences.read().question_numbering == QuestionNumbering::Sequential,
+                        "לפי הסדר במבחן (1, 2, 3...)"
+                    }
+                    option {
+                        value: "canonical",
+                        selected: preferences.read().question_numbering == QuestionNumbering::Canonical,
+                        "לפי המספר הרשמי (0862...)"
+                    }
+                }
+            }
+            label { class: "setting",
+                "סדר השאלות במבחן: "
+                select {
+                    onchange: move |e| {
+                        preferences.write().question_order = match e.value().as_str() {
+                            "category" => QuestionOrder::ByCategory,
+                            "number" => QuestionOrder::ByNumber,
+                            _ => QuestionOrder::Random,
+                        };
+                    },
+                    option {
+                        value: "random",
+                        selected: preferences.read().question_order == QuestionOrder::Random,
+                        "אקראי"
+                    }
+                    option {
+                        value: "category",
+                        selected: preferences.read().question_order == QuestionOrder::ByCategory,
+                        "לפי קטגוריה"
+                    }
+                    option {
+                        value: "number",
+                        selected: preferences.read().question_order == QuestionOrder::ByNumber,
+                        "לפי המספר הרשמי"
+                    }
+                }
+            }
+            div { class: "setting",
+                {format!("הגדלת טקסט השאלות: {}%", (preferences.read().question_zoom.scale() * 100.0).round())}
+                " "
+                button {
+                    class: "button-primary",
+                    disabled: preferences.read().question_zoom == TextZoom::default(),
+                    onclick: move |_| {
+                        preferences.write().question_zoom = TextZoom::default();
+                    },
+                    "איפוס"
+                }
+            }
+            ImageCacheSettings {}
+            h2 { "גיבוי" }
+            BackupActions {}
+            h2 { "סנכרון" }
+            SyncSettingsSection {}
+            div {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        }
+    }
+}
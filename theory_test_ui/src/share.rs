@@ -0,0 +1,195 @@
+//! A short text summary of a graded exam, to paste into a chat.
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+use crate::{
+    challenge,
+    exam_state::{ExamResult, QuestionOutcome},
+    qr_code::QrToggle,
+    result_card::ResultCardButton,
+    routing::absolute_url,
+    toast::use_toast,
+    ExamKind, Route,
+};
+
+pub fn kind_description(kind: ExamKind) -> &'static str {
+    match kind {
+        ExamKind::Real => "מבחן תיאוריה",
+        ExamKind::Practice => "מבחן תיאוריה תרגול",
+        ExamKind::DailyQuestion => "שאלת היום",
+        ExamKind::Drill => "תרגול מהיר",
+        ExamKind::SignDrill => "תרגול תמרורים",
+        ExamKind::Imported => "תרגול מיובא",
+    }
+}
+
+/// How many of the questions of each category were answered correctly, of those which weren't
+/// void, as `(category, correct, total)`. Categories without questions are left out.
+pub fn category_scores(
+    questions: &[Question],
+    result: &ExamResult,
+) -> Vec<(QuestionCategory, usize, usize)> {
+    QuestionCategory::ALL
+        .into_iter()
+        .filter_map(|category| {
+            let outcomes = questions
+                .iter()
+                .zip(&result.outcomes)
+                .filter(|(q, outcome)| q.category == category && **outcome != QuestionOutcome::Void)
+                .map(|(_, outcome)| *outcome)
+                .collect::<Vec<_>>();
+            if outcomes.is_empty() {
+                return None;
+            }
+            let correct = outcomes
+                .iter()
+                .filter(|o| **o == QuestionOutcome::Correct)
+                .count();
+            Some((category, correct, outcomes.len()))
+        })
+        .collect()
+}
+
+/// The summary of a graded exam, with a link to an exam of the same questions.
+pub fn summary_text(
+    kind: ExamKind,
+    bank_name: &str,
+    questions: &[Question],
+    result: &ExamResult,
+    link: &str,
+) -> String {
+    let verdict = if result.passed() {
+        "✅ עברתי"
+    } else {
+        "❌ נכשלתי"
+    };
+    let categories = category_scores(questions, result)
+        .into_iter()
+        .map(|(category, correct, total)| format!("{} {}/{}", category.as_str_he(), correct, total))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{}: {}/{} {} | {} | מאגר: {} | קישור לאותו מבחן: {}",
+        kind_description(kind),
+        result.correct(),
+        result.num_questions(),
+        verdict,
+        categories,
+        bank_name,
+        link,
+    )
+}
+
+/// The link to an exam of the same questions.
+async fn exam_link(questions: &[Question]) -> String {
+    let nums = questions.iter().map(|q| q.num).collect::<Vec<_>>();
+    let route = Route::Challenge {
+        qs: challenge::encode(&nums),
+    };
+    absolute_url(&route).await
+}
+
+/// Copies the summary of the exam, falling back to showing it when the clipboard can't be used.
+#[component]
+pub fn ShareResult(
+    kind: ExamKind,
+    bank_name: &'static str,
+    questions: ReadOnlySignal<Vec<Question>>,
+    result: ExamResult,
+) -> Element {
+    let mut toasts = use_toast();
+    let mut fallback = use_signal(|| None::<String>);
+    let link = use_resource(move || {
+        let questions = questions.read().clone();
+        async move { exam_link(&questions).await }
+    });
+
+    rsx! {
+        button {
+            class: "button-primary",
+            onclick: move |_| {
+                let result = result.clone();
+                async move {
+                    let questions = questions.read().clone();
+                    let link = exam_link(&questions).await;
+                    let text = summary_text(kind, bank_name, &questions, &result, &link);
+                    let written = document::eval(
+                            &format!(
+                                "try {{ await navigator.clipboard.writeText({:?}); return true; }} catch (e) {{ return false; }}",
+                                text,
+                            ),
+                        )
+                        .join::<bool>()
+                        .await
+                        .unwrap_or(false);
+                    if written {
+                        toasts.info("הסיכום הועתק ✓");
+                    } else {
+                        fallback.set(Some(text));
+                    }
+                }
+            },
+            "שתף תוצאה"
+        }
+        QrToggle { link: link.cloned() }
+        ResultCardButton { kind, bank_name, questions, result: result.clone() }
+        if let Some(text) = fallback() {
+            div { class: "share-fallback",
+                div { "לא ניתן להעתיק אוטומטית, אפשר לסמן ולהעתיק את הטקסט:" }
+                textarea { readonly: true, rows: 4, value: text }
+                button {
+                    class: "button-primary",
+                    onclick: move |_| fallback.set(None),
+                    "סגור"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::exam_state::PassThreshold;
+
+    #[test]
+    fn summary() {
+        let questions = vec![
+            Question {
+                category: QuestionCategory::RoadSigns,
+                ..Question::sample(1, 0)
+            },
+            Question {
+                category: QuestionCategory::TrafficLaws,
+                ..Question::sample(2, 0)
+            },
+            Question {
+                category: QuestionCategory::RoadSigns,
+                ..Question::sample(3, 0)
+            },
+        ];
+        let result = ExamResult {
+            outcomes: vec![
+                QuestionOutcome::Correct,
+                QuestionOutcome::Wrong,
+                QuestionOutcome::Correct,
+            ],
+            confidences: vec![None; 3],
+            flags: vec![false; 3],
+            timed_out: false,
+            elapsed: Duration::ZERO,
+            pass_threshold: PassThreshold::Official,
+            rules: None,
+        };
+        assert_eq!(
+            summary_text(ExamKind::Practice, "רכב", &questions, &result, "https://x/y"),
+            format!(
+                "מבחן תיאוריה תרגול: 2/3 ❌ נכשלתי | {} 0/1, {} 2/2 | מאגר: רכב | קישור לאותו מבחן: https://x/y",
+                QuestionCategory::TrafficLaws.as_str_he(),
+                QuestionCategory::RoadSigns.as_str_he(),
+            )
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Memorization of road signs: the road sign questions which have an image, one after the other,
+//! with immediate feedback.
+use std::{collections::HashSet, time::Duration};
+
+use dioxus::prelude::*;
+use rand::{seq::SliceRandom, SeedableRng};
+use theory_test_parser::question_parser::{Question, QuestionCategory};
+
+use crate::{
+    bank::use_bank,
+    exam_state::{PassThreshold, QuestionOutcome},
+    history::{use_history, Attempt, History, QuestionAttempt},
+    preferences::use_preferences,
+    timer, ExamKind, ExamQuestion, Route,
+};
+
+/// How long a correct answer is shown before moving on by itself.
+const CORRECT_ANSWER_DURATION: Duration = Duration::from_millis(1200);
+
+/// The road sign questions which have an image.
+pub fn sign_questions(questions: &[Question]) -> Vec<Question> {
+    questions
+        .iter()
+        .filter(|q| q.category == QuestionCategory::RoadSigns && q.image_url.is_some())
+        .cloned()
+        .collect()
+}
+
+/// How much of a pool of questions the history covers.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Coverage {
+    pub total: usize,
+    pub seen: usize,
+    pub correct: usize,
+}
+
+impl Coverage {
+    pub fn new(pool: &[Question], history: &History) -> Self {
+        let seen = history.seen();
+        let correct = history.answered_correctly();
+        Self {
+            total: pool.len(),
+            seen: pool.iter().filter(|q| seen.contains(&q.num)).count(),
+            correct: pool.iter().filter(|q| correct.contains(&q.num)).count(),
+        }
+    }
+}
+
+#[component]
+fn SignDrillSession(not_yet_correct: bool) -> Element {
+    let bank = use_bank();
+    let mut history = use_history();
+    let preferences = use_preferences();
+    let all_signs = use_hook(|| sign_questions(&bank.class_questions()));
+    let questions = use_hook(|| {
+        let correct: HashSet<usize> = if not_yet_correct {
+            history.peek().answered_correctly()
+        } else {
+            HashSet::new()
+        };
+        let mut questions = all_signs
+            .iter()
+            .filter(|q| !correct.contains(&q.num))
+            .cloned()
+            .collect::<Vec<_>>();
+        questions.shuffle(&mut rand_pcg::Pcg64::from_os_rng());
+        questions
+    });
+    let started_at = use_hook(timer::now);
+    let mut current = use_signal(|| 0);
+    let mut selection = use_signal(|| None::<usize>);
+    let mut answers = use_signal(Vec::<QuestionAttempt>::new);
+    let mut broken_images = use_signal(|| 0);
+    let coverage = Coverage::new(&all_signs, &history.read());
+    let num_correct = answers
+        .read()
+        .iter()
+        .filter(|a| a.outcome == QuestionOutcome::Correct)
+        .count();
+
+    let mut advance = move || {
+        current += 1;
+        selection.set(None);
+    };
+    let question = questions.get(current()).cloned();
+
+    rsx! {
+        div { class: "sign-drill",
+            h1 { "תרגול תמרורים עם תמונה" }
+            div { class: "sign-drill-coverage",
+                {
+                    format!(
+                        "{} שאלות תמרורים עם תמונה · ראית {} · ענית נכון על {}",
+                        coverage.total,
+                        coverage.seen,
+                        coverage.correct,
+                    )
+                }
+                progress {
+                    value: coverage.correct as f64,
+                    max: coverage.total.max(1) as f64,
+                }
+            }
+            label {
+                input {
+                    r#type: "checkbox",
+                    checked: not_yet_correct,
+                    oninput: move |e| {
+                        navigator()
+                            .replace(Route::SignDrill {
+                                not_yet_correct: e.checked(),
+                            });
+                    },
+                }
+                "רק שאלות שטרם עניתי נכון"
+            }
+            div { {format!("בתרגול הזה: {}/{} נכונות", num_correct, answers.read().len())} }
+            if broken_images() > 0 {
+                div { class: "sign-drill-broken",
+                    {format!("דולגו {} שאלות שהתמונה שלהן לא נטענה", broken_images())}
+                }
+            }
+            if let Some(question) = question {
+                div { class: "sign-drill-question",
+                    ExamQuestion {
+                        key: "{question.num}",
+                        question: question.clone(),
+                        language: bank.language,
+                        show_correct_answer: selection().is_some(),
+                        user_selection: selection(),
+                        on_select: move |answer| {
+                            if selection().is_some() {
+                                return;
+                            }
+                            selection.set(Some(answer));
+                            let correct = answer == question.answers.correct_answer;
+                            answers
+                                .write()
+                                .push(QuestionAttempt::new(question.num, if correct {
+                                        QuestionOutcome::Correct
+                                    } else {
+                                        QuestionOutcome::Wrong
+                                    }));
+                            // the attempt of the session is replaced with every answer
+                            let now = timer::now();
+                            history
+                                .write()
+                                .record(Attempt {
+                                    started_at,
+                                    graded_at: now,
+                                    kind: ExamKind::SignDrill,
+                                    strict: false,
+                                    abandoned: false,
+                                    questions: answers.read().clone(),
+                                    duration: Some(now.saturating_sub(started_at)),
+                                    pass_threshold: PassThreshold::Official,
+                                    rules: None,
+                                });
+                            if correct {
+                                let answered = current();
+                                let mut advance = advance;
+                                spawn(async move {
+                                    timer::sleep(CORRECT_ANSWER_DURATION).await;
+                                    if current() == answered {
+                                        advance();
+                                    }
+                                });
+                            }
+                        },
+                        on_image_error: move |_| {
+                            broken_images += 1;
+                            advance();
+                        },
+                        question_num: current() + 1,
+                        show_category: false,
+                        show_question_num: false,
+                        use_canonical_question_num: false,
+                        auto_read: preferences.read().read_aloud,
+                    }
+                }
+                if selection().is_some() {
+                    button { class: "button-primary", onclick: move |_| advance(), "הבא" }
+                }
+            } else {
+                div { class: "sign-drill-done",
+                    if questions.is_empty() {
+                        "אין שאלות לתרגול."
+                    } else {
+                        "עברת על כל השאלות."
+                    }
+                }
+            }
+            div {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        navigator().push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        }
+    }
+}
+
+/// The drill, restarted whenever its filter changes.
+#[component]
+pub fn SignDrill(not_yet_correct: bool) -> Element {
+    rsx! {
+        SignDrillSession { key: "{not_yet_correct}", not_yet_correct }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use theory_test_parser::question_parser::LicenseClass;
+
+    fn question(num: usize, category: QuestionCategory, image: bool) -> Question {
+        Question {
+            category,
+            license_classes: vec![LicenseClass::B],
+            image_url: image.then(|| format!("https://example.com/{}.png", num)),
+            ..Question::sample(num, 0)
+        }
+    }
+
+    #[test]
+    fn coverage_of_signs() {
+        let questions = vec![
+            question(1, QuestionCategory::RoadSigns, true),
+            question(2, QuestionCategory::RoadSigns, false),
+            question(3, QuestionCategory::Safety, true),
+            question(4, QuestionCategory::RoadSigns, true),
+            question(5, QuestionCategory::RoadSigns, true),
+        ];
+        let pool = sign_questions(&questions);
+        assert_eq!(
+            pool.iter().map(|q| q.num).collect::<Vec<_>>(),
+            vec![1, 4, 5]
+        );
+        let history = History {
+            attempts: vec![Attempt {
+                kind: ExamKind::SignDrill,
+                ..Attempt::sample(
+                    0,
+                    &[
+                        (1, QuestionOutcome::Correct),
+                        (4, QuestionOutcome::Wrong),
+                        (3, QuestionOutcome::Correct),
+                    ],
+                )
+            }],
+        };
+        assert_eq!(
+            Coverage::new(&pool, &history),
+            Coverage {
+                total: 3,
+                seen: 2,
+                correct: 1
+            }
+        );
+    }
+}
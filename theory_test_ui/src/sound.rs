@@ -0,0 +1,63 @@
+//! Short generated tones, played through the Web Audio API.
+//!
+//! Playing never fails: when audio is unavailable, e.g. blocked until the user interacts with the
+//! page, the cue is silently skipped.
+use dioxus::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Cue {
+    Correct,
+    Wrong,
+    /// The time of the exam is about to run out.
+    TimeWarning,
+    TimeUp,
+}
+
+impl Cue {
+    /// The notes of the cue, as `(frequency in Hz, duration in seconds)`.
+    fn notes(&self) -> &'static [(f32, f32)] {
+        match self {
+            Cue::Correct => &[(660.0, 0.08), (880.0, 0.12)],
+            Cue::Wrong => &[(220.0, 0.25)],
+            Cue::TimeWarning => &[(523.0, 0.15), (523.0, 0.15)],
+            Cue::TimeUp => &[(784.0, 0.2), (523.0, 0.2), (330.0, 0.4)],
+        }
+    }
+}
+
+pub fn play(cue: Cue) {
+    let notes = cue
+        .notes()
+        .iter()
+        .map(|(frequency, duration)| format!("[{}, {}]", frequency, duration))
+        .collect::<Vec<_>>()
+        .join(", ");
+    document::eval(&format!(
+        r#"
+        try {{
+            const AudioContext = window.AudioContext || window.webkitAudioContext;
+            if (!AudioContext) {{
+                return;
+            }}
+            window.__audioContext ??= new AudioContext();
+            const context = window.__audioContext;
+            if (context.state === 'suspended') {{
+                context.resume().catch(() => {{}});
+            }}
+            let time = context.currentTime;
+            for (const [frequency, duration] of [{}]) {{
+                const oscillator = context.createOscillator();
+                const gain = context.createGain();
+                oscillator.frequency.value = frequency;
+                gain.gain.setValueAtTime(0.2, time);
+                gain.gain.exponentialRampToValueAtTime(0.001, time + duration);
+                oscillator.connect(gain).connect(context.destination);
+                oscillator.start(time);
+                oscillator.stop(time + duration);
+                time += duration;
+            }}
+        }} catch (e) {{}}
+        "#,
+        notes
+    ));
+}
@@ -0,0 +1,218 @@
+//! Reading questions and their answers aloud with the browser's speech synthesis, for learners
+//! who find reading hard. Where the browser can't speak, nothing is shown.
+use std::{cell::Cell, rc::Rc};
+
+use dioxus::prelude::*;
+
+const HEBREW_LETTERS: [&str; 10] = ["א", "ב", "ג", "ד", "ה", "ו", "ז", "ח", "ט", "י"];
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct Speech {
+    /// Whether the browser can speak, `false` until it was checked.
+    available: bool,
+    /// The question being read and the number of the reading, which tells a reading which was
+    /// stopped from the one which replaced it.
+    speaking: Option<(usize, u64)>,
+    readings: u64,
+}
+
+/// Provide the speech to the whole app, checking whether the browser can speak.
+pub fn provide_speech() {
+    let mut speech = use_context_provider(|| Signal::new(Speech::default()));
+    use_future(move || async move {
+        let available = document::eval(
+            "return 'speechSynthesis' in window && typeof SpeechSynthesisUtterance !== 'undefined';",
+        )
+        .join::<bool>()
+        .await
+        .unwrap_or(false);
+        speech.write().available = available;
+    });
+}
+
+/// Entities the bank's texts may still have, as they are taken from its html.
+const ENTITIES: [(&str, &str); 6] = [
+    ("&nbsp;", " "),
+    ("&quot;", "\""),
+    ("&#39;", "'"),
+    ("&lt;", "<"),
+    ("&gt;", ">"),
+    ("&amp;", "&"),
+];
+
+/// `text` without tags and entities, with runs of whitespace as a single space.
+fn plain_text(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                plain.push(' ');
+            }
+            c if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    let plain = ENTITIES
+        .iter()
+        .fold(plain, |plain, (entity, c)| plain.replace(entity, c));
+    plain.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// What is read for a question: its text, then each answer after its letter, or its number in
+/// other languages than Hebrew.
+pub fn speech_text(question: &str, answers: &[String], language: &str) -> String {
+    let mut text = plain_text(question);
+    for (idx, answer) in answers.iter().enumerate() {
+        let label = match HEBREW_LETTERS.get(idx) {
+            Some(letter) if language == "he" => letter.to_string(),
+            _ => (idx + 1).to_string(),
+        };
+        text.push_str(&format!("\n{label}. {}", plain_text(answer)));
+    }
+    text
+}
+
+/// Read `text` of question `num` aloud in `language`, stopping whatever is being read.
+fn speak(mut speech: Signal<Speech>, num: usize, text: &str, language: &str) {
+    let reading = {
+        let mut speech = speech.write();
+        speech.readings += 1;
+        speech.speaking = Some((num, speech.readings));
+        speech.readings
+    };
+    // a voice of the language if the browser has one, its default voice otherwise. Chrome loads
+    // its voices only after they were first asked for
+    let eval = document::eval(&format!(
+        r#"
+        const synthesis = window.speechSynthesis;
+        synthesis.cancel();
+        let voices = synthesis.getVoices();
+        if (!voices.length) {{
+            await new Promise((resolve) => {{
+                synthesis.addEventListener('voiceschanged', resolve, {{ once: true }});
+                setTimeout(resolve, 1000);
+            }});
+            voices = synthesis.getVoices();
+        }}
+        const utterance = new SpeechSynthesisUtterance({text:?});
+        utterance.lang = {language:?};
+        const voice = voices.find((voice) => voice.lang.toLowerCase().startsWith({language:?}));
+        if (voice) {{
+            utterance.voice = voice;
+        }}
+        return await new Promise((resolve) => {{
+            utterance.onend = () => resolve(true);
+            utterance.onerror = () => resolve(false);
+            synthesis.speak(utterance);
+        }});
+        "#
+    ));
+    spawn(async move {
+        let _ = eval.join::<bool>().await;
+        if speech.peek().speaking == Some((num, reading)) {
+            speech.write().speaking = None;
+        }
+    });
+}
+
+/// Stop reading question `num` aloud, if it is being read.
+fn stop(mut speech: Signal<Speech>, num: usize) {
+    if speech
+        .peek()
+        .speaking
+        .is_some_and(|(speaking, _)| speaking == num)
+    {
+        speech.write().speaking = None;
+        document::eval("window.speechSynthesis.cancel();");
+    }
+}
+
+/// A button reading question `num` aloud, or stopping the reading. The reading stops when the
+/// question is left, and with `auto_read` starts by itself when the question is shown. Nothing
+/// is shown when the browser can't speak.
+#[component]
+pub fn SpeakButton(num: usize, text: String, language: &'static str, auto_read: bool) -> Element {
+    // missing outside of the app, e.g. in tests
+    let speech = use_hook(try_consume_context::<Signal<Speech>>);
+    let available = use_memo(move || speech.is_some_and(|speech| speech.read().available));
+    // the question last shown, to stop reading it once another one is
+    let shown = use_hook(|| Rc::new(Cell::new(num)));
+    use_effect({
+        let shown = shown.clone();
+        let text = text.clone();
+        use_reactive!(|(num, text, auto_read)| {
+            let Some(speech) = speech.filter(|_| available()) else {
+                return;
+            };
+            let left = shown.replace(num);
+            if left != num {
+                stop(speech, left);
+            }
+            if auto_read {
+                speak(speech, num, &text, language);
+            }
+        })
+    });
+    use_drop(move || {
+        if let Some(speech) = speech {
+            stop(speech, shown.get());
+        }
+    });
+
+    let Some(speech) = speech.filter(|_| available()) else {
+        return rsx! {};
+    };
+    let speaking = speech
+        .read()
+        .speaking
+        .is_some_and(|(speaking, _)| speaking == num);
+    rsx! {
+        button {
+            class: "speak-button",
+            class: if speaking { "active" },
+            title: "הקראת השאלה והתשובות",
+            aria_pressed: speaking,
+            onclick: move |_| {
+                if speaking {
+                    stop(speech, num);
+                } else {
+                    speak(speech, num, &text, language);
+                }
+            },
+            if speaking {
+                "⏹ עצור הקראה"
+            } else {
+                "🔊 הקרא"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn spoken_text() {
+        assert_eq!(
+            plain_text("  מה <b>משמעות</b>&nbsp;התמרור?\n"),
+            "מה משמעות התמרור?"
+        );
+        assert_eq!(
+            plain_text("&quot;50&quot; קמ&quot;ש &lt; 70"),
+            "\"50\" קמ\"ש < 70"
+        );
+        let answers = ["עצור".to_string(), "תן זכות קדימה".to_string()];
+        assert_eq!(
+            speech_text("מה משמעות התמרור?", &answers, "he"),
+            "מה משמעות התמרור?\nא. עצור\nב. תן זכות קדימה"
+        );
+        assert_eq!(
+            speech_text("What does the sign mean?", &answers[..1], "en"),
+            "What does the sign mean?\n1. עצור"
+        );
+    }
+}
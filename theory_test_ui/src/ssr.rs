@@ -0,0 +1,91 @@
+//! Rendering pages on the server, with the `server` feature, to be hydrated in the browser.
+//!
+//! Only the pages worth indexing render on the server, the browse page and the pages of single
+//! questions, see [`Route::renders_on_server`]. The exams are interactive, so they only render
+//! in the browser, once it took over the page. Nothing is read from or written to the storage of
+//! the browser on the server, so pages render as they would for a new user.
+use dioxus::prelude::*;
+
+use crate::Route;
+
+/// Whether the app is rendering pages on the server.
+pub const ON_SERVER: bool = cfg!(feature = "server");
+
+impl Route {
+    /// Whether the page renders on the server too, rather than only in the browser.
+    pub fn renders_on_server(&self) -> bool {
+        matches!(self, Route::Browse { .. } | Route::QuestionPage { .. })
+    }
+}
+
+/// The layout of every route, which leaves the pages which don't render on the server empty
+/// until the page was hydrated.
+#[component]
+pub fn Hydrated() -> Element {
+    let route = use_route::<Route>();
+    if route.renders_on_server() {
+        rsx! {
+            Outlet::<Route> {}
+        }
+    } else {
+        rsx! {
+            ClientOnly {
+                Outlet::<Route> {}
+            }
+        }
+    }
+}
+
+/// Renders its children only in the browser, once the page was hydrated, as the server rendered
+/// nothing in their place.
+#[component]
+pub fn ClientOnly(children: Element) -> Element {
+    let mut hydrated = use_signal(|| false);
+    // effects never run on the server, and run in the browser once the page was hydrated
+    use_effect(move || hydrated.set(true));
+    if hydrated() {
+        children
+    } else {
+        rsx! {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use dioxus::history::{History, MemoryHistory};
+    use theory_test_core::exam_state::question_body;
+
+    use super::*;
+    use crate::{
+        bank::{load, BankSource, BUNDLED_BANKS},
+        App,
+    };
+
+    /// The html the server renders for `path`.
+    fn render(path: &str) -> String {
+        let mut dom = VirtualDom::new(App);
+        dom.provide_root_context(Rc::new(MemoryHistory::with_initial_path(path)) as Rc<dyn History>);
+        dom.rebuild_in_place();
+        dioxus_ssr::render(&dom)
+    }
+
+    #[test]
+    fn question_page() {
+        let bank = load(BankSource::Bundled(BUNDLED_BANKS[0].id)).unwrap();
+        let question = bank
+            .questions
+            .questions
+            .iter()
+            .find(|q| q.num == 862)
+            .unwrap();
+        let html = render("/question/0862");
+        assert!(html.contains(question_body(question)), "{html}");
+        for answer in &question.answers.possible_answers {
+            assert!(html.contains(answer.as_str()));
+        }
+        // an exam renders only in the browser
+        assert!(!render("/real_exam?strict=false").contains("exam-header"));
+    }
+}
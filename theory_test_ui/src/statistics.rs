@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+use theory_test_parser::question_parser::Question;
+
+use crate::{
+    achievements::{use_achievements, Achievement, AchievementContext},
+    bank::use_bank,
+    history::{use_history, QuestionStats},
+    personal_best::use_personal_bests,
+    timer,
+    trend_chart::TrendChart,
+    Route,
+};
+
+/// Questions need this many attempts to be among the hardest, fewer say little about them.
+const HARDEST_MIN_ATTEMPTS: usize = 3;
+const HARDEST_LIMIT: usize = 10;
+
+/// The questions of `pool` with the lowest accuracy out of those with at least `min_attempts`
+/// attempts, the hardest first. Questions which aren't in `pool`, e.g. recorded with another
+/// version of the bank, are skipped.
+fn hardest_questions(
+    stats: &HashMap<usize, QuestionStats>,
+    pool: &[Question],
+    min_attempts: usize,
+    limit: usize,
+) -> Vec<(Question, QuestionStats)> {
+    let mut hardest = pool
+        .iter()
+        .filter_map(|question| {
+            let stats = stats.get(&question.num)?;
+            (stats.attempts >= min_attempts).then(|| (question.clone(), *stats))
+        })
+        .collect::<Vec<_>>();
+    hardest.sort_by(|(a, a_stats), (b, b_stats)| {
+        a_stats
+            .accuracy()
+            .total_cmp(&b_stats.accuracy())
+            .then(b_stats.attempts.cmp(&a_stats.attempts))
+            .then(a.num.cmp(&b.num))
+    });
+    hardest.truncate(limit);
+    hardest
+}
+
+/// The questions answered wrong most often, measured from the local history only.
+#[component]
+fn HardestQuestions() -> Element {
+    let history = use_history();
+    let bank = use_bank();
+    let hardest = hardest_questions(
+        &history.read().question_stats(),
+        &bank.questions.questions,
+        HARDEST_MIN_ATTEMPTS,
+        HARDEST_LIMIT,
+    );
+
+    rsx! {
+        div { class: "hardest-questions",
+            div { class: "hardest-questions-note",
+                {
+                    format!(
+                        "לפי התשובות שלך בלבד, מתוך שאלות שענית עליהן לפחות {} פעמים.",
+                        HARDEST_MIN_ATTEMPTS,
+                    )
+                }
+            }
+            if hardest.is_empty() {
+                div { "עדיין אין מספיק נתונים." }
+            } else {
+                table {
+                    for (question , stats) in hardest {
+                        tr { key: "{question.num}",
+                            td { {question.question.clone()} }
+                            td { {format!("{}/{}", stats.correct, stats.attempts)} }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The unlocked achievements, followed by the locked ones closest to being unlocked.
+#[component]
+fn AchievementList() -> Element {
+    let history = use_history();
+    let unlocked = use_achievements();
+    let pool = use_bank().class_questions();
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let utc_offset = utc_offset().unwrap_or(0);
+    let history = history.read();
+    let context = AchievementContext {
+        history: &history,
+        pool: &pool,
+        utc_offset_secs: utc_offset,
+    };
+    let mut achievements = Achievement::ALL
+        .map(|achievement| {
+            let progress = achievement.progress(&context);
+            let unlocked_at = unlocked.read().0.get(&achievement).copied();
+            (achievement, progress, unlocked_at)
+        })
+        .to_vec();
+    achievements.sort_by(|(_, a, a_unlocked), (_, b, b_unlocked)| {
+        b_unlocked
+            .is_some()
+            .cmp(&a_unlocked.is_some())
+            .then(b.fraction().total_cmp(&a.fraction()))
+    });
+
+    rsx! {
+        div { class: "achievements",
+            for (achievement , progress , unlocked_at) in achievements {
+                div {
+                    class: "achievement",
+                    class: if unlocked_at.is_some() { "unlocked" },
+                    div { class: "achievement-title", {achievement.title_he()} }
+                    div { {achievement.description_he()} }
+                    if let Some(unlocked_at) = unlocked_at {
+                        div { class: "achievement-progress",
+                            {format!("הושג ב־{}", timer::format_date_time(unlocked_at, utc_offset))}
+                        }
+                    } else {
+                        div { class: "achievement-progress",
+                            progress { value: progress.current, max: progress.target }
+                            {format!(" {}/{}", progress.current, progress.target)}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The best score of each exam configuration.
+#[component]
+fn PersonalBestList() -> Element {
+    let bests = use_personal_bests();
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let utc_offset = utc_offset().unwrap_or(0);
+    let bests = bests.read().all();
+    if bests.is_empty() {
+        return rsx! {
+            div { "עדיין אין שיאים." }
+        };
+    }
+
+    rsx! {
+        table { class: "personal-bests",
+            for (config , best) in bests {
+                tr {
+                    td { {config.description_he()} }
+                    td { {format!("{}/{}", best.correct, config.num_questions)} }
+                    td { {timer::format_date_time(best.graded_at, utc_offset)} }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn Statistics() -> Element {
+    let nav = navigator();
+
+    rsx! {
+        div { class: "statistics",
+            h1 { "סטטיסטיקה" }
+            h2 { "מגמה במבחנים האחרונים" }
+            TrendChart {}
+            h2 { "שיאים אישיים" }
+            PersonalBestList {}
+            h2 { "הישגים" }
+            AchievementList {}
+            h2 { "השאלות הקשות ביותר עבורי" }
+            HardestQuestions {}
+            div {
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        nav.push(Route::MainPage);
+                    },
+                    "חזרה לדף הראשי"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hardest() {
+        let stats = |attempts, correct| QuestionStats { attempts, correct };
+        let history_stats = HashMap::from([
+            (1, stats(4, 3)),
+            (2, stats(3, 0)),
+            (3, stats(2, 0)),
+            (4, stats(6, 3)),
+            // recorded with another bank, which has no question 9
+            (9, stats(5, 0)),
+        ]);
+        let pool = [1, 2, 3, 4].map(|num| Question::sample(num, 0));
+        let nums = |limit| {
+            hardest_questions(&history_stats, &pool, 3, limit)
+                .iter()
+                .map(|(q, _)| q.num)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(nums(10), vec![2, 4, 1]);
+        assert_eq!(nums(1), vec![2]);
+    }
+}
@@ -0,0 +1,738 @@
+//! Persistence of values in the browser, as json.
+//!
+//! Values are stored per profile and question bank: [`load`] and [`save`] use the keys of the
+//! active profile and bank, [`load_profile`] and [`save_profile`] are for values of the profile
+//! which don't depend on the bank, and [`load_global`] and [`save_global`] are for the few values
+//! shared by all profiles.
+//!
+//! All of them go through a [`Storage`], which in the browser keeps small values in local storage
+//! and moves large ones, e.g. a long history, to IndexedDB, since local storage is limited to a
+//! few megabytes.
+//!
+//! Values are stored with the version of their shape, and upgraded by the migrations of
+//! [`migration`] when they are loaded. A value stored by a newer version of the app is left as
+//! it is: it isn't loaded, and the value of its key is never saved until the page reloads.
+use std::{cell::OnceCell, future::Future};
+
+use dioxus::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use theory_test_core::migration::{self, MigrationError};
+
+use crate::toast::use_toast;
+
+/// The id of the bank whose values were stored before there were several banks, so its keys
+/// aren't namespaced by the bank.
+pub const DEFAULT_BANK: &str = "b";
+
+/// The size of the largest value kept in local storage, larger ones are kept in IndexedDB.
+const LOCAL_STORAGE_LIMIT: usize = 64 * 1024;
+
+/// A store of string values by key.
+///
+/// Writes (`set` and `delete`) are issued when they are called rather than when their futures
+/// are first polled, so they take effect in the order they were called, even if their futures
+/// are never awaited. Awaiting them waits until they're done. Their futures don't borrow the
+/// storage or the arguments, so they can be spawned.
+pub trait Storage {
+    fn get(&self, key: &str) -> impl Future<Output = Option<String>>;
+    fn set(&self, key: &str, value: &str) -> impl Future<Output = ()> + use<Self>;
+    fn delete(&self, key: &str) -> impl Future<Output = ()> + use<Self>;
+    /// The keys starting with `prefix`, sorted.
+    fn list(&self, prefix: &str) -> impl Future<Output = Vec<String>>;
+}
+
+/// The browser's local storage, which is synchronous but small.
+pub struct LocalStorage;
+
+impl Storage for LocalStorage {
+    fn get(&self, key: &str) -> impl Future<Output = Option<String>> {
+        let eval = dioxus::document::eval(&format!("return localStorage.getItem({:?});", key));
+        async move { eval.join::<Option<String>>().await.ok().flatten() }
+    }
+
+    fn set(&self, key: &str, value: &str) -> impl Future<Output = ()> + use<> {
+        let eval = dioxus::document::eval(&format!(
+            "if (!window.__storageFrozen) {{ localStorage.setItem({:?}, {:?}); }}",
+            key, value
+        ));
+        async move {
+            let _ = eval.await;
+        }
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = ()> + use<> {
+        let eval = dioxus::document::eval(&format!(
+            "if (!window.__storageFrozen) {{ localStorage.removeItem({:?}); }}",
+            key
+        ));
+        async move {
+            let _ = eval.await;
+        }
+    }
+
+    fn list(&self, prefix: &str) -> impl Future<Output = Vec<String>> {
+        let eval = dioxus::document::eval(&format!(
+            r#"
+            const keys = [];
+            for (let i = 0; i < localStorage.length; i++) {{
+                const key = localStorage.key(i);
+                if (key.startsWith({:?})) {{
+                    keys.push(key);
+                }}
+            }}
+            return keys.sort();
+            "#,
+            prefix
+        ));
+        async move { eval.join::<Vec<String>>().await.unwrap_or_default() }
+    }
+}
+
+/// Opens the database once per page load and defines `valuesRequest`, which runs a request on
+/// the store of the values and resolves to its result.
+const INDEXED_DB_PRELUDE: &str = r#"
+window.__indexedDb ??= new Promise((resolve, reject) => {
+    const request = indexedDB.open('theory-test', 1);
+    request.onupgradeneeded = () => request.result.createObjectStore('values');
+    request.onsuccess = () => resolve(request.result);
+    request.onerror = () => reject(request.error);
+});
+const valuesRequest = async (mode, makeRequest) => {
+    const db = await window.__indexedDb;
+    return new Promise((resolve, reject) => {
+        const request = makeRequest(db.transaction('values', mode).objectStore('values'));
+        request.onsuccess = () => resolve(request.result);
+        request.onerror = () => reject(request.error);
+    });
+};
+"#;
+
+/// The browser's IndexedDB, which is asynchronous but can hold much more than local storage.
+pub struct IndexedDb;
+
+impl IndexedDb {
+    fn script(body: &str) -> String {
+        format!("{}\n{}", INDEXED_DB_PRELUDE, body)
+    }
+
+    fn get_script(key: &str) -> String {
+        Self::script(&format!(
+            "return (await valuesRequest('readonly', (values) => values.get({:?}))) ?? null;",
+            key
+        ))
+    }
+
+    fn set_script(key: &str, value: &str) -> String {
+        Self::script(&format!(
+            r#"
+            if (!window.__storageFrozen) {{
+                await valuesRequest('readwrite', (values) => values.put({:?}, {:?}));
+            }}
+            "#,
+            value, key
+        ))
+    }
+
+    fn delete_script(key: &str) -> String {
+        Self::script(&format!(
+            r#"
+            if (!window.__storageFrozen) {{
+                await valuesRequest('readwrite', (values) => values.delete({:?}));
+            }}
+            "#,
+            key
+        ))
+    }
+
+    fn list_script(prefix: &str) -> String {
+        Self::script(&format!(
+            r#"
+            const prefix = {:?};
+            const range = IDBKeyRange.bound(prefix, prefix + '\uffff');
+            const keys = await valuesRequest('readonly', (values) => values.getAllKeys(range));
+            return keys.filter((key) => key.startsWith(prefix)).sort();
+            "#,
+            prefix
+        ))
+    }
+}
+
+impl Storage for IndexedDb {
+    fn get(&self, key: &str) -> impl Future<Output = Option<String>> {
+        let eval = dioxus::document::eval(&Self::get_script(key));
+        async move { eval.join::<Option<String>>().await.ok().flatten() }
+    }
+
+    fn set(&self, key: &str, value: &str) -> impl Future<Output = ()> + use<> {
+        // the requests are queued on the database in the order the scripts run
+        let eval = dioxus::document::eval(&Self::set_script(key, value));
+        async move {
+            let _ = eval.await;
+        }
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = ()> + use<> {
+        let eval = dioxus::document::eval(&Self::delete_script(key));
+        async move {
+            let _ = eval.await;
+        }
+    }
+
+    fn list(&self, prefix: &str) -> impl Future<Output = Vec<String>> {
+        let eval = dioxus::document::eval(&Self::list_script(prefix));
+        async move { eval.join::<Vec<String>>().await.unwrap_or_default() }
+    }
+}
+
+/// Keeps values of up to `limit` bytes in `small`, and larger ones in `large`. A value is in
+/// only one of them at a time, moving when its size crosses the limit.
+pub struct Tiered<S, L> {
+    pub small: S,
+    pub large: L,
+    pub limit: usize,
+}
+
+impl<S: Storage, L: Storage> Storage for Tiered<S, L> {
+    async fn get(&self, key: &str) -> Option<String> {
+        match self.small.get(key).await {
+            Some(value) => Some(value),
+            None => self.large.get(key).await,
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> impl Future<Output = ()> + use<S, L> {
+        // the value is written before it's removed from the other storage, so it's never missing
+        let fits = value.len() <= self.limit;
+        let small_set = fits.then(|| self.small.set(key, value));
+        let large_set = (!fits).then(|| self.large.set(key, value));
+        let small_delete = (!fits).then(|| self.small.delete(key));
+        let large_delete = fits.then(|| self.large.delete(key));
+        async move {
+            if let Some(set) = small_set {
+                set.await;
+            }
+            if let Some(set) = large_set {
+                set.await;
+            }
+            if let Some(delete) = small_delete {
+                delete.await;
+            }
+            if let Some(delete) = large_delete {
+                delete.await;
+            }
+        }
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = ()> + use<S, L> {
+        let small = self.small.delete(key);
+        let large = self.large.delete(key);
+        async move {
+            small.await;
+            large.await;
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Vec<String> {
+        let mut keys = self.small.list(prefix).await;
+        keys.extend(self.large.list(prefix).await);
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+/// Values kept in memory, for tests. Clones share the values.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    values: std::rc::Rc<std::cell::RefCell<std::collections::BTreeMap<String, String>>>,
+}
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> impl Future<Output = Option<String>> {
+        std::future::ready(self.values.borrow().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> impl Future<Output = ()> + use<> {
+        self.values
+            .borrow_mut()
+            .insert(key.to_string(), value.to_string());
+        std::future::ready(())
+    }
+
+    fn delete(&self, key: &str) -> impl Future<Output = ()> + use<> {
+        self.values.borrow_mut().remove(key);
+        std::future::ready(())
+    }
+
+    fn list(&self, prefix: &str) -> impl Future<Output = Vec<String>> {
+        let values = self.values.borrow();
+        std::future::ready(
+            values
+                .range(prefix.to_string()..)
+                .map(|(key, _)| key)
+                .take_while(|key| key.starts_with(prefix))
+                .cloned()
+                .collect(),
+        )
+    }
+}
+
+/// The storage of the app.
+const BROWSER: Tiered<LocalStorage, IndexedDb> = Tiered {
+    small: LocalStorage,
+    large: IndexedDb,
+    limit: LOCAL_STORAGE_LIMIT,
+};
+
+/// Defines `window.__profile` and `window.__bank`, the active profile and bank.
+///
+/// They are read once per page load, switching them reloads the page, so a save which is still
+/// pending while switching can't land in the other profile or bank.
+const PROFILE_PRELUDE: &str = r#"
+window.__profile ??= (() => {
+    let active = localStorage.getItem('active-profile');
+    if (active === null) {
+        // data stored before profiles existed belongs to the first profile
+        const keys = [];
+        for (let i = 0; i < localStorage.length; i++) {
+            keys.push(localStorage.key(i));
+        }
+        for (const key of keys) {
+            if (!key.includes('/') && key !== 'profiles' && key !== 'active-bank') {
+                localStorage.setItem('profile-0/' + key, localStorage.getItem(key));
+                localStorage.removeItem(key);
+            }
+        }
+        active = '0';
+        localStorage.setItem('active-profile', active);
+    }
+    return active;
+})();
+// must match DEFAULT_BANK
+window.__bank ??= localStorage.getItem('active-bank') ?? 'b';
+"#;
+
+/// Which values a key is shared by.
+#[derive(Clone, Copy)]
+enum Scope {
+    Bank,
+    Profile,
+    Global,
+}
+
+/// The active profile and bank, which namespace the keys of their values.
+#[derive(Clone, Debug, PartialEq)]
+struct Namespace {
+    profile: String,
+    bank: String,
+}
+
+impl Namespace {
+    fn key(&self, scope: Scope, key: &str) -> String {
+        match scope {
+            Scope::Global => key.to_string(),
+            Scope::Profile => format!("profile-{}/{}", self.profile, key),
+            // the default bank's keys predate banks
+            Scope::Bank if self.bank == DEFAULT_BANK => self.key(Scope::Profile, key),
+            Scope::Bank => self.key(Scope::Profile, &format!("bank-{}/{}", self.bank, key)),
+        }
+    }
+}
+
+thread_local! {
+    /// The namespace, once it was read, as it can't change until the page reloads.
+    static NAMESPACE: OnceCell<Namespace> = const { OnceCell::new() };
+}
+
+fn cached_namespace() -> Option<Namespace> {
+    NAMESPACE.with(|namespace| namespace.get().cloned())
+}
+
+/// The namespace of the page. Reading it the first time also moves the data stored before there
+/// were profiles to the first profile, so it's read before any value.
+async fn namespace() -> Option<Namespace> {
+    // nothing is stored for the pages rendered on the server, see `ssr`
+    if crate::ssr::ON_SERVER {
+        return None;
+    }
+    if let Some(namespace) = cached_namespace() {
+        return Some(namespace);
+    }
+    let (profile, bank) = dioxus::document::eval(&format!(
+        "{}\nreturn [window.__profile, window.__bank];",
+        PROFILE_PRELUDE
+    ))
+    .join::<(String, String)>()
+    .await
+    .ok()?;
+    let namespace = Namespace { profile, bank };
+    Some(NAMESPACE.with(|cached| cached.get_or_init(|| namespace).clone()))
+}
+
+/// The keys, in their namespace, whose values were stored by a newer version of the app.
+static READ_ONLY_KEYS: GlobalSignal<Vec<String>> = Signal::global(Vec::new);
+
+/// A value loaded from a [`Storage`].
+#[derive(Debug, PartialEq)]
+enum Loaded<T> {
+    /// There is no value, or it can't be read (e.g. it was stored by an incompatible version).
+    Missing,
+    Value(T),
+    /// The value was stored by a newer version of the app.
+    Newer,
+}
+
+/// Load the value of `key` from `storage`, whose versions are those of `name`. A value of an
+/// older version is upgraded and stored again, keeping the value it was upgraded from.
+async fn load_from<T: DeserializeOwned>(
+    storage: &impl Storage,
+    name: &str,
+    key: &str,
+) -> Loaded<T> {
+    let Some(json) = storage.get(key).await else {
+        return Loaded::Missing;
+    };
+    match migration::upgrade(name, &json) {
+        Ok(upgraded) => {
+            if upgraded.migrated(name) {
+                storage
+                    .set(&migration::backup_key(key, upgraded.from), &json)
+                    .await;
+                let wrapped = migration::wrap(name, upgraded.value.clone());
+                storage.set(key, &wrapped.to_string()).await;
+            }
+            serde_json::from_value(upgraded.value).map_or(Loaded::Missing, Loaded::Value)
+        }
+        Err(MigrationError::FutureVersion(_)) => Loaded::Newer,
+        Err(MigrationError::Failed { from, .. }) => {
+            // it is about to be replaced by the defaults
+            storage.set(&migration::backup_key(key, from), &json).await;
+            Loaded::Missing
+        }
+        Err(MigrationError::Corrupt) => Loaded::Missing,
+    }
+}
+
+/// Load the value of `key` in `scope`. Returns `None` if there is no such value, or if it can't
+/// be read. A value stored by a newer version of the app makes the key read-only.
+async fn load_in<T: DeserializeOwned>(scope: Scope, key: &str) -> Option<T> {
+    let namespaced = namespace().await?.key(scope, key);
+    match load_from(&BROWSER, key, &namespaced).await {
+        Loaded::Value(value) => Some(value),
+        Loaded::Missing => None,
+        Loaded::Newer => {
+            READ_ONLY_KEYS.write().push(namespaced);
+            None
+        }
+    }
+}
+
+fn save_in<T: Serialize>(scope: Scope, key: &str, value: &T) {
+    let Ok(value) = serde_json::to_value(value) else {
+        return;
+    };
+    let json = migration::wrap(key, value).to_string();
+    let read_only = |namespaced: &String| READ_ONLY_KEYS.peek().contains(namespaced);
+    match cached_namespace() {
+        // issued right away, so the saves of a key land in the order they were made
+        Some(namespace) => {
+            let namespaced = namespace.key(scope, key);
+            if !read_only(&namespaced) {
+                spawn(BROWSER.set(&namespaced, &json));
+            }
+        }
+        // only before anything was loaded
+        None => {
+            let key = key.to_string();
+            spawn(async move {
+                if let Some(namespace) = namespace().await {
+                    let namespaced = namespace.key(scope, &key);
+                    if !read_only(&namespaced) {
+                        BROWSER.set(&namespaced, &json).await;
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Load the value stored at `key` for the active profile and bank. Returns `None` if there is no
+/// such value, or if it can't be deserialized (e.g. it was stored by an incompatible version).
+pub async fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    load_in(Scope::Bank, key).await
+}
+
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    save_in(Scope::Bank, key, value);
+}
+
+/// Like [`load`], for a value shared by all of the banks of the profile.
+pub async fn load_profile<T: DeserializeOwned>(key: &str) -> Option<T> {
+    load_in(Scope::Profile, key).await
+}
+
+pub fn save_profile<T: Serialize>(key: &str, value: &T) {
+    save_in(Scope::Profile, key, value);
+}
+
+/// Like [`load`], for a value shared by all profiles.
+pub async fn load_global<T: DeserializeOwned>(key: &str) -> Option<T> {
+    load_in(Scope::Global, key).await
+}
+
+pub fn save_global<T: Serialize>(key: &str, value: &T) {
+    save_in(Scope::Global, key, value);
+}
+
+/// The id of the active profile.
+pub async fn active_profile() -> Option<u32> {
+    namespace().await?.profile.parse().ok()
+}
+
+/// The id of the active bank.
+pub async fn active_bank() -> String {
+    namespace()
+        .await
+        .map_or_else(|| DEFAULT_BANK.to_string(), |namespace| namespace.bank)
+}
+
+/// Make `bank` the active bank, reloading the page so everything is loaded for it.
+pub fn switch_bank(bank: &str) {
+    dioxus::document::eval(&format!(
+        r#"
+        window.__storageFrozen = true;
+        localStorage.setItem('active-bank', {:?});
+        location.reload();
+        "#,
+        bank
+    ));
+}
+
+/// Make `profile` the active profile, reloading the page so everything is loaded from it.
+pub fn switch_profile(profile: u32) {
+    dioxus::document::eval(&format!(
+        r#"
+        window.__storageFrozen = true;
+        localStorage.setItem('active-profile', '{}');
+        location.reload();
+        "#,
+        profile
+    ));
+}
+
+/// Remove every value stored for `profile`.
+pub async fn remove_profile_data(profile: u32) {
+    let active = active_profile().await == Some(profile);
+    let keys = BROWSER.list(&format!("profile-{}/", profile)).await;
+    let removals = keys
+        .iter()
+        .map(|key| BROWSER.delete(key))
+        .collect::<Vec<_>>();
+    // the removals were issued, so freezing doesn't stop them, only the saves after them
+    if active {
+        dioxus::document::eval("window.__storageFrozen = true;");
+    }
+    for removal in removals {
+        removal.await;
+    }
+}
+
+/// Warns about the values which were stored by a newer version of the app, and are left as they
+/// are.
+#[component]
+pub fn NewerDataWarning() -> Element {
+    let mut toasts = use_toast();
+    let mut warned = use_signal(|| 0);
+    use_effect(move || {
+        let keys = READ_ONLY_KEYS.read();
+        for key in &keys[*warned.peek()..] {
+            toasts.error(format!(
+                "הנתונים של {} נשמרו בגרסה חדשה יותר של האפליקציה. הם לא נטענו ושינויים בהם לא יישמרו עד לרענון לגרסה החדשה.",
+                key.rsplit('/').next().unwrap_or(key)
+            ));
+        }
+        warned.set(keys.len());
+    });
+    rsx! {}
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use futures_executor::block_on;
+    use theory_test_core::{history::History, notes::Notes};
+
+    use super::*;
+
+    /// What every storage must do.
+    async fn check_contract(storage: impl Storage) {
+        assert_eq!(storage.get("a/1").await, None);
+        storage.set("a/1", "one").await;
+        storage.set("a/2", "two").await;
+        storage.set("b/1", "other").await;
+        assert_eq!(storage.get("a/1").await.as_deref(), Some("one"));
+        assert_eq!(storage.list("a/").await, ["a/1", "a/2"]);
+        assert_eq!(storage.list("").await, ["a/1", "a/2", "b/1"]);
+        assert_eq!(storage.list("c/").await, Vec::<String>::new());
+
+        // writes take effect in order even when they aren't awaited
+        let first = storage.set("a/1", "first");
+        let second = storage.set("a/1", "second");
+        drop((second, first));
+        assert_eq!(storage.get("a/1").await.as_deref(), Some("second"));
+
+        storage.delete("a/1").await;
+        storage.delete("missing").await;
+        assert_eq!(storage.get("a/1").await, None);
+        assert_eq!(storage.list("a/").await, ["a/2"]);
+    }
+
+    #[test]
+    fn memory_contract() {
+        block_on(check_contract(MemoryStorage::default()));
+    }
+
+    #[test]
+    fn tiered_contract() {
+        block_on(check_contract(Tiered {
+            small: MemoryStorage::default(),
+            large: MemoryStorage::default(),
+            limit: 4,
+        }));
+    }
+
+    #[test]
+    fn tiered_by_size() {
+        let small = MemoryStorage::default();
+        let large = MemoryStorage::default();
+        let tiered = Tiered {
+            small: small.clone(),
+            large: large.clone(),
+            limit: 4,
+        };
+        block_on(async {
+            tiered.set("history", "[]").await;
+            assert_eq!(small.list("").await, ["history"]);
+            assert_eq!(large.list("").await, Vec::<String>::new());
+
+            // grew past the limit, so it moves
+            tiered.set("history", "[1, 2, 3]").await;
+            assert_eq!(small.list("").await, Vec::<String>::new());
+            assert_eq!(large.get("history").await.as_deref(), Some("[1, 2, 3]"));
+            assert_eq!(tiered.get("history").await.as_deref(), Some("[1, 2, 3]"));
+
+            tiered.set("history", "[1]").await;
+            assert_eq!(small.get("history").await.as_deref(), Some("[1]"));
+            assert_eq!(large.list("").await, Vec::<String>::new());
+        });
+    }
+
+    #[test]
+    fn migrated_on_load() {
+        let storage = MemoryStorage::default();
+        let v0 = r#"{"attempts":[{"graded_at":{"secs":5,"nanos":0},"questions":[]}]}"#;
+        block_on(async {
+            storage.set("profile-0/history", v0).await;
+            let Loaded::Value(history) =
+                load_from::<History>(&storage, "history", "profile-0/history").await
+            else {
+                panic!("the history should load");
+            };
+            assert_eq!(history.attempts[0].started_at, Duration::from_secs(5));
+            // the value it was upgraded from is kept, and the upgraded one replaces it
+            assert_eq!(
+                storage.get("profile-0/history.v0-backup").await.as_deref(),
+                Some(v0)
+            );
+            let stored = storage.get("profile-0/history").await.unwrap();
+            assert!(stored.starts_with(r#"{"data":{"attempts""#));
+            assert_eq!(
+                load_from::<History>(&storage, "history", "profile-0/history").await,
+                Loaded::Value(history)
+            );
+
+            storage.set("notes", r#"{"schema":9,"data":{}}"#).await;
+            assert_eq!(
+                load_from::<Notes>(&storage, "notes", "notes").await,
+                Loaded::Newer
+            );
+            assert_eq!(
+                load_from::<Notes>(&storage, "notes", "missing").await,
+                Loaded::Missing
+            );
+        });
+    }
+
+    #[test]
+    fn namespaced_keys() {
+        let default_bank = Namespace {
+            profile: "0".to_string(),
+            bank: DEFAULT_BANK.to_string(),
+        };
+        assert_eq!(
+            default_bank.key(Scope::Bank, "history"),
+            "profile-0/history"
+        );
+        assert_eq!(default_bank.key(Scope::Profile, "sync"), "profile-0/sync");
+        assert_eq!(default_bank.key(Scope::Global, "profiles"), "profiles");
+        let other_bank = Namespace {
+            profile: "2".to_string(),
+            bank: "motorcycle".to_string(),
+        };
+        assert_eq!(
+            other_bank.key(Scope::Bank, "history"),
+            "profile-2/bank-motorcycle/history"
+        );
+        assert_eq!(other_bank.key(Scope::Profile, "sync"), "profile-2/sync");
+    }
+}
+
+/// Run with `wasm-pack test --headless --firefox theory_test_ui`, IndexedDB needs a browser.
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_test {
+    use serde::de::DeserializeOwned;
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::IndexedDb;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    /// Run one of the scripts of [`IndexedDb`], as `document::eval` would.
+    async fn run<T: DeserializeOwned>(script: &str) -> T {
+        let promise = js_sys::eval(&format!(
+            "(async () => JSON.stringify((await (async () => {{ {} }})()) ?? null))()",
+            script
+        ))
+        .unwrap();
+        let json = JsFuture::from(js_sys::Promise::from(promise))
+            .await
+            .unwrap()
+            .as_string()
+            .unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    async fn indexed_db() {
+        run::<()>(&IndexedDb::set_script("smoke/a", "1")).await;
+        run::<()>(&IndexedDb::set_script("smoke/b", "2")).await;
+        run::<()>(&IndexedDb::set_script("other", "3")).await;
+        assert_eq!(
+            run::<Option<String>>(&IndexedDb::get_script("smoke/a")).await,
+            Some("1".to_string())
+        );
+        assert_eq!(
+            run::<Vec<String>>(&IndexedDb::list_script("smoke/")).await,
+            ["smoke/a", "smoke/b"]
+        );
+        run::<()>(&IndexedDb::delete_script("smoke/a")).await;
+        assert_eq!(
+            run::<Option<String>>(&IndexedDb::get_script("smoke/a")).await,
+            None
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Study of the bank in its official order, one question after the other, remembering where the
+//! study stopped.
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use theory_test_parser::question_parser::{ExamQuestions, LicenseClass, Question};
+
+use crate::{
+    bank::use_bank, preferences::use_preferences, storage, study_plan::StudyPlanDialog,
+    ExamQuestion, Route,
+};
+
+const STORAGE_KEY: &str = "study-positions";
+
+/// The official number of the last question studied, for each license class.
+#[derive(Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StudyPositions(HashMap<String, usize>);
+
+impl StudyPositions {
+    pub fn get(&self, class: LicenseClass) -> Option<usize> {
+        self.0.get(class.code()).copied()
+    }
+
+    pub fn set(&mut self, class: LicenseClass, num: usize) {
+        self.0.insert(class.code().to_string(), num);
+    }
+}
+
+/// The questions of a license class, in their official order.
+pub fn study_questions(bank: &ExamQuestions, class: LicenseClass) -> Vec<Question> {
+    let mut questions = bank
+        .questions
+        .iter()
+        .filter(|q| q.license_classes.contains(&class))
+        .cloned()
+        .collect::<Vec<_>>();
+    questions.sort_by_key(|q| q.num);
+    questions
+}
+
+/// The index of the question numbered `num`, or of the question following it if there is no
+/// such question, e.g. after the bank was updated. Numbers past the end map to the last question.
+pub fn position_index(questions: &[Question], num: usize) -> usize {
+    questions
+        .iter()
+        .position(|q| q.num >= num)
+        .unwrap_or(questions.len().saturating_sub(1))
+}
+
+/// How far into `questions` the question numbered `num` is, in percent.
+pub fn progress_percent(questions: &[Question], num: usize) -> usize {
+    if questions.is_empty() {
+        return 0;
+    }
+    (position_index(questions, num) + 1) * 100 / questions.len()
+}
+
+/// Provide the study positions to the whole app, loading the stored ones and saving every
+/// change.
+pub fn provide_study_positions() {
+    let mut positions = use_context_provider(|| Signal::new(StudyPositions::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<StudyPositions>(STORAGE_KEY).await {
+            // keep positions which were set before the stored ones loaded
+            let set = std::mem::replace(&mut *positions.write(), stored);
+            positions.write().0.extend(set.0);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let positions = positions.read();
+        if loaded() {
+            storage::save(STORAGE_KEY, &*positions);
+        }
+    });
+}
+
+pub fn use_study_positions() -> Signal<StudyPositions> {
+    use_context()
+}
+
+/// Links to start studying the bank in order, or to continue from where the study stopped.
+#[component]
+pub fn StudyLinks() -> Element {
+    let bank = use_bank();
+    let positions = use_study_positions();
+    let class = bank.class;
+    let questions = use_hook(|| study_questions(&bank.questions, class));
+    let saved = positions.read().get(class);
+
+    rsx! {
+        div { class: "study-links",
+            Link {
+                class: "main-page-link",
+                to: Route::Study {
+                    class: class.code().to_string(),
+                    from: questions.first().map(|q| q.num).unwrap_or_default(),
+                },
+                "לימוד לפי סדר"
+            }
+            if let Some(num) = saved {
+                Link {
+                    class: "main-page-link",
+                    to: Route::Study {
+                        class: class.code().to_string(),
+                        from: num,
+                    },
+                    {format!("המשך משאלה {:04} ({}%)", num, progress_percent(&questions, num))}
+                }
+            }
+            StudyPlanDialog {}
+        }
+    }
+}
+
+#[component]
+pub fn Study(class: String, from: usize) -> Element {
+    let bank = use_bank();
+    let mut positions = use_study_positions();
+    let preferences = use_preferences();
+    let nav = navigator();
+    let class = LicenseClass::from_code(&class).unwrap_or(bank.class);
+    let questions = use_hook(|| study_questions(&bank.questions, class));
+    let mut current = use_signal(|| position_index(&questions, from));
+    let mut selection = use_signal(|| None::<usize>);
+    let mut jump_to = use_signal(String::new);
+    // the route follows the current question, so a reload or a shared link keeps the position
+    let num = questions.get(current()).map(|q| q.num);
+    use_effect(use_reactive!(|(num)| {
+        if let Some(num) = num {
+            positions.write().set(class, num);
+            nav.replace(Route::Study {
+                class: class.code().to_string(),
+                from: num,
+            });
+        }
+    }));
+    let mut go_to = move |idx: usize| {
+        current.set(idx);
+        selection.set(None);
+    };
+    let Some(question) = questions.get(current()).cloned() else {
+        return rsx! {
+            div {
+                "אין שאלות לדרגה "
+                bdi { {class.code()} }
+                "."
+            }
+        };
+    };
+    let len = questions.len();
+    let jump_questions = questions.clone();
+
+    rsx! {
+        div { class: "study",
+            h1 {
+                "לימוד לפי סדר, דרגה "
+                bdi { {class.code()} }
+            }
+            div { class: "study-progress",
+                {format!("שאלה {} מתוך {}", current() + 1, len)}
+                progress { value: (current() + 1) as f64, max: len as f64 }
+            }
+            div { class: "study-jump",
+                "מעבר לשאלה: "
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: jump_to,
+                    oninput: move |e| jump_to.set(e.value()),
+                }
+                button {
+                    class: "button-primary",
+                    onclick: move |_| {
+                        if let Ok(num) = jump_to().trim().parse() {
+                            go_to(position_index(&jump_questions, num));
+                        }
+                    },
+                    "מעבר"
+                }
+            }
+            ExamQuestion {
+                key: "{question.num}",
+                question: question.clone(),
+                language: bank.language,
+                show_correct_answer: selection().is_some(),
+                user_selection: selection(),
+                on_select: move |answer| {
+                    if selection().is_none() {
+                        selection.set(Some(answer));
+                    }
+                },
+                question_num: current() + 1,
+                show_question_num: true,
+                use_canonical_question_num: true,
+                auto_read: preferences.read().read_aloud,
+            }
+            div { class: "study-navigation",
+                button {
+                    class: "button-primary",
+                    disabled: current() == 0,
+                    onclick: move |_| go_to(current() - 1),
+                    "הקודמת"
+                }
+                button {
+                    class: "button-primary",
+                    disabled: current() + 1 >= len,
+                    onclick: move |_| go_to(current() + 1),
+                    "הבאה"
+                }
+            }
+            Link { to: Route::MainPage, "חזרה לדף הראשי" }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn question(num: usize, license_classes: Vec<LicenseClass>) -> Question {
+        Question {
+            license_classes,
+            ..Question::sample(num, 0)
+        }
+    }
+
+    #[test]
+    fn positions_in_the_order() {
+        let bank = ExamQuestions {
+            questions: vec![
+                question(30, vec![LicenseClass::B]),
+                question(10, vec![LicenseClass::B, LicenseClass::C]),
+                question(20, vec![LicenseClass::C]),
+                question(40, vec![LicenseClass::B]),
+            ],
+        };
+        let questions = study_questions(&bank, LicenseClass::B);
+        assert_eq!(
+            questions.iter().map(|q| q.num).collect::<Vec<_>>(),
+            vec![10, 30, 40]
+        );
+        assert_eq!(position_index(&questions, 30), 1);
+        // a question removed by a bank update continues from the following one
+        assert_eq!(position_index(&questions, 20), 1);
+        // past the end of the bank is clamped to its last question
+        assert_eq!(position_index(&questions, 500), 2);
+        assert_eq!(progress_percent(&questions, 10), 33);
+        assert_eq!(progress_percent(&questions, 40), 100);
+        assert_eq!(progress_percent(&[], 40), 0);
+    }
+}
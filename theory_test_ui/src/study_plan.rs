@@ -0,0 +1,97 @@
+//! A plan of the study until the exam, downloaded as a calendar file.
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+use theory_test_core::{scheduler::Scheduler, time};
+use theory_test_parser::question_parser::ExamQuestions;
+
+use crate::{
+    backup::download, bank::use_bank, history::use_history, study::study_questions, timer,
+};
+
+/// The questions a day of the plan starts with, about an exam's worth.
+const DEFAULT_CAPACITY: usize = 40;
+
+/// A button opening a dialog which plans the days until a chosen exam date, from the questions
+/// due for review and those never seen, and downloads the plan for a calendar app.
+#[component]
+pub fn StudyPlanDialog() -> Element {
+    let bank = use_bank();
+    let history = use_history();
+    let mut open = use_signal(|| false);
+    let mut exam_date = use_signal(String::new);
+    let mut capacity = use_signal(|| DEFAULT_CAPACITY);
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let pool = use_hook(|| {
+        Rc::new(ExamQuestions {
+            questions: study_questions(&bank.questions, bank.class),
+        })
+    });
+    let plan = use_memo(move || {
+        let exam_day = time::parse_date(&exam_date())?;
+        let scheduler = Scheduler::new(&history.read(), timer::now(), utc_offset().unwrap_or(0));
+        Some(scheduler.study_plan(&pool, exam_day, capacity()))
+    });
+    let tomorrow = time::format_date(timer::local_day(timer::now(), utc_offset().unwrap_or(0)) + 1);
+
+    rsx! {
+        button { class: "button-primary", onclick: move |_| open.set(true), "תוכנית לימוד" }
+        if open() {
+            div { class: "study-plan-overlay",
+                div { class: "study-plan-dialog",
+                    h2 { "תוכנית לימוד" }
+                    label {
+                        "תאריך המבחן: "
+                        input {
+                            r#type: "date",
+                            min: "{tomorrow}",
+                            value: "{exam_date}",
+                            oninput: move |e| exam_date.set(e.value()),
+                        }
+                    }
+                    label {
+                        "מספר שאלות ביום: "
+                        input {
+                            r#type: "number",
+                            min: "1",
+                            max: "200",
+                            value: "{capacity}",
+                            oninput: move |e| {
+                                if let Ok(value) = e.value().parse::<usize>() {
+                                    capacity.set(value.clamp(1, 200));
+                                }
+                            },
+                        }
+                    }
+                    if let Some(study_plan) = plan() {
+                        div {
+                            {
+                                format!(
+                                    "{} שאלות ב־{} ימים עד המבחן",
+                                    study_plan.num_questions(),
+                                    study_plan.days.iter().filter(|day| day.num_questions() > 0).count(),
+                                )
+                            }
+                        }
+                        if let Some(shortfall) = study_plan.shortfall_he() {
+                            div { class: "study-plan-shortfall", {shortfall} }
+                        }
+                        button {
+                            class: "button-primary",
+                            disabled: study_plan.num_questions() == 0,
+                            onclick: move |_| {
+                                if let Some(plan) = &*plan.read() {
+                                    download("theory-test-plan.ics", "text/calendar", &plan.to_ics());
+                                }
+                            },
+                            "הורדה ליומן (.ics)"
+                        }
+                    } else {
+                        div { "בחרו את תאריך המבחן" }
+                    }
+                    button { class: "button-primary", onclick: move |_| open.set(false), "סגירה" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! Syncing the local data with a self-hosted sync server, for using the app on several devices.
+//!
+//! A sync sends the backup of the local data to the server, which merges it with what the other
+//! devices sent, and merges the result back. Records both sides changed are resolved by the
+//! server, the latest write winning, and reported as conflicts.
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    achievements::use_achievements,
+    backup::{Backup, ImportError},
+    bank::use_bank,
+    history::use_history,
+    notes::use_notes,
+    personal_best::use_personal_bests,
+    preferences::use_preferences,
+    storage,
+    tags::use_tags,
+    toast::use_toast,
+};
+
+const STORAGE_KEY: &str = "sync";
+
+/// Where and as whom to sync, per profile.
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct SyncSettings {
+    pub server_url: String,
+    pub token: String,
+    /// The name the server lists the device by.
+    pub device_name: String,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SyncError {
+    #[error("לא ניתן להתחבר לשרת הסנכרון")]
+    Network,
+    #[error("הטוקן אינו מוכר לשרת הסנכרון")]
+    Unauthorized,
+    #[error("השרת מסנכרן מאגר שאלות אחר")]
+    OtherBank,
+    #[error("שגיאת שרת ({0})")]
+    Server(u16),
+    #[error("{0}")]
+    Backup(#[from] ImportError),
+}
+
+/// The body of a successful sync.
+#[derive(Deserialize)]
+struct SyncResponse {
+    backup: serde_json::Value,
+    conflicts: Vec<String>,
+}
+
+/// The merged backup and the conflicts of the response of the server, `status` being 0 when
+/// the request didn't reach it.
+fn parse_sync_response(
+    status: u16,
+    body: &str,
+    bank_hash: u64,
+) -> Result<(Backup, Vec<String>), SyncError> {
+    match status {
+        0 => return Err(SyncError::Network),
+        401 => return Err(SyncError::Unauthorized),
+        409 => return Err(SyncError::OtherBank),
+        200 => {}
+        status => return Err(SyncError::Server(status)),
+    }
+    let response: SyncResponse = serde_json::from_str(body).map_err(|_| ImportError::Corrupt)?;
+    let backup = Backup::parse(&response.backup.to_string(), bank_hash)?;
+    Ok((backup, response.conflicts))
+}
+
+/// Send `backup` to the server, returning the status of the response and its body.
+async fn put_progress(settings: &SyncSettings, backup: &str) -> (u16, String) {
+    document::eval(&format!(
+        r#"
+        try {{
+            const response = await fetch({:?}.replace(/\/+$/, '') + '/progress', {{
+                method: 'PUT',
+                headers: {{
+                    'Authorization': 'Bearer ' + {:?},
+                    'Content-Type': 'application/json',
+                    'X-Device': {:?},
+                }},
+                body: {:?},
+            }});
+            return [response.status, await response.text()];
+        }} catch (e) {{
+            return [0, ''];
+        }}
+        "#,
+        settings.server_url, settings.token, settings.device_name, backup
+    ))
+    .join::<(u16, String)>()
+    .await
+    .unwrap_or((0, String::new()))
+}
+
+/// The settings of the sync server, and a button syncing now.
+#[component]
+pub fn SyncSettingsSection() -> Element {
+    let bank = use_bank();
+    let bank_hash = bank.content_hash;
+    let bank_id = bank.id;
+    let mut history = use_history();
+    let mut preferences = use_preferences();
+    let mut achievements = use_achievements();
+    let mut personal_bests = use_personal_bests();
+    let mut notes = use_notes();
+    let mut tags = use_tags();
+    let mut toasts = use_toast();
+    let mut settings = use_signal(SyncSettings::default);
+    let mut syncing = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load_profile::<SyncSettings>(STORAGE_KEY).await {
+            settings.set(stored);
+        }
+    });
+    let mut update = move |change: &dyn Fn(&mut SyncSettings)| {
+        change(&mut settings.write());
+        storage::save_profile(STORAGE_KEY, &*settings.read());
+    };
+    let configured = {
+        let settings = settings.read();
+        !settings.server_url.trim().is_empty() && !settings.token.trim().is_empty()
+    };
+
+    rsx! {
+        div { class: "setting sync",
+            label {
+                "כתובת השרת "
+                input {
+                    r#type: "url",
+                    value: settings.read().server_url.clone(),
+                    placeholder: "https://sync.example.com",
+                    oninput: move |e| update(&|s| s.server_url = e.value()),
+                }
+            }
+            label {
+                "טוקן "
+                input {
+                    r#type: "password",
+                    value: settings.read().token.clone(),
+                    oninput: move |e| update(&|s| s.token = e.value()),
+                }
+            }
+            label {
+                "שם המכשיר "
+                input {
+                    r#type: "text",
+                    value: settings.read().device_name.clone(),
+                    oninput: move |e| update(&|s| s.device_name = e.value()),
+                }
+            }
+            button {
+                class: "button-primary",
+                disabled: !configured || syncing(),
+                onclick: move |_| async move {
+                    syncing.set(true);
+                    let backup = Backup::new(
+                        bank_hash,
+                        bank_id,
+                        history.read().clone(),
+                        preferences.read().clone(),
+                        achievements.read().clone(),
+                        personal_bests.read().clone(),
+                        notes.read().clone(),
+                    )
+                    .with_tags(tags.read().clone());
+                    let json = serde_json::to_string(&backup).unwrap_or_default();
+                    let (status, body) = put_progress(&settings(), &json).await;
+                    match parse_sync_response(status, &body, bank_hash) {
+                        Ok((backup, conflicts)) => {
+                            let report = backup
+                                .merge_into(
+                                    &mut history.write(),
+                                    &mut preferences.write(),
+                                    &mut achievements.write(),
+                                    &mut personal_bests.write(),
+                                    &mut notes.write(),
+                                    &mut tags.write(),
+                                );
+                            toasts.success(format!("סונכרן. {}", report.description_he()));
+                            if !conflicts.is_empty() {
+                                toasts
+                                    .info(
+                                        format!(
+                                            "{} רשומות שונו גם במכשיר אחר, נשמרה הגרסה האחרונה",
+                                            conflicts.len(),
+                                        ),
+                                    );
+                            }
+                        }
+                        Err(e) => toasts.error(e.to_string()),
+                    }
+                    syncing.set(false);
+                },
+                if syncing() {
+                    "מסנכרן…"
+                } else {
+                    "סנכרן עכשיו"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn responses() {
+        assert_eq!(
+            parse_sync_response(0, "", 7).err(),
+            Some(SyncError::Network)
+        );
+        assert_eq!(
+            parse_sync_response(401, r#"{"error":"a valid token is required"}"#, 7).err(),
+            Some(SyncError::Unauthorized)
+        );
+        assert_eq!(
+            parse_sync_response(409, "", 7).err(),
+            Some(SyncError::OtherBank)
+        );
+        assert_eq!(
+            parse_sync_response(500, "", 7).err(),
+            Some(SyncError::Server(500))
+        );
+        assert_eq!(
+            parse_sync_response(200, "<html>", 7).err(),
+            Some(SyncError::Backup(ImportError::Corrupt))
+        );
+        let backup = serde_json::json!({
+            "version": 1,
+            "bank_hash": 7,
+            "history": { "attempts": [] },
+            "preferences": {},
+            "achievements": {},
+            "personal_bests": {},
+        });
+        let body = serde_json::json!({ "backup": backup, "conflicts": ["notes/3"] }).to_string();
+        assert_eq!(
+            parse_sync_response(200, &body, 8).err(),
+            Some(SyncError::Backup(ImportError::OtherBank))
+        );
+        let (_, conflicts) = parse_sync_response(200, &body, 7).unwrap();
+        assert_eq!(conflicts, ["notes/3"]);
+    }
+}
@@ -0,0 +1,103 @@
+//! The user's own tags on questions, persisted across sessions.
+use dioxus::prelude::*;
+pub use theory_test_core::tags::{Tags, MAX_TAG_CHARS};
+
+use crate::storage;
+
+const STORAGE_KEY: &str = "tags";
+/// The existing tags offered while typing a new one.
+const MAX_SUGGESTIONS: usize = 6;
+
+/// Provide the tags to the whole app, loading the stored ones and saving every change.
+pub fn provide_tags() {
+    let mut tags = use_context_provider(|| Signal::new(Tags::default()));
+    let mut loaded = use_signal(|| false);
+    use_future(move || async move {
+        if let Some(stored) = storage::load::<Tags>(STORAGE_KEY).await {
+            // tags added before the stored ones loaded are kept
+            let added = std::mem::replace(&mut *tags.write(), stored);
+            tags.write().merge(added);
+        }
+        loaded.set(true);
+    });
+    use_effect(move || {
+        let tags = tags.read();
+        if loaded() {
+            storage::save(STORAGE_KEY, &*tags);
+        }
+    });
+}
+
+pub fn use_tags() -> Signal<Tags> {
+    use_context()
+}
+
+/// The tags of a question, with adding and removing them. Typing offers the existing tags.
+#[component]
+pub fn QuestionTags(num: usize) -> Element {
+    let mut tags = use_tags();
+    let mut draft = use_signal(String::new);
+    let question_tags = tags
+        .read()
+        .get(num)
+        .into_iter()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let suggestions = if draft.read().trim().is_empty() {
+        Vec::new()
+    } else {
+        tags.read()
+            .suggestions(&draft.read(), num, MAX_SUGGESTIONS)
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let mut add = move |tag: &str| {
+        tags.write().add(num, tag);
+        draft.set(String::new());
+    };
+
+    rsx! {
+        div { class: "question-tags",
+            for tag in question_tags {
+                span { key: "{tag}", class: "question-tag",
+                    "🏷 {tag}"
+                    button {
+                        class: "note-button",
+                        title: "הסרת התגית",
+                        onclick: {
+                            let tag = tag.clone();
+                            move |_| {
+                                tags.write().remove(num, &tag);
+                            }
+                        },
+                        "✕"
+                    }
+                }
+            }
+            input {
+                r#type: "text",
+                placeholder: "הוסף תגית",
+                maxlength: MAX_TAG_CHARS as i64,
+                value: draft,
+                oninput: move |e| draft.set(e.value()),
+                onkeydown: move |e| {
+                    if e.key() == Key::Enter {
+                        add(&draft.peek().clone());
+                    }
+                },
+            }
+            for suggestion in suggestions {
+                button {
+                    key: "{suggestion}",
+                    class: "filter-chip",
+                    onclick: {
+                        let suggestion = suggestion.clone();
+                        move |_| add(&suggestion)
+                    },
+                    "{suggestion}"
+                }
+            }
+        }
+    }
+}
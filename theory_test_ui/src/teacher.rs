@@ -0,0 +1,218 @@
+//! The results of single exams as files for a teacher, and the teacher's dashboard of the
+//! results of the class. Everything is computed in the browser, the files never leave it.
+use std::{sync::Arc, time::Duration};
+
+use dioxus::{
+    html::{FileEngine, HasFileData},
+    prelude::*,
+};
+use theory_test_core::{
+    class_results::{ClassResults, ResultFile},
+    time,
+};
+
+use crate::{
+    backup::download,
+    bank::use_bank,
+    history::use_history,
+    profile::{use_profiles, DEFAULT_PROFILE_NAME},
+    timer,
+    toast::use_toast,
+    Route,
+};
+
+/// The questions missed most often which the dashboard lists.
+const MOST_MISSED_LIMIT: usize = 15;
+
+fn percent(share: f64) -> String {
+    format!("{:.0}%", share * 100.0)
+}
+
+/// A button to download the result of the exam which started at `started_at`, once it is
+/// recorded, for a teacher to import into [`TeacherDashboard`].
+#[component]
+pub fn DownloadResult(started_at: Duration) -> Element {
+    let bank = use_bank();
+    let bank_hash = bank.content_hash;
+    let history = use_history();
+    let profiles = use_profiles();
+
+    rsx! {
+        button {
+            class: "button-primary",
+            onclick: move |_| async move {
+                let Some(attempt) = history
+                    .peek()
+                    .attempts
+                    .iter()
+                    .find(|a| a.started_at == started_at)
+                    .cloned() else {
+                    return;
+                };
+                let profile_name = (profiles.active)()
+                    .and_then(|id| profiles.profiles.peek().get(id).map(|p| p.name.clone()))
+                    .filter(|name| name != DEFAULT_PROFILE_NAME)
+                    .unwrap_or_default();
+                let name = document::eval(
+                        &format!("return prompt({:?}, {:?});", "השם שיוצג למורה:", profile_name),
+                    )
+                    .join::<Option<String>>()
+                    .await
+                    .ok()
+                    .flatten();
+                let Some(name) = name.filter(|name| !name.trim().is_empty()) else {
+                    return;
+                };
+                let result = ResultFile::new(bank_hash, &name, attempt);
+                if let Ok(json) = serde_json::to_string(&result) {
+                    let file_name = format!(
+                        "theory-test-result-{}-{}.json",
+                        result.student.replace(char::is_whitespace, "-"),
+                        result.attempt.started_at.as_secs(),
+                    );
+                    download(&file_name, "application/json", &json);
+                }
+            },
+            "הורדת התוצאה למורה"
+        }
+    }
+}
+
+/// Import every file of `files` into `class`, returning the number added, the number already
+/// there and the errors of the rest by file name.
+async fn import_files(
+    files: Arc<dyn FileEngine>,
+    class: &mut Signal<ClassResults>,
+    bank_hash: u64,
+) -> (usize, usize, Vec<String>) {
+    let (mut added, mut duplicates, mut errors) = (0, 0, Vec::new());
+    for name in files.files() {
+        let json = files.read_file_to_string(&name).await.unwrap_or_default();
+        match class.write().import(&json, bank_hash) {
+            Ok(true) => added += 1,
+            Ok(false) => duplicates += 1,
+            Err(e) => errors.push(format!("{name}: {e}")),
+        }
+    }
+    (added, duplicates, errors)
+}
+
+/// The results of a class, imported from the files the students downloaded.
+#[component]
+pub fn TeacherDashboard() -> Element {
+    let bank = use_bank();
+    let bank_hash = bank.content_hash;
+    let mut toasts = use_toast();
+    let mut class = use_signal(ClassResults::default);
+    let mut dragging = use_signal(|| false);
+
+    let import = move |files: Arc<dyn FileEngine>| async move {
+        let (added, duplicates, errors) = import_files(files, &mut class, bank_hash).await;
+        let mut summary = format!("יובאו {added} תוצאות");
+        if duplicates > 0 {
+            summary += &format!(", {duplicates} כבר יובאו קודם");
+        }
+        toasts.success(summary);
+        for error in errors {
+            toasts.error(error);
+        }
+    };
+
+    let students = class.read().students();
+    let most_missed = class.read().most_missed(MOST_MISSED_LIMIT);
+    let averages = class.read().category_averages(&bank.questions);
+
+    rsx! {
+        div { class: "teacher-dashboard",
+            h1 { "לוח מורה" }
+            div { "ייבוא קובצי התוצאות שהתלמידים הורידו בסוף המבחנים. הקבצים נשארים בדפדפן בלבד." }
+            div {
+                class: if dragging() { "teacher-drop dragging" } else { "teacher-drop" },
+                ondragover: move |e| {
+                    e.prevent_default();
+                    dragging.set(true);
+                },
+                ondragleave: move |_| dragging.set(false),
+                ondrop: move |e| async move {
+                    e.prevent_default();
+                    dragging.set(false);
+                    if let Some(files) = e.files() {
+                        import(files).await;
+                    }
+                },
+                "אפשר לגרור לכאן קבצים, או "
+                label { class: "button-primary",
+                    "לבחור קבצים"
+                    input {
+                        r#type: "file",
+                        accept: ".json,application/json",
+                        multiple: true,
+                        hidden: true,
+                        onchange: move |e| async move {
+                            if let Some(files) = e.files() {
+                                import(files).await;
+                            }
+                        },
+                    }
+                }
+            }
+            if class.read().is_empty() {
+                div { "עדיין לא יובאו תוצאות." }
+            } else {
+                h2 { "תלמידים" }
+                table {
+                    tr {
+                        th { "תלמיד" }
+                        th { "מבחנים" }
+                        th { "עברו" }
+                        th { "תשובות נכונות" }
+                    }
+                    for student in students {
+                        tr { key: "{student.student}",
+                            td { {student.student.clone()} }
+                            td { "{student.exams}" }
+                            td { {format!("{} ({})", student.passed, percent(student.pass_rate()))} }
+                            td { {format!("{}/{}", student.correct, student.answered)} }
+                        }
+                    }
+                }
+                h2 { "השאלות שנענו בטעות הכי הרבה" }
+                table {
+                    for question in most_missed {
+                        tr { key: "{question.num}",
+                            td {
+                                Link { to: Route::QuestionPage { num: question.num }, {format!("{:04}", question.num)} }
+                            }
+                            td { {format!("{} מתוך {}", question.missed, question.asked)} }
+                        }
+                    }
+                }
+                h2 { "ממוצע הכיתה לפי נושא" }
+                table {
+                    for average in averages {
+                        tr {
+                            td { {average.category.as_str_he()} }
+                            td { {percent(average.average)} }
+                        }
+                    }
+                }
+                div {
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| {
+                            let csv = class.read().to_csv(&bank.questions);
+                            let day = time::format_date(timer::local_day(timer::now(), 0));
+                            download(&format!("theory-test-class-{day}.csv"), "text/csv", &csv);
+                        },
+                        "ייצוא לקובץ CSV"
+                    }
+                    button {
+                        class: "button-primary",
+                        onclick: move |_| class.set(ClassResults::default()),
+                        "ניקוי"
+                    }
+                }
+            }
+        }
+    }
+}
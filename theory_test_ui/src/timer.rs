@@ -0,0 +1,41 @@
+//! Time helpers which work both in the browser and natively.
+use std::time::Duration;
+
+use theory_test_core::time::CivilDateTime;
+pub use theory_test_core::time::{format_duration, local_day};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::i18n::tr;
+
+/// The current time as a duration since the unix epoch.
+pub fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+pub async fn sleep(duration: Duration) {
+    futures_timer::Delay::new(duration).await
+}
+
+/// Format a time, as a duration since the unix epoch, in the time zone of `utc_offset_secs` and
+/// the format of the current language.
+pub fn format_date_time(time: Duration, utc_offset_secs: i64) -> String {
+    let civil = CivilDateTime::new(time, utc_offset_secs);
+    tr!(
+        "date-time",
+        day = civil.day,
+        month = civil.month,
+        year = civil.year,
+        hour = format!("{:02}", civil.hour),
+        minute = format!("{:02}", civil.minute),
+    )
+}
+
+/// The offset of the local time zone from UTC in seconds, e.g. 7200 for UTC+2.
+pub async fn utc_offset_secs() -> i64 {
+    dioxus::document::eval("return -new Date().getTimezoneOffset() * 60;")
+        .join::<i64>()
+        .await
+        .unwrap_or(0)
+}
@@ -0,0 +1,193 @@
+//! Short notifications which show over the page and go away by themselves.
+use std::{rc::Rc, time::Duration};
+
+use dioxus::prelude::*;
+
+use crate::timer;
+
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+/// Errors stay longer, there is usually something to read in them.
+const ERROR_TOAST_DURATION: Duration = Duration::from_secs(8);
+/// At most this many toasts are shown at once, the oldest are dismissed to make room.
+const MAX_VISIBLE: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Error,
+}
+
+impl ToastKind {
+    fn class(&self) -> &'static str {
+        match self {
+            ToastKind::Info => "toast-info",
+            ToastKind::Success => "toast-success",
+            ToastKind::Error => "toast-error",
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        match self {
+            ToastKind::Info | ToastKind::Success => TOAST_DURATION,
+            ToastKind::Error => ERROR_TOAST_DURATION,
+        }
+    }
+}
+
+/// A button on a toast. Clicking it also dismisses the toast.
+#[derive(Clone)]
+pub struct ToastAction {
+    pub label: String,
+    pub run: Rc<dyn Fn()>,
+}
+
+impl ToastAction {
+    pub fn new(label: impl Into<String>, run: impl Fn() + 'static) -> Self {
+        Self {
+            label: label.into(),
+            run: Rc::new(run),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Toast {
+    id: usize,
+    kind: ToastKind,
+    message: String,
+    action: Option<ToastAction>,
+}
+
+/// The toasts which are shown, oldest first.
+#[derive(Default)]
+struct ToastQueue {
+    toasts: Vec<Toast>,
+    next_id: usize,
+}
+
+impl ToastQueue {
+    /// Add a toast, returning its id.
+    fn push(&mut self, kind: ToastKind, message: String, action: Option<ToastAction>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.toasts.push(Toast {
+            id,
+            kind,
+            message,
+            action,
+        });
+        let overflow = self.toasts.len().saturating_sub(MAX_VISIBLE);
+        self.toasts.drain(..overflow);
+        id
+    }
+
+    /// Remove a toast, if it is still shown.
+    fn dismiss(&mut self, id: usize) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Toasts {
+    queue: Signal<ToastQueue>,
+}
+
+impl Toasts {
+    /// Provide the toasts to the whole app, whose root component should also render [`ToastList`].
+    pub fn provide() -> Self {
+        use_context_provider(|| Self {
+            queue: Signal::new(ToastQueue::default()),
+        })
+    }
+
+    pub fn show(&mut self, kind: ToastKind, message: String, action: Option<ToastAction>) {
+        let id = self.queue.write().push(kind, message, action);
+        let mut queue = self.queue;
+        spawn_forever(async move {
+            timer::sleep(kind.duration()).await;
+            queue.write().dismiss(id);
+        });
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.show(ToastKind::Info, message.into(), None);
+    }
+
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.show(ToastKind::Success, message.into(), None);
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.show(ToastKind::Error, message.into(), None);
+    }
+}
+
+pub fn use_toast() -> Toasts {
+    use_context()
+}
+
+#[component]
+pub fn ToastList() -> Element {
+    let mut queue = use_toast().queue;
+    let toasts = queue.read().toasts.clone();
+    rsx! {
+        div { class: "toasts",
+            for toast in toasts {
+                div {
+                    key: "{toast.id}",
+                    class: "toast {toast.kind.class()}",
+                    role: if toast.kind == ToastKind::Error { "alert" } else { "status" },
+                    span { "{toast.message}" }
+                    if let Some(action) = toast.action {
+                        button {
+                            class: "toast-action",
+                            onclick: move |_| {
+                                (action.run)();
+                                queue.write().dismiss(toast.id);
+                            },
+                            "{action.label}"
+                        }
+                    }
+                    button {
+                        class: "toast-close",
+                        title: "סגור",
+                        onclick: move |_| queue.write().dismiss(toast.id),
+                        "✕"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn messages(queue: &ToastQueue) -> Vec<&str> {
+        queue.toasts.iter().map(|t| t.message.as_str()).collect()
+    }
+
+    #[test]
+    fn queueing_and_dismissal() {
+        let mut queue = ToastQueue::default();
+        let first = queue.push(ToastKind::Info, "a".to_string(), None);
+        let second = queue.push(ToastKind::Error, "b".to_string(), None);
+        assert_ne!(first, second);
+        assert_eq!(messages(&queue), vec!["a", "b"]);
+        queue.dismiss(first);
+        assert_eq!(messages(&queue), vec!["b"]);
+        // dismissing twice, e.g. by hand and then by the timer, is harmless
+        queue.dismiss(first);
+        assert_eq!(messages(&queue), vec!["b"]);
+        let action = ToastAction::new("רענן", || {});
+        queue.push(ToastKind::Info, "c".to_string(), Some(action));
+        queue.push(ToastKind::Success, "d".to_string(), None);
+        queue.push(ToastKind::Success, "e".to_string(), None);
+        // the oldest makes room
+        assert_eq!(messages(&queue), vec!["c", "d", "e"]);
+        assert_eq!(queue.toasts[0].action.as_ref().unwrap().label, "רענן");
+        assert_eq!(ToastKind::Error.duration(), ERROR_TOAST_DURATION);
+    }
+}
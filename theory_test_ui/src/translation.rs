@@ -0,0 +1,49 @@
+//! Peeking at a question in a second language, as an aid while practicing.
+use dioxus::prelude::*;
+
+use crate::{bank::use_bank, direction::direction};
+
+/// A button showing the question numbered `num` in the language of the bank's translation.
+/// Renders nothing when the bank has no translation.
+#[component]
+pub fn TranslationButton(num: usize) -> Element {
+    let bank = use_bank();
+    let mut open = use_signal(|| false);
+    if !bank.has_translation() {
+        return rsx! {};
+    }
+    // only parsed once it is first opened
+    let translation = open().then(|| bank.translation()).flatten();
+
+    rsx! {
+        div { class: "translation",
+            button {
+                class: "note-button",
+                title: "תרגום",
+                onclick: move |_| open.toggle(),
+                "🌐"
+            }
+            if open() {
+                div { class: "translation-popover",
+                    if let Some(translation) = translation {
+                        div { class: "translation-label", {format!("עזר: תרגום ל{}", translation.name_he)} }
+                        if let Some(question) = translation.alignment.translation(num) {
+                            div { dir: direction(translation.language), lang: translation.language,
+                                div { class: "translation-question", {question.question.clone()} }
+                                ul {
+                                    for answer in question.answers.possible_answers.iter() {
+                                        li { {answer.clone()} }
+                                    }
+                                }
+                            }
+                        } else {
+                            div { "אין תרגום זמין" }
+                        }
+                    } else {
+                        div { "אין תרגום זמין" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,316 @@
+//! A chart of the scores and durations of the latest graded exams, drawn as SVG.
+use std::time::Duration;
+
+use dioxus::prelude::*;
+
+use crate::{
+    history::{use_history, Attempt, History},
+    timer, ExamKind,
+};
+
+/// How many of the latest exams are charted.
+const TREND_LENGTH: usize = 20;
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 240.0;
+/// Room for the axis labels around the plot.
+const PADDING: f64 = 40.0;
+
+/// Which exams are charted. Drills and the question of the day aren't exams, their scores
+/// aren't comparable.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+enum TrendFilter {
+    #[default]
+    All,
+    Practice,
+    Real,
+}
+
+impl TrendFilter {
+    const ALL: [TrendFilter; 3] = [TrendFilter::All, TrendFilter::Practice, TrendFilter::Real];
+
+    fn label_he(&self) -> &'static str {
+        match self {
+            TrendFilter::All => "הכל",
+            TrendFilter::Practice => "תרגול",
+            TrendFilter::Real => "מבחן אמיתי",
+        }
+    }
+
+    fn matches(&self, kind: ExamKind) -> bool {
+        match self {
+            TrendFilter::All => matches!(kind, ExamKind::Practice | ExamKind::Real),
+            TrendFilter::Practice => kind == ExamKind::Practice,
+            TrendFilter::Real => kind == ExamKind::Real,
+        }
+    }
+}
+
+/// A graded exam on the chart.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct TrendPoint {
+    graded_at: Duration,
+    score_percent: f64,
+    /// Unknown for exams recorded before durations were.
+    duration: Option<Duration>,
+}
+
+impl TrendPoint {
+    fn from_attempt(attempt: &Attempt) -> Self {
+        Self {
+            graded_at: attempt.graded_at,
+            score_percent: attempt.num_correct() as f64 * 100.0 / attempt.questions.len() as f64,
+            duration: attempt.duration,
+        }
+    }
+}
+
+/// The latest `limit` graded exams matching `filter`, oldest first.
+fn trend_points(history: &History, filter: TrendFilter, limit: usize) -> Vec<TrendPoint> {
+    let mut points = history
+        .attempts
+        .iter()
+        .filter(|a| !a.abandoned && !a.questions.is_empty() && filter.matches(a.kind))
+        .map(TrendPoint::from_attempt)
+        .collect::<Vec<_>>();
+    points.sort_by_key(|point| point.graded_at);
+    points.drain(..points.len().saturating_sub(limit));
+    points
+}
+
+/// The range of an axis covering `values`, extended to multiples of `step` and at least one
+/// step long, e.g. so a single value isn't drawn on the edge.
+fn axis_range(values: impl Iterator<Item = f64>, step: f64) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if min > max {
+        return (0.0, step);
+    }
+    let low = (min / step).floor() * step;
+    let high = ((max / step).ceil() * step).max(low + step);
+    (low, high)
+}
+
+/// The horizontal position of the `idx`th of `len` points, a single point is centered.
+fn x_position(idx: usize, len: usize) -> f64 {
+    if len <= 1 {
+        return WIDTH / 2.0;
+    }
+    PADDING + idx as f64 * (WIDTH - 2.0 * PADDING) / (len - 1) as f64
+}
+
+/// The vertical position of `value` on an axis spanning `range`.
+fn y_position(value: f64, (low, high): (f64, f64)) -> f64 {
+    HEIGHT - PADDING - (value - low) / (high - low) * (HEIGHT - 2.0 * PADDING)
+}
+
+fn minutes(duration: Duration) -> f64 {
+    duration.as_secs_f64() / 60.0
+}
+
+/// The scores and durations of the latest graded exams, with the details of each exam on hover
+/// or tap.
+#[component]
+pub fn TrendChart() -> Element {
+    let history = use_history();
+    let mut filter = use_signal(TrendFilter::default);
+    let mut selected = use_signal(|| None::<usize>);
+    let utc_offset = use_resource(timer::utc_offset_secs);
+    let utc_offset = utc_offset().unwrap_or(0);
+    let points = trend_points(&history.read(), filter(), TREND_LENGTH);
+    let score_range = axis_range(points.iter().map(|p| p.score_percent), 10.0);
+    let duration_range = axis_range(points.iter().filter_map(|p| p.duration.map(minutes)), 5.0);
+    let len = points.len();
+    let polyline = |values: Vec<(usize, f64)>, range| {
+        values
+            .into_iter()
+            .map(|(idx, value)| {
+                format!(
+                    "{:.1},{:.1}",
+                    x_position(idx, len),
+                    y_position(value, range)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let score_line = polyline(
+        points.iter().map(|p| p.score_percent).enumerate().collect(),
+        score_range,
+    );
+    let duration_line = polyline(
+        points
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, p)| Some((idx, minutes(p.duration?))))
+            .collect(),
+        duration_range,
+    );
+    let has_durations = points.iter().any(|p| p.duration.is_some());
+    let describe = |point: &TrendPoint| {
+        let mut description = format!(
+            "{} · ציון {:.0}%",
+            timer::format_date_time(point.graded_at, utc_offset),
+            point.score_percent
+        );
+        if let Some(duration) = point.duration {
+            description += &format!(" · זמן {}", timer::format_duration(duration));
+        }
+        description
+    };
+
+    rsx! {
+        div { class: "trend-chart",
+            div { class: "filter-chips",
+                for option in TrendFilter::ALL {
+                    button {
+                        class: "filter-chip",
+                        class: if filter() == option { "selected" },
+                        onclick: move |_| {
+                            filter.set(option);
+                            selected.set(None);
+                        },
+                        {option.label_he()}
+                    }
+                }
+            }
+            if points.is_empty() {
+                div { "עדיין אין מבחנים שנבדקו." }
+            } else {
+                div { class: "trend-legend",
+                    span { class: "trend-legend-score", "ציון (%)" }
+                    if has_durations {
+                        span { class: "trend-legend-duration", "זמן (דקות)" }
+                    }
+                }
+                svg {
+                    view_box: "0 0 {WIDTH} {HEIGHT}",
+                    width: "100%",
+                    role: "img",
+                    "aria-label": "מגמת ציונים וזמנים",
+                    // the axes are laid out left to right, whatever the direction of the page
+                    direction: "ltr",
+                    for value in [score_range.0, score_range.1] {
+                        text {
+                            x: PADDING - 6.0,
+                            y: y_position(value, score_range) + 4.0,
+                            text_anchor: "end",
+                            class: "trend-axis-score",
+                            "{value:.0}"
+                        }
+                    }
+                    if has_durations {
+                        for value in [duration_range.0, duration_range.1] {
+                            text {
+                                x: WIDTH - PADDING + 6.0,
+                                y: y_position(value, duration_range) + 4.0,
+                                class: "trend-axis-duration",
+                                "{value:.0}"
+                            }
+                        }
+                    }
+                    line {
+                        x1: PADDING,
+                        y1: HEIGHT - PADDING,
+                        x2: WIDTH - PADDING,
+                        y2: HEIGHT - PADDING,
+                        class: "trend-axis",
+                    }
+                    polyline { points: score_line, class: "trend-score" }
+                    polyline { points: duration_line, class: "trend-duration" }
+                    for (idx , point) in points.iter().enumerate() {
+                        circle {
+                            cx: x_position(idx, len),
+                            cy: y_position(point.score_percent, score_range),
+                            r: if selected() == Some(idx) { 7 } else { 5 },
+                            class: "trend-point",
+                            // hovering shows the details, tapping does on touch screens
+                            onmouseenter: move |_| selected.set(Some(idx)),
+                            onclick: move |_| selected.set(Some(idx)),
+                        }
+                    }
+                }
+                div { class: "trend-details",
+                    if let Some(point) = selected().and_then(|idx| points.get(idx)) {
+                        {describe(point)}
+                    } else {
+                        "יש לרחף או להקיש על נקודה לפרטים"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::exam_state::QuestionOutcome;
+
+    fn attempt(graded_at: u64, kind: ExamKind, correct: usize, duration: Option<u64>) -> Attempt {
+        let questions: Vec<_> = (0..4)
+            .map(|num| {
+                let outcome = if num < correct {
+                    QuestionOutcome::Correct
+                } else {
+                    QuestionOutcome::Wrong
+                };
+                (num, outcome)
+            })
+            .collect();
+        Attempt {
+            kind,
+            duration: duration.map(Duration::from_secs),
+            ..Attempt::sample(graded_at, &questions)
+        }
+    }
+
+    #[test]
+    fn points() {
+        let mut history = History::default();
+        history.record(attempt(3, ExamKind::Real, 4, Some(600)));
+        history.record(attempt(1, ExamKind::Practice, 2, None));
+        history.record(attempt(2, ExamKind::Drill, 4, Some(60)));
+        history.record(Attempt {
+            abandoned: true,
+            ..attempt(4, ExamKind::Real, 0, None)
+        });
+        let points = trend_points(&history, TrendFilter::All, 10);
+        assert_eq!(
+            points,
+            vec![
+                TrendPoint {
+                    graded_at: Duration::from_secs(1),
+                    score_percent: 50.0,
+                    duration: None,
+                },
+                TrendPoint {
+                    graded_at: Duration::from_secs(3),
+                    score_percent: 100.0,
+                    duration: Some(Duration::from_secs(600)),
+                },
+            ]
+        );
+        assert_eq!(trend_points(&history, TrendFilter::Real, 10), points[1..]);
+        assert_eq!(
+            trend_points(&history, TrendFilter::Practice, 10),
+            points[..1]
+        );
+        // the latest are kept
+        assert_eq!(trend_points(&history, TrendFilter::All, 1), points[1..]);
+    }
+
+    #[test]
+    fn axes() {
+        assert_eq!(axis_range([72.0, 95.0].into_iter(), 10.0), (70.0, 100.0));
+        // a single value, or values on a step, still span a step
+        assert_eq!(axis_range([80.0].into_iter(), 10.0), (80.0, 90.0));
+        assert_eq!(axis_range(std::iter::empty(), 5.0), (0.0, 5.0));
+        assert_eq!(x_position(0, 1), WIDTH / 2.0);
+        assert_eq!(x_position(0, 2), PADDING);
+        assert_eq!(x_position(1, 2), WIDTH - PADDING);
+        assert_eq!(y_position(70.0, (70.0, 100.0)), HEIGHT - PADDING);
+        assert_eq!(y_position(100.0, (70.0, 100.0)), PADDING);
+    }
+}
@@ -0,0 +1,103 @@
+//! Windowed rendering of long lists of questions: only the questions near the viewport are
+//! mounted, the others are replaced by placeholders of their last measured height so the page
+//! keeps its length while scrolling.
+//!
+//! Only the rendering is windowed, the state of the questions must live outside of them so
+//! unmounting a question loses nothing.
+use std::collections::{HashMap, HashSet};
+
+use dioxus::prelude::*;
+
+/// Lists of up to this many questions are rendered whole.
+pub const MAX_UNWINDOWED: usize = 40;
+/// The height of a question which was never mounted, in pixels.
+const ESTIMATED_HEIGHT: f64 = 450.0;
+
+/// Observes the items of the list, reporting `[index, near the viewport, height]` whenever an
+/// item comes near the viewport or goes away from it.
+const OBSERVE_JS: &str = r#"
+await new Promise(requestAnimationFrame);
+const observer = new IntersectionObserver((entries) => {
+    for (const entry of entries) {
+        dioxus.send([
+            Number(entry.target.dataset.virtualIdx),
+            entry.isIntersecting,
+            entry.boundingClientRect.height,
+        ]);
+    }
+}, { rootMargin: '1500px 0px' });
+for (const item of document.querySelectorAll('[data-virtual-idx]')) {
+    observer.observe(item);
+}
+"#;
+
+/// Which items of the list are mounted, and the heights they had.
+#[derive(Clone, PartialEq, Default, Debug)]
+pub struct VirtualWindow {
+    mounted: HashSet<usize>,
+    heights: HashMap<usize, f64>,
+}
+
+impl VirtualWindow {
+    pub fn is_mounted(&self, idx: usize) -> bool {
+        self.mounted.contains(&idx)
+    }
+
+    /// Update an item which came near the viewport or went away from it, `height` being its
+    /// current height.
+    pub fn update(&mut self, idx: usize, near: bool, height: f64) {
+        // the height of a placeholder says nothing about its item
+        if self.mounted.contains(&idx) && height > 0.0 {
+            self.heights.insert(idx, height);
+        }
+        if near {
+            self.mounted.insert(idx);
+        } else {
+            self.mounted.remove(&idx);
+        }
+    }
+
+    /// The height of the placeholder of an unmounted item.
+    pub fn placeholder_height(&self, idx: usize) -> f64 {
+        self.heights.get(&idx).copied().unwrap_or(ESTIMATED_HEIGHT)
+    }
+}
+
+/// The window of a list of `len` items, `None` when the list is short enough to be rendered
+/// whole. The items must have the `data-virtual-idx` attribute.
+pub fn use_virtual_window(len: usize) -> Option<Signal<VirtualWindow>> {
+    let windowed = len > MAX_UNWINDOWED;
+    let mut window = use_signal(VirtualWindow::default);
+    use_future(move || async move {
+        if !windowed {
+            return;
+        }
+        let mut eval = document::eval(OBSERVE_JS);
+        while let Ok((idx, near, height)) = eval.recv::<(usize, bool, f64)>().await {
+            window.write().update(idx, near, height);
+        }
+    });
+    windowed.then_some(window)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mounting_and_heights() {
+        let mut window = VirtualWindow::default();
+        assert!(!window.is_mounted(3));
+        // coming near as a placeholder
+        window.update(3, true, ESTIMATED_HEIGHT);
+        assert!(window.is_mounted(3));
+        assert_eq!(window.placeholder_height(3), ESTIMATED_HEIGHT);
+        // going away after it was rendered keeps its real height
+        window.update(3, false, 720.0);
+        assert!(!window.is_mounted(3));
+        assert_eq!(window.placeholder_height(3), 720.0);
+        // an unmounted item reports the placeholder's height, which isn't kept
+        window.update(3, false, 100.0);
+        assert_eq!(window.placeholder_height(3), 720.0);
+    }
+}